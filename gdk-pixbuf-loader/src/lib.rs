@@ -25,6 +25,15 @@ type RsvgSizeFunc = Option<
     unsafe extern "C" fn(inout_width: *mut c_int, inout_height: *mut c_int, user_data: gpointer),
 >;
 
+#[repr(C)]
+#[derive(Default)]
+struct RsvgDimensionData {
+    width: c_int,
+    height: c_int,
+    em: f64,
+    ex: f64,
+}
+
 #[link(name = "rsvg-2")]
 extern "C" {
     fn rsvg_handle_new_from_stream_sync(
@@ -35,6 +44,8 @@ extern "C" {
         error: *mut *mut GError,
     ) -> *mut RSvgHandle;
 
+    fn rsvg_handle_get_dimensions(handle: *mut RSvgHandle, dimension_data: *mut RsvgDimensionData);
+
     fn rsvg_handle_get_pixbuf_and_error(
         handle: *mut RSvgHandle,
         error: *mut *mut GError,
@@ -102,51 +113,227 @@ unsafe extern "C" fn stop_load(user_data: gpointer, error: *mut *mut GError) ->
         *error = null_mut();
     }
 
-    let mut local_error = null_mut::<GError>();
+    // Incremental loading only gets the data, not the file it came from, so there is no
+    // base file for resolving references.
+    let pixbuf = match load_pixbuf(ctx.stream.upcast_ref(), None, ctx.size_func, ctx.user_data) {
+        Ok(Some(pixbuf)) => pixbuf,
+        Ok(None) => return true.into_glib(),
+        Err(e) => {
+            if !error.is_null() {
+                *error = e.into_glib_ptr();
+            }
+            return false.into_glib();
+        }
+    };
+
+    let w = pixbuf.width();
+    let h = pixbuf.height();
 
+    if let Some(prep_func) = ctx.prep_func {
+        prep_func(pixbuf.to_glib_none().0, null_mut(), ctx.user_data);
+    }
+    if let Some(update_func) = ctx.update_func {
+        update_func(pixbuf.to_glib_none().0, 0, 0, w, h, ctx.user_data);
+    }
+
+    true.into_glib()
+}
+
+/// Loads an SVG from a file that gdk-pixbuf already opened, as for
+/// `gdk_pixbuf_new_from_file()`.
+///
+/// By default SVGs loaded this way cannot reference external files, just like with
+/// incremental loading.  Every application that uses gdk-pixbuf goes through here,
+/// thumbnailers included, so resolving references is opt-in: set the
+/// `RSVG_PIXBUF_LOADER_BASE_FILE` environment variable to use the loaded file as the base
+/// file.  See [`base_file_for_stream`] for the platforms where this works.
+unsafe extern "C" fn load(
+    f: *mut libc::FILE,
+    error: *mut *mut GError,
+) -> *mut gdk_pixbuf::ffi::GdkPixbuf {
+    let use_base_file = std::env::var_os("RSVG_PIXBUF_LOADER_BASE_FILE").is_some();
+    load_file(f, use_base_file, error)
+}
+
+unsafe fn load_file(
+    f: *mut libc::FILE,
+    use_base_file: bool,
+    error: *mut *mut GError,
+) -> *mut gdk_pixbuf::ffi::GdkPixbuf {
+    if !error.is_null() {
+        *error = null_mut();
+    }
+
+    let mut data = Vec::new();
+    let mut buf = [0u8; 8192];
+
+    loop {
+        let n = libc::fread(buf.as_mut_ptr() as *mut libc::c_void, 1, buf.len(), f);
+        if n == 0 {
+            break;
+        }
+        data.extend_from_slice(&buf[..n]);
+    }
+
+    if libc::ferror(f) != 0 {
+        if !error.is_null() {
+            *error = glib::Error::new(gio::IOErrorEnum::Failed, "could not read the SVG file")
+                .into_glib_ptr();
+        }
+        return null_mut();
+    }
+
+    let stream = MemoryInputStream::from_bytes(&Bytes::from_owned(data));
+
+    // Giving librsvg the base file lets the SVG reference sibling files, like PNGs for
+    // `<image href="foo.png">`.  librsvg only allows references to files in the same
+    // directory as the base file or in its subdirectories, so an SVG cannot use this to
+    // read arbitrary files, but it can still read its siblings.
+    let base_file = if use_base_file {
+        base_file_for_stream(f)
+    } else {
+        None
+    };
+
+    match load_pixbuf(stream.upcast_ref(), base_file.as_ref(), None, null_mut()) {
+        Ok(Some(pixbuf)) => pixbuf.to_glib_full(),
+        Ok(None) => null_mut(),
+        Err(e) => {
+            if !error.is_null() {
+                *error = e.into_glib_ptr();
+            }
+            null_mut()
+        }
+    }
+}
+
+/// Finds the file that an open `FILE` refers to.
+///
+/// The pixbuf module API only passes the open `FILE`, not its filename, and there is no
+/// portable API to get a path from a file descriptor.  On Linux this reads the
+/// `/proc/self/fd` link for the descriptor.  Returns `None` if the path cannot be found
+/// out, for example if `/proc` is not mounted.
+///
+/// On other platforms this always returns `None`, so SVGs loaded there through the pixbuf
+/// loader cannot reference external files even if `RSVG_PIXBUF_LOADER_BASE_FILE` is set.
+#[cfg(target_os = "linux")]
+unsafe fn base_file_for_stream(f: *mut libc::FILE) -> Option<gio::File> {
+    let fd = libc::fileno(f);
+    if fd < 0 {
+        return None;
+    }
+
+    let path = std::fs::read_link(format!("/proc/self/fd/{fd}")).ok()?;
+    path.is_file().then(|| gio::File::for_path(path))
+}
+
+/// See the Linux version of this function.
+#[cfg(not(target_os = "linux"))]
+unsafe fn base_file_for_stream(_f: *mut libc::FILE) -> Option<gio::File> {
+    None
+}
+
+/// Loads the SVG data from `stream` and renders it into a pixbuf.
+///
+/// The `base_file`, if present, is used to resolve references to external files.
+///
+/// The `size_func` gets called with the document's intrinsic size, and can change it to
+/// the size it wants for the pixbuf.  Returns `Ok(None)` if the `size_func` sets the size
+/// to zero, which means that the caller only wanted to know the size.
+unsafe fn load_pixbuf(
+    stream: &gio::InputStream,
+    base_file: Option<&gio::File>,
+    size_func: GdkPixbufModuleSizeFunc,
+    user_data: gpointer,
+) -> Result<Option<gdk_pixbuf::Pixbuf>, glib::Error> {
     let handle = {
+        let mut local_error = null_mut::<GError>();
+
         let raw_handle = rsvg_handle_new_from_stream_sync(
-            ctx.stream.upcast_ref::<gio::InputStream>().to_glib_none().0,
-            null_mut(), // base_file
+            stream.to_glib_none().0,
+            base_file.to_glib_none().0,
             0,
             null_mut(), // cancellable
             &mut local_error,
         );
         if !local_error.is_null() {
-            if !error.is_null() {
-                *error = local_error;
-            }
-            return false.into_glib();
+            return Err(from_glib_full(local_error));
         }
 
         glib::Object::from_glib_full(raw_handle)
     };
 
-    rsvg_handle_set_size_callback(handle.as_ptr(), ctx.size_func, ctx.user_data, None);
+    let (intrinsic_width, intrinsic_height) = {
+        let mut dimensions = RsvgDimensionData::default();
+        rsvg_handle_get_dimensions(handle.as_ptr(), &mut dimensions);
+        (dimensions.width, dimensions.height)
+    };
 
-    let pixbuf = {
-        let p = rsvg_handle_get_pixbuf_and_error(handle.as_ptr(), &mut local_error);
-        if !local_error.is_null() {
-            if !error.is_null() {
-                *error = local_error;
-            }
-            return false.into_glib();
+    let (width, height) = if intrinsic_width > 0 && intrinsic_height > 0 {
+        let mut width = intrinsic_width;
+        let mut height = intrinsic_height;
+
+        if let Some(size_func) = size_func {
+            size_func(&mut width, &mut height, user_data);
+        }
+
+        // The size function sets the size to zero when the caller only wants to know
+        // the image's dimensions.
+        if width <= 0 || height <= 0 {
+            return Ok(None);
         }
 
-        gdk_pixbuf::Pixbuf::from_glib_full(p)
+        (width, height)
+    } else {
+        // GdkPixbuf does not allow zero-sized pixbufs; render an empty document as a
+        // single transparent pixel.
+        (1, 1)
     };
 
-    let w = pixbuf.width();
-    let h = pixbuf.height();
+    render_pixbuf(&handle, width, height).map(Some)
+}
 
-    if let Some(prep_func) = ctx.prep_func {
-        prep_func(pixbuf.to_glib_none().0, null_mut(), ctx.user_data);
+/// Renders the document into a pixbuf of `width` by `height` pixels.
+///
+/// SVGs are scalable, so instead of rendering at the intrinsic size and then scaling the
+/// pixels, this has librsvg scale the rendering itself, by giving it a size callback that
+/// requests the pixbuf's size.  As with `rsvg_handle_get_pixbuf()`, the document keeps its
+/// aspect ratio and gets centered if the requested size has a different one.
+unsafe fn render_pixbuf(
+    handle: &glib::Object,
+    width: c_int,
+    height: c_int,
+) -> Result<gdk_pixbuf::Pixbuf, glib::Error> {
+    unsafe extern "C" fn requested_size(
+        inout_width: *mut c_int,
+        inout_height: *mut c_int,
+        user_data: gpointer,
+    ) {
+        let (width, height) = *user_data.cast::<(c_int, c_int)>();
+        *inout_width = width;
+        *inout_height = height;
     }
-    if let Some(update_func) = ctx.update_func {
-        update_func(pixbuf.to_glib_none().0, 0, 0, w, h, ctx.user_data);
+
+    let size = (width, height);
+
+    rsvg_handle_set_size_callback(
+        handle.as_ptr(),
+        Some(requested_size),
+        std::ptr::addr_of!(size).cast_mut().cast(),
+        None,
+    );
+
+    let mut error = null_mut::<GError>();
+    let pixbuf = rsvg_handle_get_pixbuf_and_error(handle.as_ptr(), &mut error);
+
+    // The callback's data only lives in this function.
+    rsvg_handle_set_size_callback(handle.as_ptr(), None, null_mut(), None);
+
+    if !error.is_null() {
+        return Err(from_glib_full(error));
     }
 
-    true.into_glib()
+    Ok(from_glib_full(pixbuf))
 }
 
 #[no_mangle]
@@ -154,6 +341,7 @@ extern "C" fn fill_vtable(module: &mut GdkPixbufModule) {
     module.begin_load = Some(begin_load);
     module.stop_load = Some(stop_load);
     module.load_increment = Some(load_increment);
+    module.load = Some(load);
 }
 
 const SIGNATURE: [GdkPixbufModulePattern; 3] = [
@@ -207,10 +395,11 @@ mod tests {
     use gdk_pixbuf::ffi::{
         GdkPixbufFormat, GDK_PIXBUF_FORMAT_SCALABLE, GDK_PIXBUF_FORMAT_THREADSAFE,
     };
-    use glib::translate::IntoGlib;
+    use glib::translate::{from_glib_full, IntoGlib};
 
     use crate::{EXTENSIONS, MIME_TYPES};
-    use std::ffi::c_char;
+    use glib::ffi::gpointer;
+    use std::ffi::{c_char, c_int};
     use std::ptr::null_mut;
 
     fn pb_format_new() -> GdkPixbufFormat {
@@ -316,6 +505,109 @@ mod tests {
                                     </svg>
     "#;
 
+    #[test]
+    fn size_func_scales_the_rendering() {
+        unsafe extern "C" fn size_cb(width: *mut c_int, height: *mut c_int, user_data: gpointer) {
+            assert!(user_data.is_null());
+            assert_eq!(*width, 100);
+            assert_eq!(*height, 150);
+
+            *width /= 2;
+            *height /= 2;
+        }
+
+        unsafe extern "C" fn prep_cb(
+            pb: *mut gdk_pixbuf::ffi::GdkPixbuf,
+            _pba: *mut gdk_pixbuf::ffi::GdkPixbufAnimation,
+            _user_data: *mut std::ffi::c_void,
+        ) {
+            let w = gdk_pixbuf::ffi::gdk_pixbuf_get_width(pb);
+            let h = gdk_pixbuf::ffi::gdk_pixbuf_get_height(pb);
+            let stride = gdk_pixbuf::ffi::gdk_pixbuf_get_rowstride(pb);
+            assert_eq!(w, 50);
+            assert_eq!(h, 75);
+
+            let pixels = gdk_pixbuf::ffi::gdk_pixbuf_get_pixels(pb);
+
+            // The whole document is scaled down, so the bottom right pixel is still
+            // filled with #aa1144ff
+            let offset = (stride * (h - 1)) as isize + (w as isize - 1) * 4;
+            assert_eq!(*pixels.offset(offset), 0xaa);
+            assert_eq!(*pixels.offset(offset + 1), 0x11);
+            assert_eq!(*pixels.offset(offset + 2), 0x44);
+            assert_eq!(*pixels.offset(offset + 3), 0xff);
+        }
+
+        unsafe {
+            let ctx = crate::begin_load(Some(size_cb), Some(prep_cb), None, null_mut(), null_mut());
+            assert_ne!(ctx, null_mut());
+
+            let inc =
+                crate::load_increment(ctx, SVG_DATA.as_ptr(), SVG_DATA.len() as u32, null_mut());
+            assert_ne!(inc, 0);
+
+            assert_ne!(crate::stop_load(ctx, null_mut()), 0);
+        }
+    }
+
+    fn load_svg_with_sibling_png(name: &str, use_base_file: bool) -> gdk_pixbuf::Pixbuf {
+        let dir =
+            std::env::temp_dir().join(format!("pixbufloader-svg-{}-{name}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let png = gdk_pixbuf::Pixbuf::new(gdk_pixbuf::Colorspace::Rgb, true, 8, 1, 1).unwrap();
+        png.fill(0x00ff00ff);
+        png.savev(dir.join("green.png"), "png", &[]).unwrap();
+
+        let svg_path = dir.join("image.svg");
+        std::fs::write(
+            &svg_path,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10">
+  <image href="green.png" width="10" height="10" preserveAspectRatio="none"/>
+</svg>"#,
+        )
+        .unwrap();
+
+        let pixbuf: gdk_pixbuf::Pixbuf = unsafe {
+            let path = std::ffi::CString::new(svg_path.to_str().unwrap()).unwrap();
+            let f = libc::fopen(path.as_ptr(), c"rb".as_ptr());
+            assert!(!f.is_null());
+
+            let pb = crate::load_file(f, use_base_file, null_mut());
+            libc::fclose(f);
+
+            assert!(!pb.is_null());
+            from_glib_full(pb)
+        };
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        pixbuf
+    }
+
+    #[test]
+    fn load_from_file_does_not_resolve_sibling_files_by_default() {
+        let pixbuf = load_svg_with_sibling_png("default", false);
+
+        assert_eq!(pixbuf.width(), 10);
+        assert_eq!(pixbuf.height(), 10);
+
+        let pixels = pixbuf.read_pixel_bytes();
+        assert_eq!(&pixels[0..4], &[0x00, 0x00, 0x00, 0x00]);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn load_from_file_resolves_sibling_files_when_enabled() {
+        let pixbuf = load_svg_with_sibling_png("enabled", true);
+
+        assert_eq!(pixbuf.width(), 10);
+        assert_eq!(pixbuf.height(), 10);
+
+        let pixels = pixbuf.read_pixel_bytes();
+        assert_eq!(&pixels[0..4], &[0x00, 0xff, 0x00, 0xff]);
+    }
+
     #[test]
     fn minimal_svg() {
         unsafe extern "C" fn prep_cb(
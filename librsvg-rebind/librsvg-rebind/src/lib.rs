@@ -36,6 +36,40 @@
 //! let mut output_file = std::fs::File::create("/dev/null").unwrap();
 //! surface.write_to_png(&mut output_file).unwrap();
 //! ```
+//!
+//! # Rendering a single element
+//!
+//! Like the native crate, the functions that work on a single element take an
+//! `Option<&str>` with the element's `id`, written as a fragment identifier like
+//! `"#foo"`.  `None` means the whole document.
+//!
+//! ```
+//! use librsvg_rebind::prelude::*;
+//!
+//! let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+//!   <rect id="foo" x="10" y="20" width="30" height="40" fill="lime"/>
+//! </svg>"#;
+//!
+//! let handle = librsvg_rebind::Handle::from_data(svg).unwrap().unwrap();
+//!
+//! let (ink_rect, _logical_rect) = handle.geometry_for_element(Some("#foo")).unwrap();
+//! assert_eq!((ink_rect.width(), ink_rect.height()), (30.0, 40.0));
+//!
+//! // Render just the element, scaled to fit a 60x80 surface.
+//! let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, 60, 80).unwrap();
+//! let context = cairo::Context::new(&surface).unwrap();
+//!
+//! let element_viewport = librsvg_rebind::Rectangle::new(0., 0., 60., 80.);
+//! handle
+//!     .render_element(&context, Some("#foo"), &element_viewport)
+//!     .unwrap();
+//!
+//! // Render the element in its place within the whole document.
+//! let viewport = librsvg_rebind::Rectangle::new(0., 0., 100., 100.);
+//! handle
+//!     .render_layer(&context, Some("#foo"), &viewport)
+//!     .unwrap();
+//! ```
 
 /// No-op.
 macro_rules! skip_assert_initialized {
@@ -0,0 +1,64 @@
+//! Benchmarks for filters on poster-size output.
+//!
+//! Run these with and without the "parallel-filters" feature to measure its speedup:
+//!
+//! ```sh
+//! cargo bench -p rsvg-bench --bench filters
+//! cargo bench -p rsvg-bench --bench filters --features parallel-filters
+//! ```
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use rsvg::{CairoRenderer, SvgHandle};
+
+const SIDE: i32 = 2048;
+
+/// Creates a document that fills the whole canvas and applies `primitive` to it.
+fn filtered_document(primitive: &str) -> SvgHandle {
+    let svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{SIDE}" height="{SIDE}">
+  <defs>
+    <linearGradient id="gradient">
+      <stop offset="0" stop-color="red"/>
+      <stop offset="1" stop-color="blue" stop-opacity="0.5"/>
+    </linearGradient>
+    <filter id="filter" x="0" y="0" width="1" height="1">{primitive}</filter>
+  </defs>
+  <rect width="{SIDE}" height="{SIDE}" fill="url(#gradient)" filter="url(#filter)"/>
+</svg>"#
+    );
+
+    SvgHandle::try_from(svg.as_str()).unwrap()
+}
+
+fn bench_filter(c: &mut Criterion, name: &str, primitive: &str) {
+    let svg = filtered_document(primitive);
+    let renderer = CairoRenderer::new(&svg);
+    let viewport = cairo::Rectangle::new(0.0, 0.0, f64::from(SIDE), f64::from(SIDE));
+
+    c.bench_function(name, |b| {
+        b.iter(|| {
+            let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, SIDE, SIDE).unwrap();
+            let cr = cairo::Context::new(&surface).unwrap();
+            renderer.render_document(&cr, &viewport).unwrap();
+        })
+    });
+}
+
+fn bench_filters(c: &mut Criterion) {
+    bench_filter(
+        c,
+        "feConvolveMatrix 3x3",
+        r#"<feConvolveMatrix order="3" kernelMatrix="1 2 1 2 4 2 1 2 1"/>"#,
+    );
+
+    // Deviations under 2.0 use a convolution with the exact kernel.
+    bench_filter(
+        c,
+        "feGaussianBlur 1.5",
+        r#"<feGaussianBlur stdDeviation="1.5"/>"#,
+    );
+}
+
+criterion_group!(benches, bench_filters);
+criterion_main!(benches);
@@ -5,12 +5,26 @@
 use anyhow::Result;
 use clap::{crate_version, value_parser};
 use std::fs;
+use std::io::{self, Read};
 use std::path::{Path, PathBuf};
 use std::process;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
+/// Prints a progress message.
+///
+/// With `--json` the messages go to stderr, so that stdout only has the JSON report.
+macro_rules! info {
+    ($opt:expr, $($arg:tt)*) => {
+        if $opt.json {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
+
 #[derive(Debug)]
 /// Command-line options for `rsvg-bench`.
 struct Opt {
@@ -23,13 +37,110 @@ struct Opt {
     /// Number of times to render each file.
     num_render: usize,
 
+    /// Number of extra times to parse and render each file before measuring.
+    warmup: usize,
+
+    /// Whether to print the timings report as JSON.
+    json: bool,
+
     /// Whether to stop all processing when a file cannot be rendered.
     hard_failures: bool,
 
+    /// Width of the surface to render to, in pixels or points.
+    width: i32,
+
+    /// Height of the surface to render to, in pixels or points.
+    height: i32,
+
+    /// Type of surface to render to.
+    format: Format,
+
+    /// Id of the element to render, like `#foo`, instead of the whole document.
+    export_id: Option<String>,
+
     /// Input files or directories.
     inputs: Vec<PathBuf>,
 }
 
+/// Type of surface to render to.
+///
+/// PDF and SVG use cairo's vector surfaces, which write their output to a stream that
+/// discards it, like `/dev/null`.  This measures the cost of generating the output
+/// without the cost of writing files.
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+enum Format {
+    /// Render to an image surface; this does not encode a PNG.
+    Png,
+
+    /// Render to a PDF surface.
+    Pdf,
+
+    /// Render to an SVG surface.
+    Svg,
+}
+
+/// Minimum, mean, and maximum durations of a set of iterations.
+#[derive(Debug)]
+struct Timings {
+    iterations: usize,
+    min: Duration,
+    mean: Duration,
+    max: Duration,
+}
+
+impl Timings {
+    /// Returns `None` if there were no iterations.
+    fn new(durations: &[Duration]) -> Option<Timings> {
+        let min = *durations.iter().min()?;
+        let max = *durations.iter().max()?;
+        let total: Duration = durations.iter().sum();
+
+        Some(Timings {
+            iterations: durations.len(),
+            min,
+            mean: total / durations.len() as u32,
+            max,
+        })
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "iterations": self.iterations,
+            "min_ms": millis(self.min),
+            "mean_ms": millis(self.mean),
+            "max_ms": millis(self.max),
+        })
+    }
+}
+
+impl std::fmt::Display for Timings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.3}/{:.3}/{:.3}",
+            millis(self.min),
+            millis(self.mean),
+            millis(self.max)
+        )
+    }
+}
+
+fn millis(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+/// Timings for a single file.
+#[derive(Debug)]
+struct FileTimings {
+    path: PathBuf,
+
+    /// Parsing times; files always get parsed at least once.
+    parse: Timings,
+
+    /// Rendering times, or `None` with `--num-render 0`.
+    render: Option<Timings>,
+}
+
 #[derive(Debug)]
 enum LoadingError {
     Skipped,
@@ -57,36 +168,44 @@ impl From<rsvg::RenderingError> for ProcessingError {
     }
 }
 
-fn process_path<P: AsRef<Path>>(opt: &Opt, path: P) -> Result<()> {
+fn process_path<P: AsRef<Path>>(opt: &Opt, path: P, report: &mut Vec<FileTimings>) -> Result<()> {
     let meta = fs::metadata(&path)?;
 
     if meta.is_dir() {
-        process_directory(opt, path)?;
+        process_directory(opt, path, report)?;
     } else if let Some(ext) = path.as_ref().extension() {
         if ext == "svg" || ext == "SVG" {
-            process_file(opt, &path)?;
+            process_file(opt, &path, report)?;
         }
     }
 
     Ok(())
 }
 
-fn process_directory<P: AsRef<Path>>(opt: &Opt, path: P) -> Result<()> {
-    println!("Processing {:?}", path.as_ref());
+fn process_directory<P: AsRef<Path>>(
+    opt: &Opt,
+    path: P,
+    report: &mut Vec<FileTimings>,
+) -> Result<()> {
+    info!(opt, "Processing {:?}", path.as_ref());
 
     for entry in fs::read_dir(path)? {
         let entry = entry?;
-        process_path(opt, &entry.path())?;
+        process_path(opt, &entry.path(), report)?;
     }
 
     Ok(())
 }
 
-fn read_svg(opt: &Opt, path: &Path) -> Result<rsvg::SvgHandle, LoadingError> {
-    match (opt.hard_failures, rsvg::Loader::new().read_path(path)) {
+fn read_svg<F>(opt: &Opt, path: &Path, load: F) -> Result<rsvg::SvgHandle, LoadingError>
+where
+    F: Fn() -> Result<rsvg::SvgHandle, rsvg::LoadingError>,
+{
+    match (opt.hard_failures, load()) {
         (_, Ok(h)) => Ok(h),
         (false, Err(e)) => {
-            println!(
+            info!(
+                opt,
                 "skipping {} due to error when loading: {}",
                 path.to_string_lossy(),
                 e
@@ -97,46 +216,117 @@ fn read_svg(opt: &Opt, path: &Path) -> Result<rsvg::SvgHandle, LoadingError> {
     }
 }
 
-fn process_file<P: AsRef<Path>>(opt: &Opt, path: P) -> Result<()> {
-    println!("Processing {:?}", path.as_ref());
+fn process_file<P: AsRef<Path>>(opt: &Opt, path: P, report: &mut Vec<FileTimings>) -> Result<()> {
+    info!(opt, "Processing {:?}", path.as_ref());
+
+    let path = path.as_ref();
 
+    benchmark(opt, path, || rsvg::Loader::new().read_path(path), report)
+}
+
+fn process_stdin(opt: &Opt, report: &mut Vec<FileTimings>) -> Result<()> {
+    info!(opt, "Processing stdin");
+
+    // Stdin can only be read once, but the document may need to be parsed many times,
+    // so read it all into memory first and parse from there.
+    let mut data = Vec::new();
+    io::stdin().read_to_end(&mut data)?;
+    let bytes = glib::Bytes::from_owned(data);
+
+    benchmark(
+        opt,
+        Path::new("-"),
+        || {
+            let stream = gio::MemoryInputStream::from_bytes(&bytes);
+            rsvg::Loader::new().read_stream(&stream, None::<&gio::File>, None::<&gio::Cancellable>)
+        },
+        report,
+    )
+}
+
+/// Parses and renders a document as many times as requested, and adds its timings to `report`.
+///
+/// The `path` is only used for messages and for the report; `load` parses the document.
+fn benchmark<F>(opt: &Opt, path: &Path, load: F, report: &mut Vec<FileTimings>) -> Result<()>
+where
+    F: Fn() -> Result<rsvg::SvgHandle, rsvg::LoadingError>,
+{
     assert!(opt.num_parse > 0);
 
-    let path = path.as_ref();
+    let mut handle = None;
+    let mut parse_times = Vec::with_capacity(opt.num_parse);
+
+    for i in 0..opt.warmup + opt.num_parse {
+        let start = Instant::now();
 
-    for _ in 0..opt.num_parse - 1 {
-        match read_svg(opt, path) {
-            Ok(_) => (),
+        match read_svg(opt, path, &load) {
+            Ok(h) => handle = Some(h),
             Err(LoadingError::Skipped) => return Ok(()),
             Err(LoadingError::Rsvg(e)) => return Err(e.into()),
         }
+
+        if i >= opt.warmup {
+            parse_times.push(start.elapsed());
+        }
     }
 
-    let handle = match read_svg(opt, path) {
-        Ok(h) => h,
-        Err(LoadingError::Skipped) => return Ok(()),
-        Err(LoadingError::Rsvg(e)) => return Err(e.into()),
-    };
+    let handle = handle.expect("files get parsed at least once");
+
+    let mut render_times = Vec::with_capacity(opt.num_render);
+
+    if opt.num_render > 0 {
+        for i in 0..opt.warmup + opt.num_render {
+            let start = Instant::now();
+
+            render_to_cairo(opt, &handle)?;
 
-    for _ in 0..opt.num_render {
-        render_to_cairo(opt, &handle)?;
+            if i >= opt.warmup {
+                render_times.push(start.elapsed());
+            }
+        }
     }
 
+    report.push(FileTimings {
+        path: path.to_path_buf(),
+        parse: Timings::new(&parse_times).expect("files get parsed at least once"),
+        render: Timings::new(&render_times),
+    });
+
     Ok(())
 }
 
 fn render_to_cairo(opt: &Opt, handle: &rsvg::SvgHandle) -> Result<(), ProcessingError> {
     let renderer = rsvg::CairoRenderer::new(handle);
 
-    let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100)?;
+    let width = f64::from(opt.width);
+    let height = f64::from(opt.height);
+
+    let surface: cairo::Surface = match opt.format {
+        Format::Png => {
+            (*cairo::ImageSurface::create(cairo::Format::ARgb32, opt.width, opt.height)?).clone()
+        }
+        Format::Pdf => (*cairo::PdfSurface::for_stream(width, height, io::sink())?).clone(),
+        Format::Svg => (*cairo::SvgSurface::for_stream(width, height, io::sink())?).clone(),
+    };
+
     let cr = cairo::Context::new(&surface)?;
 
-    let viewport = cairo::Rectangle::new(0.0, 0.0, 100.0, 100.0);
+    let viewport = cairo::Rectangle::new(0.0, 0.0, width, height);
 
-    match (opt.hard_failures, renderer.render_document(&cr, &viewport)) {
+    let res = match opt.export_id {
+        Some(ref id) => renderer.render_element(&cr, Some(id.as_str()), &viewport),
+        None => renderer.render_document(&cr, &viewport),
+    };
+
+    drop(cr);
+
+    // For vector surfaces, this generates the rest of the output.
+    surface.finish();
+
+    match (opt.hard_failures, res) {
         (_, Ok(_)) => Ok(()),
         (false, Err(e)) => {
-            println!("could not render: {e}");
+            info!(opt, "could not render: {e}");
             Ok(())
         }
         (true, Err(e)) => Err(e.into()),
@@ -148,14 +338,33 @@ fn sleep(secs: usize) {
 }
 
 fn print_options(opt: &Opt) {
-    println!("Will parse each file {} times", opt.num_parse);
-    println!("Will render each file {} times", opt.num_render);
+    info!(opt, "Will parse each file {} times", opt.num_parse);
+    info!(opt, "Will render each file {} times", opt.num_render);
+    if opt.warmup > 0 {
+        info!(
+            opt,
+            "Will parse and render each file {} extra times before measuring", opt.warmup
+        );
+    }
     if opt.num_render > 0 {
-        println!("Rendering to Cairo image surface");
+        let surface_type = match opt.format {
+            Format::Png => "image",
+            Format::Pdf => "PDF",
+            Format::Svg => "SVG",
+        };
+
+        info!(
+            opt,
+            "Rendering to {}x{} Cairo {} surface", opt.width, opt.height, surface_type
+        );
+
+        if let Some(ref id) = opt.export_id {
+            info!(opt, "Rendering only element {id}");
+        }
     }
-    println!(
-        "Sleeping for {} seconds before processing SVGs...",
-        opt.sleep_secs
+    info!(
+        opt,
+        "Sleeping for {} seconds before processing SVGs...", opt.sleep_secs
     );
 }
 
@@ -163,15 +372,69 @@ fn run(opt: &Opt) -> Result<()> {
     print_options(opt);
 
     sleep(opt.sleep_secs);
-    println!("Processing files!");
+    info!(opt, "Processing files!");
+
+    let mut report = Vec::new();
 
     for path in &opt.inputs {
-        process_path(opt, path)?;
+        if path.as_os_str() == "-" {
+            process_stdin(opt, &mut report)?;
+        } else if fs::metadata(path)?.is_dir() {
+            process_directory(opt, path, &mut report)?;
+        } else {
+            // Files given explicitly are processed even if they don't have an .svg extension;
+            // the extension is only used to pick files when scanning directories.
+            process_file(opt, path, &mut report)?;
+        }
+    }
+
+    if opt.json {
+        print_json_report(&report);
+    } else {
+        print_report(&report);
     }
 
     Ok(())
 }
 
+fn print_report(report: &[FileTimings]) {
+    println!();
+    println!(
+        "{:<32} {:<32} File",
+        "Parse min/mean/max (ms)", "Render min/mean/max (ms)"
+    );
+
+    for file in report {
+        let parse = file.parse.to_string();
+        let render = file
+            .render
+            .as_ref()
+            .map(|t| t.to_string())
+            .unwrap_or_else(|| String::from("-"));
+
+        println!("{:<32} {:<32} {}", parse, render, file.path.display());
+    }
+}
+
+fn print_json_report(report: &[FileTimings]) {
+    let files: Vec<_> = report
+        .iter()
+        .map(|file| {
+            serde_json::json!({
+                "path": file.path.to_string_lossy(),
+                "parse": file.parse.to_json(),
+                "render": file.render.as_ref().map(Timings::to_json),
+            })
+        })
+        .collect();
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&serde_json::json!({ "files": files }))
+            .expect("JSON values can always be serialized")
+    );
+}
+
 fn build_cli() -> clap::Command {
     clap::Command::new("rsvg-bench")
         .version(concat!("version ", crate_version!()))
@@ -197,15 +460,55 @@ fn build_cli() -> clap::Command {
                 .default_value("1")
                 .value_parser(str::parse::<usize>),
         )
+        .arg(
+            clap::Arg::new("warmup")
+                .long("warmup")
+                .help("Number of extra times to parse and render each file before measuring")
+                .default_value("0")
+                .value_parser(str::parse::<usize>),
+        )
+        .arg(
+            clap::Arg::new("json")
+                .long("json")
+                .help("Print the timings as JSON; progress messages go to stderr")
+                .action(clap::ArgAction::SetTrue),
+        )
         .arg(
             clap::Arg::new("hard-failures")
                 .long("hard-failures")
                 .help("Stop all processing when a file cannot be rendered")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            clap::Arg::new("width")
+                .long("width")
+                .help("Width of the surface to render to")
+                .default_value("100")
+                .value_parser(value_parser!(i32).range(1..)),
+        )
+        .arg(
+            clap::Arg::new("height")
+                .long("height")
+                .help("Height of the surface to render to")
+                .default_value("100")
+                .value_parser(value_parser!(i32).range(1..)),
+        )
+        .arg(
+            clap::Arg::new("format")
+                .long("format")
+                .help("Type of surface to render to; pdf and svg output is discarded")
+                .default_value("png")
+                .value_parser(value_parser!(Format)),
+        )
+        .arg(
+            clap::Arg::new("export-id")
+                .long("export-id")
+                .help("SVG id of the element to render, instead of the whole document")
+                .value_parser(clap::builder::NonEmptyStringValueParser::new()),
+        )
         .arg(
             clap::Arg::new("inputs")
-                .help("Input files or directories")
+                .help("Input files or directories, or - for stdin")
                 .value_parser(value_parser!(PathBuf))
                 .action(clap::ArgAction::Append),
         )
@@ -228,20 +531,55 @@ fn main() {
         .get_one("num-render")
         .copied()
         .expect("already provided default_value");
+    let warmup = matches
+        .get_one("warmup")
+        .copied()
+        .expect("already provided default_value");
+    let json = matches.get_flag("json");
     let hard_failures = matches.get_flag("hard-failures");
+    let width = matches
+        .get_one("width")
+        .copied()
+        .expect("already provided default_value");
+    let height = matches
+        .get_one("height")
+        .copied()
+        .expect("already provided default_value");
+    let format = matches
+        .get_one("format")
+        .copied()
+        .expect("already provided default_value");
+    let export_id = matches.get_one::<String>("export-id").map(|id| {
+        if id.starts_with('#') {
+            id.clone()
+        } else {
+            format!("#{id}")
+        }
+    });
 
-    let inputs = if let Some(inputs) = matches.get_many("inputs") {
+    let inputs: Vec<PathBuf> = if let Some(inputs) = matches.get_many("inputs") {
         inputs.cloned().collect()
     } else {
         eprintln!("Must specify at least one SVG file or directory to process\n");
         process::exit(1);
     };
 
+    if inputs.iter().filter(|p| p.as_os_str() == "-").count() > 1 {
+        eprintln!("Only one input can be read from stdin\n");
+        process::exit(1);
+    }
+
     let opt = Opt {
         sleep_secs,
         num_parse,
         num_render,
+        warmup,
+        json,
         hard_failures,
+        width,
+        height,
+        format,
+        export_id,
         inputs,
     };
 
@@ -250,7 +588,7 @@ fn main() {
         process::exit(1);
     }
 
-    println!("hard_failures: {:?}", opt.hard_failures);
+    info!(opt, "hard_failures: {:?}", opt.hard_failures);
 
     match run(&opt) {
         Ok(_) => (),
@@ -1,7 +1,7 @@
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 
 use rsvg::bench_only::{
-    AlphaOnly, Horizontal, IRect, NotAlphaOnly, SharedImageSurface, SurfaceType, Vertical,
+    AlphaOnly, EdgeMode, Horizontal, IRect, NotAlphaOnly, SharedImageSurface, SurfaceType, Vertical,
 };
 
 const SURFACE_SIDE: i32 = 512;
@@ -47,6 +47,7 @@ fn bench_box_blur(c: &mut Criterion) {
                         BOUNDS,
                         KERNEL_SIZE,
                         KERNEL_SIZE / 2,
+                        EdgeMode::None,
                     )
                 })
             },
@@ -0,0 +1,47 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use nalgebra::DMatrix;
+
+use rsvg::bench_only::{EdgeMode, IRect, SharedImageSurface, SurfaceType};
+
+// Run these with and without the "parallel-filters" feature to compare the serial and
+// parallel implementations.
+
+const SURFACE_SIDE: i32 = 1024;
+const BOUNDS: IRect = IRect {
+    x0: 0,
+    y0: 0,
+    x1: SURFACE_SIDE,
+    y1: SURFACE_SIDE,
+};
+
+fn bench_convolve(c: &mut Criterion) {
+    let input_surface =
+        SharedImageSurface::empty(SURFACE_SIDE, SURFACE_SIDE, SurfaceType::SRgb).unwrap();
+
+    let kernel = DMatrix::from_element(3, 3, 1.0 / 9.0);
+
+    c.bench_function("convolve 3x3", |b| {
+        b.iter(|| {
+            input_surface
+                .convolve(BOUNDS, (1, 1), &kernel, EdgeMode::Duplicate)
+                .unwrap()
+        })
+    });
+}
+
+fn bench_gaussian_blur(c: &mut Criterion) {
+    let input_surface =
+        SharedImageSurface::empty(SURFACE_SIDE, SURFACE_SIDE, SurfaceType::SRgb).unwrap();
+
+    // Deviations under 2.0 use the exact kernel; larger ones use box blurs.
+    c.bench_function("gaussian_blur 1.5", |b| {
+        b.iter(|| {
+            input_surface
+                .gaussian_blur(1.5, 1.5, EdgeMode::None)
+                .unwrap()
+        })
+    });
+}
+
+criterion_group!(benches, bench_convolve, bench_gaussian_blur);
+criterion_main!(benches);
@@ -0,0 +1,54 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use rsvg::{CairoRenderer, SvgHandle};
+
+/// Creates a sprite sheet with `n` icons in a grid, each with its own id.
+fn sprite_sheet(n: usize) -> (SvgHandle, Vec<String>) {
+    let mut svg =
+        String::from(r#"<svg xmlns="http://www.w3.org/2000/svg" width="1000" height="1000">"#);
+    let mut ids = Vec::with_capacity(n);
+
+    for i in 0..n {
+        let x = (i % 50) * 20;
+        let y = (i / 50) * 20;
+
+        svg.push_str(&format!(
+            r#"<g id="icon{i}" transform="translate({x}, {y})"><path d="M 2 2 L 18 2 L 10 18 Z" stroke="black"/></g>"#
+        ));
+        ids.push(format!("#icon{i}"));
+    }
+
+    svg.push_str("</svg>");
+
+    (SvgHandle::try_from(svg.as_str()).unwrap(), ids)
+}
+
+fn bench_geometries(c: &mut Criterion) {
+    let mut group = c.benchmark_group("geometries");
+    let viewport = cairo::Rectangle::new(0.0, 0.0, 1000.0, 1000.0);
+
+    for n in [10, 100, 500] {
+        let (svg, ids) = sprite_sheet(n);
+        let ids: Vec<&str> = ids.iter().map(String::as_str).collect();
+        let renderer = CairoRenderer::new(&svg);
+
+        group.bench_with_input(BenchmarkId::new("geometry_for_layer", n), &ids, |b, ids| {
+            b.iter(|| {
+                for id in ids {
+                    renderer.geometry_for_layer(Some(*id), &viewport).unwrap();
+                }
+            })
+        });
+
+        group.bench_with_input(
+            BenchmarkId::new("geometries_for_elements", n),
+            &ids,
+            |b, ids| b.iter(|| renderer.geometries_for_elements(ids, &viewport)),
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_geometries);
+criterion_main!(benches);
@@ -17,7 +17,17 @@ use crate::api::CairoRenderer;
 /// preferred languages will be rendered.
 ///
 /// This enum, used with [`CairoRenderer::with_language`], configures how to obtain the
-/// user's prefererred languages.
+/// user's prefererred languages.  The default is [`Language::FromEnvironment`].
+///
+/// # Example
+///
+/// ```
+/// use rsvg::{AcceptLanguage, Language};
+///
+/// // Prefer German, but accept English as well.
+/// let language = Language::AcceptLanguage(AcceptLanguage::parse("de, en;q=0.5").unwrap());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
 pub enum Language {
     /// Use the Unix environment variables `LANGUAGE`, `LC_ALL`, `LC_MESSAGES` and `LANG` to obtain the
     /// user's language.
@@ -25,6 +35,7 @@ pub enum Language {
     /// This uses [`g_get_language_names()`][ggln] underneath.
     ///
     /// [ggln]: https://docs.gtk.org/glib/func.get_language_names.html
+    #[default]
     FromEnvironment,
 
     /// Use a list of languages in the form of an HTTP Accept-Language header, like `es, en;q=0.8`.
@@ -35,7 +46,7 @@ pub enum Language {
 }
 
 /// `Language` but with the environment's locale converted to something we can use.
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub enum UserLanguage {
     LanguageTags(LanguageTags),
     AcceptLanguage(AcceptLanguage),
@@ -6,7 +6,7 @@ use cssparser::{Parser, Token};
 use float_cmp::approx_eq;
 
 use crate::error::*;
-use crate::parsers::{finite_f32, Parse};
+use crate::parsers::{finite_f32, Parse, ToCssString};
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Angle(f64);
@@ -67,6 +67,12 @@ impl Angle {
 //
 // angle ::= number ("deg" | "grad" | "rad")?
 //
+impl ToCssString for Angle {
+    fn to_css_string(&self) -> String {
+        format!("{}deg", self.0.to_degrees().to_css_string())
+    }
+}
+
 impl Parse for Angle {
     fn parse<'i>(parser: &mut Parser<'i, '_>) -> Result<Angle, ParseError<'i>> {
         let angle = {
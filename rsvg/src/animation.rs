@@ -0,0 +1,821 @@
+//! SMIL animation elements: `set`, `animate`, and `animateTransform`.
+//!
+//! Librsvg renders static images, but a caller can ask for a document to be rendered as it
+//! would look at a certain time of its animation timeline; see
+//! [`CairoRenderer::with_animation_time`](crate::CairoRenderer::with_animation_time).  In
+//! that case, [`cascade_at_time`] recomputes the cascade with the values of the animations
+//! that are active at that time.
+//!
+//! Only a subset of SMIL is supported.  Animations that use unsupported features are
+//! ignored, that is, they leave the target attribute with its static value:
+//!
+//! * The target of an animation is always its parent element; `href` is not supported.
+//!
+//! * `begin` only supports offset values like `2s`.  Animations that begin on an event or
+//!   relative to other animations never start.  If there are several offset values, the
+//!   earliest one is used.
+//!
+//! * `calcMode="paced"` and `calcMode="spline"` are not supported, and neither are
+//!   animations that only have a `to` or a `by` attribute.
+//!
+//! * `additive="sum"` is only supported in `animateTransform`.  The `accumulate`,
+//!   `repeatDur`, `min`, `max`, and `restart` attributes are not supported and get ignored.
+//!
+//! Values get interpolated between numbers with the same unit, like `10px` and `20px`, and
+//! between colors.  Other values, like keywords, change discretely at the middle of each
+//! interval.
+
+use cssparser::{Parser, ParserInput, Token, RGBA};
+use markup5ever::{expanded_name, local_name, namespace_url, ns, LocalName, QualName};
+
+use crate::element::{set_attribute, ElementData, ElementTrait};
+use crate::error::*;
+use crate::node::{Node, NodeBorrow, NodeCascade};
+use crate::parse_identifiers;
+use crate::parsers::{Parse, ParseValue};
+use crate::properties::{parse_value, ComputedValues, ParseAs, ParsedProperty, SpecifiedValues};
+use crate::session::Session;
+use crate::transform::{Transform, TransformAttribute};
+use crate::xml::Attributes;
+
+/// A clock value, in seconds.
+///
+/// This supports timecount values like `2s`, `500ms`, `1.5min`, or `1h`.  A plain number is
+/// a number of seconds.  Full clock values like `00:01:30` are not supported.
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct ClockValue(f64);
+
+impl Parse for ClockValue {
+    fn parse<'i>(parser: &mut Parser<'i, '_>) -> Result<ClockValue, ParseError<'i>> {
+        let loc = parser.current_source_location();
+        let token = parser.next()?;
+
+        let seconds = match *token {
+            Token::Number { value, .. } => f64::from(value),
+
+            Token::Dimension {
+                value, ref unit, ..
+            } => {
+                let factor = match unit.as_ref() {
+                    "h" => 3600.0,
+                    "min" => 60.0,
+                    "s" => 1.0,
+                    "ms" => 0.001,
+                    _ => return Err(loc.new_unexpected_token_error(token.clone())),
+                };
+
+                f64::from(value) * factor
+            }
+
+            _ => return Err(loc.new_unexpected_token_error(token.clone())),
+        };
+
+        if seconds.is_finite() {
+            Ok(ClockValue(seconds))
+        } else {
+            Err(loc.new_custom_error(ValueErrorKind::value_error("expected finite clock value")))
+        }
+    }
+}
+
+/// The `dur` attribute.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+enum Duration {
+    #[default]
+    Indefinite,
+    Seconds(f64),
+}
+
+impl Parse for Duration {
+    fn parse<'i>(parser: &mut Parser<'i, '_>) -> Result<Duration, ParseError<'i>> {
+        if parser
+            .try_parse(|p| p.expect_ident_matching("indefinite"))
+            .is_ok()
+        {
+            return Ok(Duration::Indefinite);
+        }
+
+        let loc = parser.current_source_location();
+        let ClockValue(seconds) = ClockValue::parse(parser)?;
+
+        if seconds > 0.0 {
+            Ok(Duration::Seconds(seconds))
+        } else {
+            Err(loc.new_custom_error(ValueErrorKind::value_error("duration must be positive")))
+        }
+    }
+}
+
+/// The `repeatCount` attribute.
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum RepeatCount {
+    Count(f64),
+    Indefinite,
+}
+
+impl Parse for RepeatCount {
+    fn parse<'i>(parser: &mut Parser<'i, '_>) -> Result<RepeatCount, ParseError<'i>> {
+        if parser
+            .try_parse(|p| p.expect_ident_matching("indefinite"))
+            .is_ok()
+        {
+            return Ok(RepeatCount::Indefinite);
+        }
+
+        let loc = parser.current_source_location();
+        let count = f64::parse(parser)?;
+
+        if count > 0.0 {
+            Ok(RepeatCount::Count(count))
+        } else {
+            Err(loc.new_custom_error(ValueErrorKind::value_error("repeat count must be positive")))
+        }
+    }
+}
+
+/// The `fill` attribute of animation elements, which is unrelated to the `fill` property.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+enum Fill {
+    #[default]
+    Remove,
+    Freeze,
+}
+
+impl Parse for Fill {
+    fn parse<'i>(parser: &mut Parser<'i, '_>) -> Result<Fill, ParseError<'i>> {
+        Ok(parse_identifiers!(
+            parser,
+            "remove" => Fill::Remove,
+            "freeze" => Fill::Freeze,
+        )?)
+    }
+}
+
+/// The `calcMode` attribute.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+enum CalcMode {
+    Discrete,
+    #[default]
+    Linear,
+    Paced,
+    Spline,
+}
+
+impl Parse for CalcMode {
+    fn parse<'i>(parser: &mut Parser<'i, '_>) -> Result<CalcMode, ParseError<'i>> {
+        Ok(parse_identifiers!(
+            parser,
+            "discrete" => CalcMode::Discrete,
+            "linear" => CalcMode::Linear,
+            "paced" => CalcMode::Paced,
+            "spline" => CalcMode::Spline,
+        )?)
+    }
+}
+
+/// The `additive` attribute.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+enum Additive {
+    #[default]
+    Replace,
+    Sum,
+}
+
+impl Parse for Additive {
+    fn parse<'i>(parser: &mut Parser<'i, '_>) -> Result<Additive, ParseError<'i>> {
+        Ok(parse_identifiers!(
+            parser,
+            "replace" => Additive::Replace,
+            "sum" => Additive::Sum,
+        )?)
+    }
+}
+
+/// The `keyTimes` attribute, a semicolon-separated list of numbers.
+#[derive(Debug, Clone, PartialEq)]
+struct KeyTimes(Vec<f64>);
+
+impl Parse for KeyTimes {
+    fn parse<'i>(parser: &mut Parser<'i, '_>) -> Result<KeyTimes, ParseError<'i>> {
+        let mut times = Vec::new();
+
+        loop {
+            times.push(f64::parse(parser)?);
+
+            if parser.is_exhausted() {
+                break;
+            }
+
+            parser.expect_semicolon()?;
+        }
+
+        Ok(KeyTimes(times))
+    }
+}
+
+/// The `type` attribute of `animateTransform`.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+enum TransformType {
+    #[default]
+    Translate,
+    Scale,
+    Rotate,
+    SkewX,
+    SkewY,
+}
+
+impl Parse for TransformType {
+    fn parse<'i>(parser: &mut Parser<'i, '_>) -> Result<TransformType, ParseError<'i>> {
+        Ok(parse_identifiers!(
+            parser,
+            "translate" => TransformType::Translate,
+            "scale" => TransformType::Scale,
+            "rotate" => TransformType::Rotate,
+            "skewX" => TransformType::SkewX,
+            "skewY" => TransformType::SkewY,
+        )?)
+    }
+}
+
+impl TransformType {
+    /// Builds a transform of this type out of the numbers in a value of `animateTransform`.
+    fn to_transform(self, params: &[f64]) -> Option<Transform> {
+        let name = match self {
+            TransformType::Translate => "translate",
+            TransformType::Scale => "scale",
+            TransformType::Rotate => "rotate",
+            TransformType::SkewX => "skewX",
+            TransformType::SkewY => "skewY",
+        };
+
+        let params: Vec<String> = params.iter().map(|p| p.to_string()).collect();
+        let s = format!("{}({})", name, params.join(" "));
+
+        TransformAttribute::parse_str(&s)
+            .ok()
+            .map(|t| t.to_transform())
+    }
+}
+
+/// What an animation does to its target element at a certain time.
+enum Effect {
+    Property(ParsedProperty),
+    Transform(Transform, Additive),
+}
+
+impl Effect {
+    fn apply(&self, specified: &mut SpecifiedValues) {
+        match *self {
+            Effect::Property(ref prop) => specified.set_parsed_property(prop),
+
+            Effect::Transform(ref transform, Additive::Replace) => {
+                specified.set_transform(*transform);
+            }
+
+            Effect::Transform(ref transform, Additive::Sum) => {
+                let base = specified.transform();
+                specified.set_transform(base.pre_transform(transform));
+            }
+        }
+    }
+}
+
+/// Timing and values of an animation element; the parts that all of them share.
+struct Animation {
+    attribute_name: Option<QualName>,
+
+    /// `None` if the animation never begins; see [`parse_begin`].
+    begin: Option<f64>,
+
+    dur: Duration,
+    repeat_count: Option<RepeatCount>,
+    fill: Fill,
+    calc_mode: CalcMode,
+    additive: Additive,
+    values: Option<Vec<String>>,
+    key_times: Option<KeyTimes>,
+    from: Option<String>,
+    to: Option<String>,
+}
+
+impl Default for Animation {
+    fn default() -> Animation {
+        Animation {
+            attribute_name: None,
+            begin: Some(0.0),
+            dur: Default::default(),
+            repeat_count: None,
+            fill: Default::default(),
+            calc_mode: Default::default(),
+            additive: Default::default(),
+            values: None,
+            key_times: None,
+            from: None,
+            to: None,
+        }
+    }
+}
+
+/// Parses the `begin` attribute, and returns the earliest offset in it.
+///
+/// Returns `None` if there are no offset values, for example if the animation begins on an
+/// event; those are not supported, so the animation never begins.
+fn parse_begin(value: &str) -> Option<f64> {
+    value
+        .split(';')
+        .filter_map(|v| ClockValue::parse_str(v.trim()).ok())
+        .map(|ClockValue(seconds)| seconds)
+        .reduce(f64::min)
+}
+
+/// Parses a semicolon-separated list of values, like the `values` attribute.
+fn parse_values(value: &str) -> Option<Vec<String>> {
+    let values: Vec<String> = value
+        .split(';')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(String::from)
+        .collect();
+
+    if values.is_empty() {
+        None
+    } else {
+        Some(values)
+    }
+}
+
+impl Animation {
+    fn set_attributes(&mut self, attrs: &Attributes, session: &Session) {
+        for (attr, value) in attrs.iter() {
+            match attr.expanded() {
+                expanded_name!("", "attributeName") => {
+                    let name = LocalName::from(value.trim());
+                    self.attribute_name = Some(QualName::new(None, ns!(), name));
+                }
+                expanded_name!("", "begin") => self.begin = parse_begin(value),
+                expanded_name!("", "dur") => {
+                    set_attribute(&mut self.dur, attr.parse(value), session)
+                }
+                expanded_name!("", "repeatCount") => {
+                    set_attribute(&mut self.repeat_count, attr.parse(value), session)
+                }
+                expanded_name!("", "fill") => {
+                    set_attribute(&mut self.fill, attr.parse(value), session)
+                }
+                expanded_name!("", "calcMode") => {
+                    set_attribute(&mut self.calc_mode, attr.parse(value), session)
+                }
+                expanded_name!("", "additive") => {
+                    set_attribute(&mut self.additive, attr.parse(value), session)
+                }
+                expanded_name!("", "keyTimes") => {
+                    set_attribute(&mut self.key_times, attr.parse(value), session)
+                }
+                expanded_name!("", "values") => self.values = parse_values(value),
+                expanded_name!("", "from") => self.from = Some(value.trim().to_string()),
+                expanded_name!("", "to") => self.to = Some(value.trim().to_string()),
+                _ => (),
+            }
+        }
+    }
+
+    /// Computes how far the animation is along its simple duration at `time`, from 0 to 1.
+    ///
+    /// Returns `None` if the animation has no effect at that time, that is, if it has not
+    /// begun yet, or if it has already ended and it is not frozen.
+    fn progress_at(&self, time: f64) -> Option<f64> {
+        let elapsed = time - self.begin?;
+
+        if elapsed < 0.0 {
+            return None;
+        }
+
+        let dur = match self.dur {
+            // An animation without a duration stays at its first value.
+            Duration::Indefinite => return Some(0.0),
+            Duration::Seconds(dur) => dur,
+        };
+
+        let repeats = match self.repeat_count {
+            None => 1.0,
+            Some(RepeatCount::Count(n)) => n,
+            Some(RepeatCount::Indefinite) => f64::INFINITY,
+        };
+
+        if elapsed < dur * repeats {
+            Some((elapsed % dur) / dur)
+        } else if self.fill == Fill::Freeze {
+            let partial = repeats.fract();
+
+            Some(if partial == 0.0 { 1.0 } else { partial })
+        } else {
+            None
+        }
+    }
+
+    /// The list of values to animate through, from either `values` or `from`/`to`.
+    fn keyframes(&self) -> Option<Vec<&str>> {
+        if let Some(ref values) = self.values {
+            Some(values.iter().map(String::as_str).collect())
+        } else {
+            match (&self.from, &self.to) {
+                (Some(from), Some(to)) => Some(vec![from.as_str(), to.as_str()]),
+                _ => None,
+            }
+        }
+    }
+
+    /// The keyframe value at `progress`, as computed by [`progress_at`](Self::progress_at).
+    fn sample<T: Clone>(
+        &self,
+        values: &[T],
+        progress: f64,
+        interpolate: impl Fn(&T, &T, f64) -> T,
+    ) -> Option<T> {
+        let n = values.len();
+
+        if n == 0 {
+            return None;
+        }
+
+        let key_times = self.key_times.as_ref().map(|k| k.0.as_slice());
+
+        if let Some(key_times) = key_times {
+            if key_times.len() != n
+                || key_times[0] != 0.0
+                || key_times.windows(2).any(|w| w[0] > w[1])
+                || key_times[n - 1] > 1.0
+            {
+                return None;
+            }
+        }
+
+        match self.calc_mode {
+            CalcMode::Discrete => {
+                let index = match key_times {
+                    Some(key_times) => key_times.iter().rposition(|&k| k <= progress)?,
+                    None => ((progress * n as f64) as usize).min(n - 1),
+                };
+
+                Some(values[index].clone())
+            }
+
+            CalcMode::Linear => {
+                if n == 1 {
+                    return Some(values[0].clone());
+                }
+
+                if key_times.is_some_and(|k| k[n - 1] != 1.0) {
+                    return None;
+                }
+
+                if progress >= 1.0 {
+                    return Some(values[n - 1].clone());
+                }
+
+                let key_time = |i: usize| key_times.map_or(i as f64 / (n - 1) as f64, |k| k[i]);
+
+                let i = (0..n - 1).rev().find(|&i| key_time(i) <= progress)?;
+                let (start, end) = (key_time(i), key_time(i + 1));
+
+                Some(interpolate(
+                    &values[i],
+                    &values[i + 1],
+                    (progress - start) / (end - start),
+                ))
+            }
+
+            CalcMode::Paced | CalcMode::Spline => None,
+        }
+    }
+}
+
+/// The `set` element.
+#[derive(Default)]
+pub struct Set(Animation);
+
+impl ElementTrait for Set {
+    fn set_attributes(&mut self, attrs: &Attributes, session: &Session) {
+        self.0.set_attributes(attrs, session);
+    }
+}
+
+impl Set {
+    fn effect_at(&self, time: f64) -> Option<Effect> {
+        let animation = &self.0;
+
+        animation.progress_at(time)?;
+
+        let name = animation.attribute_name.as_ref()?;
+        let to = animation.to.as_ref()?;
+
+        parse_property(name, to).map(Effect::Property)
+    }
+}
+
+/// The `animate` element.
+#[derive(Default)]
+pub struct Animate(Animation);
+
+impl ElementTrait for Animate {
+    fn set_attributes(&mut self, attrs: &Attributes, session: &Session) {
+        self.0.set_attributes(attrs, session);
+    }
+}
+
+impl Animate {
+    /// Returns `None` for `additive="sum"`, which is not supported for properties, and for
+    /// the unsupported `calcMode` values; see the module documentation.
+    fn effect_at(&self, time: f64) -> Option<Effect> {
+        let animation = &self.0;
+
+        if animation.additive == Additive::Sum {
+            return None;
+        }
+
+        let progress = animation.progress_at(time)?;
+        let name = animation.attribute_name.as_ref()?;
+
+        let values: Vec<String> = animation
+            .keyframes()?
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        let value = animation.sample(&values, progress, |a, b, t| interpolate_value(a, b, t))?;
+
+        parse_property(name, &value).map(Effect::Property)
+    }
+}
+
+/// The `animateTransform` element.
+#[derive(Default)]
+pub struct AnimateTransform {
+    animation: Animation,
+    type_: TransformType,
+}
+
+impl ElementTrait for AnimateTransform {
+    fn set_attributes(&mut self, attrs: &Attributes, session: &Session) {
+        self.animation.set_attributes(attrs, session);
+
+        for (attr, value) in attrs.iter() {
+            if attr.expanded() == expanded_name!("", "type") {
+                set_attribute(&mut self.type_, attr.parse(value), session);
+            }
+        }
+    }
+}
+
+impl AnimateTransform {
+    fn effect_at(&self, time: f64) -> Option<Effect> {
+        let animation = &self.animation;
+
+        // gradientTransform and patternTransform are not supported yet.
+        if animation.attribute_name.as_ref()?.local != local_name!("transform") {
+            return None;
+        }
+
+        let progress = animation.progress_at(time)?;
+
+        let values = animation
+            .keyframes()?
+            .into_iter()
+            .map(parse_number_list)
+            .collect::<Option<Vec<_>>>()?;
+
+        let params = animation.sample(&values, progress, |a, b, t| interpolate_numbers(a, b, t))?;
+        let transform = self.type_.to_transform(&params)?;
+
+        Some(Effect::Transform(transform, animation.additive))
+    }
+}
+
+/// Parses a value for the property named `name`, as if it were a presentation attribute.
+fn parse_property(name: &QualName, value: &str) -> Option<ParsedProperty> {
+    let mut input = ParserInput::new(value);
+    let mut parser = Parser::new(&mut input);
+
+    let prop = parse_value(name, &mut parser, ParseAs::PresentationAttr).ok()?;
+    parser.expect_exhausted().ok()?;
+
+    Some(prop)
+}
+
+/// Parses a number with an optional unit, like `10`, `10px`, or `50%`.
+fn parse_dimension(s: &str) -> Option<(f64, String)> {
+    let mut input = ParserInput::new(s);
+    let mut parser = Parser::new(&mut input);
+
+    let dimension = match *parser.next().ok()? {
+        Token::Number { value, .. } => (f64::from(value), String::new()),
+        Token::Dimension {
+            value, ref unit, ..
+        } => (f64::from(value), unit.to_string()),
+        Token::Percentage { unit_value, .. } => (f64::from(unit_value) * 100.0, "%".to_string()),
+        _ => return None,
+    };
+
+    parser.expect_exhausted().ok()?;
+
+    Some(dimension)
+}
+
+fn parse_rgba(s: &str) -> Option<RGBA> {
+    match cssparser::Color::parse_str(s) {
+        Ok(cssparser::Color::Rgba(rgba)) => Some(rgba),
+        _ => None,
+    }
+}
+
+/// Parses the parameters of a transform in `animateTransform`, like `45 10 10`.
+fn parse_number_list(s: &str) -> Option<Vec<f64>> {
+    s.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|n| !n.is_empty())
+        .map(|n| n.parse::<f64>().ok().filter(|n| n.is_finite()))
+        .collect()
+}
+
+fn lerp(from: f64, to: f64, t: f64) -> f64 {
+    from + (to - from) * t
+}
+
+/// Interpolates between two property values, or picks one of them if they are not numbers
+/// with the same unit, or colors.
+fn interpolate_value(from: &str, to: &str, t: f64) -> String {
+    if let (Some((a, a_unit)), Some((b, b_unit))) = (parse_dimension(from), parse_dimension(to)) {
+        if a_unit == b_unit {
+            return format!("{}{}", lerp(a, b, t), a_unit);
+        }
+    }
+
+    if let (Some(a), Some(b)) = (parse_rgba(from), parse_rgba(to)) {
+        let channel = |a: Option<u8>, b: Option<u8>| {
+            lerp(f64::from(a.unwrap_or(0)), f64::from(b.unwrap_or(0)), t).round()
+        };
+
+        let alpha = lerp(
+            f64::from(a.alpha.unwrap_or(0.0)),
+            f64::from(b.alpha.unwrap_or(0.0)),
+            t,
+        );
+
+        return format!(
+            "rgba({}, {}, {}, {})",
+            channel(a.red, b.red),
+            channel(a.green, b.green),
+            channel(a.blue, b.blue),
+            alpha
+        );
+    }
+
+    if t < 0.5 {
+        from.to_string()
+    } else {
+        to.to_string()
+    }
+}
+
+/// Interpolates between two lists of numbers, or picks one of them if their lengths differ.
+fn interpolate_numbers(from: &[f64], to: &[f64], t: f64) -> Vec<f64> {
+    if from.len() == to.len() {
+        from.iter().zip(to).map(|(a, b)| lerp(*a, *b, t)).collect()
+    } else if t < 0.5 {
+        from.to_vec()
+    } else {
+        to.to_vec()
+    }
+}
+
+/// Computes the specified values of `node` with its active animations at `time` applied.
+///
+/// Returns `None` if no animations of the element are active at that time.
+fn animated_specified_values(node: &Node, time: f64) -> Option<SpecifiedValues> {
+    let mut specified: Option<SpecifiedValues> = None;
+
+    for child in node.children().filter(|c| c.is_element()) {
+        let effect = match *child.borrow_element_data() {
+            ElementData::Set(ref set) => set.effect_at(time),
+            ElementData::Animate(ref animate) => animate.effect_at(time),
+            ElementData::AnimateTransform(ref animate) => animate.effect_at(time),
+            _ => None,
+        };
+
+        if let Some(effect) = effect {
+            let specified = specified
+                .get_or_insert_with(|| node.borrow_element().get_specified_values().clone());
+
+            effect.apply(specified);
+        }
+    }
+
+    specified
+}
+
+/// Runs the cascade on the tree under `node`, with the animation values at `time`.
+///
+/// The specified values of each element get overridden by its active animations, in
+/// document order.  Call [`NodeCascade::cascade`] again on the tree to go back to the
+/// static values.
+pub fn cascade_at_time(node: &mut Node, values: &ComputedValues, time: f64) {
+    node.cascade_with(values, &|node| animated_specified_values(node, time));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_clock_values() {
+        assert_eq!(ClockValue::parse_str("2").unwrap(), ClockValue(2.0));
+        assert_eq!(ClockValue::parse_str("2s").unwrap(), ClockValue(2.0));
+        assert_eq!(ClockValue::parse_str("500ms").unwrap(), ClockValue(0.5));
+        assert_eq!(ClockValue::parse_str("1.5min").unwrap(), ClockValue(90.0));
+        assert_eq!(ClockValue::parse_str("1h").unwrap(), ClockValue(3600.0));
+
+        assert!(ClockValue::parse_str("2px").is_err());
+        assert!(ClockValue::parse_str("click").is_err());
+    }
+
+    #[test]
+    fn parses_begin_offsets() {
+        assert_eq!(parse_begin("1s"), Some(1.0));
+        assert_eq!(parse_begin("3s; 1s"), Some(1.0));
+        assert_eq!(parse_begin("click; 2s"), Some(2.0));
+        assert_eq!(parse_begin("click"), None);
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(
+            Duration::parse_str("indefinite").unwrap(),
+            Duration::Indefinite
+        );
+        assert_eq!(Duration::parse_str("2s").unwrap(), Duration::Seconds(2.0));
+        assert!(Duration::parse_str("0s").is_err());
+    }
+
+    fn animation(dur: f64, repeat_count: Option<RepeatCount>, fill: Fill) -> Animation {
+        Animation {
+            begin: Some(1.0),
+            dur: Duration::Seconds(dur),
+            repeat_count,
+            fill,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn computes_progress() {
+        let a = animation(2.0, None, Fill::Remove);
+        assert_eq!(a.progress_at(0.0), None);
+        assert_eq!(a.progress_at(1.0), Some(0.0));
+        assert_eq!(a.progress_at(2.0), Some(0.5));
+        assert_eq!(a.progress_at(3.0), None);
+
+        let a = animation(2.0, None, Fill::Freeze);
+        assert_eq!(a.progress_at(10.0), Some(1.0));
+
+        let a = animation(2.0, Some(RepeatCount::Count(1.5)), Fill::Freeze);
+        assert_eq!(a.progress_at(3.5), Some(0.25));
+        assert_eq!(a.progress_at(10.0), Some(0.5));
+
+        let a = animation(2.0, Some(RepeatCount::Indefinite), Fill::Remove);
+        assert_eq!(a.progress_at(101.0), Some(0.0));
+    }
+
+    #[test]
+    fn samples_values() {
+        let values = vec![0.0, 10.0, 30.0];
+        let interpolate = |a: &f64, b: &f64, t: f64| lerp(*a, *b, t);
+
+        let mut a = Animation::default();
+        assert_eq!(a.sample(&values, 0.25, interpolate), Some(5.0));
+        assert_eq!(a.sample(&values, 0.75, interpolate), Some(20.0));
+        assert_eq!(a.sample(&values, 1.0, interpolate), Some(30.0));
+
+        a.key_times = Some(KeyTimes(vec![0.0, 0.25, 1.0]));
+        assert_eq!(a.sample(&values, 0.125, interpolate), Some(5.0));
+        assert_eq!(a.sample(&values, 0.625, interpolate), Some(20.0));
+
+        a.calc_mode = CalcMode::Discrete;
+        a.key_times = None;
+        assert_eq!(a.sample(&values, 0.5, interpolate), Some(10.0));
+        assert_eq!(a.sample(&values, 1.0, interpolate), Some(30.0));
+
+        a.calc_mode = CalcMode::Paced;
+        assert_eq!(a.sample(&values, 0.5, interpolate), None);
+    }
+
+    #[test]
+    fn interpolates_values() {
+        assert_eq!(interpolate_value("10px", "20px", 0.5), "15px");
+        assert_eq!(interpolate_value("0", "1", 0.25), "0.25");
+        assert_eq!(
+            interpolate_value("#000000", "#ff0000", 0.5),
+            "rgba(128, 0, 0, 1)"
+        );
+        assert_eq!(interpolate_value("10px", "20%", 0.25), "10px");
+        assert_eq!(interpolate_value("inline", "none", 0.75), "none");
+    }
+}
@@ -9,33 +9,66 @@ use std::fmt;
 // Here we only re-export stuff in the public API.
 pub use crate::{
     accept_language::{AcceptLanguage, Language},
+    aspect_ratio::AspectRatio,
+    css::StylesheetOrigin,
+    dpi::Dpi,
     drawing_ctx::Viewport,
-    error::{DefsLookupErrorKind, ImplementationLimit, LoadingError},
-    length::{LengthUnit, RsvgLength as Length},
+    error::{
+        AspectRatioParseError, ColorParseError, DefsLookupErrorKind, ImplementationLimit,
+        LoadWarning, LoadingError, PathParseError, TransformParseError, ViewBoxParseError,
+    },
+    length::{LengthUnit, NormalizeParams, RsvgLength as Length},
+    limits::RenderLimits,
+    path_builder::{CubicBezierCurve, EllipticalArc, LargeArc, PathBuilder, PathCommand, Sweep},
+    rect::Rect,
+    render_observer::RenderObserver,
+    space::WhitespaceMode,
+    surface_utils::{iterators::Pixels, shared_surface::SharedImageSurface, Pixel, PixelOps},
+    text::{FontConfig, FontWarning},
+    url_resolver::{ReferenceKind, ReferencedUrl, ResolveError, ResourceCallback},
+    viewbox::ViewBox,
 };
 
 // Don't merge these in the "pub use" above!  They are not part of the public API!
 use crate::{
     accept_language::{LanguageTags, UserLanguage},
+    color::color_to_rgba,
     css::{Origin, Stylesheet},
-    document::{Document, LoadOptions, NodeId, RenderingOptions},
-    dpi::Dpi,
+    custom_properties::CustomProperties,
+    document::{
+        paint_background, Document, KeepImageData, LoadOptions, NodeId,
+        RenderingOptions as DocumentRenderingOptions,
+    },
     drawing_ctx::SvgNesting,
     error::InternalRenderingError,
-    length::NormalizeParams,
-    node::{CascadedValues, Node},
-    rsvg_log,
+    limits::RenderBudget,
+    node::{CascadedValues, Node, NodeBorrow},
+    parsers::Parse,
+    render_observer, rsvg_log,
     session::Session,
+    shapes,
+    structure::{desc_text, title_text},
+    surface_utils::shared_surface::{ExclusiveImageSurface, SurfaceType},
+    text::FontMap,
+    transform::{Transform, TransformAttribute, TransformProperty},
     url_resolver::UrlResolver,
 };
 
 use url::Url;
 
+use std::collections::HashMap;
+#[cfg(feature = "gio")]
 use std::path::Path;
+use std::rc::Rc;
 use std::sync::Arc;
 
+#[cfg(feature = "gio")]
 use gio::prelude::*; // Re-exposes glib's prelude as well
-use gio::Cancellable;
+
+use crate::io::Cancellable;
+
+#[cfg(feature = "gio")]
+use crate::xml::XmlPushLoader;
 
 use locale_config::{LanguageRange, Locale};
 
@@ -46,6 +79,11 @@ pub enum RenderingError {
     /// An error from the rendering backend.
     Rendering(String),
 
+    /// An error from Cairo, for example when the `cairo::Context` is already in an error state.
+    ///
+    /// The original error is available from [`std::error::Error::source`].
+    Cairo(cairo::Error),
+
     /// A particular implementation-defined limit was exceeded.
     LimitExceeded(ImplementationLimit),
 
@@ -55,6 +93,24 @@ pub enum RenderingError {
     /// Tried to reference an SVG element from a fragment identifier that is incorrect.
     InvalidId(String),
 
+    /// The document has a reference cycle, like a `<use>` that references one of its
+    /// ancestors, or two patterns whose contents are filled with each other.
+    ///
+    /// Librsvg skips the elements in the cycle and renders the rest of the document, and
+    /// then returns this error.  This is only returned if the renderer was configured with
+    /// [`CairoRenderer::with_circular_reference_errors`]; otherwise the cycle is not
+    /// reported.
+    CircularReference {
+        /// The `id` attribute of the element where the cycle was found, or of its nearest
+        /// ancestor that has one.  This is `None` if none of them has an `id`.
+        id: Option<String>,
+    },
+
+    /// Tried to get the path of an SVG element that is not a basic shape.
+    ///
+    /// See the documentation for [`SvgHandle::element_path`].
+    NotAShape,
+
     /// Not enough memory was available for rendering.
     OutOfMemory(String),
 
@@ -62,13 +118,25 @@ pub enum RenderingError {
     ///
     /// See the documentation for [`CairoRenderer::with_cancellable`].
     Cancelled,
+
+    /// The horizontal and vertical DPI values were not both positive and finite.
+    ///
+    /// See the documentation for [`CairoRenderer::try_with_dpi`].
+    InvalidDpi(f64, f64),
 }
 
-impl std::error::Error for RenderingError {}
+impl std::error::Error for RenderingError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            RenderingError::Cairo(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
 
 impl From<cairo::Error> for RenderingError {
     fn from(e: cairo::Error) -> RenderingError {
-        RenderingError::Rendering(format!("{e:?}"))
+        RenderingError::Cairo(e)
     }
 }
 
@@ -80,13 +148,16 @@ impl From<InternalRenderingError> for RenderingError {
         // maybe.)
         match e {
             InternalRenderingError::Rendering(s) => RenderingError::Rendering(s),
+            InternalRenderingError::Cairo(e) => RenderingError::Cairo(e),
             InternalRenderingError::LimitExceeded(l) => RenderingError::LimitExceeded(l),
             InternalRenderingError::InvalidTransform => {
                 RenderingError::Rendering("invalid transform".to_string())
             }
-            InternalRenderingError::CircularReference(c) => {
-                RenderingError::Rendering(format!("circular reference in node {c}"))
-            }
+            InternalRenderingError::CircularReference(c) => RenderingError::CircularReference {
+                id: c
+                    .ancestors()
+                    .find_map(|n| n.borrow_element().get_id().map(String::from)),
+            },
             InternalRenderingError::IdNotFound => RenderingError::IdNotFound,
             InternalRenderingError::InvalidId(s) => RenderingError::InvalidId(s),
             InternalRenderingError::OutOfMemory(s) => RenderingError::OutOfMemory(s),
@@ -99,11 +170,18 @@ impl fmt::Display for RenderingError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
             RenderingError::Rendering(ref s) => write!(f, "rendering error: {s}"),
+            RenderingError::Cairo(ref e) => write!(f, "rendering error: {e:?}"),
             RenderingError::LimitExceeded(ref l) => write!(f, "{l}"),
             RenderingError::IdNotFound => write!(f, "element id not found"),
             RenderingError::InvalidId(ref s) => write!(f, "invalid id: {s:?}"),
+            RenderingError::CircularReference { id: Some(ref id) } => {
+                write!(f, "circular reference in element {id:?}")
+            }
+            RenderingError::CircularReference { id: None } => write!(f, "circular reference"),
+            RenderingError::NotAShape => write!(f, "element is not a basic shape"),
             RenderingError::OutOfMemory(ref s) => write!(f, "out of memory: {s}"),
             RenderingError::Cancelled => write!(f, "rendering cancelled"),
+            RenderingError::InvalidDpi(x, y) => write!(f, "invalid DPI: {x}, {y}"),
         }
     }
 }
@@ -118,7 +196,10 @@ impl fmt::Display for RenderingError {
 /// [`SvgHandle`].
 pub struct Loader {
     unlimited_size: bool,
-    keep_image_data: bool,
+    keep_image_data: KeepImageData,
+    whitespace_mode: WhitespaceMode,
+    resource_callback: Option<ResourceCallback>,
+    base_url: Option<Url>,
     session: Session,
 }
 
@@ -146,7 +227,10 @@ impl Loader {
     pub fn new() -> Self {
         Self {
             unlimited_size: false,
-            keep_image_data: false,
+            keep_image_data: KeepImageData::None,
+            whitespace_mode: WhitespaceMode::Default,
+            resource_callback: None,
+            base_url: None,
             session: Session::default(),
         }
     }
@@ -159,7 +243,10 @@ impl Loader {
     pub fn new_with_session(session: Session) -> Self {
         Self {
             unlimited_size: false,
-            keep_image_data: false,
+            keep_image_data: KeepImageData::None,
+            whitespace_mode: WhitespaceMode::Default,
+            resource_callback: None,
+            base_url: None,
             session,
         }
     }
@@ -197,7 +284,9 @@ impl Loader {
     /// images into the PDF.
     ///
     /// Set this to `true` if you are using a Cairo PDF context, or any other type
-    /// of context which allows embedding compressed images.
+    /// of context which allows embedding compressed images.  This keeps the data for
+    /// all the raster images; use [`keep_image_data_for`](#method.keep_image_data_for) to
+    /// only keep it for some formats.
     ///
     /// # Example:
     ///
@@ -221,7 +310,205 @@ impl Loader {
     /// # Ok::<(), rsvg::RenderingError>(())
     /// ```
     pub fn keep_image_data(mut self, keep: bool) -> Self {
-        self.keep_image_data = keep;
+        self.keep_image_data = if keep {
+            KeepImageData::All
+        } else {
+            KeepImageData::None
+        };
+        self
+    }
+
+    /// Keeps the original compressed data only for images with the given MIME types.
+    ///
+    /// This works like [`keep_image_data`](#method.keep_image_data), but only for images
+    /// whose MIME type is in `content_types`, like `"image/jpeg"` or `"image/png"`.  Other
+    /// images only keep their decoded pixels.  Passing an empty list is the same as
+    /// `keep_image_data(false)`.
+    ///
+    /// Cairo only uses the kept data when the target surface supports embedding that
+    /// format directly: for example, PDF and PostScript surfaces can embed JPEG data,
+    /// and SVG surfaces can embed both JPEG and PNG data.  For formats that the surface
+    /// does not support, Cairo uses the decoded pixels anyway, and keeping the data just
+    /// uses more memory.
+    ///
+    /// The MIME type of an image comes from the `data:` URL that contains it, or is
+    /// guessed from the file that it was loaded from.
+    ///
+    /// After rendering, [`SvgHandle::has_kept_image_data`] tells whether any of the
+    /// document's images kept their data.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let svg_handle = rsvg::Loader::new()
+    ///     .keep_image_data_for(&["image/jpeg"])
+    ///     .read_path("example.svg")
+    ///     .unwrap();
+    /// ```
+    pub fn keep_image_data_for(mut self, content_types: &[&str]) -> Self {
+        self.keep_image_data = if content_types.is_empty() {
+            KeepImageData::None
+        } else {
+            KeepImageData::Only(content_types.iter().map(|t| t.to_string()).collect())
+        };
+        self
+    }
+
+    /// Controls how whitespace in text elements is handled for the whole document.
+    ///
+    /// SVG documents use the `xml:space` attribute to specify whether whitespace in text
+    /// should be collapsed (the default) or preserved.  Different tools that generate SVG
+    /// files have different expectations about this; with this function you can change
+    /// the document-wide default.
+    ///
+    /// * [`WhitespaceMode::Default`] follows the SVG specification.  This is the default.
+    ///
+    /// * [`WhitespaceMode::Preserve`] treats all text as if the toplevel `<svg>` element had
+    ///   `xml:space="preserve"`.  Elements with an explicit `xml:space` attribute still
+    ///   use its value.
+    ///
+    /// * [`WhitespaceMode::ForcePreserve`] preserves whitespace in all text, ignoring any
+    ///   `xml:space` attributes in the document.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let svg_handle = rsvg::Loader::new()
+    ///     .with_whitespace_mode(rsvg::WhitespaceMode::Preserve)
+    ///     .read_path("example.svg")
+    ///     .unwrap();
+    /// ```
+    pub fn with_whitespace_mode(mut self, mode: WhitespaceMode) -> Self {
+        self.whitespace_mode = mode;
+        self
+    }
+
+    /// Sets a callback to supply the contents of files referenced from the SVG.
+    ///
+    /// SVG documents can reference other files, for example with `<image href="...">`
+    /// or XInclude.  Normally librsvg loads them by following the rules described in
+    /// [security and locations of referenced
+    /// files][crate#security-and-locations-of-referenced-files].  An application which
+    /// keeps those files somewhere else, for example in a database, can use this
+    /// function to supply their contents instead.
+    ///
+    /// The callback gets called with the URL of each referenced file, already resolved
+    /// against the base URL of the document.  It can return one of these:
+    ///
+    /// * `Ok(bytes)` to use `bytes` as the contents of the file.
+    ///
+    /// * [`ResolveError::NotHandled`] to let librsvg load the file with its normal rules.
+    ///
+    /// * [`ResolveError::Denied`] to not load the file at all.
+    ///
+    /// `data:` URLs are always decoded by librsvg itself, and are not passed to the
+    /// callback.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let svg_handle = rsvg::Loader::new()
+    ///     .with_resource_callback(Box::new(|url| {
+    ///         if url.ends_with(".png") {
+    ///             Err(rsvg::ResolveError::Denied)
+    ///         } else {
+    ///             Err(rsvg::ResolveError::NotHandled)
+    ///         }
+    ///     }))
+    ///     .read_path("example.svg")
+    ///     .unwrap();
+    /// ```
+    pub fn with_resource_callback(mut self, callback: ResourceCallback) -> Self {
+        self.resource_callback = Some(callback);
+        self
+    }
+
+    /// Sets the base URL for resolving references to external files.
+    ///
+    /// Normally the [base
+    /// URL][crate#the-base-file-and-resolving-references-to-external-files] comes from
+    /// the file that the SVG document is read from, or from the `base_file` passed to
+    /// [`read_stream`].  If the document does not come from a file, for example if it is
+    /// stored in a database, you can use this function to set the URL it logically
+    /// belongs to.  Relative references in the document are resolved against this URL,
+    /// and then the usual security rules apply.
+    ///
+    /// The base URL set here takes precedence: when it is set, the `base_file` passed to
+    /// any of the loading functions is not used for resolving references.
+    ///
+    /// Returns [`LoadingError::BadUrl`] if `url` cannot be parsed as an absolute URL.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let data = std::fs::read("example.svg").unwrap();
+    ///
+    /// let svg_handle = rsvg::Loader::new()
+    ///     .with_base_url("https://example.com/drawings/example.svg")
+    ///     .unwrap()
+    ///     .read_data(&data, None)
+    ///     .unwrap();
+    /// ```
+    ///
+    /// [`read_stream`]: #method.read_stream
+    pub fn with_base_url(mut self, url: &str) -> Result<Self, LoadingError> {
+        self.base_url = Some(Url::parse(url).map_err(|_| LoadingError::BadUrl)?);
+        Ok(self)
+    }
+
+    /// Collects the recoverable problems found while loading the document.
+    ///
+    /// SVG is lenient with invalid attribute values: librsvg ignores them and uses the
+    /// attribute's default value instead, so the document still loads.  Normally these
+    /// problems are only visible by setting the `RSVG_LOG=1` environment variable.  With
+    /// this option, they are also available from [`SvgHandle::warnings`], which is useful
+    /// for authoring tools that want to point out mistakes in a document.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let svg_handle = rsvg::Loader::new()
+    ///     .collect_warnings()
+    ///     .read_path("example.svg")
+    ///     .unwrap();
+    ///
+    /// for warning in svg_handle.warnings() {
+    ///     println!("{warning}");
+    /// }
+    /// ```
+    pub fn collect_warnings(mut self) -> Self {
+        self.session = self.session.collecting_warnings();
+        self
+    }
+
+    /// Fails loading at the first feature that librsvg does not support.
+    ///
+    /// Normally librsvg skips what it does not understand: unknown elements are not
+    /// rendered, and invalid attribute values or CSS declarations are ignored, as the SVG
+    /// specification asks.  In strict mode, the first of these problems makes loading fail
+    /// with [`LoadingError::Unsupported`] instead.  This is the same set of problems that
+    /// [`collect_warnings`](#method.collect_warnings) reports, plus unknown elements and CSS
+    /// declarations.
+    ///
+    /// Loading stops as soon as an unsupported element, attribute value, or declaration in
+    /// a stylesheet is found.  The declarations in `style` attributes are only parsed
+    /// once the whole document has been read, so for them the error is returned at the
+    /// end of loading.
+    ///
+    /// This is meant for test suites and conformance checks, to make sure that a set of
+    /// documents only uses what librsvg supports.  Do not use it to render SVG files from
+    /// the wild, since many valid documents have content that librsvg ignores on purpose.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// match rsvg::Loader::new().strict().read_path("example.svg") {
+    ///     Ok(_) => println!("example.svg only uses supported features"),
+    ///     Err(e) => println!("{e}"),
+    /// }
+    /// ```
+    pub fn strict(mut self) -> Self {
+        self.session = self.session.strict();
         self
     }
 
@@ -234,6 +521,7 @@ impl Loader {
     ///     .read_path("example.svg")
     ///     .unwrap();
     /// ```
+    #[cfg(feature = "gio")]
     pub fn read_path<P: AsRef<Path>>(self, path: P) -> Result<SvgHandle, LoadingError> {
         let file = gio::File::for_path(path);
         self.read_file(&file, None::<&Cancellable>)
@@ -249,6 +537,7 @@ impl Loader {
     ///     .read_file(&gio::File::for_path("example.svg"), None::<&gio::Cancellable>)
     ///     .unwrap();
     /// ```
+    #[cfg(feature = "gio")]
     pub fn read_file<F: IsA<gio::File>, P: IsA<Cancellable>>(
         self,
         file: &F,
@@ -266,7 +555,8 @@ impl Loader {
     /// Reading an SVG document may involve resolving relative URLs if the
     /// SVG references things like raster images, or other SVG files.
     /// In this case, pass the `base_file` that correspondds to the
-    /// URL where this SVG got loaded from.
+    /// URL where this SVG got loaded from.  If a base URL was set with
+    /// [`with_base_url`](#method.with_base_url), it is used instead of the `base_file`.
     ///
     /// The `cancellable` can be used to cancel loading from another thread.
     ///
@@ -283,36 +573,223 @@ impl Loader {
     ///     .read_stream(&stream, Some(&file), None::<&gio::Cancellable>)
     ///     .unwrap();
     /// ```
+    #[cfg(feature = "gio")]
     pub fn read_stream<S: IsA<gio::InputStream>, F: IsA<gio::File>, P: IsA<Cancellable>>(
-        self,
+        mut self,
         stream: &S,
         base_file: Option<&F>,
         cancellable: Option<&P>,
     ) -> Result<SvgHandle, LoadingError> {
         let base_file = base_file.map(|f| f.as_ref());
 
-        let base_url = if let Some(base_file) = base_file {
+        let base_url = if let Some(base_url) = self.base_url.take() {
+            Some(base_url)
+        } else if let Some(base_file) = base_file {
             Some(url_from_file(base_file)?)
         } else {
             None
         };
 
-        let load_options = LoadOptions::new(UrlResolver::new(base_url))
+        let load_options = self.load_options(UrlResolver::new(base_url));
+
+        let document = Document::load_from_stream(
+            self.session.clone(),
+            Arc::new(load_options),
+            stream.as_ref(),
+            cancellable.map(|c| c.as_ref()),
+        )?;
+
+        Ok(self.into_handle(document))
+    }
+
+    /// Reads an SVG document from a byte slice in memory.
+    ///
+    /// This is a convenience wrapper around [`read_stream`](#method.read_stream) for the
+    /// common case where the SVG data is already in memory.  The data is copied, so the
+    /// `data` slice does not need to outlive the returned [`SvgHandle`].
+    ///
+    /// The `base_file`, if it is not `None`, is used to extract the
+    /// [base URL][crate#the-base-file-and-resolving-references-to-external-files] for
+    /// resolving references to external files, just like in `read_stream`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let data = std::fs::read("example.svg").unwrap();
+    ///
+    /// let svg_handle = rsvg::Loader::new()
+    ///     .read_data(&data, Some(&gio::File::for_path("example.svg")))
+    ///     .unwrap();
+    /// ```
+    #[cfg(feature = "gio")]
+    pub fn read_data(
+        self,
+        data: &[u8],
+        base_file: Option<&gio::File>,
+    ) -> Result<SvgHandle, LoadingError> {
+        let bytes = glib::Bytes::from(data);
+        let stream = gio::MemoryInputStream::from_bytes(&bytes);
+
+        self.read_stream(&stream, base_file, None::<&Cancellable>)
+    }
+
+    /// Reads an SVG document from a byte slice in memory, without using gio streams.
+    ///
+    /// Unlike [`read_data`](#method.read_data), this hands the data directly to the XML
+    /// parser instead of wrapping it in a `gio::InputStream`.  This is meant for
+    /// environments where gio's I/O machinery is not available or not wanted, like when
+    /// all the data is produced in memory.
+    ///
+    /// Referenced files are never loaded from the filesystem, even if a `file:` base URL was
+    /// set with [`with_base_url`](#method.with_base_url); an `<image>` or `<use>` that
+    /// references a file will just not be rendered.  Instead, you can supply the data for
+    /// those references with [`with_resource_callback`](#method.with_resource_callback), which
+    /// gets called with URLs resolved against the base URL.  References to `data:` URLs work
+    /// as usual.
+    ///
+    /// Loading cannot be cancelled, as there is no I/O involved.  Compressed (svgz) data
+    /// is decompressed through gio, so it is only supported with the `gio` feature.
+    ///
+    /// This is the only way to load a document when librsvg is built without the `gio`
+    /// feature.  In that case, `data:` URLs and the resource callback are the only way to
+    /// get data for referenced resources.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let data = std::fs::read("example.svg").unwrap();
+    ///
+    /// let svg_handle = rsvg::Loader::new()
+    ///     .read_bytes(&data)
+    ///     .unwrap();
+    /// ```
+    pub fn read_bytes(mut self, data: &[u8]) -> Result<SvgHandle, LoadingError> {
+        let base_url = self.base_url.take();
+        let load_options = self.load_options(UrlResolver::new(base_url).without_file_access());
+
+        let document =
+            Document::load_from_data(self.session.clone(), Arc::new(load_options), data)?;
+
+        Ok(self.into_handle(document))
+    }
+
+    /// Creates a [`StreamingLoader`] to load an SVG document from data that arrives in
+    /// chunks.
+    ///
+    /// The options set on this `Loader`, like [`with_unlimited_size`] or
+    /// [`keep_image_data`], are used when loading the document.
+    ///
+    /// The `base_file`, if it is not `None`, is used to extract the
+    /// [base URL][crate#the-base-file-and-resolving-references-to-external-files] for
+    /// resolving references to external files, just like in [`read_stream`].  It must be
+    /// provided here, before any data; if it is `None`, the document will not be able
+    /// to reference external files.
+    ///
+    /// [`with_unlimited_size`]: #method.with_unlimited_size
+    /// [`keep_image_data`]: #method.keep_image_data
+    /// [`read_stream`]: #method.read_stream
+    #[cfg(feature = "gio")]
+    pub fn streaming(mut self, base_file: Option<&gio::File>) -> StreamingLoader {
+        let base_url = if let Some(base_url) = self.base_url.take() {
+            Ok(Some(base_url))
+        } else {
+            base_file.map(url_from_file).transpose()
+        };
+
+        let push_loader = base_url.and_then(|base_url| {
+            let load_options = self.load_options(UrlResolver::new(base_url));
+            Document::push_loader(self.session.clone(), Arc::new(load_options))
+        });
+
+        StreamingLoader {
+            loader: self,
+            push_loader,
+        }
+    }
+
+    fn load_options(&mut self, url_resolver: UrlResolver) -> LoadOptions {
+        let url_resolver = url_resolver.with_resource_callback(self.resource_callback.take());
+
+        LoadOptions::new(url_resolver)
             .with_unlimited_size(self.unlimited_size)
-            .keep_image_data(self.keep_image_data);
-
-        Ok(SvgHandle {
-            document: Document::load_from_stream(
-                self.session.clone(),
-                Arc::new(load_options),
-                stream.as_ref(),
-                cancellable.map(|c| c.as_ref()),
-            )?,
+            .keep_image_data(self.keep_image_data.clone())
+            .with_whitespace_mode(self.whitespace_mode)
+    }
+
+    fn into_handle(self, document: Document) -> SvgHandle {
+        SvgHandle {
+            document,
+            user_stylesheets: Vec::new(),
+            warnings: self.session.take_warnings(),
             session: self.session,
-        })
+        }
+    }
+}
+
+/// Loads an SVG document from data that arrives in chunks.
+///
+/// This is useful when the SVG data comes from somewhere like a network download, and
+/// it is more convenient to hand it to librsvg as it arrives than to wrap it in a
+/// `gio::InputStream`.  You can create a `StreamingLoader` with [`Loader::streaming`],
+/// feed it data with [`push_bytes`], and get an [`SvgHandle`] with [`finish`].
+///
+/// Each chunk is parsed as soon as it is pushed, so the data does not need to be kept
+/// around.  Compressed data (i.e. an SVGZ file) is decompressed as it arrives.  If
+/// there is an error in the data, the rest of the chunks are ignored and [`finish`]
+/// returns the error.
+///
+/// # Example
+///
+/// ```
+/// use std::io::Read;
+///
+/// let mut file = std::fs::File::open("example.svg").unwrap();
+/// let base_file = gio::File::for_path("example.svg");
+///
+/// let mut loader = rsvg::Loader::new().streaming(Some(&base_file));
+///
+/// let mut chunk = [0u8; 1024];
+/// loop {
+///     let n = file.read(&mut chunk).unwrap();
+///     if n == 0 {
+///         break;
+///     }
+///     loader.push_bytes(&chunk[..n]);
+/// }
+///
+/// let svg_handle = loader.finish().unwrap();
+/// ```
+///
+/// [`push_bytes`]: #method.push_bytes
+/// [`finish`]: #method.finish
+#[cfg(feature = "gio")]
+pub struct StreamingLoader {
+    loader: Loader,
+
+    // Becomes the first error from loading, after which the rest of the data is ignored.
+    push_loader: Result<XmlPushLoader, LoadingError>,
+}
+
+#[cfg(feature = "gio")]
+impl StreamingLoader {
+    /// Parses a chunk of SVG data.
+    pub fn push_bytes(&mut self, data: &[u8]) {
+        if let Ok(ref mut push_loader) = self.push_loader {
+            if let Err(e) = push_loader.push(data) {
+                self.push_loader = Err(e);
+            }
+        }
+    }
+
+    /// Finishes parsing the data that was pushed and returns the loaded document.
+    pub fn finish(self) -> Result<SvgHandle, LoadingError> {
+        let document = self.push_loader?.finish()?;
+
+        Ok(self.loader.into_handle(document))
     }
 }
 
+#[cfg(feature = "gio")]
 fn url_from_file(file: &gio::File) -> Result<Url, LoadingError> {
     Url::parse(&file.uri()).map_err(|_| LoadingError::BadUrl)
 }
@@ -324,6 +801,40 @@ fn url_from_file(file: &gio::File) -> Result<Url, LoadingError> {
 pub struct SvgHandle {
     session: Session,
     pub(crate) document: Document,
+    user_stylesheets: Vec<Stylesheet>,
+    warnings: Vec<LoadWarning>,
+}
+
+/// Loads an SVG document from bytes with the default [`Loader`] options.
+///
+/// This is the same as `Loader::new().read_bytes(data)`.  Since there is no base
+/// file, the document cannot reference external files; only `data:` URLs work.
+///
+/// # Example
+///
+/// ```
+/// let data = std::fs::read("example.svg").unwrap();
+///
+/// let svg_handle = rsvg::SvgHandle::try_from(data.as_slice()).unwrap();
+/// ```
+impl TryFrom<&[u8]> for SvgHandle {
+    type Error = LoadingError;
+
+    fn try_from(data: &[u8]) -> Result<SvgHandle, LoadingError> {
+        Loader::new().read_bytes(data)
+    }
+}
+
+/// Loads an SVG document from a string with the default [`Loader`] options.
+///
+/// This works like the implementation for `&[u8]`, so the document cannot reference
+/// external files either.
+impl TryFrom<&str> for SvgHandle {
+    type Error = LoadingError;
+
+    fn try_from(data: &str) -> Result<SvgHandle, LoadingError> {
+        SvgHandle::try_from(data.as_bytes())
+    }
 }
 
 // Public API goes here
@@ -347,52 +858,304 @@ impl SvgHandle {
         }
     }
 
-    /// Sets a CSS stylesheet to use for an SVG document.
+    /// Returns the text of the `<title>` of the document or of an element.
     ///
-    /// During the CSS cascade, the specified stylesheet will be used
-    /// with a "User" [origin].
+    /// With `id` set to `None`, this looks at the `<title>` child of the toplevel `<svg>`
+    /// element, which is the title of the whole document.  Otherwise, `id` must be a
+    /// plain fragment identifier like `#foo`, and this looks at the `<title>` child of
+    /// that element.  Only the first `<title>` child is considered.
     ///
-    /// Note that `@import` rules will not be resolved, except for `data:` URLs.
+    /// The text is meant for things like alt-text and tooltips, so whitespace is
+    /// normalized: leading and trailing whitespace is removed, and any other run of
+    /// whitespace, including newlines, is replaced by a single space.  Text in child
+    /// elements of the `<title>` is included.
     ///
-    /// [origin]: https://drafts.csswg.org/css-cascade-3/#cascading-origins
-    pub fn set_stylesheet(&mut self, css: &str) -> Result<(), LoadingError> {
-        let stylesheet = Stylesheet::from_data(
-            css,
-            &UrlResolver::new(None),
-            Origin::User,
-            self.session.clone(),
-        )?;
-        self.document.cascade(&[stylesheet], &self.session);
-        Ok(())
+    /// Returns `Ok(None)` if the element does not exist or if it has no `<title>`.  The
+    /// `Err()` case indicates an incorrectly-formatted `id` argument.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let svg_handle = rsvg::Loader::new()
+    ///     .read_path("example.svg")
+    ///     .unwrap();
+    ///
+    /// if let Some(title) = svg_handle.element_title(None).unwrap() {
+    ///     println!("the document is called {title}");
+    /// }
+    /// ```
+    pub fn element_title(&self, id: Option<&str>) -> Result<Option<String>, RenderingError> {
+        Ok(self.lookup_optional_node(id)?.and_then(|n| title_text(&n)))
     }
-}
 
-// Private methods go here
-impl SvgHandle {
-    fn get_node_id_or_root(&self, id: Option<&str>) -> Result<Option<NodeId>, RenderingError> {
-        match id {
-            None => Ok(None),
-            Some(s) => Ok(Some(self.get_node_id(s)?)),
-        }
+    /// Returns the text of the `<desc>` of the document or of an element.
+    ///
+    /// This works like [`element_title`](#method.element_title), but for the `<desc>`
+    /// element, which usually contains a longer description than `<title>`.
+    pub fn element_desc(&self, id: Option<&str>) -> Result<Option<String>, RenderingError> {
+        Ok(self.lookup_optional_node(id)?.and_then(|n| desc_text(&n)))
     }
 
-    fn get_node_id(&self, id: &str) -> Result<NodeId, RenderingError> {
-        let node_id = NodeId::parse(id).map_err(|_| RenderingError::InvalidId(id.to_string()))?;
+    /// Returns the geometry of a shape element as SVG path data.
+    ///
+    /// The `id` must be a plain fragment identifier like `#foo`, and the element must be
+    /// one of `<path>`, `<rect>`, `<circle>`, `<ellipse>`, `<line>`, `<polyline>`, or
+    /// `<polygon>`; otherwise this returns [`RenderingError::NotAShape`].
+    ///
+    /// The result is normalized: all commands are absolute, and each segment has its own
+    /// command letter.  Circles and ellipses are approximated with four cubic Bézier
+    /// curves, the rounded corners of a `<rect>` are elliptical arcs, and the arcs in a
+    /// `<path>`'s data are kept as `A` commands.  A shape that does not generate any
+    /// geometry, like a `<rect>` with zero width, produces an empty string.
+    ///
+    /// The coordinates are in the element's user space; its `transform` is not applied.
+    /// Percentage lengths refer to the toplevel `<svg>` element's `viewBox`, or to its
+    /// width and height if there is no `viewBox`.  Physical units like `mm` are converted
+    /// at 96 DPI.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let svg_handle = rsvg::Loader::new()
+    ///     .read_path("example.svg")
+    ///     .unwrap();
+    ///
+    /// for id in svg_handle.element_ids() {
+    ///     if let Ok(path_data) = svg_handle.element_path(&id) {
+    ///         println!("{id}: {path_data}");
+    ///     }
+    /// }
+    /// ```
+    pub fn element_path(&self, id: &str) -> Result<String, RenderingError> {
+        let node_id = self.get_node_id(id)?;
+        let node = self.lookup_node(&node_id)?;
 
-        // The public APIs to get geometries of individual elements, or to render
-        // them, should only allow referencing elements within the main handle's
-        // SVG file; that is, only plain "#foo" fragment IDs are allowed here.
-        // Otherwise, a calling program could request "another-file#foo" and cause
-        // another-file to be loaded, even if it is not part of the set of
-        // resources that the main SVG actually references.  In the future we may
-        // relax this requirement to allow lookups within that set, but not to
-        // other random files.
-        match node_id {
-            NodeId::Internal(_) => Ok(node_id),
-            NodeId::External(_, _) => {
-                rsvg_log!(
-                    self.session,
-                    "the public API is not allowed to look up external references: {}",
+        self.document.ensure_static_cascade();
+
+        let dpi = Dpi::new(DEFAULT_DPI_X, DEFAULT_DPI_Y);
+        let viewport = self.toplevel_viewport(dpi);
+
+        let cascaded = CascadedValues::new_from_node(&node);
+        let values = cascaded.get();
+        let params = NormalizeParams::new(values, &viewport);
+
+        let path = shapes::element_path(&node.borrow_element(), &params, values);
+
+        path.map(|path| path.to_path_data())
+            .ok_or(RenderingError::NotAShape)
+    }
+
+    /// Returns the computed value of a CSS property for an element, serialized as CSS.
+    ///
+    /// The `id` must be a plain fragment identifier like `#foo`.  The `property` is a
+    /// property name like `"fill"` or `"stroke-width"`; if librsvg does not support that
+    /// property, or if it is a shorthand like `font`, this returns `Ok(None)`.
+    ///
+    /// The value is what rendering would use after the cascade, so it takes into account
+    /// stylesheets, presentation attributes, and values inherited from ancestors.  It is
+    /// serialized in a normalized form: for example, colors come out as `rgb(...)` or
+    /// `rgba(...)`, and lengths always have a unit.  Relative lengths like percentages or
+    /// `em` are not resolved to pixels.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let svg_handle = rsvg::Loader::new()
+    ///     .read_path("example.svg")
+    ///     .unwrap();
+    ///
+    /// for id in svg_handle.element_ids() {
+    ///     if let Some(fill) = svg_handle.computed_value(&id, "fill").unwrap() {
+    ///         println!("{id}: fill is {fill}");
+    ///     }
+    /// }
+    /// ```
+    pub fn computed_value(
+        &self,
+        id: &str,
+        property: &str,
+    ) -> Result<Option<String>, RenderingError> {
+        let node_id = self.get_node_id(id)?;
+        let node = self.lookup_node(&node_id)?;
+
+        self.document.ensure_static_cascade();
+        let cascaded = CascadedValues::new_from_node(&node);
+
+        Ok(cascaded.get().property_to_css_string(property))
+    }
+
+    /// Returns the `id` of every element in the SVG, in document order.
+    ///
+    /// Each `id` has a leading `#` character, so it can be passed directly to
+    /// [`CairoRenderer::render_element`] or
+    /// [`has_element_with_id`](#method.has_element_with_id).  Elements inside `<defs>`
+    /// are included.  If several elements have the same `id`, only the first one can be
+    /// referenced, and the `id` is returned only once.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let svg_handle = rsvg::Loader::new()
+    ///     .read_path("example.svg")
+    ///     .unwrap();
+    ///
+    /// for id in svg_handle.element_ids() {
+    ///     assert!(svg_handle.has_element_with_id(&id).unwrap());
+    /// }
+    /// ```
+    pub fn element_ids(&self) -> Vec<String> {
+        self.document
+            .element_ids()
+            .into_iter()
+            .map(|id| format!("#{id}"))
+            .collect()
+    }
+
+    /// Returns whether any raster image in the document kept its original compressed data.
+    ///
+    /// This is only `true` if you asked for the data to be kept with
+    /// [`Loader::keep_image_data`] or [`Loader::keep_image_data_for`], and the document
+    /// references images in those formats.
+    ///
+    /// Referenced images are loaded on demand, the first time they are needed, so this only
+    /// takes into account the images that have been rendered or measured so far.  Call it
+    /// after rendering.
+    pub fn has_kept_image_data(&self) -> bool {
+        self.document.has_kept_image_data()
+    }
+
+    /// Returns the URLs of the external files that the document references.
+    ///
+    /// This includes the `href` of `<image>`, `<feImage>`, and `xi:include` elements and of
+    /// `xml-stylesheet` processing instructions, plus references to elements in other
+    /// documents from `<use>` and from `url(...)` in properties like `fill` or `filter`.
+    /// References to elements within the same document are not included.
+    ///
+    /// Each URL comes with whether it is allowed by the rules in "Security and locations of
+    /// referenced files" in the [crate documentation](index.html#security-and-locations-of-referenced-files).
+    /// This only inspects the document; it does not load anything, and it does not call the
+    /// resource callback from [`Loader::with_resource_callback`], so URLs that the callback
+    /// would supply or deny are reported according to those rules alone.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// let svg_handle = rsvg::Loader::new()
+    ///     .read_path("example.svg")
+    ///     .unwrap();
+    ///
+    /// for url in svg_handle.referenced_urls() {
+    ///     if !url.allowed {
+    ///         println!("{} will not be loaded", url.href);
+    ///     }
+    /// }
+    /// ```
+    pub fn referenced_urls(&self) -> Vec<ReferencedUrl> {
+        self.document.referenced_urls()
+    }
+
+    /// Returns the recoverable problems found while loading the document.
+    ///
+    /// This is empty unless the document was loaded with [`Loader::collect_warnings`].
+    pub fn warnings(&self) -> &[LoadWarning] {
+        &self.warnings
+    }
+
+    /// Sets a CSS stylesheet to use for an SVG document.
+    ///
+    /// During the CSS cascade, the specified stylesheet will be used
+    /// with a "User" [origin].
+    ///
+    /// This is a shortcut for [`add_stylesheet`] with [`StylesheetOrigin::User`].
+    ///
+    /// Note that `@import` rules will not be resolved, except for `data:` URLs.
+    ///
+    /// [origin]: https://drafts.csswg.org/css-cascade-3/#cascading-origins
+    /// [`add_stylesheet`]: #method.add_stylesheet
+    pub fn set_stylesheet(&mut self, css: &str) -> Result<(), LoadingError> {
+        self.add_stylesheet(css, StylesheetOrigin::User)
+    }
+
+    /// Adds a CSS stylesheet with the specified `origin` to an SVG document.
+    ///
+    /// The stylesheet is appended to the ones added before, and all of them are used
+    /// during the CSS cascade.  See [`StylesheetOrigin`] for how their rules compete
+    /// with each other and with the document's own styles; among rules with the same
+    /// origin and specificity, the ones from later stylesheets win.
+    ///
+    /// Note that `@import` rules will not be resolved, except for `data:` URLs.
+    ///
+    /// # Example:
+    ///
+    /// ```
+    /// use rsvg::StylesheetOrigin;
+    ///
+    /// let mut svg_handle = rsvg::Loader::new()
+    ///     .read_path("example.svg")
+    ///     .unwrap();
+    ///
+    /// svg_handle.add_stylesheet("rect { fill: blue; }", StylesheetOrigin::UserAgent)?;
+    /// svg_handle.add_stylesheet("circle { fill: red; }", StylesheetOrigin::User)?;
+    /// # Ok::<(), rsvg::LoadingError>(())
+    /// ```
+    pub fn add_stylesheet(
+        &mut self,
+        css: &str,
+        origin: StylesheetOrigin,
+    ) -> Result<(), LoadingError> {
+        let stylesheet = Stylesheet::from_data(
+            css,
+            &UrlResolver::new(None),
+            Origin::from(origin),
+            self.session.clone(),
+        )?;
+        self.user_stylesheets.push(stylesheet);
+        self.document.cascade(&self.user_stylesheets, &self.session);
+        Ok(())
+    }
+}
+
+// Private methods go here
+impl SvgHandle {
+    /// Looks up the element with the given `id`, or the root element for `None`.
+    ///
+    /// A missing element is `Ok(None)`; only invalid ids are an error.
+    fn lookup_optional_node(&self, id: Option<&str>) -> Result<Option<Node>, RenderingError> {
+        let node_id = self.get_node_id_or_root(id)?;
+
+        match self.get_node_or_root(&node_id) {
+            Ok(node) => Ok(Some(node)),
+
+            Err(InternalRenderingError::IdNotFound) => Ok(None),
+
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn get_node_id_or_root(&self, id: Option<&str>) -> Result<Option<NodeId>, RenderingError> {
+        match id {
+            None => Ok(None),
+            Some(s) => Ok(Some(self.get_node_id(s)?)),
+        }
+    }
+
+    fn get_node_id(&self, id: &str) -> Result<NodeId, RenderingError> {
+        let node_id = NodeId::parse(id).map_err(|_| RenderingError::InvalidId(id.to_string()))?;
+
+        // The public APIs to get geometries of individual elements, or to render
+        // them, should only allow referencing elements within the main handle's
+        // SVG file; that is, only plain "#foo" fragment IDs are allowed here.
+        // Otherwise, a calling program could request "another-file#foo" and cause
+        // another-file to be loaded, even if it is not part of the set of
+        // resources that the main SVG actually references.  In the future we may
+        // relax this requirement to allow lookups within that set, but not to
+        // other random files.
+        match node_id {
+            NodeId::Internal(_) => Ok(node_id),
+            NodeId::External(_, _) => {
+                rsvg_log!(
+                    self.session,
+                    "the public API is not allowed to look up external references: {}",
                     node_id
                 );
 
@@ -411,6 +1174,25 @@ impl SvgHandle {
         }
     }
 
+    /// Returns a viewport for the toplevel `<svg>`, to be used outside of rendering.
+    fn toplevel_viewport(&self, dpi: Dpi) -> Viewport {
+        let dimensions = self.document.get_intrinsic_dimensions();
+
+        if let Some(vbox) = dimensions.vbox {
+            Viewport::new(dpi, vbox.width(), vbox.height())
+        } else {
+            let root = self.document.root();
+            let cascaded = CascadedValues::new_from_node(&root);
+            let params = NormalizeParams::new(cascaded.get(), &Viewport::new(dpi, 0.0, 0.0));
+
+            Viewport::new(
+                dpi,
+                dimensions.width.to_user(&params),
+                dimensions.height.to_user(&params),
+            )
+        }
+    }
+
     fn lookup_node(&self, node_id: &NodeId) -> Result<Node, InternalRenderingError> {
         // The public APIs to get geometries of individual elements, or to render
         // them, should only allow referencing elements within the main handle's
@@ -435,16 +1217,271 @@ impl SvgHandle {
 /// Can render an `SvgHandle` to a Cairo context.
 pub struct CairoRenderer<'a> {
     pub(crate) handle: &'a SvgHandle,
-    pub(crate) dpi: Dpi,
+    options: RenderingOptions,
     user_language: UserLanguage,
-    cancellable: Option<gio::Cancellable>,
-    is_testing: bool,
+    cancellable: Option<Cancellable>,
+    render_limits: RenderLimits,
+    animation_time: Option<f64>,
+    current_color: Option<Color>,
+    custom_properties: Option<CustomProperties>,
+    font_map: Option<FontMap>,
+    circular_reference_errors: bool,
 }
 
 // Note that these are different than the C API's default, which is 90.
 const DEFAULT_DPI_X: f64 = 96.0;
 const DEFAULT_DPI_Y: f64 = 96.0;
 
+/// A CSS color value, as returned by [`parse_color`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Color(cssparser::Color);
+
+/// Parses a CSS color value, like `#ff0000`, `rgba(255, 0, 0, 0.5)`, `hsl(0, 100%, 50%)`,
+/// or `red`.
+///
+/// This accepts the same syntax as librsvg uses for colors in SVG documents, including
+/// `currentColor`.  The `none` keyword is not a color, so it yields an error.
+///
+/// ```
+/// let color = rsvg::parse_color("rgba(255, 0, 0, 0.5)").unwrap();
+/// assert_eq!(color.to_rgba(), (255, 0, 0, 0.5));
+/// ```
+pub fn parse_color(s: &str) -> Result<Color, ColorParseError> {
+    <cssparser::Color as Parse>::parse_str(s)
+        .map(Color)
+        .map_err(|e| ColorParseError {
+            message: e.to_string(),
+        })
+}
+
+/// Parses SVG path data, like the value of the `d` attribute of a `<path>` element.
+///
+/// The resulting [`PathBuilder`] has the path's [`commands`](PathBuilder::commands) in
+/// absolute coordinates: relative commands get converted to absolute ones, coordinates
+/// after a moveto turn into implicit linetos, and shorthand commands like `H` or `S` turn
+/// into lines and curves.  Elliptical arcs are kept as [`PathCommand::Arc`].
+///
+/// If the data is malformed, this returns a [`PathParseError`] with the position in the
+/// string where the problem was found.  Note that while rendering, librsvg draws the
+/// commands up to the first error, as the SVG specification requires.
+///
+/// ```
+/// use rsvg::PathCommand;
+///
+/// let path = rsvg::parse_path_data("M 10 10 h 20").unwrap();
+/// assert_eq!(
+///     path.commands(),
+///     &[PathCommand::MoveTo(10.0, 10.0), PathCommand::LineTo(30.0, 10.0)]
+/// );
+///
+/// let error = rsvg::parse_path_data("M 10 10 L 20").unwrap_err();
+/// assert_eq!(error.position, 12);
+/// ```
+pub fn parse_path_data(d: &str) -> Result<PathBuilder, PathParseError> {
+    let mut builder = PathBuilder::default();
+
+    builder.parse(d).map_err(|e| PathParseError {
+        position: e.position,
+        message: e.description().to_string(),
+    })?;
+
+    Ok(builder)
+}
+
+/// Parses a list of transforms, like the value of the `transform` attribute of an element.
+///
+/// The string is first parsed with the syntax of the SVG `transform` attribute, where
+/// numbers don't have units: translations are in user units, and angles are in degrees.
+/// This syntax accepts `matrix(a b c d e f)`, `translate(tx [ty])`, `scale(sx [sy])`,
+/// `rotate(angle [cx cy])`, `skewX(angle)`, and `skewY(angle)`, with arguments separated by
+/// whitespace or commas.
+///
+/// If that fails, the string is parsed with the syntax of the CSS `transform` property,
+/// where arguments must be separated by commas, translations must have `px` units, and
+/// angles may have `deg`, `grad`, `rad`, or `turn` units.  This also accepts the keyword
+/// `none` and the CSS-only functions like `translateX()` or `scaleY()`.
+///
+/// The transforms in the list are composed from left to right, just like when they are
+/// applied to an element; the result is a matrix that you can pass to
+/// [`cairo::Context::transform`].
+///
+/// ```
+/// let matrix = rsvg::parse_transform("translate(10, 20) scale(2)").unwrap();
+/// assert_eq!(matrix.transform_point(1.0, 1.0), (12.0, 22.0));
+///
+/// let matrix = rsvg::parse_transform("translate(10px, 20px) rotate(0.5turn)").unwrap();
+/// assert_eq!(matrix.transform_point(0.0, 0.0), (10.0, 20.0));
+/// ```
+pub fn parse_transform(s: &str) -> Result<cairo::Matrix, TransformParseError> {
+    let transform = match TransformAttribute::parse_str(s) {
+        Ok(attr) => attr.to_transform(),
+
+        Err(e) => TransformProperty::parse_str(s)
+            .map(|prop| prop.to_transform())
+            .map_err(|_| TransformParseError {
+                message: e.to_string(),
+            })?,
+    };
+
+    Ok(cairo::Matrix::new(
+        transform.xx,
+        transform.yx,
+        transform.xy,
+        transform.yy,
+        transform.x0,
+        transform.y0,
+    ))
+}
+
+/// Returns the version of librsvg, like `"2.59.2"`.
+///
+/// ```
+/// assert!(!rsvg::version().is_empty());
+/// ```
+pub fn version() -> &'static str {
+    env!("CARGO_PKG_VERSION")
+}
+
+/// Optional features that were compiled into librsvg, as returned by [`features`].
+///
+/// Some features depend on Cargo features of the `rsvg` crate, and others on whether
+/// cairo was built with support for the corresponding surface types.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Features {
+    /// Whether cairo can create SVG surfaces, and `CairoRenderer::render_to_svg_bytes` is
+    /// available.
+    pub svg_output: bool,
+
+    /// Whether cairo can create PDF surfaces.
+    pub pdf_output: bool,
+
+    /// Whether cairo can create PostScript surfaces.
+    pub ps_output: bool,
+
+    /// Whether `<text>` elements get rendered; this is the `text` Cargo feature.
+    pub text: bool,
+
+    /// Whether AVIF images can be loaded; this is the `avif` Cargo feature.
+    pub avif: bool,
+
+    /// Whether `CairoRenderer::render_to_pixbuf` is available; this is the `pixbuf` Cargo
+    /// feature.
+    pub pixbuf: bool,
+}
+
+/// Returns which optional features were compiled into librsvg.
+///
+/// Programs can use this to tell users what librsvg can do, or to pick an output format:
+///
+/// ```
+/// if rsvg::features().pdf_output {
+///     // offer to export to PDF
+/// }
+/// ```
+pub fn features() -> Features {
+    Features {
+        svg_output: cfg!(system_deps_have_cairo_svg),
+        pdf_output: cfg!(system_deps_have_cairo_pdf),
+        ps_output: cfg!(system_deps_have_cairo_ps),
+        text: cfg!(feature = "text"),
+        avif: cfg!(feature = "avif"),
+        pixbuf: cfg!(feature = "pixbuf"),
+    }
+}
+
+impl Color {
+    /// Returns the red, green, blue, and alpha components of the color.
+    ///
+    /// Red, green, and blue are in the range 0 to 255; alpha is in the range 0.0 to 1.0.
+    ///
+    /// A `currentColor` value has no context from which to take the `color` property, so
+    /// it resolves to opaque black, just like the initial value of `color` in a document.
+    pub fn to_rgba(&self) -> (u8, u8, u8, f32) {
+        let rgba = color_to_rgba(&self.resolved());
+
+        (
+            rgba.red.unwrap_or(0),
+            rgba.green.unwrap_or(0),
+            rgba.blue.unwrap_or(0),
+            rgba.alpha.unwrap_or(0.0),
+        )
+    }
+
+    /// Returns the color with `currentColor` resolved to opaque black, as in [`Color::to_rgba`].
+    fn resolved(&self) -> cssparser::Color {
+        match self.0 {
+            cssparser::Color::CurrentColor => {
+                cssparser::Color::Rgba(cssparser::RGBA::new(Some(0), Some(0), Some(0), Some(1.0)))
+            }
+
+            color => color,
+        }
+    }
+
+    /// Returns whether the color is the `currentColor` keyword.
+    pub fn is_current_color(&self) -> bool {
+        matches!(self.0, cssparser::Color::CurrentColor)
+    }
+}
+
+/// Options for rendering with a [`CairoRenderer`].
+///
+/// You can set all of these at once with [`CairoRenderer::with_options`], instead of
+/// calling each of the individual `with_*` methods.  Since more options may be added in
+/// the future, you cannot create this struct with a struct literal; start from its
+/// [`Default`] value and modify the fields you need.
+///
+/// # Example
+///
+/// ```
+/// use rsvg::{CairoRenderer, Loader, RenderingOptions};
+///
+/// let svg_handle = Loader::new()
+///     .read_path("example.svg")
+///     .unwrap();
+///
+/// let mut options = RenderingOptions::default();
+/// options.dpi_x = 300.0;
+/// options.dpi_y = 300.0;
+/// options.background_color = Some(rsvg::parse_color("white").unwrap());
+///
+/// let renderer = CairoRenderer::new(&svg_handle).with_options(options);
+/// ```
+#[non_exhaustive]
+#[derive(Debug, Clone)]
+pub struct RenderingOptions {
+    /// Horizontal dots-per-inch for resolving physical lengths.  Defaults to 96.
+    pub dpi_x: f64,
+
+    /// Vertical dots-per-inch for resolving physical lengths.  Defaults to 96.
+    pub dpi_y: f64,
+
+    /// Preferred languages for the `<switch>` element.  See
+    /// [`CairoRenderer::with_language`].
+    pub language: Language,
+
+    /// Color to paint the viewport with before rendering.  See
+    /// [`CairoRenderer::with_background_color`].
+    pub background_color: Option<Color>,
+
+    /// Renders in a mode suitable for librsvg's test suite.
+    #[doc(hidden)]
+    pub testing: bool,
+}
+
+impl Default for RenderingOptions {
+    fn default() -> Self {
+        RenderingOptions {
+            dpi_x: DEFAULT_DPI_X,
+            dpi_y: DEFAULT_DPI_Y,
+            language: Language::default(),
+            background_color: None,
+            testing: false,
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 /// Contains the computed values of the `<svg>` element's `width`, `height`, and `viewBox`.
 ///
@@ -469,6 +1506,11 @@ const DEFAULT_DPI_Y: f64 = 96.0;
 /// In this case, the length fields will be set to the corresponding
 /// values with [`LengthUnit::Px`] units, and the `vbox` field will be
 /// set to to `None`.
+///
+/// With the `serde` feature, this can be serialized and deserialized.  The `vbox` gets
+/// represented as an object with `x`, `y`, `width`, and `height` fields, or as a null
+/// value if there is no `viewBox`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct IntrinsicDimensions {
     /// Computed value of the `width` property of the `<svg>`.
     pub width: Length,
@@ -477,9 +1519,114 @@ pub struct IntrinsicDimensions {
     pub height: Length,
 
     /// `viewBox` attribute of the `<svg>`, if present.
+    #[cfg_attr(feature = "serde", serde(with = "serde_vbox"))]
     pub vbox: Option<cairo::Rectangle>,
 }
 
+/// Serializes the `Option<cairo::Rectangle>` in [`IntrinsicDimensions`], since
+/// `cairo::Rectangle` does not implement the serde traits.
+#[cfg(feature = "serde")]
+mod serde_vbox {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct Rectangle {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    }
+
+    pub fn serialize<S: Serializer>(
+        vbox: &Option<cairo::Rectangle>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        vbox.map(|r| Rectangle {
+            x: r.x(),
+            y: r.y(),
+            width: r.width(),
+            height: r.height(),
+        })
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<cairo::Rectangle>, D::Error> {
+        let vbox = Option::<Rectangle>::deserialize(deserializer)?;
+
+        Ok(vbox.map(|r| cairo::Rectangle::new(r.x, r.y, r.width, r.height)))
+    }
+}
+
+impl IntrinsicDimensions {
+    /// Computes the intrinsic aspect ratio of the document, as `width / height`.
+    ///
+    /// The rules are the same as for sizing SVG documents in a web page:
+    ///
+    /// * If `width` and `height` are both non-percentage lengths whose ratio does not
+    ///   depend on the DPI or the font size (e.g. both are in pixels, both are in
+    ///   physical units like `cm` and `in`, or both are in `em`), the aspect ratio
+    ///   comes from them.
+    ///
+    /// * Otherwise, if there is a `viewBox`, the aspect ratio comes from its width and
+    ///   height.  This is the common case of an SVG with `width="100%"` and a `viewBox`.
+    ///
+    /// * Otherwise, the document does not have an intrinsic aspect ratio, and this
+    ///   function returns `None`.  This also happens if the dimensions used are zero.
+    pub fn aspect_ratio(&self) -> Option<f64> {
+        let from_lengths = if self.width.unit == self.height.unit {
+            if self.width.unit == LengthUnit::Percent {
+                None
+            } else {
+                Some((self.width.length, self.height.length))
+            }
+        } else {
+            match (
+                length_in_inches(&self.width),
+                length_in_inches(&self.height),
+            ) {
+                (Some(w), Some(h)) => Some((w, h)),
+                _ => None,
+            }
+        };
+
+        let (width, height) =
+            from_lengths.or_else(|| self.vbox.map(|vbox| (vbox.width(), vbox.height())))?;
+
+        if width > 0.0 && height > 0.0 {
+            Some(width / height)
+        } else {
+            None
+        }
+    }
+}
+
+/// A size in pixels, as returned by [`CairoRenderer::best_fit_size`].
+///
+/// The dimensions are floating-point numbers; use [`f64::ceil()`] to get the size of a
+/// surface that can hold the whole rendering.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Size {
+    /// Width in pixels.
+    pub width: f64,
+
+    /// Height in pixels.
+    pub height: f64,
+}
+
+/// Converts a length in physical units to inches, or returns `None` for other units.
+fn length_in_inches(length: &Length) -> Option<f64> {
+    match length.unit {
+        LengthUnit::In => Some(length.length),
+        LengthUnit::Cm => Some(length.length / 2.54),
+        LengthUnit::Mm => Some(length.length / 25.4),
+        LengthUnit::Pt => Some(length.length / 72.0),
+        LengthUnit::Pc => Some(length.length / 6.0),
+        _ => None,
+    }
+}
+
 /// Gets the user's preferred locale from the environment and
 /// translates it to a `Locale` with `LanguageRange` fallbacks.
 ///
@@ -492,188 +1639,958 @@ pub struct IntrinsicDimensions {
 fn locale_from_environment() -> Locale {
     let mut locale = Locale::invariant();
 
-    for name in glib::language_names() {
-        let name = name.as_str();
-        if let Ok(range) = LanguageRange::from_unix(name) {
-            locale.add(&range);
+    for name in glib::language_names() {
+        let name = name.as_str();
+        if let Ok(range) = LanguageRange::from_unix(name) {
+            locale.add(&range);
+        }
+    }
+
+    locale
+}
+
+impl UserLanguage {
+    fn new(language: &Language, session: &Session) -> UserLanguage {
+        match *language {
+            Language::FromEnvironment => UserLanguage::LanguageTags(
+                LanguageTags::from_locale(&locale_from_environment())
+                    .map_err(|s| {
+                        rsvg_log!(session, "could not convert locale to language tags: {}", s);
+                    })
+                    .unwrap_or_else(|_| LanguageTags::empty()),
+            ),
+
+            Language::AcceptLanguage(ref a) => UserLanguage::AcceptLanguage(a.clone()),
+        }
+    }
+}
+
+impl<'a> CairoRenderer<'a> {
+    /// Creates a `CairoRenderer` for the specified `SvgHandle`.
+    ///
+    /// The default dots-per-inch (DPI) value is set to 96; you can change it
+    /// with the [`with_dpi`] method.
+    ///
+    /// [`with_dpi`]: #method.with_dpi
+    pub fn new(handle: &'a SvgHandle) -> Self {
+        let session = &handle.session;
+
+        CairoRenderer {
+            handle,
+            options: RenderingOptions::default(),
+            user_language: UserLanguage::new(&Language::FromEnvironment, session),
+            cancellable: None,
+            render_limits: RenderLimits::default(),
+            animation_time: None,
+            current_color: None,
+            custom_properties: None,
+            font_map: None,
+            circular_reference_errors: false,
+        }
+    }
+
+    /// Sets all the rendering options at once.
+    ///
+    /// This replaces the options set by [`with_dpi`], [`with_language`], and
+    /// [`with_background_color`], or by a previous call to this function.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `options.dpi_x` or `options.dpi_y` are not positive.
+    ///
+    /// [`with_dpi`]: #method.with_dpi
+    /// [`with_language`]: #method.with_language
+    /// [`with_background_color`]: #method.with_background_color
+    pub fn with_options(self, options: RenderingOptions) -> Self {
+        assert!(options.dpi_x > 0.0);
+        assert!(options.dpi_y > 0.0);
+
+        let user_language = UserLanguage::new(&options.language, &self.handle.session);
+
+        CairoRenderer {
+            options,
+            user_language,
+            ..self
+        }
+    }
+
+    /// Configures the dots-per-inch for resolving physical lengths.
+    ///
+    /// If an SVG document has physical units like `5cm`, they must be resolved
+    /// to pixel-based values.  The default pixel density is 96 DPI in
+    /// both dimensions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dpi_x` or `dpi_y` are not positive.  If the values come from user
+    /// input, use [`try_with_dpi`](#method.try_with_dpi) instead.
+    pub fn with_dpi(mut self, dpi_x: f64, dpi_y: f64) -> Self {
+        assert!(dpi_x > 0.0);
+        assert!(dpi_y > 0.0);
+
+        self.options.dpi_x = dpi_x;
+        self.options.dpi_y = dpi_y;
+        self
+    }
+
+    /// Configures the dots-per-inch for resolving physical lengths, or returns an error.
+    ///
+    /// This is like [`with_dpi`](#method.with_dpi), but instead of panicking it returns
+    /// [`RenderingError::InvalidDpi`] if `dpi_x` or `dpi_y` are not positive, finite
+    /// numbers.
+    pub fn try_with_dpi(self, dpi_x: f64, dpi_y: f64) -> Result<Self, RenderingError> {
+        let is_valid = |dpi: f64| dpi > 0.0 && dpi.is_finite();
+
+        if is_valid(dpi_x) && is_valid(dpi_y) {
+            Ok(self.with_dpi(dpi_x, dpi_y))
+        } else {
+            Err(RenderingError::InvalidDpi(dpi_x, dpi_y))
+        }
+    }
+
+    /// Configures the set of languages used for rendering.
+    ///
+    /// SVG documents can use the `<switch>` element, whose children have a
+    /// `systemLanguage` attribute; only the first child which has a `systemLanguage` that
+    /// matches the preferred languages will be rendered.
+    ///
+    /// This function sets the preferred languages.  The default is
+    /// `Language::FromEnvironment`, which means that the set of preferred languages will
+    /// be obtained from the program's environment.  To set an explicit list of languages,
+    /// you can use `Language::AcceptLanguage` instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rsvg::{AcceptLanguage, CairoRenderer, Language, Loader};
+    ///
+    /// let svg_handle = Loader::new()
+    ///     .read_path("example.svg")
+    ///     .unwrap();
+    ///
+    /// let language = Language::AcceptLanguage(AcceptLanguage::parse("es-MX, es, en").unwrap());
+    ///
+    /// let renderer = CairoRenderer::new(&svg_handle).with_language(&language);
+    /// ```
+    pub fn with_language(mut self, language: &Language) -> Self {
+        self.user_language = UserLanguage::new(language, &self.handle.session);
+        self.options.language = language.clone();
+        self
+    }
+
+    /// Sets a color to paint the viewport with before rendering.
+    ///
+    /// When this is set, [`render_document`] and [`render_element`] fill their viewport
+    /// with `color` first, and then draw the SVG on top of it.  This is useful when
+    /// rendering to an image format without transparency, where transparent areas would
+    /// otherwise come out black.  Other rendering functions like [`render_layer`] ignore
+    /// the background color.
+    ///
+    /// The default is `None`, which does not paint anything behind the SVG.
+    ///
+    /// [`render_document`]: #method.render_document
+    /// [`render_element`]: #method.render_element
+    /// [`render_layer`]: #method.render_layer
+    pub fn with_background_color(mut self, color: Option<Color>) -> Self {
+        self.options.background_color = color;
+        self
+    }
+
+    /// Sets a cancellable to be able to interrupt rendering.
+    ///
+    /// The rendering functions like [`render_document`] will normally render the whole
+    /// SVG document tree.  However, they can be interrupted if you set a `cancellable`
+    /// object with this method.  To interrupt rendering, you can call
+    /// [`gio::CancellableExt::cancel()`] from a different thread than where the rendering
+    /// is happening.
+    ///
+    /// Since rendering happens as a side-effect on the Cairo context (`cr`) that is
+    /// passed to the rendering functions, it may be that the `cr`'s target surface is in
+    /// an undefined state if the rendering is cancelled.  The surface may have not yet
+    /// been painted on, or it may contain a partially-rendered document.  For this
+    /// reason, if your application does not want to leave the target surface in an
+    /// inconsistent state, you may prefer to use a temporary surface for rendering, which
+    /// can be discarded if your code cancels the rendering.
+    ///
+    /// [`render_document`]: #method.render_document
+    #[cfg(feature = "gio")]
+    pub fn with_cancellable<C: IsA<Cancellable>>(self, cancellable: &C) -> Self {
+        CairoRenderer {
+            cancellable: Some(cancellable.clone().into()),
+            ..self
+        }
+    }
+
+    /// Sets limits on the work done while rendering.
+    ///
+    /// Some SVG documents are very expensive to render, for example, by using large
+    /// filters with many primitives, or by referencing the same elements many times.  If
+    /// your application renders untrusted documents, it can use this to bound the time
+    /// and memory that they take.  By default there are no limits; see [`RenderLimits`]
+    /// for details.
+    ///
+    /// The limits apply to each call to a rendering function separately; the counters
+    /// are reset every time.  When a limit is exceeded, the rendering function returns
+    /// [`RenderingError::LimitExceeded`].  As with [`with_cancellable`], the target
+    /// surface may contain a partially-rendered document in that case.
+    ///
+    /// [`with_cancellable`]: #method.with_cancellable
+    pub fn with_render_limits(self, render_limits: RenderLimits) -> Self {
+        CairoRenderer {
+            render_limits,
+            ..self
+        }
+    }
+
+    /// Makes the rendering functions report reference cycles in the document.
+    ///
+    /// Documents can have reference cycles, like a `<use>` that references one of its
+    /// ancestors, or two patterns whose contents are filled with each other.  Librsvg
+    /// always skips the elements in a cycle and renders the rest of the document.  By
+    /// default the rendering functions then return successfully; with this option set to
+    /// `true`, they return [`RenderingError::CircularReference`] after rendering, so
+    /// that applications can tell that the document did not render completely.
+    pub fn with_circular_reference_errors(self, circular_reference_errors: bool) -> Self {
+        CairoRenderer {
+            circular_reference_errors,
+            ..self
+        }
+    }
+
+    /// Renders the document as it looks at a certain time of its SMIL animations.
+    ///
+    /// By default, librsvg ignores the `<set>`, `<animate>`, and `<animateTransform>`
+    /// elements, and renders the static values of the attributes that they animate.  With
+    /// this option, the rendering functions evaluate the animations at `seconds` after the
+    /// start of the document's timeline, and render the animated values instead.  This is
+    /// useful to make thumbnails or individual frames of animated SVG documents.
+    ///
+    /// Only a subset of SMIL is supported.  An animation must be a child of the element
+    /// that it animates, and its `begin` attribute can only have offset values like `2s`.
+    /// Animations that use other features, like event-based timing, `calcMode="paced"`,
+    /// or `to`-only animations, are ignored.  Values get interpolated between numbers with
+    /// the same unit, and between colors; other values change discretely.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use rsvg::{CairoRenderer, Loader};
+    /// let svg_handle = Loader::new()
+    ///     .read_path("example.svg")
+    ///     .unwrap();
+    ///
+    /// // Render the frame at 1.5 seconds into the animation.
+    /// let renderer = CairoRenderer::new(&svg_handle).with_animation_time(1.5);
+    /// ```
+    pub fn with_animation_time(self, seconds: f64) -> Self {
+        CairoRenderer {
+            animation_time: Some(seconds),
+            ..self
+        }
+    }
+
+    /// Sets the value of the `color` property at the root of the document.
+    ///
+    /// Elements that do not set their own `color` inherit this value, so that `fill` and
+    /// `stroke` values of `currentColor` resolve to it.  This is useful to render
+    /// symbolic icons in the foreground color of a user interface.  Without this option,
+    /// the initial value of `color` is opaque black.
+    ///
+    /// Since the root has no parent from which it could inherit a color, passing a
+    /// `currentColor` value is the same as not calling this function.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use rsvg::{CairoRenderer, Loader};
+    /// let svg_handle = Loader::new()
+    ///     .read_path("example.svg")
+    ///     .unwrap();
+    ///
+    /// let color = rsvg::parse_color("#3584e4").unwrap();
+    /// let renderer = CairoRenderer::new(&svg_handle).with_current_color(color);
+    /// ```
+    pub fn with_current_color(self, color: Color) -> Self {
+        CairoRenderer {
+            current_color: Some(color),
+            ..self
+        }
+    }
+
+    /// Sets values for CSS custom properties, to be used with `var()` in the document.
+    ///
+    /// The keys of `variables` are the names of the custom properties, including their
+    /// leading dashes, like `--brand`.  The values are pieces of CSS, like `#3584e4`, and
+    /// they get substituted as they are, without resolving any `var()` in them.
+    ///
+    /// The variables are set at the root of the document, so elements inherit them unless
+    /// the document declares its own values.  This lets applications render stylesheets
+    /// like `rect { fill: var(--brand, black); }` with their own palette.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use rsvg::{CairoRenderer, Loader};
+    /// # use std::collections::HashMap;
+    /// let svg_handle = Loader::new()
+    ///     .read_path("example.svg")
+    ///     .unwrap();
+    ///
+    /// let variables = HashMap::from([(String::from("--brand"), String::from("#3584e4"))]);
+    /// let renderer = CairoRenderer::new(&svg_handle).with_css_variables(variables);
+    /// ```
+    pub fn with_css_variables(self, variables: HashMap<String, String>) -> Self {
+        CairoRenderer {
+            custom_properties: Some(CustomProperties::new(variables)),
+            ..self
+        }
+    }
+
+    /// Sets the fonts to use for text in this rendering.
+    ///
+    /// By default, text is rendered with the fonts installed in the system.  With a
+    /// [`FontConfig`] you can use font files that your application bundles instead; see
+    /// its documentation for the platforms where this is supported.  Images that the
+    /// document references, like `<image href="other.svg"/>`, use the same fonts.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use rsvg::{CairoRenderer, FontConfig, Loader};
+    /// let svg_handle = Loader::new()
+    ///     .read_path("example.svg")
+    ///     .unwrap();
+    ///
+    /// let mut fonts = FontConfig::empty();
+    /// fonts.add_font_dir("/usr/share/myapp/fonts").unwrap();
+    ///
+    /// let renderer = CairoRenderer::new(&svg_handle).with_fonts(fonts);
+    /// ```
+    pub fn with_fonts(self, fonts: FontConfig) -> Self {
+        CairoRenderer {
+            font_map: fonts.create_font_map(),
+            ..self
+        }
+    }
+
+    /// Queries the `width`, `height`, and `viewBox` attributes in an SVG document.
+    ///
+    /// If you are calling this function to compute a scaling factor to render the SVG,
+    /// consider simply using [`render_document`] instead; it will do the scaling
+    /// computations automatically.
+    ///
+    /// See also [`intrinsic_size_in_pixels`], which does the conversion to pixels if
+    /// possible.
+    ///
+    /// [`render_document`]: #method.render_document
+    /// [`intrinsic_size_in_pixels`]: #method.intrinsic_size_in_pixels
+    pub fn intrinsic_dimensions(&self) -> IntrinsicDimensions {
+        let d = self.handle.document.get_intrinsic_dimensions();
+
+        IntrinsicDimensions {
+            width: Into::into(d.width),
+            height: Into::into(d.height),
+            vbox: d.vbox.map(|v| cairo::Rectangle::from(*v)),
+        }
+    }
+
+    /// Converts the SVG document's intrinsic dimensions to pixels, if possible.
+    ///
+    /// Returns `Some(width, height)` in pixel units if the SVG document has `width` and
+    /// `height` attributes with physical dimensions (CSS pixels, cm, in, etc.) or
+    /// font-based dimensions (em, ex).
+    ///
+    /// Note that the dimensions are floating-point numbers, so your application can know
+    /// the exact size of an SVG document.  To get integer dimensions, you should use
+    /// [`f64::ceil()`] to round up to the nearest integer (just using [`f64::round()`],
+    /// may may chop off pixels with fractional coverage).
+    ///
+    /// If the SVG document has percentage-based `width` and `height` attributes, or if
+    /// either of those attributes are not present, returns `None`.  Dimensions of that
+    /// kind require more information to be resolved to pixels; for example, the calling
+    /// application can use a viewport size to scale percentage-based dimensions.  If
+    /// the document has a `viewBox`, [`IntrinsicDimensions::aspect_ratio`] gives the
+    /// proportions to use for that size.
+    pub fn intrinsic_size_in_pixels(&self) -> Option<(f64, f64)> {
+        let dim = self.intrinsic_dimensions();
+        let width = dim.width;
+        let height = dim.height;
+
+        if width.unit == LengthUnit::Percent || height.unit == LengthUnit::Percent {
+            return None;
+        }
+
+        Some(self.width_height_to_user(self.options_dpi()))
+    }
+
+    /// Computes a size in pixels at which to render the document, optionally limited to
+    /// a maximum size.
+    ///
+    /// This is meant to answer "how big should my surface be?" for documents that may or
+    /// may not specify their own size.  It uses the document's intrinsic dimensions like
+    /// this:
+    ///
+    /// * If `width` and `height` are both non-percentage lengths, they are converted to
+    ///   pixels with the renderer's DPI, just like in [`intrinsic_size_in_pixels`].
+    ///
+    /// * If only one of them is a non-percentage length, the other one is computed from
+    ///   the document's [`IntrinsicDimensions::aspect_ratio`], which comes from the
+    ///   `viewBox` in that case.
+    ///
+    /// * If neither of them is a non-percentage length, the size of the `viewBox` is used,
+    ///   taking its user units as pixels.  This is the case for documents that only have
+    ///   a `viewBox`, since a missing `width` or `height` is the same as `100%`.
+    ///
+    /// Returns `None` if the document has no usable size, for example, if it has
+    /// percentage dimensions and no `viewBox`, or if the size is zero.
+    ///
+    /// If `max` is `Some((max_width, max_height))` and the size is larger in either
+    /// dimension, it gets scaled down to fit in that size while preserving its aspect
+    /// ratio, as with `preserveAspectRatio="xMidYMid meet"`.  Smaller sizes are not scaled
+    /// up.  Also returns `None` if `max_width` or `max_height` are not positive finite
+    /// numbers, since nothing can fit in such a size.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use rsvg::{CairoRenderer, Loader};
+    /// let svg_handle = Loader::new()
+    ///     .read_path("example.svg")
+    ///     .unwrap();
+    ///
+    /// let renderer = CairoRenderer::new(&svg_handle);
+    ///
+    /// // Make a thumbnail no bigger than 128x128 pixels.
+    /// if let Some(size) = renderer.best_fit_size(Some((128.0, 128.0))) {
+    ///     assert!(size.width <= 128.0 && size.height <= 128.0);
+    /// }
+    /// ```
+    ///
+    /// [`intrinsic_size_in_pixels`]: #method.intrinsic_size_in_pixels
+    pub fn best_fit_size(&self, max: Option<(f64, f64)>) -> Option<Size> {
+        if let Some((max_width, max_height)) = max {
+            if !(max_width.is_finite() && max_height.is_finite())
+                || max_width <= 0.0
+                || max_height <= 0.0
+            {
+                return None;
+            }
+        }
+
+        let dim = self.intrinsic_dimensions();
+        let (width, height) = self.width_height_to_user(self.options_dpi());
+
+        let (width, height) = match (
+            dim.width.unit != LengthUnit::Percent,
+            dim.height.unit != LengthUnit::Percent,
+        ) {
+            (true, true) => (width, height),
+            (true, false) => (width, width / dim.aspect_ratio()?),
+            (false, true) => (height * dim.aspect_ratio()?, height),
+            (false, false) => {
+                let vbox = dim.vbox?;
+                (vbox.width(), vbox.height())
+            }
+        };
+
+        if !(width.is_finite() && height.is_finite() && width > 0.0 && height > 0.0) {
+            return None;
+        }
+
+        match max {
+            Some((max_width, max_height)) if width > max_width || height > max_height => {
+                let fitted = AspectRatio::default().compute(
+                    &ViewBox::from(Rect::from_size(width, height)),
+                    &Rect::from_size(max_width, max_height),
+                );
+
+                Some(Size {
+                    width: fitted.width(),
+                    height: fitted.height(),
+                })
+            }
+
+            _ => Some(Size { width, height }),
+        }
+    }
+
+    fn options_dpi(&self) -> Dpi {
+        Dpi::new(self.options.dpi_x, self.options.dpi_y)
+    }
+
+    fn rendering_options(&self) -> DocumentRenderingOptions {
+        DocumentRenderingOptions {
+            dpi: self.options_dpi(),
+            cancellable: self.cancellable.clone(),
+            user_language: self.user_language.clone(),
+            svg_nesting: SvgNesting::Standalone,
+            testing: self.options.testing,
+            font_warnings: Default::default(),
+            render_budget: Rc::new(RenderBudget::new(self.render_limits)),
+            render_events: None,
+            animation_time: self.animation_time,
+            current_color: self
+                .current_color
+                .filter(|color| !color.is_current_color())
+                .map(|color| color.0),
+            custom_properties: self.custom_properties.clone(),
+            font_map: self.font_map.clone(),
+            circular_reference_errors: self.circular_reference_errors,
+        }
+    }
+
+    /// Paints the `viewport` with the background color from the options, if there is one.
+    fn paint_background(
+        &self,
+        cr: &cairo::Context,
+        viewport: &cairo::Rectangle,
+    ) -> Result<(), RenderingError> {
+        if let Some(ref color) = self.options.background_color {
+            cr.status()?;
+            paint_background(cr, viewport, &color.resolved())?;
+        }
+
+        Ok(())
+    }
+
+    /// Renders the whole SVG document fitted to a viewport
+    ///
+    /// The `viewport` gives the position and size at which the whole SVG
+    /// document will be rendered.  It is in the `cr`'s current coordinate system,
+    /// so a scene graph can translate, rotate, or scale the `cr` to place the SVG
+    /// wherever it needs.
+    ///
+    /// The `cr` must be in a `cairo::Status::Success` state, or this function
+    /// will not render anything, and instead will return
+    /// `RenderingError::Cairo` with the `cr`'s current error state.
+    pub fn render_document(
+        &self,
+        cr: &cairo::Context,
+        viewport: &cairo::Rectangle,
+    ) -> Result<(), RenderingError> {
+        self.paint_background(cr, viewport)?;
+
+        Ok(self.handle.document.render_document(
+            &self.handle.session,
+            cr,
+            viewport,
+            &self.rendering_options(),
+        )?)
+    }
+
+    /// Renders the whole SVG document to a new image surface of the given size
+    ///
+    /// The document is fitted into a `width` by `height` rectangle, as with
+    /// [`render_document`].  This is useful to inspect the rendered pixels without
+    /// encoding them to PNG first.
+    ///
+    /// The surface's pixels are in the sRGB color space, with premultiplied alpha.  You
+    /// can iterate over them with [`Pixels`], and call [`PixelOps::unpremultiply`] on
+    /// each [`Pixel`] to get straight RGBA values:
+    ///
+    /// ```
+    /// # use rsvg::{CairoRenderer, Loader, Pixels, PixelOps};
+    /// let handle = Loader::new().read_path("example.svg").unwrap();
+    /// let renderer = CairoRenderer::new(&handle);
+    ///
+    /// let surface = renderer.render_to_shared_surface(100, 100).unwrap();
+    ///
+    /// let opaque_pixels = Pixels::new(&surface)
+    ///     .map(|(_x, _y, pixel)| pixel.unpremultiply())
+    ///     .filter(|pixel| pixel.a == 255)
+    ///     .count();
+    /// ```
+    ///
+    /// Returns `RenderingError::Cairo(cairo::Error::InvalidSize)` if the size is not
+    /// positive.
+    ///
+    /// [`render_document`]: #method.render_document
+    pub fn render_to_shared_surface(
+        &self,
+        width: i32,
+        height: i32,
+    ) -> Result<SharedImageSurface, RenderingError> {
+        let mut surface = ExclusiveImageSurface::new(width, height, SurfaceType::SRgb)?;
+        let viewport = cairo::Rectangle::new(0.0, 0.0, f64::from(width), f64::from(height));
+
+        let mut result = Ok(());
+        surface.draw(&mut |cr| {
+            result = self.render_document(&cr, &viewport);
+            Ok(())
+        })?;
+        result?;
+
+        Ok(surface.share()?)
+    }
+
+    /// Renders the whole SVG document into a caller-provided buffer of RGBA pixels
+    ///
+    /// The document is fitted into a `width` by `height` rectangle, as with
+    /// [`render_document`].  The `buf` has `height` rows of `stride` bytes each, and each
+    /// row starts with `width` pixels of four bytes, in R, G, B, A order.  Any previous
+    /// contents of the pixels get overwritten; the padding at the end of each row, if any,
+    /// is left untouched.
+    ///
+    /// The output is in the sRGB color space, with straight (non-premultiplied) alpha.
+    ///
+    /// If possible, Cairo renders directly into `buf`, so that no intermediate surface needs
+    /// to be allocated.  This requires `buf` to be aligned to 4 bytes, and `stride` to be a
+    /// multiple of 4; otherwise the document is rendered to a temporary surface and copied.
+    ///
+    /// Returns `RenderingError::Cairo(cairo::Error::InvalidStride)` if `stride` is less than
+    /// `width * 4`, and `RenderingError::Cairo(cairo::Error::InvalidSize)` if `buf` is smaller
+    /// than `stride * height` bytes or if the size is too big for Cairo.  If `width` or
+    /// `height` is zero, there are no pixels to render and this returns `Ok(())` without
+    /// touching `buf`.
+    ///
+    /// ```
+    /// # use rsvg::{CairoRenderer, Loader};
+    /// let handle = Loader::new().read_path("example.svg").unwrap();
+    /// let renderer = CairoRenderer::new(&handle);
+    ///
+    /// let mut pixels = vec![0u8; 100 * 100 * 4];
+    /// renderer.render_to_rgba_buffer(&mut pixels, 100, 100, 100 * 4).unwrap();
+    /// ```
+    ///
+    /// [`render_document`]: #method.render_document
+    pub fn render_to_rgba_buffer(
+        &self,
+        buf: &mut [u8],
+        width: u32,
+        height: u32,
+        stride: usize,
+    ) -> Result<(), RenderingError> {
+        let (Ok(w), Ok(h), Ok(s)) = (
+            i32::try_from(width),
+            i32::try_from(height),
+            i32::try_from(stride),
+        ) else {
+            return Err(RenderingError::Cairo(cairo::Error::InvalidSize));
+        };
+
+        let row_len = width as usize * 4;
+
+        if stride < row_len {
+            return Err(RenderingError::Cairo(cairo::Error::InvalidStride));
         }
-    }
 
-    locale
-}
+        let buf_len = stride
+            .checked_mul(height as usize)
+            .ok_or(RenderingError::Cairo(cairo::Error::InvalidSize))?;
 
-impl UserLanguage {
-    fn new(language: &Language, session: &Session) -> UserLanguage {
-        match *language {
-            Language::FromEnvironment => UserLanguage::LanguageTags(
-                LanguageTags::from_locale(&locale_from_environment())
-                    .map_err(|s| {
-                        rsvg_log!(session, "could not convert locale to language tags: {}", s);
-                    })
-                    .unwrap_or_else(|_| LanguageTags::empty()),
-            ),
+        if buf.len() < buf_len {
+            return Err(RenderingError::Cairo(cairo::Error::InvalidSize));
+        }
 
-            Language::AcceptLanguage(ref a) => UserLanguage::AcceptLanguage(a.clone()),
+        if width == 0 || height == 0 {
+            return Ok(());
         }
-    }
-}
 
-impl<'a> CairoRenderer<'a> {
-    /// Creates a `CairoRenderer` for the specified `SvgHandle`.
-    ///
-    /// The default dots-per-inch (DPI) value is set to 96; you can change it
-    /// with the [`with_dpi`] method.
-    ///
-    /// [`with_dpi`]: #method.with_dpi
-    pub fn new(handle: &'a SvgHandle) -> Self {
-        let session = &handle.session;
+        let buf = &mut buf[..buf_len];
+        let viewport = cairo::Rectangle::new(0.0, 0.0, f64::from(width), f64::from(height));
 
-        CairoRenderer {
-            handle,
-            dpi: Dpi::new(DEFAULT_DPI_X, DEFAULT_DPI_Y),
-            user_language: UserLanguage::new(&Language::FromEnvironment, session),
-            cancellable: None,
-            is_testing: false,
+        if stride % 4 == 0 && buf.as_ptr().align_offset(4) == 0 {
+            for row in buf.chunks_exact_mut(stride) {
+                row[..row_len].fill(0);
+            }
+
+            // SAFETY: the buffer is big enough and suitably aligned for an ARGB32 surface
+            // of this size and stride, and it outlives the surface's use: we finish the
+            // surface before returning, after which Cairo does not touch its data anymore.
+            let surface = unsafe {
+                cairo::ImageSurface::create_for_data_unsafe(
+                    buf.as_mut_ptr(),
+                    cairo::Format::ARgb32,
+                    w,
+                    h,
+                    s,
+                )?
+            };
+
+            let res = cairo::Context::new(&surface)
+                .map_err(RenderingError::from)
+                .and_then(|cr| self.render_document(&cr, &viewport));
+
+            surface.finish();
+            res?;
+        } else {
+            let mut surface = cairo::ImageSurface::create(cairo::Format::ARgb32, w, h)?;
+
+            {
+                let cr = cairo::Context::new(&surface)?;
+                self.render_document(&cr, &viewport)?;
+            }
+
+            let src_stride = surface.stride() as usize;
+            let data = surface
+                .data()
+                .expect("surface should not be shared after rendering");
+
+            for (dst, src) in buf.chunks_mut(stride).zip(data.chunks(src_stride)) {
+                dst[..row_len].copy_from_slice(&src[..row_len]);
+            }
         }
-    }
 
-    /// Configures the dots-per-inch for resolving physical lengths.
-    ///
-    /// If an SVG document has physical units like `5cm`, they must be resolved
-    /// to pixel-based values.  The default pixel density is 96 DPI in
-    /// both dimensions.
-    pub fn with_dpi(self, dpi_x: f64, dpi_y: f64) -> Self {
-        assert!(dpi_x > 0.0);
-        assert!(dpi_y > 0.0);
+        // Cairo's pixels are native-endian u32 values in premultiplied ARGB
+        for row in buf.chunks_mut(stride) {
+            for p in row[..row_len].chunks_exact_mut(4) {
+                let pixel = Pixel::from_u32(u32::from_ne_bytes([p[0], p[1], p[2], p[3]]));
+                let pixel = pixel.unpremultiply();
 
-        CairoRenderer {
-            dpi: Dpi::new(dpi_x, dpi_y),
-            ..self
+                p.copy_from_slice(&[pixel.r, pixel.g, pixel.b, pixel.a]);
+            }
         }
+
+        Ok(())
     }
 
-    /// Configures the set of languages used for rendering.
+    /// Renders the whole SVG document into a new SVG document, and returns its bytes.
     ///
-    /// SVG documents can use the `<switch>` element, whose children have a
-    /// `systemLanguage` attribute; only the first child which has a `systemLanguage` that
-    /// matches the preferred languages will be rendered.
+    /// The document is fitted into a rectangle of the given `size`, as with
+    /// [`render_document`], on a Cairo SVG surface of that size.  The result has the
+    /// `<use>` references, CSS styles, and inherited properties resolved into plain shapes,
+    /// which is useful for flattening a document.
     ///
-    /// This function sets the preferred languages.  The default is
-    /// `Language::FromEnvironment`, which means that the set of preferred languages will
-    /// be obtained from the program's environment.  To set an explicit list of languages,
-    /// you can use `Language::AcceptLanguage` instead.
-    pub fn with_language(self, language: &Language) -> Self {
-        let user_language = UserLanguage::new(language, &self.handle.session);
+    /// Note that this is Cairo replaying the drawing operations as SVG, not a semantic
+    /// round-trip: element structure, `id` attributes, and text as text are not preserved,
+    /// and some features like filters may end up as raster images.
+    ///
+    /// Returns `RenderingError::Cairo(cairo::Error::InvalidSize)` if the size is not
+    /// positive.
+    ///
+    /// This function is only available if Cairo was built with support for SVG surfaces.
+    ///
+    /// ```
+    /// # use rsvg::{CairoRenderer, Loader, Size};
+    /// let handle = Loader::new().read_path("example.svg").unwrap();
+    /// let renderer = CairoRenderer::new(&handle);
+    ///
+    /// let svg = renderer
+    ///     .render_to_svg_bytes(Size { width: 100.0, height: 100.0 })
+    ///     .unwrap();
+    /// ```
+    ///
+    /// [`render_document`]: #method.render_document
+    #[cfg(system_deps_have_cairo_svg)]
+    pub fn render_to_svg_bytes(&self, size: Size) -> Result<Vec<u8>, RenderingError> {
+        let Size { width, height } = size;
 
-        CairoRenderer {
-            user_language,
-            ..self
+        if !(width.is_finite() && height.is_finite()) || width <= 0.0 || height <= 0.0 {
+            return Err(RenderingError::Cairo(cairo::Error::InvalidSize));
         }
+
+        let surface = cairo::SvgSurface::for_stream(width, height, Vec::<u8>::new())?;
+
+        {
+            let cr = cairo::Context::new(&surface)?;
+            let viewport = cairo::Rectangle::new(0.0, 0.0, width, height);
+            self.render_document(&cr, &viewport)?;
+        }
+
+        let stream = surface
+            .finish_output_stream()
+            .map_err(|_| RenderingError::Cairo(cairo::Error::WriteError))?;
+
+        Ok(*stream
+            .downcast::<Vec<u8>>()
+            .expect("the SVG surface was created with a Vec<u8> stream"))
     }
 
-    /// Sets a cancellable to be able to interrupt rendering.
+    /// Renders the whole SVG document to a new `Pixbuf` of the given size
     ///
-    /// The rendering functions like [`render_document`] will normally render the whole
-    /// SVG document tree.  However, they can be interrupted if you set a `cancellable`
-    /// object with this method.  To interrupt rendering, you can call
-    /// [`gio::CancellableExt::cancel()`] from a different thread than where the rendering
-    /// is happening.
+    /// The document is fitted into a `width` by `height` rectangle, as with
+    /// [`render_document`], so it keeps its aspect ratio and may leave transparent areas
+    /// at the sides.  The resulting pixbuf has unpremultiplied RGBA pixels, which is
+    /// what GdkPixbuf expects.
     ///
-    /// Since rendering happens as a side-effect on the Cairo context (`cr`) that is
-    /// passed to the rendering functions, it may be that the `cr`'s target surface is in
-    /// an undefined state if the rendering is cancelled.  The surface may have not yet
-    /// been painted on, or it may contain a partially-rendered document.  For this
-    /// reason, if your application does not want to leave the target surface in an
-    /// inconsistent state, you may prefer to use a temporary surface for rendering, which
-    /// can be discarded if your code cancels the rendering.
+    /// Since GdkPixbuf does not support empty images, this returns
+    /// `RenderingError::Cairo(cairo::Error::InvalidSize)` if the size is not positive.
+    ///
+    /// This function is only available with the `pixbuf` feature.
     ///
     /// [`render_document`]: #method.render_document
-    pub fn with_cancellable<C: IsA<Cancellable>>(self, cancellable: &C) -> Self {
-        CairoRenderer {
-            cancellable: Some(cancellable.clone().into()),
-            ..self
-        }
+    #[cfg(feature = "pixbuf")]
+    pub fn render_to_pixbuf(
+        &self,
+        width: i32,
+        height: i32,
+    ) -> Result<gdk_pixbuf::Pixbuf, RenderingError> {
+        self.render_to_shared_surface(width, height)?
+            .to_pixbuf()
+            .ok_or_else(|| RenderingError::OutOfMemory(String::from("creating a Pixbuf")))
     }
 
-    /// Queries the `width`, `height`, and `viewBox` attributes in an SVG document.
+    /// Renders the whole SVG document fitted to a viewport, and allows cancelling it
     ///
-    /// If you are calling this function to compute a scaling factor to render the SVG,
-    /// consider simply using [`render_document`] instead; it will do the scaling
-    /// computations automatically.
+    /// This works like [`render_document`], but rendering can be interrupted by calling
+    /// [`gio::CancellableExt::cancel()`] on `cancellable` from another thread.  The
+    /// `cancellable` is checked before drawing each element, so rendering stops soon after
+    /// it gets cancelled, and this function returns [`RenderingError::Cancelled`].
     ///
-    /// See also [`intrinsic_size_in_pixels`], which does the conversion to pixels if
-    /// possible.
+    /// The `cancellable` is only used for this call; it overrides the one from
+    /// [`with_cancellable`], if any.  See the caveats there about the state of the `cr`'s
+    /// target surface after a cancelled rendering.
     ///
     /// [`render_document`]: #method.render_document
-    /// [`intrinsic_size_in_pixels`]: #method.intrinsic_size_in_pixels
-    pub fn intrinsic_dimensions(&self) -> IntrinsicDimensions {
-        let d = self.handle.document.get_intrinsic_dimensions();
+    /// [`with_cancellable`]: #method.with_cancellable
+    #[cfg(feature = "gio")]
+    pub fn render_document_cancellable<C: IsA<Cancellable>>(
+        &self,
+        cr: &cairo::Context,
+        viewport: &cairo::Rectangle,
+        cancellable: &C,
+    ) -> Result<(), RenderingError> {
+        self.paint_background(cr, viewport)?;
 
-        IntrinsicDimensions {
-            width: Into::into(d.width),
-            height: Into::into(d.height),
-            vbox: d.vbox.map(|v| cairo::Rectangle::from(*v)),
-        }
+        let options = DocumentRenderingOptions {
+            cancellable: Some(cancellable.clone().into()),
+            ..self.rendering_options()
+        };
+
+        Ok(self
+            .handle
+            .document
+            .render_document(&self.handle.session, cr, viewport, &options)?)
     }
 
-    /// Converts the SVG document's intrinsic dimensions to pixels, if possible.
+    /// Renders the whole SVG document fitted to a viewport, with an extra transform
     ///
-    /// Returns `Some(width, height)` in pixel units if the SVG document has `width` and
-    /// `height` attributes with physical dimensions (CSS pixels, cm, in, etc.) or
-    /// font-based dimensions (em, ex).
+    /// This works like [`render_document`], but after the document is fitted to the
+    /// `viewport`, it gets transformed by `transform`.  The transform is applied in the
+    /// viewport's coordinate system, whose origin is at the viewport's upper-left
+    /// corner; so, to rotate the document around the center of the viewport, `transform`
+    /// should translate to the center, rotate, and translate back.
     ///
-    /// Note that the dimensions are floating-point numbers, so your application can know
-    /// the exact size of an SVG document.  To get integer dimensions, you should use
-    /// [`f64::ceil()`] to round up to the nearest integer (just using [`f64::round()`],
-    /// may may chop off pixels with fractional coverage).
+    /// The `cr` must be in a `cairo::Status::Success` state, or this function
+    /// will not render anything, and instead will return
+    /// `RenderingError::Cairo` with the `cr`'s current error state.  If `transform` is
+    /// not invertible, nothing is rendered and an error is returned.
     ///
-    /// If the SVG document has percentage-based `width` and `height` attributes, or if
-    /// either of those attributes are not present, returns `None`.  Dimensions of that
-    /// kind require more information to be resolved to pixels; for example, the calling
-    /// application can use a viewport size to scale percentage-based dimensions.
-    pub fn intrinsic_size_in_pixels(&self) -> Option<(f64, f64)> {
-        let dim = self.intrinsic_dimensions();
-        let width = dim.width;
-        let height = dim.height;
-
-        if width.unit == LengthUnit::Percent || height.unit == LengthUnit::Percent {
-            return None;
-        }
-
-        Some(self.width_height_to_user(self.dpi))
-    }
+    /// [`render_document`]: #method.render_document
+    pub fn render_document_with_transform(
+        &self,
+        cr: &cairo::Context,
+        viewport: &cairo::Rectangle,
+        transform: cairo::Matrix,
+    ) -> Result<(), RenderingError> {
+        self.paint_background(cr, viewport)?;
 
-    fn rendering_options(&self) -> RenderingOptions {
-        RenderingOptions {
-            dpi: self.dpi,
-            cancellable: self.cancellable.clone(),
-            user_language: self.user_language.clone(),
-            svg_nesting: SvgNesting::Standalone,
-            testing: self.is_testing,
-        }
+        Ok(self.handle.document.render_document_with_transform(
+            &self.handle.session,
+            cr,
+            viewport,
+            Transform::from(transform),
+            &self.rendering_options(),
+        )?)
     }
 
-    /// Renders the whole SVG document fitted to a viewport
+    /// Renders only the part of the SVG document that falls inside a region
     ///
-    /// The `viewport` gives the position and size at which the whole SVG
-    /// document will be rendered.
+    /// This works like [`render_document`], but everything is clipped to `region`, which
+    /// is in the user coordinate system of the document's toplevel `<svg>` element (that
+    /// is, the coordinates of its `viewBox`).  Elements whose extents fall completely
+    /// outside the region are skipped, so this is cheaper than rendering the whole
+    /// document.
+    ///
+    /// This is useful for rendering a very large document in tiles, for example when the
+    /// whole image would be larger than the maximum size of a Cairo surface.  To draw a
+    /// tile, use a `cr` whose target surface is the size of the tile, translate it so that
+    /// the tile's position is at the origin, and pass the same `viewport` that you would
+    /// use to render the whole document.
+    ///
+    /// Elements with filter effects whose filter region overlaps the `region` are rendered
+    /// with their whole filter region, so that effects that spread pixels around, like
+    /// blurs or offsets, produce the same pixels as when rendering the whole document.
+    /// Tiles rendered this way can be stitched together without seams.
+    ///
+    /// The background color from [`with_background_color`](#method.with_background_color)
+    /// gets painted only inside the region, so that it does not cover other tiles.
     ///
     /// The `cr` must be in a `cairo::Status::Success` state, or this function
     /// will not render anything, and instead will return
     /// `RenderingError::Cairo` with the `cr`'s current error state.
-    pub fn render_document(
+    ///
+    /// [`render_document`]: #method.render_document
+    pub fn render_document_region(
         &self,
         cr: &cairo::Context,
         viewport: &cairo::Rectangle,
+        region: cairo::Rectangle,
     ) -> Result<(), RenderingError> {
-        Ok(self.handle.document.render_document(
+        let background = self.options.background_color.as_ref().map(Color::resolved);
+
+        Ok(self.handle.document.render_document_region(
             &self.handle.session,
             cr,
             viewport,
+            &region,
+            background.as_ref(),
             &self.rendering_options(),
         )?)
     }
 
+    /// Renders the whole SVG document like [`render_document`], and reports unusable fonts
+    ///
+    /// If a font file is corrupt or cannot be loaded, text that would use it is rendered
+    /// with the next family in its `font-family` list instead, or with a generic
+    /// `sans-serif` font if there are no more families.  This function returns one
+    /// [`FontWarning`] for each font that had to be replaced in that way; the list is
+    /// empty if all the fonts could be used.
+    ///
+    /// [`render_document`]: #method.render_document
+    pub fn render_document_checked_fonts(
+        &self,
+        cr: &cairo::Context,
+        viewport: &cairo::Rectangle,
+    ) -> Result<Vec<FontWarning>, RenderingError> {
+        self.paint_background(cr, viewport)?;
+
+        let options = self.rendering_options();
+
+        self.handle
+            .document
+            .render_document(&self.handle.session, cr, viewport, &options)?;
+
+        Ok(options.font_warnings.take())
+    }
+
+    /// Renders the whole SVG document like [`render_document`], and reports what gets drawn
+    ///
+    /// The document is rendered to the `cr` as usual.  In addition, the `observer` gets
+    /// notified of each layer, fill, stroke, image, and span of text that got drawn, in the
+    /// order in which they were drawn.  The notifications are delivered once rendering
+    /// finishes, even if it fails partway; in that case the `observer` only sees the
+    /// primitives that got drawn before the error.
+    ///
+    /// The background color from [`with_background_color`] is not reported.
+    ///
+    /// [`render_document`]: #method.render_document
+    /// [`with_background_color`]: #method.with_background_color
+    pub fn render_document_observed(
+        &self,
+        cr: &cairo::Context,
+        viewport: &cairo::Rectangle,
+        observer: &mut dyn RenderObserver,
+    ) -> Result<(), RenderingError> {
+        self.paint_background(cr, viewport)?;
+
+        let options = DocumentRenderingOptions {
+            render_events: Some(Default::default()),
+            ..self.rendering_options()
+        };
+
+        let result =
+            self.handle
+                .document
+                .render_document(&self.handle.session, cr, viewport, &options);
+
+        if let Some(ref events) = options.render_events {
+            render_observer::replay(events.take(), observer);
+        }
+
+        Ok(result?)
+    }
+
     /// Computes the (ink_rect, logical_rect) of an SVG element, as if
     /// the SVG were rendered to a specific viewport.
     ///
@@ -695,7 +2612,9 @@ impl<'a> CairoRenderer<'a> {
     /// root element.
     ///
     /// This operation is not constant-time, as it involves going through all
-    /// the child elements.
+    /// the child elements.  To measure many elements, use
+    /// [`geometries_for_elements`](#method.geometries_for_elements), which goes through
+    /// the document only once.
     ///
     /// FIXME: example
     pub fn geometry_for_layer(
@@ -714,6 +2633,79 @@ impl<'a> CairoRenderer<'a> {
         )?)
     }
 
+    /// Computes the (ink_rect, logical_rect) of many SVG elements at once, as if the SVG
+    /// were rendered to a specific viewport.
+    ///
+    /// This returns the same geometries as calling
+    /// [`geometry_for_layer`](#method.geometry_for_layer) for each of the `ids`, but it
+    /// goes through the document only once, instead of once for each element.  Use this
+    /// when you need the geometries of many elements, for example, of all the icons in a
+    /// sprite sheet.
+    ///
+    /// The result has one entry for each of the `ids`, in the same order.  An entry is an
+    /// error if its id is invalid or not found in the document; if measuring the document
+    /// fails, all the entries have that error.
+    ///
+    /// ```
+    /// # let svg = rsvg::SvgHandle::try_from(
+    /// #     r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+    /// #          <rect id="a" x="10" y="10" width="20" height="20"/>
+    /// #          <rect id="b" x="50" y="50" width="40" height="40"/>
+    /// #        </svg>"#,
+    /// # ).unwrap();
+    /// let renderer = rsvg::CairoRenderer::new(&svg);
+    /// let viewport = cairo::Rectangle::new(0.0, 0.0, 100.0, 100.0);
+    ///
+    /// let geometries = renderer.geometries_for_elements(&["#a", "#b", "#nonexistent"], &viewport);
+    ///
+    /// let (ink_rect, _logical_rect) = geometries[1].as_ref().unwrap();
+    /// assert_eq!((ink_rect.x(), ink_rect.width()), (50.0, 40.0));
+    /// assert!(geometries[2].is_err());
+    /// ```
+    pub fn geometries_for_elements(
+        &self,
+        ids: &[&str],
+        viewport: &cairo::Rectangle,
+    ) -> Vec<Result<(cairo::Rectangle, cairo::Rectangle), RenderingError>> {
+        let lookups: Vec<Result<Node, RenderingError>> = ids
+            .iter()
+            .map(|id| {
+                let node_id = self.handle.get_node_id(id)?;
+                Ok(self.handle.lookup_node(&node_id)?)
+            })
+            .collect();
+
+        let nodes: Vec<Node> = lookups.iter().flatten().cloned().collect();
+
+        let geometries = self.handle.document.get_geometries_for_layers(
+            &self.handle.session,
+            &nodes,
+            viewport,
+            &self.rendering_options(),
+        );
+
+        match geometries {
+            Ok(geometries) => {
+                let mut geometries = geometries.into_iter();
+
+                lookups
+                    .into_iter()
+                    .map(|lookup| {
+                        lookup.map(|_| geometries.next().expect("one geometry for each node"))
+                    })
+                    .collect()
+            }
+
+            Err(e) => {
+                let e = RenderingError::from(e);
+                lookups
+                    .into_iter()
+                    .map(|lookup| lookup.and_then(|_| Err(e.clone())))
+                    .collect()
+            }
+        }
+    }
+
     /// Renders a single SVG element in the same place as for a whole SVG document
     ///
     /// This is equivalent to `render_document`, but renders only a single element and its
@@ -806,7 +2798,14 @@ impl<'a> CairoRenderer<'a> {
     /// a leading `#` character.
     ///
     /// The `element_viewport` gives the position and size at which the named element will
-    /// be rendered.  FIXME: mention proportional scaling.
+    /// be rendered.  The element's ink rectangle, as returned by
+    /// [`geometry_for_element`](#method.geometry_for_element), is scaled proportionally
+    /// so that it is as large as possible while still fitting inside the viewport, and
+    /// it is placed at the viewport's top-left corner.  If the aspect ratios of the
+    /// element and the viewport differ, the viewport will have some empty space to the
+    /// right or at the bottom.  Use
+    /// [`render_element_filling`](#method.render_element_filling) to stretch the element
+    /// instead.
     ///
     /// The `cr` must be in a `cairo::Status::Success` state, or this function
     /// will not render anything, and instead will return
@@ -816,23 +2815,97 @@ impl<'a> CairoRenderer<'a> {
         cr: &cairo::Context,
         id: Option<&str>,
         element_viewport: &cairo::Rectangle,
+    ) -> Result<(), RenderingError> {
+        self.render_element_impl(cr, id, element_viewport, false)
+    }
+
+    /// Renders a single SVG element, stretched to fill a given viewport
+    ///
+    /// This works like [`render_element`](#method.render_element), but the element's ink
+    /// rectangle is scaled independently in the horizontal and vertical directions so
+    /// that it exactly covers the `element_viewport`.  The element will look distorted
+    /// if its aspect ratio is different from the viewport's.
+    ///
+    /// This is useful for extracting sprites that must fill a fixed-size cell.
+    pub fn render_element_filling(
+        &self,
+        cr: &cairo::Context,
+        id: Option<&str>,
+        element_viewport: &cairo::Rectangle,
+    ) -> Result<(), RenderingError> {
+        self.render_element_impl(cr, id, element_viewport, true)
+    }
+
+    fn render_element_impl(
+        &self,
+        cr: &cairo::Context,
+        id: Option<&str>,
+        element_viewport: &cairo::Rectangle,
+        stretch: bool,
     ) -> Result<(), RenderingError> {
         let node_id = self.handle.get_node_id_or_root(id)?;
         let node = self.handle.get_node_or_root(&node_id)?;
 
+        self.paint_background(cr, element_viewport)?;
+
         Ok(self.handle.document.render_element(
             &self.handle.session,
             cr,
             node,
             element_viewport,
+            stretch,
             &self.rendering_options(),
         )?)
     }
 
+    /// Finds the frontmost element that is painted at a point.
+    ///
+    /// The point `(x, y)` is in the same coordinate system as the `viewport`, that is, the
+    /// one you would use to call [`render_document`] with the same `viewport`.  This is
+    /// useful for interactive viewers that need to know which element is under the
+    /// pointer.
+    ///
+    /// Elements are tested by their actual painted area, which takes into account the
+    /// fill, stroke, clipping paths, and masks, not just their bounding boxes.  Elements
+    /// that paint nothing at the point, like ones with `fill="none"`, `opacity="0"`, or
+    /// `visibility="hidden"`, are skipped.  The `pointer-events` property is ignored.
+    ///
+    /// Returns the `id` of the element that was hit, with a leading `#` character so
+    /// that it can be passed directly to [`render_element`].  If the element that was hit
+    /// does not have an `id`, this returns the `id` of its nearest ancestor that has one.
+    /// Returns `None` if nothing is painted at the point, or if the element and all its
+    /// ancestors lack an `id`.
+    ///
+    /// This renders separately each element whose bounding box contains the point, so it
+    /// can be slow for documents with many overlapping elements.
+    ///
+    /// [`render_document`]: #method.render_document
+    /// [`render_element`]: #method.render_element
+    pub fn element_at_point(&self, viewport: &cairo::Rectangle, x: f64, y: f64) -> Option<String> {
+        match self.handle.document.element_at_point(
+            &self.handle.session,
+            viewport,
+            x,
+            y,
+            &self.rendering_options(),
+        ) {
+            Ok(id) => id.map(|id| format!("#{id}")),
+
+            Err(e) => {
+                rsvg_log!(
+                    self.handle.session,
+                    "could not hit-test the document: {}",
+                    e
+                );
+                None
+            }
+        }
+    }
+
     #[doc(hidden)]
     #[cfg(feature = "capi")]
     pub fn dpi(&self) -> Dpi {
-        self.dpi
+        self.options_dpi()
     }
 
     /// Normalizes the svg's width/height properties with a 0-sized viewport
@@ -859,7 +2932,8 @@ impl<'a> CairoRenderer<'a> {
 
     #[doc(hidden)]
     #[cfg(feature = "capi")]
-    pub fn test_mode(self, is_testing: bool) -> Self {
-        CairoRenderer { is_testing, ..self }
+    pub fn test_mode(mut self, is_testing: bool) -> Self {
+        self.options.testing = is_testing;
+        self
     }
 }
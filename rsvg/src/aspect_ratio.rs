@@ -4,8 +4,8 @@
 //! We have an [`AspectRatio`] struct which encapsulates such a value.
 //!
 //! ```
-//! # use rsvg::doctest_only::AspectRatio;
-//! # use rsvg::doctest_only::Parse;
+//! use rsvg::AspectRatio;
+//!
 //! assert_eq!(
 //!     AspectRatio::parse_str("xMidYMid").unwrap(),
 //!     AspectRatio::default()
@@ -77,6 +77,11 @@ struct Align {
     fit: FitMode,
 }
 
+/// The value of a `preserveAspectRatio` attribute.
+///
+/// This says how to fit a [`ViewBox`] into a viewport with different proportions.  The
+/// default is `xMidYMid meet`, which scales the viewbox proportionally so that it fits
+/// entirely in the viewport, and centers it.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct AspectRatio {
     defer: bool,
@@ -93,6 +98,31 @@ impl Default for AspectRatio {
 }
 
 impl AspectRatio {
+    /// Parses the value of a `preserveAspectRatio` attribute.
+    ///
+    /// The syntax is `[defer] <align> [<meetOrSlice>]`, where:
+    ///
+    /// * `<align>` is `none`, or one of `xMinYMin`, `xMidYMin`, `xMaxYMin`, `xMinYMid`,
+    ///   `xMidYMid`, `xMaxYMid`, `xMinYMax`, `xMidYMax`, `xMaxYMax`.
+    ///
+    /// * `<meetOrSlice>` is `meet` (the default) to fit the whole viewbox inside the
+    ///   viewport, or `slice` to cover the whole viewport with the viewbox.
+    ///
+    /// * `defer` is accepted for compatibility with SVG 1.1; it only has an effect on
+    ///   `<image>` elements that reference SVG documents.
+    ///
+    /// ```
+    /// let aspect = rsvg::AspectRatio::parse_str("xMinYMax slice").unwrap();
+    /// assert!(aspect.is_slice());
+    ///
+    /// assert!(rsvg::AspectRatio::parse_str("xMidYMid fit").is_err());
+    /// ```
+    pub fn parse_str(s: &str) -> Result<AspectRatio, AspectRatioParseError> {
+        <AspectRatio as Parse>::parse_str(s).map_err(|e| AspectRatioParseError {
+            message: e.to_string(),
+        })
+    }
+
     /// Produces the equivalent of `preserveAspectRatio="none"`.
     pub fn none() -> AspectRatio {
         AspectRatio {
@@ -101,6 +131,7 @@ impl AspectRatio {
         }
     }
 
+    /// Returns whether the viewbox covers the whole viewport, possibly overflowing it.
     pub fn is_slice(&self) -> bool {
         matches!(
             self.align,
@@ -111,6 +142,20 @@ impl AspectRatio {
         )
     }
 
+    /// Computes where the `vbox` goes when it is fitted into the `viewport`.
+    ///
+    /// The result has the proportions of the `vbox`, unless the aspect ratio is `none`;
+    /// in that case the result is the `viewport` itself.
+    ///
+    /// ```
+    /// use rsvg::{AspectRatio, Rect, ViewBox};
+    ///
+    /// let aspect = AspectRatio::parse_str("xMidYMid meet").unwrap();
+    /// let vbox = ViewBox::from(Rect::new(0.0, 0.0, 200.0, 100.0));
+    /// let viewport = Rect::new(0.0, 0.0, 100.0, 300.0);
+    ///
+    /// assert_eq!(aspect.compute(&vbox, &viewport), Rect::new(0.0, 125.0, 100.0, 175.0));
+    /// ```
     pub fn compute(&self, vbox: &ViewBox, viewport: &Rect) -> Rect {
         match self.align {
             None => *viewport,
@@ -33,6 +33,13 @@ impl BoundingBox {
         }
     }
 
+    /// Returns this bounding box converted to the coordinate space of `transform`.
+    pub fn in_space(&self, transform: Transform) -> BoundingBox {
+        let mut bbox = BoundingBox::new().with_transform(transform);
+        bbox.insert(self);
+        bbox
+    }
+
     pub fn clear(mut self) {
         self.rect = None;
         self.ink_rect = None;
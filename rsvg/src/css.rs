@@ -93,11 +93,12 @@ use std::fmt;
 use std::str;
 use std::str::FromStr;
 
+use crate::custom_properties::{has_variable_references, parse_unparsed_value};
 use crate::element::Element;
 use crate::error::*;
 use crate::io::{self, BinaryData};
 use crate::node::{Node, NodeBorrow, NodeCascade};
-use crate::properties::{parse_value, ComputedValues, ParseAs, ParsedProperty};
+use crate::properties::{is_property_name, parse_value, ComputedValues, ParseAs, ParsedProperty};
 use crate::rsvg_log;
 use crate::session::Session;
 use crate::url_resolver::{AllowedUrl, UrlResolver};
@@ -105,15 +106,29 @@ use crate::url_resolver::{AllowedUrl, UrlResolver};
 /// A parsed CSS declaration
 ///
 /// For example, in the declaration `fill: green !important`, the
-/// `prop_name` would be `fill`, the `property` would be
-/// `ParsedProperty::Fill(...)` with the green value, and `important`
-/// would be `true`.
+/// `prop_name` would be `fill`, the `value` would be
+/// `DeclaredValue::Property(ParsedProperty::Fill(...))` with the green value,
+/// and `important` would be `true`.
 pub struct Declaration {
     pub prop_name: QualName,
-    pub property: ParsedProperty,
+    pub value: DeclaredValue,
     pub important: bool,
 }
 
+/// The value of a CSS declaration.
+pub enum DeclaredValue {
+    /// A value for one of the properties that librsvg supports.
+    Property(ParsedProperty),
+
+    /// The unparsed value of a custom property, like `--brand: #3584e4`.
+    Custom(String),
+
+    /// The unparsed value of a property that has `var()` references, like
+    /// `fill: var(--brand)`.  It gets parsed after substituting the references, when
+    /// computing the element's values.
+    WithVariables(String),
+}
+
 /// This enum represents the fact that a rule body can be either a
 /// declaration or a nested rule.
 pub enum RuleBodyItem {
@@ -139,13 +154,41 @@ impl<'i> DeclarationParser<'i> for DeclParser {
         input: &mut Parser<'i, 't>,
     ) -> Result<RuleBodyItem, cssparser::ParseError<'i, Self::Error>> {
         let prop_name = QualName::new(None, ns!(), markup5ever::LocalName::from(name.as_ref()));
+
+        if name.starts_with("--") {
+            let (value, important) = parse_unparsed_value(input);
+
+            return Ok(RuleBodyItem::Decl(Declaration {
+                prop_name,
+                value: DeclaredValue::Custom(value),
+                important,
+            }));
+        }
+
+        let state = input.state();
+        let (value, important) = parse_unparsed_value(input);
+
+        if has_variable_references(&value) {
+            if !is_property_name(&prop_name) {
+                return Err(input.new_custom_error(ValueErrorKind::UnknownProperty));
+            }
+
+            return Ok(RuleBodyItem::Decl(Declaration {
+                prop_name,
+                value: DeclaredValue::WithVariables(value),
+                important,
+            }));
+        }
+
+        input.reset(&state);
+
         let property = parse_value(&prop_name, input, ParseAs::Property)?;
 
         let important = input.try_parse(parse_important).is_ok();
 
         Ok(RuleBodyItem::Decl(Declaration {
             prop_name,
-            property,
+            value: DeclaredValue::Property(property),
             important,
         }))
     }
@@ -325,6 +368,8 @@ impl<'i> QualifiedRuleParser<'i> for RuleParser {
                 Ok(RuleBodyItem::Rule(_)) => None,
                 Err(e) => {
                     rsvg_log!(self.session, "Invalid declaration; ignoring: {:?}", e);
+                    self.session
+                        .add_unsupported(format!("invalid CSS declaration \"{}\"", e.1));
                     None
                 }
             })
@@ -813,6 +858,41 @@ pub enum Origin {
     Author,
 }
 
+/// Origin for a stylesheet supplied by the calling program.
+///
+/// This determines how the stylesheet's rules compete with the ones from other stylesheets
+/// during the CSS cascade.  Among normal declarations, those from `Author` stylesheets win
+/// over those from `User` stylesheets, which in turn win over those from `UserAgent`
+/// stylesheets.  The SVG document's own `<style>` elements and `style` attributes have the
+/// `Author` origin.
+///
+/// Among stylesheets of the same origin, rules with higher specificity win; for rules of
+/// equal specificity, the stylesheet that was added last wins.
+///
+/// CSS2.2: <https://www.w3.org/TR/CSS22/cascade.html#cascading-order>
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StylesheetOrigin {
+    /// Styles at the same level as librsvg's own defaults; the document's styles win over them.
+    UserAgent,
+
+    /// Styles from the user of the program.
+    User,
+
+    /// Styles at the same level as the document's own styles.
+    Author,
+}
+
+impl From<StylesheetOrigin> for Origin {
+    fn from(o: StylesheetOrigin) -> Origin {
+        match o {
+            StylesheetOrigin::UserAgent => Origin::UserAgent,
+            StylesheetOrigin::User => Origin::User,
+            StylesheetOrigin::Author => Origin::Author,
+        }
+    }
+}
+
 /// A parsed CSS stylesheet.
 pub struct Stylesheet {
     origin: Origin,
@@ -881,13 +961,17 @@ impl Stylesheet {
     }
 
     /// Parses a new stylesheet by loading CSS data from a URL.
+    ///
+    /// The `aurl` must have been obtained from `url_resolver`, which is also used for
+    /// the stylesheet's `@import` rules.
     pub fn from_href(
         aurl: &AllowedUrl,
+        url_resolver: &UrlResolver,
         origin: Origin,
         session: Session,
     ) -> Result<Self, LoadingError> {
         let mut stylesheet = Stylesheet::empty(origin);
-        stylesheet.load(aurl, session)?;
+        stylesheet.load(aurl, url_resolver, session)?;
         Ok(stylesheet)
     }
 
@@ -922,7 +1006,7 @@ impl Stylesheet {
                 Rule::AtRule(AtRule::Import(url)) => match url_resolver.resolve_href(&url) {
                     Ok(aurl) => {
                         // ignore invalid imports
-                        let _ = self.load(&aurl, session.clone());
+                        let _ = self.load(&aurl, url_resolver, session.clone());
                     }
 
                     Err(e) => {
@@ -937,7 +1021,15 @@ impl Stylesheet {
     }
 
     /// Parses a stylesheet referenced by an URL
-    fn load(&mut self, aurl: &AllowedUrl, session: Session) -> Result<(), LoadingError> {
+    ///
+    /// Its own `@import` rules are resolved relative to `aurl`, with the same policy as
+    /// `url_resolver`.
+    fn load(
+        &mut self,
+        aurl: &AllowedUrl,
+        url_resolver: &UrlResolver,
+        session: Session,
+    ) -> Result<(), LoadingError> {
         io::acquire_data(aurl, None)
             .map_err(LoadingError::from)
             .and_then(|data| {
@@ -964,8 +1056,8 @@ impl Stylesheet {
                 })
             })
             .and_then(|utf8| {
-                let url = (**aurl).clone();
-                self.add_rules_from_string(&utf8, &UrlResolver::new(Some(url)), session)
+                let url_resolver = url_resolver.with_base_url(Some((**aurl).clone()));
+                self.add_rules_from_string(&utf8, &url_resolver, session)
             })
     }
 
@@ -0,0 +1,377 @@
+//! CSS custom properties and `var()` substitution.
+//!
+//! Custom properties are declarations like `--brand: #3584e4` whose names start with two
+//! dashes.  Librsvg does not interpret their values; it keeps them as unparsed CSS, and they
+//! get inherited by all descendants.  Other properties can reference them with `var(--brand)`
+//! or `var(--brand, black)`, and those references get substituted at computed-value time,
+//! before the property's value is parsed.
+//!
+//! <https://www.w3.org/TR/css-variables-1/>
+
+use cssparser::{parse_important, Parser, ParserInput, Token};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::error::*;
+
+/// The computed values of the custom properties for an element.
+///
+/// This is cheap to clone, since it is stored in the computed values of every element.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CustomProperties(Rc<HashMap<String, String>>);
+
+impl CustomProperties {
+    /// Creates a set of custom properties from their names, like `--brand`, and values.
+    ///
+    /// The values are taken literally; they don't get `var()` references substituted.
+    pub fn new(values: HashMap<String, String>) -> CustomProperties {
+        CustomProperties(Rc::new(values))
+    }
+
+    /// Computes the custom properties of an element.
+    ///
+    /// `self` has the properties inherited from the element's parent, and `declared` has the
+    /// element's own declarations as `(name, value)` pairs.  Declarations that reference
+    /// undefined properties without a fallback, or that are part of a reference cycle, are
+    /// invalid; they are left out of the result, as if they had not been declared.
+    pub fn cascade(&self, declared: &[(String, String)]) -> CustomProperties {
+        if declared.is_empty() {
+            return self.clone();
+        }
+
+        let mut resolver = Resolver {
+            inherited: self,
+            declared: declared
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str()))
+                .collect(),
+            resolved: HashMap::new(),
+            in_progress: Vec::new(),
+            cyclic: Vec::new(),
+        };
+
+        let mut values = (*self.0).clone();
+
+        for (name, _) in declared {
+            match resolver.resolve(name) {
+                Some(value) => values.insert(name.clone(), value),
+                None => values.remove(name),
+            };
+        }
+
+        CustomProperties(Rc::new(values))
+    }
+
+    /// Substitutes the `var()` references in `value` with the values of these properties.
+    ///
+    /// Returns `None` if `value` references an undefined property without a fallback, or if
+    /// a `var()` is malformed.  In both cases the declaration that contains `value` is
+    /// invalid at computed-value time.
+    pub fn substitute(&self, value: &str) -> Option<String> {
+        substitute_variables(value, &mut |name| self.0.get(name).cloned())
+    }
+}
+
+/// Resolves the `var()` references among an element's own custom properties.
+struct Resolver<'a> {
+    inherited: &'a CustomProperties,
+    declared: HashMap<&'a str, &'a str>,
+    resolved: HashMap<&'a str, Option<String>>,
+
+    /// Stack of the properties being resolved, to detect cycles.
+    in_progress: Vec<&'a str>,
+
+    /// Properties that are part of a cycle; these are all invalid.
+    cyclic: Vec<&'a str>,
+}
+
+impl<'a> Resolver<'a> {
+    fn resolve(&mut self, name: &str) -> Option<String> {
+        let Some((&name, &value)) = self.declared.get_key_value(name) else {
+            return self.inherited.0.get(name).cloned();
+        };
+
+        if let Some(resolved) = self.resolved.get(name) {
+            return resolved.clone();
+        }
+
+        if let Some(pos) = self.in_progress.iter().position(|n| *n == name) {
+            self.cyclic.extend_from_slice(&self.in_progress[pos..]);
+            return None;
+        }
+
+        self.in_progress.push(name);
+        let substituted = substitute_variables(value, &mut |n| self.resolve(n));
+        self.in_progress.pop();
+
+        let resolved = if self.cyclic.contains(&name) {
+            None
+        } else {
+            substituted
+        };
+
+        self.resolved.insert(name, resolved.clone());
+        resolved
+    }
+}
+
+/// Consumes the rest of a declaration's value and returns it as unparsed CSS, along with
+/// whether the declaration ends in `!important`.
+pub fn parse_unparsed_value(input: &mut Parser<'_, '_>) -> (String, bool) {
+    let start = input.position();
+    let mut end = start;
+
+    let important = loop {
+        if input
+            .try_parse(|p| {
+                parse_important(p)?;
+                p.expect_exhausted()
+            })
+            .is_ok()
+        {
+            break true;
+        }
+
+        let is_block = match input.next_including_whitespace_and_comments() {
+            Ok(token) => opens_block(token),
+            Err(_) => break false,
+        };
+
+        if is_block {
+            // Consume the whole block, so that `end` includes its closing token.
+            let _ = input.parse_nested_block(|p| {
+                while p.next_including_whitespace_and_comments().is_ok() {}
+                Ok::<_, ParseError<'_>>(())
+            });
+        }
+
+        end = input.position();
+    };
+
+    (input.slice(start..end).trim().to_string(), important)
+}
+
+/// Whether an unparsed `value` has any `var()` functions in it.
+pub fn has_variable_references(value: &str) -> bool {
+    let mut found = false;
+
+    substitute_variables(value, &mut |_| {
+        found = true;
+        None
+    });
+
+    found
+}
+
+fn opens_block(token: &Token<'_>) -> bool {
+    matches!(
+        *token,
+        Token::Function(_)
+            | Token::ParenthesisBlock
+            | Token::SquareBracketBlock
+            | Token::CurlyBracketBlock
+    )
+}
+
+fn substitute_variables(
+    value: &str,
+    lookup: &mut dyn FnMut(&str) -> Option<String>,
+) -> Option<String> {
+    let mut input = ParserInput::new(value);
+    let mut parser = Parser::new(&mut input);
+
+    let mut result = String::new();
+    substitute(&mut parser, lookup, &mut result).ok()?;
+
+    Some(result.trim().to_string())
+}
+
+fn substitute<'i>(
+    input: &mut Parser<'i, '_>,
+    lookup: &mut dyn FnMut(&str) -> Option<String>,
+    out: &mut String,
+) -> Result<(), ParseError<'i>> {
+    loop {
+        let start = input.position();
+
+        let token = match input.next_including_whitespace_and_comments() {
+            Ok(token) => token.clone(),
+            Err(_) => return Ok(()),
+        };
+
+        match token {
+            Token::Function(ref name) if name.eq_ignore_ascii_case("var") => {
+                let value = input.parse_nested_block(|p| parse_var_function(p, lookup))?;
+                out.push_str(&value);
+            }
+
+            ref t if opens_block(t) => {
+                out.push_str(input.slice_from(start));
+                input.parse_nested_block(|p| substitute(p, lookup, out))?;
+                out.push_str(match *t {
+                    Token::SquareBracketBlock => "]",
+                    Token::CurlyBracketBlock => "}",
+                    _ => ")",
+                });
+            }
+
+            _ => out.push_str(input.slice_from(start)),
+        }
+    }
+}
+
+/// Parses the arguments of `var(--name [, fallback])` and returns its substituted value.
+fn parse_var_function<'i>(
+    input: &mut Parser<'i, '_>,
+    lookup: &mut dyn FnMut(&str) -> Option<String>,
+) -> Result<String, ParseError<'i>> {
+    let loc = input.current_source_location();
+
+    let name = input.expect_ident()?.to_string();
+    if !name.starts_with("--") {
+        return Err(loc.new_custom_error(ValueErrorKind::parse_error(
+            "expected a custom property name",
+        )));
+    }
+
+    let has_fallback = !input.is_exhausted();
+    if has_fallback {
+        input.expect_comma()?;
+    }
+
+    // The fallback is only substituted if it is needed, so that references in it to
+    // undefined properties, or back to the property being resolved, don't invalidate a
+    // declaration whose primary reference is defined.
+    if let Some(value) = lookup(&name) {
+        while input.next_including_whitespace_and_comments().is_ok() {}
+        return Ok(value);
+    }
+
+    if has_fallback {
+        let mut fallback = String::new();
+        substitute(input, lookup, &mut fallback)?;
+        Ok(fallback.trim().to_string())
+    } else {
+        Err(loc.new_custom_error(ValueErrorKind::value_error(&format!(
+            "undefined custom property {name}"
+        ))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn properties(values: &[(&str, &str)]) -> CustomProperties {
+        CustomProperties::new(
+            values
+                .iter()
+                .map(|&(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        )
+    }
+
+    fn declared(values: &[(&str, &str)]) -> Vec<(String, String)> {
+        values
+            .iter()
+            .map(|&(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn parse(s: &str) -> (String, bool) {
+        let mut input = ParserInput::new(s);
+        let mut parser = Parser::new(&mut input);
+        parse_unparsed_value(&mut parser)
+    }
+
+    #[test]
+    fn parses_unparsed_values() {
+        assert_eq!(parse("  red "), (String::from("red"), false));
+        assert_eq!(
+            parse("rgb(1, 2, 3) !important"),
+            (String::from("rgb(1, 2, 3)"), true)
+        );
+        assert_eq!(
+            parse("var(--a, var(--b))"),
+            (String::from("var(--a, var(--b))"), false)
+        );
+    }
+
+    #[test]
+    fn detects_variable_references() {
+        assert!(has_variable_references("var(--brand)"));
+        assert!(has_variable_references("rgb(var(--r), 0, 0)"));
+        assert!(!has_variable_references("url(#var)"));
+        assert!(!has_variable_references("red"));
+    }
+
+    #[test]
+    fn substitutes_variables() {
+        let props = properties(&[("--brand", "#ff0000"), ("--r", "255")]);
+
+        assert_eq!(props.substitute("var(--brand)").unwrap(), "#ff0000");
+        assert_eq!(
+            props.substitute("rgb(var(--r), 0, 0)").unwrap(),
+            "rgb(255, 0, 0)"
+        );
+        assert_eq!(props.substitute("1px var(--r)").unwrap(), "1px 255");
+    }
+
+    #[test]
+    fn uses_fallbacks_for_undefined_variables() {
+        let props = properties(&[("--brand", "lime")]);
+
+        assert_eq!(props.substitute("var(--other, blue)").unwrap(), "blue");
+        assert_eq!(
+            props.substitute("var(--other, var(--brand))").unwrap(),
+            "lime"
+        );
+        assert_eq!(props.substitute("var(--brand, blue)").unwrap(), "lime");
+        assert_eq!(
+            props.substitute("var(--brand, var(--other))").unwrap(),
+            "lime"
+        );
+        assert!(props.substitute("var(--other, var(--undefined))").is_none());
+        assert!(props.substitute("var(--other)").is_none());
+        assert!(props.substitute("var(other, blue)").is_none());
+    }
+
+    #[test]
+    fn cascades_declared_properties() {
+        let inherited = properties(&[("--a", "red"), ("--b", "blue")]);
+
+        let computed =
+            inherited.cascade(&declared(&[("--b", "green"), ("--c", "var(--a) var(--b)")]));
+
+        assert_eq!(
+            computed,
+            properties(&[("--a", "red"), ("--b", "green"), ("--c", "red green")])
+        );
+    }
+
+    #[test]
+    fn cycles_are_invalid() {
+        let inherited = properties(&[("--a", "red")]);
+
+        let computed = inherited.cascade(&declared(&[
+            ("--a", "var(--b)"),
+            ("--b", "var(--a, blue)"),
+            ("--c", "var(--c)"),
+            ("--d", "var(--a, yellow)"),
+        ]));
+
+        assert_eq!(computed, properties(&[("--d", "yellow")]));
+    }
+
+    #[test]
+    fn unused_fallbacks_do_not_make_cycles() {
+        let inherited = properties(&[("--b", "green")]);
+
+        let computed = inherited.cascade(&declared(&[
+            ("--a", "var(--b, var(--a))"),
+            ("--c", "var(--undefined, var(--c))"),
+        ]));
+
+        assert_eq!(computed, properties(&[("--a", "green"), ("--b", "green")]));
+    }
+}
@@ -4,7 +4,7 @@ use cssparser::Parser;
 
 use crate::error::*;
 use crate::length::*;
-use crate::parsers::{optional_comma, Parse};
+use crate::parsers::{optional_comma, Parse, ToCssString};
 
 #[derive(Debug, Default, PartialEq, Clone)]
 pub enum Dasharray {
@@ -39,9 +39,54 @@ impl Parse for Dasharray {
     }
 }
 
+impl Dasharray {
+    /// Resolves the dash lengths to user-space units, in the form that cairo expects.
+    ///
+    /// Percentages are relative to the normalized diagonal of the viewport, like other
+    /// lengths that are not horizontal nor vertical.  Per the [spec], an odd number of
+    /// values gets repeated to yield an even number, and if all the values are zero the
+    /// stroke is solid; this returns an empty slice in that case, as for `none`.
+    ///
+    /// [spec]: https://www.w3.org/TR/SVG2/painting.html#StrokeDashing
+    pub fn to_user(&self, params: &NormalizeParams) -> Box<[f64]> {
+        match self {
+            Dasharray::None => Box::new([]),
+
+            Dasharray::Array(dashes) => {
+                let dashes: Vec<f64> = dashes.iter().map(|l| l.to_user(params)).collect();
+
+                let total_length: f64 = dashes.iter().sum();
+                if total_length <= 0.0 {
+                    return Box::new([]);
+                }
+
+                if dashes.len() % 2 == 1 {
+                    dashes.repeat(2).into_boxed_slice()
+                } else {
+                    dashes.into_boxed_slice()
+                }
+            }
+        }
+    }
+}
+
+impl ToCssString for Dasharray {
+    fn to_css_string(&self) -> String {
+        match self {
+            Dasharray::None => "none".to_string(),
+            Dasharray::Array(a) => a
+                .iter()
+                .map(|l| l.to_css_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::dpi::Dpi;
 
     fn dasharray(l: &[ULength<Both>]) -> Dasharray {
         Dasharray::Array(l.to_vec().into_boxed_slice())
@@ -105,4 +150,48 @@ mod tests {
         // A comma should be followed by a number
         assert!(Dasharray::parse_str("20,,10").is_err());
     }
+
+    #[test]
+    fn resolves_percentages_against_the_normalized_diagonal() {
+        let params =
+            NormalizeParams::from_dpi(Dpi::new(96.0, 96.0)).with_viewport_size(300.0, 400.0);
+
+        let dashes = Dasharray::parse_str("50%").unwrap().to_user(&params);
+        let half_diagonal = 0.5 * (300.0f64 * 300.0 + 400.0 * 400.0).sqrt() / 2.0f64.sqrt();
+
+        assert_eq!(dashes.len(), 2);
+        assert!((dashes[0] - half_diagonal).abs() < 1e-9);
+        assert!((dashes[1] - half_diagonal).abs() < 1e-9);
+    }
+
+    #[test]
+    fn repeats_odd_number_of_dashes() {
+        let params = NormalizeParams::from_dpi(Dpi::new(96.0, 96.0));
+
+        assert_eq!(
+            &*Dasharray::parse_str("4 2 6").unwrap().to_user(&params),
+            &[4.0, 2.0, 6.0, 4.0, 2.0, 6.0]
+        );
+        assert_eq!(
+            &*Dasharray::parse_str("4 2").unwrap().to_user(&params),
+            &[4.0, 2.0]
+        );
+    }
+
+    #[test]
+    fn zero_dashes_mean_a_solid_stroke() {
+        let params = NormalizeParams::from_dpi(Dpi::new(96.0, 96.0));
+
+        assert!(Dasharray::parse_str("0 0 0")
+            .unwrap()
+            .to_user(&params)
+            .is_empty());
+        assert!(Dasharray::None.to_user(&params).is_empty());
+
+        // Without a viewport, percentages resolve to zero.
+        assert!(Dasharray::parse_str("50%")
+            .unwrap()
+            .to_user(&params)
+            .is_empty());
+    }
 }
@@ -1,8 +1,7 @@
 //! Main SVG document structure.
 
 use data_url::mime::Mime;
-use glib::prelude::*;
-use markup5ever::QualName;
+use markup5ever::{expanded_name, local_name, namespace_url, ns, QualName};
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::fmt;
@@ -14,25 +13,40 @@ use std::sync::Arc;
 use std::{cell::RefCell, sync::OnceLock};
 
 use crate::accept_language::UserLanguage;
+use crate::animation;
 use crate::bbox::BoundingBox;
 use crate::borrow_element_as;
 use crate::css::{self, Origin, Stylesheet};
+use crate::custom_properties::CustomProperties;
 use crate::dpi::Dpi;
 use crate::drawing_ctx::{
-    draw_tree, with_saved_cr, DrawingMode, RenderingConfiguration, SvgNesting,
+    draw_tree, set_source_color_on_cairo, with_saved_cr, DrawingMode, GeometryRecorder,
+    RegionCuller, RegionRecorder, RenderingConfiguration, SvgNesting, Viewport,
 };
+use crate::element::ElementData;
 use crate::error::{AcquireError, InternalRenderingError, LoadingError, NodeIdError};
-use crate::io::{self, BinaryData};
+use crate::filter::{FilterSpecCache, FilterValue};
+use crate::io::{self, BinaryData, Cancellable};
 use crate::is_element_of_type;
-use crate::limits;
-use crate::node::{CascadedValues, Node, NodeBorrow, NodeData};
-use crate::rect::Rect;
+use crate::length::NormalizeParams;
+use crate::limits::{self, RenderBudget};
+use crate::node::{CascadedValues, Node, NodeBorrow, NodeCascade, NodeData};
+use crate::paint_server::PaintServer;
+use crate::properties::{ComputedValues, Filter, XmlSpace};
+use crate::rect::{IRect, Rect};
+use crate::render_observer::RenderEvents;
 use crate::rsvg_log;
 use crate::session::Session;
+use crate::space::WhitespaceMode;
 use crate::structure::IntrinsicDimensions;
-use crate::surface_utils::shared_surface::SharedImageSurface;
-use crate::url_resolver::{AllowedUrl, UrlResolver};
-use crate::xml::{xml_load_from_possibly_compressed_stream, Attributes};
+use crate::surface_utils::shared_surface::{SharedImageSurface, SurfaceType};
+use crate::text::{FontMap, FontWarnings};
+use crate::transform::{Transform, ValidTransform};
+use crate::url_resolver::{AllowedUrl, ReferenceKind, ReferencedUrl, UrlResolver};
+use crate::viewbox::ViewBox;
+use crate::xml::{xml_load_from_bytes, Attributes};
+#[cfg(feature = "gio")]
+use crate::xml::{xml_load_from_possibly_compressed_stream, XmlPushLoader};
 
 /// Identifier of a node
 #[derive(Debug, PartialEq, Clone)]
@@ -70,6 +84,115 @@ impl fmt::Display for NodeId {
     }
 }
 
+/// Returns the URLs that an element references, either from its attributes or properties.
+///
+/// References to elements in the same document are not included.
+fn element_references(node: &Node) -> Vec<(ReferenceKind, String)> {
+    fn external(node_id: &NodeId) -> Option<String> {
+        match node_id {
+            NodeId::External(url, _) => Some(url.clone()),
+            NodeId::Internal(_) => None,
+        }
+    }
+
+    let mut references = Vec::new();
+
+    match *node.borrow_element_data() {
+        ElementData::Image(ref image) => {
+            references.extend(
+                image
+                    .href()
+                    .map(|href| (ReferenceKind::Image, href.to_string())),
+            );
+        }
+
+        ElementData::FeImage(ref fe_image) => {
+            // feImage can reference either an image or an element.
+            if let Some(href) = fe_image.href() {
+                match NodeId::parse(href) {
+                    Ok(node_id) => {
+                        references.extend(external(&node_id).map(|url| (ReferenceKind::Image, url)))
+                    }
+                    Err(_) => references.push((ReferenceKind::Image, href.to_string())),
+                }
+            }
+        }
+
+        ElementData::Use(ref use_) => {
+            references.extend(
+                use_.link()
+                    .and_then(external)
+                    .map(|url| (ReferenceKind::Use, url)),
+            );
+        }
+
+        _ => (),
+    }
+
+    let element = node.borrow_element();
+    let values = element.get_computed_values();
+
+    let mut node_ids = Vec::new();
+
+    for paint in [values.fill().0, values.stroke().0] {
+        if let PaintServer::Iri { iri, .. } = paint {
+            node_ids.push(*iri);
+        }
+    }
+
+    if let Filter::List(list) = values.filter() {
+        node_ids.extend(list.iter().filter_map(|f| match f {
+            FilterValue::Url(node_id) => Some(node_id.clone()),
+            FilterValue::Function(_) => None,
+        }));
+    }
+
+    for iri in [
+        values.clip_path().0,
+        values.mask().iri,
+        values.marker_start().0,
+        values.marker_mid().0,
+        values.marker_end().0,
+    ] {
+        node_ids.extend(iri.get().cloned());
+    }
+
+    references.extend(
+        node_ids
+            .iter()
+            .filter_map(external)
+            .map(|url| (ReferenceKind::Property, url)),
+    );
+
+    references
+}
+
+/// Which referenced raster images keep their original, compressed data.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub enum KeepImageData {
+    /// Only keep the decoded pixels.
+    #[default]
+    None,
+
+    /// Keep the data for all images that have a known MIME type.
+    All,
+
+    /// Keep the data only for images with one of these MIME types, like `image/jpeg`.
+    Only(Vec<String>),
+}
+
+impl KeepImageData {
+    fn keeps(&self, content_type: &str) -> bool {
+        match *self {
+            KeepImageData::None => false,
+            KeepImageData::All => true,
+            KeepImageData::Only(ref types) => {
+                types.iter().any(|t| t.eq_ignore_ascii_case(content_type))
+            }
+        }
+    }
+}
+
 /// Loading options for SVG documents.
 pub struct LoadOptions {
     /// Load url resolver; all references will be resolved with respect to this.
@@ -79,7 +202,10 @@ pub struct LoadOptions {
     pub unlimited_size: bool,
 
     /// Whether to keep original (undecoded) image data to embed in Cairo PDF surfaces.
-    pub keep_image_data: bool,
+    pub keep_image_data: KeepImageData,
+
+    /// Document-wide override for `xml:space`.
+    pub whitespace_mode: WhitespaceMode,
 }
 
 impl LoadOptions {
@@ -88,7 +214,8 @@ impl LoadOptions {
         LoadOptions {
             url_resolver,
             unlimited_size: false,
-            keep_image_data: false,
+            keep_image_data: KeepImageData::None,
+            whitespace_mode: WhitespaceMode::Default,
         }
     }
 
@@ -100,16 +227,22 @@ impl LoadOptions {
         self
     }
 
-    /// Sets whether to keep the original compressed image data from referenced JPEG/PNG images.
+    /// Sets which referenced images keep their original compressed data.
     ///
     /// This is only useful for rendering to Cairo PDF
     /// surfaces, which can embed the original, compressed image data instead of uncompressed
     /// RGB buffers.
-    pub fn keep_image_data(mut self, keep: bool) -> Self {
+    pub fn keep_image_data(mut self, keep: KeepImageData) -> Self {
         self.keep_image_data = keep;
         self
     }
 
+    /// Sets how whitespace in text content should be handled for the whole document.
+    pub fn with_whitespace_mode(mut self, mode: WhitespaceMode) -> Self {
+        self.whitespace_mode = mode;
+        self
+    }
+
     /// Creates a new `LoadOptions` with a different `url resolver`.
     ///
     /// This is used when loading a referenced file that may in turn cause other files
@@ -121,7 +254,8 @@ impl LoadOptions {
         LoadOptions {
             url_resolver,
             unlimited_size: self.unlimited_size,
-            keep_image_data: self.keep_image_data,
+            keep_image_data: self.keep_image_data.clone(),
+            whitespace_mode: self.whitespace_mode,
         }
     }
 }
@@ -132,10 +266,18 @@ impl LoadOptions {
 /// parameters are known.
 pub struct RenderingOptions {
     pub dpi: Dpi,
-    pub cancellable: Option<gio::Cancellable>,
+    pub cancellable: Option<Cancellable>,
     pub user_language: UserLanguage,
     pub svg_nesting: SvgNesting,
     pub testing: bool,
+    pub font_warnings: FontWarnings,
+    pub render_budget: Rc<RenderBudget>,
+    pub render_events: Option<RenderEvents>,
+    pub animation_time: Option<f64>,
+    pub current_color: Option<cssparser::Color>,
+    pub custom_properties: Option<CustomProperties>,
+    pub font_map: Option<FontMap>,
+    pub circular_reference_errors: bool,
 }
 
 impl RenderingOptions {
@@ -147,6 +289,15 @@ impl RenderingOptions {
             user_language: self.user_language.clone(),
             svg_nesting: self.svg_nesting,
             testing: self.testing,
+            font_warnings: self.font_warnings.clone(),
+            render_budget: self.render_budget.clone(),
+            render_events: self.render_events.clone(),
+            font_map: self.font_map.clone(),
+            geometry_recorder: None,
+            render_area: None,
+            region_recorder: None,
+            region_culler: None,
+            circular_reference_errors: self.circular_reference_errors,
             measuring,
         }
     }
@@ -175,15 +326,63 @@ pub struct Document {
 
     /// Stylesheets defined in the document.
     stylesheets: Vec<Stylesheet>,
+
+    /// URLs from `xml-stylesheet` and `xi:include`, which don't remain in the tree.
+    load_time_references: Vec<(ReferenceKind, String)>,
+
+    /// Filters resolved while rendering, to reuse them in later renders.
+    filter_specs: RefCell<FilterSpecCache>,
+
+    /// The overrides that the tree is currently cascaded with; see
+    /// [`Document::set_cascade_overrides`].  `None` while the cascade is in progress.
+    cascade_overrides: RefCell<Option<CascadeOverrides>>,
+
+    /// Regions recorded by [`Document::render_document_region`], to reuse them when
+    /// rendering other tiles of the same viewport.
+    recorded_regions: RefCell<Option<(RegionKey, Rc<RegionRecorder>)>>,
+}
+
+/// What the regions recorded by [`Document::render_document_region`] depend on, other
+/// than the cascade.
+#[derive(PartialEq)]
+struct RegionKey {
+    viewport: Rect,
+    scale: (f64, f64),
+    dpi: Dpi,
+    user_language: UserLanguage,
+    svg_nesting: SvgNesting,
+    testing: bool,
+    font_map: Option<FontMap>,
+}
+
+/// The parts of [`RenderingOptions`] that change the cascade.
+///
+/// The default value, without overrides, is for the static values of the document.
+#[derive(Default, PartialEq)]
+struct CascadeOverrides {
+    animation_time: Option<f64>,
+    current_color: Option<cssparser::Color>,
+    custom_properties: Option<CustomProperties>,
+}
+
+impl From<&RenderingOptions> for CascadeOverrides {
+    fn from(options: &RenderingOptions) -> CascadeOverrides {
+        CascadeOverrides {
+            animation_time: options.animation_time,
+            current_color: options.current_color,
+            custom_properties: options.custom_properties.clone(),
+        }
+    }
 }
 
 impl Document {
     /// Constructs a `Document` by loading it from a stream.
+    #[cfg(feature = "gio")]
     pub fn load_from_stream(
         session: Session,
         load_options: Arc<LoadOptions>,
         stream: &gio::InputStream,
-        cancellable: Option<&gio::Cancellable>,
+        cancellable: Option<&Cancellable>,
     ) -> Result<Document, LoadingError> {
         xml_load_from_possibly_compressed_stream(
             session.clone(),
@@ -194,17 +393,43 @@ impl Document {
         )
     }
 
+    /// Creates an `XmlPushLoader` to construct a `Document` from data that arrives in chunks.
+    #[cfg(feature = "gio")]
+    pub fn push_loader(
+        session: Session,
+        load_options: Arc<LoadOptions>,
+    ) -> Result<XmlPushLoader, LoadingError> {
+        XmlPushLoader::new(
+            session.clone(),
+            DocumentBuilder::new(session, load_options.clone()),
+            load_options,
+        )
+    }
+
+    /// Constructs a `Document` by loading it from a buffer in memory.
+    ///
+    /// Unlike `Document::load_from_stream`, this does not create a gio stream for the
+    /// data, except for compressed (svgz) data, which needs gio to be decompressed.
+    pub fn load_from_data(
+        session: Session,
+        load_options: Arc<LoadOptions>,
+        data: &[u8],
+    ) -> Result<Document, LoadingError> {
+        xml_load_from_bytes(
+            session.clone(),
+            DocumentBuilder::new(session, load_options.clone()),
+            load_options,
+            data,
+        )
+    }
+
     /// Utility function to load a document from a static string in tests.
     #[cfg(test)]
     pub fn load_from_bytes(input: &'static [u8]) -> Document {
-        let bytes = glib::Bytes::from_static(input);
-        let stream = gio::MemoryInputStream::from_bytes(&bytes);
-
-        Document::load_from_stream(
+        Document::load_from_data(
             Session::new_for_test_suite(),
             Arc::new(LoadOptions::new(UrlResolver::new(None))),
-            &stream.upcast(),
-            None::<&gio::Cancellable>,
+            input,
         )
         .unwrap()
     }
@@ -215,11 +440,7 @@ impl Document {
     }
 
     /// Looks up a node in this document or one of its resources by its `id` attribute.
-    fn lookup_node(
-        &self,
-        node_id: &NodeId,
-        cancellable: Option<&gio::Cancellable>,
-    ) -> Option<Node> {
+    fn lookup_node(&self, node_id: &NodeId, cancellable: Option<&Cancellable>) -> Option<Node> {
         match node_id {
             NodeId::Internal(id) => self.lookup_internal_node(id),
             NodeId::External(url, id) => self
@@ -235,11 +456,59 @@ impl Document {
         self.ids.get(id).map(|n| (*n).clone())
     }
 
+    /// Returns the `id` attributes of all the elements in this document, in document order.
+    ///
+    /// If several elements have the same `id`, only the first one is addressable, so the
+    /// `id` is returned only once.
+    pub fn element_ids(&self) -> Vec<String> {
+        self.tree
+            .descendants()
+            .filter(|n| n.is_element())
+            .filter_map(|n| {
+                let id = n.borrow_element().get_id()?.to_string();
+
+                if self.ids.get(&id) == Some(&n) {
+                    Some(id)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the URLs of the external resources that this document references.
+    ///
+    /// The URLs from `xml-stylesheet` and `xi:include` come first, then the ones from
+    /// elements and properties in document order.  Each URL of a given kind is returned
+    /// only once.  This does not load anything, and does not call the resource callback.
+    pub fn referenced_urls(&self) -> Vec<ReferencedUrl> {
+        self.ensure_static_cascade();
+
+        let mut references = self.load_time_references.clone();
+
+        for node in self.tree.descendants().filter(|n| n.is_element()) {
+            for reference in element_references(&node) {
+                if !references.contains(&reference) {
+                    references.push(reference);
+                }
+            }
+        }
+
+        references
+            .into_iter()
+            .map(|(kind, href)| ReferencedUrl {
+                allowed: self.load_options.url_resolver.check_href(&href).is_ok(),
+                href,
+                kind,
+            })
+            .collect()
+    }
+
     /// Loads a resource by URL, or returns a pre-loaded one.
     fn lookup_resource(
         &self,
         url: &str,
-        cancellable: Option<&gio::Cancellable>,
+        cancellable: Option<&Cancellable>,
     ) -> Result<Resource, LoadingError> {
         let aurl = self
             .load_options
@@ -255,6 +524,13 @@ impl Document {
         )
     }
 
+    /// Returns whether any of the images loaded so far kept their original data.
+    ///
+    /// This includes images referenced from SVG documents that this one references.
+    pub fn has_kept_image_data(&self) -> bool {
+        self.resources.borrow().has_kept_image_data()
+    }
+
     /// Runs the CSS cascade on the document tree
     ///
     /// This uses the default UserAgent stylesheet, the document's internal stylesheets,
@@ -279,9 +555,16 @@ impl Document {
             extra,
             session,
         );
+
+        // Filters depend on computed values, which may have just changed.
+        self.filter_specs.get_mut().clear();
+        *self.recorded_regions.get_mut() = None;
+        *self.cascade_overrides.get_mut() = Some(CascadeOverrides::default());
     }
 
     pub fn get_intrinsic_dimensions(&self) -> IntrinsicDimensions {
+        self.ensure_static_cascade();
+
         let root = self.root();
         let cascaded = CascadedValues::new_from_node(&root);
         let values = cascaded.get();
@@ -299,20 +582,257 @@ impl Document {
         self.render_layer(session, cr, root, viewport, options)
     }
 
-    pub fn render_layer(
+    pub fn render_document_with_transform(
+        &self,
+        session: &Session,
+        cr: &cairo::Context,
+        viewport: &cairo::Rectangle,
+        transform: Transform,
+        options: &RenderingOptions,
+    ) -> Result<(), InternalRenderingError> {
+        cr.status()?;
+
+        let transform = ValidTransform::try_from(transform)?;
+
+        // Translate to the viewport's origin and apply the extra transform there; the
+        // document gets fitted to a viewport of the same size at (0, 0).
+        let fit_viewport = cairo::Rectangle::new(0.0, 0.0, viewport.width(), viewport.height());
+
+        with_saved_cr(cr, || {
+            cr.translate(viewport.x(), viewport.y());
+            cr.transform(transform.into());
+
+            self.render_document(session, cr, &fit_viewport, options)
+        })
+    }
+
+    /// Renders the part of the document that falls inside `region`.
+    ///
+    /// The `region` is in the user space of the toplevel `<svg>` element, that is, in the
+    /// coordinates of its `viewBox`.  The document is laid out in `viewport` as in
+    /// [`Document::render_document`], but everything is clipped to the region.  If there is
+    /// a `background` color, the part of the `viewport` inside the region gets painted with
+    /// it first.
+    ///
+    /// The document gets drawn twice.  The first pass records the filter effects regions
+    /// and the pixels that each element can change; see [`Document::recorded_regions`].
+    /// The second pass only covers the region plus the filter effects regions that reach
+    /// into it, so that filters get the same input as when rendering the whole document,
+    /// and it skips the elements outside of that.
+    pub fn render_document_region(
         &self,
         session: &Session,
         cr: &cairo::Context,
-        node: Node,
         viewport: &cairo::Rectangle,
+        region: &cairo::Rectangle,
+        background: Option<&cssparser::Color>,
         options: &RenderingOptions,
     ) -> Result<(), InternalRenderingError> {
         cr.status()?;
 
+        // If the toplevel `<svg>` does not establish a usable coordinate system,
+        // nothing gets rendered anyway.
+        let transform = self.with_cascade_overrides(options, || {
+            self.toplevel_user_space_transform(viewport, options.dpi)
+        });
+        let Some(transform) = transform else {
+            if let Some(color) = background {
+                paint_background(cr, viewport, color)?;
+            }
+
+            return self.render_document(session, cr, viewport, options);
+        };
+
+        let transform = ValidTransform::try_from(transform)?;
+
+        // The region in the pixels of the toplevel viewport's temporary surfaces, which have
+        // the scale of the initial transform but not its translation.
+        let initial_transform =
+            Transform::from(cr.matrix()).pre_translate(viewport.x(), viewport.y());
+        let scale = initial_transform.transform_distance(1.0, 1.0);
+        let tile = IRect::from(
+            transform
+                .post_scale(scale.0, scale.1)
+                .transform_rect(&Rect::from(*region)),
+        );
+
         let root = self.root();
+        let cairo_viewport = viewport;
+        let viewport = Rect::from(*viewport);
+
+        self.with_cascade_overrides(options, || {
+            let recorder = self.recorded_regions(session, viewport, scale, options)?;
+            let area = recorder.area_for_tile(tile);
+
+            let mut config = options.to_rendering_configuration(false);
+            config.render_area = Some(area);
+            config.region_culler = Some(Rc::new(RefCell::new(RegionCuller::new(recorder, area))));
+
+            with_saved_cr(cr, || {
+                // Build the clip path in the document's user space, but clip in the
+                // caller's space; the path is kept in device space.
+                let matrix = cr.matrix();
+                cr.translate(viewport.x0, viewport.y0);
+                cr.transform(transform.into());
+                cr.new_path();
+                cr.rectangle(region.x(), region.y(), region.width(), region.height());
+                cr.set_matrix(matrix);
+                cr.clip();
+
+                if let Some(color) = background {
+                    paint_background(cr, cairo_viewport, color)?;
+                }
+
+                draw_tree(
+                    session.clone(),
+                    DrawingMode::LimitToStack {
+                        node: root.clone(),
+                        root,
+                    },
+                    cr,
+                    viewport,
+                    config,
+                    &mut AcquiredNodes::new(self, options.cancellable.clone()),
+                )
+                .map(|_bbox| ())
+            })
+        })
+    }
+
+    /// Returns the areas that each element can change when the document is drawn in
+    /// `viewport` with the given `scale`, for [`Document::render_document_region`].
+    ///
+    /// These don't depend on the region being rendered, so they are recorded once and
+    /// reused until the viewport, the options, or the cascade change.  The tree must
+    /// already be cascaded for the `options`.
+    fn recorded_regions(
+        &self,
+        session: &Session,
+        viewport: Rect,
+        scale: (f64, f64),
+        options: &RenderingOptions,
+    ) -> Result<Rc<RegionRecorder>, InternalRenderingError> {
+        let key = RegionKey {
+            viewport,
+            scale,
+            dpi: options.dpi,
+            user_language: options.user_language.clone(),
+            svg_nesting: options.svg_nesting,
+            testing: options.testing,
+            font_map: options.font_map.clone(),
+        };
+
+        if let Some((ref cached_key, ref recorder)) = *self.recorded_regions.borrow() {
+            if *cached_key == key {
+                return Ok(recorder.clone());
+            }
+        }
+
+        let root = self.root();
+        let recorder = Rc::new(RefCell::new(RegionRecorder::default()));
+
+        {
+            // Nothing needs to be rendered for real here, so use a tiny target and
+            // tiny temporary surfaces.  The target's device space is the pixels of the
+            // temporary surfaces, so that everything gets recorded in the same space.
+            let target = cairo::ImageSurface::create(cairo::Format::ARgb32, 1, 1)?;
+            let recording_cr = cairo::Context::new(&target)?;
+            recording_cr.scale(scale.0, scale.1);
+            recording_cr.translate(-viewport.x0, -viewport.y0);
+
+            // Only the rendering pass gets reported.
+            let mut config = options.to_rendering_configuration(false);
+            config.font_warnings = Default::default();
+            config.render_events = None;
+            config.render_area = Some(IRect::from_size(1, 1));
+            config.region_recorder = Some(recorder.clone());
+
+            draw_tree(
+                session.clone(),
+                DrawingMode::LimitToStack {
+                    node: root.clone(),
+                    root,
+                },
+                &recording_cr,
+                viewport,
+                config,
+                &mut AcquiredNodes::new(self, options.cancellable.clone()),
+            )?;
+        }
+
+        let recorder = Rc::new(recorder.take());
+        *self.recorded_regions.borrow_mut() = Some((key, recorder.clone()));
+
+        Ok(recorder)
+    }
+
+    /// Computes the transform from the toplevel `<svg>`'s user space to the space of a
+    /// `viewport` with its origin at (0, 0).
+    ///
+    /// This mirrors what the `<svg>` element does when it is drawn as the toplevel
+    /// element.  Returns `None` if the `<svg>` does not establish a usable coordinate
+    /// system, for example if its `viewBox` is empty.
+    fn toplevel_user_space_transform(
+        &self,
+        viewport: &cairo::Rectangle,
+        dpi: Dpi,
+    ) -> Option<Transform> {
+        let root = self.root();
+        let cascaded = CascadedValues::new_from_node(&root);
+        let values = cascaded.get();
+        let svg = borrow_element_as!(root, Svg);
+
+        let geometry = Rect::from_size(viewport.width(), viewport.height());
+
+        let vbox = svg.get_viewbox().or_else(|| {
+            let dimensions = svg.get_intrinsic_dimensions(values);
+            let initial_viewport = Viewport::new(dpi, viewport.width(), viewport.height());
+            let params = NormalizeParams::new(values, &initial_viewport);
+
+            Some(ViewBox::from(Rect::from_size(
+                dimensions.width.to_user(&params),
+                dimensions.height.to_user(&params),
+            )))
+        });
+
+        let vbox_transform = svg
+            .get_preserve_aspect_ratio()
+            .viewport_to_viewbox_transform(vbox, &geometry)
+            .ok()
+            .flatten()?;
+
+        Some(values.transform().pre_transform(&vbox_transform))
+    }
+
+    pub fn render_layer(
+        &self,
+        session: &Session,
+        cr: &cairo::Context,
+        node: Node,
+        viewport: &cairo::Rectangle,
+        options: &RenderingOptions,
+    ) -> Result<(), InternalRenderingError> {
+        cr.status()?;
 
         let viewport = Rect::from(*viewport);
 
+        self.with_cascade_overrides(options, || {
+            self.draw_layer(session, cr, node, viewport, options)
+        })
+    }
+
+    /// Draws `node` as in [`Document::render_layer`], for callers that have already
+    /// applied the cascade overrides from `options` to the tree.
+    fn draw_layer(
+        &self,
+        session: &Session,
+        cr: &cairo::Context,
+        node: Node,
+        viewport: Rect,
+        options: &RenderingOptions,
+    ) -> Result<(), InternalRenderingError> {
+        let root = self.root();
+
         let config = options.to_rendering_configuration(false);
 
         with_saved_cr(cr, || {
@@ -328,6 +848,73 @@ impl Document {
         })
     }
 
+    /// Finds the frontmost element whose painted area contains the point `(x, y)`.
+    ///
+    /// The point is in the `cr`-space of a caller who would render the document into
+    /// `viewport`.  The areas that elements can change are recorded in a single traversal,
+    /// like for [`Document::render_document_region`], to discard the ones that cannot touch
+    /// the point.  The remaining ones are rendered by themselves, starting with the one that
+    /// is painted last, into a one-pixel surface at the point; the first one that leaves a
+    /// mark there is the one that was hit.  This takes into account everything that affects
+    /// painting, like fill, stroke, clipping paths, masks, and `visibility`.  The
+    /// `pointer-events` property is not supported, so elements are hit-tested as if it were
+    /// `visiblePainted`.
+    ///
+    /// Returns the `id` of the element that was hit, or the `id` of its nearest ancestor
+    /// if the element does not have one.
+    pub fn element_at_point(
+        &self,
+        session: &Session,
+        viewport: &cairo::Rectangle,
+        x: f64,
+        y: f64,
+        options: &RenderingOptions,
+    ) -> Result<Option<String>, InternalRenderingError> {
+        let viewport = Rect::from(*viewport);
+
+        // The pixel that gets sampled, in the space where the regions are recorded;
+        // antialiasing lets an element that merely overlaps it leave a mark.
+        let pixel = IRect::from(Rect::new(
+            x - viewport.x0 - 0.5,
+            y - viewport.y0 - 0.5,
+            x - viewport.x0 + 0.5,
+            y - viewport.y0 + 0.5,
+        ));
+
+        // Cascade the tree only once for all the candidates, instead of once per render.
+        self.with_cascade_overrides(options, || -> Result<_, InternalRenderingError> {
+            let recorder = self.recorded_regions(session, viewport, (1.0, 1.0), options)?;
+
+            let candidates = recorder
+                .elements_touching(pixel)
+                .filter(|n| n.is_element() && is_hit_testable(n));
+
+            for node in candidates.rev() {
+                let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, 1, 1)?;
+
+                {
+                    let cr = cairo::Context::new(&surface)?;
+
+                    // Put the point at the center of the only pixel.
+                    cr.translate(0.5 - x, 0.5 - y);
+
+                    self.draw_layer(session, &cr, node.clone(), viewport, options)?;
+                }
+
+                let surface = SharedImageSurface::wrap(surface, SurfaceType::SRgb)?;
+
+                if surface.get_pixel(0, 0).a > 0 {
+                    return Ok(node
+                        .ancestors()
+                        .filter(|n| n.is_element())
+                        .find_map(|n| n.borrow_element().get_id().map(String::from)));
+                }
+            }
+
+            Ok(None)
+        })
+    }
+
     fn geometry_for_layer(
         &self,
         session: &Session,
@@ -342,14 +929,16 @@ impl Document {
 
         let config = options.to_rendering_configuration(true);
 
-        let bbox = draw_tree(
-            session.clone(),
-            DrawingMode::LimitToStack { node, root },
-            &cr,
-            viewport,
-            config,
-            &mut AcquiredNodes::new(self, options.cancellable.clone()),
-        )?;
+        let bbox = self.with_cascade_overrides(options, || {
+            draw_tree(
+                session.clone(),
+                DrawingMode::LimitToStack { node, root },
+                &cr,
+                viewport,
+                config,
+                &mut AcquiredNodes::new(self, options.cancellable.clone()),
+            )
+        })?;
 
         let ink_rect = bbox.ink_rect.unwrap_or_default();
         let logical_rect = bbox.rect.unwrap_or_default();
@@ -374,6 +963,76 @@ impl Document {
         ))
     }
 
+    /// Returns the (ink_rect, logical_rect) of each of the `nodes`, like
+    /// [`Self::get_geometry_for_layer`] would, but with a single traversal of the document.
+    pub fn get_geometries_for_layers(
+        &self,
+        session: &Session,
+        nodes: &[Node],
+        viewport: &cairo::Rectangle,
+        options: &RenderingOptions,
+    ) -> Result<Vec<(cairo::Rectangle, cairo::Rectangle)>, InternalRenderingError> {
+        let viewport = Rect::from(*viewport);
+        let root = self.root();
+
+        let target = cairo::ImageSurface::create(cairo::Format::Rgb24, 1, 1)?;
+        let cr = cairo::Context::new(&target)?;
+
+        let recorder = Rc::new(RefCell::new(GeometryRecorder::new(
+            Transform::from(cr.matrix()),
+            nodes,
+        )));
+
+        let mut config = options.to_rendering_configuration(true);
+        config.geometry_recorder = Some(recorder.clone());
+
+        self.with_cascade_overrides(options, || {
+            draw_tree(
+                session.clone(),
+                DrawingMode::LimitToStack {
+                    node: root.clone(),
+                    root,
+                },
+                &cr,
+                viewport,
+                config,
+                &mut AcquiredNodes::new(self, options.cancellable.clone()),
+            )
+        })?;
+
+        let recorder = recorder.borrow();
+
+        nodes
+            .iter()
+            .map(|node| {
+                let (ink_rect, logical_rect) = match recorder.bbox_for(node) {
+                    Some(bbox) => (
+                        bbox.ink_rect.unwrap_or_default(),
+                        bbox.rect.unwrap_or_default(),
+                    ),
+
+                    // The contents of a <text> are laid out together with it, not drawn
+                    // one by one, so they don't get recorded.  Measure them by themselves.
+                    None if node
+                        .ancestors()
+                        .skip(1)
+                        .any(|a| a.is_element() && is_element_of_type!(a, Text)) =>
+                    {
+                        self.geometry_for_layer(session, node.clone(), viewport, options)?
+                    }
+
+                    // Elements that don't get drawn, like the ones inside <defs>.
+                    None => (Rect::default(), Rect::default()),
+                };
+
+                Ok((
+                    cairo::Rectangle::from(ink_rect),
+                    cairo::Rectangle::from(logical_rect),
+                ))
+            })
+            .collect()
+    }
+
     fn get_bbox_for_element(
         &self,
         session: &Session,
@@ -387,14 +1046,16 @@ impl Document {
 
         let config = options.to_rendering_configuration(true);
 
-        draw_tree(
-            session.clone(),
-            DrawingMode::OnlyNode(node),
-            &cr,
-            unit_rectangle(),
-            config,
-            &mut AcquiredNodes::new(self, options.cancellable.clone()),
-        )
+        self.with_cascade_overrides(options, || {
+            draw_tree(
+                session.clone(),
+                DrawingMode::OnlyNode(node),
+                &cr,
+                unit_rectangle(),
+                config,
+                &mut AcquiredNodes::new(self, options.cancellable.clone()),
+            )
+        })
     }
 
     /// Returns (ink_rect, logical_rect)
@@ -418,12 +1079,18 @@ impl Document {
         ))
     }
 
+    /// Renders a single element so that its ink rectangle fits in `element_viewport`.
+    ///
+    /// If `stretch` is false, the element is scaled proportionally and placed at the
+    /// viewport's top-left corner; otherwise it is scaled independently in each axis to
+    /// fill the whole viewport.
     pub fn render_element(
         &self,
         session: &Session,
         cr: &cairo::Context,
         node: Node,
         element_viewport: &cairo::Rectangle,
+        stretch: bool,
         options: &RenderingOptions,
     ) -> Result<(), InternalRenderingError> {
         cr.status()?;
@@ -444,32 +1111,126 @@ impl Document {
         // Render, transforming so element is at the new viewport's origin
 
         with_saved_cr(cr, || {
-            let factor = (element_viewport.width() / ink_r.width())
-                .min(element_viewport.height() / ink_r.height());
+            let sx = element_viewport.width() / ink_r.width();
+            let sy = element_viewport.height() / ink_r.height();
+
+            let (sx, sy) = if stretch {
+                (sx, sy)
+            } else {
+                let factor = sx.min(sy);
+                (factor, factor)
+            };
 
             cr.translate(element_viewport.x(), element_viewport.y());
-            cr.scale(factor, factor);
+            cr.scale(sx, sy);
             cr.translate(-ink_r.x0, -ink_r.y0);
 
             let config = options.to_rendering_configuration(false);
 
-            draw_tree(
-                session.clone(),
-                DrawingMode::OnlyNode(node),
-                cr,
-                unit_rectangle(),
-                config,
-                &mut AcquiredNodes::new(self, options.cancellable.clone()),
-            )
-            .map(|_bbox| ())
+            self.with_cascade_overrides(options, || {
+                draw_tree(
+                    session.clone(),
+                    DrawingMode::OnlyNode(node),
+                    cr,
+                    unit_rectangle(),
+                    config,
+                    &mut AcquiredNodes::new(self, options.cancellable.clone()),
+                )
+                .map(|_bbox| ())
+            })
         })
     }
+
+    /// Runs `f` with the tree cascaded for the animation time, the root `color`, and the
+    /// custom properties in `options`, if there are any.
+    fn with_cascade_overrides<T>(&self, options: &RenderingOptions, f: impl FnOnce() -> T) -> T {
+        self.set_cascade_overrides(CascadeOverrides::from(options));
+        f()
+    }
+
+    /// Makes sure that the tree has its static computed values.
+    ///
+    /// Rendering with overrides in [`RenderingOptions`] leaves the tree cascaded with them,
+    /// so code that reads computed values outside of rendering must call this first.
+    pub fn ensure_static_cascade(&self) {
+        self.set_cascade_overrides(CascadeOverrides::default());
+    }
+
+    /// Cascades the tree with `overrides`, unless it is already cascaded with them.
+    ///
+    /// The tree stays like that until the next call with different overrides, so that
+    /// repeated renders with the same options don't redo the cascade.
+    fn set_cascade_overrides(&self, overrides: CascadeOverrides) {
+        if self.cascade_overrides.borrow().as_ref() == Some(&overrides) {
+            return;
+        }
+
+        // If the cascade panics halfway, this makes the next call redo it.
+        *self.cascade_overrides.borrow_mut() = None;
+
+        let mut values = ComputedValues::default();
+        if let Some(color) = overrides.current_color {
+            values.set_color(color);
+        }
+        if let Some(ref custom_properties) = overrides.custom_properties {
+            values.set_custom_properties(custom_properties.clone());
+        }
+
+        match overrides.animation_time {
+            Some(time) => animation::cascade_at_time(&mut self.root(), &values, time),
+            None => self.root().cascade(&values),
+        }
+
+        // Filters and recorded regions depend on computed values, so they can't be reused
+        // across the change.
+        self.filter_specs.borrow_mut().clear();
+        *self.recorded_regions.borrow_mut() = None;
+
+        *self.cascade_overrides.borrow_mut() = Some(overrides);
+    }
+}
+
+/// Paints the `viewport` with a background `color`.
+pub fn paint_background(
+    cr: &cairo::Context,
+    viewport: &cairo::Rectangle,
+    color: &cssparser::Color,
+) -> Result<(), cairo::Error> {
+    cr.save()?;
+    cr.new_path();
+    cr.rectangle(
+        viewport.x(),
+        viewport.y(),
+        viewport.width(),
+        viewport.height(),
+    );
+    set_source_color_on_cairo(cr, color);
+    cr.fill()?;
+    cr.restore()
 }
 
 fn unit_rectangle() -> Rect {
     Rect::from_size(1.0, 1.0)
 }
 
+/// Whether an element paints by itself, without children, so that it can be the target of
+/// [`Document::element_at_point`].
+fn is_hit_testable(node: &Node) -> bool {
+    matches!(
+        *node.borrow_element_data(),
+        ElementData::Circle(_)
+            | ElementData::Ellipse(_)
+            | ElementData::Image(_)
+            | ElementData::Line(_)
+            | ElementData::Path(_)
+            | ElementData::Polygon(_)
+            | ElementData::Polyline(_)
+            | ElementData::Rect(_)
+            | ElementData::Text(_)
+            | ElementData::Use(_)
+    )
+}
+
 /// Any kind of resource loaded while processing an SVG document: images, or SVGs themselves.
 #[derive(Clone)]
 pub enum Resource {
@@ -494,7 +1255,7 @@ impl Resources {
         load_options: &LoadOptions,
         url: &str,
         id: &str,
-        cancellable: Option<&gio::Cancellable>,
+        cancellable: Option<&Cancellable>,
     ) -> Result<Node, LoadingError> {
         self.get_extern_document(session, load_options, url, cancellable)
             .and_then(|resource| match resource {
@@ -508,7 +1269,7 @@ impl Resources {
         session: &Session,
         load_options: &LoadOptions,
         href: &str,
-        cancellable: Option<&gio::Cancellable>,
+        cancellable: Option<&Cancellable>,
     ) -> Result<Resource, LoadingError> {
         let aurl = load_options
             .url_resolver
@@ -530,7 +1291,7 @@ impl Resources {
         session: &Session,
         load_options: &LoadOptions,
         aurl: &AllowedUrl,
-        cancellable: Option<&gio::Cancellable>,
+        cancellable: Option<&Cancellable>,
     ) -> Result<Resource, LoadingError> {
         match self.resources.entry(aurl.clone()) {
             Entry::Occupied(e) => e.get().clone(),
@@ -542,20 +1303,30 @@ impl Resources {
             }
         }
     }
+
+    fn has_kept_image_data(&self) -> bool {
+        self.resources.values().any(|resource| match resource {
+            Ok(Resource::Image(surface)) => IMAGE_CONTENT_TYPES
+                .iter()
+                .any(|content_type| surface.has_mime_data(content_type)),
+            Ok(Resource::Document(document)) => document.has_kept_image_data(),
+            Err(_) => false,
+        })
+    }
 }
 
 fn load_resource(
     session: &Session,
     load_options: &LoadOptions,
     aurl: &AllowedUrl,
-    cancellable: Option<&gio::Cancellable>,
+    cancellable: Option<&Cancellable>,
 ) -> Result<Resource, LoadingError> {
     let data = io::acquire_data(aurl, cancellable)?;
 
     let svg_mime_type = Mime::from_str("image/svg+xml").unwrap();
 
     if data.mime_type == svg_mime_type {
-        load_svg_resource_from_bytes(session, load_options, aurl, data, cancellable)
+        load_svg_resource_from_bytes(session, load_options, aurl, data)
     } else {
         load_image_resource_from_bytes(load_options, aurl, data)
     }
@@ -566,21 +1337,16 @@ fn load_svg_resource_from_bytes(
     load_options: &LoadOptions,
     aurl: &AllowedUrl,
     data: BinaryData,
-    cancellable: Option<&gio::Cancellable>,
 ) -> Result<Resource, LoadingError> {
     let BinaryData {
         data: input_bytes,
         mime_type: _mime_type,
     } = data;
 
-    let bytes = glib::Bytes::from_owned(input_bytes);
-    let stream = gio::MemoryInputStream::from_bytes(&bytes);
-
-    let document = Document::load_from_stream(
+    let document = Document::load_from_data(
         session.clone(),
         Arc::new(load_options.copy_with_base_url(aurl)),
-        &stream.upcast(),
-        cancellable,
+        &input_bytes,
     )?;
 
     Ok(Resource::Document(Rc::new(document)))
@@ -605,6 +1371,15 @@ fn load_image_resource_from_bytes(
     load_image_with_image_rs(aurl, bytes, content_type, load_options)
 }
 
+/// MIME types of the raster image formats in [`image_format`].
+const IMAGE_CONTENT_TYPES: &[&str] = &[
+    "image/png",
+    "image/jpeg",
+    "image/gif",
+    "image/webp",
+    "image/avif",
+];
+
 fn image_format(content_type: &str) -> Result<image::ImageFormat, LoadingError> {
     match content_type {
         "image/png" => Ok(image::ImageFormat::Png),
@@ -642,11 +1417,11 @@ fn load_image_with_image_rs(
         .decode()
         .map_err(|e| LoadingError::Other(format!("error decoding image: {e}")))?;
 
-    let bytes = if load_options.keep_image_data {
-        Some(bytes)
-    } else {
-        None
-    };
+    let keep = content_type
+        .as_deref()
+        .is_some_and(|t| load_options.keep_image_data.keeps(t));
+
+    let bytes = if keep { Some(bytes) } else { None };
 
     let surface = SharedImageSurface::from_image(&image, content_type.as_deref(), bytes)
         .map_err(|e| image_loading_error_from_cairo(e, aurl))?;
@@ -745,11 +1520,11 @@ pub struct AcquiredNodes<'i> {
     num_elements_acquired: usize,
     node_stack: Rc<RefCell<NodeStack>>,
     nodes_with_cycles: Vec<Node>,
-    cancellable: Option<gio::Cancellable>,
+    cancellable: Option<Cancellable>,
 }
 
 impl<'i> AcquiredNodes<'i> {
-    pub fn new(document: &Document, cancellable: Option<gio::Cancellable>) -> AcquiredNodes<'_> {
+    pub fn new(document: &Document, cancellable: Option<Cancellable>) -> AcquiredNodes<'_> {
         AcquiredNodes {
             document,
             num_elements_acquired: 0,
@@ -764,6 +1539,27 @@ impl<'i> AcquiredNodes<'i> {
             .lookup_resource(url, self.cancellable.as_ref())
     }
 
+    /// Returns the document's cache of resolved filters.
+    pub fn filter_spec_cache(&self) -> &'i RefCell<FilterSpecCache> {
+        &self.document.filter_specs
+    }
+
+    /// Records that `node` is part of a reference cycle that was found by other means
+    /// than acquiring it, for example with [`Self::would_render_acquired_node`].
+    pub fn mark_circular_reference(&mut self, node: &Node) {
+        if !self.nodes_with_cycles.contains(node) {
+            self.nodes_with_cycles.push(node.clone());
+        }
+    }
+
+    /// Returns the first element that was found to be part of a reference cycle.
+    ///
+    /// The drawing code skips elements with circular references so that it can continue
+    /// rendering; this is used to report the cycle to the caller afterwards.
+    pub fn first_circular_reference(&self) -> Option<&Node> {
+        self.nodes_with_cycles.first()
+    }
+
     /// Acquires a node by its id.
     ///
     /// This is typically used during an "early resolution" stage, when XML `id`s are being
@@ -826,6 +1622,34 @@ impl<'i> AcquiredNodes<'i> {
             })
         }
     }
+
+    /// Acquires a node that is about to be rendered with a filter.
+    ///
+    /// While the returned [`AcquiredNode`] is alive, a filter primitive like `feImage`
+    /// that references the node will be able to detect the circular reference with
+    /// [`Self::would_render_acquired_node`].  Returns `None` if the node is already in the
+    /// stack of acquired nodes, for example if it is being rendered through a reference.
+    pub fn acquire_filtered_node(&mut self, node: &Node) -> Option<AcquiredNode> {
+        if self.node_stack.borrow().contains(node) {
+            None
+        } else {
+            self.node_stack.borrow_mut().push(node);
+            Some(AcquiredNode {
+                stack: Some(self.node_stack.clone()),
+                node: node.clone(),
+            })
+        }
+    }
+
+    /// Returns whether rendering `node` would render one of the acquired nodes again,
+    /// that is, whether `node` is one of them or one of their ancestors.
+    pub fn would_render_acquired_node(&self, node: &Node) -> bool {
+        self.node_stack
+            .borrow()
+            .0
+            .iter()
+            .any(|acquired| acquired.ancestors().any(|n| n == *node))
+    }
 }
 
 /// Keeps a stack of nodes and can check if a certain node is contained in the stack
@@ -879,6 +1703,9 @@ pub struct DocumentBuilder {
 
     /// Stylesheets defined in the document.
     stylesheets: Vec<Stylesheet>,
+
+    /// URLs referenced by `xml-stylesheet` and `xi:include`.
+    load_time_references: Vec<(ReferenceKind, String)>,
 }
 
 impl DocumentBuilder {
@@ -889,6 +1716,18 @@ impl DocumentBuilder {
             tree: None,
             ids: HashMap::new(),
             stylesheets: Vec::new(),
+            load_time_references: Vec::new(),
+        }
+    }
+
+    /// Records a URL that gets processed while loading, like an `xi:include`.
+    ///
+    /// These are returned later by [`Document::referenced_urls`].
+    pub fn add_load_time_reference(&mut self, kind: ReferenceKind, href: &str) {
+        let reference = (kind, href.to_string());
+
+        if !self.load_time_references.contains(&reference) {
+            self.load_time_references.push(reference);
         }
     }
 
@@ -916,7 +1755,23 @@ impl DocumentBuilder {
         attrs: Attributes,
         parent: Option<Node>,
     ) -> Node {
-        let node = Node::new(NodeData::new_element(&self.session, name, attrs));
+        let has_xml_space = attrs
+            .iter()
+            .any(|(attr, _)| attr.expanded() == expanded_name!(xml "space"));
+
+        let mut node = Node::new(NodeData::new_element(&self.session, name, attrs));
+
+        // Since xml:space is inherited, setting it on the root element is enough to
+        // change the default for the whole document.
+        let override_xml_space = match self.load_options.whitespace_mode {
+            WhitespaceMode::Default => false,
+            WhitespaceMode::Preserve => parent.is_none() && !has_xml_space,
+            WhitespaceMode::ForcePreserve => true,
+        };
+
+        if override_xml_space {
+            node.borrow_element_mut().set_xml_space(XmlSpace::Preserve);
+        }
 
         if let Some(id) = node.borrow_element().get_id() {
             match self.ids.entry(id.to_string()) {
@@ -962,6 +1817,7 @@ impl DocumentBuilder {
             tree,
             ids,
             stylesheets,
+            load_time_references,
             ..
         } = self;
 
@@ -975,10 +1831,22 @@ impl DocumentBuilder {
                         resources: RefCell::new(Resources::new()),
                         load_options,
                         stylesheets,
+                        load_time_references,
+                        filter_specs: RefCell::new(FilterSpecCache::default()),
+                        cascade_overrides: RefCell::new(None),
+                        recorded_regions: RefCell::new(None),
                     };
 
                     document.cascade(&[], &session);
 
+                    // The XML parser stops at unsupported elements, attribute values, and
+                    // stylesheet declarations, but `style` attributes are only parsed during
+                    // the cascade, so their invalid declarations are reported here, once the
+                    // whole document has been read.
+                    if let Some(detail) = session.take_strict_failure() {
+                        return Err(LoadingError::Unsupported { detail });
+                    }
+
                     Ok(document)
                 } else {
                     Err(LoadingError::NoSvgRoot)
@@ -993,6 +1861,9 @@ impl DocumentBuilder {
 mod tests {
     use super::*;
 
+    use crate::accept_language::LanguageTags;
+    use crate::render_observer::RenderEvent;
+
     #[test]
     fn parses_node_id() {
         assert_eq!(
@@ -1027,4 +1898,146 @@ mod tests {
             Some(String::from("image/png"))
         );
     }
+
+    #[test]
+    fn render_document_region_skips_elements_outside_the_region() {
+        let document = Document::load_from_bytes(
+            br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <filter id="blur" x="-200%" y="0" width="500%" height="100%">
+    <feGaussianBlur stdDeviation="1"/>
+  </filter>
+  <rect id="inside" x="10" y="10" width="10" height="10"/>
+  <rect id="outside" x="70" y="70" width="10" height="10"/>
+  <rect id="blurred" x="60" y="10" width="10" height="10" filter="url(#blur)"/>
+  <g id="group">
+    <rect id="in_group" x="80" y="70" width="10" height="10"/>
+  </g>
+</svg>
+"#,
+        );
+
+        let events: RenderEvents = Default::default();
+
+        let options = RenderingOptions {
+            dpi: Dpi::new(96.0, 96.0),
+            cancellable: None,
+            user_language: UserLanguage::LanguageTags(LanguageTags::empty()),
+            svg_nesting: SvgNesting::Standalone,
+            testing: true,
+            font_warnings: Default::default(),
+            render_budget: Default::default(),
+            render_events: Some(events.clone()),
+            animation_time: None,
+            current_color: None,
+            custom_properties: None,
+            font_map: None,
+            circular_reference_errors: false,
+        };
+
+        let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, 50, 50).unwrap();
+        let cr = cairo::Context::new(&surface).unwrap();
+
+        assert!(document
+            .render_document_region(
+                &Session::new_for_test_suite(),
+                &cr,
+                &cairo::Rectangle::new(0.0, 0.0, 100.0, 100.0),
+                &cairo::Rectangle::new(0.0, 0.0, 50.0, 50.0),
+                None,
+                &options,
+            )
+            .is_ok());
+
+        // The filter region of "blurred" reaches into the region, so it gets drawn.
+        let drawn: Vec<String> = events
+            .borrow()
+            .iter()
+            .filter_map(|event| match event {
+                RenderEvent::Layer(id) => id.clone(),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(drawn, ["inside", "blurred"]);
+
+        // Other tiles of the same viewport reuse the recorded regions.
+        let recorded = document
+            .recorded_regions
+            .borrow()
+            .as_ref()
+            .unwrap()
+            .1
+            .clone();
+
+        assert!(document
+            .render_document_region(
+                &Session::new_for_test_suite(),
+                &cr,
+                &cairo::Rectangle::new(0.0, 0.0, 100.0, 100.0),
+                &cairo::Rectangle::new(50.0, 50.0, 50.0, 50.0),
+                None,
+                &options,
+            )
+            .is_ok());
+
+        assert!(Rc::ptr_eq(
+            &recorded,
+            &document.recorded_regions.borrow().as_ref().unwrap().1
+        ));
+    }
+
+    #[test]
+    fn element_at_point_only_renders_elements_near_the_point() {
+        let document = Document::load_from_bytes(
+            br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect id="under" x="0" y="0" width="50" height="50"/>
+  <rect id="hit" x="10" y="10" width="10" height="10"/>
+  <rect id="elsewhere" x="70" y="70" width="10" height="10"/>
+  <rect id="above_elsewhere" x="60" y="60" width="30" height="30"/>
+</svg>
+"#,
+        );
+
+        let events: RenderEvents = Default::default();
+
+        let options = RenderingOptions {
+            dpi: Dpi::new(96.0, 96.0),
+            cancellable: None,
+            user_language: UserLanguage::LanguageTags(LanguageTags::empty()),
+            svg_nesting: SvgNesting::Standalone,
+            testing: true,
+            font_warnings: Default::default(),
+            render_budget: Default::default(),
+            render_events: Some(events.clone()),
+            animation_time: None,
+            current_color: None,
+            custom_properties: None,
+            font_map: None,
+            circular_reference_errors: false,
+        };
+
+        let hit = document.element_at_point(
+            &Session::new_for_test_suite(),
+            &cairo::Rectangle::new(0.0, 0.0, 100.0, 100.0),
+            15.0,
+            15.0,
+            &options,
+        );
+
+        assert!(matches!(hit, Ok(Some(ref id)) if id == "hit"));
+
+        // The frontmost candidate was hit, so the one under it did not need rendering.
+        let drawn: Vec<String> = events
+            .borrow()
+            .iter()
+            .filter_map(|event| match event {
+                RenderEvent::Layer(id) => id.clone(),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(drawn, ["hit"]);
+    }
 }
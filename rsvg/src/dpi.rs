@@ -1,12 +1,19 @@
 //! Resolution for rendering (dots per inch = DPI).
 
-#[derive(Debug, Copy, Clone)]
+/// Horizontal and vertical resolution, in dots per inch.
+///
+/// This is used to convert lengths in physical units like `cm` or `in` to pixels.
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Dpi {
+    /// Horizontal resolution.
     pub x: f64,
+
+    /// Vertical resolution.
     pub y: f64,
 }
 
 impl Dpi {
+    /// Creates a resolution with the given horizontal and vertical dots per inch.
     pub fn new(x: f64, y: f64) -> Dpi {
         Dpi { x, y }
     }
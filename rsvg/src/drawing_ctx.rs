@@ -1,12 +1,17 @@
 //! The main context structure which drives the drawing process.
 
 use float_cmp::approx_eq;
+#[cfg(feature = "gio")]
 use gio::prelude::*;
+#[cfg(feature = "text")]
 use glib::translate::*;
+#[cfg(feature = "text")]
 use pango::ffi::PangoMatrix;
+#[cfg(feature = "text")]
 use pango::prelude::FontMapExt;
 use regex::{Captures, Regex};
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::rc::Rc;
 use std::{borrow::Cow, sync::OnceLock};
@@ -23,12 +28,14 @@ use crate::error::{AcquireError, ImplementationLimit, InternalRenderingError};
 use crate::filters::{self, FilterSpec};
 use crate::float_eq_cairo::ApproxEqCairo;
 use crate::gradient::{GradientVariant, SpreadMethod, UserSpaceGradient};
+use crate::io::Cancellable;
 use crate::layout::{
     self, Filter, Group, Image, Layer, LayerKind, LayoutViewport, Shape, StackingContext, Stroke,
-    Text, TextSpan,
 };
+#[cfg(feature = "text")]
+use crate::layout::{Text, TextGlyphs, TextSpan};
 use crate::length::*;
-use crate::limits;
+use crate::limits::{self, RenderBudget};
 use crate::marker;
 use crate::node::{CascadedValues, Node, NodeBorrow, NodeDraw};
 use crate::paint_server::{PaintSource, UserSpacePaintSource};
@@ -38,11 +45,13 @@ use crate::properties::{
     PaintTarget, ShapeRendering, StrokeLinecap, StrokeLinejoin, TextRendering,
 };
 use crate::rect::{rect_to_transform, IRect, Rect};
+use crate::render_observer::{RenderEvent, RenderEvents};
 use crate::rsvg_log;
 use crate::session::Session;
 use crate::surface_utils::shared_surface::{
     ExclusiveImageSurface, Interpolation, SharedImageSurface, SurfaceType,
 };
+use crate::text::{FontMap, FontWarnings};
 use crate::transform::{Transform, ValidTransform};
 use crate::unit_interval::UnitInterval;
 use crate::viewbox::ViewBox;
@@ -51,8 +60,12 @@ use crate::{borrow_element_as, is_element_of_type};
 /// Opaque font options for a DrawingCtx.
 ///
 /// This is used for DrawingCtx::create_pango_context.
+#[cfg(feature = "text")]
 pub struct FontOptions {
     options: cairo::FontOptions,
+
+    /// Font map from a [`crate::text::FontConfig`], or `None` for Pango's default one.
+    font_map: Option<FontMap>,
 }
 
 /// Set path on the cairo context, or clear it.
@@ -172,11 +185,300 @@ impl Viewport {
 #[derive(Clone)]
 pub struct RenderingConfiguration {
     pub dpi: Dpi,
-    pub cancellable: Option<gio::Cancellable>,
+    pub cancellable: Option<Cancellable>,
     pub user_language: UserLanguage,
     pub svg_nesting: SvgNesting,
     pub measuring: bool,
     pub testing: bool,
+    pub font_warnings: FontWarnings,
+    pub render_budget: Rc<RenderBudget>,
+    pub render_events: Option<RenderEvents>,
+    pub font_map: Option<FontMap>,
+    pub geometry_recorder: Option<Rc<RefCell<GeometryRecorder>>>,
+
+    /// Part of the toplevel viewport that needs to be drawn, in the pixels of its
+    /// temporary surfaces.
+    ///
+    /// Temporary surfaces for layers and masks only cover this area instead of the whole
+    /// viewport.  `None` means the whole viewport.
+    pub render_area: Option<IRect>,
+
+    pub region_recorder: Option<Rc<RefCell<RegionRecorder>>>,
+    pub region_culler: Option<Rc<RefCell<RegionCuller>>>,
+
+    /// Whether [`draw_tree`] returns an error for reference cycles after drawing.
+    pub circular_reference_errors: bool,
+}
+
+/// Bounding boxes of elements, recorded during a single traversal of the document.
+///
+/// This is used to measure many elements at once.  Only the elements that get drawn as
+/// part of the document tree are recorded; elements that get drawn indirectly, like the
+/// contents of a `<pattern>` or the element referenced by a `<use>`, are not.  This way
+/// the recorded boxes are the same as measuring each element with
+/// [`DrawingMode::LimitToStack`].
+pub struct GeometryRecorder {
+    /// Transform of the outermost bounding box; the recorded boxes get converted to it.
+    user_transform: Transform,
+
+    /// The elements to measure, by id, and their bounding boxes once they are drawn.
+    elements: HashMap<String, (Node, Option<BoundingBox>)>,
+
+    /// Nodes being drawn, and whether each of them is being drawn as part of the tree.
+    stack: Vec<(Node, bool)>,
+}
+
+impl GeometryRecorder {
+    pub fn new(user_transform: Transform, nodes: &[Node]) -> GeometryRecorder {
+        let elements = nodes
+            .iter()
+            .filter_map(|node| {
+                let id = node.borrow_element().get_id().map(String::from)?;
+                Some((id, (node.clone(), None)))
+            })
+            .collect();
+
+        GeometryRecorder {
+            user_transform,
+            elements,
+            stack: Vec::new(),
+        }
+    }
+
+    /// Returns the bounding box of `node` in the user's space, if it was drawn.
+    pub fn bbox_for(&self, node: &Node) -> Option<BoundingBox> {
+        match self.elements.get(node.borrow_element().get_id()?) {
+            Some((n, bbox)) if n == node => *bbox,
+            _ => None,
+        }
+    }
+
+    fn push(&mut self, node: &Node) {
+        let top = self.stack.last().map(|(top, in_tree)| (top, *in_tree));
+        let in_tree = is_drawn_in_tree(top, node);
+
+        self.stack.push((node.clone(), in_tree));
+    }
+
+    fn pop(&mut self, bbox: Option<&BoundingBox>) {
+        let (node, in_tree) = self.stack.pop().expect("push() and pop() must be balanced");
+
+        let Some(bbox) = bbox else {
+            return;
+        };
+
+        if !in_tree || !node.is_element() {
+            return;
+        }
+
+        let element = node.borrow_element();
+
+        if let Some(id) = element.get_id() {
+            if let Some((n, recorded)) = self.elements.get_mut(id) {
+                if *n == node {
+                    let mut user_bbox = BoundingBox::new().with_transform(self.user_transform);
+                    user_bbox.insert(bbox);
+                    *recorded = Some(user_bbox);
+                }
+            }
+        }
+    }
+}
+
+/// Returns whether `node` is drawn as part of the document tree, given the node on top of
+/// the drawing stack and whether that one is drawn as part of the tree.
+fn is_drawn_in_tree(top: Option<(&Node, bool)>, node: &Node) -> bool {
+    match top {
+        None => true,
+        Some((top, top_in_tree)) => top_in_tree && node.parent().as_ref() == Some(top),
+    }
+}
+
+/// An element drawn as part of the document tree, and the pixels it can change.
+struct RecordedRegion {
+    node: Node,
+
+    /// Pixels that the element and its children can change, or `None` if they draw nothing.
+    area: Option<IRect>,
+
+    /// Number of recorded elements in the element's subtree, including itself.
+    subtree_len: usize,
+}
+
+/// Areas that elements can change, recorded during a single traversal of the document.
+///
+/// The areas are in the pixels of the toplevel viewport's temporary surfaces.  Bounding
+/// boxes of isolated layers come out in the space of their temporary surface, and the rest
+/// in the space of the target surface, so the document must be drawn on a target whose
+/// device space is those pixels, and with a [`RenderingConfiguration::render_area`] at the
+/// origin.
+///
+/// This also records the filter effects regions, since a filter can change pixels outside
+/// of the bounding box of its element, and it needs all of its input pixels to produce the
+/// same output as when rendering the whole viewport.
+#[derive(Default)]
+pub struct RegionRecorder {
+    /// Elements drawn as part of the document tree, in the order in which they are drawn.
+    elements: Vec<RecordedRegion>,
+
+    /// Filter effects regions of all the filters that were run, without clipping them.
+    filter_regions: Vec<IRect>,
+
+    /// Nodes being drawn, their index in `elements` if they are drawn as part of the tree,
+    /// and the area changed so far by them and their children.
+    stack: Vec<(Node, Option<usize>, Option<IRect>)>,
+}
+
+fn union_areas(a: Option<IRect>, b: Option<IRect>) -> Option<IRect> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.union(&b)),
+        (a, b) => a.or(b),
+    }
+}
+
+impl RegionRecorder {
+    fn push(&mut self, node: &Node) {
+        let top = self
+            .stack
+            .last()
+            .map(|(top, index, _)| (top, index.is_some()));
+
+        let index = if is_drawn_in_tree(top, node) {
+            self.elements.push(RecordedRegion {
+                node: node.clone(),
+                area: None,
+                subtree_len: 1,
+            });
+            Some(self.elements.len() - 1)
+        } else {
+            None
+        };
+
+        self.stack.push((node.clone(), index, None));
+    }
+
+    fn pop(&mut self, drawn: Option<IRect>) {
+        let (_, index, area) = self.stack.pop().expect("push() and pop() must be balanced");
+
+        let area = union_areas(area, drawn);
+
+        if let Some(index) = index {
+            self.elements[index].area = area;
+            self.elements[index].subtree_len = self.elements.len() - index;
+        }
+
+        if let Some((_, _, parent_area)) = self.stack.last_mut() {
+            *parent_area = union_areas(*parent_area, area);
+        }
+    }
+
+    fn record_filter_region(&mut self, region: IRect) {
+        self.filter_regions.push(region);
+
+        if let Some((_, _, area)) = self.stack.last_mut() {
+            *area = union_areas(*area, Some(region));
+        }
+    }
+
+    /// Returns the elements drawn as part of the tree that can change pixels in `area`, in
+    /// the order in which they are drawn.
+    pub fn elements_touching(&self, area: IRect) -> impl DoubleEndedIterator<Item = &Node> {
+        self.elements
+            .iter()
+            .filter(move |element| element.area.and_then(|a| a.intersection(&area)).is_some())
+            .map(|element| &element.node)
+    }
+
+    /// Returns the area that needs to be drawn so that the pixels in `tile` come out the
+    /// same as when drawing the whole viewport.
+    ///
+    /// This is the `tile` plus the filter effects regions that touch it, and in turn the
+    /// ones that touch those, since a filter reads all the pixels in its region.
+    pub fn area_for_tile(&self, tile: IRect) -> IRect {
+        let mut area = tile;
+
+        loop {
+            let grown = self
+                .filter_regions
+                .iter()
+                .filter(|region| region.intersection(&area).is_some())
+                .fold(area, |area, region| area.union(region));
+
+            if grown == area {
+                return area;
+            }
+
+            area = grown;
+        }
+    }
+}
+
+/// Skips drawing the elements from a [`RegionRecorder`] that do not touch an area.
+///
+/// The document must be drawn in the same way as when its regions were recorded, so that
+/// the elements drawn as part of the tree come in the same order.  If they don't, this
+/// stops skipping elements.
+pub struct RegionCuller {
+    recorder: Rc<RegionRecorder>,
+    area: IRect,
+
+    /// Index in `elements` of the next element to be drawn as part of the tree.
+    next: usize,
+
+    /// Nodes being drawn, and whether each of them is being drawn as part of the tree.
+    stack: Vec<(Node, bool)>,
+}
+
+impl RegionCuller {
+    /// Creates a culler that skips the elements which cannot change any pixels in `area`.
+    ///
+    /// The `recorder` can be shared by several cullers, to draw different areas without
+    /// recording the document again.
+    pub fn new(recorder: Rc<RegionRecorder>, area: IRect) -> RegionCuller {
+        RegionCuller {
+            recorder,
+            area,
+            next: 0,
+            stack: Vec::new(),
+        }
+    }
+
+    /// Returns whether `node` needs to be drawn.
+    fn push(&mut self, node: &Node) -> bool {
+        let top = self.stack.last().map(|(top, in_tree)| (top, *in_tree));
+        let in_tree = is_drawn_in_tree(top, node);
+
+        self.stack.push((node.clone(), in_tree));
+
+        if !in_tree {
+            return true;
+        }
+
+        match self.recorder.elements.get(self.next) {
+            Some(element) if element.node == *node => {
+                if element
+                    .area
+                    .and_then(|area| area.intersection(&self.area))
+                    .is_some()
+                {
+                    self.next += 1;
+                    true
+                } else {
+                    self.next += element.subtree_len;
+                    false
+                }
+            }
+
+            _ => {
+                self.next = self.recorder.elements.len();
+                true
+            }
+        }
+    }
+
+    fn pop(&mut self) {
+        self.stack.pop().expect("push() and pop() must be balanced");
+    }
 }
 
 pub struct DrawingCtx {
@@ -187,6 +489,12 @@ pub struct DrawingCtx {
     cr_stack: Rc<RefCell<Vec<cairo::Context>>>,
     cr: cairo::Context,
 
+    /// Whether `cr` is the toplevel one or one for a temporary surface in the pixels of the
+    /// toplevel viewport, as opposed to one for a pattern tile or a paint server.
+    ///
+    /// Only the former are limited to the [`RenderingConfiguration::render_area`].
+    in_toplevel_viewport: bool,
+
     drawsub_stack: Vec<Node>,
 
     config: RenderingConfiguration,
@@ -212,7 +520,7 @@ pub enum DrawingMode {
 /// its `preserveAspectRatio` needs to be ignored so that the one from the `<image>` element can
 /// be used instead.  This lets the parent document (the one with the `<image>` element) specify
 /// how it wants the child SVG to be scaled into the viewport.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq)]
 pub enum SvgNesting {
     Standalone,
     ReferencedFromImageElement,
@@ -272,6 +580,8 @@ pub fn draw_tree(
         transform,
     };
 
+    let circular_reference_errors = config.circular_reference_errors;
+
     let mut draw_ctx = DrawingCtx::new(session, cr, &initial_viewport, config, drawsub_stack);
 
     let content_bbox = draw_ctx.draw_node_from_stack(
@@ -286,6 +596,11 @@ pub fn draw_tree(
 
     if draw_ctx.is_rendering_cancelled() {
         Err(InternalRenderingError::Cancelled)
+    } else if let Some(node) = acquired_nodes
+        .first_circular_reference()
+        .filter(|_| circular_reference_errors)
+    {
+        Err(InternalRenderingError::CircularReference(node.clone()))
     } else {
         Ok(user_bbox)
     }
@@ -327,6 +642,7 @@ impl DrawingCtx {
             initial_viewport: initial_viewport.clone(),
             cr_stack: Rc::new(RefCell::new(Vec::new())),
             cr: cr.clone(),
+            in_toplevel_viewport: true,
             drawsub_stack,
             config,
             recursion_depth: 0,
@@ -349,6 +665,7 @@ impl DrawingCtx {
             initial_viewport: self.initial_viewport.clone(),
             cr_stack,
             cr,
+            in_toplevel_viewport: self.in_toplevel_viewport,
             drawsub_stack: self.drawsub_stack.clone(),
             config: self.config.clone(),
             recursion_depth: self.recursion_depth,
@@ -367,9 +684,35 @@ impl DrawingCtx {
             user_language: self.config.user_language.clone(),
             svg_nesting,
             testing: self.config.testing,
+            font_warnings: self.config.font_warnings.clone(),
+            render_budget: self.config.render_budget.clone(),
+            render_events: self.config.render_events.clone(),
+            animation_time: None,
+            current_color: None,
+            custom_properties: None,
+            font_map: self.config.font_map.clone(),
+            circular_reference_errors: self.config.circular_reference_errors,
         }
     }
 
+    /// Whether drawing events need to be recorded for a [`RenderObserver`].
+    ///
+    /// [`RenderObserver`]: crate::render_observer::RenderObserver
+    fn is_observed(&self) -> bool {
+        self.config.render_events.is_some()
+    }
+
+    fn record_event(&self, event: RenderEvent) {
+        if let Some(ref events) = self.config.render_events {
+            events.borrow_mut().push(event);
+        }
+    }
+
+    /// Returns where text layout should report fonts that could not be used.
+    pub fn font_warnings(&self) -> FontWarnings {
+        self.config.font_warnings.clone()
+    }
+
     pub fn user_language(&self) -> &UserLanguage {
         &self.config.user_language
     }
@@ -393,6 +736,7 @@ impl DrawingCtx {
                 *self.get_transform(),
                 self.initial_viewport.transform,
                 self.cr_stack.borrow().len(),
+                self.temporary_surface_area(),
             );
 
             Ok(ValidTransform::try_from(affines.for_temporary_surface)?)
@@ -423,7 +767,8 @@ impl DrawingCtx {
         BoundingBox::new().with_transform(*self.get_transform())
     }
 
-    fn size_for_temporary_surface(&self) -> (i32, i32) {
+    /// Returns the pixels of the toplevel viewport that temporary surfaces need to cover.
+    fn temporary_surface_area(&self) -> IRect {
         let rect = self.toplevel_viewport();
 
         let (viewport_width, viewport_height) = (rect.width(), rect.height());
@@ -435,7 +780,41 @@ impl DrawingCtx {
 
         // We need a size in whole pixels, so use ceil() to ensure the whole viewport fits
         // into the temporary surface.
-        (width.ceil().abs() as i32, height.ceil().abs() as i32)
+        let viewport = IRect::from_size(width.ceil().abs() as i32, height.ceil().abs() as i32);
+
+        match self.config.render_area {
+            Some(area) if self.in_toplevel_viewport => viewport
+                .intersection(&area)
+                .unwrap_or(IRect::new(area.x0, area.y0, area.x0, area.y0)),
+            _ => viewport,
+        }
+    }
+
+    fn size_for_temporary_surface(&self) -> (i32, i32) {
+        self.temporary_surface_area().size()
+    }
+
+    /// Converts a bounding box to the pixels of the toplevel viewport, for a
+    /// [`RegionRecorder`].
+    fn bbox_in_toplevel_pixels(&self, bbox: &BoundingBox) -> Option<IRect> {
+        if !self.in_toplevel_viewport {
+            return None;
+        }
+
+        let bbox = bbox.in_space(Transform::identity());
+
+        union_areas(bbox.rect.map(IRect::from), bbox.ink_rect.map(IRect::from))
+    }
+
+    /// Records the filter effects region of a filter being run on the current surface.
+    pub fn record_filter_region(&self, effects_region: Rect) {
+        if let Some(ref recorder) = self.config.region_recorder {
+            if self.in_toplevel_viewport {
+                recorder
+                    .borrow_mut()
+                    .record_filter_region(IRect::from(effects_region));
+            }
+        }
     }
 
     pub fn create_surface_for_toplevel_viewport(
@@ -539,13 +918,16 @@ impl DrawingCtx {
             // FMQ: here
             self.cr.transform(transform_for_clip.into());
 
+            // With objectBoundingBox units, percentages refer to the bounding box.
+            let clip_viewport = viewport.with_units(units);
+
             for child in node.children().filter(|c| {
                 c.is_element() && element_can_be_used_inside_clip_path(&c.borrow_element())
             }) {
                 child.draw(
                     acquired_nodes,
                     &CascadedValues::clone_with_node(&cascaded, &child),
-                    viewport,
+                    &clip_viewport,
                     self,
                     true,
                 )?;
@@ -562,6 +944,7 @@ impl DrawingCtx {
     fn generate_cairo_mask(
         &mut self,
         mask_node: &Node,
+        mask_mode: Option<MaskType>,
         viewport: &Viewport,
         transform: Transform,
         bbox: &BoundingBox,
@@ -668,7 +1051,7 @@ impl DrawingCtx {
 
         let tmp = SharedImageSurface::wrap(mask_content_surface, SurfaceType::SRgb)?;
 
-        let mask_result = match values.mask_type() {
+        let mask_result = match mask_mode.unwrap_or_else(|| values.mask_type()) {
             MaskType::Luminance => tmp.to_luminance_mask()?,
             MaskType::Alpha => tmp.extract_alpha(IRect::from_size(tmp.width(), tmp.height()))?,
         };
@@ -697,6 +1080,29 @@ impl DrawingCtx {
         Ok(())
     }
 
+    /// Counts the pixels produced by a filter primitive against the [`RenderLimits`].
+    ///
+    /// [`RenderLimits`]: crate::limits::RenderLimits
+    pub fn count_filter_pixels(&self, bounds: IRect) -> Result<(), InternalRenderingError> {
+        let pixels =
+            u64::from(bounds.width().unsigned_abs()) * u64::from(bounds.height().unsigned_abs());
+
+        self.config
+            .render_budget
+            .count_filter_pixels(pixels)
+            .map_err(InternalRenderingError::LimitExceeded)
+    }
+
+    /// Checks whether the deadline from the [`RenderLimits`] has passed.
+    ///
+    /// [`RenderLimits`]: crate::limits::RenderLimits
+    pub fn check_render_deadline(&self) -> Result<(), InternalRenderingError> {
+        self.config
+            .render_budget
+            .check_deadline()
+            .map_err(InternalRenderingError::LimitExceeded)
+    }
+
     fn check_layer_nesting_depth(&mut self) -> Result<(), InternalRenderingError> {
         if self.recursion_depth > limits::MAX_LAYER_NESTING_DEPTH {
             return Err(InternalRenderingError::LimitExceeded(
@@ -842,6 +1248,7 @@ impl DrawingCtx {
                         *affine_at_start,
                         self.initial_viewport.transform,
                         self.cr_stack.borrow().len(),
+                        self.temporary_surface_area(),
                     ));
 
                     // Create temporary surface and its cr
@@ -897,6 +1304,11 @@ impl DrawingCtx {
                         }
                     };
 
+                    // The bbox may be in the coordinate space of a nested viewport, like the one
+                    // of an <svg> element.  Clipping and masking in objectBoundingBox units need
+                    // it in the element's own user space.
+                    let bbox = bbox.in_space(affines.for_temporary_surface);
+
                     // Set temporary surface as source
 
                     self.cr
@@ -921,9 +1333,10 @@ impl DrawingCtx {
                         res = res.and_then(|bbox| {
                             self.generate_cairo_mask(
                                 mask_node,
+                                stacking_ctx.mask_mode,
                                 &viewport,
                                 affines.for_temporary_surface,
-                                &bbox,
+                                &bbox.in_space(affines.for_temporary_surface),
                                 acquired_nodes,
                             )
                             .and_then(|mask_surf| {
@@ -996,6 +1409,16 @@ impl DrawingCtx {
     ) -> Result<BoundingBox, InternalRenderingError> {
         self.check_cancellation()?;
 
+        self.config
+            .render_budget
+            .count_element()
+            .map_err(InternalRenderingError::LimitExceeded)?;
+
+        if self.is_observed() && !clipping {
+            let element_id = stacking_ctx.element_id.as_deref().map(String::from);
+            self.record_event(RenderEvent::Layer(element_id));
+        }
+
         self.recursion_depth += 1;
 
         match self.check_layer_nesting_depth() {
@@ -1085,7 +1508,7 @@ impl DrawingCtx {
                     node_name,
                 )
             })
-            .collect::<Result<Vec<FilterSpec>, _>>();
+            .collect::<Result<Vec<Rc<FilterSpec>>, _>>();
 
         match filter_specs {
             Ok(specs) => {
@@ -1180,8 +1603,11 @@ impl DrawingCtx {
 
         let taffine = self.get_transform().pre_transform(&pattern.transform);
 
-        let mut scwscale = (taffine.xx.powi(2) + taffine.xy.powi(2)).sqrt();
-        let mut schscale = (taffine.yx.powi(2) + taffine.yy.powi(2)).sqrt();
+        // Size of the tile's surface in device pixels.  The scale factors are the lengths of
+        // the pattern's transformed x and y axes, so they stay correct when a rotation swaps
+        // the axes of a non-uniform scale.
+        let mut scwscale = (taffine.xx.powi(2) + taffine.yx.powi(2)).sqrt();
+        let mut schscale = (taffine.xy.powi(2) + taffine.yy.powi(2)).sqrt();
 
         let pw: i32 = (pattern.width * scwscale) as i32;
         let ph: i32 = (pattern.height * schscale) as i32;
@@ -1222,6 +1648,7 @@ impl DrawingCtx {
 
         {
             let mut pattern_draw_ctx = self.nested(cr_pattern);
+            pattern_draw_ctx.in_toplevel_viewport = false;
 
             let pattern_viewport = Viewport {
                 dpi: self.config.dpi,
@@ -1318,6 +1745,7 @@ impl DrawingCtx {
 
         surface.draw(&mut |cr| {
             let mut temporary_draw_ctx = self.nested(cr);
+            temporary_draw_ctx.in_toplevel_viewport = false;
 
             // FIXME: we are ignoring any error
 
@@ -1341,6 +1769,11 @@ impl DrawingCtx {
     ) -> Result<(), InternalRenderingError> {
         let had_paint_server = self.set_paint_source(paint_source, acquired_nodes)?;
         if had_paint_server {
+            if self.is_observed() {
+                let extents = device_extents(cr, cr.stroke_extents()?);
+                self.record_event(RenderEvent::Stroke(extents));
+            }
+
             cr.stroke_preserve()?;
         }
 
@@ -1355,6 +1788,11 @@ impl DrawingCtx {
     ) -> Result<(), InternalRenderingError> {
         let had_paint_server = self.set_paint_source(paint_source, acquired_nodes)?;
         if had_paint_server {
+            if self.is_observed() {
+                let extents = device_extents(cr, cr.fill_extents()?);
+                self.record_event(RenderEvent::Fill(extents));
+            }
+
             cr.fill_preserve()?;
         }
 
@@ -1376,6 +1814,7 @@ impl DrawingCtx {
                 clipping,
                 viewport,
             ),
+            #[cfg(feature = "text")]
             LayerKind::Text(text) => self.draw_text(
                 text,
                 &layer.stacking_ctx,
@@ -1569,6 +2008,13 @@ impl DrawingCtx {
                 Some(layout_viewport),
                 clipping,
                 &mut |_an, dc, _new_viewport| {
+                    if dc.is_observed() {
+                        let transform = Transform::from(dc.cr.matrix());
+                        let extents =
+                            transform.transform_rect(&Rect::from_size(image_width, image_height));
+                        dc.record_event(RenderEvent::Image(extents));
+                    }
+
                     dc.paint_surface(
                         &image.surface,
                         image_width,
@@ -1595,13 +2041,15 @@ impl DrawingCtx {
         unimplemented!()
     }
 
+    #[cfg(feature = "text")]
     fn draw_text_span(
         &mut self,
         span: &TextSpan,
         acquired_nodes: &mut AcquiredNodes<'_>,
         clipping: bool,
     ) -> Result<BoundingBox, InternalRenderingError> {
-        let path = pango_layout_to_cairo_path(span.x, span.y, &span.layout, span.gravity)?;
+        let path =
+            pango_layout_to_cairo_path(span.x, span.y, &span.glyphs, span.gravity, span.rotation)?;
         if path.is_empty() {
             // Empty strings, or only-whitespace text, get turned into empty paths.
             // In that case, we really want to return "no bounds" rather than an
@@ -1632,6 +2080,12 @@ impl DrawingCtx {
                 &span.stroke_paint,
                 &self.initial_viewport,
             )?;
+
+            if span.is_visible && self.is_observed() {
+                let extents = device_extents(&self.cr, self.cr.fill_extents()?);
+                self.record_event(RenderEvent::Text(extents));
+            }
+
             self.cr.new_path();
 
             if span.is_visible {
@@ -1655,13 +2109,29 @@ impl DrawingCtx {
 
                                     let matrix = self.cr.matrix();
 
-                                    let rotation_from_gravity = span.gravity.to_rotation();
-                                    if !rotation_from_gravity.approx_eq_cairo(0.0) {
-                                        self.cr.rotate(-rotation_from_gravity);
+                                    let rotation = span.rotation - span.gravity.to_rotation();
+                                    if !rotation.approx_eq_cairo(0.0) {
+                                        self.cr.rotate(rotation);
                                     }
 
-                                    pangocairo::functions::update_layout(&self.cr, &span.layout);
-                                    pangocairo::functions::show_layout(&self.cr, &span.layout);
+                                    match span.glyphs {
+                                        TextGlyphs::Layout(ref layout) => {
+                                            pangocairo::functions::update_layout(&self.cr, layout);
+                                            pangocairo::functions::show_layout(&self.cr, layout);
+                                        }
+
+                                        TextGlyphs::Cluster {
+                                            ref font,
+                                            ref glyphs,
+                                        } => {
+                                            let mut glyphs = glyphs.clone();
+                                            pangocairo::functions::show_glyph_string(
+                                                &self.cr,
+                                                font,
+                                                &mut glyphs,
+                                            );
+                                        }
+                                    }
 
                                     self.cr.set_matrix(matrix);
                                 }
@@ -1692,6 +2162,7 @@ impl DrawingCtx {
         })
     }
 
+    #[cfg(feature = "text")]
     fn draw_text(
         &mut self,
         text: &Text,
@@ -1751,6 +2222,7 @@ impl DrawingCtx {
                     Transform::from(draw.matrix()),
                     self.initial_viewport.transform,
                     depth,
+                    self.temporary_surface_area(),
                 );
 
                 cr.set_matrix(ValidTransform::try_from(affines.for_snapshot)?.into());
@@ -1812,12 +2284,41 @@ impl DrawingCtx {
             true
         };
 
-        let res = if draw {
+        if let Some(ref recorder) = self.config.geometry_recorder {
+            recorder.borrow_mut().push(node);
+        }
+
+        if let Some(ref recorder) = self.config.region_recorder {
+            recorder.borrow_mut().push(node);
+        }
+
+        let culled = match self.config.region_culler {
+            Some(ref culler) => !culler.borrow_mut().push(node),
+            None => false,
+        };
+
+        let res = if draw && !culled {
             node.draw(acquired_nodes, cascaded, viewport, self, clipping)
         } else {
             Ok(self.empty_bbox())
         };
 
+        if let Some(ref culler) = self.config.region_culler {
+            culler.borrow_mut().pop();
+        }
+
+        if let Some(ref recorder) = self.config.region_recorder {
+            let area = res
+                .as_ref()
+                .ok()
+                .and_then(|bbox| self.bbox_in_toplevel_pixels(bbox));
+            recorder.borrow_mut().pop(area);
+        }
+
+        if let Some(ref recorder) = self.config.geometry_recorder {
+            recorder.borrow_mut().pop(res.as_ref().ok());
+        }
+
         if let Some(top) = stack_top {
             self.drawsub_stack.push(top);
         }
@@ -2012,6 +2513,7 @@ impl DrawingCtx {
     /// Extracts the font options for the current state of the DrawingCtx.
     ///
     /// You can use the font options later with create_pango_context().
+    #[cfg(feature = "text")]
     pub fn get_font_options(&self) -> FontOptions {
         let mut options = cairo::FontOptions::new().unwrap();
         if self.config.testing {
@@ -2021,7 +2523,10 @@ impl DrawingCtx {
         options.set_hint_style(cairo::HintStyle::None);
         options.set_hint_metrics(cairo::HintMetrics::Off);
 
-        FontOptions { options }
+        FontOptions {
+            options,
+            font_map: self.config.font_map.clone(),
+        }
     }
 }
 
@@ -2041,8 +2546,12 @@ impl From<ImageRendering> for Interpolation {
 }
 
 /// Create a Pango context with a particular configuration.
+#[cfg(feature = "text")]
 pub fn create_pango_context(font_options: &FontOptions, transform: &Transform) -> pango::Context {
-    let font_map = pangocairo::FontMap::default();
+    let font_map = font_options
+        .font_map
+        .clone()
+        .unwrap_or_else(pangocairo::FontMap::default);
     let context = font_map.create_context();
 
     context.set_round_glyph_positions(false);
@@ -2095,18 +2604,21 @@ pub fn set_source_color_on_cairo(cr: &cairo::Context, color: &cssparser::Color)
     );
 }
 
-/// Converts a Pango layout to a Cairo path on the specified cr starting at (x, y).
-/// Does not clear the current path first.
+/// Converts a Pango layout or glyph cluster to a Cairo path on the specified cr starting
+/// at (x, y).  The glyphs are rotated by `rotation` radians around that point, in addition
+/// to the rotation implied by the `gravity`.  Does not clear the current path first.
+#[cfg(feature = "text")]
 fn pango_layout_to_cairo(
     x: f64,
     y: f64,
-    layout: &pango::Layout,
+    glyphs: &TextGlyphs,
     gravity: pango::Gravity,
+    rotation: f64,
     cr: &cairo::Context,
 ) {
-    let rotation_from_gravity = gravity.to_rotation();
-    let rotation = if !rotation_from_gravity.approx_eq_cairo(0.0) {
-        Some(-rotation_from_gravity)
+    let rotation = rotation - gravity.to_rotation();
+    let rotation = if !rotation.approx_eq_cairo(0.0) {
+        Some(rotation)
     } else {
         None
     };
@@ -2118,22 +2630,38 @@ fn pango_layout_to_cairo(
         cr.rotate(rot);
     }
 
-    pangocairo::functions::update_layout(cr, layout);
-    pangocairo::functions::layout_path(cr, layout);
+    match *glyphs {
+        TextGlyphs::Layout(ref layout) => {
+            pangocairo::functions::update_layout(cr, layout);
+            pangocairo::functions::layout_path(cr, layout);
+        }
+
+        TextGlyphs::Cluster {
+            ref font,
+            ref glyphs,
+        } => {
+            let mut glyphs = glyphs.clone();
+            pangocairo::functions::glyph_string_path(cr, font, &mut glyphs);
+        }
+    }
+
     cr.set_matrix(matrix);
 }
 
-/// Converts a Pango layout to a CairoPath starting at (x, y).
+/// Converts a Pango layout or glyph cluster to a CairoPath starting at (x, y), rotated by
+/// `rotation` radians.
+#[cfg(feature = "text")]
 fn pango_layout_to_cairo_path(
     x: f64,
     y: f64,
-    layout: &pango::Layout,
+    glyphs: &TextGlyphs,
     gravity: pango::Gravity,
+    rotation: f64,
 ) -> Result<CairoPath, InternalRenderingError> {
     let surface = cairo::RecordingSurface::create(cairo::Content::ColorAlpha, None)?;
     let cr = cairo::Context::new(&surface)?;
 
-    pango_layout_to_cairo(x, y, layout, gravity, &cr);
+    pango_layout_to_cairo(x, y, glyphs, gravity, rotation, &cr);
 
     let cairo_path = cr.copy_path()?;
     Ok(CairoPath::from_cairo(cairo_path))
@@ -2178,9 +2706,17 @@ struct CompositingAffines {
 }
 
 impl CompositingAffines {
-    fn new(current: Transform, initial: Transform, cr_stack_depth: usize) -> CompositingAffines {
+    fn new(
+        current: Transform,
+        initial: Transform,
+        cr_stack_depth: usize,
+        area: IRect,
+    ) -> CompositingAffines {
         let is_topmost_temporary_surface = cr_stack_depth == 0;
 
+        // Temporary surfaces only cover an area of the viewport; offset them to its origin.
+        let (area_x, area_y) = (f64::from(area.x0), f64::from(area.y0));
+
         let initial_inverse = initial.invert().unwrap();
 
         let outside_temporary_surface = if is_topmost_temporary_surface {
@@ -2195,12 +2731,15 @@ impl CompositingAffines {
             current
                 .post_transform(&initial_inverse)
                 .post_scale(scale_x, scale_y)
+                .post_translate(-area_x, -area_y)
         } else {
             current
         };
 
         let compositing = if is_topmost_temporary_surface {
-            initial.pre_scale(1.0 / scale_x, 1.0 / scale_y)
+            initial
+                .pre_scale(1.0 / scale_x, 1.0 / scale_y)
+                .pre_translate(area_x, area_y)
         } else {
             Transform::identity()
         };
@@ -2325,13 +2864,8 @@ fn setup_cr_for_stroke(cr: &cairo::Context, stroke: &Stroke) {
     cr.set_line_cap(cairo::LineCap::from(stroke.line_cap));
     cr.set_line_join(cairo::LineJoin::from(stroke.line_join));
 
-    let total_length: f64 = stroke.dashes.iter().sum();
-
-    if total_length > 0.0 {
-        cr.set_dash(&stroke.dashes, stroke.dash_offset);
-    } else {
-        cr.set_dash(&[], 0.0);
-    }
+    // An empty array turns off dashing; see Dasharray::to_user().
+    cr.set_dash(&stroke.dashes, stroke.dash_offset);
 }
 
 /// escape quotes and backslashes with backslash
@@ -2355,6 +2889,11 @@ fn clip_to_rectangle(cr: &cairo::Context, r: &Rect) {
     cr.clip();
 }
 
+/// Converts extents from the user space of `cr` to its device space.
+fn device_extents(cr: &cairo::Context, (x0, y0, x1, y1): (f64, f64, f64, f64)) -> Rect {
+    Transform::from(cr.matrix()).transform_rect(&Rect::new(x0, y0, x1, y1))
+}
+
 impl From<SpreadMethod> for cairo::Extend {
     fn from(s: SpreadMethod) -> cairo::Extend {
         match s {
@@ -6,6 +6,7 @@ use std::fmt;
 use std::sync::OnceLock;
 
 use crate::accept_language::UserLanguage;
+use crate::animation::{Animate, AnimateTransform, Set};
 use crate::bbox::BoundingBox;
 use crate::cond::{RequiredExtensions, RequiredFeatures, SystemLanguage};
 use crate::css::{Declaration, Origin};
@@ -38,15 +39,17 @@ use crate::layout::Layer;
 use crate::marker::Marker;
 use crate::node::*;
 use crate::pattern::Pattern;
-use crate::properties::{ComputedValues, SpecifiedValues};
+use crate::properties::{
+    ComputedValues, ParsedProperty, SpecifiedValue, SpecifiedValues, XmlSpace,
+};
 use crate::rsvg_log;
 use crate::session::Session;
 use crate::shapes::{Circle, Ellipse, Line, Path, Polygon, Polyline, Rect};
 use crate::structure::{ClipPath, Group, Link, Mask, NonRendering, Svg, Switch, Symbol, Use};
 use crate::style::Style;
-use crate::text::{TRef, TSpan, Text};
+use crate::text::{TRef, TSpan, Text, TextPath};
 use crate::text2::Text2;
-use crate::xml::Attributes;
+use crate::xml::{AttributeValue, Attributes};
 
 pub trait ElementTrait {
     /// Sets per-element attributes.
@@ -102,7 +105,7 @@ pub trait ElementTrait {
 /// gets parsed.  This function will set that attribute's value only if parsing was successful.
 ///
 /// In case the `parse_result` is an error, this function will log an appropriate notice
-/// via the [`Session`].
+/// via the [`Session`], and record it as a warning if the session is collecting them.
 pub fn set_attribute<T>(dest: &mut T, parse_result: Result<T, ElementError>, session: &Session) {
     match parse_result {
         Ok(v) => *dest = v,
@@ -110,6 +113,7 @@ pub fn set_attribute<T>(dest: &mut T, parse_result: Result<T, ElementError>, ses
             // FIXME: this does not provide a clue of what was the problematic element.
             // We need tracking of the current parsing position to do that.
             rsvg_log!(session, "ignoring attribute with invalid value: {}", e);
+            session.add_warning(&e.attr, e.err.to_string());
         }
     }
 }
@@ -139,6 +143,8 @@ impl fmt::Display for Element {
 /// This enum uses `Box<Foo>` in order to make each variant the size of
 /// a pointer.
 pub enum ElementData {
+    Animate(Box<Animate>),
+    AnimateTransform(Box<AnimateTransform>),
     Circle(Box<Circle>),
     ClipPath(Box<ClipPath>),
     Ellipse(Box<Ellipse>),
@@ -157,6 +163,7 @@ pub enum ElementData {
     Polyline(Box<Polyline>),
     RadialGradient(Box<RadialGradient>),
     Rect(Box<Rect>),
+    Set(Box<Set>),
     Stop(Box<Stop>),
     Style(Box<Style>),
     Svg(Box<Svg>),
@@ -164,6 +171,7 @@ pub enum ElementData {
     Symbol(Box<Symbol>),
     Text(Box<Text>),
     Text2(Box<Text2>),
+    TextPath(Box<TextPath>),
     TRef(Box<TRef>),
     TSpan(Box<TSpan>),
     Use(Box<Use>),
@@ -208,10 +216,10 @@ fn get_element_creators() -> &'static HashMap<&'static str, (ElementDataCreateFn
             /* ("altGlyph",         ), */
             /* ("altGlyphDef",      ), */
             /* ("altGlyphItem",     ), */
-            /* ("animate",          ), */
+            ("animate",             create_animate,               Default),
             /* ("animateColor",     ), */
             /* ("animateMotion",    ), */
-            /* ("animateTransform", ), */
+            ("animateTransform",    create_animate_transform,     Default),
             ("circle",              create_circle,                Default),
             ("clipPath",            create_clip_path,             Default),
             /* ("color-profile",    ), */
@@ -272,7 +280,7 @@ fn get_element_creators() -> &'static HashMap<&'static str, (ElementDataCreateFn
             ("radialGradient",      create_radial_gradient,       Default),
             ("rect",                create_rect,                  Default),
             /* ("script",           ), */
-            /* ("set",              ), */
+            ("set",                 create_set,                   Default),
             ("stop",                create_stop,                  Default),
             ("style",               create_style,                 IgnoreClass),
             /* ("subImage",         ), */
@@ -282,7 +290,7 @@ fn get_element_creators() -> &'static HashMap<&'static str, (ElementDataCreateFn
             ("symbol",              create_symbol,                Default),
             ("text",                create_text,                  Default),
             ("text2",               create_text2,                 Default),
-            /* ("textPath",         ), */
+            ("textPath",            create_text_path,             Default),
             /* ("title",            ), */
             ("tref",                create_tref,                  Default),
             ("tspan",               create_tspan,                 Default),
@@ -309,7 +317,13 @@ impl Element {
                 // Whenever we encounter a element name we don't understand, represent it as a
                 // non-rendering element.  This is like a group, but it doesn't do any rendering
                 // of children.  The effect is that we will ignore all children of unknown elements.
-                None => (create_non_rendering, ElementCreateFlags::Default),
+                None => {
+                    if !matches!(name.local.as_ref(), "desc" | "metadata" | "title") {
+                        session.add_unsupported(format!("unsupported element {}", name.local));
+                    }
+
+                    (create_non_rendering, ElementCreateFlags::Default)
+                }
             }
         } else {
             (create_non_rendering, ElementCreateFlags::Default)
@@ -319,6 +333,14 @@ impl Element {
             attributes.clear_class();
         };
 
+        let element_session;
+        let session = if session.is_collecting_warnings() || session.is_strict() {
+            element_session = session.for_element(name.local.as_ref());
+            &element_session
+        } else {
+            session
+        };
+
         let element_data = create_fn(session, &attributes);
 
         let mut e = Self {
@@ -351,6 +373,11 @@ impl Element {
         self.attributes.get_id()
     }
 
+    /// Like `get_id`, but returns the interned value, which is cheap to clone.
+    pub fn get_id_value(&self) -> Option<&AttributeValue> {
+        self.attributes.get_id_value()
+    }
+
     pub fn get_class(&self) -> Option<&str> {
         self.attributes.get_class()
     }
@@ -360,6 +387,14 @@ impl Element {
             .inherit_xml_lang(&mut self.values, parent);
     }
 
+    /// Overrides the element's `xml:space` attribute.
+    ///
+    /// This is used at loading time to implement [`WhitespaceMode`][crate::space::WhitespaceMode].
+    pub fn set_xml_space(&mut self, space: XmlSpace) {
+        self.specified_values
+            .set_parsed_property(&ParsedProperty::XmlSpace(SpecifiedValue::Specified(space)));
+    }
+
     pub fn get_specified_values(&self) -> &SpecifiedValues {
         &self.specified_values
     }
@@ -540,6 +575,8 @@ impl ElementData {
         use ElementData::*;
 
         let data: &dyn ElementTrait = match self {
+            Animate(d) =>              &**d,
+            AnimateTransform(d) =>     &**d,
             Circle(d) =>               &**d,
             ClipPath(d) =>             &**d,
             Ellipse(d) =>              &**d,
@@ -558,6 +595,7 @@ impl ElementData {
             Polyline(d) =>             &**d,
             RadialGradient(d) =>       &**d,
             Rect(d) =>                 &**d,
+            Set(d) =>                  &**d,
             Stop(d) =>                 &**d,
             Style(d) =>                &**d,
             Svg(d) =>                  &**d,
@@ -565,6 +603,7 @@ impl ElementData {
             Symbol(d) =>               &**d,
             Text(d) =>                 &**d,
             Text2(d) =>                 &**d,
+            TextPath(d) =>              &**d,
             TRef(d) =>                 &**d,
             TSpan(d) =>                &**d,
             Use(d) =>                  &**d,
@@ -613,6 +652,8 @@ impl ElementData {
         use ElementData::*;
 
         let data: &dyn ElementTrait = match self {
+            Animate(d) =>              &**d,
+            AnimateTransform(d) =>     &**d,
             Circle(d) =>               &**d,
             ClipPath(d) =>             &**d,
             Ellipse(d) =>              &**d,
@@ -631,6 +672,7 @@ impl ElementData {
             Polyline(d) =>             &**d,
             RadialGradient(d) =>       &**d,
             Rect(d) =>                 &**d,
+            Set(d) =>                  &**d,
             Stop(d) =>                 &**d,
             Style(d) =>                &**d,
             Svg(d) =>                  &**d,
@@ -638,6 +680,7 @@ impl ElementData {
             Symbol(d) =>               &**d,
             Text(d) =>                 &**d,
             Text2(d) =>                 &**d,
+            TextPath(d) =>              &**d,
             TRef(d) =>                 &**d,
             TSpan(d) =>                &**d,
             Use(d) =>                  &**d,
@@ -688,6 +731,8 @@ macro_rules! e {
 mod creators {
     use super::*;
 
+    e!(create_animate,                  Animate);
+    e!(create_animate_transform,        AnimateTransform);
     e!(create_circle,                   Circle);
     e!(create_clip_path,                ClipPath);
     e!(create_defs,                     NonRendering);
@@ -732,6 +777,7 @@ mod creators {
     e!(create_polyline,                 Polyline);
     e!(create_radial_gradient,          RadialGradient);
     e!(create_rect,                     Rect);
+    e!(create_set,                      Set);
     e!(create_stop,                     Stop);
     e!(create_style,                    Style);
     e!(create_svg,                      Svg);
@@ -739,6 +785,7 @@ mod creators {
     e!(create_symbol,                   Symbol);
     e!(create_text,                     Text);
     e!(create_text2,                    Text2);
+    e!(create_text_path,                TextPath);
     e!(create_tref,                     TRef);
     e!(create_tspan,                    TSpan);
     e!(create_use,                      Use);
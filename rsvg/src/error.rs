@@ -126,6 +126,9 @@ pub enum InternalRenderingError {
     /// An error from the rendering backend.
     Rendering(String),
 
+    /// An error from Cairo.
+    Cairo(cairo::Error),
+
     /// A particular implementation-defined limit was exceeded.
     LimitExceeded(ImplementationLimit),
 
@@ -169,6 +172,7 @@ impl fmt::Display for InternalRenderingError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
             InternalRenderingError::Rendering(ref s) => write!(f, "rendering error: {s}"),
+            InternalRenderingError::Cairo(ref e) => write!(f, "rendering error: {e:?}"),
             InternalRenderingError::LimitExceeded(ref l) => write!(f, "{l}"),
             InternalRenderingError::InvalidTransform => write!(f, "invalid transform"),
             InternalRenderingError::CircularReference(ref c) => {
@@ -184,7 +188,7 @@ impl fmt::Display for InternalRenderingError {
 
 impl From<cairo::Error> for InternalRenderingError {
     fn from(e: cairo::Error) -> InternalRenderingError {
-        InternalRenderingError::Rendering(format!("{e:?}"))
+        InternalRenderingError::Cairo(e)
     }
 }
 
@@ -355,6 +359,9 @@ pub enum AllowedUrlError {
 
     /// Error when canonicalizing either the file path or the base file path
     CanonicalizationError,
+
+    /// The application's resource callback did not allow loading the URL
+    DeniedByCallback,
 }
 
 impl fmt::Display for AllowedUrlError {
@@ -371,6 +378,7 @@ impl fmt::Display for AllowedUrlError {
             InvalidPath => write!(f, "invalid path"),
             BaseIsRoot => write!(f, "base is root"),
             CanonicalizationError => write!(f, "canonicalization error"),
+            DeniedByCallback => write!(f, "denied by resource callback"),
         }
     }
 }
@@ -422,9 +430,21 @@ pub enum LoadingError {
     /// I/O error.
     Io(String),
 
+    /// Error from GIO or GLib, usually an I/O error.
+    ///
+    /// The original error is available from [`std::error::Error::source`].
+    Glib(glib::Error),
+
     /// A particular implementation-defined limit was exceeded.
     LimitExceeded(ImplementationLimit),
 
+    /// The document uses a feature that librsvg does not support, and it was loaded with
+    /// [`crate::Loader::strict`].
+    Unsupported {
+        /// Description of the unsupported feature.
+        detail: String,
+    },
+
     /// Catch-all for loading errors.
     Other(String),
 }
@@ -487,9 +507,135 @@ pub enum ImplementationLimit {
     /// nest.  This is to avoid malicious SVGs which try to have layers that are nested
     /// extremely deep, as this could cause stack exhaustion.
     MaximumLayerNestingDepthExceeded,
+
+    /// Rendering drew more elements than allowed by [`crate::RenderLimits`].
+    TooManyDrawnElements,
+
+    /// Filters produced more pixels than allowed by [`crate::RenderLimits`].
+    TooManyFilterPixels,
+
+    /// Rendering took longer than the deadline in [`crate::RenderLimits`].
+    RenderingDeadlineExceeded,
+}
+
+/// A recoverable problem found while loading an SVG document.
+///
+/// Invalid attribute values do not keep a document from loading; librsvg ignores them and
+/// uses the attribute's default instead.  Use [`crate::Loader::collect_warnings`] to get a
+/// list of these problems through [`crate::SvgHandle::warnings`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadWarning {
+    /// Name of the element with the problem, e.g. `rect`.
+    pub element: String,
+
+    /// Name of the offending attribute, e.g. `width`.
+    pub attribute: String,
+
+    /// Description of the problem.
+    pub message: String,
+}
+
+impl fmt::Display for LoadWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "element {}: attribute {}: {}",
+            self.element, self.attribute, self.message
+        )
+    }
+}
+
+/// Error returned by [`crate::parse_color`] when a string is not a valid CSS color.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorParseError {
+    /// Description of the problem.
+    pub message: String,
+}
+
+impl error::Error for ColorParseError {}
+
+impl fmt::Display for ColorParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid color: {}", self.message)
+    }
+}
+
+/// Error returned by [`crate::parse_path_data`] when a string is not valid SVG path data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathParseError {
+    /// Offset in bytes from the start of the path data where the problem was found.
+    pub position: usize,
+
+    /// Description of the problem.
+    pub message: String,
+}
+
+impl error::Error for PathParseError {}
+
+impl fmt::Display for PathParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid path data at position {}: {}",
+            self.position, self.message
+        )
+    }
+}
+
+/// Error returned by [`crate::parse_transform`] when a string is not a valid transform list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransformParseError {
+    /// Description of the problem.
+    pub message: String,
+}
+
+impl error::Error for TransformParseError {}
+
+impl fmt::Display for TransformParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid transform: {}", self.message)
+    }
+}
+
+/// Error returned by [`crate::ViewBox::parse_str`] when a string is not a valid `viewBox`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewBoxParseError {
+    /// Description of the problem.
+    pub message: String,
 }
 
-impl error::Error for LoadingError {}
+impl error::Error for ViewBoxParseError {}
+
+impl fmt::Display for ViewBoxParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid viewBox: {}", self.message)
+    }
+}
+
+/// Error returned by [`crate::AspectRatio::parse_str`] when a string is not a valid
+/// `preserveAspectRatio` value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AspectRatioParseError {
+    /// Description of the problem.
+    pub message: String,
+}
+
+impl error::Error for AspectRatioParseError {}
+
+impl fmt::Display for AspectRatioParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid preserveAspectRatio: {}", self.message)
+    }
+}
+
+impl error::Error for LoadingError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            LoadingError::Glib(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
 
 impl fmt::Display for LoadingError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -500,7 +646,9 @@ impl fmt::Display for LoadingError {
             LoadingError::BadCss => write!(f, "invalid CSS"),
             LoadingError::NoSvgRoot => write!(f, "XML does not have <svg> root"),
             LoadingError::Io(ref s) => write!(f, "I/O error: {s}"),
+            LoadingError::Glib(ref e) => write!(f, "I/O error: {e}"),
             LoadingError::LimitExceeded(ref l) => write!(f, "{l}"),
+            LoadingError::Unsupported { ref detail } => write!(f, "unsupported feature: {detail}"),
             LoadingError::Other(ref s) => write!(f, "{s}"),
         }
     }
@@ -510,7 +658,7 @@ impl From<glib::Error> for LoadingError {
     fn from(e: glib::Error) -> LoadingError {
         // FIXME: this is somewhat fishy; not all GError are I/O errors, but in librsvg
         // most GError do come from gio.  Some come from GdkPixbufLoader, though.
-        LoadingError::Io(format!("{e}"))
+        LoadingError::Glib(e)
     }
 }
 
@@ -518,7 +666,10 @@ impl From<IoError> for LoadingError {
     fn from(e: IoError) -> LoadingError {
         match e {
             IoError::BadDataUrl => LoadingError::BadUrl,
-            IoError::Glib(e) => LoadingError::Io(format!("{e}")),
+            IoError::Glib(e) => LoadingError::Glib(e),
+
+            #[cfg(not(feature = "gio"))]
+            e @ IoError::NeedsGio(_) => LoadingError::Io(e.to_string()),
         }
     }
 }
@@ -549,6 +700,21 @@ impl fmt::Display for ImplementationLimit {
                 "maximum depth of {} nested layers has been exceeded",
                 limits::MAX_LAYER_NESTING_DEPTH,
             ),
+
+            ImplementationLimit::TooManyDrawnElements => {
+                write!(f, "exceeded the maximum number of drawn elements")
+            }
+
+            ImplementationLimit::TooManyFilterPixels => {
+                write!(
+                    f,
+                    "exceeded the maximum number of pixels produced by filters"
+                )
+            }
+
+            ImplementationLimit::RenderingDeadlineExceeded => {
+                write!(f, "exceeded the deadline for rendering")
+            }
         }
     }
 }
@@ -2,10 +2,12 @@
 
 use cssparser::{Color, Parser};
 use markup5ever::{expanded_name, local_name, namespace_url, ns};
+use std::rc::Rc;
 use std::slice::Iter;
 
 use crate::coord_units::CoordUnits;
 use crate::document::{AcquiredNodes, NodeId};
+use crate::dpi::Dpi;
 use crate::drawing_ctx::Viewport;
 use crate::element::{set_attribute, ElementData, ElementTrait};
 use crate::error::ValueErrorKind;
@@ -13,10 +15,11 @@ use crate::filter_func::FilterFunction;
 use crate::filters::{FilterResolveError, FilterSpec};
 use crate::length::*;
 use crate::node::{Node, NodeBorrow};
-use crate::parsers::{Parse, ParseValue};
+use crate::parsers::{Parse, ParseValue, ToCssString};
 use crate::rect::Rect;
 use crate::rsvg_log;
 use crate::session::Session;
+use crate::viewbox::ViewBox;
 use crate::xml::Attributes;
 use crate::{borrow_element_as, is_element_of_type};
 
@@ -102,6 +105,59 @@ pub enum FilterValue {
     Function(FilterFunction),
 }
 
+/// Maximum number of resolved filters that a [`FilterSpecCache`] keeps.
+const MAX_CACHED_FILTER_SPECS: usize = 64;
+
+/// Remembers the [`FilterSpec`] for each `<filter>` element across renders of a document.
+///
+/// Resolving a filter only depends on the `<filter>` element and its primitives, their
+/// computed values, and the DPI and viewBox of the viewport where the filter is used.  The
+/// computed values only change when the document gets cascaded again, which clears the cache.
+#[derive(Default)]
+pub struct FilterSpecCache {
+    entries: Vec<CachedFilterSpec>,
+}
+
+struct CachedFilterSpec {
+    node: Node,
+    dpi: Dpi,
+    vbox: ViewBox,
+    spec: Rc<FilterSpec>,
+}
+
+impl FilterSpecCache {
+    fn lookup(&self, node: &Node, viewport: &Viewport) -> Option<Rc<FilterSpec>> {
+        self.entries
+            .iter()
+            .find(|e| {
+                e.node == *node
+                    && e.dpi.x == viewport.dpi.x
+                    && e.dpi.y == viewport.dpi.y
+                    && e.vbox == viewport.vbox
+            })
+            .map(|e| e.spec.clone())
+    }
+
+    fn insert(&mut self, node: &Node, viewport: &Viewport, spec: Rc<FilterSpec>) {
+        // Rendering at many different sizes can produce many different viewports; don't
+        // let the cache grow without bounds.
+        if self.entries.len() >= MAX_CACHED_FILTER_SPECS {
+            self.entries.remove(0);
+        }
+
+        self.entries.push(CachedFilterSpec {
+            node: node.clone(),
+            dpi: viewport.dpi,
+            vbox: viewport.vbox,
+            spec,
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
 impl FilterValue {
     pub fn to_filter_spec(
         &self,
@@ -111,7 +167,7 @@ impl FilterValue {
         viewport: &Viewport,
         session: &Session,
         node_being_filtered_name: &str,
-    ) -> Result<FilterSpec, FilterResolveError> {
+    ) -> Result<Rc<FilterSpec>, FilterResolveError> {
         match *self {
             FilterValue::Url(ref node_id) => filter_spec_from_filter_node(
                 acquired_nodes,
@@ -121,9 +177,9 @@ impl FilterValue {
                 node_being_filtered_name,
             ),
 
-            FilterValue::Function(ref func) => {
-                Ok(func.to_filter_spec(user_space_params, current_color))
-            }
+            FilterValue::Function(ref func) => Ok(Rc::new(
+                func.to_filter_spec(user_space_params, current_color),
+            )),
         }
     }
 }
@@ -232,8 +288,9 @@ fn filter_spec_from_filter_node(
     session: &Session,
     node_id: &NodeId,
     node_being_filtered_name: &str,
-) -> Result<FilterSpec, FilterResolveError> {
+) -> Result<Rc<FilterSpec>, FilterResolveError> {
     let filter_viewport = ViewportGen::new(viewport);
+    let cache = acquired_nodes.filter_spec_cache();
 
     acquired_nodes
         .acquire(node_id)
@@ -252,7 +309,22 @@ fn filter_spec_from_filter_node(
 
             match *node.borrow_element_data() {
                 ElementData::Filter(_) => {
-                    extract_filter_from_filter_node(node, acquired_nodes, session, &filter_viewport)
+                    let cached = cache.borrow().lookup(node, viewport);
+
+                    if let Some(spec) = cached {
+                        return Ok(spec);
+                    }
+
+                    let spec = Rc::new(extract_filter_from_filter_node(
+                        node,
+                        acquired_nodes,
+                        session,
+                        &filter_viewport,
+                    )?);
+
+                    cache.borrow_mut().insert(node, viewport, spec.clone());
+
+                    Ok(spec)
                 }
 
                 _ => {
@@ -309,6 +381,18 @@ impl Parse for FilterValueList {
     }
 }
 
+impl ToCssString for FilterValueList {
+    fn to_css_string(&self) -> String {
+        self.iter()
+            .map(|v| match v {
+                FilterValue::Url(node_id) => format!("url({node_id})"),
+                FilterValue::Function(func) => func.to_css_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -28,7 +28,7 @@ use crate::filters::{
 };
 use crate::length::*;
 use crate::paint_server::resolve_color;
-use crate::parsers::{CustomIdent, NumberOptionalNumber, NumberOrPercentage, Parse};
+use crate::parsers::{CustomIdent, NumberOptionalNumber, NumberOrPercentage, Parse, ToCssString};
 use crate::unit_interval::UnitInterval;
 
 /// CSS Filter functions from the Filter Effects Module Level 1
@@ -372,7 +372,11 @@ impl Contrast {
 /// Both the `drop-shadow()` filter function and the `feDropShadow` element need to create
 /// a sequence of filter primitives (blur, offset, etc.) to build the drop shadow.  This
 /// function builds that sequence.
+///
+/// The shadow is made from the alpha channel of `in1`, and `in1` is then drawn over the
+/// shadow.  If `in1` is [`Input::Unspecified`], the `SourceGraphic` is used.
 pub fn drop_shadow_primitives(
+    in1: Input,
     dx: f64,
     dy: f64,
     std_deviation: NumberOptionalNumber<f64>,
@@ -380,10 +384,17 @@ pub fn drop_shadow_primitives(
 ) -> Vec<ResolvedPrimitive> {
     let offsetblur = CustomIdent("offsetblur".to_string());
 
+    // Only the alpha channel of the blurred input gets used by the composite below, so
+    // blurring the input itself is the same as blurring its alpha.
+    let (blur_input, graphic_input) = match in1 {
+        Input::Unspecified => (Input::SourceAlpha, Input::SourceGraphic),
+        _ => (in1.clone(), in1),
+    };
+
     let gaussian_blur = ResolvedPrimitive {
         primitive: Primitive::default(),
         params: PrimitiveParams::GaussianBlur(GaussianBlur {
-            in1: Input::SourceAlpha,
+            in1: blur_input,
             std_deviation,
             ..GaussianBlur::default()
         }),
@@ -421,7 +432,7 @@ pub fn drop_shadow_primitives(
             merge_nodes: vec![
                 MergeNode::default(),
                 MergeNode {
-                    in1: Input::SourceGraphic,
+                    in1: graphic_input,
                     ..MergeNode::default()
                 },
             ],
@@ -447,7 +458,8 @@ impl DropShadow {
             .map(|c| resolve_color(c, UnitInterval::clamp(1.0), &default_color))
             .unwrap_or(default_color);
 
-        let resolved_primitives = drop_shadow_primitives(dx, dy, std_deviation, color);
+        let resolved_primitives =
+            drop_shadow_primitives(Input::Unspecified, dx, dy, std_deviation, color);
 
         let primitives = resolved_primitives
             .into_iter()
@@ -672,6 +684,34 @@ impl Parse for FilterFunction {
     }
 }
 
+impl ToCssString for FilterFunction {
+    fn to_css_string(&self) -> String {
+        fn arg<T: ToCssString>(v: &Option<T>) -> Option<String> {
+            v.as_ref().map(|v| v.to_css_string())
+        }
+
+        let (name, args) = match self {
+            FilterFunction::Blur(v) => ("blur", vec![arg(&v.std_deviation)]),
+            FilterFunction::Brightness(v) => ("brightness", vec![arg(&v.proportion)]),
+            FilterFunction::Contrast(v) => ("contrast", vec![arg(&v.proportion)]),
+            FilterFunction::DropShadow(v) => (
+                "drop-shadow",
+                vec![arg(&v.color), arg(&v.dx), arg(&v.dy), arg(&v.std_deviation)],
+            ),
+            FilterFunction::Grayscale(v) => ("grayscale", vec![arg(&v.proportion)]),
+            FilterFunction::HueRotate(v) => ("hue-rotate", vec![arg(&v.angle)]),
+            FilterFunction::Invert(v) => ("invert", vec![arg(&v.proportion)]),
+            FilterFunction::Opacity(v) => ("opacity", vec![arg(&v.proportion)]),
+            FilterFunction::Saturate(v) => ("saturate", vec![arg(&v.proportion)]),
+            FilterFunction::Sepia(v) => ("sepia", vec![arg(&v.proportion)]),
+        };
+
+        let args: Vec<String> = args.into_iter().flatten().collect();
+
+        format!("{name}({})", args.join(" "))
+    }
+}
+
 impl FilterFunction {
     // If this function starts actually returning an Err, remove this Clippy exception:
     #[allow(clippy::unnecessary_wraps)]
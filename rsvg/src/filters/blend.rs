@@ -1,14 +1,11 @@
-use cssparser::Parser;
 use markup5ever::{expanded_name, local_name, namespace_url, ns};
 
 use crate::document::AcquiredNodes;
 use crate::drawing_ctx::DrawingCtx;
 use crate::element::{set_attribute, ElementTrait};
-use crate::error::*;
 use crate::node::{CascadedValues, Node};
-use crate::parse_identifiers;
-use crate::parsers::{Parse, ParseValue};
-use crate::properties::ColorInterpolationFilters;
+use crate::parsers::ParseValue;
+use crate::properties::{ColorInterpolationFilters, MixBlendMode};
 use crate::rect::IRect;
 use crate::session::Session;
 use crate::surface_utils::shared_surface::Operator;
@@ -21,28 +18,6 @@ use super::{
     ResolvedPrimitive,
 };
 
-/// Enumeration of the possible blending modes.
-#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash)]
-enum Mode {
-    #[default]
-    Normal,
-    Multiply,
-    Screen,
-    Darken,
-    Lighten,
-    Overlay,
-    ColorDodge,
-    ColorBurn,
-    HardLight,
-    SoftLight,
-    Difference,
-    Exclusion,
-    HslHue,
-    HslSaturation,
-    HslColor,
-    HslLuminosity,
-}
-
 /// The `feBlend` filter primitive.
 #[derive(Default)]
 pub struct FeBlend {
@@ -55,7 +30,7 @@ pub struct FeBlend {
 pub struct Blend {
     in1: Input,
     in2: Input,
-    mode: Mode,
+    mode: MixBlendMode,
     color_interpolation_filters: ColorInterpolationFilters,
 }
 
@@ -127,34 +102,10 @@ impl FilterEffect for FeBlend {
     }
 }
 
-impl Parse for Mode {
-    fn parse<'i>(parser: &mut Parser<'i, '_>) -> Result<Self, ParseError<'i>> {
-        Ok(parse_identifiers!(
-            parser,
-            "normal" => Mode::Normal,
-            "multiply" => Mode::Multiply,
-            "screen" => Mode::Screen,
-            "darken" => Mode::Darken,
-            "lighten" => Mode::Lighten,
-            "overlay" => Mode::Overlay,
-            "color-dodge" => Mode::ColorDodge,
-            "color-burn" => Mode::ColorBurn,
-            "hard-light" => Mode::HardLight,
-            "soft-light" => Mode::SoftLight,
-            "difference" => Mode::Difference,
-            "exclusion" => Mode::Exclusion,
-            "hue" => Mode::HslHue,
-            "saturation" => Mode::HslSaturation,
-            "color" => Mode::HslColor,
-            "luminosity" => Mode::HslLuminosity,
-        )?)
-    }
-}
-
-impl From<Mode> for Operator {
+impl From<MixBlendMode> for Operator {
     #[inline]
-    fn from(x: Mode) -> Self {
-        use Mode::*;
+    fn from(x: MixBlendMode) -> Self {
+        use MixBlendMode::*;
 
         match x {
             Normal => Operator::Over,
@@ -169,10 +120,10 @@ impl From<Mode> for Operator {
             SoftLight => Operator::SoftLight,
             Difference => Operator::Difference,
             Exclusion => Operator::Exclusion,
-            HslHue => Operator::HslHue,
-            HslSaturation => Operator::HslSaturation,
-            HslColor => Operator::HslColor,
-            HslLuminosity => Operator::HslLuminosity,
+            Hue => Operator::HslHue,
+            Saturation => Operator::HslSaturation,
+            Color => Operator::HslColor,
+            Luminosity => Operator::HslLuminosity,
         }
     }
 }
@@ -279,6 +279,38 @@ macro_rules! func_or_default {
     };
 }
 
+/// Returns a function that transfers a premultiplied color component.
+///
+/// The function takes the component's value, the pixel's original alpha, and the pixel's
+/// new alpha, and returns the new premultiplied value.  The transfer function is applied to
+/// the unpremultiplied value, and its result is clamped to [0, 1] before premultiplying it
+/// again, as the spec requires.
+fn compute_func(func: &FeFuncCommon) -> impl Fn(u8, f64, f64) -> u8 {
+    let compute = func.function();
+    let params = func.function_parameters();
+
+    move |value, alpha, new_alpha| {
+        let value = f64::from(value) / 255f64;
+
+        let unpremultiplied = if alpha == 0f64 { 0f64 } else { value / alpha };
+
+        let new_value = compute(&params, unpremultiplied);
+        let new_value = clamp(new_value, 0f64, 1f64);
+
+        ((new_value * new_alpha * 255f64) + 0.5) as u8
+    }
+}
+
+/// Returns a function that transfers the alpha component, as a value in [0, 1].
+///
+/// The result is clamped, since the color components get premultiplied by it.
+fn compute_alpha_func(func: &FeFuncCommon) -> impl Fn(f64) -> f64 {
+    let compute = func.function();
+    let params = func.function_parameters();
+
+    move |alpha| clamp(compute(&params, alpha), 0f64, 1f64)
+}
+
 macro_rules! get_func_x_node {
     ($func_node:ident, $func_type:ident) => {
         $func_node
@@ -316,30 +348,12 @@ impl ComponentTransfer {
             input_1.surface().surface_type(),
         )?;
 
-        fn compute_func(func: &FeFuncCommon) -> impl Fn(u8, f64, f64) -> u8 {
-            let compute = func.function();
-            let params = func.function_parameters();
-
-            move |value, alpha, new_alpha| {
-                let value = f64::from(value) / 255f64;
-
-                let unpremultiplied = if alpha == 0f64 { 0f64 } else { value / alpha };
-
-                let new_value = compute(&params, unpremultiplied);
-                let new_value = clamp(new_value, 0f64, 1f64);
-
-                ((new_value * new_alpha * 255f64) + 0.5) as u8
-            }
-        }
-
         let compute_r = compute_func(&self.functions.r.0);
         let compute_g = compute_func(&self.functions.g.0);
         let compute_b = compute_func(&self.functions.b.0);
 
         // Alpha gets special handling since everything else depends on it.
-        let compute_a = self.functions.a.0.function();
-        let params_a = self.functions.a.0.function_parameters();
-        let compute_a = |alpha| compute_a(&params_a, alpha);
+        let compute_a = compute_alpha_func(&self.functions.a.0);
 
         // Do the actual processing.
         surface.modify(&mut |data, stride| {
@@ -455,4 +469,107 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn discrete_uses_floor_of_c_times_n() {
+        let func = FeFuncCommon {
+            function_type: FunctionType::Discrete,
+            table_values: vec![0.1, 0.2, 0.3, 0.4],
+            ..FeFuncCommon::default()
+        };
+        let params = func.function_parameters();
+
+        assert_eq!(discrete(&params, 0.0), 0.1);
+        assert_eq!(discrete(&params, 0.24), 0.1);
+        assert_eq!(discrete(&params, 0.25), 0.2);
+        assert_eq!(discrete(&params, 0.49), 0.2);
+        assert_eq!(discrete(&params, 0.5), 0.3);
+        assert_eq!(discrete(&params, 0.74), 0.3);
+        assert_eq!(discrete(&params, 0.75), 0.4);
+
+        // C = 1 would be k = n, which is clamped to the last value.
+        assert_eq!(discrete(&params, 1.0), 0.4);
+
+        // Each quarter of the input range maps to a single output value.
+        let compute = compute_func(&func);
+        assert_eq!(compute(63, 1.0, 1.0), 26);
+        assert_eq!(compute(64, 1.0, 1.0), 51);
+        assert_eq!(compute(255, 1.0, 1.0), 102);
+    }
+
+    #[test]
+    fn table_interpolates_and_ends_at_last_value() {
+        let func = FeFuncCommon {
+            function_type: FunctionType::Table,
+            table_values: vec![0.0, 1.0, 0.5],
+            ..FeFuncCommon::default()
+        };
+        let params = func.function_parameters();
+
+        assert_eq!(table(&params, 0.0), 0.0);
+        assert_eq!(table(&params, 0.25), 0.5);
+        assert_eq!(table(&params, 0.5), 1.0);
+        assert_eq!(table(&params, 0.75), 0.75);
+        assert_eq!(table(&params, 1.0), 0.5);
+    }
+
+    #[test]
+    fn gamma_applies_amplitude_and_offset_before_clamping() {
+        let func = FeFuncCommon {
+            function_type: FunctionType::Gamma,
+            amplitude: 2.0,
+            exponent: 0.5,
+            offset: 0.1,
+            ..FeFuncCommon::default()
+        };
+        let params = func.function_parameters();
+
+        assert!((gamma(&params, 0.0) - 0.1).abs() < 1e-12);
+        assert!((gamma(&params, 0.01) - 0.3).abs() < 1e-12);
+        assert!((gamma(&params, 0.25) - 1.1).abs() < 1e-12);
+
+        let compute = compute_func(&func);
+
+        // 2 * sqrt(1 / 255) + 0.1 = 0.2252
+        assert_eq!(compute(1, 1.0, 1.0), 57);
+
+        // Values above 1 get clamped.
+        assert_eq!(compute(64, 1.0, 1.0), 255);
+
+        // The function is applied to unpremultiplied values; with alpha = 0.5, a
+        // premultiplied value of 32 is 64 / 255 unpremultiplied.
+        assert_eq!(compute(32, 0.5, 0.5), 128);
+
+        // Negative results get clamped too.
+        let func = FeFuncCommon {
+            offset: -1.0,
+            ..func
+        };
+
+        // 2 * sqrt(100 / 255) - 1 = 0.2524
+        assert_eq!(compute_func(&func)(100, 1.0, 1.0), 64);
+        assert_eq!(compute_func(&func)(1, 1.0, 1.0), 0);
+    }
+
+    #[test]
+    fn alpha_gets_clamped() {
+        let func = FeFuncCommon {
+            function_type: FunctionType::Linear,
+            slope: 2.0,
+            intercept: 0.5,
+            ..FeFuncCommon::default()
+        };
+
+        let compute = compute_alpha_func(&func);
+        assert_eq!(compute(0.0), 0.5);
+        assert_eq!(compute(0.5), 1.0);
+
+        let func = FeFuncCommon {
+            intercept: -0.5,
+            ..func
+        };
+
+        let compute = compute_alpha_func(&func);
+        assert_eq!(compute(0.1), 0.0);
+    }
 }
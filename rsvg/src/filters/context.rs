@@ -73,6 +73,8 @@ pub struct FilterContext {
     primitive_units: CoordUnits,
     /// The filter effects region.
     effects_region: Rect,
+    /// The filter effects region before clipping it to the source surface.
+    unclipped_effects_region: Rect,
 
     /// The filter element affine matrix.
     ///
@@ -143,6 +145,8 @@ impl FilterContext {
             ));
         }
 
+        let unclipped_effects_region = affine.transform_rect(&filter.rect);
+
         let effects_region = {
             let mut bbox = BoundingBox::new();
             let other_bbox = BoundingBox::new()
@@ -173,6 +177,7 @@ impl FilterContext {
             fill_paint_surface: OnceCell::new(),
             primitive_units: filter.primitive_units,
             effects_region,
+            unclipped_effects_region,
             _affine: affine,
             paffine,
         })
@@ -280,6 +285,12 @@ impl FilterContext {
         self.effects_region
     }
 
+    /// Returns the filter effects region, without clipping it to the source surface.
+    #[inline]
+    pub fn unclipped_effects_region(&self) -> Rect {
+        self.unclipped_effects_region
+    }
+
     /// Get a filter primitive's default input as if its `in=\"...\"` were not specified.
     ///
     /// Per <https://drafts.fxtf.org/filter-effects/#element-attrdef-filter-primitive-in>,
@@ -3,9 +3,7 @@ use markup5ever::{expanded_name, local_name, namespace_url, ns};
 use nalgebra::{DMatrix, Dyn, VecStorage};
 use xml5ever::QualName;
 
-use crate::bench_only::{
-    EdgeMode, ExclusiveImageSurface, ImageSurfaceDataExt, Pixel, PixelRectangle, Pixels,
-};
+use crate::bench_only::{EdgeMode, ExclusiveImageSurface, Pixel, PixelRectangle};
 use crate::document::AcquiredNodes;
 use crate::drawing_ctx::DrawingCtx;
 use crate::element::{set_attribute, ElementTrait};
@@ -17,6 +15,7 @@ use crate::properties::ColorInterpolationFilters;
 use crate::rect::IRect;
 use crate::rsvg_log;
 use crate::session::Session;
+use crate::surface_utils::shared_surface::{fill_pixels, PARALLEL_FILTERS};
 use crate::util::clamp;
 use crate::xml::Attributes;
 
@@ -62,10 +61,12 @@ impl KernelUnitLength {
             } // Only accept positive values
             Ok(_) => {
                 rsvg_log!(session, "ignoring attribute with non-positive values");
+                session.add_warning(attr, String::from("values must be positive"));
                 Err(())
             }
             Err(e) => {
                 rsvg_log!(session, "ignoring attribute with invalid value: {}", e);
+                session.add_warning(&e.attr, e.err.to_string());
                 Err(())
             }
         }
@@ -240,68 +241,77 @@ impl ConvolveMatrix {
             input_1.surface().surface_type(),
         )?;
 
-        surface.modify(&mut |data, stride| {
-            for (x, y, pixel) in Pixels::within(&input_surface, bounds) {
-                // Compute the convolution rectangle bounds.
-                let kernel_bounds = IRect::new(
-                    x as i32 - target_x as i32,
-                    y as i32 - target_y as i32,
-                    x as i32 - target_x as i32 + self.order.0 as i32,
-                    y as i32 - target_y as i32 + self.order.1 as i32,
-                );
-
-                // Do the convolution.
-                let mut r = 0.0;
-                let mut g = 0.0;
-                let mut b = 0.0;
-                let mut a = 0.0;
-
-                for (x, y, pixel) in
-                    PixelRectangle::within(&input_surface, bounds, kernel_bounds, self.edge_mode)
-                {
-                    let kernel_x = (kernel_bounds.x1 - x - 1) as usize;
-                    let kernel_y = (kernel_bounds.y1 - y - 1) as usize;
-
-                    r += f64::from(pixel.r) / 255.0 * matrix[(kernel_y, kernel_x)];
-                    g += f64::from(pixel.g) / 255.0 * matrix[(kernel_y, kernel_x)];
-                    b += f64::from(pixel.b) / 255.0 * matrix[(kernel_y, kernel_x)];
-
-                    if !self.preserve_alpha {
-                        a += f64::from(pixel.a) / 255.0 * matrix[(kernel_y, kernel_x)];
-                    }
-                }
+        // Computes the output pixel at (x, y).
+        let convolve_pixel = |x: u32, y: u32| -> Pixel {
+            let pixel = input_surface.get_pixel(x, y);
 
-                // If preserve_alpha is true, set a to the source alpha value.
-                if self.preserve_alpha {
-                    a = f64::from(pixel.a) / 255.0;
-                } else {
-                    a = a / divisor + self.bias;
+            // Compute the convolution rectangle bounds.
+            let kernel_bounds = IRect::new(
+                x as i32 - target_x as i32,
+                y as i32 - target_y as i32,
+                x as i32 - target_x as i32 + self.order.0 as i32,
+                y as i32 - target_y as i32 + self.order.1 as i32,
+            );
+
+            // Do the convolution.
+            let mut r = 0.0;
+            let mut g = 0.0;
+            let mut b = 0.0;
+            let mut a = 0.0;
+
+            for (x, y, pixel) in
+                PixelRectangle::within(&input_surface, bounds, kernel_bounds, self.edge_mode)
+            {
+                let kernel_x = (kernel_bounds.x1 - x - 1) as usize;
+                let kernel_y = (kernel_bounds.y1 - y - 1) as usize;
+
+                r += f64::from(pixel.r) / 255.0 * matrix[(kernel_y, kernel_x)];
+                g += f64::from(pixel.g) / 255.0 * matrix[(kernel_y, kernel_x)];
+                b += f64::from(pixel.b) / 255.0 * matrix[(kernel_y, kernel_x)];
+
+                if !self.preserve_alpha {
+                    a += f64::from(pixel.a) / 255.0 * matrix[(kernel_y, kernel_x)];
                 }
+            }
 
-                let clamped_a = clamp(a, 0.0, 1.0);
+            // If preserve_alpha is true, set a to the source alpha value.
+            if self.preserve_alpha {
+                a = f64::from(pixel.a) / 255.0;
+            } else {
+                a = a / divisor + self.bias;
+            }
 
-                let compute = |x| {
-                    let x = x / divisor + self.bias * a;
+            let clamped_a = clamp(a, 0.0, 1.0);
 
-                    let x = if self.preserve_alpha {
-                        // Premultiply the output value.
-                        clamp(x, 0.0, 1.0) * clamped_a
-                    } else {
-                        clamp(x, 0.0, clamped_a)
-                    };
+            let compute = |x| {
+                let x = x / divisor + self.bias * a;
 
-                    ((x * 255.0) + 0.5) as u8
+                let x = if self.preserve_alpha {
+                    // Premultiply the output value.
+                    clamp(x, 0.0, 1.0) * clamped_a
+                } else {
+                    clamp(x, 0.0, clamped_a)
                 };
 
-                let output_pixel = Pixel {
-                    r: compute(r),
-                    g: compute(g),
-                    b: compute(b),
-                    a: ((clamped_a * 255.0) + 0.5) as u8,
-                };
+                ((x * 255.0) + 0.5) as u8
+            };
 
-                data.set_pixel(stride, output_pixel, x, y);
+            Pixel {
+                r: compute(r),
+                g: compute(g),
+                b: compute(b),
+                a: ((clamped_a * 255.0) + 0.5) as u8,
             }
+        };
+
+        surface.modify(&mut |data, stride| {
+            fill_pixels(
+                &mut data[..],
+                stride,
+                bounds,
+                PARALLEL_FILTERS,
+                convolve_pixel,
+            );
         });
 
         let mut surface = surface.share()?;
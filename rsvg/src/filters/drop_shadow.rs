@@ -77,11 +77,20 @@ impl FilterEffect for FeDropShadow {
             &values.color().0,
         );
 
-        Ok(drop_shadow_primitives(
+        let mut primitives = drop_shadow_primitives(
+            self.params.in1.clone(),
             self.params.dx,
             self.params.dy,
             self.params.std_deviation,
             color,
-        ))
+        );
+
+        // The element's subregion and result name apply to the final merge, which is what
+        // later primitives see as the output of the feDropShadow.
+        if let Some(last) = primitives.last_mut() {
+            last.primitive = self.base.clone();
+        }
+
+        Ok(primitives)
     }
 }
@@ -6,7 +6,8 @@ use crate::drawing_ctx::{DrawingCtx, SvgNesting};
 use crate::element::{set_attribute, ElementTrait};
 use crate::href::{is_href, set_href};
 use crate::image::checked_i32;
-use crate::node::{CascadedValues, Node};
+use crate::is_element_of_type;
+use crate::node::{CascadedValues, Node, NodeBorrow};
 use crate::parsers::ParseValue;
 use crate::properties::ComputedValues;
 use crate::rect::Rect;
@@ -89,20 +90,41 @@ impl Image {
             Source::None => return Err(FilterError::InvalidInput),
 
             Source::Node(node, ref name) => {
-                if let Ok(acquired) = acquired_nodes.acquire_ref(node) {
-                    rsvg_log!(draw_ctx.session(), "(feImage \"{}\"", name);
-                    let res = self.render_node(
-                        ctx,
-                        acquired_nodes,
-                        draw_ctx,
-                        bounds.clipped,
-                        acquired.get(),
+                if acquired_nodes.would_render_acquired_node(node) {
+                    rsvg_log!(
+                        draw_ctx.session(),
+                        "feImage \"{}\" references an element that is being filtered; ignoring it",
+                        name
                     );
-                    rsvg_log!(draw_ctx.session(), ")");
-                    res?
-                } else {
-                    return Err(FilterError::InvalidInput);
+                    acquired_nodes.mark_circular_reference(node);
+
+                    // The referenced element does not get rendered, so the result is
+                    // transparent black.
+                    return Ok(FilterOutput {
+                        surface: SharedImageSurface::empty(
+                            ctx.source_graphic().width(),
+                            ctx.source_graphic().height(),
+                            SurfaceType::SRgb,
+                        )?,
+                        bounds: bounds.clipped.into(),
+                    });
                 }
+
+                // A <use> element acquires itself while it is rendered.
+                let _acquired = if is_element_of_type!(node, Use) {
+                    None
+                } else {
+                    Some(
+                        acquired_nodes
+                            .acquire_ref(node)
+                            .map_err(|_| FilterError::InvalidInput)?,
+                    )
+                };
+
+                rsvg_log!(draw_ctx.session(), "(feImage \"{}\"", name);
+                let res = self.render_node(ctx, acquired_nodes, draw_ctx, bounds.clipped, node);
+                rsvg_log!(draw_ctx.session(), ")");
+                res?
             }
 
             Source::ExternalImage(ref href) => {
@@ -280,6 +302,13 @@ impl Image {
     }
 }
 
+impl FeImage {
+    /// Returns the `href` attribute, as written in the document.
+    pub fn href(&self) -> Option<&str> {
+        self.params.href.as_deref()
+    }
+}
+
 impl FilterEffect for FeImage {
     fn resolve(
         &self,
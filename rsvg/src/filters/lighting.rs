@@ -26,7 +26,7 @@ use crate::rect::IRect;
 use crate::session::Session;
 use crate::surface_utils::{
     shared_surface::{ExclusiveImageSurface, SharedImageSurface, SurfaceType},
-    ImageSurfaceDataExt, Pixel,
+    srgb, ImageSurfaceDataExt, Pixel,
 };
 use crate::transform::Transform;
 use crate::unit_interval::UnitInterval;
@@ -509,14 +509,29 @@ macro_rules! impl_lighting_filter {
 
                 let source = self.light.source.transform(ctx.paffine());
 
+                let surface_type = SurfaceType::from(self.light.color_interpolation_filters);
+
                 let mut surface = ExclusiveImageSurface::new(
                     input_surface.width(),
                     input_surface.height(),
-                    SurfaceType::from(self.light.color_interpolation_filters),
+                    surface_type,
                 )?;
 
                 let lighting_color = color_to_rgba(&self.light.lighting_color);
 
+                // The lighting-color is an sRGB value, but the lighting gets computed in
+                // the output surface's color space, so the color must be converted to it.
+                let lighting_color = if surface_type == SurfaceType::LinearRgb {
+                    RGBA {
+                        red: lighting_color.red.map(srgb::linearize),
+                        green: lighting_color.green.map(srgb::linearize),
+                        blue: lighting_color.blue.map(srgb::linearize),
+                        ..lighting_color
+                    }
+                } else {
+                    lighting_color
+                };
+
                 {
                     let output_stride = surface.stride() as usize;
                     let mut output_data = surface.data();
@@ -1013,7 +1028,7 @@ mod tests {
 </svg>
 "#,
         );
-        let mut acquired_nodes = AcquiredNodes::new(&document, None::<gio::Cancellable>);
+        let mut acquired_nodes = AcquiredNodes::new(&document, None);
 
         let node = document.lookup_internal_node("diffuse_distant").unwrap();
         let lighting = borrow_element_as!(node, FeDiffuseLighting);
@@ -275,6 +275,8 @@ pub fn render(
         *node_bbox,
     )
     .and_then(|mut filter_ctx| {
+        draw_ctx.record_filter_region(filter_ctx.unclipped_effects_region());
+
         // the message has an unclosed parenthesis; we'll close it below.
         rsvg_log!(
             session,
@@ -283,6 +285,10 @@ pub fn render(
             filter_ctx.effects_region()
         );
         for user_space_primitive in &filter.primitives {
+            draw_ctx
+                .check_render_deadline()
+                .map_err(FilterError::Rendering)?;
+
             let start = Instant::now();
 
             match render_primitive(user_space_primitive, &filter_ctx, acquired_nodes, draw_ctx) {
@@ -295,6 +301,10 @@ pub fn render(
                         elapsed.as_secs() as f64 + f64::from(elapsed.subsec_nanos()) / 1e9
                     );
 
+                    draw_ctx
+                        .count_filter_pixels(output.bounds)
+                        .map_err(FilterError::Rendering)?;
+
                     filter_ctx.store_result(FilterResult {
                         name: user_space_primitive.result.clone(),
                         output,
@@ -312,9 +322,15 @@ pub fn render(
                     // close the opening parenthesis from the message at the start of this function
                     rsvg_log!(session, ")");
 
-                    // Exit early on Cairo errors. Continue rendering otherwise.
-                    if let FilterError::CairoError(status) = err {
-                        return Err(FilterError::CairoError(status));
+                    // Exit early on Cairo errors and exceeded render limits. Continue
+                    // rendering otherwise.
+                    match err {
+                        FilterError::CairoError(_)
+                        | FilterError::Rendering(InternalRenderingError::LimitExceeded(_)) => {
+                            return Err(err);
+                        }
+
+                        _ => (),
                     }
                 }
             }
@@ -331,6 +347,11 @@ pub fn render(
             Err(InternalRenderingError::from(status))
         }
 
+        FilterError::Rendering(InternalRenderingError::LimitExceeded(limit)) => {
+            // Do not keep rendering once a render limit is exceeded
+            Err(InternalRenderingError::LimitExceeded(limit))
+        }
+
         _ => {
             // ignore other filter errors and just return an empty surface
             Ok(SharedImageSurface::empty(
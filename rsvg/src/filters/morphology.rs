@@ -15,7 +15,7 @@ use crate::rect::IRect;
 use crate::session::Session;
 use crate::surface_utils::{
     iterators::{PixelRectangle, Pixels},
-    shared_surface::ExclusiveImageSurface,
+    shared_surface::{ExclusiveImageSurface, SharedImageSurface},
     EdgeMode, ImageSurfaceDataExt, Pixel,
 };
 use crate::xml::Attributes;
@@ -109,34 +109,62 @@ impl Morphology {
 
         let NumberOptionalNumber(rx, ry) = self.radius;
 
-        if rx <= 0.0 && ry <= 0.0 {
+        // https://www.w3.org/TR/filter-effects/#element-attrdef-femorphology-radius
+        //
+        // "A negative or zero value disables the effect of the given filter primitive
+        // (i.e., the result is the filter input image)."
+        //
+        // We read "value" as the whole attribute: a negative radius in either axis, or
+        // zero in both, returns the input unchanged.
+        //
+        // A zero radius in only one axis still runs the filter.  The spec describes the
+        // kernel as "a rectangle with a width of 2*x-radius and a height of 2*y-radius",
+        // so a zero radius leaves the kernel one pixel wide along that axis: that axis is
+        // left unchanged, and the other axis is eroded or dilated as usual.
+        if rx < 0.0 || ry < 0.0 || (rx == 0.0 && ry == 0.0) {
             return Ok(FilterOutput {
                 surface: input_1.surface().clone(),
                 bounds,
             });
         }
 
-        let (rx, ry) = ctx.paffine().transform_distance(rx, ry);
+        // Scale each radius by the length of its axis in the filter's coordinate system.
+        let paffine = ctx.paffine();
+        let (rx_x, rx_y) = paffine.transform_distance(rx, 0.0);
+        let (ry_x, ry_y) = paffine.transform_distance(0.0, ry);
 
-        // The radii can become negative here due to the transform.
-        // Additionally The radii being excessively large causes cpu hangups
-        let (rx, ry) = (rx.abs().min(10.0), ry.abs().min(10.0));
+        // The radii being excessively large causes cpu hangups
+        let rx = rx_x.hypot(rx_y).min(10.0).round() as i32;
+        let ry = ry_x.hypot(ry_y).min(10.0).round() as i32;
 
-        let mut surface = ExclusiveImageSurface::new(
-            ctx.source_graphic().width(),
-            ctx.source_graphic().height(),
-            input_1.surface().surface_type(),
-        )?;
+        // The rectangular kernel is separable, so apply it horizontally and then vertically.
+        let horizontal = self.apply_along_axis(input_1.surface(), bounds, rx, 0)?;
+        let surface = self.apply_along_axis(&horizontal, bounds, 0, ry)?;
+
+        Ok(FilterOutput { surface, bounds })
+    }
+
+    /// Takes the per-channel minimum or maximum of the pixels within `rx` columns and `ry`
+    /// rows of each pixel.
+    fn apply_along_axis(
+        &self,
+        input: &SharedImageSurface,
+        bounds: IRect,
+        rx: i32,
+        ry: i32,
+    ) -> Result<SharedImageSurface, FilterError> {
+        if rx == 0 && ry == 0 {
+            return Ok(input.clone());
+        }
+
+        let mut surface =
+            ExclusiveImageSurface::new(input.width(), input.height(), input.surface_type())?;
 
         surface.modify(&mut |data, stride| {
-            for (x, y, _pixel) in Pixels::within(input_1.surface(), bounds) {
+            for (x, y, _pixel) in Pixels::within(input, bounds) {
                 // Compute the kernel rectangle bounds.
-                let kernel_bounds = IRect::new(
-                    (f64::from(x) - rx).floor() as i32,
-                    (f64::from(y) - ry).floor() as i32,
-                    (f64::from(x) + rx).ceil() as i32 + 1,
-                    (f64::from(y) + ry).ceil() as i32 + 1,
-                );
+                let (xi, yi) = (x as i32, y as i32);
+                let kernel_bounds = IRect::new(xi - rx, yi - ry, xi + rx + 1, yi + ry + 1);
 
                 // Compute the new pixel values.
                 let initial = match self.operator {
@@ -152,7 +180,7 @@ impl Morphology {
                 };
 
                 for (_x, _y, pixel) in
-                    PixelRectangle::within(input_1.surface(), bounds, kernel_bounds, EdgeMode::None)
+                    PixelRectangle::within(input, bounds, kernel_bounds, EdgeMode::None)
                 {
                     let op = match self.operator {
                         Operator::Erode => min,
@@ -169,10 +197,7 @@ impl Morphology {
             }
         });
 
-        Ok(FilterOutput {
-            surface: surface.share()?,
-            bounds,
-        })
+        Ok(surface.share()?)
     }
 }
 
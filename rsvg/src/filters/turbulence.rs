@@ -9,7 +9,7 @@ use crate::node::{CascadedValues, Node};
 use crate::parse_identifiers;
 use crate::parsers::{NumberOptionalNumber, Parse, ParseValue};
 use crate::properties::ColorInterpolationFilters;
-use crate::rect::IRect;
+use crate::rect::{IRect, Rect};
 use crate::rsvg_log;
 use crate::session::Session;
 use crate::surface_utils::{
@@ -154,6 +154,9 @@ struct NoiseGenerator {
     stitch_tiles: StitchTiles,
     type_: NoiseType,
 
+    // The tile to stitch, in the filter's user space.
+    tile_x: f64,
+    tile_y: f64,
     tile_width: f64,
     tile_height: f64,
 
@@ -176,8 +179,7 @@ impl NoiseGenerator {
         num_octaves: i32,
         type_: NoiseType,
         stitch_tiles: StitchTiles,
-        tile_width: f64,
-        tile_height: f64,
+        tile: Rect,
     ) -> Self {
         let mut rv = Self {
             base_frequency,
@@ -185,8 +187,10 @@ impl NoiseGenerator {
             type_,
             stitch_tiles,
 
-            tile_width,
-            tile_height,
+            tile_x: tile.x0,
+            tile_y: tile.y0,
+            tile_width: tile.width(),
+            tile_height: tile.height(),
 
             lattice_selector: [0; B_SIZE + B_SIZE + 2],
             gradient: [[[0.0; 2]; B_SIZE + B_SIZE + 2]; 4],
@@ -287,7 +291,7 @@ impl NoiseGenerator {
         lerp(sy, a, b)
     }
 
-    fn turbulence(&self, color_channel: usize, point: [f64; 2], tile_x: f64, tile_y: f64) -> f64 {
+    fn turbulence(&self, color_channel: usize, point: [f64; 2]) -> f64 {
         let mut stitch_info = None;
         let mut base_frequency = self.base_frequency;
 
@@ -314,14 +318,17 @@ impl NoiseGenerator {
                 }
             }
 
-            // Set up initial stitch values.
+            // Set up initial stitch values.  The wrap points are relative to the tile's
+            // origin, not to the point being computed.
             let width = (self.tile_width * base_frequency.0 + 0.5) as usize;
             let height = (self.tile_height * base_frequency.1 + 0.5) as usize;
             stitch_info = Some(StitchInfo {
                 width,
-                wrap_x: (tile_x * base_frequency.0) as usize + PERLIN_N as usize + width,
+                wrap_x: (self.tile_x * base_frequency.0 + f64::from(PERLIN_N) + width as f64)
+                    as usize,
                 height,
-                wrap_y: (tile_y * base_frequency.1) as usize + PERLIN_N as usize + height,
+                wrap_y: (self.tile_y * base_frequency.1 + f64::from(PERLIN_N) + height as f64)
+                    as usize,
             });
         }
 
@@ -394,14 +401,17 @@ impl Turbulence {
             (x, y)
         };
 
+        // The tile to stitch is the primitive subregion, but the noise is computed in user
+        // space, so its position and size must be in user space as well.
+        let tile = affine.transform_rect(&Rect::from(bounds));
+
         let noise_generator = NoiseGenerator::new(
             seed,
             base_frequency,
             self.num_octaves,
             self.type_,
             self.stitch_tiles,
-            f64::from(bounds.width()),
-            f64::from(bounds.height()),
+            tile,
         );
 
         // The generated color values are in the color space determined by
@@ -421,12 +431,7 @@ impl Turbulence {
                     let point = [point.0, point.1];
 
                     let generate = |color_channel| {
-                        let v = noise_generator.turbulence(
-                            color_channel,
-                            point,
-                            f64::from(x - bounds.x0),
-                            f64::from(y - bounds.y0),
-                        );
+                        let v = noise_generator.turbulence(color_channel, point);
 
                         let v = match self.type_ {
                             NoiseType::FractalNoise => (v * 255.0 + 255.0) / 2.0,
@@ -510,4 +515,30 @@ mod tests {
 
         assert_eq!(r, 1043618065);
     }
+
+    #[test]
+    fn stitched_turbulence_wraps_around_the_tile() {
+        let generator = NoiseGenerator::new(
+            0,
+            (0.05, 0.05),
+            3,
+            NoiseType::FractalNoise,
+            StitchTiles::Stitch,
+            Rect::new(10.0, 20.0, 110.0, 120.0),
+        );
+
+        for i in 0..20 {
+            let t = 20.0 + f64::from(i) * 5.0;
+
+            for channel in 0..4 {
+                let left = generator.turbulence(channel, [10.0, t]);
+                let right = generator.turbulence(channel, [110.0, t]);
+                assert!((left - right).abs() < 1e-9);
+
+                let top = generator.turbulence(channel, [t - 10.0, 20.0]);
+                let bottom = generator.turbulence(channel, [t - 10.0, 120.0]);
+                assert!((top - bottom).abs() < 1e-9);
+            }
+        }
+    }
 }
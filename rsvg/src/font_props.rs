@@ -9,7 +9,7 @@ use cssparser::{Parser, Token};
 use crate::error::*;
 use crate::length::*;
 use crate::parse_identifiers;
-use crate::parsers::{finite_f32, Parse};
+use crate::parsers::{finite_f32, Parse, ToCssString};
 use crate::properties::ComputedValues;
 use crate::property_defs::{FontStretch, FontStyle, FontVariant};
 
@@ -275,6 +275,24 @@ impl Parse for FontSize {
     }
 }
 
+impl ToCssString for FontSize {
+    #[rustfmt::skip]
+    fn to_css_string(&self) -> String {
+        match self {
+            FontSize::Smaller  => "smaller".to_string(),
+            FontSize::Larger   => "larger".to_string(),
+            FontSize::XXSmall  => "xx-small".to_string(),
+            FontSize::XSmall   => "x-small".to_string(),
+            FontSize::Small    => "small".to_string(),
+            FontSize::Medium   => "medium".to_string(),
+            FontSize::Large    => "large".to_string(),
+            FontSize::XLarge   => "x-large".to_string(),
+            FontSize::XXLarge  => "xx-large".to_string(),
+            FontSize::Value(l) => l.to_css_string(),
+        }
+    }
+}
+
 /// `font-weight` property.
 ///
 /// CSS Fonts 3: <https://www.w3.org/TR/css-fonts-3/#propdef-font-weight>
@@ -315,6 +333,18 @@ impl Parse for FontWeight {
     }
 }
 
+impl ToCssString for FontWeight {
+    fn to_css_string(&self) -> String {
+        match self {
+            FontWeight::Normal => "normal".to_string(),
+            FontWeight::Bold => "bold".to_string(),
+            FontWeight::Bolder => "bolder".to_string(),
+            FontWeight::Lighter => "lighter".to_string(),
+            FontWeight::Weight(w) => w.to_string(),
+        }
+    }
+}
+
 impl FontWeight {
     #[rustfmt::skip]
     pub fn compute(&self, v: &Self) -> Self {
@@ -414,6 +444,71 @@ impl Parse for LetterSpacing {
     }
 }
 
+impl ToCssString for LetterSpacing {
+    fn to_css_string(&self) -> String {
+        match self {
+            LetterSpacing::Normal => "normal".to_string(),
+            LetterSpacing::Value(l) => l.to_css_string(),
+        }
+    }
+}
+
+/// `word-spacing` property.
+///
+/// SVG1.1: <https://www.w3.org/TR/SVG11/text.html#WordSpacingProperty>
+///
+/// CSS Text 3: <https://www.w3.org/TR/css-text-3/#word-spacing-property>
+#[derive(Debug, Clone, PartialEq)]
+pub enum WordSpacing {
+    Normal,
+    Value(Length<Horizontal>),
+}
+
+impl WordSpacing {
+    pub fn value(&self) -> Length<Horizontal> {
+        match self {
+            WordSpacing::Value(s) => *s,
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn compute(&self) -> Self {
+        let spacing = match self {
+            WordSpacing::Normal => Length::<Horizontal>::new(0.0, LengthUnit::Px),
+            WordSpacing::Value(s) => *s,
+        };
+
+        WordSpacing::Value(spacing)
+    }
+
+    pub fn to_user(&self, params: &NormalizeParams) -> f64 {
+        self.value().to_user(params)
+    }
+}
+
+impl Parse for WordSpacing {
+    fn parse<'i>(parser: &mut Parser<'i, '_>) -> Result<WordSpacing, ParseError<'i>> {
+        parser
+            .try_parse(|p| Length::<Horizontal>::parse(p))
+            .map(WordSpacing::Value)
+            .or_else(|_| {
+                Ok(parse_identifiers!(
+                    parser,
+                    "normal" => WordSpacing::Normal,
+                )?)
+            })
+    }
+}
+
+impl ToCssString for WordSpacing {
+    fn to_css_string(&self) -> String {
+        match self {
+            WordSpacing::Normal => "normal".to_string(),
+            WordSpacing::Value(l) => l.to_css_string(),
+        }
+    }
+}
+
 /// `line-height` property.
 ///
 /// CSS2: <https://www.w3.org/TR/CSS2/visudet.html#propdef-line-height>
@@ -486,6 +581,17 @@ impl Parse for LineHeight {
     }
 }
 
+impl ToCssString for LineHeight {
+    fn to_css_string(&self) -> String {
+        match self {
+            LineHeight::Normal => "normal".to_string(),
+            LineHeight::Number(n) => n.to_css_string(),
+            LineHeight::Length(l) => l.to_css_string(),
+            LineHeight::Percentage(p) => format!("{}%", (p * 100.0).to_css_string()),
+        }
+    }
+}
+
 /// `font-family` property.
 ///
 /// SVG1.1: <https://www.w3.org/TR/SVG11/text.html#FontFamilyProperty>
@@ -525,6 +631,12 @@ impl Parse for FontFamily {
     }
 }
 
+impl ToCssString for FontFamily {
+    fn to_css_string(&self) -> String {
+        self.0.clone()
+    }
+}
+
 impl FontFamily {
     pub fn as_str(&self) -> &str {
         &self.0
@@ -591,6 +703,17 @@ impl Parse for GlyphOrientationVertical {
     }
 }
 
+impl ToCssString for GlyphOrientationVertical {
+    fn to_css_string(&self) -> String {
+        match self {
+            GlyphOrientationVertical::Auto => "auto",
+            GlyphOrientationVertical::Angle0 => "0deg",
+            GlyphOrientationVertical::Angle90 => "90deg",
+        }
+        .to_string()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -796,6 +919,35 @@ mod tests {
         assert!(LetterSpacing::parse_str("furlong").is_err());
     }
 
+    #[test]
+    fn parses_word_spacing() {
+        assert_eq!(
+            <WordSpacing as Parse>::parse_str("normal").unwrap(),
+            WordSpacing::Normal
+        );
+        assert_eq!(
+            <WordSpacing as Parse>::parse_str("-2px").unwrap(),
+            WordSpacing::Value(Length::<Horizontal>::new(-2.0, LengthUnit::Px,))
+        );
+        assert!(WordSpacing::parse_str("furlong").is_err());
+    }
+
+    #[test]
+    fn computes_word_spacing() {
+        assert_eq!(
+            <WordSpacing as Parse>::parse_str("normal")
+                .map(|s| s.compute())
+                .unwrap(),
+            WordSpacing::Value(Length::<Horizontal>::new(0.0, LengthUnit::Px,))
+        );
+        assert_eq!(
+            <WordSpacing as Parse>::parse_str("1em")
+                .map(|s| s.compute())
+                .unwrap(),
+            WordSpacing::Value(Length::<Horizontal>::new(1.0, LengthUnit::Em,))
+        );
+    }
+
     #[test]
     fn parses_font_family() {
         assert_eq!(
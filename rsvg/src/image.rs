@@ -83,6 +83,11 @@ impl ElementTrait for Image {
 }
 
 impl Image {
+    /// Returns the `href` attribute, as written in the document.
+    pub fn href(&self) -> Option<&str> {
+        self.href.as_deref()
+    }
+
     fn layout_from_url(
         &self,
         url: &str,
@@ -1,19 +1,47 @@
 //! Utilities to acquire streams and data from from URLs.
 
 use data_url::{mime::Mime, DataUrl};
+#[cfg(feature = "gio")]
 use gio::{
-    prelude::{FileExt, FileExtManual},
-    Cancellable, File as GFile, InputStream, MemoryInputStream,
+    prelude::{
+        BufferedInputStreamExt, FileExt, FileExtManual, MemoryOutputStreamExt, OutputStreamExt,
+    },
+    BufferedInputStream, ConverterInputStream, File as GFile, InputStream, MemoryInputStream,
+    MemoryOutputStream, OutputStreamSpliceFlags, ZlibCompressorFormat, ZlibDecompressor,
 };
-use glib::{self, object::Cast, Bytes as GBytes};
+#[cfg(feature = "gio")]
+use glib::{object::Cast, Bytes as GBytes};
 use std::fmt;
 use std::str::FromStr;
 
 use crate::url_resolver::AllowedUrl;
 
+#[cfg(feature = "gio")]
+pub use gio::Cancellable;
+
+/// Stand-in for `gio::Cancellable` when librsvg is built without the "gio" feature.
+///
+/// This type has no values, so an `Option<Cancellable>` is always `None` and nothing
+/// can be cancelled.
+#[cfg(not(feature = "gio"))]
+#[derive(Clone)]
+pub enum Cancellable {}
+
+#[cfg(not(feature = "gio"))]
+impl Cancellable {
+    pub fn is_cancelled(&self) -> bool {
+        match *self {}
+    }
+}
+
+#[derive(Debug)]
 pub enum IoError {
     BadDataUrl,
     Glib(glib::Error),
+
+    /// Reading the data needs gio, but librsvg was built without the "gio" feature.
+    #[cfg(not(feature = "gio"))]
+    NeedsGio(&'static str),
 }
 
 impl From<glib::Error> for IoError {
@@ -27,6 +55,9 @@ impl fmt::Display for IoError {
         match *self {
             IoError::BadDataUrl => write!(f, "invalid data: URL"),
             IoError::Glib(ref e) => e.fmt(f),
+
+            #[cfg(not(feature = "gio"))]
+            IoError::NeedsGio(what) => write!(f, "reading {what} needs the \"gio\" feature"),
         }
     }
 }
@@ -36,6 +67,20 @@ pub struct BinaryData {
     pub mime_type: Mime,
 }
 
+impl BinaryData {
+    /// Returns the value of the `charset` parameter of the MIME type, if any.
+    ///
+    /// For `data:` URLs, this comes from the media type in the URL itself, like in
+    /// `data:text/plain;charset=utf-16,...`.
+    pub fn charset(&self) -> Option<&str> {
+        self.mime_type
+            .parameters
+            .iter()
+            .find(|(name, _)| name == "charset")
+            .map(|(_, value)| value.as_str())
+    }
+}
+
 fn decode_data_uri(uri: &str) -> Result<BinaryData, IoError> {
     let data_url = DataUrl::process(uri).map_err(|_| IoError::BadDataUrl)?;
 
@@ -66,14 +111,116 @@ fn decode_data_uri(uri: &str) -> Result<BinaryData, IoError> {
     })
 }
 
+// Header of a gzip data stream
+const GZ_MAGIC_0: u8 = 0x1f;
+const GZ_MAGIC_1: u8 = 0x8b;
+
+pub fn is_gzipped(data: &[u8]) -> bool {
+    data.len() >= 2 && data[0..2] == [GZ_MAGIC_0, GZ_MAGIC_1]
+}
+
+/// Returns a stream that decompresses `buffered` if it contains gzipped data, like svgz files.
+///
+/// The `buffered` stream must already be filled with at least two bytes to sniff the
+/// gzip header.
+#[cfg(feature = "gio")]
+pub fn decompress_if_gzipped(buffered: BufferedInputStream) -> InputStream {
+    if is_gzipped(&buffered.peek_buffer()) {
+        let decomp = ZlibDecompressor::new(ZlibCompressorFormat::Gzip);
+        let converter = ConverterInputStream::new(&buffered, &decomp);
+        converter.upcast::<InputStream>()
+    } else {
+        buffered.upcast::<InputStream>()
+    }
+}
+
+#[cfg(feature = "gio")]
+fn decompress_stream_if_gzipped(
+    stream: &InputStream,
+    cancellable: Option<&Cancellable>,
+) -> Result<InputStream, IoError> {
+    let buffered = BufferedInputStream::new(stream);
+    buffered.fill(2, cancellable)?;
+
+    Ok(decompress_if_gzipped(buffered))
+}
+
+#[cfg(feature = "gio")]
+fn decompress_gzipped_data(
+    data: &[u8],
+    cancellable: Option<&Cancellable>,
+) -> Result<Vec<u8>, IoError> {
+    let stream = MemoryInputStream::from_bytes(&GBytes::from(data));
+    let decomp = ZlibDecompressor::new(ZlibCompressorFormat::Gzip);
+    let converter = ConverterInputStream::new(&stream, &decomp);
+
+    let output = MemoryOutputStream::new_resizable();
+    output.splice(
+        &converter,
+        OutputStreamSpliceFlags::CLOSE_SOURCE | OutputStreamSpliceFlags::CLOSE_TARGET,
+        cancellable,
+    )?;
+
+    Ok(output.steal_as_bytes().to_vec())
+}
+
+#[cfg(not(feature = "gio"))]
+fn decompress_gzipped_data(
+    _data: &[u8],
+    _cancellable: Option<&Cancellable>,
+) -> Result<Vec<u8>, IoError> {
+    Err(IoError::NeedsGio("compressed data"))
+}
+
+/// Decompresses gzipped data, and fixes up its MIME type to the uncompressed one.
+fn decompress_binary_data(
+    binary: BinaryData,
+    cancellable: Option<&Cancellable>,
+) -> Result<BinaryData, IoError> {
+    let data = decompress_gzipped_data(&binary.data, cancellable)?;
+
+    let Mime {
+        type_,
+        subtype,
+        parameters,
+    } = binary.mime_type;
+
+    let mime_type = if let Some(uncompressed) = subtype.strip_suffix("-compressed") {
+        // For example, gio guesses image/svg+xml-compressed for svgz files
+        Mime {
+            subtype: uncompressed.to_string(),
+            type_,
+            parameters,
+        }
+    } else if type_ == "application"
+        && matches!(subtype.as_str(), "gzip" | "x-gzip" | "octet-stream")
+    {
+        guess_mime_type(None, &data)
+    } else {
+        Mime {
+            type_,
+            subtype,
+            parameters,
+        }
+    };
+
+    Ok(BinaryData { data, mime_type })
+}
+
 /// Creates a stream for reading.  The url can be a data: URL or a plain URI.
+///
+/// If the contents are gzipped, the stream decompresses them.
+#[cfg(feature = "gio")]
 pub fn acquire_stream(
     aurl: &AllowedUrl,
     cancellable: Option<&Cancellable>,
 ) -> Result<InputStream, IoError> {
     let uri = aurl.as_str();
 
-    if uri.starts_with("data:") {
+    if let Some(bytes) = aurl.supplied_data() {
+        let stream = MemoryInputStream::from_bytes(bytes);
+        decompress_stream_if_gzipped(&stream.upcast(), cancellable)
+    } else if uri.starts_with("data:") {
         let BinaryData { data, .. } = decode_data_uri(uri)?;
 
         //        {
@@ -85,41 +232,127 @@ pub fn acquire_stream(
         //        }
 
         let stream = MemoryInputStream::from_bytes(&GBytes::from_owned(data));
-        Ok(stream.upcast::<InputStream>())
+        decompress_stream_if_gzipped(&stream.upcast(), cancellable)
     } else {
         let file = GFile::for_uri(uri);
         let stream = file.read(cancellable)?;
 
-        Ok(stream.upcast::<InputStream>())
+        decompress_stream_if_gzipped(&stream.upcast(), cancellable)
     }
 }
 
 /// Reads the entire contents pointed by an URL.  The url can be a data: URL or a plain URI.
+///
+/// If the contents are gzipped, they get decompressed.
 pub fn acquire_data(
     aurl: &AllowedUrl,
     cancellable: Option<&Cancellable>,
 ) -> Result<BinaryData, IoError> {
     let uri = aurl.as_str();
 
-    if uri.starts_with("data:") {
-        Ok(decode_data_uri(uri)?)
+    let binary = if let Some(bytes) = aurl.supplied_data() {
+        BinaryData {
+            data: bytes.to_vec(),
+            mime_type: guess_mime_type(Some(uri), bytes),
+        }
+    } else if uri.starts_with("data:") {
+        decode_data_uri(uri)?
     } else {
-        let file = GFile::for_uri(uri);
-        let (contents, _etag) = file.load_contents(cancellable)?;
-
-        let (content_type, _uncertain) = gio::content_type_guess(Some(uri), &contents);
-
-        let mime_type = if let Some(mime_type_str) = gio::content_type_get_mime_type(&content_type)
-        {
-            Mime::from_str(&mime_type_str)
-                .expect("gio::content_type_get_mime_type returned an invalid MIME-type!?")
-        } else {
-            Mime::from_str("application/octet-stream").unwrap()
-        };
-
-        Ok(BinaryData {
-            data: contents.to_vec(),
-            mime_type,
-        })
+        let contents = load_uri(uri, cancellable)?;
+
+        BinaryData {
+            mime_type: guess_mime_type(Some(uri), &contents),
+            data: contents,
+        }
+    };
+
+    if is_gzipped(&binary.data) {
+        decompress_binary_data(binary, cancellable)
+    } else {
+        Ok(binary)
+    }
+}
+
+/// Reads the entire contents of a plain URI, like a `file:` URL.
+#[cfg(feature = "gio")]
+fn load_uri(uri: &str, cancellable: Option<&Cancellable>) -> Result<Vec<u8>, IoError> {
+    let file = GFile::for_uri(uri);
+    let (contents, _etag) = file.load_contents(cancellable)?;
+
+    Ok(contents.to_vec())
+}
+
+#[cfg(not(feature = "gio"))]
+fn load_uri(_uri: &str, _cancellable: Option<&Cancellable>) -> Result<Vec<u8>, IoError> {
+    Err(IoError::NeedsGio("URLs other than data: URLs"))
+}
+
+/// Guesses the MIME type of a file's contents, using its URI as a hint.
+#[cfg(feature = "gio")]
+fn guess_mime_type(uri: Option<&str>, contents: &[u8]) -> Mime {
+    let (content_type, _uncertain) = gio::content_type_guess(uri, contents);
+
+    if let Some(mime_type_str) = gio::content_type_get_mime_type(&content_type) {
+        Mime::from_str(&mime_type_str)
+            .expect("gio::content_type_get_mime_type returned an invalid MIME-type!?")
+    } else {
+        Mime::from_str("application/octet-stream").unwrap()
+    }
+}
+
+/// Guesses the MIME type of a file's contents from its first bytes.
+///
+/// Without gio there is no content type database, so this only recognizes the raster
+/// formats that librsvg can decode, and assumes that anything else that looks like XML
+/// is an SVG document.
+#[cfg(not(feature = "gio"))]
+fn guess_mime_type(_uri: Option<&str>, contents: &[u8]) -> Mime {
+    let looks_like_xml = contents
+        .strip_prefix(b"\xef\xbb\xbf")
+        .unwrap_or(contents)
+        .iter()
+        .find(|b| !b.is_ascii_whitespace())
+        == Some(&b'<');
+
+    let mime_type_str = match image::guess_format(contents) {
+        Ok(format) => format.to_mime_type(),
+        Err(_) if looks_like_xml => "image/svg+xml",
+        Err(_) => "application/octet-stream",
+    };
+
+    Mime::from_str(mime_type_str).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_base64_png_data_url() {
+        let binary = decode_data_uri(
+            "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAoAAAAKCAIAAAACUFjqAAAABmJLR0QA/wD/AP+gvaeTAAAAE0lEQVQYlWNk+M+ABzDhkxy50gBALQETCv6BWAAAAABJRU5ErkJggg==",
+        )
+        .unwrap();
+
+        assert_eq!(binary.mime_type.type_, "image");
+        assert_eq!(binary.mime_type.subtype, "png");
+        assert_eq!(binary.charset(), None);
+        assert!(binary.data.starts_with(b"\x89PNG\r\n\x1a\n"));
+    }
+
+    #[test]
+    fn decodes_charset_of_text_data_urls() {
+        let binary = decode_data_uri("data:text/plain;charset=ISO-8859-1,h%E9llo").unwrap();
+        assert_eq!(binary.charset(), Some("ISO-8859-1"));
+        assert_eq!(binary.data, b"h\xe9llo");
+
+        let binary =
+            decode_data_uri("data:text/plain;charset=utf-16le;base64,aADpAGwAbABvAA==").unwrap();
+        assert_eq!(binary.charset(), Some("utf-16le"));
+        assert_eq!(binary.data, b"h\0\xe9\0l\0l\0o\0");
+
+        // The default media type for data: URLs is text/plain;charset=US-ASCII
+        let binary = decode_data_uri("data:,hello").unwrap();
+        assert_eq!(binary.charset(), Some("US-ASCII"));
     }
 }
@@ -4,7 +4,7 @@ use cssparser::Parser;
 
 use crate::document::NodeId;
 use crate::error::*;
-use crate::parsers::Parse;
+use crate::parsers::{Parse, ToCssString};
 
 /// Used where style properties take a funciri or "none"
 ///
@@ -46,6 +46,15 @@ impl Parse for Iri {
     }
 }
 
+impl ToCssString for Iri {
+    fn to_css_string(&self) -> String {
+        match self {
+            Iri::None => "none".to_string(),
+            Iri::Resource(id) => format!("url({id})"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
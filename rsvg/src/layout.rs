@@ -8,10 +8,10 @@ use cssparser::Color;
 use float_cmp::approx_eq;
 
 use crate::aspect_ratio::AspectRatio;
+#[cfg(feature = "text")]
 use crate::bbox::BoundingBox;
 use crate::cairo_path::CairoPath;
 use crate::coord_units::CoordUnits;
-use crate::dasharray::Dasharray;
 use crate::document::AcquiredNodes;
 use crate::element::{Element, ElementData};
 use crate::filter::FilterValueList;
@@ -20,10 +20,14 @@ use crate::node::*;
 use crate::paint_server::{PaintSource, UserSpacePaintSource};
 use crate::path_builder::Path as SvgPath;
 use crate::properties::{
-    self, ClipRule, ComputedValues, Direction, FillRule, FontFamily, FontStretch, FontStyle,
-    FontVariant, FontWeight, ImageRendering, Isolation, MixBlendMode, Opacity, Overflow,
-    PaintOrder, ShapeRendering, StrokeDasharray, StrokeLinecap, StrokeLinejoin, StrokeMiterlimit,
-    TextDecoration, TextRendering, UnicodeBidi, VectorEffect, XmlLang,
+    self, ClipRule, ComputedValues, FillRule, ImageRendering, Isolation, MaskType, MixBlendMode,
+    Opacity, Overflow, PaintOrder, ShapeRendering, StrokeDasharray, StrokeLinecap, StrokeLinejoin,
+    StrokeMiterlimit, VectorEffect,
+};
+#[cfg(feature = "text")]
+use crate::properties::{
+    Direction, FontFamily, FontStretch, FontStyle, FontVariant, FontWeight, TextDecoration,
+    TextRendering, UnicodeBidi, XmlLang,
 };
 use crate::rect::Rect;
 use crate::rsvg_log;
@@ -32,6 +36,7 @@ use crate::surface_utils::shared_surface::SharedImageSurface;
 use crate::transform::Transform;
 use crate::unit_interval::UnitInterval;
 use crate::viewbox::ViewBox;
+use crate::xml::AttributeValue;
 use crate::{borrow_element_as, is_element_of_type};
 
 /// SVG Stacking context, an inner node in the layout tree.
@@ -49,6 +54,10 @@ use crate::{borrow_element_as, is_element_of_type};
 /// render an element as an isolated group.
 pub struct StackingContext {
     pub element_name: String,
+
+    /// The element's `id`, kept interned so that it is only copied to a `String` when
+    /// there is a [`RenderObserver`](crate::RenderObserver) to report it to.
+    pub element_id: Option<AttributeValue>,
     pub transform: Transform,
     pub opacity: Opacity,
     pub filter: Option<Filter>,
@@ -56,6 +65,10 @@ pub struct StackingContext {
     pub clip_in_user_space: Option<Node>,
     pub clip_in_object_space: Option<Node>,
     pub mask: Option<Node>,
+
+    /// Masking mode from the `mask` property; it overrides the mask's `mask-type`.
+    pub mask_mode: Option<MaskType>,
+
     pub mix_blend_mode: MixBlendMode,
     pub isolation: Isolation,
 
@@ -70,6 +83,7 @@ pub struct Layer {
 }
 pub enum LayerKind {
     Shape(Box<Shape>),
+    #[cfg(feature = "text")]
     Text(Box<Text>),
     Image(Box<Image>),
     Group(Box<Group>),
@@ -173,14 +187,32 @@ pub struct Image {
     pub image_rendering: ImageRendering,
 }
 
+/// The glyphs to draw for a text span.
+#[cfg(feature = "text")]
+#[derive(Clone)]
+pub enum TextGlyphs {
+    /// A whole Pango layout, with its origin at the layout's top-left corner.
+    Layout(pango::Layout),
+
+    /// A cluster of glyphs that were already shaped as part of a layout, with its origin
+    /// on the baseline.  A `<textPath>` places each cluster on its own.
+    Cluster {
+        font: pango::Font,
+        glyphs: pango::GlyphString,
+    },
+}
+
 /// A single text span in user-space coordinates.
+#[cfg(feature = "text")]
 pub struct TextSpan {
-    pub layout: pango::Layout,
+    pub glyphs: TextGlyphs,
     pub gravity: pango::Gravity,
     pub bbox: Option<BoundingBox>,
     pub is_visible: bool,
     pub x: f64,
     pub y: f64,
+    /// Rotation around `(x, y)` in radians, for glyphs placed along a `<textPath>`.
+    pub rotation: f64,
     pub paint_order: PaintOrder,
     pub stroke: Stroke,
     pub stroke_paint: UserSpacePaintSource,
@@ -190,11 +222,13 @@ pub struct TextSpan {
 }
 
 /// Fully laid-out text in user-space coordinates.
+#[cfg(feature = "text")]
 pub struct Text {
     pub spans: Vec<TextSpan>,
 }
 
 /// Font-related properties extracted from `ComputedValues`.
+#[cfg(feature = "text")]
 pub struct FontProperties {
     pub xml_lang: XmlLang,
     pub unicode_bidi: UnicodeBidi,
@@ -206,6 +240,7 @@ pub struct FontProperties {
     pub font_stretch: FontStretch,
     pub font_size: f64,
     pub letter_spacing: f64,
+    pub word_spacing: f64,
     pub text_decoration: TextDecoration,
 }
 
@@ -281,6 +316,7 @@ impl StackingContext {
         values: &ComputedValues,
     ) -> StackingContext {
         let element_name = format!("{element}");
+        let element_id = element.get_id_value().cloned();
 
         let opacity;
         let filter;
@@ -320,7 +356,8 @@ impl StackingContext {
             })
             .unwrap_or((None, None));
 
-        let mask = values.mask().0.get().and_then(|mask_id| {
+        let mask_mode = values.mask().mode;
+        let mask = values.mask().iri.get().and_then(|mask_id| {
             if let Ok(acquired) = acquired_nodes.acquire(mask_id) {
                 let node = acquired.get();
                 match *node.borrow_element_data() {
@@ -354,6 +391,7 @@ impl StackingContext {
 
         StackingContext {
             element_name,
+            element_id,
             transform,
             opacity,
             filter,
@@ -361,6 +399,7 @@ impl StackingContext {
             clip_in_user_space,
             clip_in_object_space,
             mask,
+            mask_mode,
             mix_blend_mode,
             isolation,
             link_target: None,
@@ -407,13 +446,8 @@ impl Stroke {
         let dash_offset = values.stroke_dashoffset().0.to_user(params);
         let non_scaling = values.vector_effect() == VectorEffect::NonScalingStroke;
 
-        let dashes = match values.stroke_dasharray() {
-            StrokeDasharray(Dasharray::None) => Box::new([]),
-            StrokeDasharray(Dasharray::Array(dashes)) => dashes
-                .iter()
-                .map(|l| l.to_user(params))
-                .collect::<Box<[f64]>>(),
-        };
+        let StrokeDasharray(dasharray) = values.stroke_dasharray();
+        let dashes = dasharray.to_user(params);
 
         Stroke {
             width,
@@ -427,6 +461,7 @@ impl Stroke {
     }
 }
 
+#[cfg(feature = "text")]
 impl FontProperties {
     /// Collects font properties from a `ComputedValues`.
     ///
@@ -444,6 +479,7 @@ impl FontProperties {
             font_stretch: values.font_stretch(),
             font_size: values.font_size().to_user(params),
             letter_spacing: values.letter_spacing().to_user(params),
+            word_spacing: values.word_spacing().to_user(params),
             text_decoration: values.text_decoration(),
         }
     }
@@ -50,7 +50,7 @@ use std::marker::PhantomData;
 use crate::dpi::Dpi;
 use crate::drawing_ctx::Viewport;
 use crate::error::*;
-use crate::parsers::{finite_f32, Parse};
+use crate::parsers::{finite_f32, Parse, ToCssString};
 use crate::properties::{ComputedValues, FontSize, TextOrientation, WritingMode};
 use crate::rect::Rect;
 use crate::viewbox::ViewBox;
@@ -60,6 +60,8 @@ use crate::viewbox::ViewBox;
 #[non_exhaustive]
 #[repr(C)]
 #[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum LengthUnit {
     /// `1.0` means 100%
     Percent,
@@ -102,10 +104,21 @@ pub enum LengthUnit {
 /// where the [`unit`][RsvgLength::unit] field is not [`LengthUnit::Px`]) into something
 /// meaningful to the application.  For example, if your application knows the
 /// dots-per-inch (DPI) it is using, it can convert lengths with [`unit`] in
-/// [`LengthUnit::In`] or other physical units.
+/// [`LengthUnit::In`] or other physical units.  Use [`RsvgLength::to_user`] and the other
+/// conversion methods for this, with a [`NormalizeParams`] that describes the DPI, and
+/// the viewport and font size if you need to convert relative lengths.
+///
+/// # Panics
+///
+/// The conversions to physical units ([`to_points`][RsvgLength::to_points],
+/// [`to_inches`][RsvgLength::to_inches], [`to_cm`][RsvgLength::to_cm],
+/// [`to_mm`][RsvgLength::to_mm], and [`to_picas`][RsvgLength::to_picas]) will panic if
+/// the length is in `%`, `em`, `ex`, or `ch` units, since those are not absolute lengths.
+/// Use [`RsvgLength::to_user`] for those.
 // Keep this in sync with rsvg.h:RsvgLength
 #[repr(C)]
 #[derive(Debug, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RsvgLength {
     /// Numeric part of the length
     pub length: f64,
@@ -119,6 +132,59 @@ impl RsvgLength {
     pub fn new(l: f64, unit: LengthUnit) -> RsvgLength {
         RsvgLength { length: l, unit }
     }
+
+    /// Converts the length to pixels, or user units.
+    ///
+    /// Physical units like `cm` are converted with the DPI in the `params`.  Percentages
+    /// are resolved against the viewport's normalized diagonal, which is
+    /// `sqrt(width² + height²) / sqrt(2)`, as the SVG specification does for lengths that
+    /// are neither horizontal nor vertical.  Lengths in `em`, `ex`, and `ch` units are
+    /// resolved against the font size.
+    ///
+    /// ```
+    /// use rsvg::{Dpi, Length, LengthUnit, NormalizeParams};
+    ///
+    /// let params = NormalizeParams::from_dpi(Dpi::new(96.0, 96.0)).with_font_size(10.0);
+    ///
+    /// let inch = Length::new(1.0, LengthUnit::In).to_user(&params);
+    /// assert!((inch - 96.0).abs() < 1e-9);
+    ///
+    /// assert_eq!(Length::new(2.0, LengthUnit::Em).to_user(&params), 20.0);
+    /// ```
+    pub fn to_user(&self, params: &NormalizeParams) -> f64 {
+        self.to_css_length().to_user(params)
+    }
+
+    /// Converts the length to points, which are 1/72 of an inch.
+    ///
+    /// Pixels are converted with the DPI in the `params`.
+    pub fn to_points(&self, params: &NormalizeParams) -> f64 {
+        self.to_css_length().to_points(params)
+    }
+
+    /// Converts the length to inches.
+    pub fn to_inches(&self, params: &NormalizeParams) -> f64 {
+        self.to_css_length().to_inches(params)
+    }
+
+    /// Converts the length to centimeters.
+    pub fn to_cm(&self, params: &NormalizeParams) -> f64 {
+        self.to_css_length().to_cm(params)
+    }
+
+    /// Converts the length to millimeters.
+    pub fn to_mm(&self, params: &NormalizeParams) -> f64 {
+        self.to_css_length().to_mm(params)
+    }
+
+    /// Converts the length to picas, which are 1/6 of an inch.
+    pub fn to_picas(&self, params: &NormalizeParams) -> f64 {
+        self.to_css_length().to_picas(params)
+    }
+
+    fn to_css_length(self) -> CssLength<Both, Signed> {
+        CssLength::new(self.length, self.unit)
+    }
 }
 
 /// Used for the `N` type parameter of `CssLength<N: Normalize, V: Validate>`.
@@ -332,7 +398,12 @@ impl NormalizeValues {
     }
 }
 
-/// Parameters to normalize [`Length`] values to user-space distances.
+/// Parameters to normalize lengths to user-space distances.
+///
+/// Lengths in physical units like `cm` need the DPI for this; percentages need the size
+/// of the viewport, and lengths in `em` or `ex` units need the font size.  Create a
+/// `NormalizeParams` with [`NormalizeParams::from_dpi`], and add the other values if
+/// needed.
 pub struct NormalizeParams {
     vbox: ViewBox,
     font_size: f64,
@@ -357,7 +428,11 @@ impl NormalizeParams {
         }
     }
 
-    /// Just used by rsvg-convert, where there is no font size nor viewport.
+    /// Creates parameters for lengths in absolute units, with the given resolution.
+    ///
+    /// The viewport is empty, so percentages resolve to zero, and the font size is 1
+    /// pixel.  Use [`NormalizeParams::with_viewport_size`] and
+    /// [`NormalizeParams::with_font_size`] to change them.
     pub fn from_dpi(dpi: Dpi) -> NormalizeParams {
         NormalizeParams {
             vbox: ViewBox::from(Rect::default()),
@@ -366,6 +441,29 @@ impl NormalizeParams {
             is_vertical_text: false,
         }
     }
+
+    /// Sets the size of the viewport, in pixels, for lengths with percentages.
+    pub fn with_viewport_size(self, width: f64, height: f64) -> NormalizeParams {
+        NormalizeParams {
+            vbox: ViewBox::from(Rect::from_size(width, height)),
+            ..self
+        }
+    }
+
+    /// Sets the font size, in pixels, for lengths in `em`, `ex`, and `ch` units.
+    pub fn with_font_size(self, font_size: f64) -> NormalizeParams {
+        NormalizeParams { font_size, ..self }
+    }
+}
+
+impl<N: Normalize, V: Validate> ToCssString for CssLength<N, V> {
+    fn to_css_string(&self) -> String {
+        match self.unit {
+            // Percentages are stored as a fraction, e.g. 50% is 0.5
+            LengthUnit::Percent => format!("{}%", (self.length * 100.0).to_css_string()),
+            unit => format!("{}{unit}", self.length.to_css_string()),
+        }
+    }
 }
 
 impl<N: Normalize, V: Validate> CssLength<N, V> {
@@ -557,6 +655,15 @@ impl<N: Normalize> Parse for LengthOrAuto<N> {
     }
 }
 
+impl<N: Normalize> ToCssString for LengthOrAuto<N> {
+    fn to_css_string(&self) -> String {
+        match self {
+            LengthOrAuto::Auto => "auto".to_string(),
+            LengthOrAuto::Length(l) => l.to_css_string(),
+        }
+    }
+}
+
 impl fmt::Display for LengthUnit {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let unit = match &self {
@@ -604,6 +711,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn serializes_lengths() {
+        for s in ["42px", "-1.5em", "50%", "0.25in"] {
+            assert_eq!(Length::<Both>::parse_str(s).unwrap().to_css_string(), s);
+        }
+
+        assert_eq!(
+            Length::<Both>::parse_str("42").unwrap().to_css_string(),
+            "42px"
+        );
+        assert_eq!(LengthOrAuto::<Both>::Auto.to_css_string(), "auto");
+    }
+
     #[test]
     fn parses_font_em() {
         assert_eq!(
@@ -147,6 +147,7 @@ pub use crate::api::*;
 
 mod accept_language;
 mod angle;
+mod animation;
 mod api;
 mod aspect_ratio;
 mod bbox;
@@ -155,6 +156,7 @@ mod color;
 mod cond;
 mod coord_units;
 mod css;
+mod custom_properties;
 mod dasharray;
 mod document;
 mod dpi;
@@ -186,13 +188,20 @@ mod properties;
 mod property_defs;
 mod property_macros;
 mod rect;
+mod render_observer;
 mod session;
 mod shapes;
 mod space;
 mod structure;
 mod style;
 mod surface_utils;
+#[cfg(feature = "text")]
 mod text;
+
+#[cfg(not(feature = "text"))]
+#[path = "text_stub.rs"]
+mod text;
+
 mod text2;
 mod transform;
 mod unit_interval;
@@ -257,6 +266,7 @@ pub mod rsvg_convert_only {
     pub use crate::parsers::{Parse, ParseValue};
     pub use crate::rect::Rect;
     pub use crate::surface_utils::shared_surface::{SharedImageSurface, SurfaceType};
+    pub use crate::surface_utils::{Pixel, PixelOps};
     pub use crate::viewbox::ViewBox;
 }
 
@@ -1,5 +1,10 @@
 //! Processing limits to mitigate malicious SVGs.
 
+use std::cell::Cell;
+use std::time::Instant;
+
+use crate::error::ImplementationLimit;
+
 /// Maximum number of times that elements can be referenced through URL fragments.
 ///
 /// This is a mitigation for the security-related bugs:
@@ -54,3 +59,160 @@ pub const MAX_XINCLUDE_DEPTH: usize = 20;
 /// This is a mitigation for malicious SVGs which try to have layers that are nested
 /// extremely deep, as this could cause stack exhaustion.
 pub const MAX_LAYER_NESTING_DEPTH: u16 = 50;
+
+/// Limits on the work done while rendering, to mitigate untrusted SVG documents.
+///
+/// Unlike the constants in this module, which are always in effect, these limits are
+/// chosen by the application with [`CairoRenderer::with_render_limits`].  All of them
+/// default to `None`, which means unlimited.  When a limit is exceeded, rendering stops
+/// and returns [`RenderingError::LimitExceeded`].
+///
+/// [`CairoRenderer::with_render_limits`]: crate::CairoRenderer::with_render_limits
+/// [`RenderingError::LimitExceeded`]: crate::RenderingError::LimitExceeded
+#[derive(Debug, Default, Copy, Clone)]
+pub struct RenderLimits {
+    /// Maximum number of pixels that filter primitives may produce, added over all of
+    /// the primitives in the document.
+    pub max_filter_pixels: Option<u64>,
+
+    /// Maximum number of elements that may be drawn.  Elements that get drawn several
+    /// times, like the ones referenced by `<use>` or `<pattern>`, count each time.
+    pub max_elements_drawn: Option<u64>,
+
+    /// Time at which rendering gets stopped.
+    pub deadline: Option<Instant>,
+}
+
+/// Keeps track of the work done during a single rendering operation, to enforce
+/// [`RenderLimits`].
+pub struct RenderBudget {
+    limits: RenderLimits,
+    elements_drawn: Cell<u64>,
+    filter_pixels: Cell<u64>,
+}
+
+impl RenderBudget {
+    pub fn new(limits: RenderLimits) -> RenderBudget {
+        RenderBudget {
+            limits,
+            elements_drawn: Cell::new(0),
+            filter_pixels: Cell::new(0),
+        }
+    }
+
+    /// Counts one more drawn element, and checks the deadline.
+    pub fn count_element(&self) -> Result<(), ImplementationLimit> {
+        let elements_drawn = self.elements_drawn.get() + 1;
+        self.elements_drawn.set(elements_drawn);
+
+        if self
+            .limits
+            .max_elements_drawn
+            .is_some_and(|max| elements_drawn > max)
+        {
+            return Err(ImplementationLimit::TooManyDrawnElements);
+        }
+
+        self.check_deadline()
+    }
+
+    /// Counts the pixels produced by a filter primitive, and checks the deadline.
+    pub fn count_filter_pixels(&self, pixels: u64) -> Result<(), ImplementationLimit> {
+        let filter_pixels = self.filter_pixels.get().saturating_add(pixels);
+        self.filter_pixels.set(filter_pixels);
+
+        if self
+            .limits
+            .max_filter_pixels
+            .is_some_and(|max| filter_pixels > max)
+        {
+            return Err(ImplementationLimit::TooManyFilterPixels);
+        }
+
+        self.check_deadline()
+    }
+
+    pub fn check_deadline(&self) -> Result<(), ImplementationLimit> {
+        if self
+            .limits
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return Err(ImplementationLimit::RenderingDeadlineExceeded);
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for RenderBudget {
+    fn default() -> RenderBudget {
+        RenderBudget::new(RenderLimits::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    #[test]
+    fn default_budget_is_unlimited() {
+        let budget = RenderBudget::default();
+
+        for _ in 0..1000 {
+            assert!(budget.count_element().is_ok());
+            assert!(budget.count_filter_pixels(u64::MAX).is_ok());
+        }
+    }
+
+    #[test]
+    fn counts_elements() {
+        let budget = RenderBudget::new(RenderLimits {
+            max_elements_drawn: Some(2),
+            ..Default::default()
+        });
+
+        assert!(budget.count_element().is_ok());
+        assert!(budget.count_element().is_ok());
+        assert!(matches!(
+            budget.count_element(),
+            Err(ImplementationLimit::TooManyDrawnElements)
+        ));
+    }
+
+    #[test]
+    fn counts_filter_pixels() {
+        let budget = RenderBudget::new(RenderLimits {
+            max_filter_pixels: Some(100),
+            ..Default::default()
+        });
+
+        assert!(budget.count_filter_pixels(60).is_ok());
+        assert!(matches!(
+            budget.count_filter_pixels(60),
+            Err(ImplementationLimit::TooManyFilterPixels)
+        ));
+    }
+
+    #[test]
+    fn checks_deadline() {
+        let now = Instant::now();
+
+        let future = RenderBudget::new(RenderLimits {
+            deadline: Some(now + Duration::from_secs(3600)),
+            ..Default::default()
+        });
+        assert!(future.check_deadline().is_ok());
+
+        let past = RenderBudget::new(RenderLimits {
+            deadline: Some(now),
+            ..Default::default()
+        });
+        assert!(matches!(
+            past.count_element(),
+            Err(ImplementationLimit::RenderingDeadlineExceeded)
+        ));
+    }
+}
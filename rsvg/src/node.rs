@@ -18,7 +18,7 @@ use crate::drawing_ctx::{DrawingCtx, Viewport};
 use crate::element::*;
 use crate::error::*;
 use crate::paint_server::PaintSource;
-use crate::properties::ComputedValues;
+use crate::properties::{self, ComputedValues, SpecifiedValues};
 use crate::rsvg_log;
 use crate::session::Session;
 use crate::text::Chars;
@@ -283,10 +283,26 @@ macro_rules! borrow_element_as {
 /// Helper trait for cascading recursively
 pub trait NodeCascade {
     fn cascade(&mut self, values: &ComputedValues);
+
+    /// Like [`cascade`](Self::cascade), but `override_specified` can supply other specified
+    /// values for an element, to be used instead of the element's own.
+    fn cascade_with(
+        &mut self,
+        values: &ComputedValues,
+        override_specified: &dyn Fn(&Node) -> Option<SpecifiedValues>,
+    );
 }
 
 impl NodeCascade for Node {
     fn cascade(&mut self, values: &ComputedValues) {
+        self.cascade_with(values, &|_| None);
+    }
+
+    fn cascade_with(
+        &mut self,
+        values: &ComputedValues,
+        override_specified: &dyn Fn(&Node) -> Option<SpecifiedValues>,
+    ) {
         // We box this because ComputedValues is a big structure.  Since this function is
         // recursive, we want to minimize stack consumption during recursion.
         //
@@ -297,15 +313,21 @@ impl NodeCascade for Node {
 
         let mut values = Box::new(values.clone());
 
+        let overridden = override_specified(self);
+
         {
             let mut elt = self.borrow_element_mut();
 
-            elt.get_specified_values().to_computed_values(&mut values);
+            match overridden {
+                Some(ref specified) => specified.to_computed_values(&mut values),
+                None => elt.get_specified_values().to_computed_values(&mut values),
+            }
+
             elt.set_computed_values(&values);
         }
 
         for mut child in self.children().filter(|c| c.is_element()) {
-            child.cascade(&values);
+            child.cascade_with(&values, override_specified);
         }
     }
 }
@@ -345,6 +367,18 @@ impl NodeDraw for Node {
         match *self.borrow() {
             NodeData::Element(ref e) => {
                 rsvg_log!(draw_ctx.session(), "({}", e);
+
+                // Keep a filtered element in the stack of acquired nodes while it is
+                // rendered, so that an feImage in its filter that references the element
+                // itself gets detected as a circular reference.  A <use> element already
+                // acquires itself while it is rendered.
+                let _filtered = match cascaded.get().filter() {
+                    properties::Filter::List(_) if !is_element_of_type!(self, Use) => {
+                        acquired_nodes.acquire_filtered_node(self)
+                    }
+                    _ => None,
+                };
+
                 let res = match e.draw(self, acquired_nodes, cascaded, viewport, draw_ctx, clipping)
                 {
                     Ok(bbox) => Ok(bbox),
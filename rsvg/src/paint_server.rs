@@ -13,7 +13,7 @@ use crate::error::{AcquireError, NodeIdError, ParseError, ValueErrorKind};
 use crate::gradient::{ResolvedGradient, UserSpaceGradient};
 use crate::length::NormalizeValues;
 use crate::node::NodeBorrow;
-use crate::parsers::Parse;
+use crate::parsers::{Parse, ToCssString};
 use crate::pattern::{ResolvedPattern, UserSpacePattern};
 use crate::rect::Rect;
 use crate::rsvg_log;
@@ -126,6 +126,28 @@ impl Parse for PaintServer {
     }
 }
 
+impl ToCssString for PaintServer {
+    fn to_css_string(&self) -> String {
+        match self {
+            PaintServer::None => "none".to_string(),
+
+            PaintServer::Iri {
+                iri,
+                alternate: None,
+            } => format!("url({iri})"),
+
+            PaintServer::Iri {
+                iri,
+                alternate: Some(color),
+            } => format!("url({iri}) {}", color.to_css_string()),
+
+            PaintServer::SolidColor(color) => color.to_css_string(),
+            PaintServer::ContextFill => "context-fill".to_string(),
+            PaintServer::ContextStroke => "context-stroke".to_string(),
+        }
+    }
+}
+
 impl PaintServer {
     /// Resolves colors, plus node references for gradients and patterns.
     ///
@@ -28,6 +28,14 @@ pub trait Parse: Sized {
     }
 }
 
+/// Trait to serialize values back to CSS syntax; the opposite of [`Parse`].
+///
+/// This is used to report computed values, so the output is normalized rather than a
+/// copy of what the document had: for example, colors are always written as `rgb()`.
+pub trait ToCssString {
+    fn to_css_string(&self) -> String;
+}
+
 /// Consumes a comma if it exists, or does nothing.
 pub fn optional_comma(parser: &mut Parser<'_, '_>) {
     let _ = parser.try_parse(|p| p.expect_comma());
@@ -90,6 +98,26 @@ impl Parse for f64 {
     }
 }
 
+impl ToCssString for f64 {
+    fn to_css_string(&self) -> String {
+        // Numbers get parsed as f32, so print them with that precision to avoid
+        // noise like "0.30000001192092896".
+        (*self as f32).to_string()
+    }
+}
+
+impl ToCssString for f32 {
+    fn to_css_string(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl ToCssString for cssparser::Color {
+    fn to_css_string(&self) -> String {
+        cssparser::ToCss::to_css_string(self)
+    }
+}
+
 /// Non-Negative number
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct NonNegative(pub f64);
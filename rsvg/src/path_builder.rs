@@ -328,15 +328,20 @@ pub(crate) fn arc_segment(
 /// This is returned from iterators on paths and subpaths.
 #[derive(Clone, Default, Debug, PartialEq)]
 pub enum PathCommand {
+    /// Starts a new subpath at the (x, y) point.
     MoveTo(f64, f64),
+    /// Draws a straight line to the (x, y) point.
     LineTo(f64, f64),
+    /// Draws a cubic Bézier curve.
     CurveTo(CubicBezierCurve),
+    /// Draws an elliptical arc.
     Arc(EllipticalArc),
 
     // The #[default] is just so we can use TinyVec, whose type
     // parameter requires T: Default.  There is no actual default for
     // path commands in the SVG spec; this is just our implementation
     // detail.
+    /// Closes the current subpath with a straight line to its starting point.
     #[default]
     ClosePath,
 }
@@ -428,7 +433,7 @@ impl PathCommand {
 /// `parse` method.  When you are finished constructing a path builder, turn it into a
 /// `Path` with `into_path`.  You can then iterate on that `Path`'s commands with its
 /// methods.
-#[derive(Default)]
+#[derive(Debug, Default)]
 pub struct PathBuilder {
     path_commands: TinyVec<[PathCommand; 32]>,
 }
@@ -519,6 +524,14 @@ impl PathBuilder {
         }
     }
 
+    /// Returns the commands that have been added to the path so far.
+    ///
+    /// All the commands have absolute coordinates, since the path parser converts relative
+    /// coordinates and shorthand commands as it goes.
+    pub fn commands(&self) -> &[PathCommand] {
+        &self.path_commands
+    }
+
     /// Adds a MoveTo command to the path.
     pub fn move_to(&mut self, x: f64, y: f64) {
         self.path_commands.push(PathCommand::MoveTo(x, y));
@@ -720,6 +733,40 @@ impl Path {
     pub fn is_empty(&self) -> bool {
         self.commands.is_empty()
     }
+
+    /// Serializes the path as SVG path data, suitable for the `d` attribute of a `<path>`.
+    ///
+    /// All the commands are absolute, and each command letter is repeated for each
+    /// segment.  Elliptical arcs are kept as `A` commands.
+    pub fn to_path_data(&self) -> String {
+        let flag = |b: bool| if b { 1 } else { 0 };
+
+        self.iter()
+            .map(|cmd| match cmd {
+                PathCommand::MoveTo(x, y) => format!("M {x} {y}"),
+                PathCommand::LineTo(x, y) => format!("L {x} {y}"),
+                PathCommand::CurveTo(CubicBezierCurve {
+                    pt1: (x1, y1),
+                    pt2: (x2, y2),
+                    to: (x, y),
+                }) => format!("C {x1} {y1} {x2} {y2} {x} {y}"),
+                PathCommand::Arc(EllipticalArc {
+                    r: (rx, ry),
+                    x_axis_rotation,
+                    large_arc: LargeArc(large_arc),
+                    sweep,
+                    to: (x, y),
+                    ..
+                }) => format!(
+                    "A {rx} {ry} {x_axis_rotation} {} {} {x} {y}",
+                    flag(large_arc),
+                    flag(sweep == Sweep::Positive)
+                ),
+                PathCommand::ClosePath => String::from("Z"),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
 }
 
 fn take_one(iter: &mut slice::Iter<'_, f64>) -> f64 {
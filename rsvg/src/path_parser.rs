@@ -852,15 +852,26 @@ impl ParseError {
 
 use crate::path_parser::ErrorKind::*;
 
-impl fmt::Display for ParseError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let description = match self.kind {
+impl ParseError {
+    /// Describes the error, without its position.
+    pub fn description(&self) -> &'static str {
+        match self.kind {
             UnexpectedToken(_t) => "unexpected token",
             UnexpectedCommand(_c) => "unexpected command",
             UnexpectedEof => "unexpected end of data",
             LexError(_le) => "error processing token",
-        };
-        write!(f, "error at position {}: {}", self.position, description)
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "error at position {}: {}",
+            self.position,
+            self.description()
+        )
     }
 }
 
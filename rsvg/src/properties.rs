@@ -25,9 +25,10 @@ use std::collections::HashSet;
 #[cfg(doc)]
 use crate::make_property;
 
-use crate::css::{DeclParser, Declaration, Origin, RuleBodyItem};
+use crate::css::{DeclParser, Declaration, DeclaredValue, Origin, RuleBodyItem};
+use crate::custom_properties::CustomProperties;
 use crate::error::*;
-use crate::parsers::{Parse, ParseValue};
+use crate::parsers::{Parse, ParseValue, ToCssString};
 use crate::property_macros::Property;
 use crate::rsvg_log;
 use crate::session::Session;
@@ -114,6 +115,12 @@ pub struct SpecifiedValues {
     props: Vec<ParsedProperty>,
 
     transform: Option<Transform>,
+
+    /// Declared custom properties, like `--brand: #3584e4`, as `(name, value)` pairs.
+    custom_properties: Vec<(String, String)>,
+
+    /// Declarations with `var()` references, which get parsed when computing the values.
+    variable_references: Vec<(QualName, String)>,
 }
 
 impl Default for SpecifiedValues {
@@ -123,6 +130,8 @@ impl Default for SpecifiedValues {
             indices: [PropertyId::UnsetProperty.as_u8(); PropertyId::UnsetProperty as usize],
             props: Vec::new(),
             transform: None,
+            custom_properties: Vec::new(),
+            variable_references: Vec::new(),
         }
     }
 }
@@ -134,6 +143,17 @@ impl ComputedValues {
         self.transform
     }
 
+    /// Serializes the computed value of a property, or returns `None` if `prop_name`
+    /// is not the name of a longhand property.
+    pub fn property_to_css_string(&self, prop_name: &str) -> Option<String> {
+        if prop_name == "transform" {
+            // The transform attribute and property get combined into a single value.
+            Some(self.transform.to_css_string())
+        } else {
+            self.longhand_to_css_string(prop_name)
+        }
+    }
+
     pub fn is_overflow(&self) -> bool {
         matches!(self.overflow(), Overflow::Auto | Overflow::Visible)
     }
@@ -151,6 +171,16 @@ impl ComputedValues {
     pub fn is_visible(&self) -> bool {
         self.visibility() == Visibility::Visible
     }
+
+    /// Overrides the `color` property, for example to set it at the root of the cascade.
+    pub fn set_color(&mut self, color: cssparser::Color) {
+        self.set_value(ComputedValue::Color(Color(color)));
+    }
+
+    /// Overrides the custom properties, for example to set them at the root of the cascade.
+    pub fn set_custom_properties(&mut self, custom_properties: CustomProperties) {
+        self.custom_properties = custom_properties;
+    }
 }
 
 /// Macro to generate all the machinery for properties.
@@ -161,6 +191,7 @@ impl ComputedValues {
 /// * `ParsedProperty`, a variant enum for all the specified property values.
 /// * `ComputedValue`, a variant enum for all the computed values.
 /// * `parse_value`, the main function to parse a property or attribute value from user input.
+/// * `ComputedValues::longhand_to_css_string`, to serialize a computed value by property name.
 ///
 /// There is a lot of repetitive code, for example, because sometimes
 /// we need to operate on `PropertyId::Foo`, `ParsedProperty::Foo` and
@@ -267,6 +298,8 @@ macro_rules! make_properties {
             )+
 
             transform: Transform,
+
+            custom_properties: CustomProperties,
         }
 
         impl ParsedProperty {
@@ -351,6 +384,14 @@ macro_rules! make_properties {
                     _ => unreachable!(),
                 }
             }
+
+            fn longhand_to_css_string(&self, prop_name: &str) -> Option<String> {
+                match prop_name {
+                    $($long_str => Some(self.$long_field.to_css_string()),)+
+                    $($long_m5e_str => Some(self.$long_m5e_field.to_css_string()),)+
+                    _ => None,
+                }
+            }
         }
 
         /// Parses a value from either a style property or from an element's attribute.
@@ -489,8 +530,8 @@ make_properties! {
         // "transform-origin"         => (PresentationAttr::Yes, unimplemented),
         "unicode-bidi"                => (PresentationAttr::Yes, unicode_bidi                : UnicodeBidi),
         "visibility"                  => (PresentationAttr::Yes, visibility                  : Visibility),
-        // "word-spacing"             => (PresentationAttr::Yes, unimplemented),
         "width"                       => (PresentationAttr::Yes, width: Width),
+        "word-spacing"                => (PresentationAttr::Yes, word_spacing                : WordSpacing),
         "writing-mode"                => (PresentationAttr::Yes, writing_mode                : WritingMode),
         "x"                           => (PresentationAttr::Yes, x: X),
         "y"                           => (PresentationAttr::Yes, y: Y),
@@ -673,6 +714,14 @@ impl SpecifiedValues {
     }
 
     pub fn to_computed_values(&self, computed: &mut ComputedValues) {
+        computed.custom_properties = computed.custom_properties.cascade(&self.custom_properties);
+
+        if !self.variable_references.is_empty() {
+            let specified = self.with_variables_substituted(&computed.custom_properties);
+            specified.to_computed_values(computed);
+            return;
+        }
+
         macro_rules! compute {
             ($name:ident, $field:ident) => {{
                 // This extra block --------^
@@ -697,6 +746,9 @@ impl SpecifiedValues {
 
         compute!(FontSize, font_size);
 
+        // baseline-shift percentages refer to the line-height.
+        compute!(LineHeight, line_height);
+
         // Then, do all the other properties.
 
         compute!(BaselineShift, baseline_shift);
@@ -759,20 +811,73 @@ impl SpecifiedValues {
         compute!(Visibility, visibility);
         compute!(Width, width);
         compute!(WhiteSpace, white_space);
+        compute!(WordSpacing, word_spacing);
         compute!(WritingMode, writing_mode);
         compute!(X, x);
         compute!(XmlSpace, xml_space);
         compute!(XmlLang, xml_lang);
         compute!(Y, y);
 
-        computed.transform = self.transform.unwrap_or_else(|| {
-            match self.get_property(PropertyId::TransformProperty) {
+        computed.transform = self.transform();
+    }
+
+    /// Returns a copy of `self` with the declarations that have `var()` references replaced
+    /// by their parsed values, after substituting the `custom_properties`.
+    ///
+    /// Declarations that are invalid after substitution make their property behave as
+    /// `unset`, per <https://www.w3.org/TR/css-variables-1/#invalid-at-computed-value-time>.
+    fn with_variables_substituted(&self, custom_properties: &CustomProperties) -> Self {
+        let mut specified = SpecifiedValues {
+            custom_properties: Vec::new(),
+            variable_references: Vec::new(),
+            ..self.clone()
+        };
+
+        for (name, value) in &self.variable_references {
+            let property = custom_properties
+                .substitute(value)
+                .and_then(|value| parse_property(name, &value));
+
+            match property {
+                Some(ref prop) => specified.set_parsed_property(prop),
+                None => specified.unset_property(name),
+            }
+        }
+
+        specified
+    }
+
+    /// Makes the property with the given `name` behave as if it were not specified.
+    fn unset_property(&mut self, name: &QualName) {
+        let Some(inherit) = parse_property(name, "inherit") else {
+            return;
+        };
+
+        let id = inherit.get_property_id();
+
+        if id.is_shorthand() {
+            // All the longhands of `font` and `marker` are inherited properties, so
+            // `inherit` is the same as `unset` for them.
+            self.set_parsed_property(&inherit);
+        } else {
+            self.set_property(&ParsedProperty::unspecified(id), true);
+        }
+    }
+
+    /// Returns the element's transform, from either its `transform` attribute or property.
+    pub fn transform(&self) -> Transform {
+        self.transform
+            .unwrap_or_else(|| match self.get_property(PropertyId::TransformProperty) {
                 ParsedProperty::TransformProperty(SpecifiedValue::Specified(ref t)) => {
                     t.to_transform()
                 }
                 _ => Transform::identity(),
-            }
-        });
+            })
+    }
+
+    /// Overrides the element's transform, as if it came from its `transform` attribute.
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.transform = Some(transform);
     }
 
     /// This is a somewhat egregious hack to allow xml:lang to be stored as a presentational
@@ -828,6 +933,7 @@ impl SpecifiedValues {
                         attr.expanded(),
                         value,
                     );
+                    session.add_warning(&attr, format!("invalid value \"{value}\""));
                 }
             }
 
@@ -855,6 +961,7 @@ impl SpecifiedValues {
                     value,
                     tok,
                 );
+                session.add_warning(&attr, format!("unexpected token '{tok}'"));
             }
 
             Err(ParseError {
@@ -868,6 +975,7 @@ impl SpecifiedValues {
                     attr.expanded(),
                     value,
                 );
+                session.add_warning(&attr, String::from("unexpected end of input"));
             }
 
             Err(ParseError {
@@ -881,6 +989,7 @@ impl SpecifiedValues {
                     attr.expanded(),
                     value,
                 );
+                session.add_warning(&attr, String::from("unexpected error"));
             }
 
             Err(ParseError {
@@ -894,6 +1003,7 @@ impl SpecifiedValues {
                     value,
                     v
                 );
+                session.add_warning(&attr, v.to_string());
             }
         }
     }
@@ -924,6 +1034,7 @@ impl SpecifiedValues {
 
                         Err(e) => {
                             rsvg_log!(session, "ignoring attribute with invalid value: {}", e);
+                            session.add_warning(&e.attr, e.err.to_string());
                         }
                     }
                 }
@@ -942,6 +1053,7 @@ impl SpecifiedValues {
 
                         Err(e) => {
                             rsvg_log!(session, "ignoring attribute with invalid value: {}", e);
+                            session.add_warning(&e.attr, e.err.to_string());
                         }
                     }
                 }
@@ -965,10 +1077,28 @@ impl SpecifiedValues {
             important_styles.insert(declaration.prop_name.clone());
         }
 
-        if origin == Origin::UserAgent {
-            self.set_parsed_property_user_agent(&declaration.property);
-        } else {
-            self.set_parsed_property(&declaration.property);
+        let replace = origin != Origin::UserAgent;
+
+        match declaration.value {
+            DeclaredValue::Property(ref property) => {
+                if replace {
+                    self.variable_references
+                        .retain(|(name, _)| *name != declaration.prop_name);
+                    self.set_parsed_property(property);
+                } else {
+                    self.set_parsed_property_user_agent(property);
+                }
+            }
+
+            DeclaredValue::Custom(ref value) => {
+                let name = declaration.prop_name.local.to_string();
+                set_unparsed(&mut self.custom_properties, name, value, replace);
+            }
+
+            DeclaredValue::WithVariables(ref value) => {
+                let name = declaration.prop_name.clone();
+                set_unparsed(&mut self.variable_references, name, value, replace);
+            }
         }
     }
 
@@ -988,6 +1118,7 @@ impl SpecifiedValues {
                 Ok(RuleBodyItem::Rule(_)) => None,
                 Err(e) => {
                     rsvg_log!(session, "Invalid declaration; ignoring: {:?}", e);
+                    session.add_unsupported(format!("invalid CSS declaration \"{}\"", e.1));
                     None
                 }
             })
@@ -995,6 +1126,32 @@ impl SpecifiedValues {
     }
 }
 
+/// Adds or replaces the unparsed declaration for `name` in `list`.
+fn set_unparsed<N: PartialEq>(list: &mut Vec<(N, String)>, name: N, value: &str, replace: bool) {
+    if let Some(pos) = list.iter().position(|(n, _)| *n == name) {
+        if replace {
+            list[pos].1 = value.to_string();
+        }
+    } else {
+        list.push((name, value.to_string()));
+    }
+}
+
+/// Parses a property's `value`, which must not have anything after it.
+fn parse_property(name: &QualName, value: &str) -> Option<ParsedProperty> {
+    let mut input = ParserInput::new(value);
+    let mut parser = Parser::new(&mut input);
+
+    parse_value(name, &mut parser, ParseAs::Property)
+        .ok()
+        .filter(|_| parser.expect_exhausted().is_ok())
+}
+
+/// Whether `name` is the name of a property that librsvg supports.
+pub fn is_property_name(name: &QualName) -> bool {
+    parse_property(name, "inherit").is_some()
+}
+
 // Parses the value for the type `T` of the property out of the Parser, including `inherit` values.
 fn parse_input<'i, T>(input: &mut Parser<'i, '_>) -> Result<SpecifiedValue<T>, ParseError<'i>>
 where
@@ -45,12 +45,13 @@ use crate::error::*;
 use crate::filter::FilterValueList;
 use crate::font_props::{
     Font, FontFamily, FontSize, FontWeight, GlyphOrientationVertical, LetterSpacing, LineHeight,
+    WordSpacing,
 };
 use crate::iri::Iri;
 use crate::length::*;
 use crate::paint_server::PaintServer;
 use crate::parse_identifiers;
-use crate::parsers::Parse;
+use crate::parsers::{Parse, ToCssString};
 use crate::properties::ComputedValues;
 use crate::property_macros::Property;
 use crate::rect::Rect;
@@ -77,26 +78,36 @@ make_property!(
                 let font_size = v.font_size().value();
                 let parent = v.baseline_shift();
 
-                match (self.0.unit, parent.0.unit) {
-                    (LengthUnit::Percent, _) => {
-                        BaselineShift(Length::<Both>::new(self.0.length * font_size.length + parent.0.length, font_size.unit))
-                    }
-
-                    (x, y) if x == y || parent.0.length == 0.0 => {
-                        BaselineShift(Length::<Both>::new(self.0.length + parent.0.length, self.0.unit))
-                    }
+                // Express font-relative lengths in the units of the font size, so that
+                // they can be added to the parent's shift, which was computed the same way.
+                let in_font_units = |l: Length<Both>| match l.unit {
+                    LengthUnit::Em => Length::<Both>::new(l.length * font_size.length, font_size.unit),
+                    LengthUnit::Ex => Length::<Both>::new(l.length * font_size.length / 2.0, font_size.unit),
+                    _ => l,
+                };
 
-                    _ => {
-                        // FIXME: the limitation here is that the parent's baseline_shift
-                        // and ours have different units.  We should be able to normalize
-                        // the lengths and add them even if they have different units, but
-                        // at the moment that requires access to the draw_ctx, which we
-                        // don't have here.
-                        //
-                        // So for now we won't add to the parent's baseline_shift.
+                // Percentages, including the "sub" and "super" keywords, refer to the line-height.
+                let shift = if self.0.unit == LengthUnit::Percent {
+                    let line_height = in_font_units(v.line_height().compute(v).value());
+                    Length::<Both>::new(self.0.length * line_height.length, line_height.unit)
+                } else {
+                    in_font_units(self.0)
+                };
 
-                        parent
-                    }
+                if shift.length == 0.0 {
+                    parent
+                } else if parent.0.length == 0.0 || shift.unit == parent.0.unit {
+                    BaselineShift(Length::<Both>::new(shift.length + parent.0.length, shift.unit))
+                } else {
+                    // FIXME: the limitation here is that the parent's baseline_shift
+                    // and ours have different absolute units.  We should be able to normalize
+                    // the lengths and add them even if they have different units, but
+                    // at the moment that requires access to the draw_ctx, which we
+                    // don't have here.
+                    //
+                    // So for now we won't add the parent's baseline_shift to ours.
+
+                    BaselineShift(shift)
                 }
             }
         }
@@ -299,6 +310,22 @@ make_property!(
     }
 );
 
+impl ToCssString for EnableBackground {
+    fn to_css_string(&self) -> String {
+        match self {
+            EnableBackground::Accumulate => "accumulate".to_string(),
+            EnableBackground::New(None) => "new".to_string(),
+            EnableBackground::New(Some(r)) => format!(
+                "new {} {} {} {}",
+                r.x0.to_css_string(),
+                r.y0.to_css_string(),
+                r.width().to_css_string(),
+                r.height().to_css_string()
+            ),
+        }
+    }
+}
+
 #[cfg(test)]
 #[test]
 fn parses_enable_background() {
@@ -396,6 +423,15 @@ make_property!(
     }
 );
 
+impl ToCssString for Filter {
+    fn to_css_string(&self) -> String {
+        match self {
+            Filter::None => "none".to_string(),
+            Filter::List(l) => l.to_css_string(),
+        }
+    }
+}
+
 make_property!(
     /// `flood-color` property, for `feFlood` and `feDropShadow` filter elements.
     ///
@@ -669,13 +705,79 @@ make_property!(
     ///
     /// CSS Masking 1: <https://www.w3.org/TR/css-masking-1/#the-mask>
     ///
-    /// Note that librsvg implements SVG1.1 semantics, where this is not a shorthand.
+    /// Note that librsvg implements SVG1.1 semantics, where this is not a shorthand, plus an
+    /// optional masking mode from CSS Masking, as in `mask="url(#m) alpha"`.  The mode
+    /// overrides the `mask-type` of the `<mask>` element.
     Mask,
-    default: Iri::None,
     inherits_automatically: false,
-    newtype_parse: Iri,
+
+    fields: {
+        iri: Iri, default: Iri::None,
+        mode: Option<MaskType>, default: None,
+    }
+
+    parse_impl: {
+        impl Parse for Mask {
+            fn parse<'i>(parser: &mut Parser<'i, '_>) -> Result<Mask, ParseError<'i>> {
+                let mut mode = parser.try_parse(|p| MaskType::parse(p)).ok();
+                let iri = Iri::parse(parser)?;
+
+                if mode.is_none() {
+                    mode = parser.try_parse(|p| MaskType::parse(p)).ok();
+                }
+
+                Ok(Mask { iri, mode })
+            }
+        }
+    }
 );
 
+impl ToCssString for Mask {
+    fn to_css_string(&self) -> String {
+        match self.mode {
+            Some(mode) => format!("{} {}", self.iri.to_css_string(), mode.to_css_string()),
+            None => self.iri.to_css_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn parses_mask() {
+    use crate::document::NodeId;
+
+    let iri = Iri::Resource(Box::new(NodeId::Internal("m".to_string())));
+
+    assert_eq!(Mask::parse_str("none").unwrap(), Mask::default());
+
+    assert_eq!(
+        Mask::parse_str("url(#m)").unwrap(),
+        Mask {
+            iri: iri.clone(),
+            mode: None
+        }
+    );
+
+    assert_eq!(
+        Mask::parse_str("url(#m) alpha").unwrap(),
+        Mask {
+            iri: iri.clone(),
+            mode: Some(MaskType::Alpha)
+        }
+    );
+
+    assert_eq!(
+        Mask::parse_str("luminance url(#m)").unwrap(),
+        Mask {
+            iri,
+            mode: Some(MaskType::Luminance)
+        }
+    );
+
+    assert!(Mask::parse_str("alpha").is_err());
+    assert!(Mask::parse_str("url(#m) alpha luminance").is_err());
+}
+
 make_property!(
     /// `mask-type` property.
     ///
@@ -812,6 +914,20 @@ make_property!(
     }
 );
 
+impl ToCssString for PaintOrder {
+    fn to_css_string(&self) -> String {
+        self.targets
+            .iter()
+            .map(|t| match t {
+                PaintTarget::Fill => "fill",
+                PaintTarget::Stroke => "stroke",
+                PaintTarget::Markers => "markers",
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
 #[cfg(test)]
 #[test]
 fn parses_paint_order() {
@@ -1063,6 +1179,30 @@ make_property!(
     }
 );
 
+impl ToCssString for TextDecoration {
+    fn to_css_string(&self) -> String {
+        let mut values = Vec::new();
+
+        if self.underline {
+            values.push("underline");
+        }
+
+        if self.overline {
+            values.push("overline");
+        }
+
+        if self.strike {
+            values.push("line-through");
+        }
+
+        if values.is_empty() {
+            "none".to_string()
+        } else {
+            values.join(" ")
+        }
+    }
+}
+
 #[cfg(test)]
 #[test]
 fn parses_text_decoration() {
@@ -1223,6 +1363,23 @@ make_property!(
     newtype_parse: LengthOrAuto<Horizontal>,
 );
 
+make_property!(
+    // docs are in font_props.rs
+    WordSpacing,
+    default: WordSpacing::Normal,
+    property_impl: {
+        impl Property for WordSpacing {
+            fn inherits_automatically() -> bool {
+                true
+            }
+
+            fn compute(&self, _v: &ComputedValues) -> Self {
+                self.compute()
+            }
+        }
+    }
+);
+
 make_property!(
     /// `writing-mode` property.
     ///
@@ -28,6 +28,8 @@ pub trait Property {
 ///
 /// * A [`Parse`] implementation to parse the property.
 ///
+/// * A [`ToCssString`] implementation to serialize the property's computed value.
+///
 /// * A [`Default`] implementation to define the property's *initial* value.
 ///
 /// * A [`Property`] implementation to define whether the property
@@ -67,7 +69,7 @@ pub trait Property {
 /// ```
 ///
 /// This generates a simple enum like the following, with implementations of [`Parse`],
-/// [`Default`], and [`Property`].
+/// [`ToCssString`], [`Default`], and [`Property`].
 ///
 /// ```
 /// pub enum StrokeLinejoin { Miter, Round, Bevel }
@@ -94,9 +96,11 @@ pub trait Property {
 /// For example, font-related properties have custom, complex types that require an
 /// implentation of `Property::compute` that is more than a simple `clone`.  In this case,
 /// define the custom type separately, and use the macro to specify the default value and
-/// the `Property` implementation.
+/// the `Property` implementation.  The custom type must also implement `ToCssString` by
+/// hand.
 ///
 /// [`Parse`]: crate::parsers::Parse
+/// [`ToCssString`]: crate::parsers::ToCssString
 /// [`Property`]: crate::property_macros::Property
 /// [`ComputedValues`]: crate::properties::ComputedValues
 /// [`SpecifiedValues`]: crate::properties::SpecifiedValues
@@ -130,6 +134,15 @@ macro_rules! make_property {
                 )?)
             }
         }
+
+        impl $crate::parsers::ToCssString for $name {
+            fn to_css_string(&self) -> String {
+                match *self {
+                    $($name::$variant => $str_prop,)+
+                }
+                .to_string()
+            }
+        }
     };
 
     ($(#[$attr:meta])*
@@ -156,6 +169,15 @@ macro_rules! make_property {
                 )?)
             }
         }
+
+        impl $crate::parsers::ToCssString for $name {
+            fn to_css_string(&self) -> String {
+                match *self {
+                    $($name::$variant => $str_prop,)+
+                }
+                .to_string()
+            }
+        }
     };
 
     ($(#[$attr:meta])*
@@ -176,6 +198,12 @@ macro_rules! make_property {
                 Ok($name(<$type as $crate::parsers::Parse>::parse(parser)?))
             }
         }
+
+        impl $crate::parsers::ToCssString for $name {
+            fn to_css_string(&self) -> String {
+                $crate::parsers::ToCssString::to_css_string(&self.0)
+            }
+        }
     };
 
     ($(#[$attr:meta])*
@@ -223,6 +251,12 @@ macro_rules! make_property {
         $prop
 
         $parse
+
+        impl $crate::parsers::ToCssString for $name {
+            fn to_css_string(&self) -> String {
+                $crate::parsers::ToCssString::to_css_string(&self.0)
+            }
+        }
     };
 
     // pending - only XmlLang
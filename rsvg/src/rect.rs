@@ -170,6 +170,8 @@ mod rect {
     }
 }
 
+/// A rectangle given by its top-left corner `(x0, y0)` and its bottom-right corner
+/// `(x1, y1)`.
 pub type Rect = rect::Rect<f64>;
 
 impl From<Rect> for IRect {
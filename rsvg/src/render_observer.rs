@@ -0,0 +1,67 @@
+//! Hooks to observe the drawing primitives emitted while rendering.
+//!
+//! While rendering, the [`DrawingCtx`](crate::drawing_ctx::DrawingCtx) records a
+//! [`RenderEvent`] for each layer, fill, stroke, image, and text span that it draws, if the
+//! caller asked for it.  The events are then handed to a public [`RenderObserver`].
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::rect::Rect;
+
+/// Receives notifications about what gets drawn while rendering a document.
+///
+/// Pass an implementation of this trait to [`CairoRenderer::render_document_observed`] to
+/// collect statistics about a document, or to debug its rendering.  All the methods have
+/// empty default implementations, so you only need to implement the ones you are
+/// interested in.
+///
+/// Bounding boxes are in the device space of the surface being drawn to, which for
+/// elements outside of filters and masks is the surface of the `cr` passed to the renderer.
+///
+/// Elements used as clipping paths are not reported, since they are not drawn.
+///
+/// [`CairoRenderer::render_document_observed`]: crate::CairoRenderer::render_document_observed
+pub trait RenderObserver {
+    /// An element starts getting drawn in a layer of its own.
+    ///
+    /// `id` is the value of the element's `id` attribute, if it has one.
+    fn on_layer(&mut self, _id: Option<&str>) {}
+
+    /// A shape gets filled.
+    fn on_fill(&mut self, _bbox: &cairo::Rectangle) {}
+
+    /// A shape gets stroked.
+    fn on_stroke(&mut self, _bbox: &cairo::Rectangle) {}
+
+    /// A raster image, or an image of an SVG document, gets painted.
+    fn on_image(&mut self, _bbox: &cairo::Rectangle) {}
+
+    /// A span of text gets drawn.
+    fn on_text(&mut self, _bbox: &cairo::Rectangle) {}
+}
+
+/// A drawing primitive, as recorded while rendering.
+pub enum RenderEvent {
+    Layer(Option<String>),
+    Fill(Rect),
+    Stroke(Rect),
+    Image(Rect),
+    Text(Rect),
+}
+
+/// Where the drawing code records [`RenderEvent`]s.
+pub type RenderEvents = Rc<RefCell<Vec<RenderEvent>>>;
+
+/// Sends the recorded `events` to the `observer`, in the order in which they happened.
+pub fn replay(events: Vec<RenderEvent>, observer: &mut dyn RenderObserver) {
+    for event in events {
+        match event {
+            RenderEvent::Layer(id) => observer.on_layer(id.as_deref()),
+            RenderEvent::Fill(r) => observer.on_fill(&r.into()),
+            RenderEvent::Stroke(r) => observer.on_stroke(&r.into()),
+            RenderEvent::Image(r) => observer.on_image(&r.into()),
+            RenderEvent::Text(r) => observer.on_text(&r.into()),
+        }
+    }
+}
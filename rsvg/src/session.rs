@@ -1,6 +1,9 @@
 //! Tracks metadata for a loading/rendering session.
 
-use std::sync::Arc;
+use markup5ever::QualName;
+use std::sync::{Arc, Mutex};
+
+use crate::error::LoadWarning;
 
 /// Metadata for a loading/rendering session.
 ///
@@ -14,6 +17,15 @@ pub struct Session {
 
 struct SessionInner {
     log_enabled: bool,
+
+    /// Recoverable problems found while loading, if the caller asked to collect them.
+    warnings: Option<Arc<Mutex<Vec<LoadWarning>>>>,
+
+    /// In strict mode, holds the first unsupported feature found while loading.
+    strict_failure: Option<Arc<Mutex<Option<String>>>>,
+
+    /// Name of the element being created, for reporting warnings.
+    element_name: Option<String>,
 }
 
 fn log_enabled_via_env_var() -> bool {
@@ -25,6 +37,9 @@ impl Default for Session {
         Self {
             inner: Arc::new(SessionInner {
                 log_enabled: log_enabled_via_env_var(),
+                warnings: None,
+                strict_failure: None,
+                element_name: None,
             }),
         }
     }
@@ -34,11 +49,113 @@ impl Session {
     #[cfg(test)]
     pub fn new_for_test_suite() -> Self {
         Self {
-            inner: Arc::new(SessionInner { log_enabled: false }),
+            inner: Arc::new(SessionInner {
+                log_enabled: false,
+                warnings: None,
+                strict_failure: None,
+                element_name: None,
+            }),
         }
     }
 
     pub fn log_enabled(&self) -> bool {
         self.inner.log_enabled
     }
+
+    /// Returns a copy of this session that collects [`LoadWarning`]s.
+    pub fn collecting_warnings(&self) -> Self {
+        Self {
+            inner: Arc::new(SessionInner {
+                log_enabled: self.inner.log_enabled,
+                warnings: Some(Arc::new(Mutex::new(Vec::new()))),
+                strict_failure: self.inner.strict_failure.clone(),
+                element_name: None,
+            }),
+        }
+    }
+
+    pub fn is_collecting_warnings(&self) -> bool {
+        self.inner.warnings.is_some()
+    }
+
+    /// Returns a copy of this session in strict mode, where unsupported features are errors.
+    pub fn strict(&self) -> Self {
+        Self {
+            inner: Arc::new(SessionInner {
+                log_enabled: self.inner.log_enabled,
+                warnings: self.inner.warnings.clone(),
+                strict_failure: Some(Arc::new(Mutex::new(None))),
+                element_name: None,
+            }),
+        }
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.inner.strict_failure.is_some()
+    }
+
+    /// Records an unsupported feature.  Does nothing if the session is not strict, or if it
+    /// already found an unsupported feature.
+    pub fn add_unsupported(&self, detail: String) {
+        if let Some(ref failure) = self.inner.strict_failure {
+            failure.lock().unwrap().get_or_insert(detail);
+        }
+    }
+
+    /// Removes and returns the first unsupported feature found in strict mode.
+    pub fn take_strict_failure(&self) -> Option<String> {
+        self.inner
+            .strict_failure
+            .as_ref()
+            .and_then(|failure| failure.lock().unwrap().take())
+    }
+
+    /// Returns a session that attributes warnings to the element called `element_name`.
+    ///
+    /// Warnings are collected in the same list as the original session's.
+    pub fn for_element(&self, element_name: &str) -> Self {
+        Self {
+            inner: Arc::new(SessionInner {
+                log_enabled: self.inner.log_enabled,
+                warnings: self.inner.warnings.clone(),
+                strict_failure: self.inner.strict_failure.clone(),
+                element_name: Some(element_name.to_string()),
+            }),
+        }
+    }
+
+    /// Records a recoverable problem with an attribute.  Does nothing if warnings are not being
+    /// collected.
+    ///
+    /// In strict mode, the problem is also recorded as an unsupported feature.
+    pub fn add_warning(&self, attribute: &QualName, message: String) {
+        if self.inner.warnings.is_none() && !self.is_strict() {
+            return;
+        }
+
+        let attribute = match attribute.prefix {
+            Some(ref prefix) => format!("{}:{}", prefix, attribute.local),
+            None => attribute.local.to_string(),
+        };
+
+        let warning = LoadWarning {
+            element: self.inner.element_name.clone().unwrap_or_default(),
+            attribute,
+            message,
+        };
+
+        self.add_unsupported(warning.to_string());
+
+        if let Some(ref warnings) = self.inner.warnings {
+            warnings.lock().unwrap().push(warning);
+        }
+    }
+
+    /// Removes and returns the warnings collected so far.
+    pub fn take_warnings(&self) -> Vec<LoadWarning> {
+        match self.inner.warnings {
+            Some(ref warnings) => std::mem::take(&mut *warnings.lock().unwrap()),
+            None => Vec::new(),
+        }
+    }
 }
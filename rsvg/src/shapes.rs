@@ -10,7 +10,7 @@ use crate::bbox::BoundingBox;
 use crate::cairo_path::validate_path;
 use crate::document::AcquiredNodes;
 use crate::drawing_ctx::{DrawingCtx, Viewport};
-use crate::element::{set_attribute, ElementTrait};
+use crate::element::{set_attribute, Element, ElementData, ElementTrait};
 use crate::error::*;
 use crate::iri::Iri;
 use crate::is_element_of_type;
@@ -45,6 +45,29 @@ trait BasicShape {
     fn make_shape(&self, params: &NormalizeParams, values: &ComputedValues) -> ShapeDef;
 }
 
+/// Computes the path of a basic shape element, in the element's user space.
+///
+/// Returns `None` if the element is not a basic shape.  This is what `<textPath>` uses
+/// to find out the path for its text.
+#[cfg(feature = "text")]
+pub fn basic_shape_path(element: &Element, viewport: &Viewport) -> Option<Rc<SvgPath>> {
+    let basic_shape: &dyn BasicShape = match element.element_data {
+        ElementData::Circle(ref e) => &**e,
+        ElementData::Ellipse(ref e) => &**e,
+        ElementData::Line(ref e) => &**e,
+        ElementData::Path(ref e) => &**e,
+        ElementData::Polygon(ref e) => &**e,
+        ElementData::Polyline(ref e) => &**e,
+        ElementData::Rect(ref e) => &**e,
+        _ => return None,
+    };
+
+    let values = element.get_computed_values();
+    let params = NormalizeParams::new(values, viewport);
+
+    Some(basic_shape.make_shape(&params, values).path)
+}
+
 fn draw_basic_shape(
     basic_shape: &dyn BasicShape,
     node: &Node,
@@ -199,6 +222,26 @@ macro_rules! impl_draw {
     };
 }
 
+/// Computes the path of a basic shape element, or returns `None` for other elements.
+pub fn element_path(
+    element: &Element,
+    params: &NormalizeParams,
+    values: &ComputedValues,
+) -> Option<Rc<SvgPath>> {
+    let shape: &dyn BasicShape = match element.element_data {
+        ElementData::Circle(ref c) => &**c,
+        ElementData::Ellipse(ref e) => &**e,
+        ElementData::Line(ref l) => &**l,
+        ElementData::Path(ref p) => &**p,
+        ElementData::Polygon(ref p) => &**p,
+        ElementData::Polyline(ref p) => &**p,
+        ElementData::Rect(ref r) => &**r,
+        _ => return None,
+    };
+
+    Some(shape.make_shape(params, values).path)
+}
+
 fn acquire_marker(
     session: &Session,
     acquired_nodes: &mut AcquiredNodes<'_>,
@@ -1,12 +1,39 @@
 //! Processing of the `xml:space` attribute.
 
+#[cfg(feature = "text")]
 use itertools::Itertools;
 
+/// How to handle whitespace in text content for a whole document.
+///
+/// This is set with [`Loader::with_whitespace_mode`][crate::Loader::with_whitespace_mode].
+/// SVG documents specify whitespace handling with the `xml:space` attribute; this lets
+/// the calling program override the document-wide default, for example, to match the
+/// expectations of the tool that generated the SVG.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum WhitespaceMode {
+    /// The SVG spec's behavior: collapse whitespace, except for elements with
+    /// `xml:space="preserve"`.
+    #[default]
+    Default,
+
+    /// Treat all text as if the root element had `xml:space="preserve"`.
+    ///
+    /// Elements with an explicit `xml:space` attribute still get its value, so
+    /// `xml:space="default"` can be used to collapse whitespace in a subtree.
+    Preserve,
+
+    /// Treat all text as having `xml:space="preserve"`, ignoring any `xml:space`
+    /// attributes in the document.
+    ForcePreserve,
+}
+
+#[cfg(feature = "text")]
 pub struct NormalizeDefault {
     pub has_element_before: bool,
     pub has_element_after: bool,
 }
 
+#[cfg(feature = "text")]
 pub enum XmlSpaceNormalize {
     Default(NormalizeDefault),
     Preserve,
@@ -17,6 +44,7 @@ pub enum XmlSpaceNormalize {
 /// Normalizes a string as it comes out of the XML parser's handler
 /// for character data according to the SVG rules in
 /// <https://www.w3.org/TR/SVG/text.html#WhiteSpace>
+#[cfg(feature = "text")]
 pub fn xml_space_normalize(mode: XmlSpaceNormalize, s: &str) -> String {
     match mode {
         XmlSpaceNormalize::Default(d) => normalize_default(d, s),
@@ -32,6 +60,7 @@ pub fn xml_space_normalize(mode: XmlSpaceNormalize, s: &str) -> String {
 // characters into space characters. Then, it will strip off all
 // leading and trailing space characters. Then, all contiguous space
 // characters will be consolidated.
+#[cfg(feature = "text")]
 fn normalize_default(elements: NormalizeDefault, mut s: &str) -> String {
     if !elements.has_element_before {
         s = s.trim_start();
@@ -64,6 +93,7 @@ fn normalize_default(elements: NormalizeDefault, mut s: &str) -> String {
 // xml:space="preserve", the string "a   b" (three spaces between "a"
 // and "b") will produce a larger separation between "a" and "b" than
 // "a b" (one space between "a" and "b").
+#[cfg(feature = "text")]
 fn normalize_preserve(s: &str) -> String {
     s.chars()
         .map(|ch| match ch {
@@ -74,7 +104,7 @@ fn normalize_preserve(s: &str) -> String {
         .collect()
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "text"))]
 mod tests {
     use super::*;
 
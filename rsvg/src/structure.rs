@@ -1,6 +1,6 @@
 //! Structural elements in SVG: the `g`, `switch`, `svg`, `use`, `symbol`, `clip_path`, `mask`, `link` elements.
 
-use markup5ever::{expanded_name, local_name, namespace_url, ns};
+use markup5ever::{expanded_name, local_name, namespace_url, ns, ExpandedName};
 
 use crate::aspect_ratio::*;
 use crate::bbox::BoundingBox;
@@ -352,6 +352,11 @@ pub struct Use {
 }
 
 impl Use {
+    /// Returns the element that this `<use>` references.
+    pub fn link(&self) -> Option<&NodeId> {
+        self.link.as_ref()
+    }
+
     fn get_rect(&self, params: &NormalizeParams) -> Rect {
         let x = self.x.to_user(params);
         let y = self.y.to_user(params);
@@ -645,3 +650,38 @@ impl ElementTrait for Link {
         )
     }
 }
+
+/// Returns the text of the first `<title>` child of `node`, for accessibility tools.
+///
+/// See [`child_text_content`] for how the text is collected.
+pub fn title_text(node: &Node) -> Option<String> {
+    child_text_content(node, expanded_name!(svg "title"))
+}
+
+/// Returns the text of the first `<desc>` child of `node`, for accessibility tools.
+///
+/// See [`child_text_content`] for how the text is collected.
+pub fn desc_text(node: &Node) -> Option<String> {
+    child_text_content(node, expanded_name!(svg "desc"))
+}
+
+/// Collects the character data of the first child element of `node` called `name`.
+///
+/// The text of all the descendants of that child is concatenated, and then runs of
+/// whitespace are collapsed into a single space, with leading and trailing whitespace
+/// removed.  This happens regardless of `xml:space`, since the text is not rendered.
+///
+/// Returns `None` if there is no such child.
+fn child_text_content(node: &Node, name: ExpandedName<'_>) -> Option<String> {
+    let child = node
+        .children()
+        .find(|c| c.is_element() && c.borrow_element().element_name().expanded() == name)?;
+
+    let text: String = child
+        .descendants()
+        .filter(|d| d.is_chars())
+        .map(|d| d.borrow_chars().get_string())
+        .collect();
+
+    Some(text.split_whitespace().collect::<Vec<_>>().join(" "))
+}
@@ -1,12 +1,14 @@
 //! Shared access to Cairo image surfaces.
 use std::cmp::min;
+use std::f64;
 use std::marker::PhantomData;
 use std::ptr::NonNull;
 use std::slice;
 
 use cast::i32;
 use cssparser::Color;
-use nalgebra::{storage::Storage, Dim, Matrix};
+use nalgebra::{storage::Storage, DMatrix, Dim, Dyn, Matrix, VecStorage};
+use rayon::prelude::*;
 
 use crate::color::color_to_rgba;
 use crate::drawing_ctx::set_source_color_on_cairo;
@@ -20,6 +22,11 @@ use super::{
     AsCairoARGB, CairoARGB, EdgeMode, ImageSurfaceDataExt, Pixel, PixelOps, ToCairoARGB, ToPixel,
 };
 
+/// The maximum gaussian blur kernel size.
+///
+/// The value of 500 is used in webkit.
+const MAXIMUM_KERNEL_SIZE: usize = 500;
+
 /// Interpolation when scaling images.
 ///
 /// This is meant to be translated from the `ImageRendering` property.  We don't use
@@ -319,11 +326,55 @@ impl ImageSurface<Shared> {
             .flat_map(|(src_row, dest_row)| src_row.zip(dest_row.iter_mut()))
             .for_each(|(src, dest)| *dest = src.to_pixel().premultiply().to_cairo_argb());
 
+        // Flushing the surface in share() would detach the MIME data, so attach it
+        // afterwards.
+        let shared = surf.share()?;
+
         if let (Some(content_type), Some(bytes)) = (content_type, mime_data) {
-            surf.surface.set_mime_data(content_type, bytes)?;
+            shared.surface.set_mime_data(content_type, bytes)?;
         }
 
-        surf.share()
+        Ok(shared)
+    }
+
+    /// Returns whether the original image data of the given MIME type is attached to
+    /// the surface, as done by [`Self::from_image`].
+    pub fn has_mime_data(&self, content_type: &str) -> bool {
+        self.surface.mime_data(content_type).is_some()
+    }
+
+    /// Converts the surface to a `Pixbuf` with unpremultiplied RGBA pixels.
+    ///
+    /// The surface must be in the sRGB color space. Returns `None` if the `Pixbuf` cannot
+    /// be allocated; note that GdkPixbuf does not support zero-sized images.
+    #[cfg(feature = "pixbuf")]
+    pub fn to_pixbuf(&self) -> Option<gdk_pixbuf::Pixbuf> {
+        assert!(self.surface_type != SurfaceType::LinearRgb);
+
+        let pixbuf = gdk_pixbuf::Pixbuf::new(
+            gdk_pixbuf::Colorspace::Rgb,
+            true,
+            8,
+            self.width,
+            self.height,
+        )?;
+
+        let stride = pixbuf.rowstride() as usize;
+        let pixbuf_data = unsafe { pixbuf.pixels() };
+
+        // We use chunks_mut(), not chunks_exact_mut(), because gdk-pixbuf tends
+        // to make the last row *not* have the full stride.
+        pixbuf_data
+            .chunks_mut(stride)
+            .take(self.height as usize)
+            .zip(self.rows())
+            .flat_map(|(dest_row, src_row)| src_row.iter().zip(dest_row.chunks_exact_mut(4)))
+            .for_each(|(src, dest)| {
+                let pixel = src.to_pixel().unpremultiply();
+                dest.copy_from_slice(&[pixel.r, pixel.g, pixel.b, pixel.a]);
+            });
+
+        Some(pixbuf)
     }
 
     /// Returns `true` if the surface contains meaningful data only in the alpha channel.
@@ -550,6 +601,19 @@ impl ImageSurface<Shared> {
         target: (i32, i32),
         kernel: &Matrix<f64, R, C, S>,
         edge_mode: EdgeMode,
+    ) -> Result<SharedImageSurface, cairo::Error> {
+        self.convolve_rows(bounds, target, kernel, edge_mode, PARALLEL_FILTERS)
+    }
+
+    /// Performs a convolution like [`Self::convolve`], computing the rows in parallel if
+    /// `parallel` is true.
+    fn convolve_rows<R: Dim, C: Dim, S: Storage<f64, R, C>>(
+        &self,
+        bounds: IRect,
+        target: (i32, i32),
+        kernel: &Matrix<f64, R, C, S>,
+        edge_mode: EdgeMode,
+        parallel: bool,
     ) -> Result<SharedImageSurface, cairo::Error> {
         assert!(kernel.nrows() >= 1);
         assert!(kernel.ncols() >= 1);
@@ -557,81 +621,59 @@ impl ImageSurface<Shared> {
         let mut output_surface =
             cairo::ImageSurface::create(cairo::Format::ARgb32, self.width, self.height)?;
 
-        let output_stride = output_surface.stride() as usize;
-        {
-            let mut output_data = output_surface.data().unwrap();
-
-            if self.is_alpha_only() {
-                for (x, y, _pixel) in Pixels::within(self, bounds) {
-                    let kernel_bounds = IRect::new(
-                        x as i32 - target.0,
-                        y as i32 - target.1,
-                        x as i32 - target.0 + kernel.ncols() as i32,
-                        y as i32 - target.1 + kernel.nrows() as i32,
-                    );
-
-                    let mut a = 0.0;
-
-                    for (x, y, pixel) in
-                        PixelRectangle::within(self, bounds, kernel_bounds, edge_mode)
-                    {
-                        let kernel_x = (kernel_bounds.x1 - x - 1) as usize;
-                        let kernel_y = (kernel_bounds.y1 - y - 1) as usize;
-                        let factor = kernel[(kernel_y, kernel_x)];
-
-                        a += f64::from(pixel.a) * factor;
-                    }
-
-                    let convert = |x: f64| (clamp(x, 0.0, 255.0) + 0.5) as u8;
-
-                    let output_pixel = Pixel {
-                        r: 0,
-                        g: 0,
-                        b: 0,
-                        a: convert(a),
-                    };
-
-                    output_data.set_pixel(output_stride, output_pixel, x, y);
+        // Copy the kernel into an owned matrix so that it can be shared between threads.
+        let kernel = DMatrix::from_iterator(kernel.nrows(), kernel.ncols(), kernel.iter().copied());
+        let alpha_only = self.is_alpha_only();
+
+        // Computes the output pixel at (x, y).
+        let convolve_pixel = |x: u32, y: u32| -> Pixel {
+            let kernel_bounds = IRect::new(
+                x as i32 - target.0,
+                y as i32 - target.1,
+                x as i32 - target.0 + kernel.ncols() as i32,
+                y as i32 - target.1 + kernel.nrows() as i32,
+            );
+
+            let mut r = 0.0;
+            let mut g = 0.0;
+            let mut b = 0.0;
+            let mut a = 0.0;
+
+            for (x, y, pixel) in PixelRectangle::within(self, bounds, kernel_bounds, edge_mode) {
+                let kernel_x = (kernel_bounds.x1 - x - 1) as usize;
+                let kernel_y = (kernel_bounds.y1 - y - 1) as usize;
+                let factor = kernel[(kernel_y, kernel_x)];
+
+                if !alpha_only {
+                    r += f64::from(pixel.r) * factor;
+                    g += f64::from(pixel.g) * factor;
+                    b += f64::from(pixel.b) * factor;
                 }
-            } else {
-                for (x, y, _pixel) in Pixels::within(self, bounds) {
-                    let kernel_bounds = IRect::new(
-                        x as i32 - target.0,
-                        y as i32 - target.1,
-                        x as i32 - target.0 + kernel.ncols() as i32,
-                        y as i32 - target.1 + kernel.nrows() as i32,
-                    );
-
-                    let mut r = 0.0;
-                    let mut g = 0.0;
-                    let mut b = 0.0;
-                    let mut a = 0.0;
-
-                    for (x, y, pixel) in
-                        PixelRectangle::within(self, bounds, kernel_bounds, edge_mode)
-                    {
-                        let kernel_x = (kernel_bounds.x1 - x - 1) as usize;
-                        let kernel_y = (kernel_bounds.y1 - y - 1) as usize;
-                        let factor = kernel[(kernel_y, kernel_x)];
-
-                        r += f64::from(pixel.r) * factor;
-                        g += f64::from(pixel.g) * factor;
-                        b += f64::from(pixel.b) * factor;
-                        a += f64::from(pixel.a) * factor;
-                    }
 
-                    let convert = |x: f64| (clamp(x, 0.0, 255.0) + 0.5) as u8;
+                a += f64::from(pixel.a) * factor;
+            }
 
-                    let output_pixel = Pixel {
-                        r: convert(r),
-                        g: convert(g),
-                        b: convert(b),
-                        a: convert(a),
-                    };
+            let convert = |x: f64| (clamp(x, 0.0, 255.0) + 0.5) as u8;
 
-                    output_data.set_pixel(output_stride, output_pixel, x, y);
-                }
+            Pixel {
+                r: convert(r),
+                g: convert(g),
+                b: convert(b),
+                a: convert(a),
             }
+        };
+
+        let output_stride = output_surface.stride() as usize;
+        {
+            let mut output_data = output_surface.data().unwrap();
+
+            fill_pixels(
+                &mut output_data[..],
+                output_stride,
+                bounds,
+                parallel,
+                convolve_pixel,
+            );
         }
 
         SharedImageSurface::wrap(output_surface, self.surface_type)
@@ -644,6 +686,8 @@ impl ImageSurface<Shared> {
     /// current pixel, and the rest of the kernel is to the right or bottom of the pixel. The value
     /// of `kernel_size / 2` centers a kernel with an odd size.
     ///
+    /// Pixels outside of `bounds` covered by the kernel are computed according to `edge_mode`.
+    ///
     /// # Panics
     /// Panics if `kernel_size` is `0` or if `target >= kernel_size`.
     // This is public (and not inlined into box_blur()) for the purpose of accessing it from the
@@ -654,6 +698,7 @@ impl ImageSurface<Shared> {
         bounds: IRect,
         kernel_size: usize,
         target: usize,
+        edge_mode: EdgeMode,
     ) {
         assert_ne!(kernel_size, 0);
         assert!(target < kernel_size);
@@ -804,13 +849,36 @@ impl ImageSurface<Shared> {
                 (bounds.x0, bounds.x1, bounds.y0, bounds.y1)
             };
 
-            // Helper function for getting the pixels.
-            let pixel = |i, j| {
+            // Helper function for getting the pixels.  The main axis coordinate may be out of
+            // bounds, in which case the pixel is computed according to the edge mode.
+            let pixel = |i, j: i32| {
+                let j = if j < main_axis_min || j >= main_axis_max {
+                    match edge_mode {
+                        EdgeMode::None => return Pixel::default(),
+                        EdgeMode::Duplicate => j.clamp(main_axis_min, main_axis_max - 1),
+                        EdgeMode::Wrap => {
+                            let length = main_axis_max - main_axis_min;
+                            main_axis_min + (j - main_axis_min).rem_euclid(length)
+                        }
+                    }
+                } else {
+                    j
+                };
+
                 let (x, y) = if B::IS_VERTICAL { (i, j) } else { (j, i) };
 
                 self.get_pixel(x as u32, y as u32)
             };
 
+            // With EdgeMode::None the pixels outside of bounds are transparent, so they don't
+            // need to be added to or subtracted from the sums.  Otherwise the whole kernel is used.
+            let (first_sum_start, first_sum_end) =
+                if edge_mode == EdgeMode::None || main_axis_min >= main_axis_max {
+                    (main_axis_min, min(main_axis_max, main_axis_min + shift))
+                } else {
+                    (main_axis_min - target, main_axis_min + shift)
+                };
+
             // The following loop assumes the first row or column of `output_data` is the first row
             // or column inside `bounds`.
             let mut output_data = if B::IS_VERTICAL {
@@ -852,10 +920,8 @@ impl ImageSurface<Shared> {
                         let mut sum_b = 0;
                         let mut sum_a = 0;
 
-                        // The whole sum needs to be computed for the first pixel. However, we know
-                        // that values outside of bounds are transparent, so the loop starts on the
-                        // first pixel in bounds.
-                        for j in main_axis_min..min(main_axis_max, main_axis_min + shift) {
+                        // The whole sum needs to be computed for the first pixel.
+                        for j in first_sum_start..first_sum_end {
                             let Pixel { r, g, b, a } = pixel(i, j);
 
                             if !A::IS_ALPHA_ONLY {
@@ -881,11 +947,15 @@ impl ImageSurface<Shared> {
                         //
                         // j - target - 1 >= main_axis_min
                         // j >= main_axis_min + target + 1
-                        let start_subtracting_at = main_axis_min + target + 1;
-
+                        //
                         // j + shift - 1 < main_axis_max
                         // j < main_axis_max - shift + 1
-                        let stop_adding_at = main_axis_max - shift + 1;
+                        let (start_subtracting_at, stop_adding_at) = if edge_mode == EdgeMode::None
+                        {
+                            (main_axis_min + target + 1, main_axis_max - shift + 1)
+                        } else {
+                            (main_axis_min + 1, main_axis_max)
+                        };
 
                         for j in main_axis_min + 1..main_axis_max {
                             if j >= start_subtracting_at {
@@ -947,19 +1017,149 @@ impl ImageSurface<Shared> {
         bounds: IRect,
         kernel_size: usize,
         target: usize,
+        edge_mode: EdgeMode,
     ) -> Result<SharedImageSurface, cairo::Error> {
         let mut output_surface =
             cairo::ImageSurface::create(cairo::Format::ARgb32, self.width, self.height)?;
 
         if self.is_alpha_only() {
-            self.box_blur_loop::<B, AlphaOnly>(&mut output_surface, bounds, kernel_size, target);
+            self.box_blur_loop::<B, AlphaOnly>(
+                &mut output_surface,
+                bounds,
+                kernel_size,
+                target,
+                edge_mode,
+            );
         } else {
-            self.box_blur_loop::<B, NotAlphaOnly>(&mut output_surface, bounds, kernel_size, target);
+            self.box_blur_loop::<B, NotAlphaOnly>(
+                &mut output_surface,
+                bounds,
+                kernel_size,
+                target,
+                edge_mode,
+            );
         }
 
         SharedImageSurface::wrap(output_surface, self.surface_type)
     }
 
+    /// Applies a gaussian blur to the whole surface, the same way as `feGaussianBlur`.
+    ///
+    /// `std_x` and `std_y` are the standard deviations in pixels for the horizontal and
+    /// vertical directions.  A deviation of zero (or a negative one) does not blur in that
+    /// direction, so passing zero for both returns a clone of the surface.  Very large
+    /// deviations are clamped so that the blur kernel is at most 500 pixels wide.
+    ///
+    /// Deviations of 2.0 or more are approximated with three successive box blurs, as the
+    /// Filter Effects spec allows; smaller ones are convolved with an exact gaussian kernel.
+    pub fn gaussian_blur(
+        &self,
+        std_x: f64,
+        std_y: f64,
+        edge_mode: EdgeMode,
+    ) -> Result<SharedImageSurface, cairo::Error> {
+        let bounds = IRect::from_size(self.width, self.height);
+        self.gaussian_blur_within(bounds, std_x, std_y, edge_mode)
+    }
+
+    /// Applies a gaussian blur to the pixels within `bounds`.
+    ///
+    /// See [`gaussian_blur`](#method.gaussian_blur) for the meaning of the parameters.
+    pub fn gaussian_blur_within(
+        &self,
+        bounds: IRect,
+        std_x: f64,
+        std_y: f64,
+        edge_mode: EdgeMode,
+    ) -> Result<SharedImageSurface, cairo::Error> {
+        // Performance TODO: gaussian blur is frequently used for shadows, operating on SourceAlpha
+        // (so the image is alpha-only). We can use this to not waste time processing the other
+        // channels.
+
+        // Horizontal convolution.
+        let horiz_result_surface = if std_x >= 2.0 {
+            // The spec says for deviation >= 2.0 three box blurs can be used as an optimization.
+            self.three_box_blurs::<Horizontal>(bounds, std_x, edge_mode)?
+        } else if std_x > 0.0 {
+            self.gaussian_convolve(bounds, std_x, edge_mode, false)?
+        } else {
+            self.clone()
+        };
+
+        // Vertical convolution.
+        let output_surface = if std_y >= 2.0 {
+            // The spec says for deviation >= 2.0 three box blurs can be used as an optimization.
+            horiz_result_surface.three_box_blurs::<Vertical>(bounds, std_y, edge_mode)?
+        } else if std_y > 0.0 {
+            horiz_result_surface.gaussian_convolve(bounds, std_y, edge_mode, true)?
+        } else {
+            horiz_result_surface
+        };
+
+        Ok(output_surface)
+    }
+
+    /// Applies three box blurs to approximate the gaussian blur.
+    ///
+    /// This is intended to be used in two steps, horizontal and vertical.
+    fn three_box_blurs<B: BlurDirection>(
+        &self,
+        bounds: IRect,
+        std_deviation: f64,
+        edge_mode: EdgeMode,
+    ) -> Result<SharedImageSurface, cairo::Error> {
+        let d = box_blur_kernel_size(std_deviation);
+        if d == 0 {
+            return Ok(self.clone());
+        }
+
+        let surface = if d % 2 == 1 {
+            // Odd kernel sizes just get three successive box blurs.
+            let mut surface = self.clone();
+
+            for _ in 0..3 {
+                surface = surface.box_blur::<B>(bounds, d, d / 2, edge_mode)?;
+            }
+
+            surface
+        } else {
+            // Even kernel sizes have a more interesting scheme.
+            let surface = self.box_blur::<B>(bounds, d, d / 2, edge_mode)?;
+            let surface = surface.box_blur::<B>(bounds, d, d / 2 - 1, edge_mode)?;
+
+            let d = d + 1;
+            surface.box_blur::<B>(bounds, d, d / 2, edge_mode)?
+        };
+
+        Ok(surface)
+    }
+
+    /// Convolves with an exact gaussian kernel.
+    ///
+    /// This is intended to be used in two steps, horizontal and vertical.
+    fn gaussian_convolve(
+        &self,
+        bounds: IRect,
+        std_deviation: f64,
+        edge_mode: EdgeMode,
+        vertical: bool,
+    ) -> Result<SharedImageSurface, cairo::Error> {
+        let kernel = gaussian_kernel(std_deviation);
+        let (rows, cols) = if vertical {
+            (kernel.len(), 1)
+        } else {
+            (1, kernel.len())
+        };
+        let kernel = DMatrix::from_data(VecStorage::new(Dyn(rows), Dyn(cols), kernel));
+
+        self.convolve(
+            bounds,
+            ((cols / 2) as i32, (rows / 2) as i32),
+            &kernel,
+            edge_mode,
+        )
+    }
+
     /// Fills the with a specified color.
     #[inline]
     pub fn flood(&self, bounds: IRect, color: Color) -> Result<SharedImageSurface, cairo::Error> {
@@ -1243,6 +1443,103 @@ impl<'a> Iterator for RowsMut<'a> {
     }
 }
 
+/// Computes a gaussian kernel line for the given standard deviation.
+fn gaussian_kernel(std_deviation: f64) -> Vec<f64> {
+    assert!(std_deviation > 0.0);
+
+    // Make sure there aren't any infinities.
+    let maximal_deviation = (MAXIMUM_KERNEL_SIZE / 2) as f64 / 3.0;
+
+    // Values further away than std_deviation * 3 are too small to contribute anything meaningful.
+    let radius = ((std_deviation.min(maximal_deviation) * 3.0) + 0.5) as usize;
+    // Clamp the radius rather than diameter because `MAXIMUM_KERNEL_SIZE` might be even and we
+    // want an odd-sized kernel.
+    let radius = min(radius, (MAXIMUM_KERNEL_SIZE - 1) / 2);
+    let diameter = radius * 2 + 1;
+
+    let mut kernel = Vec::with_capacity(diameter);
+
+    let gauss_point = |x: f64| (-x.powi(2) / (2.0 * std_deviation.powi(2))).exp();
+
+    // Fill the matrix by doing numerical integration approximation from -2*std_dev to 2*std_dev,
+    // sampling 50 points per pixel. We do the bottom half, mirror it to the top half, then compute
+    // the center point. Otherwise asymmetric quantization errors will occur. The formula to
+    // integrate is e^-(x^2/2s^2).
+    for i in 0..diameter / 2 {
+        let base_x = (diameter / 2 + 1 - i) as f64 - 0.5;
+
+        let mut sum = 0.0;
+        for j in 1..=50 {
+            let r = base_x + 0.02 * f64::from(j);
+            sum += gauss_point(r);
+        }
+
+        kernel.push(sum / 50.0);
+    }
+
+    // We'll compute the middle point later.
+    kernel.push(0.0);
+
+    // Mirror the bottom half to the top half.
+    for i in 0..diameter / 2 {
+        let x = kernel[diameter / 2 - 1 - i];
+        kernel.push(x);
+    }
+
+    // Find center val -- calculate an odd number of quanta to make it symmetric, even if the
+    // center point is weighted slightly higher than others.
+    let mut sum = 0.0;
+    for j in 0..=50 {
+        let r = -0.5 + 0.02 * f64::from(j);
+        sum += gauss_point(r);
+    }
+    kernel[diameter / 2] = sum / 51.0;
+
+    // Normalize the distribution by scaling the total sum to 1.
+    let sum = kernel.iter().sum::<f64>();
+    kernel.iter_mut().for_each(|x| *x /= sum);
+
+    kernel
+}
+
+/// Returns a size of the box blur kernel to approximate the gaussian blur.
+fn box_blur_kernel_size(std_deviation: f64) -> usize {
+    let d = (std_deviation * 3.0 * (2.0 * f64::consts::PI).sqrt() / 4.0 + 0.5).floor();
+    let d = d.min(MAXIMUM_KERNEL_SIZE as f64);
+    d as usize
+}
+
+/// Whether filters compute the rows of their output in parallel; see [`fill_pixels`].
+pub const PARALLEL_FILTERS: bool = cfg!(feature = "parallel-filters");
+
+/// Sets each pixel of an `ARgb32` image within `bounds` to the result of `f(x, y)`.
+///
+/// Rows are independent of each other, so if `parallel` is true they are computed with rayon,
+/// each one writing into its own slice of `data`.  Since `f` computes each pixel in the same way
+/// regardless of the order, the output is bit-identical to the serial case.
+pub fn fill_pixels<F>(data: &mut [u8], stride: usize, bounds: IRect, parallel: bool, f: F)
+where
+    F: Fn(u32, u32) -> Pixel + Sync,
+{
+    let rows = &mut data[bounds.y0 as usize * stride..bounds.y1 as usize * stride];
+
+    let fill_row = |(row, y): (&mut [u8], u32)| {
+        for x in bounds.x0 as u32..bounds.x1 as u32 {
+            row.set_pixel(stride, f(x, y), x, 0);
+        }
+    };
+
+    if parallel {
+        rows.par_chunks_mut(stride)
+            .zip(bounds.y0 as u32..bounds.y1 as u32)
+            .for_each(fill_row);
+    } else {
+        rows.chunks_mut(stride)
+            .zip(bounds.y0 as u32..bounds.y1 as u32)
+            .for_each(fill_row);
+    }
+}
+
 /// Performs the arithmetic composite operation. Public for benchmarking.
 #[inline]
 pub fn composite_arithmetic(
@@ -1446,4 +1743,189 @@ mod tests {
             }
         }
     }
+
+    fn white_pixel_surface(size: i32) -> SharedImageSurface {
+        let mut surface = ExclusiveImageSurface::new(size, size, SurfaceType::SRgb).unwrap();
+
+        let white = Pixel {
+            r: 255,
+            g: 255,
+            b: 255,
+            a: 255,
+        };
+
+        let center = (size / 2) as u32;
+        surface.modify(&mut |data, stride| data.set_pixel(stride, white, center, center));
+
+        surface.share().unwrap()
+    }
+
+    #[test]
+    fn gaussian_blur_is_symmetric() {
+        const SIZE: i32 = 41;
+        let center = SIZE / 2;
+
+        // 1.0 uses the exact kernel; 3.0 uses the box blur approximation.
+        for std_deviation in [1.0, 3.0] {
+            let surface = white_pixel_surface(SIZE);
+            let blurred = surface
+                .gaussian_blur(std_deviation, std_deviation, EdgeMode::None)
+                .unwrap();
+
+            for dy in 0..=center {
+                for dx in 0..=center {
+                    let a = blurred
+                        .get_pixel((center + dx) as u32, (center + dy) as u32)
+                        .a;
+
+                    for (x, y) in [(center - dx, center + dy), (center + dx, center - dy)] {
+                        let b = blurred.get_pixel(x as u32, y as u32).a;
+                        assert!((i32::from(a) - i32::from(b)).abs() <= 1);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn gaussian_blur_preserves_energy() {
+        const SIZE: i32 = 41;
+
+        let surface = white_pixel_surface(SIZE);
+        let blurred = surface.gaussian_blur(1.0, 1.0, EdgeMode::None).unwrap();
+
+        let bounds = IRect::from_size(SIZE, SIZE);
+        let total: u32 = Pixels::within(&blurred, bounds)
+            .map(|(_, _, p)| u32::from(p.a))
+            .sum();
+
+        // Allow for rounding of each blurred pixel to 8 bits.
+        assert!((f64::from(total) - 255.0).abs() < 255.0 * 0.05);
+
+        // The center gets spread out.
+        let center = (SIZE / 2) as u32;
+        assert!(blurred.get_pixel(center, center).a < 255);
+        assert!(blurred.get_pixel(center + 1, center).a > 0);
+    }
+
+    /// A surface whose top half is opaque white, so that it touches the top edge.
+    fn top_half_surface(width: i32, height: i32) -> SharedImageSurface {
+        let mut surface = ExclusiveImageSurface::new(width, height, SurfaceType::SRgb).unwrap();
+
+        let white = Pixel {
+            r: 255,
+            g: 255,
+            b: 255,
+            a: 255,
+        };
+
+        surface.modify(&mut |data, stride| {
+            for y in 0..(height / 2) as u32 {
+                for x in 0..width as u32 {
+                    data.set_pixel(stride, white, x, y);
+                }
+            }
+        });
+
+        surface.share().unwrap()
+    }
+
+    #[test]
+    fn gaussian_blur_honors_edge_mode_at_the_top_edge() {
+        const WIDTH: i32 = 4;
+        const HEIGHT: i32 = 40;
+        let bottom = (HEIGHT - 1) as u32;
+
+        // 1.0 uses the exact kernel; 3.0 uses the box blur approximation.
+        for std_deviation in [1.0, 3.0] {
+            let surface = top_half_surface(WIDTH, HEIGHT);
+
+            let none = surface
+                .gaussian_blur(0.0, std_deviation, EdgeMode::None)
+                .unwrap();
+            let duplicate = surface
+                .gaussian_blur(0.0, std_deviation, EdgeMode::Duplicate)
+                .unwrap();
+            let wrap = surface
+                .gaussian_blur(0.0, std_deviation, EdgeMode::Wrap)
+                .unwrap();
+
+            for x in 0..WIDTH as u32 {
+                // Transparent pixels above the edge get blended into the top row.
+                let a = none.get_pixel(x, 0).a;
+                assert!(a > 64 && a < 240);
+                assert_eq!(none.get_pixel(x, bottom).a, 0);
+
+                // The top row gets duplicated upwards, so it stays opaque.
+                assert!(duplicate.get_pixel(x, 0).a >= 254);
+                assert_eq!(duplicate.get_pixel(x, bottom).a, 0);
+
+                // The transparent bottom half wraps around to the top, and vice versa.
+                assert!(wrap.get_pixel(x, 0).a < 240);
+                assert!(wrap.get_pixel(x, bottom).a > 0);
+            }
+        }
+    }
+
+    #[test]
+    fn parallel_convolution_is_identical_to_serial() {
+        const WIDTH: i32 = 37;
+        const HEIGHT: i32 = 23;
+
+        let mut surface = ExclusiveImageSurface::new(WIDTH, HEIGHT, SurfaceType::SRgb).unwrap();
+
+        // Fill the surface with arbitrary premultiplied pixels.
+        surface.modify(&mut |data, stride| {
+            for y in 0..HEIGHT as u32 {
+                for x in 0..WIDTH as u32 {
+                    let a = (x * 7 + y * 13) % 256;
+                    let channel = |k: u32| ((x * k + y * (k + 6)) % (a + 1)) as u8;
+
+                    let pixel = Pixel {
+                        r: channel(31),
+                        g: channel(11),
+                        b: channel(3),
+                        a: a as u8,
+                    };
+                    data.set_pixel(stride, pixel, x, y);
+                }
+            }
+        });
+
+        let surface = surface.share().unwrap();
+        let alpha = surface
+            .extract_alpha(IRect::from_size(WIDTH, HEIGHT))
+            .unwrap();
+
+        let kernel = DMatrix::from_iterator(3, 5, (1..=15).map(|i| f64::from(i) / 120.0 - 0.05));
+        let bounds = IRect::new(3, 2, WIDTH - 4, HEIGHT - 1);
+
+        for input in [&surface, &alpha] {
+            for edge_mode in [EdgeMode::None, EdgeMode::Duplicate, EdgeMode::Wrap] {
+                let serial = input
+                    .convolve_rows(bounds, (2, 1), &kernel, edge_mode, false)
+                    .unwrap();
+                let parallel = input
+                    .convolve_rows(bounds, (2, 1), &kernel, edge_mode, true)
+                    .unwrap();
+
+                for (x, y, p) in Pixels::within(&serial, IRect::from_size(WIDTH, HEIGHT)) {
+                    assert_eq!(parallel.get_pixel(x, y), p);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn gaussian_blur_with_zero_deviation_is_a_copy() {
+        const SIZE: i32 = 9;
+
+        let surface = white_pixel_surface(SIZE);
+        let blurred = surface.gaussian_blur(0.0, 0.0, EdgeMode::None).unwrap();
+
+        let bounds = IRect::from_size(SIZE, SIZE);
+        for (x, y, p) in Pixels::within(&surface, bounds) {
+            assert_eq!(blurred.get_pixel(x, y), p);
+        }
+    }
 }
@@ -1,21 +1,31 @@
-//! Text elements: `text`, `tspan`, `tref`.
+//! Text elements: `text`, `tspan`, `tref`, `textPath`.
 
+use glib::prelude::*;
 use markup5ever::{expanded_name, local_name, namespace_url, ns, QualName};
+use pango::prelude::FontExt;
 use pango::IsAttribute;
+use pangocairo::prelude::PangoCairoFontExt;
 use std::cell::RefCell;
 use std::convert::TryFrom;
+use std::fmt;
+use std::path::Path;
 use std::rc::Rc;
 
+use crate::angle::Angle;
 use crate::bbox::BoundingBox;
 use crate::document::{AcquiredNodes, NodeId};
 use crate::drawing_ctx::{create_pango_context, DrawingCtx, FontOptions, Viewport};
 use crate::element::{set_attribute, ElementData, ElementTrait};
 use crate::error::*;
-use crate::layout::{self, FontProperties, Layer, LayerKind, StackingContext, Stroke, TextSpan};
+use crate::href::{is_href, set_href};
+use crate::layout::{
+    self, FontProperties, Layer, LayerKind, StackingContext, Stroke, TextGlyphs, TextSpan,
+};
 use crate::length::*;
 use crate::node::{CascadedValues, Node, NodeBorrow};
 use crate::paint_server::PaintSource;
 use crate::parsers::{CommaSeparatedList, Parse, ParseValue};
+use crate::path_builder::Path as SvgPath;
 use crate::properties::{
     ComputedValues, Direction, FontStretch, FontStyle, FontVariant, FontWeight, PaintOrder,
     TextAnchor, TextRendering, UnicodeBidi, WritingMode, XmlLang, XmlSpace,
@@ -23,6 +33,7 @@ use crate::properties::{
 use crate::rect::Rect;
 use crate::rsvg_log;
 use crate::session::Session;
+use crate::shapes::basic_shape_path;
 use crate::space::{xml_space_normalize, NormalizeDefault, XmlSpaceNormalize};
 use crate::transform::{Transform, ValidTransform};
 use crate::xml::Attributes;
@@ -43,6 +54,231 @@ struct LayoutContext {
 
     /// Session metadata for the document
     session: Session,
+
+    /// Where to report fonts that could not be used.
+    font_warnings: FontWarnings,
+}
+
+/// A font that could not be used while laying out text.
+///
+/// When a font file is corrupt, fontconfig may still pick it, but cairo cannot create
+/// a usable font from it.  In that case librsvg lays out the text again with the next
+/// family in the `font-family` list, or with `sans-serif` as a last resort.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FontWarning {
+    /// Family name of the font that could not be used.
+    pub family: String,
+}
+
+impl fmt::Display for FontWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "font \"{}\" could not be loaded; a fallback font was used",
+            self.family
+        )
+    }
+}
+
+/// Shared list of the [`FontWarning`]s that happened during a rendering operation.
+pub type FontWarnings = Rc<RefCell<Vec<FontWarning>>>;
+
+/// Font map for a rendering, created from a [`FontConfig`].
+pub type FontMap = pango::FontMap;
+
+/// A set of fonts to use for text, for [`CairoRenderer::with_fonts`].
+///
+/// Normally librsvg uses the fonts that are installed in the system.  A `FontConfig`
+/// lets an application use font files that it bundles, without installing them or
+/// changing the process-wide font configuration.  It only affects the renderings that
+/// use it.
+///
+/// This is implemented with fontconfig, so it only works on platforms where Pango uses
+/// fontconfig.  On Windows and macOS, which have their own font systems and where
+/// `rsvg-convert` does not use fontconfig either, adding fonts returns an error, and
+/// text is rendered with the system's fonts.
+///
+/// # Example
+///
+/// ```no_run
+/// # use rsvg::{CairoRenderer, FontConfig, Loader};
+/// let svg_handle = Loader::new()
+///     .read_path("example.svg")
+///     .unwrap();
+///
+/// let mut fonts = FontConfig::new();
+/// fonts.add_font_file("fonts/MyBrand-Regular.ttf").unwrap();
+///
+/// let renderer = CairoRenderer::new(&svg_handle).with_fonts(fonts);
+/// ```
+///
+/// [`CairoRenderer::with_fonts`]: crate::CairoRenderer::with_fonts
+pub struct FontConfig {
+    #[cfg(all(not(target_os = "macos"), not(target_os = "windows")))]
+    config: fc::Config,
+}
+
+impl FontConfig {
+    /// Creates a font configuration with the system's fonts.
+    ///
+    /// This loads the system's fontconfig configuration, so fonts added later are used
+    /// in addition to the installed ones.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> FontConfig {
+        FontConfig {
+            #[cfg(all(not(target_os = "macos"), not(target_os = "windows")))]
+            config: fc::Config::with_system_fonts(),
+        }
+    }
+
+    /// Creates a font configuration without any fonts.
+    ///
+    /// Only the fonts added later are available, which is useful for reproducible
+    /// renderings that should not depend on the fonts installed in the system.
+    pub fn empty() -> FontConfig {
+        FontConfig {
+            #[cfg(all(not(target_os = "macos"), not(target_os = "windows")))]
+            config: fc::Config::empty(),
+        }
+    }
+
+    /// Uses an existing fontconfig configuration.
+    ///
+    /// # Safety
+    ///
+    /// `config` must be a valid pointer to an `FcConfig`.  This takes a new reference to
+    /// it, so the caller keeps its own.
+    #[cfg(all(not(target_os = "macos"), not(target_os = "windows")))]
+    pub unsafe fn from_raw(config: *mut std::ffi::c_void) -> FontConfig {
+        FontConfig {
+            config: fc::Config::from_raw(config.cast()),
+        }
+    }
+
+    /// Adds a font file, like a `.ttf` or `.otf`, to the configuration.
+    pub fn add_font_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), LoadingError> {
+        let path = path.as_ref();
+
+        #[cfg(all(not(target_os = "macos"), not(target_os = "windows")))]
+        if self.config.add_font_file(path) {
+            return Ok(());
+        }
+
+        Err(font_loading_error(path))
+    }
+
+    /// Adds all the font files in a directory, and its subdirectories, to the configuration.
+    pub fn add_font_dir<P: AsRef<Path>>(&mut self, path: P) -> Result<(), LoadingError> {
+        let path = path.as_ref();
+
+        #[cfg(all(not(target_os = "macos"), not(target_os = "windows")))]
+        if self.config.add_font_dir(path) {
+            return Ok(());
+        }
+
+        Err(font_loading_error(path))
+    }
+
+    /// Creates a Pango font map that uses this configuration.
+    #[cfg(all(not(target_os = "macos"), not(target_os = "windows")))]
+    pub(crate) fn create_font_map(&self) -> Option<FontMap> {
+        self.config.create_font_map()
+    }
+
+    /// Returns `None`, since fonts can only be configured with fontconfig.
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    pub(crate) fn create_font_map(&self) -> Option<FontMap> {
+        None
+    }
+}
+
+fn font_loading_error(path: &Path) -> LoadingError {
+    if cfg!(any(target_os = "macos", target_os = "windows")) {
+        LoadingError::Other(String::from(
+            "fonts can only be added on platforms that use fontconfig",
+        ))
+    } else {
+        LoadingError::Io(format!("could not load fonts from {}", path.display()))
+    }
+}
+
+/// Minimal bindings for the parts of fontconfig that [`FontConfig`] needs.
+#[cfg(all(not(target_os = "macos"), not(target_os = "windows")))]
+mod fc {
+    use fontconfig_sys as sys;
+    use glib::translate::*;
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    extern "C" {
+        // This is not bound in gtk-rs, and PangoFcFontMap is not even exposed, so we bind
+        // it by hand, like test_utils does.
+        fn pango_fc_font_map_set_config(font_map: *mut libc::c_void, config: *mut sys::FcConfig);
+    }
+
+    /// An owned reference to an `FcConfig`.
+    pub struct Config(*mut sys::FcConfig);
+
+    impl Config {
+        pub fn with_system_fonts() -> Config {
+            let config = unsafe { sys::FcInitLoadConfigAndFonts() };
+            assert!(
+                !config.is_null(),
+                "could not load the fontconfig configuration"
+            );
+            Config(config)
+        }
+
+        pub fn empty() -> Config {
+            let config = unsafe { sys::FcConfigCreate() };
+            assert!(
+                !config.is_null(),
+                "could not create a fontconfig configuration"
+            );
+            Config(config)
+        }
+
+        pub unsafe fn from_raw(config: *mut sys::FcConfig) -> Config {
+            Config(sys::FcConfigReference(config))
+        }
+
+        pub fn add_font_file(&self, path: &Path) -> bool {
+            path_to_cstring(path)
+                .map(|p| unsafe { sys::FcConfigAppFontAddFile(self.0, p.as_ptr().cast()) != 0 })
+                .unwrap_or(false)
+        }
+
+        pub fn add_font_dir(&self, path: &Path) -> bool {
+            path_to_cstring(path)
+                .map(|p| unsafe { sys::FcConfigAppFontAddDir(self.0, p.as_ptr().cast()) != 0 })
+                .unwrap_or(false)
+        }
+
+        pub fn create_font_map(&self) -> Option<pango::FontMap> {
+            let font_map = pangocairo::FontMap::for_font_type(cairo::FontType::FontTypeFt)?;
+            let raw_font_map: *mut pango::ffi::PangoFontMap = font_map.to_glib_none().0;
+
+            // The font map takes its own reference to the config.
+            unsafe {
+                pango_fc_font_map_set_config(raw_font_map.cast(), self.0);
+            }
+
+            Some(font_map)
+        }
+    }
+
+    impl Drop for Config {
+        fn drop(&mut self) {
+            unsafe {
+                sys::FcConfigDestroy(self.0);
+            }
+        }
+    }
+
+    fn path_to_cstring(path: &Path) -> Option<CString> {
+        CString::new(path.as_os_str().as_bytes()).ok()
+    }
 }
 
 /// An absolutely-positioned array of `Span`s
@@ -57,18 +293,32 @@ struct LayoutContext {
 /// Subsequent children of the `<text>` element will create new chunks
 /// whenever they have `x` or `y` attributes.
 ///
+/// The contents of a `<textPath>` element also form a chunk of their own, which gets
+/// laid out along the path instead of at an absolute position.
+///
 /// [text chunk]: https://www.w3.org/TR/SVG11/text.html#TextLayoutIntroduction
 struct Chunk {
     values: Rc<ComputedValues>,
     x: Option<f64>,
     y: Option<f64>,
+    on_path: Option<PathPlacement>,
     spans: Vec<Span>,
 }
 
+/// Where to start laying out the text of a `<textPath>`.
+#[derive(Clone)]
+struct PathPlacement {
+    path: Rc<PathGeometry>,
+
+    /// Distance along the path where the text starts, in user-space units.
+    start_offset: f64,
+}
+
 struct MeasuredChunk {
     values: Rc<ComputedValues>,
     x: Option<f64>,
     y: Option<f64>,
+    on_path: Option<PathPlacement>,
     dx: f64,
     dy: f64,
     spans: Vec<MeasuredSpan>,
@@ -100,9 +350,11 @@ struct MeasuredSpan {
 }
 
 struct PositionedSpan {
-    layout: pango::Layout,
+    glyphs: TextGlyphs,
+    gravity: pango::Gravity,
     values: Rc<ComputedValues>,
     rendered_position: (f64, f64),
+    rotation: f64,
     next_span_position: (f64, f64),
     link_target: Option<String>,
 }
@@ -113,12 +365,13 @@ struct PositionedSpan {
 ///
 /// This is the non-user-space version of `layout::TextSpan`.
 struct LayoutSpan {
-    layout: pango::Layout,
+    glyphs: TextGlyphs,
     gravity: pango::Gravity,
     bbox: Option<BoundingBox>,
     is_visible: bool,
     x: f64,
     y: f64,
+    rotation: f64,
     paint_order: PaintOrder,
     stroke: Stroke,
     stroke_paint: Rc<PaintSource>,
@@ -134,6 +387,17 @@ impl Chunk {
             values: Rc::new(values.clone()),
             x,
             y,
+            on_path: None,
+            spans: Vec::new(),
+        }
+    }
+
+    fn new_on_path(values: &ComputedValues, on_path: PathPlacement) -> Chunk {
+        Chunk {
+            values: Rc::new(values.clone()),
+            x: None,
+            y: None,
+            on_path: Some(on_path),
             spans: Vec::new(),
         }
     }
@@ -165,6 +429,7 @@ impl MeasuredChunk {
             values: chunk.values.clone(),
             x: chunk.x,
             y: chunk.y,
+            on_path: chunk.on_path.clone(),
             dx: chunk_dx,
             dy: chunk_dy,
             spans: measured_spans,
@@ -179,6 +444,10 @@ impl PositionedChunk {
         chunk_x: f64,
         chunk_y: f64,
     ) -> PositionedChunk {
+        if let Some(ref on_path) = measured.on_path {
+            return PositionedChunk::from_measured_on_path(layout_context, measured, on_path);
+        }
+
         let chunk_direction = measured.values.direction();
 
         // Position the spans relatively to each other, starting at (0, 0)
@@ -238,9 +507,11 @@ impl PositionedChunk {
             y = y + span_advance.1 + dy;
 
             let positioned_span = PositionedSpan {
-                layout,
+                gravity: layout.context().gravity(),
+                glyphs: TextGlyphs::Layout(layout),
                 values,
                 rendered_position,
+                rotation: 0.0,
                 next_span_position: (x, y),
                 link_target: mspan.link_target.clone(),
             };
@@ -280,6 +551,248 @@ impl PositionedChunk {
             spans: positioned,
         }
     }
+
+    /// Positions the contents of a `<textPath>` along its path.
+    ///
+    /// Each span is shaped only once, as a normal layout.  Then each cluster of its glyphs
+    /// is centered on the point of the path that corresponds to the cluster's midpoint,
+    /// and rotated to follow the direction of the path at that point.  Clusters whose
+    /// midpoint falls off either end of the path are not rendered.
+    ///
+    /// The next chunk starts at the point of the path where the text ends.
+    fn from_measured_on_path(
+        layout_context: &LayoutContext,
+        measured: &MeasuredChunk,
+        on_path: &PathPlacement,
+    ) -> PositionedChunk {
+        let path = &on_path.path;
+
+        let total_advance: f64 = measured.spans.iter().map(|s| s.advance.0 + s.dx).sum();
+
+        let anchor_offset = match measured.values.text_anchor() {
+            TextAnchor::Start => 0.0,
+            TextAnchor::Middle => -total_advance / 2.0,
+            TextAnchor::End => -total_advance,
+        };
+
+        // Distance along the path of the start of the current span.
+        let mut offset = on_path.start_offset + anchor_offset + measured.dx;
+
+        // Offset perpendicular to the path.
+        let mut dy = measured.dy;
+
+        let mut positioned = Vec::new();
+
+        for mspan in &measured.spans {
+            let params = NormalizeParams::new(&mspan.values, &layout_context.viewport);
+            let baseline_shift = mspan.values.baseline_shift().0.to_user(&params);
+            let gravity = mspan.layout.context().gravity();
+
+            offset += mspan.dx;
+            dy += mspan.dy;
+
+            for cluster in layout_clusters(&mspan.layout) {
+                let half_width = cluster.width / 2.0;
+                let midpoint = offset + cluster.x + half_width;
+
+                let (px, py, direction) = match path.point_at(midpoint) {
+                    Some(point) => point,
+                    None => continue,
+                };
+
+                // The cluster gets rotated to be parallel to the line between the points
+                // on the path at its start and end.  Near the ends of the path, use the
+                // direction of the path at the midpoint instead.
+                let angle = match (
+                    path.point_at(midpoint - half_width),
+                    path.point_at(midpoint + half_width),
+                ) {
+                    (Some((x0, y0, _)), Some((x1, y1, _))) if half_width > 0.0 => {
+                        (y1 - y0).atan2(x1 - x0)
+                    }
+                    _ => direction,
+                };
+
+                // Move from the point on the path to the start of the cluster's baseline,
+                // in the rotated coordinate system of the glyphs.
+                let (sin, cos) = angle.sin_cos();
+                let (ox, oy) = (-half_width, dy - baseline_shift);
+                let rendered_position = (px + ox * cos - oy * sin, py + ox * sin + oy * cos);
+
+                positioned.push(PositionedSpan {
+                    glyphs: TextGlyphs::Cluster {
+                        font: cluster.font,
+                        glyphs: cluster.glyphs,
+                    },
+                    gravity,
+                    values: mspan.values.clone(),
+                    rendered_position,
+                    rotation: angle,
+                    next_span_position: rendered_position,
+                    link_target: mspan.link_target.clone(),
+                });
+            }
+
+            offset += mspan.advance.0;
+        }
+
+        let (next_chunk_x, next_chunk_y) = path
+            .point_at(offset.clamp(0.0, path.length()))
+            .map(|(x, y, _)| (x, y))
+            .unwrap_or_default();
+
+        PositionedChunk {
+            next_chunk_x,
+            next_chunk_y,
+            spans: positioned,
+        }
+    }
+}
+
+/// A cluster of glyphs from a Pango layout, which must be rendered as a unit.
+struct Cluster {
+    /// Horizontal position within the layout.
+    x: f64,
+    width: f64,
+    font: pango::Font,
+    glyphs: pango::GlyphString,
+}
+
+/// Splits a layout's shaped glyphs into their clusters, in visual order.
+fn layout_clusters(layout: &pango::Layout) -> Vec<Cluster> {
+    let pango_scale = f64::from(pango::SCALE);
+
+    let mut clusters = Vec::new();
+    let mut iter = layout.iter();
+    loop {
+        // There is no run at the end of a line.
+        if let Some(run) = iter.run_readonly() {
+            let (_, logical) = iter.cluster_extents();
+            let item = run.item();
+            let run_glyphs = run.glyph_string();
+
+            // The glyphs' log_clusters are byte indices relative to the start of the run.
+            let index = iter.index() - item.offset();
+            let infos: Vec<pango::GlyphInfo> = run_glyphs
+                .glyph_info()
+                .iter()
+                .zip(run_glyphs.log_clusters())
+                .filter(|&(_, &cluster)| cluster == index)
+                .map(|(info, _)| info.clone())
+                .collect();
+
+            if let Ok(num_glyphs) = cast::i32(infos.len()) {
+                if num_glyphs > 0 {
+                    let mut glyphs = pango::GlyphString::new();
+                    glyphs.set_size(num_glyphs);
+                    glyphs.glyph_info_mut().clone_from_slice(&infos);
+                    glyphs.log_clusters_mut().fill(0);
+
+                    clusters.push(Cluster {
+                        x: f64::from(logical.x()) / pango_scale,
+                        width: f64::from(logical.width()) / pango_scale,
+                        font: item.analysis().font(),
+                        glyphs,
+                    });
+                }
+            }
+        }
+
+        if !iter.next_cluster() {
+            break;
+        }
+    }
+
+    clusters
+}
+
+/// A path flattened to line segments, for finding points at a given distance along it.
+///
+/// This is what `<textPath>` uses to place glyphs.
+struct PathGeometry {
+    /// Line segments as `(x0, y0, x1, y1)`, in the user space of the `<text>` element.
+    segments: Vec<(f64, f64, f64, f64)>,
+
+    /// Distance along the path at the end of each segment.
+    distances: Vec<f64>,
+}
+
+impl PathGeometry {
+    fn new(path: &SvgPath, transform: Transform) -> Result<PathGeometry, InternalRenderingError> {
+        let surface = cairo::RecordingSurface::create(cairo::Content::ColorAlpha, None)?;
+        let cr = cairo::Context::new(&surface)?;
+
+        // Glyphs get placed on points of the flattened path, so use a smaller tolerance
+        // than Cairo's default of 0.1 to follow curves closely.
+        cr.set_tolerance(0.001);
+
+        path.to_cairo(&cr, false)?;
+        let flat = cr.copy_path_flat()?;
+
+        let mut segments = Vec::new();
+        let mut distances = Vec::new();
+
+        let mut length = 0.0;
+        let mut current = (0.0, 0.0);
+        let mut subpath_start = (0.0, 0.0);
+
+        for segment in flat.iter() {
+            let next = match segment {
+                cairo::PathSegment::MoveTo((x, y)) => {
+                    current = transform.transform_point(x, y);
+                    subpath_start = current;
+                    continue;
+                }
+
+                cairo::PathSegment::LineTo((x, y)) => transform.transform_point(x, y),
+
+                // Flattened paths should not have curves, but just in case.
+                cairo::PathSegment::CurveTo(_, _, (x, y)) => transform.transform_point(x, y),
+
+                cairo::PathSegment::ClosePath => subpath_start,
+            };
+
+            let segment_length = (next.0 - current.0).hypot(next.1 - current.1);
+            if segment_length > 0.0 {
+                length += segment_length;
+                segments.push((current.0, current.1, next.0, next.1));
+                distances.push(length);
+            }
+
+            current = next;
+        }
+
+        Ok(PathGeometry {
+            segments,
+            distances,
+        })
+    }
+
+    fn length(&self) -> f64 {
+        self.distances.last().copied().unwrap_or(0.0)
+    }
+
+    /// Returns the point at `distance` along the path, and the direction of the path at
+    /// that point in radians.
+    ///
+    /// Returns `None` if the distance falls outside of the path.
+    fn point_at(&self, distance: f64) -> Option<(f64, f64, f64)> {
+        if self.segments.is_empty() || !(0.0..=self.length()).contains(&distance) {
+            return None;
+        }
+
+        let i = self.distances.partition_point(|&d| d < distance);
+        let (x0, y0, x1, y1) = self.segments[i];
+
+        let segment_length = (x1 - x0).hypot(y1 - y0);
+        let t = 1.0 - (self.distances[i] - distance) / segment_length;
+
+        Some((
+            x0 + (x1 - x0) * t,
+            y0 + (y1 - y0) * t,
+            (y1 - y0).atan2(x1 - x0),
+        ))
+    }
 }
 
 fn compute_baseline_offset(
@@ -346,6 +859,7 @@ impl Span {
 }
 
 /// Use as `PangoUnits::from_pixels()` so that we can check for overflow.
+#[derive(Copy, Clone)]
 struct PangoUnits(i32);
 
 impl PangoUnits {
@@ -409,7 +923,7 @@ fn gravity_is_vertical(gravity: pango::Gravity) -> bool {
 }
 
 fn compute_text_box(
-    layout: &pango::Layout,
+    glyphs: &TextGlyphs,
     x: f64,
     y: f64,
     transform: Transform,
@@ -417,7 +931,13 @@ fn compute_text_box(
 ) -> Option<BoundingBox> {
     #![allow(clippy::many_single_char_names)]
 
-    let (ink, _) = layout.extents();
+    let ink = match *glyphs {
+        TextGlyphs::Layout(ref layout) => layout.extents().0,
+        TextGlyphs::Cluster {
+            ref font,
+            ref glyphs,
+        } => glyphs.clone().extents(font).0,
+    };
     if ink.width() == 0 || ink.height() == 0 {
         return None;
     }
@@ -461,15 +981,23 @@ impl PositionedSpan {
     ) -> LayoutSpan {
         let params = NormalizeParams::new(&self.values, &layout_context.viewport);
 
-        let layout = self.layout.clone();
+        let glyphs = self.glyphs.clone();
         let is_visible = self.values.is_visible();
         let (x, y) = self.rendered_position;
 
         let stroke = Stroke::new(&self.values, &params);
 
-        let gravity = layout.context().gravity();
+        let gravity = self.gravity;
 
-        let bbox = compute_text_box(&layout, x, y, *layout_context.transform, gravity);
+        let bbox = if self.rotation == 0.0 {
+            compute_text_box(&glyphs, x, y, *layout_context.transform, gravity)
+        } else {
+            let transform = layout_context
+                .transform
+                .pre_translate(x, y)
+                .pre_rotate(Angle::new(self.rotation));
+            compute_text_box(&glyphs, 0.0, 0.0, transform, gravity)
+        };
 
         let stroke_paint = self.values.stroke().0.resolve(
             acquired_nodes,
@@ -493,12 +1021,13 @@ impl PositionedSpan {
         let text_rendering = self.values.text_rendering();
 
         LayoutSpan {
-            layout,
+            glyphs,
             gravity,
             bbox,
             is_visible,
             x,
             y,
+            rotation: self.rotation,
             paint_order,
             stroke,
             stroke_paint,
@@ -510,7 +1039,7 @@ impl PositionedSpan {
     }
 }
 
-/// Walks the children of a `<text>`, `<tspan>`, or `<tref>` element
+/// Walks the children of a `<text>`, `<tspan>`, `<tref>`, or `<textPath>` element
 /// and appends chunks/spans from them into the specified `chunks`
 /// array.
 fn children_to_chunks(
@@ -582,6 +1111,24 @@ fn children_to_chunks(
                     );
                 }
 
+                ElementData::TextPath(ref text_path) => {
+                    let child_cascaded = CascadedValues::clone_with_node(cascaded, &child);
+                    text_path.to_chunks(
+                        &child,
+                        acquired_nodes,
+                        &child_cascaded,
+                        layout_context,
+                        chunks,
+                        dx,
+                        dy,
+                        depth + 1,
+                        link.clone(),
+                    );
+
+                    // Text after the `<textPath>` continues where the text on the path ended.
+                    chunks.push(Chunk::new(cascaded.get(), None, None));
+                }
+
                 ElementData::TRef(ref tref) => {
                     let cascaded = CascadedValues::clone_with_node(cascaded, &child);
                     tref.to_chunks(
@@ -830,6 +1377,7 @@ impl ElementTrait for Text {
                 font_options: draw_ctx.get_font_options(),
                 viewport: viewport.clone(),
                 session: session.clone(),
+                font_warnings: draw_ctx.font_warnings(),
             };
 
             let mut x = self.x.to_user(&params);
@@ -889,12 +1437,13 @@ impl ElementTrait for Text {
                 );
 
                 let text_span = TextSpan {
-                    layout: span.layout,
+                    glyphs: span.glyphs,
                     gravity: span.gravity,
                     bbox: span.bbox,
                     is_visible: span.is_visible,
                     x: span.x,
                     y: span.y,
+                    rotation: span.rotation,
                     paint_order: span.paint_order,
                     stroke: span.stroke,
                     stroke_paint,
@@ -1076,6 +1625,112 @@ impl ElementTrait for TSpan {
     }
 }
 
+#[derive(Default)]
+pub struct TextPath {
+    link: Option<NodeId>,
+    start_offset: Length<Horizontal>,
+}
+
+impl TextPath {
+    fn to_chunks(
+        &self,
+        node: &Node,
+        acquired_nodes: &mut AcquiredNodes<'_>,
+        cascaded: &CascadedValues<'_>,
+        layout_context: &LayoutContext,
+        chunks: &mut Vec<Chunk>,
+        dx: f64,
+        dy: f64,
+        depth: usize,
+        link: Option<String>,
+    ) {
+        let values = cascaded.get();
+        if !values.is_displayed() {
+            return;
+        }
+
+        let path = match self.path_geometry(acquired_nodes, &layout_context.viewport) {
+            Some(path) => path,
+            None => {
+                rsvg_log!(
+                    layout_context.session,
+                    "element {} does not reference a path or basic shape; not rendering it",
+                    node,
+                );
+                return;
+            }
+        };
+
+        // A percentage in startOffset refers to the length of the path.
+        let start_offset = match self.start_offset.unit {
+            LengthUnit::Percent => self.start_offset.length * path.length(),
+            _ => {
+                let params = NormalizeParams::new(values, &layout_context.viewport);
+                self.start_offset.to_user(&params)
+            }
+        };
+
+        chunks.push(Chunk::new_on_path(
+            values,
+            PathPlacement {
+                path: Rc::new(path),
+                start_offset,
+            },
+        ));
+
+        children_to_chunks(
+            chunks,
+            node,
+            acquired_nodes,
+            cascaded,
+            layout_context,
+            dx,
+            dy,
+            depth,
+            link,
+        );
+    }
+
+    /// Flattens the referenced `<path>` or basic shape, including its own `transform`.
+    fn path_geometry(
+        &self,
+        acquired_nodes: &mut AcquiredNodes<'_>,
+        viewport: &Viewport,
+    ) -> Option<PathGeometry> {
+        let link = self.link.as_ref()?;
+        let acquired = acquired_nodes.acquire(link).ok()?;
+        let shape_node = acquired.get();
+        let element = shape_node.borrow_element();
+
+        let path = basic_shape_path(&element, viewport)?;
+        let transform = element.get_computed_values().transform();
+
+        PathGeometry::new(&path, transform).ok()
+    }
+}
+
+impl ElementTrait for TextPath {
+    fn set_attributes(&mut self, attrs: &Attributes, session: &Session) {
+        for (attr, value) in attrs.iter() {
+            match attr.expanded() {
+                ref a if is_href(a) => {
+                    let mut href = None;
+                    set_attribute(
+                        &mut href,
+                        NodeId::parse(value).map(Some).attribute(attr.clone()),
+                        session,
+                    );
+                    set_href(a, &mut self.link, href);
+                }
+                expanded_name!("", "startOffset") => {
+                    set_attribute(&mut self.start_offset, attr.parse(value), session)
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
 impl From<FontStyle> for pango::Style {
     fn from(s: FontStyle) -> pango::Style {
         match s {
@@ -1291,6 +1946,7 @@ fn create_pango_layout(
 
     let font_size = PangoUnits::from_pixels(props.font_size);
     let letter_spacing = PangoUnits::from_pixels(props.letter_spacing);
+    let word_spacing = PangoUnits::from_pixels(props.letter_spacing + props.word_spacing);
 
     if font_size.is_none() {
         rsvg_log!(
@@ -1308,24 +1964,183 @@ fn create_pango_layout(
         );
     }
 
-    if let (Some(font_size), Some(letter_spacing)) = (font_size, letter_spacing) {
-        let attr_list = pango::AttrList::new();
-        add_pango_attributes(&attr_list, props, 0, text.len(), font_size, letter_spacing);
+    if word_spacing.is_none() {
+        rsvg_log!(
+            &layout_context.session,
+            "word-spacing {} is out of bounds; ignoring span",
+            props.word_spacing
+        );
+    }
 
-        layout.set_attributes(Some(&attr_list));
+    if let (Some(font_size), Some(letter_spacing), Some(word_spacing)) =
+        (font_size, letter_spacing, word_spacing)
+    {
         layout.set_text(text);
         layout.set_auto_dir(false);
 
+        let set_font_family = |font_family: &str| {
+            let attr_list = pango::AttrList::new();
+            add_pango_attributes(
+                &attr_list,
+                props,
+                font_family,
+                0,
+                text.len(),
+                font_size,
+                letter_spacing,
+            );
+
+            if props.word_spacing != 0.0 {
+                add_word_spacing_attributes(&attr_list, text, word_spacing);
+            }
+
+            layout.set_attributes(Some(&attr_list));
+        };
+
+        set_font_family(props.font_family.as_str());
+
+        // Finding out if a font is usable means creating a cairo scaled font for each run,
+        // so only do it if something went wrong.  A font file that cannot be loaded
+        // gets all of its characters shaped as unknown glyphs.
+        if layout.unknown_glyphs_count() == 0 {
+            return Some(layout);
+        }
+
+        let mut families = font_family_names(props.font_family.as_str());
+        let mut tried_generic_family = false;
+
+        while let Some(unusable) = find_unusable_font(&layout) {
+            rsvg_log!(
+                &layout_context.session,
+                "font \"{}\" could not be loaded; trying a fallback font",
+                unusable
+            );
+
+            {
+                let warning = FontWarning {
+                    family: unusable.clone(),
+                };
+
+                let mut warnings = layout_context.font_warnings.borrow_mut();
+                if !warnings.contains(&warning) {
+                    warnings.push(warning);
+                }
+            }
+
+            let num_families = families.len();
+            families.retain(|f| !f.eq_ignore_ascii_case(&unusable));
+
+            if families.len() == num_families && !families.is_empty() {
+                // The unusable font was substituted for a family that is not installed.
+                // Generic families are kept as a last resort, as long as there are
+                // specific ones to drop.
+                let pos = families
+                    .iter()
+                    .position(|f| !is_generic_family(f))
+                    .unwrap_or(0);
+                families.remove(pos);
+            }
+
+            if families.is_empty() {
+                if tried_generic_family {
+                    rsvg_log!(
+                        &layout_context.session,
+                        "no usable font for text \"{}\"; ignoring span",
+                        text
+                    );
+                    return None;
+                }
+
+                tried_generic_family = true;
+                families.push(String::from("sans-serif"));
+            }
+
+            set_font_family(&families.join(","));
+        }
+
         Some(layout)
     } else {
         None
     }
 }
 
+/// Splits a `font-family` value into its family names, without surrounding whitespace
+/// or quotes, to drop the unusable ones when falling back to another font.
+fn font_family_names(font_family: &str) -> Vec<String> {
+    font_family
+        .split(',')
+        .map(|name| {
+            let name = name.trim();
+
+            for quote in ['"', '\''] {
+                if let Some(unquoted) = name.strip_prefix(quote).and_then(|n| n.strip_suffix(quote))
+                {
+                    return unquoted.trim().to_string();
+                }
+            }
+
+            name.to_string()
+        })
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// Whether `family` is one of the CSS generic font families, which fontconfig always
+/// resolves to some installed font.
+fn is_generic_family(family: &str) -> bool {
+    const GENERIC_FAMILIES: &[&str] = &[
+        "serif",
+        "sans-serif",
+        "monospace",
+        "cursive",
+        "fantasy",
+        "system-ui",
+    ];
+
+    GENERIC_FAMILIES
+        .iter()
+        .any(|generic| family.eq_ignore_ascii_case(generic))
+}
+
+/// Returns the family name of a font in the `layout` for which cairo cannot create a scaled font.
+///
+/// Drawing with such a font would put the cairo context in an error state and abort the
+/// whole rendering.
+fn find_unusable_font(layout: &pango::Layout) -> Option<String> {
+    let mut iter = layout.iter();
+
+    loop {
+        if let Some(run) = iter.run_readonly() {
+            let font = run.item().analysis().font();
+
+            let is_usable = match font.dynamic_cast_ref::<pangocairo::Font>() {
+                Some(cairo_font) => cairo_font
+                    .scaled_font()
+                    .map_or(false, |scaled_font| scaled_font.status().is_ok()),
+                None => true,
+            };
+
+            if !is_usable {
+                return Some(
+                    font.describe()
+                        .family()
+                        .map(|family| family.to_string())
+                        .unwrap_or_default(),
+                );
+            }
+        }
+
+        if !iter.next_run() {
+            return None;
+        }
+    }
+}
+
 /// Adds Pango attributes, suitable for a span of text, to an `AttrList`.
 fn add_pango_attributes(
     attr_list: &pango::AttrList,
     props: &FontProperties,
+    font_family: &str,
     start_index: usize,
     end_index: usize,
     font_size: PangoUnits,
@@ -1338,7 +2153,7 @@ fn add_pango_attributes(
     let mut attributes = Vec::new();
 
     let mut font_desc = pango::FontDescription::new();
-    font_desc.set_family(props.font_family.as_str());
+    font_desc.set_family(font_family);
     font_desc.set_style(pango::Style::from(props.font_style));
 
     font_desc.set_variant(pango::Variant::from(props.font_variant));
@@ -1378,6 +2193,38 @@ fn add_pango_attributes(
     }
 }
 
+/// Adds the extra spacing from `word-spacing` to the word separators in `text`.
+///
+/// Pango has no attribute for word spacing, so we give each word separator a
+/// `letter_spacing` that includes the word spacing; Pango distributes letter spacing on
+/// both sides of a character, so this widens the gap between words by the specified
+/// amount.
+fn add_word_spacing_attributes(attr_list: &pango::AttrList, text: &str, spacing: PangoUnits) {
+    // CSS Text 3 lists the word-separator characters.
+    let separators = [
+        '\u{0020}',
+        '\u{00a0}',
+        '\u{1361}',
+        '\u{10100}',
+        '\u{10101}',
+        '\u{1039f}',
+        '\u{1091f}',
+    ];
+
+    for (index, c) in text.char_indices() {
+        if separators.contains(&c) {
+            let start_index = u32::try_from(index).expect("Pango attribute index must fit in u32");
+            let end_index =
+                u32::try_from(index + c.len_utf8()).expect("Pango attribute index must fit in u32");
+
+            let mut attr = pango::AttrInt::new_letter_spacing(spacing.0).upcast();
+            attr.set_start_index(start_index);
+            attr.set_end_index(end_index);
+            attr_list.insert(attr);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1389,6 +2236,18 @@ mod tests {
         assert!(c.space_normalized.borrow().is_none());
     }
 
+    #[test]
+    fn splits_font_family_lists() {
+        assert_eq!(
+            font_family_names(" 'Foo Bar', \"Baz\" ,Qux Sans,  serif "),
+            vec!["Foo Bar", "Baz", "Qux Sans", "serif"]
+        );
+        assert!(font_family_names(" , ").is_empty());
+
+        assert!(is_generic_family("Sans-Serif"));
+        assert!(!is_generic_family("Foo Bar"));
+    }
+
     #[test]
     fn chars_new() {
         let example = "Test 123";
@@ -0,0 +1,183 @@
+//! Stand-ins for the text elements, for builds without the `text` feature.
+//!
+//! Text layout needs Pango, HarfBuzz, and fontconfig, which are a large set of dependencies
+//! for programs that only render icons.  When librsvg is built without the `text` feature,
+//! this module replaces `text.rs`: the `text`, `tspan`, `tref`, and `textPath` elements
+//! still get parsed, but `<text>` elements are skipped during rendering, with a logged
+//! warning.  Everything else renders as usual.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::bbox::BoundingBox;
+use crate::document::AcquiredNodes;
+use crate::drawing_ctx::{DrawingCtx, Viewport};
+use crate::element::ElementTrait;
+use crate::error::*;
+use crate::node::{CascadedValues, Node};
+use crate::rsvg_log;
+
+/// A font that could not be used while laying out text.
+///
+/// Without the `text` feature no text gets laid out, so this never happens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FontWarning {
+    /// Family name of the font that could not be used.
+    pub family: String,
+}
+
+impl fmt::Display for FontWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "font \"{}\" could not be loaded; a fallback font was used",
+            self.family
+        )
+    }
+}
+
+/// Shared list of the [`FontWarning`]s that happened during a rendering operation.
+pub type FontWarnings = Rc<RefCell<Vec<FontWarning>>>;
+
+/// Font map for a rendering; there are none without the `text` feature.
+#[derive(Clone, PartialEq)]
+pub enum FontMap {}
+
+/// A set of fonts to use for text, for [`CairoRenderer::with_fonts`].
+///
+/// Librsvg was built without the `text` feature, so text is not rendered and fonts
+/// cannot be added.
+///
+/// [`CairoRenderer::with_fonts`]: crate::CairoRenderer::with_fonts
+pub struct FontConfig {}
+
+impl FontConfig {
+    /// Creates a font configuration with the system's fonts.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> FontConfig {
+        FontConfig {}
+    }
+
+    /// Creates a font configuration without any fonts.
+    pub fn empty() -> FontConfig {
+        FontConfig {}
+    }
+
+    /// Returns an error, since librsvg was built without text support.
+    pub fn add_font_file<P: AsRef<Path>>(&mut self, _path: P) -> Result<(), LoadingError> {
+        Err(text_disabled_error())
+    }
+
+    /// Returns an error, since librsvg was built without text support.
+    pub fn add_font_dir<P: AsRef<Path>>(&mut self, _path: P) -> Result<(), LoadingError> {
+        Err(text_disabled_error())
+    }
+
+    pub(crate) fn create_font_map(&self) -> Option<FontMap> {
+        None
+    }
+}
+
+fn text_disabled_error() -> LoadingError {
+    LoadingError::Other(String::from(
+        "librsvg was built without the \"text\" feature",
+    ))
+}
+
+/// Characters inside a text element; see the `text` module for details.
+///
+/// These are still kept without the `text` feature, since `<style>`, `<title>`, and `<desc>`
+/// use them.
+#[derive(Default)]
+pub struct Chars {
+    string: RefCell<String>,
+}
+
+impl Chars {
+    pub fn new(initial_text: &str) -> Chars {
+        Chars {
+            string: RefCell::new(String::from(initial_text)),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.string.borrow().is_empty()
+    }
+
+    pub fn append(&self, s: &str) {
+        self.string.borrow_mut().push_str(s);
+    }
+
+    pub fn get_string(&self) -> String {
+        self.string.borrow().clone()
+    }
+}
+
+#[derive(Default)]
+pub struct Text;
+
+impl ElementTrait for Text {
+    fn draw(
+        &self,
+        node: &Node,
+        _acquired_nodes: &mut AcquiredNodes<'_>,
+        _cascaded: &CascadedValues<'_>,
+        _viewport: &Viewport,
+        draw_ctx: &mut DrawingCtx,
+        _clipping: bool,
+    ) -> Result<BoundingBox, InternalRenderingError> {
+        rsvg_log!(
+            draw_ctx.session(),
+            "not rendering element {} since librsvg was built without text support",
+            node
+        );
+
+        Ok(draw_ctx.empty_bbox())
+    }
+}
+
+#[derive(Default)]
+pub struct TRef;
+
+impl ElementTrait for TRef {}
+
+#[derive(Default)]
+pub struct TSpan;
+
+impl ElementTrait for TSpan {}
+
+#[derive(Default)]
+pub struct TextPath;
+
+impl ElementTrait for TextPath {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CairoRenderer, Loader};
+
+    #[test]
+    fn renders_shapes_and_skips_text() {
+        let handle = Loader::new()
+            .read_bytes(
+                br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10">
+  <rect width="10" height="10" fill="lime"/>
+  <text x="0" y="10" font-size="10" fill="red">X</text>
+</svg>
+"#,
+            )
+            .unwrap();
+
+        let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, 10, 10).unwrap();
+        let cr = cairo::Context::new(&surface).unwrap();
+        CairoRenderer::new(&handle)
+            .render_document(&cr, &cairo::Rectangle::new(0.0, 0.0, 10.0, 10.0))
+            .unwrap();
+        drop(cr);
+
+        let data = surface.take_data().unwrap();
+        assert!(data.chunks(4).all(|pixel| pixel == [0, 255, 0, 255]));
+    }
+}
@@ -18,7 +18,7 @@ use std::ops::Deref;
 use crate::angle::Angle;
 use crate::error::*;
 use crate::length::*;
-use crate::parsers::{optional_comma, Parse};
+use crate::parsers::{optional_comma, Parse, ToCssString};
 use crate::properties::ComputedValues;
 use crate::property_macros::Property;
 use crate::rect::Rect;
@@ -130,6 +130,26 @@ impl TransformProperty {
     }
 }
 
+impl ToCssString for TransformProperty {
+    fn to_css_string(&self) -> String {
+        self.to_transform().to_css_string()
+    }
+}
+
+impl ToCssString for Transform {
+    fn to_css_string(&self) -> String {
+        if *self == Transform::identity() {
+            "none".to_string()
+        } else {
+            let m = [self.xx, self.yx, self.xy, self.yy, self.x0, self.y0];
+
+            let m: Vec<String> = m.iter().map(|v| v.to_css_string()).collect();
+
+            format!("matrix({})", m.join(", "))
+        }
+    }
+}
+
 // https://www.w3.org/TR/css-transforms-1/#typedef-transform-function
 #[derive(Debug, Clone, PartialEq)]
 pub enum TransformFunction {
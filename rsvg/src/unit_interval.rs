@@ -4,7 +4,7 @@ use cssparser::Parser;
 
 use crate::error::*;
 use crate::length::*;
-use crate::parsers::Parse;
+use crate::parsers::{Parse, ToCssString};
 use crate::util;
 
 #[derive(Debug, Default, Copy, Clone, PartialEq, PartialOrd)]
@@ -29,6 +29,12 @@ impl Parse for UnitInterval {
     }
 }
 
+impl ToCssString for UnitInterval {
+    fn to_css_string(&self) -> String {
+        self.0.to_css_string()
+    }
+}
+
 impl From<UnitInterval> for u8 {
     fn from(val: UnitInterval) -> u8 {
         let UnitInterval(x) = val;
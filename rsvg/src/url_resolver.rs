@@ -2,6 +2,7 @@
 
 use std::fmt;
 use std::ops::Deref;
+use std::sync::Arc;
 use url::Url;
 
 use crate::error::AllowedUrlError;
@@ -21,12 +22,64 @@ use crate::error::AllowedUrlError;
 pub struct UrlResolver {
     /// Base URL; all relative references will be resolved with respect to this.
     pub base_url: Option<Url>,
+
+    /// Supplied by the application to provide the contents of referenced files.
+    resource_callback: Option<Arc<ResourceCallback>>,
+
+    /// Whether `file:` URLs may be loaded from the filesystem.
+    allow_files: bool,
+}
+
+/// Signature for callbacks that supply the contents of referenced files.
+///
+/// See [`Loader::with_resource_callback`](crate::Loader::with_resource_callback).
+pub type ResourceCallback = Box<dyn Fn(&str) -> Result<glib::Bytes, ResolveError> + Send + Sync>;
+
+/// Returned by a [`ResourceCallback`] when it does not supply the contents for a URL.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ResolveError {
+    /// The callback does not handle this URL; librsvg will load it with its normal
+    /// rules for referenced files.
+    NotHandled,
+
+    /// The URL must not be loaded at all.
+    Denied,
 }
 
 impl UrlResolver {
     /// Creates a `UrlResolver` with defaults, and sets the `base_url`.
     pub fn new(base_url: Option<Url>) -> Self {
-        UrlResolver { base_url }
+        UrlResolver {
+            base_url,
+            resource_callback: None,
+            allow_files: true,
+        }
+    }
+
+    /// Disallows loading `file:` URLs from the filesystem.
+    ///
+    /// The resource callback still gets called for them, and it can supply their contents.
+    pub fn without_file_access(mut self) -> Self {
+        self.allow_files = false;
+        self
+    }
+
+    /// Sets a callback that gets consulted before the normal rules for loading files.
+    pub fn with_resource_callback(mut self, callback: Option<ResourceCallback>) -> Self {
+        self.resource_callback = callback.map(Arc::new);
+        self
+    }
+
+    /// Returns a copy of this resolver with a different base URL.
+    ///
+    /// This is for resolving references in a file that was loaded through this resolver,
+    /// like an `@import` in a stylesheet; the file access policy and the resource
+    /// callback are kept.
+    pub fn with_base_url(&self, base_url: Option<Url>) -> Self {
+        UrlResolver {
+            base_url,
+            ..self.clone()
+        }
     }
 
     /// Decides which URLs are allowed to be loaded based on the presence of a base URL.
@@ -34,17 +87,57 @@ impl UrlResolver {
     /// This function implements the policy described in "Security and locations of
     /// referenced files" in the [crate
     /// documentation](index.html#security-and-locations-of-referenced-files).
+    ///
+    /// If there is a resource callback, it gets called with the resolved URL before
+    /// applying those rules.  It can supply the file's contents, deny the load, or
+    /// let the normal rules take place.
     pub fn resolve_href(&self, href: &str) -> Result<AllowedUrl, AllowedUrlError> {
-        let url = Url::options()
-            .base_url(self.base_url.as_ref())
-            .parse(href)
-            .map_err(AllowedUrlError::UrlParseError)?;
+        let url = self.parse_href(href)?;
 
         // Allow loads of data: from any location
         if url.scheme() == "data" {
-            return Ok(AllowedUrl(url));
+            return Ok(AllowedUrl::new(url));
+        }
+
+        if let Some(ref callback) = self.resource_callback {
+            match callback(url.as_str()) {
+                Ok(bytes) => {
+                    return Ok(AllowedUrl {
+                        url,
+                        supplied_data: Some(bytes),
+                    })
+                }
+                Err(ResolveError::Denied) => return Err(AllowedUrlError::DeniedByCallback),
+                Err(ResolveError::NotHandled) => (),
+            }
+        }
+
+        self.check_rules(url)
+    }
+
+    /// Checks if the rules for referenced files allow loading an `href`.
+    ///
+    /// Unlike [`resolve_href`](#method.resolve_href), this does not call the resource
+    /// callback, so it has no side effects.
+    pub fn check_href(&self, href: &str) -> Result<AllowedUrl, AllowedUrlError> {
+        let url = self.parse_href(href)?;
+
+        if url.scheme() == "data" {
+            return Ok(AllowedUrl::new(url));
         }
 
+        self.check_rules(url)
+    }
+
+    fn parse_href(&self, href: &str) -> Result<Url, AllowedUrlError> {
+        Url::options()
+            .base_url(self.base_url.as_ref())
+            .parse(href)
+            .map_err(AllowedUrlError::UrlParseError)
+    }
+
+    // The rules for URLs that are not data: and were not supplied by the resource callback.
+    fn check_rules(&self, url: Url) -> Result<AllowedUrl, AllowedUrlError> {
         // Queries are not allowed.
         if url.query().is_some() {
             return Err(AllowedUrlError::NoQueriesAllowed);
@@ -56,6 +149,10 @@ impl UrlResolver {
             return Err(AllowedUrlError::NoFragmentIdentifierAllowed);
         }
 
+        if !self.allow_files && url.scheme() == "file" {
+            return Err(AllowedUrlError::DisallowedScheme);
+        }
+
         // All other sources require a base url
         if self.base_url.is_none() {
             return Err(AllowedUrlError::BaseRequired);
@@ -70,7 +167,7 @@ impl UrlResolver {
 
         // resource: is allowed to load anything from other resources
         if url.scheme() == "resource" {
-            return Ok(AllowedUrl(url));
+            return Ok(AllowedUrl::new(url));
         }
 
         // Non-file: isn't allowed to load anything
@@ -125,7 +222,7 @@ impl UrlResolver {
         if path_canon.starts_with(parent_canon) {
             // Finally, convert the canonicalized path back to a URL.
             let path_to_url = Url::from_file_path(path_canon).unwrap();
-            Ok(AllowedUrl(path_to_url))
+            Ok(AllowedUrl::new(path_to_url))
         } else {
             Err(AllowedUrlError::NotSiblingOrChildOfBaseFile)
         }
@@ -139,23 +236,79 @@ impl UrlResolver {
 /// a specified `href` (a possibly-relative filename, for example)
 /// should be allowed to be loaded, given the base URL of the SVG
 /// being loaded.
+///
+/// If the URL's contents were supplied by a resource callback, they are
+/// stored here as well.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct AllowedUrl(Url);
+pub struct AllowedUrl {
+    url: Url,
+    supplied_data: Option<glib::Bytes>,
+}
+
+impl AllowedUrl {
+    fn new(url: Url) -> AllowedUrl {
+        AllowedUrl {
+            url,
+            supplied_data: None,
+        }
+    }
+
+    /// Returns the contents for the URL if a resource callback supplied them.
+    pub fn supplied_data(&self) -> Option<&glib::Bytes> {
+        self.supplied_data.as_ref()
+    }
+}
 
 impl Deref for AllowedUrl {
     type Target = Url;
 
     fn deref(&self) -> &Url {
-        &self.0
+        &self.url
     }
 }
 
 impl fmt::Display for AllowedUrl {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.0.fmt(f)
+        self.url.fmt(f)
     }
 }
 
+/// Where a [`ReferencedUrl`] appears in an SVG document.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ReferenceKind {
+    /// The `href` of an `<image>` or `<feImage>` element.
+    Image,
+
+    /// The `href` of a `<use>` element that points to an element in another document.
+    Use,
+
+    /// A `url(...)` in a property like `fill`, `stroke`, `filter`, `clip-path`, `mask`, or
+    /// the `marker` properties, that points to an element in another document.
+    Property,
+
+    /// The `href` of an `xi:include` element.
+    XInclude,
+
+    /// The `href` of an `xml-stylesheet` processing instruction.
+    Stylesheet,
+}
+
+/// A URL that an SVG document references; see
+/// [`SvgHandle::referenced_urls`](crate::SvgHandle::referenced_urls).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferencedUrl {
+    /// The URL as written in the document, without the fragment identifier if it points
+    /// to an element in another document.
+    pub href: String,
+
+    /// Where the URL appears in the document.
+    pub kind: ReferenceKind,
+
+    /// Whether the rules for referenced files allow loading this URL.
+    pub allowed: bool,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -210,6 +363,21 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn disallows_files_without_file_access() {
+        let url_resolver = UrlResolver::new(Some(
+            Url::parse(&make_file_uri("/example/bar.svg")).unwrap(),
+        ))
+        .without_file_access();
+        assert!(matches!(
+            url_resolver.resolve_href("foo.svg"),
+            Err(AllowedUrlError::DisallowedScheme)
+        ));
+        assert!(url_resolver
+            .resolve_href("data:image/jpeg;base64,xxyyzz")
+            .is_ok());
+    }
+
     #[test]
     fn allows_data_url_with_no_base_file() {
         let url_resolver = UrlResolver::new(None);
@@ -343,6 +511,43 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn resource_callback_supplies_data() {
+        let base_url = Url::parse("https://example.com/foo.svg").unwrap();
+        let url_resolver =
+            UrlResolver::new(Some(base_url)).with_resource_callback(Some(Box::new(|url| {
+                assert_eq!(url, "https://example.com/images/bar.png");
+                Ok(glib::Bytes::from_static(b"hello"))
+            })));
+
+        let resolved = url_resolver.resolve_href("images/bar.png").unwrap();
+        assert_eq!(resolved.as_str(), "https://example.com/images/bar.png");
+        assert_eq!(resolved.supplied_data().unwrap().as_ref(), b"hello");
+    }
+
+    #[test]
+    fn resource_callback_can_deny() {
+        let base_url = url_from_test_fixtures("tests/fixtures/loading/bar.svg");
+        let url_resolver = UrlResolver::new(Some(base_url))
+            .with_resource_callback(Some(Box::new(|_url| Err(ResolveError::Denied))));
+
+        assert!(matches!(
+            url_resolver.resolve_href("foo.svg"),
+            Err(AllowedUrlError::DeniedByCallback)
+        ));
+    }
+
+    #[test]
+    fn resource_callback_falls_back_to_default_rules() {
+        let base_url = url_from_test_fixtures("tests/fixtures/loading/bar.svg");
+        let url_resolver = UrlResolver::new(Some(base_url))
+            .with_resource_callback(Some(Box::new(|_url| Err(ResolveError::NotHandled))));
+
+        let resolved = url_resolver.resolve_href("foo.svg").unwrap();
+        assert!(resolved.as_str().ends_with("/loading/foo.svg"));
+        assert!(resolved.supplied_data().is_none());
+    }
+
     #[cfg(windows)]
     #[test]
     fn invalid_url_from_test_suite() {
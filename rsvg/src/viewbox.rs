@@ -25,6 +25,25 @@ impl Deref for ViewBox {
     }
 }
 
+impl ViewBox {
+    /// Parses the value of a `viewBox` attribute.
+    ///
+    /// The syntax is four numbers for `x`, `y`, `width`, and `height`, separated by
+    /// whitespace and/or a comma.  The width and height must not be negative.
+    ///
+    /// ```
+    /// let vbox = rsvg::ViewBox::parse_str("0 0 100 50").unwrap();
+    /// assert_eq!(vbox.size(), (100.0, 50.0));
+    ///
+    /// assert!(rsvg::ViewBox::parse_str("0 0 -100 50").is_err());
+    /// ```
+    pub fn parse_str(s: &str) -> Result<ViewBox, ViewBoxParseError> {
+        <ViewBox as Parse>::parse_str(s).map_err(|e| ViewBoxParseError {
+            message: e.to_string(),
+        })
+    }
+}
+
 impl From<Rect> for ViewBox {
     fn from(r: Rect) -> ViewBox {
         ViewBox(r)
@@ -137,11 +137,13 @@ impl Attributes {
     }
 
     pub fn get_id(&self) -> Option<&str> {
-        self.id_idx.and_then(|idx| {
-            self.attrs
-                .get(usize::from(idx))
-                .map(|(_name, value)| &value[..])
-        })
+        self.get_id_value().map(|value| &value[..])
+    }
+
+    /// Like `get_id`, but returns the interned value, which is cheap to clone.
+    pub fn get_id_value(&self) -> Option<&AttributeValue> {
+        self.id_idx
+            .and_then(|idx| self.attrs.get(usize::from(idx)).map(|(_name, value)| value))
     }
 
     pub fn get_class(&self) -> Option<&str> {
@@ -1,10 +1,13 @@
 //! The main XML parser.
 
 use encoding_rs::Encoding;
+#[cfg(feature = "gio")]
 use gio::{
-    prelude::BufferedInputStreamExt, BufferedInputStream, Cancellable, ConverterInputStream,
-    InputStream, ZlibCompressorFormat, ZlibDecompressor,
+    prelude::{BufferedInputStreamExt, ConverterExtManual},
+    BufferedInputStream, Cancellable, ConverterFlags, ConverterResult, InputStream,
+    MemoryInputStream, ZlibCompressorFormat, ZlibDecompressor,
 };
+#[cfg(feature = "gio")]
 use glib::object::Cast;
 use markup5ever::{
     expanded_name, local_name, namespace_url, ns, ExpandedName, LocalName, Namespace, QualName,
@@ -29,7 +32,7 @@ use crate::node::{Node, NodeBorrow};
 use crate::rsvg_log;
 use crate::session::Session;
 use crate::style::StyleType;
-use crate::url_resolver::AllowedUrl;
+use crate::url_resolver::{AllowedUrl, ReferenceKind};
 
 use xml2_load::Xml2Parser;
 
@@ -39,7 +42,7 @@ mod xml2_load;
 
 use xml2::xmlEntityPtr;
 
-pub use attributes::Attributes;
+pub use attributes::{AttributeValue, Attributes};
 
 #[derive(Clone)]
 enum Context {
@@ -255,6 +258,8 @@ impl XmlState {
 
         // We can unwrap since start_element() always adds a context to the stack
         self.inner.borrow_mut().context_stack.pop().unwrap();
+
+        self.check_strict_failure();
     }
 
     pub fn characters(&self, text: &str) {
@@ -313,10 +318,17 @@ impl XmlState {
             }
 
             if let Some(href) = href {
+                inner
+                    .document_builder
+                    .add_load_time_reference(ReferenceKind::Stylesheet, &href);
+
                 if let Ok(aurl) = self.load_options.url_resolver.resolve_href(&href) {
-                    if let Ok(stylesheet) =
-                        Stylesheet::from_href(&aurl, Origin::Author, self.session.clone())
-                    {
+                    if let Ok(stylesheet) = Stylesheet::from_href(
+                        &aurl,
+                        &self.load_options.url_resolver,
+                        Origin::Author,
+                        self.session.clone(),
+                    ) {
                         inner.document_builder.append_stylesheet(stylesheet);
                     } else {
                         // FIXME: https://www.w3.org/TR/xml-stylesheet/ does not seem to specify
@@ -345,6 +357,8 @@ impl XmlState {
                 "invalid processing instruction data in xml-stylesheet",
             )));
         }
+
+        self.check_strict_failure();
     }
 
     pub fn error(&self, e: LoadingError) {
@@ -354,6 +368,16 @@ impl XmlState {
             .push(Context::FatalError(e));
     }
 
+    /// In strict mode, stops loading at the first unsupported feature.
+    ///
+    /// This is for features found after an element is created, like invalid declarations
+    /// in a stylesheet.
+    fn check_strict_failure(&self) {
+        if let Some(detail) = self.session.take_strict_failure() {
+            self.error(LoadingError::Unsupported { detail });
+        }
+    }
+
     pub fn entity_lookup(&self, entity_name: &str) -> Option<xmlEntityPtr> {
         self.inner
             .borrow()
@@ -380,6 +404,11 @@ impl XmlState {
             let node = inner.document_builder.append_element(name, attrs, parent);
             inner.current_node = Some(node);
 
+            // In strict mode, stop at the first unsupported feature.
+            if let Some(detail) = self.session.take_strict_failure() {
+                return Context::FatalError(LoadingError::Unsupported { detail });
+            }
+
             if name.expanded() == expanded_name!(svg "style") {
                 Context::Style
             } else {
@@ -531,6 +560,11 @@ impl XmlState {
         encoding: Option<&str>,
     ) -> Result<(), AcquireError> {
         if let Some(href) = href {
+            self.inner
+                .borrow_mut()
+                .document_builder
+                .add_load_time_reference(ReferenceKind::XInclude, href);
+
             let aurl = self
                 .load_options
                 .url_resolver
@@ -600,7 +634,13 @@ impl XmlState {
             AcquireError::ResourceError
         })?;
 
-        let encoding = encoding.unwrap_or("utf-8");
+        // An explicit encoding in the xi:include element takes precedence over the
+        // charset from the resource's MIME type.  US-ASCII is the default charset of
+        // data: URLs; treat it as UTF-8, which is a superset.
+        let encoding = encoding
+            .or_else(|| binary.charset())
+            .filter(|e| !e.eq_ignore_ascii_case("us-ascii"))
+            .unwrap_or("utf-8");
 
         let encoder = Encoding::for_label_no_replacement(encoding.as_bytes()).ok_or_else(|| {
             AcquireError::FatalError(format!("unknown encoding \"{encoding}\" for \"{aurl}\""))
@@ -616,6 +656,7 @@ impl XmlState {
         Ok(())
     }
 
+    #[cfg(feature = "gio")]
     fn acquire_xml(&self, aurl: &AllowedUrl) -> Result<(), AcquireError> {
         // FIXME: distinguish between "file not found" and "invalid XML"
 
@@ -626,7 +667,22 @@ impl XmlState {
 
         // FIXME: pass a cancellable
         self.parse_from_stream(&stream, None).map_err(|e| match e {
-            LoadingError::Io(_) => AcquireError::ResourceError,
+            LoadingError::Io(_) | LoadingError::Glib(_) => AcquireError::ResourceError,
+            LoadingError::XmlParseError(s) => AcquireError::FatalError(s),
+            _ => AcquireError::FatalError(String::from("unknown error")),
+        })
+    }
+
+    // Without gio there are no streams, so read the whole resource and parse it from memory.
+    #[cfg(not(feature = "gio"))]
+    fn acquire_xml(&self, aurl: &AllowedUrl) -> Result<(), AcquireError> {
+        let binary = io::acquire_data(aurl, None).map_err(|e| match e {
+            IoError::BadDataUrl => AcquireError::FatalError(String::from("malformed data: URL")),
+            _ => AcquireError::ResourceError,
+        })?;
+
+        self.parse_from_bytes(&binary.data).map_err(|e| match e {
+            LoadingError::Io(_) | LoadingError::Glib(_) => AcquireError::ResourceError,
             LoadingError::XmlParseError(s) => AcquireError::FatalError(s),
             _ => AcquireError::FatalError(String::from("unknown error")),
         })
@@ -636,6 +692,7 @@ impl XmlState {
     //
     // This can be called "in the middle" of an XmlState's processing status,
     // for example, when including another XML file via xi:include.
+    #[cfg(feature = "gio")]
     fn parse_from_stream(
         &self,
         stream: &gio::InputStream,
@@ -646,17 +703,19 @@ impl XmlState {
             .and_then(|_: ()| self.check_last_error())
     }
 
+    // Parses XML from a buffer in memory into an XmlState, without going
+    // through a gio stream.
+    fn parse_from_bytes(&self, bytes: &[u8]) -> Result<(), LoadingError> {
+        Xml2Parser::from_bytes(self, self.load_options.unlimited_size, bytes)
+            .and_then(|parser| parser.parse())
+            .and_then(|_: ()| self.check_last_error())
+    }
+
     fn unsupported_xinclude_start_element(&self, _name: &QualName) -> Context {
         Context::UnsupportedXIncludeChild
     }
 
-    fn build_document(
-        self,
-        stream: &gio::InputStream,
-        cancellable: Option<&gio::Cancellable>,
-    ) -> Result<Document, LoadingError> {
-        self.parse_from_stream(stream, cancellable)?;
-
+    fn build_document(self) -> Result<Document, LoadingError> {
         // consume self, then consume inner, then consume document_builder by calling .build()
 
         let XmlState { inner, .. } = self;
@@ -732,6 +791,7 @@ fn parse_xml_stylesheet_processing_instruction(data: &str) -> Result<Vec<(String
     }
 }
 
+#[cfg(feature = "gio")]
 pub fn xml_load_from_possibly_compressed_stream(
     session: Session,
     document_builder: DocumentBuilder,
@@ -743,13 +803,190 @@ pub fn xml_load_from_possibly_compressed_stream(
 
     let stream = get_input_stream_for_loading(stream, cancellable)?;
 
-    state.build_document(&stream, cancellable)
+    state.parse_from_stream(&stream, cancellable)?;
+    state.build_document()
+}
+
+pub fn xml_load_from_bytes(
+    session: Session,
+    document_builder: DocumentBuilder,
+    load_options: Arc<LoadOptions>,
+    bytes: &[u8],
+) -> Result<Document, LoadingError> {
+    if bytes.len() < 2 {
+        // FIXME: this string was localized in the original; localize it
+        return Err(LoadingError::XmlParseError(String::from(
+            "Input file is too short",
+        )));
+    }
+
+    // Decompressing svgz data needs gio
+    if io::is_gzipped(bytes) {
+        #[cfg(feature = "gio")]
+        {
+            let stream = MemoryInputStream::from_bytes(&glib::Bytes::from(bytes));
+
+            return xml_load_from_possibly_compressed_stream(
+                session,
+                document_builder,
+                load_options,
+                &stream.upcast(),
+                None,
+            );
+        }
+
+        #[cfg(not(feature = "gio"))]
+        return Err(LoadingError::Io(String::from(
+            "compressed SVG data needs the \"gio\" feature",
+        )));
+    }
+
+    let state = XmlState::new(session, document_builder, load_options);
+
+    state.parse_from_bytes(bytes)?;
+    state.build_document()
 }
 
-// Header of a gzip data stream
-const GZ_MAGIC_0: u8 = 0x1f;
-const GZ_MAGIC_1: u8 = 0x8b;
+/// Loads an XML document from data that arrives in chunks.
+///
+/// Each chunk is fed to a libxml2 push parser as soon as it arrives, so the document is
+/// built while the data comes in.  Gzipped data (svgz) is decompressed on the fly.
+#[cfg(feature = "gio")]
+pub struct XmlPushLoader {
+    // The parser refers to the XmlState in `state`, so it must be dropped
+    // before it.  Struct fields are dropped in declaration order.
+    parser: Box<Xml2Parser<'static>>,
+    state: Box<XmlState>,
+    input: PushInput,
+}
 
+#[cfg(feature = "gio")]
+enum PushInput {
+    /// Not enough data yet to see if it is gzipped; holds what we have so far.
+    Sniffing(Vec<u8>),
+
+    /// Plain XML, fed directly to the parser.
+    Plain,
+
+    /// Gzipped XML, decompressed before feeding it to the parser.
+    Gzipped(ZlibDecompressor),
+}
+
+#[cfg(feature = "gio")]
+impl XmlPushLoader {
+    pub fn new(
+        session: Session,
+        document_builder: DocumentBuilder,
+        load_options: Arc<LoadOptions>,
+    ) -> Result<XmlPushLoader, LoadingError> {
+        let unlimited_size = load_options.unlimited_size;
+        let state = Box::new(XmlState::new(session, document_builder, load_options));
+
+        // SAFETY: the XmlState is in a box, so it does not move when the box
+        // moves, and it outlives the parser; see the comment in the struct.
+        let state_ptr: *const XmlState = state.as_ref();
+        let state_ref: &'static XmlState = unsafe { &*state_ptr };
+        let parser = Xml2Parser::for_push(state_ref, unlimited_size)?;
+
+        Ok(XmlPushLoader {
+            parser,
+            state,
+            input: PushInput::Sniffing(Vec::new()),
+        })
+    }
+
+    /// Parses a chunk of data.
+    pub fn push(&mut self, data: &[u8]) -> Result<(), LoadingError> {
+        let result = match self.input {
+            PushInput::Sniffing(ref mut header) => {
+                header.extend_from_slice(data);
+
+                if header.len() < 2 {
+                    return Ok(());
+                }
+
+                let header = std::mem::take(header);
+
+                self.input = if io::is_gzipped(&header) {
+                    PushInput::Gzipped(ZlibDecompressor::new(ZlibCompressorFormat::Gzip))
+                } else {
+                    PushInput::Plain
+                };
+
+                self.push(&header)
+            }
+
+            PushInput::Plain => self.parser.push(data, false),
+
+            PushInput::Gzipped(ref decompressor) => {
+                decompress_chunk(decompressor, data, false, |d| self.parser.push(d, false))
+            }
+        };
+
+        result.and_then(|_: ()| self.state.check_last_error())
+    }
+
+    /// Tells the parser that there is no more data, and builds the document.
+    pub fn finish(self) -> Result<Document, LoadingError> {
+        match self.input {
+            PushInput::Sniffing(_) => {
+                // FIXME: this string was localized in the original; localize it
+                return Err(LoadingError::XmlParseError(String::from(
+                    "Input file is too short",
+                )));
+            }
+
+            PushInput::Plain => self.parser.push(&[], true)?,
+
+            PushInput::Gzipped(ref decompressor) => {
+                decompress_chunk(decompressor, &[], true, |d| self.parser.push(d, false))?;
+                self.parser.push(&[], true)?;
+            }
+        }
+
+        self.state.check_last_error()?;
+
+        let XmlPushLoader { parser, state, .. } = self;
+        drop(parser);
+        state.build_document()
+    }
+}
+
+// Decompresses a chunk of gzipped data and feeds the result to `sink` in pieces.
+//
+// With `at_end` set to true, this also flushes the data that the decompressor has
+// buffered, and fails if the compressed data is truncated.
+#[cfg(feature = "gio")]
+fn decompress_chunk(
+    decompressor: &ZlibDecompressor,
+    data: &[u8],
+    at_end: bool,
+    mut sink: impl FnMut(&[u8]) -> Result<(), LoadingError>,
+) -> Result<(), LoadingError> {
+    let flags = if at_end {
+        ConverterFlags::INPUT_AT_END
+    } else {
+        ConverterFlags::NONE
+    };
+
+    let mut output = vec![0u8; 64 * 1024];
+    let mut data = data;
+
+    while !data.is_empty() || at_end {
+        let (result, num_read, num_written) = decompressor.convert(data, &mut output[..], flags)?;
+
+        sink(&output[..num_written])?;
+        data = &data[num_read..];
+
+        if result == ConverterResult::Finished {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "gio")]
 fn get_input_stream_for_loading(
     stream: &InputStream,
     cancellable: Option<&Cancellable>,
@@ -765,15 +1002,7 @@ fn get_input_stream_for_loading(
         )));
     }
 
-    let buf = buffered.peek_buffer();
-    assert!(buf.len() >= 2);
-    if buf[0..2] == [GZ_MAGIC_0, GZ_MAGIC_1] {
-        let decomp = ZlibDecompressor::new(ZlibCompressorFormat::Gzip);
-        let converter = ConverterInputStream::new(&buffered, &decomp);
-        Ok(converter.upcast::<InputStream>())
-    } else {
-        Ok(buffered.upcast::<InputStream>())
-    }
+    Ok(io::decompress_if_gzipped(buffered))
 }
 
 #[cfg(test)]
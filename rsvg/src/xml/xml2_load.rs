@@ -1,8 +1,10 @@
 //! Glue between the libxml2 API and our xml parser module.
 //!
 //! This file provides functions to create a libxml2 xmlParserCtxtPtr, configured
-//! to read from a gio::InputStream, and to maintain its loading data in an XmlState.
+//! to read from a gio::InputStream or from a buffer in memory, and to maintain its
+//! loading data in an XmlState.
 
+#[cfg(feature = "gio")]
 use gio::prelude::*;
 use std::borrow::Cow;
 use std::cell::{Cell, RefCell};
@@ -322,6 +324,7 @@ fn set_xml_parse_options(parser: xmlParserCtxtPtr, unlimited_size: bool) {
 // is set; if it is, it means that there was an I/O error.  Otherwise,
 // there were no I/O errors but the caller must then ask libxml2 for
 // XML parsing errors.
+#[cfg(feature = "gio")]
 struct StreamCtx {
     stream: gio::InputStream,
     cancellable: Option<gio::Cancellable>,
@@ -329,6 +332,7 @@ struct StreamCtx {
 }
 
 // read() callback from xmlCreateIOParserCtxt()
+#[cfg(feature = "gio")]
 unsafe extern "C" fn stream_ctx_read(
     context: *mut libc::c_void,
     buffer: *mut libc::c_char,
@@ -363,6 +367,7 @@ unsafe extern "C" fn stream_ctx_read(
 }
 
 // close() callback from xmlCreateIOParserCtxt()
+#[cfg(feature = "gio")]
 unsafe extern "C" fn stream_ctx_close(context: *mut libc::c_void) -> libc::c_int {
     let ctx = &mut *(context as *mut StreamCtx);
 
@@ -386,6 +391,44 @@ unsafe extern "C" fn stream_ctx_close(context: *mut libc::c_void) -> libc::c_int
     ret
 }
 
+// Struct used as closure data for xmlCreateIOParserCtxt() when parsing
+// from a buffer in memory, with bytes_ctx_read() and bytes_ctx_close().
+//
+// The Xml2Parser that owns the libxml2 context does not outlive the
+// buffer, so it is fine to keep a plain slice here.
+struct BytesCtx<'b> {
+    bytes: &'b [u8],
+}
+
+// read() callback from xmlCreateIOParserCtxt()
+unsafe extern "C" fn bytes_ctx_read(
+    context: *mut libc::c_void,
+    buffer: *mut libc::c_char,
+    len: libc::c_int,
+) -> libc::c_int {
+    let ctx = &mut *(context as *mut BytesCtx<'_>);
+
+    let size = ctx.bytes.len().min(len as usize);
+    let (head, tail) = ctx.bytes.split_at(size);
+
+    // See stream_ctx_read() for why we cast like this.
+    #[allow(trivial_casts)]
+    let u8_buffer = buffer as *mut u8;
+    let buf = slice::from_raw_parts_mut(u8_buffer, size);
+
+    buf.copy_from_slice(head);
+    ctx.bytes = tail;
+
+    size as libc::c_int
+}
+
+// close() callback from xmlCreateIOParserCtxt()
+unsafe extern "C" fn bytes_ctx_close(context: *mut libc::c_void) -> libc::c_int {
+    drop(Box::from_raw(context as *mut BytesCtx<'_>));
+
+    0
+}
+
 fn init_libxml2() {
     static ONCE: Once = Once::new();
 
@@ -401,14 +444,13 @@ pub struct Xml2Parser<'a> {
 }
 
 impl<'a> Xml2Parser<'a> {
+    #[cfg(feature = "gio")]
     pub fn from_stream(
         state: &'a XmlState,
         unlimited_size: bool,
         stream: &gio::InputStream,
         cancellable: Option<&gio::Cancellable>,
     ) -> Result<Box<Xml2Parser<'a>>, LoadingError> {
-        init_libxml2();
-
         // The Xml2Parser we end up creating, if
         // xmlCreateIOParserCtxt() is successful, needs to hold a
         // location to place a GError from within the I/O callbacks
@@ -424,6 +466,95 @@ impl<'a> Xml2Parser<'a> {
             gio_error: gio_error.clone(),
         });
 
+        Xml2Parser::create_io(
+            state,
+            unlimited_size,
+            gio_error,
+            Some(stream_ctx_read),
+            Some(stream_ctx_close),
+            Box::into_raw(ctx) as *mut _,
+        )
+    }
+
+    /// Creates a parser that reads XML directly from `bytes`, without a gio stream.
+    pub fn from_bytes(
+        state: &'a XmlState,
+        unlimited_size: bool,
+        bytes: &'a [u8],
+    ) -> Result<Box<Xml2Parser<'a>>, LoadingError> {
+        // There is no I/O that can fail here, so gio_error will never be set.
+        let gio_error = Rc::new(RefCell::new(None));
+
+        let ctx = Box::new(BytesCtx { bytes });
+
+        Xml2Parser::create_io(
+            state,
+            unlimited_size,
+            gio_error,
+            Some(bytes_ctx_read),
+            Some(bytes_ctx_close),
+            Box::into_raw(ctx) as *mut _,
+        )
+    }
+
+    /// Creates a push parser, which gets fed XML data in chunks with [`Xml2Parser::push`]
+    /// instead of reading it by itself.
+    #[cfg(feature = "gio")]
+    pub fn for_push(
+        state: &'a XmlState,
+        unlimited_size: bool,
+    ) -> Result<Box<Xml2Parser<'a>>, LoadingError> {
+        // There is no I/O that can fail here, so gio_error will never be set.
+        let gio_error = Rc::new(RefCell::new(None));
+
+        Xml2Parser::create(
+            state,
+            unlimited_size,
+            gio_error,
+            |sax_handler, user_data| unsafe {
+                xmlCreatePushParserCtxt(sax_handler, user_data, ptr::null(), 0, ptr::null())
+            },
+        )
+    }
+
+    // Creates the libxml2 parser context with our SAX handler and the
+    // given I/O callbacks.  On failure, libxml2 frees the `io_ctx` by
+    // calling `io_close`.
+    fn create_io(
+        state: &'a XmlState,
+        unlimited_size: bool,
+        gio_error: Rc<RefCell<Option<glib::Error>>>,
+        io_read: xmlInputReadCallback,
+        io_close: xmlInputCloseCallback,
+        io_ctx: *mut libc::c_void,
+    ) -> Result<Box<Xml2Parser<'a>>, LoadingError> {
+        Xml2Parser::create(
+            state,
+            unlimited_size,
+            gio_error,
+            |sax_handler, user_data| unsafe {
+                xmlCreateIOParserCtxt(
+                    sax_handler,
+                    user_data,
+                    io_read,
+                    io_close,
+                    io_ctx,
+                    XML_CHAR_ENCODING_NONE,
+                )
+            },
+        )
+    }
+
+    // Creates the libxml2 parser context with `create_ctxt`, which gets
+    // passed our SAX handler and the user_data for its callbacks.
+    fn create(
+        state: &'a XmlState,
+        unlimited_size: bool,
+        gio_error: Rc<RefCell<Option<glib::Error>>>,
+        create_ctxt: impl FnOnce(xmlSAXHandlerPtr, *mut libc::c_void) -> xmlParserCtxtPtr,
+    ) -> Result<Box<Xml2Parser<'a>>, LoadingError> {
+        init_libxml2();
+
         let mut sax_handler = get_xml2_sax_handler();
 
         let mut xml2_parser = Box::new(Xml2Parser {
@@ -432,52 +563,77 @@ impl<'a> Xml2Parser<'a> {
             gio_error,
         });
 
-        unsafe {
-            let xml2_parser_ptr: *mut Xml2Parser<'a> = xml2_parser.as_mut();
-            let parser = xmlCreateIOParserCtxt(
-                &mut sax_handler,
-                xml2_parser_ptr as *mut _,
-                Some(stream_ctx_read),
-                Some(stream_ctx_close),
-                Box::into_raw(ctx) as *mut _,
-                XML_CHAR_ENCODING_NONE,
-            );
-
-            if parser.is_null() {
-                // on error, xmlCreateIOParserCtxt() frees our ctx via the
-                // stream_ctx_close function
-                Err(LoadingError::OutOfMemory(String::from(
-                    "could not create XML parser",
-                )))
-            } else {
-                xml2_parser.parser.set(parser);
+        let xml2_parser_ptr: *mut Xml2Parser<'a> = xml2_parser.as_mut();
+        let parser = create_ctxt(&mut sax_handler, xml2_parser_ptr as *mut _);
 
-                set_xml_parse_options(parser, unlimited_size);
+        if parser.is_null() {
+            Err(LoadingError::OutOfMemory(String::from(
+                "could not create XML parser",
+            )))
+        } else {
+            xml2_parser.parser.set(parser);
 
-                Ok(xml2_parser)
-            }
+            set_xml_parse_options(parser, unlimited_size);
+
+            Ok(xml2_parser)
         }
     }
 
     pub fn parse(&self) -> Result<(), LoadingError> {
-        unsafe {
-            let parser = self.parser.get();
+        let xml_parse_success = unsafe { xmlParseDocument(self.parser.get()) == 0 };
+
+        self.check_result(xml_parse_success)
+    }
 
-            let xml_parse_success = xmlParseDocument(parser) == 0;
+    /// Feeds a chunk of data to a parser created with [`Xml2Parser::for_push`].
+    ///
+    /// With `terminate` set to true, this also tells the parser that the document ends
+    /// after `data`.
+    #[cfg(feature = "gio")]
+    pub fn push(&self, data: &[u8], terminate: bool) -> Result<(), LoadingError> {
+        let parser = self.parser.get();
 
-            let mut err_ref = self.gio_error.borrow_mut();
+        // libxml2 takes the chunk size as an int, so feed huge buffers in pieces.
+        let max_chunk = libc::c_int::MAX as usize;
+        let mut data = data;
+
+        loop {
+            let size = data.len().min(max_chunk);
+            let (chunk, rest) = data.split_at(size);
+            let is_last = rest.is_empty();
+
+            let xml_parse_success = unsafe {
+                xmlParseChunk(
+                    parser,
+                    chunk.as_ptr().cast::<libc::c_char>(),
+                    size as libc::c_int,
+                    libc::c_int::from(terminate && is_last),
+                ) == 0
+            };
 
-            let io_error = err_ref.take();
+            self.check_result(xml_parse_success)?;
 
-            if let Some(io_error) = io_error {
-                Err(LoadingError::from(io_error))
-            } else if !xml_parse_success {
-                let xerr = xmlCtxtGetLastError(parser as *mut _);
-                let msg = xml2_error_to_string(xerr);
-                Err(LoadingError::XmlParseError(msg))
-            } else {
-                Ok(())
+            if is_last {
+                return Ok(());
             }
+
+            data = rest;
+        }
+    }
+
+    fn check_result(&self, xml_parse_success: bool) -> Result<(), LoadingError> {
+        let mut err_ref = self.gio_error.borrow_mut();
+
+        let io_error = err_ref.take();
+
+        if let Some(io_error) = io_error {
+            Err(LoadingError::from(io_error))
+        } else if !xml_parse_success {
+            let xerr = unsafe { xmlCtxtGetLastError(self.parser.get() as *mut _) };
+            let msg = xml2_error_to_string(xerr);
+            Err(LoadingError::XmlParseError(msg))
+        } else {
+            Ok(())
         }
     }
 }
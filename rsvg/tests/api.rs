@@ -1,7 +1,16 @@
 use gio::prelude::*;
 
 use rsvg::tests_only::{SharedImageSurface, SurfaceType};
-use rsvg::{CairoRenderer, RenderingError};
+use rsvg::{
+    parse_color, parse_path_data, parse_transform, AcceptLanguage, AspectRatio, CairoRenderer,
+    CubicBezierCurve, Dpi, EllipticalArc, FontConfig, ImplementationLimit, Language, LargeArc,
+    Length, LengthUnit, Loader, LoadingError, NormalizeParams, PathCommand, PixelOps, Pixels, Rect,
+    ReferenceKind, ReferencedUrl, RenderLimits, RenderObserver, RenderingError, RenderingOptions,
+    StylesheetOrigin, SvgHandle, Sweep, ViewBox,
+};
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use rsvg::test_utils::load_svg;
 use rsvg::test_utils::reference_utils::{Compare, Evaluate, Reference};
@@ -37,136 +46,304 @@ fn has_element_with_id_works() {
 }
 
 #[test]
-fn render_layer() {
-    let svg = load_svg(
-        br##"<?xml version="1.0" encoding="UTF-8"?>
-<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
-  <rect id="foo" x="10" y="10" width="30" height="30" fill="#00ff00"/>
-  <rect id="bar" x="20" y="20" width="30" height="30" fill="#0000ff"/>
-</svg>
-"##,
-    )
-    .unwrap();
+fn read_data_rejects_empty_input() {
+    assert!(matches!(
+        Loader::new().read_data(b"", None),
+        Err(LoadingError::XmlParseError(_))
+    ));
+}
+
+#[test]
+fn svg_handle_try_from_bytes_and_str() {
+    let data = r#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="20"/>"#;
 
+    let svg = SvgHandle::try_from(data).unwrap();
     let renderer = CairoRenderer::new(&svg);
+    assert_eq!(renderer.intrinsic_size_in_pixels(), Some((10.0, 20.0)));
 
-    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 300, 300).unwrap();
+    assert!(SvgHandle::try_from(data.as_bytes()).is_ok());
 
-    let res = {
-        let cr = cairo::Context::new(&output).expect("Failed to create cairo context");
-        let viewport = cairo::Rectangle::new(100.0, 100.0, 100.0, 100.0);
+    assert!(matches!(
+        SvgHandle::try_from(&b""[..]),
+        Err(LoadingError::XmlParseError(_))
+    ));
 
-        renderer.render_layer(&cr, Some("#bar"), &viewport)
-    };
+    assert!(matches!(
+        SvgHandle::try_from("<svg"),
+        Err(LoadingError::XmlParseError(_))
+    ));
+}
 
-    let output_surf = res
-        .map(|_| SharedImageSurface::wrap(output, SurfaceType::SRgb).unwrap())
-        .unwrap();
+#[test]
+fn streaming_loader_accepts_data_in_chunks() {
+    let data = br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10">
+  <rect id="foo" width="10" height="10"/>
+</svg>
+"##;
 
-    let reference_surf = cairo::ImageSurface::create(cairo::Format::ARgb32, 300, 300).unwrap();
+    let mut loader = Loader::new().streaming(None);
+    for chunk in data.chunks(7) {
+        loader.push_bytes(chunk);
+    }
 
-    {
-        let cr = cairo::Context::new(&reference_surf).expect("Failed to create a cairo context");
+    let svg = loader.finish().unwrap();
+    assert!(svg.has_element_with_id("#foo").unwrap());
+}
 
-        cr.translate(100.0, 100.0);
+#[test]
+fn streaming_loader_decompresses_gzipped_chunks() {
+    let data = std::fs::read("tests/fixtures/api/gzipped-lime.svgz").unwrap();
 
-        cr.rectangle(20.0, 20.0, 30.0, 30.0);
-        cr.set_source_rgba(0.0, 0.0, 1.0, 1.0);
-        cr.fill().unwrap();
+    let mut loader = Loader::new().streaming(None);
+    for chunk in data.chunks(1) {
+        loader.push_bytes(chunk);
     }
 
-    Reference::from_surface(reference_surf)
-        .compare(&output_surf)
-        .evaluate(&output_surf, "render_layer");
+    let svg = loader.finish().unwrap();
+    assert!(svg.has_element_with_id("#lime-rect").unwrap());
 }
 
 #[test]
-fn untransformed_element() {
-    // This has a rectangle inside a transformed group.  The rectangle
-    // inherits its stroke-width from the group.
-    //
-    // The idea is that we'll be able to extract the geometry of the rectangle
-    // as if it were not transformed by its ancestors, but still retain the
-    // cascade from the ancestors.
-    let svg = load_svg(
-        br##"<?xml version="1.0" encoding="UTF-8"?>
-<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
-  <g transform="rotate(45)" stroke-width="10" stroke="#000000">
-    <rect id="foo" x="10" y="20" width="30" height="40" fill="#0000ff"/>
-  </g>
+fn streaming_loader_rejects_truncated_gzipped_data() {
+    let data = std::fs::read("tests/fixtures/api/gzipped-lime.svgz").unwrap();
+
+    let mut loader = Loader::new().streaming(None);
+    loader.push_bytes(&data[..data.len() / 2]);
+
+    assert!(loader.finish().is_err());
+}
+
+#[test]
+fn streaming_loader_reports_error_from_an_early_chunk() {
+    let mut loader = Loader::new().streaming(None);
+    loader.push_bytes(b"<svg xmlns=\"http://www.w3.org/2000/svg\"><rect></circle>");
+    loader.push_bytes(b"</svg>");
+
+    assert!(matches!(
+        loader.finish(),
+        Err(LoadingError::XmlParseError(_))
+    ));
+}
+
+#[test]
+fn streaming_loader_rejects_empty_input() {
+    assert!(matches!(
+        Loader::new().streaming(None).finish(),
+        Err(LoadingError::XmlParseError(_))
+    ));
+}
+
+#[test]
+fn read_bytes_loads_document_from_memory() {
+    let data = br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10">
+  <rect id="foo" width="10" height="10"/>
 </svg>
-"##,
-    )
-    .unwrap();
+"##;
 
-    let renderer = CairoRenderer::new(&svg);
+    let svg = Loader::new().read_bytes(data).unwrap();
+    assert!(svg.has_element_with_id("#foo").unwrap());
 
-    /* Measuring */
+    assert!(matches!(
+        Loader::new().read_bytes(b"<svg"),
+        Err(LoadingError::XmlParseError(_))
+    ));
 
-    let (ink_r, logical_r) = renderer.geometry_for_element(Some("#foo")).unwrap();
+    assert!(matches!(
+        Loader::new().read_bytes(b""),
+        Err(LoadingError::XmlParseError(_))
+    ));
+}
 
-    assert_eq!(ink_r, cairo::Rectangle::new(0.0, 0.0, 40.0, 50.0));
+fn render_image_reference(loader: Loader) -> SharedImageSurface {
+    let data = br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10">
+  <image href="lime.png" width="10" height="10"/>
+</svg>
+"##;
 
-    assert_eq!(logical_r, cairo::Rectangle::new(5.0, 5.0, 30.0, 40.0));
+    let svg = loader.read_bytes(data).unwrap();
 
-    /* Rendering */
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 10, 10).unwrap();
 
-    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 300, 300).unwrap();
+    {
+        let cr = cairo::Context::new(&output).unwrap();
+        let viewport = cairo::Rectangle::new(0.0, 0.0, 10.0, 10.0);
+        CairoRenderer::new(&svg)
+            .render_document(&cr, &viewport)
+            .unwrap();
+    }
 
-    let res = {
-        let cr = cairo::Context::new(&output).expect("Failed to create cairo context");
-        let viewport = cairo::Rectangle::new(100.0, 100.0, 100.0, 100.0);
+    SharedImageSurface::wrap(output, SurfaceType::SRgb).unwrap()
+}
 
-        renderer.render_element(&cr, Some("#foo"), &viewport)
-    };
+#[test]
+fn read_bytes_does_not_load_external_files() {
+    let base_url = gio::File::for_path("tests/fixtures/api/read-data.svg").uri();
+    let loader = Loader::new().with_base_url(&base_url).unwrap();
 
-    let output_surf = res
-        .map(|_| SharedImageSurface::wrap(output, SurfaceType::SRgb).unwrap())
+    let output_surf = render_image_reference(loader);
+    assert_eq!(output_surf.get_pixel(5, 5).a, 0);
+}
+
+#[test]
+fn read_bytes_gets_references_from_resource_callback() {
+    let png = std::fs::read("tests/fixtures/api/lime.png").unwrap();
+
+    let loader = Loader::new()
+        .with_base_url("https://example.com/doc.svg")
+        .unwrap()
+        .with_resource_callback(Box::new(move |url| {
+            assert_eq!(url, "https://example.com/lime.png");
+            Ok(glib::Bytes::from(png.as_slice()))
+        }));
+
+    let output_surf = render_image_reference(loader);
+    let pixel = output_surf.get_pixel(5, 5);
+    assert_eq!((pixel.r, pixel.g, pixel.b, pixel.a), (0, 255, 0, 255));
+}
+
+#[test]
+fn nested_css_imports_use_the_resource_callback() {
+    let loader = Loader::new()
+        .with_base_url("https://example.com/doc.svg")
+        .unwrap()
+        .with_resource_callback(Box::new(|url| match url {
+            "https://example.com/a.css" => Ok(glib::Bytes::from_static(b"@import url(b.css);")),
+            "https://example.com/b.css" => Ok(glib::Bytes::from_static(b"rect { fill: lime; }")),
+            _ => Err(rsvg::ResolveError::Denied),
+        }));
+
+    let svg = loader
+        .read_data(
+            br#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10">
+  <style>@import url(a.css);</style>
+  <rect width="10" height="10" fill="red"/>
+</svg>
+"#,
+            None,
+        )
         .unwrap();
 
-    let reference_surf = cairo::ImageSurface::create(cairo::Format::ARgb32, 300, 300).unwrap();
+    let output_surf = render_to_pixels(&svg, 10, 10);
+    let pixel = output_surf.get_pixel(5, 5);
+    assert_eq!((pixel.r, pixel.g, pixel.b, pixel.a), (0, 255, 0, 255));
+}
 
-    {
-        let cr = cairo::Context::new(&reference_surf).expect("Failed to create a cairo context");
+#[test]
+fn collect_warnings_reports_invalid_attributes() {
+    let data = br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10">
+  <rect width="foo" height="10"/>
+</svg>
+"##;
 
-        cr.translate(100.0, 100.0);
+    let svg = Loader::new()
+        .collect_warnings()
+        .read_data(data, None)
+        .unwrap();
+    let warnings = svg.warnings();
 
-        cr.rectangle(10.0, 10.0, 60.0, 80.0);
-        cr.set_source_rgba(0.0, 0.0, 1.0, 1.0);
-        cr.fill_preserve().unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].element, "rect");
+    assert_eq!(warnings[0].attribute, "width");
 
-        cr.set_line_width(20.0);
-        cr.set_source_rgba(0.0, 0.0, 0.0, 1.0);
-        cr.stroke().unwrap();
+    let svg = Loader::new().read_data(data, None).unwrap();
+    assert!(svg.warnings().is_empty());
+}
+
+#[test]
+fn strict_mode_fails_on_unknown_filter_primitive() {
+    let data = br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10">
+  <title>Unknown primitive</title>
+  <filter id="filter">
+    <feFoo/>
+  </filter>
+  <rect width="10" height="10" filter="url(#filter)"/>
+</svg>
+"##;
+
+    match Loader::new().strict().read_data(data, None) {
+        Err(LoadingError::Unsupported { detail }) => assert!(detail.contains("feFoo")),
+        Err(e) => panic!("unexpected error: {e}"),
+        Ok(_) => panic!("strict mode should reject feFoo"),
     }
 
-    Reference::from_surface(reference_surf)
-        .compare(&output_surf)
-        .evaluate(&output_surf, "untransformed_element");
+    assert!(Loader::new().read_data(data, None).is_ok());
 }
 
 #[test]
-fn set_stylesheet() {
-    // This has a rectangle which we style from a user-supplied stylesheet.
-    let mut svg = load_svg(
-        br##"<?xml version="1.0" encoding="UTF-8"?>
-<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
-  <rect id="foo" x="10" y="20" width="30" height="40" fill="black"/>
+fn strict_mode_fails_on_invalid_stylesheet_declaration() {
+    let data = br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10">
+  <style>
+    rect { fill: lime; stroke-width: bogus; }
+  </style>
 </svg>
-"##,
-    )
-    .unwrap();
+"##;
 
-    svg.set_stylesheet("rect { fill: #00ff00; }")
-        .expect("should be a valid stylesheet");
+    match Loader::new().strict().read_data(data, None) {
+        Err(LoadingError::Unsupported { detail }) => assert!(detail.contains("stroke-width")),
+        Err(e) => panic!("unexpected error: {e}"),
+        Ok(_) => panic!("strict mode should reject the invalid declaration"),
+    }
+}
+
+#[test]
+fn strict_mode_accepts_supported_documents() {
+    let data = br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10">
+  <desc>Only supported features</desc>
+  <rect width="10" height="10" style="fill: lime"/>
+</svg>
+"##;
+
+    assert!(Loader::new().strict().read_data(data, None).is_ok());
+}
+
+#[test]
+fn element_ids_are_in_document_order() {
+    let data = br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" id="root" width="10" height="10">
+  <defs>
+    <rect id="template" width="10" height="10"/>
+  </defs>
+  <g id="group">
+    <circle id="dot" r="1"/>
+    <rect/>
+    <rect id="dot" width="2" height="2"/>
+  </g>
+</svg>
+"##;
+
+    let svg = Loader::new().read_data(data, None).unwrap();
+
+    assert_eq!(
+        svg.element_ids(),
+        vec!["#root", "#template", "#group", "#dot"]
+    );
+}
+
+#[test]
+fn read_data_resolves_references_from_base_file() {
+    let data = br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10">
+  <image href="lime.png" width="10" height="10"/>
+</svg>
+"##;
+
+    let base_file = gio::File::for_path("tests/fixtures/api/read-data.svg");
+    let svg = Loader::new().read_data(data, Some(&base_file)).unwrap();
 
     let renderer = CairoRenderer::new(&svg);
 
-    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 10, 10).unwrap();
 
     let res = {
         let cr = cairo::Context::new(&output).expect("Failed to create cairo context");
-        let viewport = cairo::Rectangle::new(0.0, 0.0, 100.0, 100.0);
+        let viewport = cairo::Rectangle::new(0.0, 0.0, 10.0, 10.0);
 
         renderer.render_document(&cr, &viewport)
     };
@@ -175,87 +352,2586 @@ fn set_stylesheet() {
         .map(|_| SharedImageSurface::wrap(output, SurfaceType::SRgb).unwrap())
         .unwrap();
 
-    let reference_surf = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+    let reference_surf = cairo::ImageSurface::create(cairo::Format::ARgb32, 10, 10).unwrap();
 
     {
         let cr = cairo::Context::new(&reference_surf).expect("Failed to create a cairo context");
 
-        cr.rectangle(10.0, 20.0, 30.0, 40.0);
         cr.set_source_rgba(0.0, 1.0, 0.0, 1.0);
-        cr.fill().unwrap();
+        cr.paint().unwrap();
     }
 
     Reference::from_surface(reference_surf)
         .compare(&output_surf)
-        .evaluate(&output_surf, "set_stylesheet");
+        .evaluate(&output_surf, "read_data_resolves_references_from_base_file");
 }
 
-// https://gitlab.gnome.org/GNOME/librsvg/-/issues/799
 #[test]
-fn text_doesnt_leave_points_in_current_path() {
-    let svg = load_svg(
-        br##"<?xml version="1.0" encoding="UTF-8"?>
-<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
-  <text>Hello world!</text>
+fn xinclude_decompresses_gzipped_svg() {
+    let data = br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xi="http://www.w3.org/2001/XInclude"
+     width="10" height="10">
+  <xi:include href="gzipped-lime.svgz"/>
 </svg>
-"##,
-    )
-    .unwrap();
+"##;
 
-    let renderer = CairoRenderer::new(&svg);
+    let base_file = gio::File::for_path("tests/fixtures/api/read-data.svg");
+    let svg = Loader::new().read_data(data, Some(&base_file)).unwrap();
 
-    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
-    let cr = cairo::Context::new(&output).unwrap();
+    assert!(svg.has_element_with_id("#lime-rect").unwrap());
+}
 
-    assert!(!cr.has_current_point().unwrap());
+fn referenced(href: &str, kind: ReferenceKind, allowed: bool) -> ReferencedUrl {
+    ReferencedUrl {
+        href: href.to_string(),
+        kind,
+        allowed,
+    }
+}
 
-    let viewport = cairo::Rectangle::new(0.0, 0.0, 100.0, 100.0);
+#[test]
+fn referenced_urls_lists_images_and_checks_them() {
+    let data = br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10">
+  <image href="data:image/png;base64,iVBORw0KGgo=" width="10" height="10"/>
+  <image href="lime.png" width="10" height="10"/>
+  <image href="http://example.com/remote.png" width="10" height="10"/>
+  <image href="lime.png" width="10" height="10"/>
+</svg>
+"##;
 
-    renderer.render_document(&cr, &viewport).unwrap();
+    let base_file = gio::File::for_path("tests/fixtures/api/read-data.svg");
+    let svg = Loader::new().read_data(data, Some(&base_file)).unwrap();
 
-    assert!(!cr.has_current_point().unwrap());
+    assert_eq!(
+        svg.referenced_urls(),
+        vec![
+            referenced(
+                "data:image/png;base64,iVBORw0KGgo=",
+                ReferenceKind::Image,
+                true
+            ),
+            referenced("lime.png", ReferenceKind::Image, true),
+            referenced("http://example.com/remote.png", ReferenceKind::Image, false),
+        ]
+    );
 }
 
 #[test]
-fn cancellation_works() {
-    let svg = load_svg(
-        br##"<?xml version="1.0" encoding="UTF-8"?>
-<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
-  <rect x="0" y="0" width="100%" height="100%" fill="blue"/>
+fn referenced_urls_includes_load_time_and_external_element_references() {
+    let data = br##"<?xml version="1.0" encoding="UTF-8"?>
+<?xml-stylesheet href="missing.css" type="text/css"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xi="http://www.w3.org/2001/XInclude"
+     xmlns:xlink="http://www.w3.org/1999/xlink" width="10" height="10">
+  <defs>
+    <rect id="internal" width="1" height="1"/>
+  </defs>
+  <xi:include href="gzipped-lime.svgz"/>
+  <use xlink:href="#internal"/>
+  <use xlink:href="nested-svg-inner.svg#inner"/>
+  <g fill="url(#internal)" filter="url(http://example.com/filters.svg#blur)">
+    <rect width="10" height="10"/>
+  </g>
 </svg>
-"##,
-    )
-    .unwrap();
+"##;
 
-    // To test cancellation, we'll start out by creating a cancellable and a renderer, and
-    // immediately cancelling the operation.  Then we'll start rendering.  In theory this
-    // will cause nothing to be rendered.
+    let base_file = gio::File::for_path("tests/fixtures/api/read-data.svg");
+    let svg = Loader::new().read_data(data, Some(&base_file)).unwrap();
 
-    let cancellable = gio::Cancellable::new();
+    assert_eq!(
+        svg.referenced_urls(),
+        vec![
+            referenced("missing.css", ReferenceKind::Stylesheet, false),
+            referenced("gzipped-lime.svgz", ReferenceKind::XInclude, true),
+            referenced("nested-svg-inner.svg", ReferenceKind::Use, true),
+            referenced(
+                "http://example.com/filters.svg",
+                ReferenceKind::Property,
+                false
+            ),
+        ]
+    );
+}
 
-    let renderer = CairoRenderer::new(&svg).with_cancellable(&cancellable);
-    cancellable.cancel();
+#[test]
+fn referenced_urls_does_not_call_the_resource_callback() {
+    let calls = Arc::new(Mutex::new(0));
 
-    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+    let base_file = gio::File::for_path("tests/fixtures/api/read-data.svg");
+    let svg = Loader::new()
+        .with_resource_callback({
+            let calls = calls.clone();
+            Box::new(move |_url| {
+                *calls.lock().unwrap() += 1;
+                Err(rsvg::ResolveError::Denied)
+            })
+        })
+        .read_data(
+            br#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10">
+  <image href="lime.png" width="10" height="10"/>
+</svg>
+"#,
+            Some(&base_file),
+        )
+        .unwrap();
+
+    // The rules alone allow lime.png, even though the callback would deny it.
+    assert_eq!(
+        svg.referenced_urls(),
+        vec![referenced("lime.png", ReferenceKind::Image, true)]
+    );
+    assert_eq!(*calls.lock().unwrap(), 0);
+}
+
+#[test]
+fn image_element_decompresses_gzipped_svg() {
+    let data = br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10">
+  <image href="gzipped-lime.svgz" width="10" height="10"/>
+</svg>
+"##;
+
+    let base_file = gio::File::for_path("tests/fixtures/api/read-data.svg");
+    let svg = Loader::new().read_data(data, Some(&base_file)).unwrap();
+
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 10, 10).unwrap();
 
     {
         let cr = cairo::Context::new(&output).unwrap();
+        let viewport = cairo::Rectangle::new(0.0, 0.0, 10.0, 10.0);
+        CairoRenderer::new(&svg)
+            .render_document(&cr, &viewport)
+            .unwrap();
+    }
+
+    let output_surf = SharedImageSurface::wrap(output, SurfaceType::SRgb).unwrap();
+    let pixel = output_surf.get_pixel(5, 5);
+    assert_eq!((pixel.r, pixel.g, pixel.b, pixel.a), (0, 255, 0, 255));
+}
+
+#[test]
+fn image_element_renders_referenced_svg_as_vectors() {
+    // The inner SVG is 20x10 with a lime square on its right half.  It gets scaled up to
+    // fit the 100x100 <image> with the default "xMidYMid meet", so it occupies the
+    // band from y=25 to y=75.
+    let data = br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <image href="nested-svg-inner.svg" width="100" height="100"/>
+</svg>
+"##;
+
+    let base_file = gio::File::for_path("tests/fixtures/api/nested-svg-outer.svg");
+    let svg = Loader::new().read_data(data, Some(&base_file)).unwrap();
+
+    let renderer = CairoRenderer::new(&svg);
+
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+
+    {
+        let cr = cairo::Context::new(&output).expect("Failed to create cairo context");
         let viewport = cairo::Rectangle::new(0.0, 0.0, 100.0, 100.0);
 
-        // Test that cancellation happens...
-        assert!(matches!(
-            renderer.render_document(&cr, &viewport),
-            Err(RenderingError::Cancelled)
-        ));
+        renderer.render_document(&cr, &viewport).unwrap();
     }
 
     let output_surf = SharedImageSurface::wrap(output, SurfaceType::SRgb).unwrap();
 
-    // ... and test that we got an empty surface, since hopefully cancellation occurred
-    // before actually rendering anything.
-    let reference_surf = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+    let inside = output_surf.get_pixel(75, 50);
+    assert_eq!((inside.r, inside.g, inside.b, inside.a), (0, 255, 0, 255));
 
-    Reference::from_surface(reference_surf)
-        .compare(&output_surf)
-        .evaluate(&output_surf, "cancellation_works");
+    // The edges are sharp, since the inner SVG is rendered at the final size instead
+    // of being rasterized at 20x10 and scaled.
+    assert_eq!(output_surf.get_pixel(50, 50).a, 255);
+    assert_eq!(output_surf.get_pixel(49, 50).a, 0);
+
+    assert_eq!(output_surf.get_pixel(75, 24).a, 0);
+    assert_eq!(output_surf.get_pixel(75, 75).a, 0);
+}
+
+#[test]
+fn element_at_point_returns_frontmost_element() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect id="back" x="10" y="10" width="50" height="50" fill="#0000ff"/>
+  <rect id="front" x="30" y="30" width="50" height="50" fill="#00ff00"/>
+  <g id="group">
+    <rect x="85" y="0" width="10" height="10" fill="#ff0000"/>
+  </g>
+</svg>
+"##,
+    )
+    .unwrap();
+
+    let renderer = CairoRenderer::new(&svg);
+    let viewport = cairo::Rectangle::new(0.0, 0.0, 100.0, 100.0);
+
+    assert_eq!(
+        renderer.element_at_point(&viewport, 40.0, 40.0).as_deref(),
+        Some("#front")
+    );
+    assert_eq!(
+        renderer.element_at_point(&viewport, 20.0, 20.0).as_deref(),
+        Some("#back")
+    );
+    assert_eq!(
+        renderer.element_at_point(&viewport, 90.0, 5.0).as_deref(),
+        Some("#group")
+    );
+    assert_eq!(renderer.element_at_point(&viewport, 5.0, 95.0), None);
+}
+
+#[test]
+fn element_at_point_uses_painted_area_not_bounding_box() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect id="back" x="0" y="0" width="100" height="100" fill="#0000ff"/>
+  <circle id="circle" cx="50" cy="50" r="40" fill="#00ff00"/>
+  <rect id="hidden" x="40" y="40" width="20" height="20" visibility="hidden"/>
+</svg>
+"##,
+    )
+    .unwrap();
+
+    let renderer = CairoRenderer::new(&svg);
+    let viewport = cairo::Rectangle::new(0.0, 0.0, 100.0, 100.0);
+
+    // Inside the circle's bounding box, but outside the circle.
+    assert_eq!(
+        renderer.element_at_point(&viewport, 15.0, 15.0).as_deref(),
+        Some("#back")
+    );
+    // Under the hidden rect.
+    assert_eq!(
+        renderer.element_at_point(&viewport, 50.0, 50.0).as_deref(),
+        Some("#circle")
+    );
+}
+
+#[test]
+fn render_layer() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect id="foo" x="10" y="10" width="30" height="30" fill="#00ff00"/>
+  <rect id="bar" x="20" y="20" width="30" height="30" fill="#0000ff"/>
+</svg>
+"##,
+    )
+    .unwrap();
+
+    let renderer = CairoRenderer::new(&svg);
+
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 300, 300).unwrap();
+
+    let res = {
+        let cr = cairo::Context::new(&output).expect("Failed to create cairo context");
+        let viewport = cairo::Rectangle::new(100.0, 100.0, 100.0, 100.0);
+
+        renderer.render_layer(&cr, Some("#bar"), &viewport)
+    };
+
+    let output_surf = res
+        .map(|_| SharedImageSurface::wrap(output, SurfaceType::SRgb).unwrap())
+        .unwrap();
+
+    let reference_surf = cairo::ImageSurface::create(cairo::Format::ARgb32, 300, 300).unwrap();
+
+    {
+        let cr = cairo::Context::new(&reference_surf).expect("Failed to create a cairo context");
+
+        cr.translate(100.0, 100.0);
+
+        cr.rectangle(20.0, 20.0, 30.0, 30.0);
+        cr.set_source_rgba(0.0, 0.0, 1.0, 1.0);
+        cr.fill().unwrap();
+    }
+
+    Reference::from_surface(reference_surf)
+        .compare(&output_surf)
+        .evaluate(&output_surf, "render_layer");
+}
+
+#[test]
+fn try_with_dpi_rejects_invalid_values() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="1in" height="1in"/>
+"#,
+    )
+    .unwrap();
+
+    let renderer = CairoRenderer::new(&svg).try_with_dpi(20.0, 20.0).unwrap();
+    assert_eq!(renderer.intrinsic_size_in_pixels(), Some((20.0, 20.0)));
+
+    for (x, y) in [
+        (0.0, 96.0),
+        (96.0, f64::NAN),
+        (-1.0, 96.0),
+        (f64::INFINITY, 96.0),
+    ] {
+        assert!(matches!(
+            CairoRenderer::new(&svg).try_with_dpi(x, y),
+            Err(RenderingError::InvalidDpi(..))
+        ));
+    }
+}
+
+#[test]
+fn with_options_paints_background_color() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="1in" height="1in" viewBox="0 0 10 10">
+  <rect x="0" y="0" width="5" height="10" fill="#00ff00"/>
+</svg>
+"##,
+    )
+    .unwrap();
+
+    let mut options = RenderingOptions::default();
+    options.dpi_x = 20.0;
+    options.dpi_y = 20.0;
+    options.background_color = Some(parse_color("#0000ff").unwrap());
+
+    let renderer = CairoRenderer::new(&svg).with_options(options);
+
+    assert_eq!(renderer.intrinsic_size_in_pixels(), Some((20.0, 20.0)));
+
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 20, 20).unwrap();
+
+    let res = {
+        let cr = cairo::Context::new(&output).expect("Failed to create cairo context");
+        let viewport = cairo::Rectangle::new(0.0, 0.0, 20.0, 20.0);
+
+        renderer.render_document(&cr, &viewport)
+    };
+
+    let output_surf = res
+        .map(|_| SharedImageSurface::wrap(output, SurfaceType::SRgb).unwrap())
+        .unwrap();
+
+    let reference_surf = cairo::ImageSurface::create(cairo::Format::ARgb32, 20, 20).unwrap();
+
+    {
+        let cr = cairo::Context::new(&reference_surf).expect("Failed to create a cairo context");
+
+        cr.set_source_rgba(0.0, 0.0, 1.0, 1.0);
+        cr.paint().unwrap();
+
+        cr.rectangle(0.0, 0.0, 10.0, 20.0);
+        cr.set_source_rgba(0.0, 1.0, 0.0, 1.0);
+        cr.fill().unwrap();
+    }
+
+    Reference::from_surface(reference_surf)
+        .compare(&output_surf)
+        .evaluate(&output_surf, "with_options_paints_background_color");
+}
+
+#[test]
+fn with_background_color_paints_element_viewport() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect id="foo" x="10" y="10" width="20" height="20" fill="#00ff00"/>
+</svg>
+"##,
+    )
+    .unwrap();
+
+    let white = parse_color("white").unwrap();
+    let renderer = CairoRenderer::new(&svg).with_background_color(Some(white));
+
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+
+    let res = {
+        let cr = cairo::Context::new(&output).expect("Failed to create cairo context");
+        let viewport = cairo::Rectangle::new(50.0, 50.0, 40.0, 40.0);
+
+        renderer.render_element(&cr, Some("#foo"), &viewport)
+    };
+
+    let output_surf = res
+        .map(|_| SharedImageSurface::wrap(output, SurfaceType::SRgb).unwrap())
+        .unwrap();
+
+    let reference_surf = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+
+    {
+        let cr = cairo::Context::new(&reference_surf).expect("Failed to create a cairo context");
+
+        cr.rectangle(50.0, 50.0, 40.0, 40.0);
+        cr.set_source_rgba(1.0, 1.0, 1.0, 1.0);
+        cr.fill().unwrap();
+
+        cr.rectangle(50.0, 50.0, 40.0, 40.0);
+        cr.set_source_rgba(0.0, 1.0, 0.0, 1.0);
+        cr.fill().unwrap();
+    }
+
+    Reference::from_surface(reference_surf)
+        .compare(&output_surf)
+        .evaluate(
+            &output_surf,
+            "with_background_color_paints_element_viewport",
+        );
+}
+
+#[test]
+fn no_background_color_leaves_surface_transparent() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10"/>
+"##,
+    )
+    .unwrap();
+
+    let renderer = CairoRenderer::new(&svg).with_background_color(None);
+
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 10, 10).unwrap();
+
+    {
+        let cr = cairo::Context::new(&output).expect("Failed to create cairo context");
+        let viewport = cairo::Rectangle::new(0.0, 0.0, 10.0, 10.0);
+
+        renderer.render_document(&cr, &viewport).unwrap();
+    }
+
+    let output_surf = SharedImageSurface::wrap(output, SurfaceType::SRgb).unwrap();
+
+    let reference_surf = cairo::ImageSurface::create(cairo::Format::ARgb32, 10, 10).unwrap();
+
+    Reference::from_surface(reference_surf)
+        .compare(&output_surf)
+        .evaluate(
+            &output_surf,
+            "no_background_color_leaves_surface_transparent",
+        );
+}
+
+#[test]
+fn render_to_shared_surface_gives_premultiplied_pixels() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="20" height="10" viewBox="0 0 20 10">
+  <rect x="0" y="0" width="10" height="10" fill="red"/>
+  <rect x="10" y="0" width="10" height="10" fill="blue" fill-opacity="0.5"/>
+</svg>
+"##,
+    )
+    .unwrap();
+
+    let surface = CairoRenderer::new(&svg)
+        .render_to_shared_surface(40, 20)
+        .unwrap();
+
+    assert_eq!((surface.width(), surface.height()), (40, 20));
+    assert_eq!(Pixels::new(&surface).count(), 40 * 20);
+
+    let red = surface.get_pixel(5, 10);
+    assert_eq!((red.r, red.g, red.b, red.a), (255, 0, 0, 255));
+
+    let blue = surface.get_pixel(30, 10);
+    assert!((127..=128).contains(&blue.a));
+    assert_eq!(blue.b, blue.a);
+
+    let blue = blue.unpremultiply();
+    assert_eq!((blue.r, blue.g, blue.b), (0, 0, 255));
+}
+
+#[cfg(feature = "pixbuf")]
+#[test]
+fn render_to_pixbuf_fits_document_and_unpremultiplies() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="20" height="10" viewBox="0 0 20 10">
+  <rect x="0" y="0" width="10" height="10" fill="red"/>
+  <rect x="10" y="0" width="10" height="10" fill="blue" fill-opacity="0.5"/>
+</svg>
+"##,
+    )
+    .unwrap();
+
+    let pixbuf = CairoRenderer::new(&svg).render_to_pixbuf(40, 20).unwrap();
+
+    assert_eq!((pixbuf.width(), pixbuf.height()), (40, 20));
+    assert_eq!(pixbuf.n_channels(), 4);
+    assert!(pixbuf.has_alpha());
+
+    let bytes = pixbuf.read_pixel_bytes();
+    let stride = pixbuf.rowstride() as usize;
+    let pixel = |x: usize, y: usize| &bytes[y * stride + x * 4..y * stride + x * 4 + 4];
+
+    assert_eq!(pixel(5, 10), &[255, 0, 0, 255]);
+
+    let blue = pixel(30, 10);
+    assert_eq!(&blue[0..3], &[0, 0, 255]);
+    assert!((127..=128).contains(&blue[3]));
+}
+
+#[cfg(feature = "pixbuf")]
+#[test]
+fn render_to_pixbuf_rejects_empty_size() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10"/>
+"##,
+    )
+    .unwrap();
+
+    assert!(matches!(
+        CairoRenderer::new(&svg).render_to_pixbuf(0, 10),
+        Err(RenderingError::Cairo(cairo::Error::InvalidSize))
+    ));
+}
+
+/// Renders a 200x200 document in four 100x100 tiles and stitches them together.
+fn render_stitched_tiles(renderer: &CairoRenderer) -> cairo::ImageSurface {
+    let viewport = cairo::Rectangle::new(0.0, 0.0, 200.0, 200.0);
+
+    let stitched = cairo::ImageSurface::create(cairo::Format::ARgb32, 200, 200).unwrap();
+
+    {
+        let stitched_cr = cairo::Context::new(&stitched).expect("Failed to create cairo context");
+
+        for (x, y) in [(0.0, 0.0), (100.0, 0.0), (0.0, 100.0), (100.0, 100.0)] {
+            let tile = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+
+            {
+                let cr = cairo::Context::new(&tile).expect("Failed to create cairo context");
+                cr.translate(-x, -y);
+
+                // The document is rendered at twice the size of its viewBox.
+                let region = cairo::Rectangle::new(x / 2.0, y / 2.0, 50.0, 50.0);
+                renderer
+                    .render_document_region(&cr, &viewport, region)
+                    .unwrap();
+            }
+
+            stitched_cr.set_source_surface(&tile, x, y).unwrap();
+            stitched_cr.paint().unwrap();
+        }
+    }
+
+    stitched
+}
+
+fn render_full(renderer: &CairoRenderer) -> cairo::ImageSurface {
+    let viewport = cairo::Rectangle::new(0.0, 0.0, 200.0, 200.0);
+
+    let full = cairo::ImageSurface::create(cairo::Format::ARgb32, 200, 200).unwrap();
+
+    {
+        let cr = cairo::Context::new(&full).expect("Failed to create cairo context");
+        renderer.render_document(&cr, &viewport).unwrap();
+    }
+
+    full
+}
+
+fn surface_data(mut surface: cairo::ImageSurface) -> Vec<u8> {
+    surface.flush();
+    surface.data().unwrap().to_vec()
+}
+
+// The clip, the blur, and the offset shadow all cross the edges of the tiles; the shadow
+// of the red square is even in a different tile than the square itself.
+#[test]
+fn render_document_region_tiles_match_full_render() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="200" viewBox="0 0 100 100">
+  <defs>
+    <clipPath id="clip">
+      <circle cx="50" cy="50" r="30"/>
+    </clipPath>
+    <filter id="blur">
+      <feGaussianBlur stdDeviation="3"/>
+    </filter>
+    <filter id="shadow" x="-100%" y="-100%" width="300%" height="300%">
+      <feOffset dx="30" dy="30"/>
+      <feGaussianBlur stdDeviation="2"/>
+    </filter>
+  </defs>
+  <rect x="20" y="20" width="60" height="60" fill="#0000ff" clip-path="url(#clip)"/>
+  <rect x="40" y="40" width="20" height="20" fill="#00ff00" filter="url(#blur)"/>
+  <rect x="10" y="10" width="20" height="20" fill="#ff0000" filter="url(#shadow)"/>
+</svg>
+"##,
+    )
+    .unwrap();
+
+    let renderer = CairoRenderer::new(&svg);
+
+    let full = surface_data(render_full(&renderer));
+    let stitched = surface_data(render_stitched_tiles(&renderer));
+
+    assert!(
+        full == stitched,
+        "the stitched tiles are different from the full render"
+    );
+}
+
+#[test]
+fn render_document_region_paints_background() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="200" viewBox="0 0 100 100">
+  <rect x="25" y="25" width="50" height="50" fill="#0000ff" fill-opacity="0.5"/>
+</svg>
+"##,
+    )
+    .unwrap();
+
+    let renderer =
+        CairoRenderer::new(&svg).with_background_color(Some(parse_color("lime").unwrap()));
+
+    let full = surface_data(render_full(&renderer));
+    let stitched = surface_data(render_stitched_tiles(&renderer));
+
+    assert!(
+        full == stitched,
+        "the stitched tiles are different from the full render"
+    );
+}
+
+// The viewport is larger than what fits in a Cairo surface, but temporary surfaces for the
+// group with opacity and for the filter only need to cover the region being rendered.
+#[test]
+fn render_document_region_renders_tile_of_huge_viewport() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100" viewBox="0 0 100 100">
+  <defs>
+    <filter id="blur">
+      <feGaussianBlur stdDeviation="1"/>
+    </filter>
+  </defs>
+  <g opacity="0.5">
+    <rect width="100" height="100" fill="#00ff00"/>
+  </g>
+  <rect x="10" y="10" width="10" height="10" fill="#0000ff" filter="url(#blur)"/>
+</svg>
+"##,
+    )
+    .unwrap();
+
+    let renderer = CairoRenderer::new(&svg);
+
+    let viewport = cairo::Rectangle::new(0.0, 0.0, 40000.0, 40000.0);
+
+    let tile = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+
+    {
+        let cr = cairo::Context::new(&tile).expect("Failed to create cairo context");
+        cr.translate(-20000.0, -20000.0);
+
+        let region = cairo::Rectangle::new(50.0, 50.0, 0.25, 0.25);
+        renderer
+            .render_document_region(&cr, &viewport, region)
+            .unwrap();
+    }
+
+    let data = surface_data(tile);
+    // Half-transparent green, premultiplied.
+    let green = 0x8000_8000_u32.to_ne_bytes();
+    assert!(data.chunks(4).all(|pixel| pixel == green));
+}
+
+#[test]
+fn render_document_composes_with_current_transform() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="20" viewBox="0 0 40 20">
+  <rect x="0" y="0" width="40" height="20" fill="#00ff00"/>
+</svg>
+"##,
+    )
+    .unwrap();
+
+    let renderer = CairoRenderer::new(&svg);
+
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+
+    let res = {
+        let cr = cairo::Context::new(&output).expect("Failed to create cairo context");
+        cr.translate(50.0, 10.0);
+        cr.rotate(std::f64::consts::FRAC_PI_2);
+
+        renderer.render_document(&cr, &cairo::Rectangle::new(0.0, 0.0, 80.0, 40.0))
+    };
+
+    let output_surf = res
+        .map(|_| SharedImageSurface::wrap(output, SurfaceType::SRgb).unwrap())
+        .unwrap();
+
+    let reference_surf = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+
+    {
+        let cr = cairo::Context::new(&reference_surf).expect("Failed to create a cairo context");
+
+        cr.rectangle(10.0, 10.0, 40.0, 80.0);
+        cr.set_source_rgba(0.0, 1.0, 0.0, 1.0);
+        cr.fill().unwrap();
+    }
+
+    Reference::from_surface(reference_surf)
+        .compare(&output_surf)
+        .evaluate(
+            &output_surf,
+            "render_document_composes_with_current_transform",
+        );
+}
+
+fn render_switch_with_language(accept_language: &str) -> SharedImageSurface {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10">
+  <switch>
+    <rect systemLanguage="de" width="10" height="10" fill="#00ff00"/>
+    <rect systemLanguage="en" width="10" height="10" fill="#0000ff"/>
+  </switch>
+</svg>
+"##,
+    )
+    .unwrap();
+
+    let language = Language::AcceptLanguage(AcceptLanguage::parse(accept_language).unwrap());
+    let renderer = CairoRenderer::new(&svg).with_language(&language);
+
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 10, 10).unwrap();
+
+    {
+        let cr = cairo::Context::new(&output).expect("Failed to create cairo context");
+        let viewport = cairo::Rectangle::new(0.0, 0.0, 10.0, 10.0);
+
+        renderer.render_document(&cr, &viewport).unwrap();
+    }
+
+    SharedImageSurface::wrap(output, SurfaceType::SRgb).unwrap()
+}
+
+fn solid_color_surface(r: f64, g: f64, b: f64) -> cairo::ImageSurface {
+    let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, 10, 10).unwrap();
+
+    {
+        let cr = cairo::Context::new(&surface).expect("Failed to create a cairo context");
+        cr.set_source_rgba(r, g, b, 1.0);
+        cr.paint().unwrap();
+    }
+
+    surface
+}
+
+#[test]
+fn with_language_selects_switch_branch() {
+    let german = render_switch_with_language("de");
+    Reference::from_surface(solid_color_surface(0.0, 1.0, 0.0))
+        .compare(&german)
+        .evaluate(&german, "with_language_selects_switch_branch_de");
+
+    let english = render_switch_with_language("en");
+    Reference::from_surface(solid_color_surface(0.0, 0.0, 1.0))
+        .compare(&english)
+        .evaluate(&english, "with_language_selects_switch_branch_en");
+}
+
+#[test]
+fn render_document_with_transform() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect x="25" y="25" width="50" height="50" fill="#00ff00"/>
+</svg>
+"##,
+    )
+    .unwrap();
+
+    let renderer = CairoRenderer::new(&svg);
+
+    // Rotate by 45 degrees around the center of the viewport.
+    let mut transform = cairo::Matrix::identity();
+    transform.translate(50.0, 50.0);
+    transform.rotate(std::f64::consts::FRAC_PI_4);
+    transform.translate(-50.0, -50.0);
+
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 300, 300).unwrap();
+
+    let res = {
+        let cr = cairo::Context::new(&output).expect("Failed to create cairo context");
+        let viewport = cairo::Rectangle::new(100.0, 100.0, 100.0, 100.0);
+
+        renderer.render_document_with_transform(&cr, &viewport, transform)
+    };
+
+    let output_surf = res
+        .map(|_| SharedImageSurface::wrap(output, SurfaceType::SRgb).unwrap())
+        .unwrap();
+
+    let reference_surf = cairo::ImageSurface::create(cairo::Format::ARgb32, 300, 300).unwrap();
+
+    {
+        let cr = cairo::Context::new(&reference_surf).expect("Failed to create a cairo context");
+
+        cr.translate(150.0, 150.0);
+        cr.rotate(std::f64::consts::FRAC_PI_4);
+        cr.translate(-50.0, -50.0);
+
+        cr.rectangle(25.0, 25.0, 50.0, 50.0);
+        cr.set_source_rgba(0.0, 1.0, 0.0, 1.0);
+        cr.fill().unwrap();
+    }
+
+    Reference::from_surface(reference_surf)
+        .compare(&output_surf)
+        .evaluate(&output_surf, "render_document_with_transform");
+}
+
+#[test]
+fn render_document_with_transform_rejects_non_invertible_transform() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect x="25" y="25" width="50" height="50" fill="#00ff00"/>
+</svg>
+"##,
+    )
+    .unwrap();
+
+    let renderer = CairoRenderer::new(&svg);
+
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+    let cr = cairo::Context::new(&output).expect("Failed to create cairo context");
+    let viewport = cairo::Rectangle::new(0.0, 0.0, 100.0, 100.0);
+
+    let transform = cairo::Matrix::new(0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+
+    assert!(matches!(
+        renderer.render_document_with_transform(&cr, &viewport, transform),
+        Err(RenderingError::Rendering(_))
+    ));
+    assert!(cr.status().is_ok());
+}
+
+#[test]
+fn rendering_error_source_is_cairo_error() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect x="25" y="25" width="50" height="50" fill="#00ff00"/>
+</svg>
+"##,
+    )
+    .unwrap();
+
+    let renderer = CairoRenderer::new(&svg);
+
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+    let cr = cairo::Context::new(&output).expect("Failed to create cairo context");
+
+    // Put the context in an error state by restoring without a matching save.
+    assert!(cr.restore().is_err());
+
+    let viewport = cairo::Rectangle::new(0.0, 0.0, 100.0, 100.0);
+    let err = renderer.render_document(&cr, &viewport).unwrap_err();
+
+    assert!(matches!(
+        err,
+        RenderingError::Cairo(cairo::Error::InvalidRestore)
+    ));
+
+    let source = std::error::Error::source(&err).expect("error should have a source");
+    assert!(matches!(
+        source.downcast_ref::<cairo::Error>(),
+        Some(cairo::Error::InvalidRestore)
+    ));
+}
+
+#[test]
+fn untransformed_element() {
+    // This has a rectangle inside a transformed group.  The rectangle
+    // inherits its stroke-width from the group.
+    //
+    // The idea is that we'll be able to extract the geometry of the rectangle
+    // as if it were not transformed by its ancestors, but still retain the
+    // cascade from the ancestors.
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <g transform="rotate(45)" stroke-width="10" stroke="#000000">
+    <rect id="foo" x="10" y="20" width="30" height="40" fill="#0000ff"/>
+  </g>
+</svg>
+"##,
+    )
+    .unwrap();
+
+    let renderer = CairoRenderer::new(&svg);
+
+    /* Measuring */
+
+    let (ink_r, logical_r) = renderer.geometry_for_element(Some("#foo")).unwrap();
+
+    assert_eq!(ink_r, cairo::Rectangle::new(0.0, 0.0, 40.0, 50.0));
+
+    assert_eq!(logical_r, cairo::Rectangle::new(5.0, 5.0, 30.0, 40.0));
+
+    /* Rendering */
+
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 300, 300).unwrap();
+
+    let res = {
+        let cr = cairo::Context::new(&output).expect("Failed to create cairo context");
+        let viewport = cairo::Rectangle::new(100.0, 100.0, 100.0, 100.0);
+
+        renderer.render_element(&cr, Some("#foo"), &viewport)
+    };
+
+    let output_surf = res
+        .map(|_| SharedImageSurface::wrap(output, SurfaceType::SRgb).unwrap())
+        .unwrap();
+
+    let reference_surf = cairo::ImageSurface::create(cairo::Format::ARgb32, 300, 300).unwrap();
+
+    {
+        let cr = cairo::Context::new(&reference_surf).expect("Failed to create a cairo context");
+
+        cr.translate(100.0, 100.0);
+
+        cr.rectangle(10.0, 10.0, 60.0, 80.0);
+        cr.set_source_rgba(0.0, 0.0, 1.0, 1.0);
+        cr.fill_preserve().unwrap();
+
+        cr.set_line_width(20.0);
+        cr.set_source_rgba(0.0, 0.0, 0.0, 1.0);
+        cr.stroke().unwrap();
+    }
+
+    Reference::from_surface(reference_surf)
+        .compare(&output_surf)
+        .evaluate(&output_surf, "untransformed_element");
+}
+
+#[test]
+fn render_element_filling_stretches_to_viewport() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect id="square" x="10" y="10" width="10" height="10" fill="#0000ff"/>
+</svg>
+"##,
+    )
+    .unwrap();
+
+    let renderer = CairoRenderer::new(&svg);
+    let viewport = cairo::Rectangle::new(0.0, 0.0, 40.0, 20.0);
+
+    for (filling, name) in [
+        (false, "render_element_proportional"),
+        (true, "render_element_filling"),
+    ] {
+        let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 40, 20).unwrap();
+
+        let res = {
+            let cr = cairo::Context::new(&output).expect("Failed to create cairo context");
+
+            if filling {
+                renderer.render_element_filling(&cr, Some("#square"), &viewport)
+            } else {
+                renderer.render_element(&cr, Some("#square"), &viewport)
+            }
+        };
+
+        let output_surf = res
+            .map(|_| SharedImageSurface::wrap(output, SurfaceType::SRgb).unwrap())
+            .unwrap();
+
+        let reference_surf = cairo::ImageSurface::create(cairo::Format::ARgb32, 40, 20).unwrap();
+
+        {
+            let cr =
+                cairo::Context::new(&reference_surf).expect("Failed to create a cairo context");
+
+            // The proportional case leaves the right half of the viewport empty.
+            let width = if filling { 40.0 } else { 20.0 };
+
+            cr.rectangle(0.0, 0.0, width, 20.0);
+            cr.set_source_rgba(0.0, 0.0, 1.0, 1.0);
+            cr.fill().unwrap();
+        }
+
+        Reference::from_surface(reference_surf)
+            .compare(&output_surf)
+            .evaluate(&output_surf, name);
+    }
+}
+
+#[test]
+fn set_stylesheet() {
+    // This has a rectangle which we style from a user-supplied stylesheet.
+    let mut svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect id="foo" x="10" y="20" width="30" height="40" fill="black"/>
+</svg>
+"##,
+    )
+    .unwrap();
+
+    svg.set_stylesheet("rect { fill: #00ff00; }")
+        .expect("should be a valid stylesheet");
+
+    let renderer = CairoRenderer::new(&svg);
+
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+
+    let res = {
+        let cr = cairo::Context::new(&output).expect("Failed to create cairo context");
+        let viewport = cairo::Rectangle::new(0.0, 0.0, 100.0, 100.0);
+
+        renderer.render_document(&cr, &viewport)
+    };
+
+    let output_surf = res
+        .map(|_| SharedImageSurface::wrap(output, SurfaceType::SRgb).unwrap())
+        .unwrap();
+
+    let reference_surf = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+
+    {
+        let cr = cairo::Context::new(&reference_surf).expect("Failed to create a cairo context");
+
+        cr.rectangle(10.0, 20.0, 30.0, 40.0);
+        cr.set_source_rgba(0.0, 1.0, 0.0, 1.0);
+        cr.fill().unwrap();
+    }
+
+    Reference::from_surface(reference_surf)
+        .compare(&output_surf)
+        .evaluate(&output_surf, "set_stylesheet");
+}
+
+#[test]
+fn add_stylesheet_applies_all_stylesheets_in_order() {
+    let mut svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect id="a" x="10" y="10" width="30" height="30" fill="black"/>
+  <rect id="b" x="50" y="50" width="30" height="30" fill="black"/>
+</svg>
+"##,
+    )
+    .unwrap();
+
+    svg.add_stylesheet(
+        "#a { fill: #ff0000; } #b { fill: #0000ff; }",
+        StylesheetOrigin::User,
+    )
+    .expect("should be a valid stylesheet");
+    svg.add_stylesheet("#a { fill: #00ff00; }", StylesheetOrigin::User)
+        .expect("should be a valid stylesheet");
+
+    let renderer = CairoRenderer::new(&svg);
+
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+
+    let res = {
+        let cr = cairo::Context::new(&output).expect("Failed to create cairo context");
+        let viewport = cairo::Rectangle::new(0.0, 0.0, 100.0, 100.0);
+
+        renderer.render_document(&cr, &viewport)
+    };
+
+    let output_surf = res
+        .map(|_| SharedImageSurface::wrap(output, SurfaceType::SRgb).unwrap())
+        .unwrap();
+
+    let reference_surf = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+
+    {
+        let cr = cairo::Context::new(&reference_surf).expect("Failed to create a cairo context");
+
+        cr.rectangle(10.0, 10.0, 30.0, 30.0);
+        cr.set_source_rgba(0.0, 1.0, 0.0, 1.0);
+        cr.fill().unwrap();
+
+        cr.rectangle(50.0, 50.0, 30.0, 30.0);
+        cr.set_source_rgba(0.0, 0.0, 1.0, 1.0);
+        cr.fill().unwrap();
+    }
+
+    Reference::from_surface(reference_surf)
+        .compare(&output_surf)
+        .evaluate(
+            &output_surf,
+            "add_stylesheet_applies_all_stylesheets_in_order",
+        );
+}
+
+#[test]
+fn add_stylesheet_updates_filters_from_previous_renders() {
+    let mut svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="40" height="40">
+  <filter id="filter" x="0" y="0" width="1" height="1">
+    <feFlood flood-color="#ff0000"/>
+  </filter>
+  <rect x="10" y="10" width="20" height="20" filter="url(#filter)"/>
+</svg>
+"##,
+    )
+    .unwrap();
+
+    let render = |svg: &rsvg::SvgHandle| {
+        let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 40, 40).unwrap();
+
+        {
+            let cr = cairo::Context::new(&output).expect("Failed to create cairo context");
+            let viewport = cairo::Rectangle::new(0.0, 0.0, 40.0, 40.0);
+
+            CairoRenderer::new(svg)
+                .render_document(&cr, &viewport)
+                .unwrap();
+        }
+
+        SharedImageSurface::wrap(output, SurfaceType::SRgb).unwrap()
+    };
+
+    // The first render resolves the filter with a red flood...
+    let _ = render(&svg);
+
+    // ... and the new stylesheet must not be ignored by later renders.
+    svg.add_stylesheet(
+        "feFlood { flood-color: #00ff00; }",
+        StylesheetOrigin::Author,
+    )
+    .expect("should be a valid stylesheet");
+
+    let output_surf = render(&svg);
+
+    let reference_surf = cairo::ImageSurface::create(cairo::Format::ARgb32, 40, 40).unwrap();
+
+    {
+        let cr = cairo::Context::new(&reference_surf).expect("Failed to create a cairo context");
+
+        cr.rectangle(10.0, 10.0, 20.0, 20.0);
+        cr.set_source_rgba(0.0, 1.0, 0.0, 1.0);
+        cr.fill().unwrap();
+    }
+
+    Reference::from_surface(reference_surf)
+        .compare(&output_surf)
+        .evaluate(
+            &output_surf,
+            "add_stylesheet_updates_filters_from_previous_renders",
+        );
+}
+
+// https://gitlab.gnome.org/GNOME/librsvg/-/issues/799
+#[cfg(feature = "text")]
+#[test]
+fn text_doesnt_leave_points_in_current_path() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <text>Hello world!</text>
+</svg>
+"##,
+    )
+    .unwrap();
+
+    let renderer = CairoRenderer::new(&svg);
+
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+    let cr = cairo::Context::new(&output).unwrap();
+
+    assert!(!cr.has_current_point().unwrap());
+
+    let viewport = cairo::Rectangle::new(0.0, 0.0, 100.0, 100.0);
+
+    renderer.render_document(&cr, &viewport).unwrap();
+
+    assert!(!cr.has_current_point().unwrap());
+}
+
+#[test]
+fn cancellation_works() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect x="0" y="0" width="100%" height="100%" fill="blue"/>
+</svg>
+"##,
+    )
+    .unwrap();
+
+    // To test cancellation, we'll start out by creating a cancellable and a renderer, and
+    // immediately cancelling the operation.  Then we'll start rendering.  In theory this
+    // will cause nothing to be rendered.
+
+    let cancellable = gio::Cancellable::new();
+
+    let renderer = CairoRenderer::new(&svg).with_cancellable(&cancellable);
+    cancellable.cancel();
+
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+
+    {
+        let cr = cairo::Context::new(&output).unwrap();
+        let viewport = cairo::Rectangle::new(0.0, 0.0, 100.0, 100.0);
+
+        // Test that cancellation happens...
+        assert!(matches!(
+            renderer.render_document(&cr, &viewport),
+            Err(RenderingError::Cancelled)
+        ));
+    }
+
+    let output_surf = SharedImageSurface::wrap(output, SurfaceType::SRgb).unwrap();
+
+    // ... and test that we got an empty surface, since hopefully cancellation occurred
+    // before actually rendering anything.
+    let reference_surf = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+
+    Reference::from_surface(reference_surf)
+        .compare(&output_surf)
+        .evaluate(&output_surf, "cancellation_works");
+}
+
+#[test]
+fn render_document_cancellable_stops_in_the_middle() {
+    // The document has a long row of one-pixel rectangles, with an image in the middle.
+    // Loading the image calls the resource callback while rendering is underway, and the
+    // callback cancels the rendering.  So, the rectangles before the image should get
+    // drawn, but not the ones after it.
+
+    const NUM_RECTS: usize = 2000;
+
+    let mut svg =
+        format!(r#"<svg xmlns="http://www.w3.org/2000/svg" width="{NUM_RECTS}" height="1">"#);
+
+    for i in 0..NUM_RECTS {
+        if i == NUM_RECTS / 2 {
+            svg.push_str(r#"<image href="https://example.com/cancel.png" width="1" height="1"/>"#);
+        }
+
+        svg.push_str(&format!(
+            r#"<rect x="{i}" width="1" height="1" fill="lime"/>"#
+        ));
+    }
+
+    svg.push_str("</svg>");
+
+    let cancellable = gio::Cancellable::new();
+
+    let handle = {
+        let cancellable = cancellable.clone();
+
+        Loader::new()
+            .with_resource_callback(Box::new(move |_url| {
+                cancellable.cancel();
+                Err(rsvg::ResolveError::Denied)
+            }))
+            .read_data(svg.as_bytes(), None)
+            .unwrap()
+    };
+
+    let renderer = CairoRenderer::new(&handle);
+
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, NUM_RECTS as i32, 1).unwrap();
+
+    {
+        let cr = cairo::Context::new(&output).unwrap();
+        let viewport = cairo::Rectangle::new(0.0, 0.0, NUM_RECTS as f64, 1.0);
+
+        assert!(matches!(
+            renderer.render_document_cancellable(&cr, &viewport, &cancellable),
+            Err(RenderingError::Cancelled)
+        ));
+    }
+
+    let output_surf = SharedImageSurface::wrap(output, SurfaceType::SRgb).unwrap();
+
+    assert_eq!(output_surf.get_pixel(0, 0).a, 255);
+    assert_eq!(output_surf.get_pixel(NUM_RECTS as u32 - 1, 0).a, 0);
+}
+
+fn render_with_limits(svg: &[u8], limits: RenderLimits) -> Result<(), RenderingError> {
+    let handle = Loader::new().read_bytes(svg).unwrap();
+    let renderer = CairoRenderer::new(&handle).with_render_limits(limits);
+
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+    let cr = cairo::Context::new(&output).unwrap();
+    let viewport = cairo::Rectangle::new(0.0, 0.0, 100.0, 100.0);
+
+    renderer.render_document(&cr, &viewport)
+}
+
+#[test]
+fn render_limits_are_unlimited_by_default() {
+    let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <filter id="filter">
+    <feFlood flood-color="lime"/>
+    <feOffset dx="10"/>
+  </filter>
+  <rect width="100" height="100" filter="url(#filter)"/>
+  <rect width="10" height="10"/>
+</svg>
+"#;
+
+    assert!(render_with_limits(svg, RenderLimits::default()).is_ok());
+}
+
+#[test]
+fn render_limits_stop_at_deadline_in_expensive_filter() {
+    // A big turbulence with many octaves, chained several times, takes much longer to
+    // render than the deadline.
+    let mut svg = String::from(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <filter id="filter" x="0" y="0" width="100" height="100" filterUnits="userSpaceOnUse">"#,
+    );
+
+    for _ in 0..50 {
+        svg.push_str(
+            r#"<feTurbulence baseFrequency="0.05" numOctaves="10"/><feComposite operator="arithmetic" k1="1"/>"#,
+        );
+    }
+
+    svg.push_str(
+        r#"</filter>
+  <rect width="100" height="100" filter="url(#filter)"/>
+</svg>"#,
+    );
+
+    let start = Instant::now();
+
+    let res = render_with_limits(
+        svg.as_bytes(),
+        RenderLimits {
+            deadline: Some(start + Duration::from_millis(1)),
+            ..Default::default()
+        },
+    );
+
+    assert!(matches!(
+        res,
+        Err(RenderingError::LimitExceeded(
+            ImplementationLimit::RenderingDeadlineExceeded
+        ))
+    ));
+}
+
+#[test]
+fn render_limits_count_drawn_elements() {
+    let svg = br##"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect id="rect" width="10" height="10"/>
+  <use href="#rect" x="10"/>
+  <use href="#rect" x="20"/>
+</svg>
+"##;
+
+    // svg, rect, and two times use and rect
+    assert!(render_with_limits(
+        svg,
+        RenderLimits {
+            max_elements_drawn: Some(6),
+            ..Default::default()
+        }
+    )
+    .is_ok());
+
+    assert!(matches!(
+        render_with_limits(
+            svg,
+            RenderLimits {
+                max_elements_drawn: Some(5),
+                ..Default::default()
+            }
+        ),
+        Err(RenderingError::LimitExceeded(
+            ImplementationLimit::TooManyDrawnElements
+        ))
+    ));
+}
+
+#[test]
+fn render_limits_count_filter_pixels() {
+    let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <filter id="filter" x="0" y="0" width="100" height="100" filterUnits="userSpaceOnUse">
+    <feFlood flood-color="lime"/>
+    <feOffset dx="10"/>
+  </filter>
+  <rect width="100" height="100" filter="url(#filter)"/>
+</svg>
+"#;
+
+    // Each primitive produces 100x100 pixels
+    assert!(render_with_limits(
+        svg,
+        RenderLimits {
+            max_filter_pixels: Some(20_000),
+            ..Default::default()
+        }
+    )
+    .is_ok());
+
+    assert!(matches!(
+        render_with_limits(
+            svg,
+            RenderLimits {
+                max_filter_pixels: Some(19_999),
+                ..Default::default()
+            }
+        ),
+        Err(RenderingError::LimitExceeded(
+            ImplementationLimit::TooManyFilterPixels
+        ))
+    ));
+}
+
+#[test]
+fn element_title_and_desc() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="50" height="50">
+  <title>
+    A   picture
+    of a square
+  </title>
+  <desc>It has <tspan>nested</tspan> text.</desc>
+  <rect id="square" x="10" y="10" width="30" height="30">
+    <title>The square</title>
+    <title>Ignored second title</title>
+  </rect>
+  <circle id="circle" cx="25" cy="25" r="5"/>
+</svg>
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        svg.element_title(None).unwrap().as_deref(),
+        Some("A picture of a square")
+    );
+    assert_eq!(
+        svg.element_desc(None).unwrap().as_deref(),
+        Some("It has nested text.")
+    );
+
+    assert_eq!(
+        svg.element_title(Some("#square")).unwrap().as_deref(),
+        Some("The square")
+    );
+    assert_eq!(svg.element_desc(Some("#square")).unwrap(), None);
+
+    assert_eq!(svg.element_title(Some("#circle")).unwrap(), None);
+    assert_eq!(svg.element_title(Some("#nonexistent")).unwrap(), None);
+
+    assert!(matches!(
+        svg.element_title(Some("square")),
+        Err(RenderingError::InvalidId(_))
+    ));
+    assert!(matches!(
+        svg.element_desc(Some("other.svg#square")),
+        Err(RenderingError::InvalidId(_))
+    ));
+}
+
+#[test]
+fn xinclude_text_honors_charset_of_data_urls() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xi="http://www.w3.org/2001/XInclude">
+  <title><xi:include parse="text"
+             href="data:text/plain;charset=utf-16le;base64,aADpAGwAbABvAA=="/></title>
+  <desc><xi:include parse="text" href="data:text/plain;charset=iso-8859-1,h%E9llo"/></desc>
+  <rect id="plain" width="10" height="10">
+    <title><xi:include parse="text" href="data:,h%C3%A9llo"/></title>
+    <desc><xi:include parse="text" href="data:text/plain;charset=utf-16le,h%C3%A9llo"
+                      encoding="utf-8"/></desc>
+  </rect>
+</svg>
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(svg.element_title(None).unwrap().as_deref(), Some("héllo"));
+    assert_eq!(svg.element_desc(None).unwrap().as_deref(), Some("héllo"));
+
+    // No charset means UTF-8, and an explicit encoding overrides the charset
+    assert_eq!(
+        svg.element_title(Some("#plain")).unwrap().as_deref(),
+        Some("héllo")
+    );
+    assert_eq!(
+        svg.element_desc(Some("#plain")).unwrap().as_deref(),
+        Some("héllo")
+    );
+}
+
+#[test]
+fn with_base_url_rejects_malformed_url() {
+    assert!(matches!(
+        Loader::new().with_base_url("not a url"),
+        Err(LoadingError::BadUrl)
+    ));
+}
+
+fn resolved_image_url(loader: Loader, base_file: Option<&gio::File>) -> String {
+    let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10">
+  <image href="images/foo.png" width="10" height="10"/>
+</svg>
+"#;
+
+    let urls = Arc::new(Mutex::new(Vec::new()));
+
+    let handle = {
+        let urls = urls.clone();
+
+        loader
+            .with_resource_callback(Box::new(move |url| {
+                urls.lock().unwrap().push(url.to_string());
+                Err(rsvg::ResolveError::Denied)
+            }))
+            .read_data(svg, base_file)
+            .unwrap()
+    };
+
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 10, 10).unwrap();
+    let cr = cairo::Context::new(&output).unwrap();
+    let viewport = cairo::Rectangle::new(0.0, 0.0, 10.0, 10.0);
+    CairoRenderer::new(&handle)
+        .render_document(&cr, &viewport)
+        .unwrap();
+
+    let urls = urls.lock().unwrap();
+    assert_eq!(urls.len(), 1);
+    urls[0].clone()
+}
+
+#[test]
+fn with_base_url_resolves_relative_references() {
+    let loader = Loader::new()
+        .with_base_url("https://example.com/drawings/doc.svg")
+        .unwrap();
+
+    assert_eq!(
+        resolved_image_url(loader, None),
+        "https://example.com/drawings/images/foo.png"
+    );
+}
+
+#[test]
+fn with_base_url_takes_precedence_over_base_file() {
+    let loader = Loader::new()
+        .with_base_url("https://example.com/drawings/doc.svg")
+        .unwrap();
+    let base_file = gio::File::for_path("/some/directory/doc.svg");
+
+    assert_eq!(
+        resolved_image_url(loader, Some(&base_file)),
+        "https://example.com/drawings/images/foo.png"
+    );
+}
+
+#[test]
+fn parse_color_returns_rgba_components() {
+    assert_eq!(
+        parse_color("#112233").unwrap().to_rgba(),
+        (0x11, 0x22, 0x33, 1.0)
+    );
+    assert_eq!(
+        parse_color("rgb(255, 128, 0)").unwrap().to_rgba(),
+        (255, 128, 0, 1.0)
+    );
+    assert_eq!(
+        parse_color("rgba(0, 0, 255, 0.5)").unwrap().to_rgba(),
+        (0, 0, 255, 0.5)
+    );
+    assert_eq!(
+        parse_color("hsl(120, 100%, 50%)").unwrap().to_rgba(),
+        (0, 255, 0, 1.0)
+    );
+    assert_eq!(parse_color("blue").unwrap().to_rgba(), (0, 0, 255, 1.0));
+}
+
+#[test]
+fn parse_color_resolves_current_color_to_black() {
+    let color = parse_color("currentColor").unwrap();
+
+    assert!(color.is_current_color());
+    assert_eq!(color.to_rgba(), (0, 0, 0, 1.0));
+}
+
+#[test]
+fn parse_color_rejects_none_and_garbage() {
+    assert!(parse_color("none").is_err());
+    assert!(parse_color("").is_err());
+    assert!(parse_color("#12345").is_err());
+    assert!(parse_color("not a color").is_err());
+}
+
+#[derive(Default)]
+struct CountingObserver {
+    layers: usize,
+    ids: Vec<String>,
+    fills: Vec<cairo::Rectangle>,
+    strokes: usize,
+}
+
+impl RenderObserver for CountingObserver {
+    fn on_layer(&mut self, id: Option<&str>) {
+        self.layers += 1;
+        self.ids.extend(id.map(String::from));
+    }
+
+    fn on_fill(&mut self, bbox: &cairo::Rectangle) {
+        self.fills.push(*bbox);
+    }
+
+    fn on_stroke(&mut self, _bbox: &cairo::Rectangle) {
+        self.strokes += 1;
+    }
+}
+
+#[test]
+fn render_document_observed_reports_layers_and_fills() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <defs>
+    <clipPath id="clip">
+      <rect width="100" height="100"/>
+    </clipPath>
+  </defs>
+  <rect id="first" x="10" y="10" width="30" height="30" fill="blue"/>
+  <g id="group" clip-path="url(#clip)">
+    <rect x="50" y="50" width="20" height="20" fill="lime" stroke="black"/>
+    <circle cx="80" cy="20" r="10" fill="none" stroke="black"/>
+  </g>
+</svg>
+"#,
+    )
+    .unwrap();
+
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+    let cr = cairo::Context::new(&output).expect("Failed to create cairo context");
+    let viewport = cairo::Rectangle::new(0.0, 0.0, 100.0, 100.0);
+
+    let mut observer = CountingObserver::default();
+    CairoRenderer::new(&svg)
+        .render_document_observed(&cr, &viewport, &mut observer)
+        .unwrap();
+
+    // The toplevel svg, the two rects, the group, and the circle; the clipPath's
+    // contents are not drawn.
+    assert_eq!(observer.layers, 5);
+    assert_eq!(observer.ids, vec!["first", "group"]);
+
+    assert_eq!(observer.fills.len(), 2);
+    assert_eq!(
+        observer.fills[0],
+        cairo::Rectangle::new(10.0, 10.0, 30.0, 30.0)
+    );
+    assert_eq!(
+        observer.fills[1],
+        cairo::Rectangle::new(50.0, 50.0, 20.0, 20.0)
+    );
+
+    assert_eq!(observer.strokes, 2);
+}
+
+#[test]
+fn element_path_of_rect() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100" viewBox="0 0 200 100">
+  <rect id="rect" x="10" y="20" width="25%" height="40" transform="scale(2)"/>
+  <rect id="rounded" x="10" y="20" width="30" height="40" rx="5"/>
+</svg>
+"#,
+    )
+    .unwrap();
+
+    // The transform is not applied, and the percentage refers to the viewBox.
+    assert_eq!(
+        svg.element_path("#rect").unwrap(),
+        "M 10 20 L 60 20 L 60 60 L 10 60 L 10 20 Z"
+    );
+
+    assert_eq!(
+        svg.element_path("#rounded").unwrap(),
+        "M 15 20 L 35 20 A 5 5 0 0 1 40 25 L 40 55 A 5 5 0 0 1 35 60 \
+         L 15 60 A 5 5 0 0 1 10 55 L 10 25 A 5 5 0 0 1 15 20 Z"
+    );
+}
+
+#[test]
+fn element_path_of_circle() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <circle id="circle" cx="50" cy="50" r="10"/>
+</svg>
+"#,
+    )
+    .unwrap();
+
+    let path = svg.element_path("#circle").unwrap();
+
+    // The circle is approximated with four Bézier curves that start and end on its
+    // rightmost point.
+    assert!(path.starts_with("M 60 50 C 60 "));
+    assert!(path.ends_with(" 60 50 Z"));
+    assert_eq!(path.matches('C').count(), 4);
+    assert!(path.contains(" 50 60 C "));
+    assert!(path.contains(" 40 50 C "));
+    assert!(path.contains(" 50 40 C "));
+}
+
+#[test]
+fn element_path_rejects_non_shapes() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <g id="group">
+    <rect x="10" y="10" width="10" height="10"/>
+  </g>
+</svg>
+"#,
+    )
+    .unwrap();
+
+    assert!(matches!(
+        svg.element_path("#group"),
+        Err(RenderingError::NotAShape)
+    ));
+
+    assert!(matches!(
+        svg.element_path("#nonexistent"),
+        Err(RenderingError::IdNotFound)
+    ));
+}
+
+#[test]
+fn computed_value_inherits_from_parent() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <g fill="red" stroke-width="2">
+    <rect id="rect" x="10" y="10" width="10" height="10" opacity="50%"/>
+  </g>
+</svg>
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        svg.computed_value("#rect", "fill").unwrap().as_deref(),
+        Some("rgb(255, 0, 0)")
+    );
+
+    assert_eq!(
+        svg.computed_value("#rect", "stroke-width")
+            .unwrap()
+            .as_deref(),
+        Some("2px")
+    );
+
+    assert_eq!(
+        svg.computed_value("#rect", "opacity").unwrap().as_deref(),
+        Some("0.5")
+    );
+
+    assert_eq!(
+        svg.computed_value("#rect", "no-such-property").unwrap(),
+        None
+    );
+
+    assert!(matches!(
+        svg.computed_value("#nonexistent", "fill"),
+        Err(RenderingError::IdNotFound)
+    ));
+}
+
+#[test]
+fn parse_path_data_makes_relative_commands_absolute() {
+    let path = parse_path_data("m 10 20 l 5 5 c 1 2 3 4 5 6 z").unwrap();
+
+    assert_eq!(
+        path.commands(),
+        &[
+            PathCommand::MoveTo(10.0, 20.0),
+            PathCommand::LineTo(15.0, 25.0),
+            PathCommand::CurveTo(CubicBezierCurve {
+                pt1: (16.0, 27.0),
+                pt2: (18.0, 29.0),
+                to: (20.0, 31.0),
+            }),
+            PathCommand::ClosePath,
+        ]
+    );
+}
+
+#[test]
+fn parse_path_data_turns_moveto_coordinates_into_linetos() {
+    let path = parse_path_data("M 10 10 20 20 30 10 m 5 5 10 0").unwrap();
+
+    assert_eq!(
+        path.commands(),
+        &[
+            PathCommand::MoveTo(10.0, 10.0),
+            PathCommand::LineTo(20.0, 20.0),
+            PathCommand::LineTo(30.0, 10.0),
+            PathCommand::MoveTo(35.0, 15.0),
+            PathCommand::LineTo(45.0, 15.0),
+        ]
+    );
+}
+
+#[test]
+fn parse_path_data_keeps_elliptical_arcs() {
+    let path = parse_path_data("M 10 10 a 20 10 30 1 0 40 0").unwrap();
+
+    assert_eq!(
+        path.commands(),
+        &[
+            PathCommand::MoveTo(10.0, 10.0),
+            PathCommand::Arc(EllipticalArc {
+                r: (20.0, 10.0),
+                x_axis_rotation: 30.0,
+                large_arc: LargeArc(true),
+                sweep: Sweep::Negative,
+                from: (10.0, 10.0),
+                to: (50.0, 10.0),
+            }),
+        ]
+    );
+}
+
+#[test]
+fn parse_path_data_reports_error_position() {
+    let error = parse_path_data("M 10 10 L 20 x").unwrap_err();
+    assert_eq!(error.position, 13);
+
+    let error = parse_path_data("M 10 10 L").unwrap_err();
+    assert_eq!(error.position, 9);
+}
+
+fn assert_point_eq(matrix: &cairo::Matrix, point: (f64, f64), expected: (f64, f64)) {
+    let (x, y) = matrix.transform_point(point.0, point.1);
+    assert!(
+        (x - expected.0).abs() < 1e-9 && (y - expected.1).abs() < 1e-9,
+        "{point:?} transformed to {:?}, expected {expected:?}",
+        (x, y)
+    );
+}
+
+#[test]
+fn parse_transform_rotates_around_a_center() {
+    let matrix = parse_transform("rotate(90 10 10)").unwrap();
+
+    assert_point_eq(&matrix, (10.0, 10.0), (10.0, 10.0));
+    assert_point_eq(&matrix, (20.0, 10.0), (10.0, 20.0));
+}
+
+#[test]
+fn parse_transform_composes_a_list_from_left_to_right() {
+    let matrix = parse_transform("translate(10,20) scale(2) , skewX(45)").unwrap();
+
+    assert_point_eq(&matrix, (0.0, 0.0), (10.0, 20.0));
+    assert_point_eq(&matrix, (1.0, 1.0), (14.0, 22.0));
+
+    let matrix = parse_transform("matrix(1 0 0 1 5 5) rotate(90)").unwrap();
+    assert_point_eq(&matrix, (1.0, 0.0), (5.0, 6.0));
+}
+
+#[test]
+fn parse_transform_accepts_css_syntax() {
+    let matrix = parse_transform("translateX(10px) rotate(0.25turn) scale(2, 3)").unwrap();
+    assert_point_eq(&matrix, (1.0, 1.0), (7.0, 2.0));
+
+    let matrix = parse_transform("none").unwrap();
+    assert_point_eq(&matrix, (3.0, 4.0), (3.0, 4.0));
+}
+
+#[test]
+fn parse_transform_rejects_invalid_lists() {
+    assert!(parse_transform("scale()").is_err());
+    assert!(parse_transform("translate(10em)").is_err());
+    assert!(parse_transform("frobnicate(1)").is_err());
+}
+
+#[test]
+fn aspect_ratio_fits_wide_viewbox_into_tall_viewport() {
+    let vbox = ViewBox::parse_str("0 0 200 100").unwrap();
+    let viewport = Rect::new(10.0, 20.0, 110.0, 320.0);
+
+    let meet = AspectRatio::parse_str("xMidYMid meet").unwrap();
+    assert_eq!(
+        meet.compute(&vbox, &viewport),
+        Rect::new(10.0, 145.0, 110.0, 195.0)
+    );
+
+    let slice = AspectRatio::parse_str("xMidYMid slice").unwrap();
+    assert_eq!(
+        slice.compute(&vbox, &viewport),
+        Rect::new(-240.0, 20.0, 360.0, 320.0)
+    );
+}
+
+#[test]
+fn length_converts_centimeters_to_pixels_and_back() {
+    let params = NormalizeParams::from_dpi(Dpi::new(96.0, 96.0));
+    let length = Length::new(2.0, LengthUnit::Cm);
+
+    assert!((length.to_user(&params) - 2.0 / 2.54 * 96.0).abs() < 1e-9);
+    assert!((length.to_mm(&params) - 20.0).abs() < 1e-9);
+    assert!((length.to_points(&params) - 2.0 / 2.54 * 72.0).abs() < 1e-9);
+
+    let pixels = Length::new(96.0, LengthUnit::Px);
+    assert!((pixels.to_inches(&params) - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn length_resolves_relative_units_with_context() {
+    let params = NormalizeParams::from_dpi(Dpi::new(96.0, 96.0))
+        .with_viewport_size(300.0, 300.0)
+        .with_font_size(12.0);
+
+    let percent = Length::new(0.5, LengthUnit::Percent).to_user(&params);
+    assert!((percent - 150.0).abs() < 1e-9);
+
+    assert_eq!(Length::new(2.0, LengthUnit::Em).to_user(&params), 24.0);
+    assert_eq!(Length::new(2.0, LengthUnit::Ex).to_user(&params), 12.0);
+}
+
+#[test]
+fn aspect_ratio_and_viewbox_reject_invalid_strings() {
+    assert!(AspectRatio::parse_str("xMidYMid fit").is_err());
+    assert!(AspectRatio::parse_str("meet").is_err());
+    assert!(ViewBox::parse_str("0 0 100").is_err());
+    assert!(ViewBox::parse_str("0 0 100 -1").is_err());
+}
+
+fn render_to_rgba_buffer(buf: &mut [u8], stride: usize) -> Result<(), RenderingError> {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="4" height="2">
+  <rect width="2" height="2" fill="red"/>
+  <rect x="2" width="2" height="2" fill="lime" fill-opacity="0.5"/>
+</svg>
+"#,
+    )
+    .unwrap();
+
+    CairoRenderer::new(&svg).render_to_rgba_buffer(buf, 4, 2, stride)
+}
+
+fn assert_straight_rgba_pixels(buf: &[u8], stride: usize) {
+    for y in 0..2 {
+        let row = &buf[y * stride..];
+
+        assert_eq!(&row[0..4], &[255, 0, 0, 255]);
+
+        let (r, g, b, a) = (row[12], row[13], row[14], row[15]);
+        assert_eq!((r, b), (0, 0));
+        assert!(g >= 254, "green should be unpremultiplied, got {g}");
+        assert!(
+            (127..=128).contains(&a),
+            "alpha should be about half, got {a}"
+        );
+    }
+}
+
+#[test]
+fn render_to_rgba_buffer_writes_straight_alpha() {
+    let stride = 4 * 4 + 8;
+    let mut buf = vec![0xaa; stride * 2];
+
+    render_to_rgba_buffer(&mut buf, stride).unwrap();
+    assert_straight_rgba_pixels(&buf, stride);
+
+    // The padding at the end of each row is not touched
+    assert!(buf[16..stride].iter().all(|&b| b == 0xaa));
+    assert!(buf[stride + 16..].iter().all(|&b| b == 0xaa));
+}
+
+#[test]
+fn render_to_rgba_buffer_supports_unaligned_buffers() {
+    let stride = 4 * 4 + 1;
+    let mut buf = vec![0; stride * 2 + 1];
+
+    render_to_rgba_buffer(&mut buf[1..], stride).unwrap();
+    assert_straight_rgba_pixels(&buf[1..], stride);
+}
+
+#[test]
+fn render_to_rgba_buffer_validates_the_buffer() {
+    let mut buf = vec![0; 64];
+
+    assert!(matches!(
+        render_to_rgba_buffer(&mut buf, 15),
+        Err(RenderingError::Cairo(cairo::Error::InvalidStride))
+    ));
+
+    assert!(matches!(
+        render_to_rgba_buffer(&mut buf[..31], 16),
+        Err(RenderingError::Cairo(cairo::Error::InvalidSize))
+    ));
+}
+
+#[test]
+fn render_to_rgba_buffer_accepts_empty_sizes() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="4" height="2">
+  <rect width="4" height="2" fill="lime"/>
+</svg>
+"#,
+    )
+    .unwrap();
+
+    let renderer = CairoRenderer::new(&svg);
+
+    // Zero width, with a stride of zero
+    renderer.render_to_rgba_buffer(&mut [], 0, 2, 0).unwrap();
+
+    // Zero height; the buffer is left untouched
+    let mut buf = vec![0xaa; 16];
+    renderer.render_to_rgba_buffer(&mut buf, 4, 0, 16).unwrap();
+    assert!(buf.iter().all(|&b| b == 0xaa));
+}
+
+#[cfg(system_deps_have_cairo_svg)]
+#[test]
+fn render_to_svg_bytes_produces_an_svg_document() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink"
+     width="10" height="10">
+  <style>rect { fill: lime; }</style>
+  <defs>
+    <rect id="square" width="10" height="10"/>
+  </defs>
+  <use xlink:href="#square"/>
+</svg>
+"##,
+    )
+    .unwrap();
+
+    let renderer = CairoRenderer::new(&svg);
+    let bytes = renderer
+        .render_to_svg_bytes(rsvg::Size {
+            width: 10.0,
+            height: 10.0,
+        })
+        .unwrap();
+
+    assert!(!bytes.is_empty());
+
+    let output = std::str::from_utf8(&bytes).unwrap();
+    assert!(output.starts_with("<?xml") || output.starts_with("<svg"));
+    assert!(output.contains("<svg"));
+
+    assert!(matches!(
+        renderer.render_to_svg_bytes(rsvg::Size {
+            width: 0.0,
+            height: 10.0,
+        }),
+        Err(RenderingError::Cairo(cairo::Error::InvalidSize))
+    ));
+}
+
+fn kept_image_data_after_rendering(loader: Loader) -> bool {
+    let data = br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10">
+  <image width="10" height="10"
+         href="data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAoAAAAKCAIAAAACUFjqAAAABmJLR0QA/wD/AP+gvaeTAAAAE0lEQVQYlWNk+M+ABzDhkxy50gBALQETCv6BWAAAAABJRU5ErkJggg=="/>
+</svg>
+"##;
+
+    let svg = loader.read_data(data, None).unwrap();
+    assert!(!svg.has_kept_image_data());
+
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 10, 10).unwrap();
+    let cr = cairo::Context::new(&output).unwrap();
+    let viewport = cairo::Rectangle::new(0.0, 0.0, 10.0, 10.0);
+    CairoRenderer::new(&svg)
+        .render_document(&cr, &viewport)
+        .unwrap();
+
+    svg.has_kept_image_data()
+}
+
+#[test]
+fn keep_image_data_for_specific_formats() {
+    assert!(!kept_image_data_after_rendering(Loader::new()));
+    assert!(kept_image_data_after_rendering(
+        Loader::new().keep_image_data(true)
+    ));
+
+    assert!(kept_image_data_after_rendering(
+        Loader::new().keep_image_data_for(&["image/jpeg", "image/png"])
+    ));
+    assert!(!kept_image_data_after_rendering(
+        Loader::new().keep_image_data_for(&["image/jpeg"])
+    ));
+    assert!(!kept_image_data_after_rendering(
+        Loader::new().keep_image_data_for(&[])
+    ));
+}
+
+fn pixel_at_animation_time(data: &'static [u8], time: Option<f64>) -> [u8; 4] {
+    let svg = load_svg(data).unwrap();
+
+    let renderer = CairoRenderer::new(&svg);
+    let renderer = match time {
+        Some(seconds) => renderer.with_animation_time(seconds),
+        None => renderer,
+    };
+
+    let mut buf = [0; 4];
+    renderer.render_to_rgba_buffer(&mut buf, 1, 1, 4).unwrap();
+    buf
+}
+
+#[test]
+fn animation_time_applies_set() {
+    let data = br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="1" height="1">
+  <rect width="1" height="1" fill="red">
+    <set attributeName="fill" to="lime" begin="1s"/>
+  </rect>
+</svg>
+"#;
+
+    assert_eq!(pixel_at_animation_time(data, None), [255, 0, 0, 255]);
+    assert_eq!(pixel_at_animation_time(data, Some(0.5)), [255, 0, 0, 255]);
+    assert_eq!(pixel_at_animation_time(data, Some(2.0)), [0, 255, 0, 255]);
+}
+
+#[test]
+fn animation_time_interpolates_animate() {
+    let data = br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="1" height="1">
+  <rect width="1" height="1" fill="lime">
+    <animate attributeName="opacity" from="0" to="1" dur="2s"/>
+  </rect>
+</svg>
+"#;
+
+    assert_eq!(pixel_at_animation_time(data, None), [0, 255, 0, 255]);
+
+    let [_, _, _, a] = pixel_at_animation_time(data, Some(1.0));
+    assert!(
+        (127..=128).contains(&a),
+        "alpha should be about half, got {a}"
+    );
+
+    // The animation is over and it is not frozen, so the static value is back.
+    assert_eq!(pixel_at_animation_time(data, Some(3.0)), [0, 255, 0, 255]);
+}
+
+#[test]
+fn animation_time_applies_animate_transform() {
+    let data = br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="1" height="1">
+  <rect width="1" height="1" fill="lime">
+    <animateTransform attributeName="transform" type="translate" from="0 0" to="2 0"
+                      dur="2s" fill="freeze"/>
+  </rect>
+</svg>
+"#;
+
+    assert_eq!(pixel_at_animation_time(data, Some(0.0)), [0, 255, 0, 255]);
+    assert_eq!(pixel_at_animation_time(data, Some(1.0)), [0, 0, 0, 0]);
+    assert_eq!(pixel_at_animation_time(data, Some(5.0)), [0, 0, 0, 0]);
+}
+
+#[test]
+fn animation_time_ignores_unsupported_animations() {
+    let data = br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="1" height="1">
+  <rect id="r" width="1" height="1" fill="lime">
+    <set attributeName="fill" to="red" begin="click"/>
+    <animate attributeName="fill" to="red" dur="1s" fill="freeze"/>
+    <animate attributeName="fill" values="red; blue" calcMode="paced" dur="1s"/>
+    <animate attributeName="fill" values="red; blue" calcMode="spline" keySplines="0 0 1 1"
+             dur="1s"/>
+    <animate attributeName="opacity" from="0" to="-1" additive="sum" dur="1s"/>
+  </rect>
+  <set href="#r" attributeName="fill" to="red"/>
+</svg>
+"##;
+
+    assert_eq!(pixel_at_animation_time(data, Some(0.5)), [0, 255, 0, 255]);
+}
+
+#[test]
+fn animation_time_does_not_leak_into_static_values() {
+    let data = br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="1" height="1">
+  <rect id="r" width="1" height="1" fill="red">
+    <set attributeName="fill" to="lime"/>
+  </rect>
+</svg>
+"#;
+
+    let svg = load_svg(data).unwrap();
+
+    let mut buf = [0; 4];
+    CairoRenderer::new(&svg)
+        .with_animation_time(1.0)
+        .render_to_rgba_buffer(&mut buf, 1, 1, 4)
+        .unwrap();
+    assert_eq!(buf, [0, 255, 0, 255]);
+
+    assert_eq!(
+        svg.computed_value("#r", "fill").unwrap().as_deref(),
+        Some("rgb(255, 0, 0)")
+    );
+
+    CairoRenderer::new(&svg)
+        .render_to_rgba_buffer(&mut buf, 1, 1, 4)
+        .unwrap();
+    assert_eq!(buf, [255, 0, 0, 255]);
+}
+
+fn pixel_with_current_color(data: &'static [u8], color: &str) -> [u8; 4] {
+    let svg = load_svg(data).unwrap();
+
+    let renderer = CairoRenderer::new(&svg).with_current_color(parse_color(color).unwrap());
+
+    let mut buf = [0; 4];
+    renderer.render_to_rgba_buffer(&mut buf, 1, 1, 4).unwrap();
+    buf
+}
+
+#[test]
+fn current_color_can_be_overridden() {
+    let data = br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="1" height="1">
+  <g>
+    <rect width="1" height="1" fill="currentColor"/>
+  </g>
+</svg>
+"#;
+
+    assert_eq!(pixel_with_current_color(data, "lime"), [0, 255, 0, 255]);
+    assert_eq!(pixel_with_current_color(data, "blue"), [0, 0, 255, 255]);
+    assert_eq!(pixel_at_animation_time(data, None), [0, 0, 0, 255]);
+
+    // Elements that set their own color don't get the overridden one.
+    let data = br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="1" height="1">
+  <rect width="1" height="1" color="red" fill="currentColor"/>
+</svg>
+"#;
+
+    assert_eq!(pixel_with_current_color(data, "lime"), [255, 0, 0, 255]);
+}
+
+fn pixel_with_css_variables(data: &'static [u8], variables: &[(&str, &str)]) -> [u8; 4] {
+    let svg = load_svg(data).unwrap();
+
+    let variables = variables
+        .iter()
+        .map(|&(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+    let renderer = CairoRenderer::new(&svg).with_css_variables(variables);
+
+    let mut buf = [0; 4];
+    renderer.render_to_rgba_buffer(&mut buf, 1, 1, 4).unwrap();
+    buf
+}
+
+#[test]
+fn css_variables_can_be_set_by_the_caller() {
+    let data = br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="1" height="1">
+  <style>
+    rect { fill: var(--brand, red); }
+  </style>
+  <rect width="1" height="1"/>
+</svg>
+"#;
+
+    assert_eq!(pixel_with_css_variables(data, &[]), [255, 0, 0, 255]);
+    assert_eq!(
+        pixel_with_css_variables(data, &[("--brand", "lime")]),
+        [0, 255, 0, 255]
+    );
+    assert_eq!(
+        pixel_with_css_variables(data, &[("--brand", "#0000ff")]),
+        [0, 0, 255, 255]
+    );
+}
+
+#[test]
+fn css_variables_from_the_document_override_the_caller() {
+    let data = br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="1" height="1">
+  <g style="--brand: lime">
+    <rect width="1" height="1" style="fill: rgb(0, 0, 0); fill: var(--brand)"/>
+  </g>
+</svg>
+"#;
+
+    assert_eq!(
+        pixel_with_css_variables(data, &[("--brand", "blue")]),
+        [0, 255, 0, 255]
+    );
+}
+
+#[test]
+fn css_variable_cycles_are_invalid() {
+    // The fill is invalid at computed-value time, so it becomes unset and inherits
+    // the lime from the group.
+    let data = br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="1" height="1">
+  <g fill="lime">
+    <rect width="1" height="1" fill="red" style="--a: var(--b); --b: var(--a); fill: var(--a)"/>
+  </g>
+</svg>
+"#;
+
+    assert_eq!(pixel_with_css_variables(data, &[]), [0, 255, 0, 255]);
+}
+
+#[cfg(all(not(target_os = "macos"), not(target_os = "windows")))]
+#[cfg(feature = "text")]
+#[test]
+fn renders_text_with_fonts_from_font_config() {
+    // Ahem's glyphs are squares that fill the whole em box; its ascent is 0.8 em.
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10">
+  <text x="0" y="8" font-family="Ahem" font-size="10" fill="lime">X</text>
+</svg>
+"#,
+    )
+    .unwrap();
+
+    let mut fonts = FontConfig::empty();
+    fonts
+        .add_font_file(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/resources/Ahem.ttf"
+        ))
+        .unwrap();
+
+    let renderer = CairoRenderer::new(&svg).with_fonts(fonts);
+
+    let mut buf = [0; 10 * 10 * 4];
+    renderer
+        .render_to_rgba_buffer(&mut buf, 10, 10, 10 * 4)
+        .unwrap();
+
+    for (x, y) in [(1, 1), (8, 1), (1, 8), (8, 8), (5, 5)] {
+        let offset = (y * 10 + x) * 4;
+        assert_eq!(
+            buf[offset..offset + 4],
+            [0, 255, 0, 255],
+            "pixel at ({x}, {y})"
+        );
+    }
+}
+
+#[test]
+fn font_config_rejects_missing_files() {
+    let mut fonts = FontConfig::empty();
+    assert!(fonts.add_font_file("/nonexistent/font.ttf").is_err());
+}
+
+/// Renders `svg` scaled by `zoom`, and returns how many rows of pixels the
+/// horizontal line through its middle covers.
+fn stroke_width_in_device_pixels(svg: &SvgHandle, zoom: i32) -> usize {
+    let size = 20 * zoom;
+    let output_surf = render_to_pixels(svg, size, size);
+
+    (0..size as u32)
+        .filter(|&y| output_surf.get_pixel(size as u32 / 2, y).a == 255)
+        .count()
+}
+
+#[test]
+fn non_scaling_stroke_keeps_its_width_when_zooming() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="20" height="20">
+  <path d="M 0 10 H 20" stroke="black" stroke-width="2" vector-effect="non-scaling-stroke"/>
+</svg>
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(stroke_width_in_device_pixels(&svg, 1), 2);
+    assert_eq!(stroke_width_in_device_pixels(&svg, 4), 2);
+
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="20" height="20">
+  <path d="M 0 10 H 20" stroke="black" stroke-width="2"/>
+</svg>
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(stroke_width_in_device_pixels(&svg, 1), 2);
+    assert_eq!(stroke_width_in_device_pixels(&svg, 4), 8);
+}
+
+fn render_to_pixels(svg: &SvgHandle, width: i32, height: i32) -> SharedImageSurface {
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, width, height).unwrap();
+
+    {
+        let cr = cairo::Context::new(&output).expect("Failed to create cairo context");
+        let viewport = cairo::Rectangle::new(0.0, 0.0, f64::from(width), f64::from(height));
+
+        CairoRenderer::new(svg)
+            .render_document(&cr, &viewport)
+            .unwrap();
+    }
+
+    SharedImageSurface::wrap(output, SurfaceType::SRgb).unwrap()
+}
+
+#[test]
+fn shape_rendering_crisp_edges_disables_antialiasing() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="50">
+  <g shape-rendering="crispEdges">
+    <rect x="5.25" y="5.25" width="20.5" height="20.5" fill="black"/>
+    <circle cx="37.5" cy="37.5" r="10.3" fill="black"/>
+  </g>
+  <rect x="55.25" y="5.25" width="20.5" height="20.5" fill="black"/>
+  <circle cx="87.5" cy="37.5" r="10.3" fill="black"/>
+</svg>
+"#,
+    )
+    .unwrap();
+
+    let output_surf = render_to_pixels(&svg, 100, 50);
+
+    let is_blended = |x: u32, y: u32| {
+        let a = output_surf.get_pixel(x, y).a;
+        a != 0 && a != 255
+    };
+
+    let mut crisp = (0..50).flat_map(|x| (0..50).map(move |y| (x, y)));
+    assert!(!crisp.any(|(x, y)| is_blended(x, y)));
+
+    let mut smooth = (50..100).flat_map(|x| (0..50).map(move |y| (x, y)));
+    assert!(smooth.any(|(x, y)| is_blended(x, y)));
+}
+
+#[test]
+fn image_rendering_pixelated_uses_nearest_neighbor() {
+    // A 2x2 checkerboard, scaled up to 200x200 with and without "pixelated".
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="400" height="200">
+  <image image-rendering="pixelated" width="200" height="200"
+         href="data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAIAAAACCAIAAAD91JpzAAAAE0lEQVR4nGNgYGD4//8/GDMwAAAp5AX71ZPZmwAAAABJRU5ErkJggg=="/>
+  <image x="200" width="200" height="200"
+         href="data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAIAAAACCAIAAAD91JpzAAAAE0lEQVR4nGNgYGD4//8/GDMwAAAp5AX71ZPZmwAAAABJRU5ErkJggg=="/>
+</svg>
+"#,
+    )
+    .unwrap();
+
+    let output_surf = render_to_pixels(&svg, 400, 200);
+
+    let is_blended = |x: u32, y: u32| {
+        let r = output_surf.get_pixel(x, y).r;
+        r != 0 && r != 255
+    };
+
+    let mut pixelated = (0..200).flat_map(|x| (0..200).map(move |y| (x, y)));
+    assert!(!pixelated.any(|(x, y)| is_blended(x, y)));
+
+    assert_eq!(output_surf.get_pixel(99, 50).r, 0);
+    assert_eq!(output_surf.get_pixel(100, 50).r, 255);
+
+    let mut smooth = (200..400).flat_map(|x| (0..200).map(move |y| (x, y)));
+    assert!(smooth.any(|(x, y)| is_blended(x, y)));
+}
+
+#[test]
+fn version_is_semver() {
+    let version = rsvg::version();
+    let release = version.split(['-', '+']).next().unwrap();
+
+    let parts: Vec<_> = release.split('.').collect();
+    assert_eq!(parts.len(), 3, "version {version} is not major.minor.micro");
+    assert!(parts.iter().all(|p| p.parse::<u32>().is_ok()));
+}
+
+#[test]
+fn features_match_cargo_features() {
+    let features = rsvg::features();
+
+    assert_eq!(features.text, cfg!(feature = "text"));
+    assert_eq!(features.pixbuf, cfg!(feature = "pixbuf"));
+}
+
+#[test]
+fn geometries_for_elements_match_individual_geometries() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="200" viewBox="0 0 100 100">
+  <defs>
+    <rect id="in_defs" width="10" height="10"/>
+    <symbol id="symbol">
+      <circle id="in_symbol" cx="5" cy="5" r="5"/>
+    </symbol>
+  </defs>
+  <g id="group" transform="translate(10, 10)">
+    <rect id="stroked" x="5" y="5" width="20" height="10" stroke="black" stroke-width="4"/>
+    <g transform="rotate(45)">
+      <rect id="rotated" width="10" height="10"/>
+    </g>
+  </g>
+  <svg id="nested" x="50" y="50" width="40" height="40" viewBox="0 0 10 10">
+    <rect id="in_nested" x="2" y="2" width="6" height="6"/>
+  </svg>
+  <use id="use" href="#symbol" x="60" y="10"/>
+  <rect id="hidden" width="10" height="10" display="none"/>
+</svg>
+"##,
+    )
+    .unwrap();
+
+    let ids = [
+        "#group",
+        "#stroked",
+        "#rotated",
+        "#nested",
+        "#in_nested",
+        "#use",
+        "#in_symbol",
+        "#in_defs",
+        "#hidden",
+        "#nonexistent",
+        "not_a_fragment",
+    ];
+
+    let renderer = CairoRenderer::new(&svg);
+    let viewport = cairo::Rectangle::new(10.0, 20.0, 200.0, 200.0);
+
+    let geometries = renderer.geometries_for_elements(&ids, &viewport);
+    assert_eq!(geometries.len(), ids.len());
+
+    for (id, batch) in ids.iter().zip(geometries) {
+        let single = renderer.geometry_for_layer(Some(*id), &viewport);
+
+        match (batch, single) {
+            (Ok(batch), Ok(single)) => assert_eq!(batch, single, "geometry for {id}"),
+            (Err(_), Err(_)) => (),
+            (batch, single) => panic!("{id}: batch gave {batch:?}, single gave {single:?}"),
+        }
+    }
 }
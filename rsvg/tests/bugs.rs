@@ -1,8 +1,11 @@
 use matches::matches;
-use rsvg::{CairoRenderer, Loader, LoadingError, SvgHandle};
+use rsvg::tests_only::{SharedImageSurface, SurfaceType};
+use rsvg::{CairoRenderer, Loader, LoadingError, RenderingError, SvgHandle};
 
 use rsvg::test_utils::reference_utils::{Compare, Evaluate, Reference};
-use rsvg::test_utils::{load_svg, render_document, setup_font_map, setup_language, SurfaceSize};
+#[cfg(feature = "text")]
+use rsvg::test_utils::setup_font_map;
+use rsvg::test_utils::{load_svg, render_document, setup_language, SurfaceSize};
 
 // https://gitlab.gnome.org/GNOME/librsvg/issues/335
 #[test]
@@ -223,6 +226,35 @@ fn test_renders_as_empty(svg: &SvgHandle, test_name: &str) {
         .evaluate(&output_surf, test_name);
 }
 
+/// Checks that the cycle at `id` is reported when circular reference errors are enabled,
+/// and that the document still renders as empty, just like without them.
+fn assert_circular_reference(svg: &SvgHandle, id: &str, test_name: &str) {
+    test_renders_as_empty(svg, test_name);
+
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+
+    let res = {
+        let cr = cairo::Context::new(&output).expect("Failed to create cairo context");
+        CairoRenderer::new(svg)
+            .with_circular_reference_errors(true)
+            .render_document(&cr, &cairo::Rectangle::new(0.0, 0.0, 100.0, 100.0))
+    };
+
+    match res {
+        Err(RenderingError::CircularReference {
+            id: Some(ref found),
+        }) => assert_eq!(found, id),
+        r => panic!("expected a circular reference in {id}, got {r:?}"),
+    }
+
+    let output_surf = SharedImageSurface::wrap(output, SurfaceType::SRgb).unwrap();
+    let reference_surf = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+
+    Reference::from_surface(reference_surf)
+        .compare(&output_surf)
+        .evaluate(&output_surf, &format!("{test_name}-with-errors"));
+}
+
 // https://gitlab.gnome.org/GNOME/librsvg/-/issues/308
 #[test]
 fn recursive_use() {
@@ -241,7 +273,7 @@ fn recursive_use() {
     )
     .unwrap();
 
-    test_renders_as_empty(&svg, "308-recursive-use");
+    assert_circular_reference(&svg, "one", "308-recursive-use");
 }
 
 // https://gitlab.gnome.org/GNOME/librsvg/-/issues/308
@@ -260,7 +292,7 @@ fn use_self_ref() {
     )
     .unwrap();
 
-    test_renders_as_empty(&svg, "308-use-self-ref");
+    assert_circular_reference(&svg, "one", "308-use-self-ref");
 }
 
 // https://gitlab.gnome.org/GNOME/librsvg/-/issues/308
@@ -285,10 +317,35 @@ fn doubly_recursive_use() {
     )
     .unwrap();
 
-    test_renders_as_empty(&svg, "308-doubly-recursive-use");
+    assert_circular_reference(&svg, "one", "308-doubly-recursive-use");
+}
+
+#[test]
+fn mutually_recursive_patterns() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <defs>
+    <pattern id="a" patternUnits="userSpaceOnUse" width="10" height="10">
+      <rect width="10" height="10" fill="url(#b)"/>
+    </pattern>
+
+    <pattern id="b" patternUnits="userSpaceOnUse" width="10" height="10">
+      <rect width="10" height="10" fill="url(#a)"/>
+    </pattern>
+  </defs>
+
+  <rect width="100" height="100" fill="url(#a)"/>
+</svg>
+"##,
+    )
+    .unwrap();
+
+    assert_circular_reference(&svg, "a", "mutually-recursive-patterns");
 }
 
 // https://gitlab.gnome.org/GNOME/librsvg/-/issues/347
+#[cfg(feature = "text")]
 #[test]
 fn test_text_bounds() {
     setup_font_map();
@@ -452,3 +509,78 @@ fn can_draw_to_non_image_surface() {
         .render_document(&cr, &viewport)
         .expect("Failed to render to non-image surface");
 }
+
+#[test]
+fn rotated_pattern_has_no_seams_between_tiles() {
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="200">
+  <pattern id="pat" patternUnits="userSpaceOnUse" width="7.3" height="5.6"
+           patternTransform="rotate(30) scale(1.5 1)">
+    <rect width="7.3" height="5.6" fill="lime"/>
+  </pattern>
+  <rect width="200" height="200" fill="url(#pat)"/>
+</svg>
+"##,
+    )
+    .unwrap();
+
+    let output_surf = render_document(
+        &svg,
+        SurfaceSize(200, 200),
+        |_| (),
+        cairo::Rectangle::new(0.0, 0.0, 200.0, 200.0),
+    )
+    .unwrap();
+
+    for y in 20..180 {
+        for x in 20..180 {
+            let pixel = output_surf.get_pixel(x, y);
+            assert_eq!(
+                (pixel.r, pixel.g, pixel.b, pixel.a),
+                (0, 255, 0, 255),
+                "seam at ({x}, {y})"
+            );
+        }
+    }
+}
+
+#[test]
+fn pattern_tile_resolution_follows_rotated_axes() {
+    // The pattern's x axis ends up vertical and its y axis, scaled by 4, horizontal.  The
+    // tile must be rendered at 10x40 device pixels, not 40x10; otherwise the edge of the
+    // stripe falls in the middle of a pixel of the tile, and comes out blurry after the
+    // tile is stretched.
+    let svg = load_svg(
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="200">
+  <pattern id="pat" patternUnits="userSpaceOnUse" width="10" height="10"
+           patternTransform="rotate(90) scale(1 4)">
+    <rect width="10" height="5.5" fill="lime"/>
+  </pattern>
+  <rect width="200" height="200" fill="url(#pat)"/>
+</svg>
+"##,
+    )
+    .unwrap();
+
+    let output_surf = render_document(
+        &svg,
+        SurfaceSize(200, 200),
+        |_| (),
+        cairo::Rectangle::new(0.0, 0.0, 200.0, 200.0),
+    )
+    .unwrap();
+
+    // The stripes' edges fall on device pixel boundaries, so each pixel is either in a stripe or
+    // out of it.
+    for y in 0..200 {
+        for x in 0..200 {
+            let pixel = output_surf.get_pixel(x, y);
+            assert!(
+                pixel.a == 0 || pixel.a == 255,
+                "partially covered pixel at ({x}, {y}): {pixel:?}"
+            );
+        }
+    }
+}
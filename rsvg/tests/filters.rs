@@ -1,6 +1,8 @@
 use rsvg::test_utils::reference_utils::{Compare, Evaluate, Reference};
 use rsvg::test_utils::{load_svg, render_document, SurfaceSize};
+use rsvg::tests_only::{SharedImageSurface, SurfaceType};
 use rsvg::{test_compare_render_output, test_svg_reference};
+use rsvg::{CairoRenderer, RenderingError};
 
 #[test]
 fn invalid_filter_reference_cancels_filter_chain() {
@@ -191,6 +193,82 @@ br##"<?xml version="1.0" encoding="UTF-8"?>
 "##,
 );
 
+test_compare_render_output!(
+    fe_drop_shadow_element,
+    400,
+    400,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg version="1.1" xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="400" height="400">
+  <defs>
+    <filter id="filter">
+      <feDropShadow dx="1" dy="4" stdDeviation="6" flood-color="#ff0000" flood-opacity="0.5"/>
+    </filter>
+  </defs>
+
+  <rect x="100" y="100" width="200" height="200" fill="green" filter="url(#filter)"/>
+</svg>
+"##,
+br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg version="1.1" xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="400" height="400">
+  <defs>
+    <filter id="filter">
+      <feGaussianBlur in="SourceAlpha" stdDeviation="6" />
+      <feOffset dx="1" dy="4" result="offsetblur" />
+      <feFlood flood-color="#ff0000" flood-opacity="0.5" />
+      <feComposite in2="offsetblur" operator="in" />
+      <feMerge>
+        <feMergeNode />
+        <feMergeNode in="SourceGraphic" />
+      </feMerge>
+    </filter>
+  </defs>
+
+  <rect x="100" y="100" width="200" height="200" fill="green" filter="url(#filter)"/>
+</svg>
+"##,
+);
+
+test_compare_render_output!(
+    fe_drop_shadow_element_uses_input_and_result,
+    400,
+    400,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg version="1.1" xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="400" height="400">
+  <defs>
+    <filter id="filter">
+      <feOffset dx="20" dy="20" result="moved"/>
+      <feFlood flood-color="blue"/>
+      <feDropShadow in="moved" dx="5" dy="5" stdDeviation="3" flood-color="black" result="shadow"/>
+      <feMerge>
+        <feMergeNode in="shadow"/>
+      </feMerge>
+    </filter>
+  </defs>
+
+  <rect x="100" y="100" width="200" height="200" fill="green" filter="url(#filter)"/>
+</svg>
+"##,
+br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg version="1.1" xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="400" height="400">
+  <defs>
+    <filter id="filter">
+      <feOffset dx="20" dy="20" result="moved"/>
+      <feGaussianBlur in="moved" stdDeviation="3" />
+      <feOffset dx="5" dy="5" result="offsetblur" />
+      <feFlood flood-color="black" />
+      <feComposite in2="offsetblur" operator="in" />
+      <feMerge>
+        <feMergeNode />
+        <feMergeNode in="moved" />
+      </feMerge>
+    </filter>
+  </defs>
+
+  <rect x="100" y="100" width="200" height="200" fill="green" filter="url(#filter)"/>
+</svg>
+"##,
+);
+
 test_compare_render_output!(
     grayscale_filter_func,
     400,
@@ -362,8 +440,374 @@ test_compare_render_output!(
 "##,
 );
 
+// A semi-transparent black mask hides everything as a luminance mask, but lets half of the
+// content through as an alpha mask.  The masking mode in the `mask` property overrides the
+// mask's own `mask-type`.
+test_compare_render_output!(
+    mask_type_with_semi_transparent_mask,
+    300,
+    100,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="300" height="100">
+  <mask id="luminance" mask-type="luminance">
+    <rect width="300" height="100" fill="black" fill-opacity="0.5"/>
+  </mask>
+  <mask id="alpha" mask-type="alpha">
+    <rect width="300" height="100" fill="black" fill-opacity="0.5"/>
+  </mask>
+
+  <rect x="0" y="0" width="100" height="100" fill="green" mask="url(#luminance)"/>
+  <rect x="100" y="0" width="100" height="100" fill="green" mask="url(#alpha)"/>
+  <rect x="200" y="0" width="100" height="100" fill="green" style="mask: url(#luminance) alpha"/>
+</svg>
+"##,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="300" height="100">
+  <rect x="100" y="0" width="200" height="100" fill="green" fill-opacity="0.5"/>
+</svg>
+"##,
+);
+
 test_svg_reference!(
     bug_743_fe_drop_shadow,
     "tests/fixtures/reftests/svg2/bug743-fe-drop-shadow.svg",
     "tests/fixtures/reftests/svg2/bug743-fe-drop-shadow-ref.svg"
 );
+
+// feImage renders a referenced element in the user space of the filtered element,
+// including the referenced element's own transform.
+test_compare_render_output!(
+    fe_image_renders_referenced_use_element,
+    200,
+    200,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="200" height="200">
+  <defs>
+    <rect id="shape" width="50" height="50" fill="lime"/>
+    <use id="copy" xlink:href="#shape" transform="translate(100 50)"/>
+    <filter id="filter" filterUnits="userSpaceOnUse" x="0" y="0" width="200" height="200">
+      <feImage href="#copy"/>
+    </filter>
+  </defs>
+
+  <rect width="200" height="200" fill="red" filter="url(#filter)"/>
+</svg>
+"##,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="200">
+  <rect x="100" y="50" width="50" height="50" fill="lime"/>
+</svg>
+"##,
+);
+
+// The feImage references an ancestor of the element being filtered, which would render
+// that element again.  That is a circular reference, so the feImage produces no output.
+test_compare_render_output!(
+    fe_image_referencing_ancestor_of_filtered_element_is_circular,
+    200,
+    100,
+    FE_IMAGE_REFERENCING_ANCESTOR,
+    FE_IMAGE_REFERENCING_ANCESTOR_REFERENCE,
+);
+
+const FE_IMAGE_REFERENCING_ANCESTOR: &[u8] = br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100">
+  <defs>
+    <filter id="filter" filterUnits="userSpaceOnUse" x="0" y="0" width="200" height="100">
+      <feImage href="#group"/>
+      <feOffset dx="100"/>
+    </filter>
+  </defs>
+
+  <g id="group">
+    <rect x="10" y="10" width="80" height="80" fill="lime"/>
+    <rect x="10" y="10" width="80" height="80" filter="url(#filter)"/>
+  </g>
+</svg>
+"##;
+
+const FE_IMAGE_REFERENCING_ANCESTOR_REFERENCE: &[u8] = br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100">
+  <rect x="10" y="10" width="80" height="80" fill="lime"/>
+</svg>
+"##;
+
+// With circular reference errors enabled, the same document gets rendered, and then the
+// cycle is reported as an error.
+#[test]
+fn fe_image_referencing_ancestor_of_filtered_element_reports_circular_reference() {
+    let svg = load_svg(FE_IMAGE_REFERENCING_ANCESTOR).unwrap();
+
+    let output = cairo::ImageSurface::create(cairo::Format::ARgb32, 200, 100).unwrap();
+
+    let res = {
+        let cr = cairo::Context::new(&output).expect("Failed to create cairo context");
+        CairoRenderer::new(&svg)
+            .with_circular_reference_errors(true)
+            .render_document(&cr, &cairo::Rectangle::new(0.0, 0.0, 200.0, 100.0))
+    };
+
+    assert!(matches!(
+        res,
+        Err(RenderingError::CircularReference { id: Some(ref id) }) if id == "group"
+    ));
+
+    let output_surf = SharedImageSurface::wrap(output, SurfaceType::SRgb).unwrap();
+
+    let reference = load_svg(FE_IMAGE_REFERENCING_ANCESTOR_REFERENCE).unwrap();
+    let reference_surf = render_document(
+        &reference,
+        SurfaceSize(200, 100),
+        |_| (),
+        cairo::Rectangle::new(0.0, 0.0, 200.0, 100.0),
+    )
+    .unwrap();
+
+    Reference::from_surface(reference_surf.into_image_surface().unwrap())
+        .compare(&output_surf)
+        .evaluate(
+            &output_surf,
+            "fe_image_referencing_ancestor_of_filtered_element_reports_circular_reference",
+        );
+}
+
+// The feBlend tests below blend a source rectangle (in) over a flood (in2) in sRGB, so the
+// expected colors can be computed by hand from the formulas in Compositing and Blending 1.
+// The filter region is the rectangle's bounds, so the flood does not extend past it.
+
+test_compare_render_output!(
+    fe_blend_multiply,
+    100,
+    100,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <defs>
+    <filter id="filter" x="0" y="0" width="1" height="1" color-interpolation-filters="sRGB">
+      <feFlood flood-color="#6633ff" result="backdrop"/>
+      <feBlend in="SourceGraphic" in2="backdrop" mode="multiply"/>
+    </filter>
+  </defs>
+
+  <rect x="10" y="10" width="80" height="80" fill="#338000" filter="url(#filter)"/>
+</svg>
+"##,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect x="10" y="10" width="80" height="80" fill="rgb(20,26,0)"/>
+</svg>
+"##,
+);
+
+test_compare_render_output!(
+    fe_blend_screen,
+    100,
+    100,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <defs>
+    <filter id="filter" x="0" y="0" width="1" height="1" color-interpolation-filters="sRGB">
+      <feFlood flood-color="#6633ff" result="backdrop"/>
+      <feBlend in="SourceGraphic" in2="backdrop" mode="screen"/>
+    </filter>
+  </defs>
+
+  <rect x="10" y="10" width="80" height="80" fill="#338000" filter="url(#filter)"/>
+</svg>
+"##,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect x="10" y="10" width="80" height="80" fill="rgb(133,153,255)"/>
+</svg>
+"##,
+);
+
+test_compare_render_output!(
+    fe_blend_color_dodge,
+    100,
+    100,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <defs>
+    <filter id="filter" x="0" y="0" width="1" height="1" color-interpolation-filters="sRGB">
+      <feFlood flood-color="#6633ff" result="backdrop"/>
+      <feBlend in="SourceGraphic" in2="backdrop" mode="color-dodge"/>
+    </filter>
+  </defs>
+
+  <rect x="10" y="10" width="80" height="80" fill="#338000" filter="url(#filter)"/>
+</svg>
+"##,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect x="10" y="10" width="80" height="80" fill="rgb(128,102,255)"/>
+</svg>
+"##,
+);
+
+test_compare_render_output!(
+    fe_blend_luminosity,
+    100,
+    100,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <defs>
+    <filter id="filter" x="0" y="0" width="1" height="1" color-interpolation-filters="sRGB">
+      <feFlood flood-color="#ff0000" result="backdrop"/>
+      <feBlend in="SourceGraphic" in2="backdrop" mode="luminosity"/>
+    </filter>
+  </defs>
+
+  <rect x="10" y="10" width="80" height="80" fill="#999999" filter="url(#filter)"/>
+</svg>
+"##,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect x="10" y="10" width="80" height="80" fill="rgb(255,109,109)"/>
+</svg>
+"##,
+);
+
+test_compare_render_output!(
+    fe_morphology_dilate_per_axis_radius,
+    100,
+    100,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <defs>
+    <filter id="filter" filterUnits="userSpaceOnUse" x="0" y="0" width="100" height="100">
+      <feMorphology operator="dilate" radius="4 1"/>
+    </filter>
+  </defs>
+
+  <rect x="30" y="30" width="40" height="40" fill="black" filter="url(#filter)"/>
+</svg>
+"##,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect x="26" y="29" width="48" height="42" fill="black"/>
+</svg>
+"##,
+);
+
+test_compare_render_output!(
+    fe_morphology_erode_per_axis_radius,
+    100,
+    100,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <defs>
+    <filter id="filter" filterUnits="userSpaceOnUse" x="0" y="0" width="100" height="100">
+      <feMorphology operator="erode" radius="0 2"/>
+    </filter>
+  </defs>
+
+  <rect x="30" y="30" width="40" height="40" fill="black" filter="url(#filter)"/>
+</svg>
+"##,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect x="30" y="32" width="40" height="36" fill="black"/>
+</svg>
+"##,
+);
+
+test_compare_render_output!(
+    fe_morphology_negative_radius_disables_effect,
+    100,
+    100,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <defs>
+    <filter id="filter" filterUnits="userSpaceOnUse" x="0" y="0" width="100" height="100">
+      <feMorphology operator="dilate" radius="5 -1"/>
+    </filter>
+  </defs>
+
+  <rect x="30" y="30" width="40" height="40" fill="black" filter="url(#filter)"/>
+</svg>
+"##,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect x="30" y="30" width="40" height="40" fill="black"/>
+</svg>
+"##,
+);
+
+// With a flat surface and a light that shines straight down, feDiffuseLighting
+// produces the lighting-color, no matter which color space it operates in.
+test_compare_render_output!(
+    fe_diffuse_lighting_in_srgb,
+    100,
+    100,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <defs>
+    <filter id="filter" filterUnits="userSpaceOnUse" x="30" y="30" width="40" height="40"
+            color-interpolation-filters="sRGB">
+      <feDiffuseLighting lighting-color="#808080">
+        <feDistantLight elevation="90"/>
+      </feDiffuseLighting>
+    </filter>
+  </defs>
+
+  <rect x="30" y="30" width="40" height="40" fill="black" filter="url(#filter)"/>
+</svg>
+"##,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect x="30" y="30" width="40" height="40" fill="#808080"/>
+</svg>
+"##,
+);
+
+test_compare_render_output!(
+    fe_diffuse_lighting_in_linear_rgb,
+    100,
+    100,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <defs>
+    <filter id="filter" filterUnits="userSpaceOnUse" x="30" y="30" width="40" height="40"
+            color-interpolation-filters="linearRGB">
+      <feDiffuseLighting lighting-color="#808080">
+        <feDistantLight elevation="90"/>
+      </feDiffuseLighting>
+    </filter>
+  </defs>
+
+  <rect x="30" y="30" width="40" height="40" fill="black" filter="url(#filter)"/>
+</svg>
+"##,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect x="30" y="30" width="40" height="40" fill="#808080"/>
+</svg>
+"##,
+);
+
+// A filtered <use> element acquires itself while it is rendered; that must not be taken as
+// a circular reference that stops the rest of the document from rendering.
+test_compare_render_output!(
+    filtered_use_element_is_not_a_circular_reference,
+    200,
+    100,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="200" height="100">
+  <defs>
+    <filter id="filter">
+      <feOffset dx="0" dy="0"/>
+    </filter>
+    <rect id="shape" width="80" height="80" fill="lime"/>
+  </defs>
+
+  <use xlink:href="#shape" x="10" y="10" filter="url(#filter)"/>
+  <rect x="110" y="10" width="80" height="80" fill="blue"/>
+</svg>
+"##,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100">
+  <rect x="10" y="10" width="80" height="80" fill="lime"/>
+  <rect x="110" y="10" width="80" height="80" fill="blue"/>
+</svg>
+"##,
+);
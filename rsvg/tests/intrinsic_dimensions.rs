@@ -1,4 +1,4 @@
-use rsvg::{CairoRenderer, IntrinsicDimensions, Length, LengthUnit, RenderingError};
+use rsvg::{CairoRenderer, IntrinsicDimensions, Length, LengthUnit, RenderingError, Size};
 
 use rsvg::test_utils::reference_utils::{Compare, Evaluate, Reference};
 use rsvg::test_utils::{load_svg, render_document, SurfaceSize};
@@ -41,6 +41,42 @@ fn has_intrinsic_dimensions() {
     );
 }
 
+#[cfg(feature = "serde")]
+#[test]
+fn intrinsic_dimensions_serde_round_trip() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="50%" height="20mm" viewBox="1 2 30 40"/>
+"#,
+    )
+    .unwrap();
+
+    let dimensions = CairoRenderer::new(&svg).intrinsic_dimensions();
+    let json = serde_json::to_value(dimensions).unwrap();
+
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "width": { "length": 0.5, "unit": "percent" },
+            "height": { "length": 20.0, "unit": "mm" },
+            "vbox": { "x": 1.0, "y": 2.0, "width": 30.0, "height": 40.0 },
+        })
+    );
+
+    let deserialized: IntrinsicDimensions = serde_json::from_value(json).unwrap();
+    assert_eq!(deserialized, dimensions);
+
+    let no_vbox = IntrinsicDimensions {
+        vbox: None,
+        ..dimensions
+    };
+    let json = serde_json::to_string(&no_vbox).unwrap();
+    assert_eq!(
+        serde_json::from_str::<IntrinsicDimensions>(&json).unwrap(),
+        no_vbox
+    );
+}
+
 #[test]
 fn intrinsic_size_in_pixels() {
     let svg = load_svg(
@@ -92,6 +128,164 @@ fn no_intrinsic_size_in_pixels_with_one_missing_dimension() {
     assert_eq!(CairoRenderer::new(&svg).intrinsic_size_in_pixels(), None);
 }
 
+#[test]
+fn best_fit_size_from_width_and_height() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="1in" height="200" viewBox="0 0 10 10"/>
+"#,
+    )
+    .unwrap();
+
+    let renderer = CairoRenderer::new(&svg);
+
+    assert_eq!(
+        renderer.best_fit_size(None),
+        Some(Size {
+            width: 96.0,
+            height: 200.0
+        })
+    );
+
+    // Scaled down to fit, preserving the aspect ratio.
+    assert_eq!(
+        renderer.best_fit_size(Some((100.0, 100.0))),
+        Some(Size {
+            width: 48.0,
+            height: 100.0
+        })
+    );
+
+    // Not scaled up.
+    assert_eq!(
+        renderer.best_fit_size(Some((1000.0, 1000.0))),
+        Some(Size {
+            width: 96.0,
+            height: 200.0
+        })
+    );
+}
+
+#[test]
+fn best_fit_size_from_viewbox() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 400 100"/>
+"#,
+    )
+    .unwrap();
+
+    let renderer = CairoRenderer::new(&svg);
+
+    assert_eq!(
+        renderer.best_fit_size(None),
+        Some(Size {
+            width: 400.0,
+            height: 100.0
+        })
+    );
+    assert_eq!(
+        renderer.best_fit_size(Some((200.0, 200.0))),
+        Some(Size {
+            width: 200.0,
+            height: 50.0
+        })
+    );
+}
+
+#[test]
+fn best_fit_size_with_percentages() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="50" height="100%" viewBox="0 0 100 200"/>
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        CairoRenderer::new(&svg).best_fit_size(None),
+        Some(Size {
+            width: 50.0,
+            height: 100.0
+        })
+    );
+
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100%" height="100%"/>
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(CairoRenderer::new(&svg).best_fit_size(None), None);
+}
+
+#[test]
+fn best_fit_size_rejects_invalid_max_size() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100"/>
+"#,
+    )
+    .unwrap();
+
+    let renderer = CairoRenderer::new(&svg);
+
+    assert_eq!(renderer.best_fit_size(Some((0.0, 100.0))), None);
+    assert_eq!(renderer.best_fit_size(Some((100.0, -1.0))), None);
+    assert_eq!(renderer.best_fit_size(Some((f64::NAN, 100.0))), None);
+    assert_eq!(renderer.best_fit_size(Some((100.0, f64::INFINITY))), None);
+}
+
+#[test]
+fn aspect_ratio_from_width_and_height() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="10" height="20" viewBox="0 0 300 100"/>
+"#,
+    )
+    .unwrap();
+
+    let dim = CairoRenderer::new(&svg).intrinsic_dimensions();
+    assert_eq!(dim.aspect_ratio(), Some(0.5));
+
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="1in" height="72pt"/>
+"#,
+    )
+    .unwrap();
+
+    let dim = CairoRenderer::new(&svg).intrinsic_dimensions();
+    assert_eq!(dim.aspect_ratio(), Some(1.0));
+}
+
+#[test]
+fn aspect_ratio_from_viewbox_with_percent_dimensions() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100%" height="100%" viewBox="0 0 300 100"/>
+"#,
+    )
+    .unwrap();
+
+    let dim = CairoRenderer::new(&svg).intrinsic_dimensions();
+    assert_eq!(dim.aspect_ratio(), Some(3.0));
+}
+
+#[test]
+fn no_aspect_ratio_with_percent_dimensions_and_no_viewbox() {
+    let svg = load_svg(
+        br#"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100%" height="50"/>
+"#,
+    )
+    .unwrap();
+
+    let dim = CairoRenderer::new(&svg).intrinsic_dimensions();
+    assert_eq!(dim.aspect_ratio(), None);
+}
+
 #[test]
 fn root_geometry_with_percent_viewport() {
     let svg = load_svg(
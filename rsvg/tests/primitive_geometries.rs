@@ -166,3 +166,11 @@ fn rect_isolate() {
         "tests/fixtures/primitive_geometries/rect_isolate.svg.geom",
     );
 }
+
+#[test]
+fn use_symbol_overflow_visible() {
+    test(
+        "tests/fixtures/primitive_geometries/use_symbol_overflow_visible.svg",
+        "tests/fixtures/primitive_geometries/use_symbol_overflow_visible.svg.geom",
+    );
+}
@@ -1,6 +1,6 @@
 use rsvg::test_utils::reference_utils::{Compare, Evaluate, Reference};
 use rsvg::test_utils::{load_svg, render_document, SurfaceSize};
-use rsvg::{test_compare_render_output, test_svg_reference};
+use rsvg::{test_compare_render_output, test_svg_reference, Loader};
 
 #[test]
 fn simple_opacity_with_transform() {
@@ -336,6 +336,134 @@ test_compare_render_output!(
     </svg>"##,
 );
 
+// The images in the following tests are tiny PNGs with two pixels: lime and blue, side by
+// side for the wide image, and top to bottom for the tall image.  They are rendered with
+// image-rendering="pixelated" so that the edge between both colors is sharp.
+const WIDE_IMAGE: &str = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAIAAAABCAIAAAB7QOjdAAAAD0lEQVR4nGNg+M/AwPAfAAYBAf9d/01yAAAAAElFTkSuQmCC";
+
+test_compare_render_output!(
+    image_slice_wide_image_in_tall_box,
+    100,
+    200,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="200">
+  <image x="25" y="50" width="50" height="100" preserveAspectRatio="xMidYMid slice"
+         image-rendering="pixelated"
+         href="data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAIAAAABCAIAAAB7QOjdAAAAD0lEQVR4nGNg+M/AwPAfAAYBAf9d/01yAAAAAElFTkSuQmCC"/>
+</svg>"##,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="200">
+  <rect x="25" y="50" width="25" height="100" fill="lime"/>
+  <rect x="50" y="50" width="25" height="100" fill="blue"/>
+</svg>"##,
+);
+
+test_compare_render_output!(
+    image_slice_tall_image_in_wide_box,
+    200,
+    100,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100">
+  <image x="50" y="25" width="100" height="50" preserveAspectRatio="xMidYMid slice"
+         image-rendering="pixelated"
+         href="data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAACCAIAAAAW4yFwAAAADklEQVR4nGNg+M8ABP8BBwEB/zbTlp0AAAAASUVORK5CYII="/>
+</svg>"##,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100">
+  <rect x="50" y="25" width="100" height="25" fill="lime"/>
+  <rect x="50" y="50" width="100" height="25" fill="blue"/>
+</svg>"##,
+);
+
+test_compare_render_output!(
+    image_preserve_aspect_ratio_none_stretches,
+    100,
+    100,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <image width="100" height="100" preserveAspectRatio="none"
+         image-rendering="pixelated"
+         href="data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAIAAAABCAIAAAB7QOjdAAAAD0lEQVR4nGNg+M/AwPAfAAYBAf9d/01yAAAAAElFTkSuQmCC"/>
+</svg>"##,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect width="50" height="100" fill="lime"/>
+  <rect x="50" width="50" height="100" fill="blue"/>
+</svg>"##,
+);
+
+#[test]
+fn image_preserve_aspect_ratio_all_alignments() {
+    // The wide image goes in a 100x100 box at (50, 50).  With "meet" it gets scaled to
+    // 100x50 and is aligned vertically; with "slice" it gets scaled to 200x100, aligned
+    // horizontally, and clipped to the box.
+    const BOX_START: f64 = 50.0;
+    const BOX_END: f64 = 150.0;
+
+    let alignments = [("Min", 0.0), ("Mid", 0.5), ("Max", 1.0)];
+
+    for (x_name, x_fraction) in alignments {
+        for (y_name, y_fraction) in alignments {
+            for slice in [false, true] {
+                let align = format!("x{x_name}Y{y_name}");
+                let mode = if slice { "slice" } else { "meet" };
+
+                let svg = Loader::new()
+                    .read_bytes(
+                        format!(
+                            r#"<svg xmlns="http://www.w3.org/2000/svg" width="200" height="200">
+  <image x="50" y="50" width="100" height="100" preserveAspectRatio="{align} {mode}"
+         image-rendering="pixelated" href="{WIDE_IMAGE}"/>
+</svg>"#
+                        )
+                        .as_bytes(),
+                    )
+                    .unwrap();
+
+                let output_surf = render_document(
+                    &svg,
+                    SurfaceSize(200, 200),
+                    |_| (),
+                    cairo::Rectangle::new(0.0, 0.0, 200.0, 200.0),
+                )
+                .unwrap();
+
+                let (image_width, image_height) =
+                    if slice { (200.0, 100.0) } else { (100.0, 50.0) };
+                let image_x = BOX_START + (100.0 - image_width) * x_fraction;
+                let image_y = BOX_START + (100.0 - image_height) * y_fraction;
+
+                let reference_surf =
+                    cairo::ImageSurface::create(cairo::Format::ARgb32, 200, 200).unwrap();
+
+                {
+                    let cr = cairo::Context::new(&reference_surf).unwrap();
+
+                    let halves = [
+                        ((0.0, 1.0, 0.0), image_x),
+                        ((0.0, 0.0, 1.0), image_x + image_width / 2.0),
+                    ];
+
+                    for ((r, g, b), x0) in halves {
+                        let x1 = (x0 + image_width / 2.0).min(BOX_END);
+                        let x0 = x0.max(BOX_START);
+
+                        if x1 > x0 {
+                            cr.set_source_rgb(r, g, b);
+                            cr.rectangle(x0, image_y, x1 - x0, image_height);
+                            cr.fill().unwrap();
+                        }
+                    }
+                }
+
+                Reference::from_surface(reference_surf)
+                    .compare(&output_surf)
+                    .evaluate(&output_surf, &format!("image_{align}_{mode}"));
+            }
+        }
+    }
+}
+
 test_compare_render_output!(
     transform_property,
     100,
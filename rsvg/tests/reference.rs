@@ -203,12 +203,14 @@ macro_rules! t {
 mod tests {
     use super::*;
 
+    #[cfg(feature = "text")]
     t!(a_pseudo_class_svg,                                          "a-pseudo-class.svg");
     t!(bug483_attribute_selectors_svg,                              "bug483-attribute-selectors.svg");
     t!(bug525_specificity_svg,                                      "bug525-specificity.svg");
     t!(css_import_svg,                                              "css-import.svg");
     t!(css_import_url_svg,                                          "css-import-url.svg");
     t!(duplicate_id_svg,                                            "duplicate-id.svg");
+    #[cfg(feature = "text")]
     t!(filter_component_transfer_from_reference_page_svg,           "filter-component-transfer-from-reference-page.svg");
     t!(filter_conv_bounds_svg,                                      "filter-conv-bounds.svg");
     t!(filter_conv_divisor_svg,                                     "filter-conv-divisor.svg");
@@ -216,13 +218,17 @@ mod tests {
     t!(filter_image_from_reference_page_svg,                        "filter-image-from-reference-page.svg");
     t!(filter_kernel_unit_length_svg,                               "filter-kernel-unit-length.svg");
     t!(filter_offset_svg,                                           "filter-offset.svg");
+    #[cfg(feature = "text")]
     t!(font_shorthand_svg,                                          "font-shorthand.svg");
     t!(gzip_compressed_svg,                                         "gzip-compressed.svg");
     t!(hexchat_svg,                                                 "hexchat.svg");
     t!(ignore_filter_composite_color_interpolation_filters_svg,     "ignore-filter-composite-color-interpolation-filters.svg");
     t!(include_compressed_svg,                                      "include-compressed.svg");
+    #[cfg(feature = "text")]
     t!(include_fallback_svg,                                        "include-fallback.svg");
+    #[cfg(feature = "text")]
     t!(include_text_svg,                                            "include-text.svg");
+    #[cfg(feature = "text")]
     t!(rtl_tspan_svg,                                               "rtl-tspan.svg");
     t!(specificity_svg,                                             "specificity.svg");
     t!(structural_pseudo_classes_svg,                               "structural-pseudo-classes.svg");
@@ -230,6 +236,7 @@ mod tests {
     t!(system_language_de_svg,                                      "system-language-de.svg");
     t!(system_language_en_svg,                                      "system-language-en.svg");
     t!(system_language_other_svg,                                   "system-language-other.svg");
+    #[cfg(feature = "text")]
     t!(text_objectboundingbox_svg,                                  "text-objectBoundingBox.svg");
     t!(xml_lang_css_inherit_svg,                                    "xml-lang-css-inherit.svg");
     t!(xml_lang_css_svg,                                            "xml-lang-css.svg");
@@ -721,8 +728,11 @@ mod tests {
     t!(adwaita_zoom_in_symbolic_svg,                                "adwaita/zoom-in-symbolic.svg");
     t!(adwaita_zoom_original_symbolic_svg,                          "adwaita/zoom-original-symbolic.svg");
     t!(adwaita_zoom_out_symbolic_svg,                               "adwaita/zoom-out-symbolic.svg");
+    #[cfg(feature = "text")]
     t!(bugs_a_inside_text_content_738_svg,                          "bugs/a-inside-text-content-738.svg");
+    #[cfg(feature = "text")]
     t!(bugs_a_inside_text_content_pseudo_class_738_svg,             "bugs/a-inside-text-content-pseudo-class-738.svg");
+    #[cfg(feature = "text")]
     t!(bugs_bug108_font_size_relative_svg,                          "bugs/bug108-font-size-relative.svg");
     t!(bugs_bug112_svg_delayed_attributes_svg,                      "bugs/bug112-svg-delayed-attributes.svg");
     t!(bugs_bug165_zero_length_subpath_square_linecap_svg,          "bugs/bug165-zero-length-subpath-square-linecap.svg");
@@ -730,31 +740,41 @@ mod tests {
     t!(bugs_bug241_light_source_type_svg,                           "bugs/bug241-light-source-type.svg");
     t!(bugs_bug245_negative_dashoffset_svg,                         "bugs/bug245-negative-dashoffset.svg");
     t!(bugs_bug282_drop_shadow_svg,                                 "bugs/bug282-drop-shadow.svg");
+    #[cfg(feature = "text")]
     t!(bugs_bug340047_svg,                                          "bugs/bug340047.svg");
+    #[cfg(feature = "text")]
     t!(bugs_bug363_missing_space_svg,                               "bugs/bug363-missing-space.svg");
     t!(bugs_bug372_small_arcs_svg,                                  "bugs/bug372-small-arcs.svg");
     t!(bugs_bug373_gradient_userspaceonuse_svg,                     "bugs/bug373-gradient-userspaceonuse.svg");
     t!(bugs_bug403357_svg,                                          "bugs/bug403357.svg");
     t!(bugs_bug476507_svg,                                          "bugs/bug476507.svg");
+    #[cfg(feature = "text")]
     t!(bugs_bug481_tspan_uses_at_least_first_x_svg,                 "bugs/bug481-tspan-uses-at-least-first-x.svg");
+    #[cfg(feature = "text")]
     t!(bugs_bug494_text_accumulate_dy_svg,                          "bugs/bug494-text-accumulate-dy.svg");
     t!(bugs_bug506_pattern_fallback_svg,                            "bugs/bug506-pattern-fallback.svg");
     t!(bugs_bug510_pattern_fill_opacity_svg,                        "bugs/bug510-pattern-fill-opacity.svg");
     t!(bugs_bug510_pattern_fill_svg,                                "bugs/bug510-pattern-fill.svg");
     t!(bugs_bug548_data_url_without_mimetype_svg,                   "bugs/bug548-data-url-without-mimetype.svg");
     t!(bugs_bug563933_svg,                                          "bugs/bug563933.svg");
+    #[cfg(feature = "text")]
     t!(bugs_bug587721_text_transform_svg,                           "bugs/bug587721-text-transform.svg");
     t!(bugs_bug590_mask_units_svg,                                  "bugs/bug590-mask-units.svg");
     t!(bugs_bug603550_mask_luminance_svg,                           "bugs/bug603550-mask-luminance.svg");
     t!(bugs_bug609_clippath_transform_svg,                          "bugs/bug609-clippath-transform.svg");
     t!(bugs_bug634324_blur_negative_transform_svg,                  "bugs/bug634324-blur-negative-transform.svg");
+    #[cfg(feature = "text")]
     t!(bugs_bug642_nested_tspan_dx_dy_svg,                          "bugs/bug642-nested-tspan-dx-dy.svg");
+    #[cfg(feature = "text")]
     t!(bugs_bug667_tspan_visibility_svg,                            "bugs/bug667-tspan-visibility.svg");
+    #[cfg(feature = "text")]
     t!(bugs_bug668_small_caps_svg,                                  "bugs/bug668-small-caps.svg");
     t!(bugs_bug689832_unresolved_gradient_svg,                      "bugs/bug689832-unresolved-gradient.svg");
     t!(bugs_bug718_rect_negative_rx_ry_svg,                         "bugs/bug718-rect-negative-rx-ry.svg");
+    #[cfg(feature = "text")]
     t!(bugs_bug730_font_scaling_svg,                                "bugs/bug730-font-scaling.svg");
     t!(bugs_bug738367_svg,                                          "bugs/bug738367.svg");
+    #[cfg(feature = "text")]
     t!(bugs_bug760180_svg,                                          "bugs/bug760180.svg");
     t!(bugs_bug761175_recursive_masks_svg,                          "bugs/bug761175-recursive-masks.svg");
     t!(bugs_bug761871_reset_reflection_points_svg,                  "bugs/bug761871-reset-reflection-points.svg");
@@ -764,171 +784,330 @@ mod tests {
     t!(bugs_bug788_inner_svg_viewbox_svg,                           "bugs/bug788-inner-svg-viewbox.svg");
     t!(bugs_bug1128_elliptical_arcs_big_radius_svg,                 "bugs/bug1128-elliptical-arcs-big-radius.svg");
     t!(bugs_ignore_577_multiple_font_families_svg,                  "bugs/ignore-577-multiple-font-families.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_coords_trans_01_b_svg,                                "svg1.1/coords-trans-01-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_coords_trans_02_t_svg,                                "svg1.1/coords-trans-02-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_coords_trans_03_t_svg,                                "svg1.1/coords-trans-03-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_coords_trans_04_t_svg,                                "svg1.1/coords-trans-04-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_coords_trans_05_t_svg,                                "svg1.1/coords-trans-05-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_coords_trans_06_t_svg,                                "svg1.1/coords-trans-06-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_coords_trans_07_t_svg,                                "svg1.1/coords-trans-07-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_coords_trans_08_t_svg,                                "svg1.1/coords-trans-08-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_coords_trans_09_t_svg,                                "svg1.1/coords-trans-09-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_coords_viewattr_01_b_svg,                             "svg1.1/coords-viewattr-01-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_coords_viewattr_02_b_svg,                             "svg1.1/coords-viewattr-02-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_coords_viewattr_03_b_svg,                             "svg1.1/coords-viewattr-03-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_coords_viewattr_04_f_svg,                             "svg1.1/coords-viewattr-04-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_background_01_f_svg,                          "svg1.1/filters-background-01-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_blend_01_b_svg,                               "svg1.1/filters-blend-01-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_color_01_b_svg,                               "svg1.1/filters-color-01-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_color_02_b_svg,                               "svg1.1/filters-color-02-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_composite_02_b_svg,                           "svg1.1/filters-composite-02-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_composite_03_f_svg,                           "svg1.1/filters-composite-03-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_composite_04_f_svg,                           "svg1.1/filters-composite-04-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_composite_05_f_svg,                           "svg1.1/filters-composite-05-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_comptran_01_b_svg,                            "svg1.1/filters-comptran-01-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_conv_01_f_svg,                                "svg1.1/filters-conv-01-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_conv_02_f_svg,                                "svg1.1/filters-conv-02-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_conv_03_f_svg,                                "svg1.1/filters-conv-03-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_conv_04_f_svg,                                "svg1.1/filters-conv-04-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_conv_05_f_svg,                                "svg1.1/filters-conv-05-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_diffuse_01_f_svg,                             "svg1.1/filters-diffuse-01-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_displace_02_f_svg,                            "svg1.1/filters-displace-02-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_felem_02_f_svg,                               "svg1.1/filters-felem-02-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_gauss_01_b_svg,                               "svg1.1/filters-gauss-01-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_gauss_02_f_svg,                               "svg1.1/filters-gauss-02-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_gauss_03_f_svg,                               "svg1.1/filters-gauss-03-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_image_01_b_svg,                               "svg1.1/filters-image-01-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_image_02_b_svg,                               "svg1.1/filters-image-02-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_image_03_f_svg,                               "svg1.1/filters-image-03-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_image_04_f_svg,                               "svg1.1/filters-image-04-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_image_05_f_svg,                               "svg1.1/filters-image-05-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_light_01_f_svg,                               "svg1.1/filters-light-01-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_light_02_f_svg,                               "svg1.1/filters-light-02-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_light_03_f_svg,                               "svg1.1/filters-light-03-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_light_04_f_svg,                               "svg1.1/filters-light-04-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_light_05_f_svg,                               "svg1.1/filters-light-05-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_morph_01_f_svg,                               "svg1.1/filters-morph-01-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_offset_01_b_svg,                              "svg1.1/filters-offset-01-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_overview_01_b_svg,                            "svg1.1/filters-overview-01-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_overview_02_b_svg,                            "svg1.1/filters-overview-02-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_overview_03_b_svg,                            "svg1.1/filters-overview-03-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_specular_01_f_svg,                            "svg1.1/filters-specular-01-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_tile_01_b_svg,                                "svg1.1/filters-tile-01-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_turb_01_f_svg,                                "svg1.1/filters-turb-01-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_filters_turb_02_f_svg,                                "svg1.1/filters-turb-02-f.svg");
     t!(svg1_1_ignore_filters_displace_01_f_svg,                     "svg1.1/ignore-filters-displace-01-f.svg");
     t!(svg1_1_ignore_filters_example_01_b_svg,                      "svg1.1/ignore-filters-example-01-b.svg");
     t!(svg1_1_ignore_masking_path_07_b_svg,                         "svg1.1/ignore-masking-path-07-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_masking_filter_01_f_svg,                              "svg1.1/masking-filter-01-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_masking_intro_01_f_svg,                               "svg1.1/masking-intro-01-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_masking_mask_01_b_svg,                                "svg1.1/masking-mask-01-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_masking_mask_02_f_svg,                                "svg1.1/masking-mask-02-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_masking_opacity_01_b_svg,                             "svg1.1/masking-opacity-01-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_masking_path_01_b_svg,                                "svg1.1/masking-path-01-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_masking_path_02_b_svg,                                "svg1.1/masking-path-02-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_masking_path_03_b_svg,                                "svg1.1/masking-path-03-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_masking_path_04_b_svg,                                "svg1.1/masking-path-04-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_masking_path_05_f_svg,                                "svg1.1/masking-path-05-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_masking_path_08_b_svg,                                "svg1.1/masking-path-08-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_painting_control_02_f_svg,                            "svg1.1/painting-control-02-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_painting_marker_01_f_svg,                             "svg1.1/painting-marker-01-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_painting_marker_02_f_svg,                             "svg1.1/painting-marker-02-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_painting_marker_03_f_svg,                             "svg1.1/painting-marker-03-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_painting_marker_04_f_svg,                             "svg1.1/painting-marker-04-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_painting_marker_06_f_svg,                             "svg1.1/painting-marker-06-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_painting_marker_07_f_svg,                             "svg1.1/painting-marker-07-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_painting_marker_properties_01_f_svg,                  "svg1.1/painting-marker-properties-01-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_painting_stroke_01_t_svg,                             "svg1.1/painting-stroke-01-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_painting_stroke_02_t_svg,                             "svg1.1/painting-stroke-02-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_painting_stroke_03_t_svg,                             "svg1.1/painting-stroke-03-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_painting_stroke_04_t_svg,                             "svg1.1/painting-stroke-04-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_painting_stroke_05_t_svg,                             "svg1.1/painting-stroke-05-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_painting_stroke_06_t_svg,                             "svg1.1/painting-stroke-06-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_painting_stroke_07_t_svg,                             "svg1.1/painting-stroke-07-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_painting_stroke_08_t_svg,                             "svg1.1/painting-stroke-08-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_painting_stroke_09_t_svg,                             "svg1.1/painting-stroke-09-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_paths_data_01_t_svg,                                  "svg1.1/paths-data-01-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_paths_data_02_t_svg,                                  "svg1.1/paths-data-02-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_paths_data_03_f_svg,                                  "svg1.1/paths-data-03-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_paths_data_04_t_svg,                                  "svg1.1/paths-data-04-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_paths_data_05_t_svg,                                  "svg1.1/paths-data-05-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_paths_data_06_t_svg,                                  "svg1.1/paths-data-06-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_paths_data_07_t_svg,                                  "svg1.1/paths-data-07-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_paths_data_08_t_svg,                                  "svg1.1/paths-data-08-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_paths_data_09_t_svg,                                  "svg1.1/paths-data-09-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_paths_data_10_t_svg,                                  "svg1.1/paths-data-10-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_paths_data_12_t_svg,                                  "svg1.1/paths-data-12-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_paths_data_13_t_svg,                                  "svg1.1/paths-data-13-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_paths_data_14_t_svg,                                  "svg1.1/paths-data-14-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_paths_data_15_t_svg,                                  "svg1.1/paths-data-15-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_paths_data_16_t_svg,                                  "svg1.1/paths-data-16-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_paths_data_17_f_svg,                                  "svg1.1/paths-data-17-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_paths_data_18_f_svg,                                  "svg1.1/paths-data-18-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_paths_data_19_f_svg,                                  "svg1.1/paths-data-19-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_paths_data_20_f_svg,                                  "svg1.1/paths-data-20-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_grad_01_b_svg,                               "svg1.1/pservers-grad-01-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_grad_02_b_svg,                               "svg1.1/pservers-grad-02-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_grad_03_b_svg,                               "svg1.1/pservers-grad-03-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_grad_04_b_svg,                               "svg1.1/pservers-grad-04-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_grad_05_b_svg,                               "svg1.1/pservers-grad-05-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_grad_06_b_svg,                               "svg1.1/pservers-grad-06-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_grad_07_b_svg,                               "svg1.1/pservers-grad-07-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_grad_08_b_svg,                               "svg1.1/pservers-grad-08-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_grad_09_b_svg,                               "svg1.1/pservers-grad-09-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_grad_10_b_svg,                               "svg1.1/pservers-grad-10-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_grad_11_b_svg,                               "svg1.1/pservers-grad-11-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_grad_12_b_svg,                               "svg1.1/pservers-grad-12-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_grad_14_b_svg,                               "svg1.1/pservers-grad-14-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_grad_15_b_svg,                               "svg1.1/pservers-grad-15-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_grad_16_b_svg,                               "svg1.1/pservers-grad-16-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_grad_18_b_svg,                               "svg1.1/pservers-grad-18-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_grad_22_b_svg,                               "svg1.1/pservers-grad-22-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_grad_23_f_svg,                               "svg1.1/pservers-grad-23-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_grad_24_f_svg,                               "svg1.1/pservers-grad-24-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_grad_stops_01_f_svg,                         "svg1.1/pservers-grad-stops-01-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_pattern_01_b_svg,                            "svg1.1/pservers-pattern-01-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_pattern_02_f_svg,                            "svg1.1/pservers-pattern-02-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_pattern_03_f_svg,                            "svg1.1/pservers-pattern-03-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_pattern_04_f_svg,                            "svg1.1/pservers-pattern-04-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_pattern_05_f_svg,                            "svg1.1/pservers-pattern-05-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_pattern_06_f_svg,                            "svg1.1/pservers-pattern-06-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_pattern_07_f_svg,                            "svg1.1/pservers-pattern-07-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_pattern_08_f_svg,                            "svg1.1/pservers-pattern-08-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_pservers_pattern_09_f_svg,                            "svg1.1/pservers-pattern-09-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_shapes_intro_01_t_svg,                                "svg1.1/shapes-intro-01-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_shapes_intro_02_f_svg,                                "svg1.1/shapes-intro-02-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_struct_cond_01_t_svg,                                 "svg1.1/struct-cond-01-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_struct_cond_03_t_svg,                                 "svg1.1/struct-cond-03-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_struct_group_03_t_svg,                                "svg1.1/struct-group-03-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_struct_image_05_b_svg,                                "svg1.1/struct-image-05-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_struct_svg_03_f_svg,                                  "svg1.1/struct-svg-03-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_struct_symbol_01_b_svg,                               "svg1.1/struct-symbol-01-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_struct_use_01_t_svg,                                  "svg1.1/struct-use-01-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_struct_use_03_t_svg,                                  "svg1.1/struct-use-03-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_struct_use_04_b_svg,                                  "svg1.1/struct-use-04-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_struct_use_09_b_svg,                                  "svg1.1/struct-use-09-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_struct_use_10_f_svg,                                  "svg1.1/struct-use-10-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_styling_css_01_b_svg,                                 "svg1.1/styling-css-01-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_styling_css_02_b_svg,                                 "svg1.1/styling-css-02-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_styling_css_03_b_svg,                                 "svg1.1/styling-css-03-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_styling_css_04_f_svg,                                 "svg1.1/styling-css-04-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_styling_css_07_f_svg,                                 "svg1.1/styling-css-07-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_styling_css_08_f_svg,                                 "svg1.1/styling-css-08-f.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_text_align_01_b_svg,                                  "svg1.1/text-align-01-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_text_align_02_b_svg,                                  "svg1.1/text-align-02-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_text_align_03_b_svg,                                  "svg1.1/text-align-03-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_text_fonts_02_t_svg,                                  "svg1.1/text-fonts-02-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_text_text_03_b_svg,                                   "svg1.1/text-text-03-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_text_text_08_b_svg,                                   "svg1.1/text-text-08-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_text_text_10_t_svg,                                   "svg1.1/text-text-10-t.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_text_tref_01_b_svg,                                   "svg1.1/text-tref-01-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_text_tref_02_b_svg,                                   "svg1.1/text-tref-02-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_text_tref_03_b_svg,                                   "svg1.1/text-tref-03-b.svg");
+    #[cfg(feature = "text")]
     t!(svg1_1_types_basic_02_f_svg,                                 "svg1.1/types-basic-02-f.svg");
     t!(svg2_gradient_01_b_svg,                                      "svg2/gradient-01-b.svg");
     t!(svg2_mix_blend_mode_svg,                                     "svg2/mix-blend-mode.svg");
     t!(svg2_multi_filter_svg,                                       "svg2/multi-filter.svg");
     t!(svg2_paint_order_svg,                                        "svg2/paint-order.svg");
+    #[cfg(feature = "text")]
     t!(svg2_text_paint_order_svg,                                   "svg2/text-paint-order.svg");
 }
 
@@ -1070,6 +1249,22 @@ test_compare_render_output!(
     </svg>"##,
 );
 
+test_compare_render_output!(
+    paint_order_stroke_before_fill,
+    100,
+    100,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+    <svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+      <rect x="20" y="20" width="60" height="60" fill="lime" stroke="blue" stroke-width="20"
+            style="paint-order: stroke fill"/>
+    </svg>"##,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+    <svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+      <rect x="20" y="20" width="60" height="60" fill="none" stroke="blue" stroke-width="20"/>
+      <rect x="20" y="20" width="60" height="60" fill="lime"/>
+    </svg>"##,
+);
+
 test_compare_render_output!(
     use_context_stroke,
     100,
@@ -1207,3 +1402,39 @@ test_svg_reference!(
     "tests/fixtures/reftests/bugs-reftests/bug1121-feimage-embedded-svg.svg",
     "tests/fixtures/reftests/bugs-reftests/bug1121-feimage-embedded-svg-ref.svg"
 );
+
+test_svg_reference!(
+    mix_blend_mode_multiply,
+    "tests/fixtures/reftests/svg2/mix-blend-mode-multiply.svg",
+    "tests/fixtures/reftests/svg2/mix-blend-mode-multiply-ref.svg"
+);
+
+test_svg_reference!(
+    isolation_isolate,
+    "tests/fixtures/reftests/svg2/isolation-isolate.svg",
+    "tests/fixtures/reftests/svg2/isolation-isolate-ref.svg"
+);
+
+test_svg_reference!(
+    symbol_overflow_visible,
+    "tests/fixtures/reftests/symbol-overflow-visible.svg",
+    "tests/fixtures/reftests/symbol-overflow-visible-ref.svg"
+);
+
+test_svg_reference!(
+    symbol_overflow_hidden,
+    "tests/fixtures/reftests/symbol-overflow-hidden.svg",
+    "tests/fixtures/reftests/symbol-overflow-hidden-ref.svg"
+);
+
+test_svg_reference!(
+    clip_path_bbox_units_rotated_rect,
+    "tests/fixtures/reftests/clip-path-bbox-units-rotated-rect.svg",
+    "tests/fixtures/reftests/clip-path-bbox-units-rotated-rect-ref.svg"
+);
+
+test_svg_reference!(
+    clip_path_bbox_units_ellipse,
+    "tests/fixtures/reftests/clip-path-bbox-units-ellipse.svg",
+    "tests/fixtures/reftests/clip-path-bbox-units-ellipse-ref.svg"
+);
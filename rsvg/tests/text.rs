@@ -1,5 +1,6 @@
 use float_cmp::approx_eq;
-use rsvg::{CairoRenderer, Loader};
+use rsvg::tests_only::{SharedImageSurface, SurfaceType};
+use rsvg::{CairoRenderer, FontConfig, Loader, WhitespaceMode};
 
 use rsvg::test_utils::setup_font_map;
 use rsvg::{test_compare_render_output, test_svg_reference};
@@ -85,6 +86,124 @@ test_svg_reference!(
     "tests/fixtures/text/bug1109-scaled-text-anchor-middle-ref.svg"
 );
 
+// Text on a circle that goes clockwise from an angle of -45 degrees.  The circumference
+// is 80, the width of four Ahem glyphs of 20px, so the midpoints of the glyphs are at
+// angles of 0, 90, 180, and 270 degrees.  The reference draws each glyph as a square
+// centered on that point of the circle, rotated to follow the tangent, with the top of
+// the glyph facing outwards.  This keeps the glyphs aligned to the pixel grid.
+test_compare_render_output!(
+    text_path_on_circle,
+    200,
+    200,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="200">
+  <defs>
+    <path id="circle" d="M 109.0032 90.9968 A 12.7324 12.7324 0 0 1 90.9968 109.0032 A 12.7324 12.7324 0 0 1 109.0032 90.9968 Z"/>
+  </defs>
+  <text style="font: 20px Ahem;" fill="black">
+    <textPath href="#circle">abcd</textPath>
+  </text>
+</svg>"##,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="200">
+  <g transform="translate(100 100)">
+    <rect transform="rotate(0) translate(12.7324 0) rotate(90)" x="-10" y="-16" width="20" height="20"/>
+    <rect transform="rotate(90) translate(12.7324 0) rotate(90)" x="-10" y="-16" width="20" height="20"/>
+    <rect transform="rotate(180) translate(12.7324 0) rotate(90)" x="-10" y="-16" width="20" height="20"/>
+    <rect transform="rotate(270) translate(12.7324 0) rotate(90)" x="-10" y="-16" width="20" height="20"/>
+  </g>
+</svg>"##,
+);
+
+// The path is 40px long, so only the first two glyphs have their midpoints on it.
+test_compare_render_output!(
+    text_path_does_not_render_glyphs_past_the_end,
+    100,
+    100,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <path id="line" d="M 10 50 L 50 50" fill="none"/>
+  <text style="font: 20px Ahem;" fill="black">
+    <textPath href="#line">abcd</textPath>
+  </text>
+</svg>"##,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <rect x="10" y="34" width="40" height="20" fill="black"/>
+</svg>"##,
+);
+
+// A percentage in startOffset is relative to the length of the path.
+test_compare_render_output!(
+    text_path_start_offset_percentage,
+    200,
+    100,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100">
+  <path id="line" d="M 10 50 L 110 50" fill="none"/>
+  <text style="font: 20px Ahem;" fill="black">
+    <textPath href="#line" startOffset="50%">ab</textPath>
+  </text>
+</svg>"##,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100">
+  <rect x="60" y="34" width="40" height="20" fill="black"/>
+</svg>"##,
+);
+
+// A textPath can reference basic shapes, not just paths.
+test_compare_render_output!(
+    text_path_on_basic_shape,
+    200,
+    100,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100">
+  <line id="line" x1="10" y1="50" x2="110" y2="50"/>
+  <text style="font: 20px Ahem;" fill="black">
+    <textPath href="#line">ab</textPath>
+  </text>
+</svg>"##,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100">
+  <rect x="10" y="34" width="40" height="20" fill="black"/>
+</svg>"##,
+);
+
+// "sub" moves the glyph down by 20% of the line-height, which is normally the font-size.
+test_compare_render_output!(
+    baseline_shift_sub,
+    300,
+    200,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="300" height="200">
+  <text style="font: 50px Ahem;" x="50" y="100" fill="black">H<tspan baseline-shift="sub">2</tspan>O</text>
+</svg>"##,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="300" height="200">
+  <rect x="50" y="60" width="50" height="50" fill="black"/>
+  <rect x="100" y="70" width="50" height="50" fill="black"/>
+  <rect x="150" y="60" width="50" height="50" fill="black"/>
+</svg>"##,
+);
+
+// Nested shifts add up: "super" is 40% up, then "sub" is 20% down, then 0.4em is up.
+test_compare_render_output!(
+    baseline_shift_nested,
+    300,
+    200,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="300" height="200">
+  <text style="font: 50px Ahem;" x="50" y="100" fill="black">a<tspan baseline-shift="super">b<tspan baseline-shift="sub">c<tspan baseline-shift="0.4em">d</tspan></tspan></tspan></text>
+</svg>"##,
+    br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="300" height="200">
+  <rect x="50" y="60" width="50" height="50" fill="black"/>
+  <rect x="100" y="40" width="50" height="50" fill="black"/>
+  <rect x="150" y="50" width="50" height="50" fill="black"/>
+  <rect x="200" y="30" width="50" height="50" fill="black"/>
+</svg>"##,
+);
+
 fn rect(x: f64, y: f64, width: f64, height: f64) -> cairo::Rectangle {
     cairo::Rectangle::new(x, y, width, height)
 }
@@ -129,3 +248,269 @@ fn test_text_layer_geometry() {
         );
     }
 }
+
+fn ink_rect_with_whitespace_mode(mode: WhitespaceMode, input: &'static [u8]) -> cairo::Rectangle {
+    let bytes = glib::Bytes::from_static(input);
+    let stream = gio::MemoryInputStream::from_bytes(&bytes);
+
+    let handle = Loader::new()
+        .with_whitespace_mode(mode)
+        .read_stream(&stream, None::<&gio::File>, None::<&gio::Cancellable>)
+        .unwrap();
+
+    let renderer = CairoRenderer::new(&handle).test_mode(true);
+    let viewport = rect(0.0, 0.0, 500.0, 500.0);
+
+    let (ink_rect, _) = renderer.geometry_for_layer(Some("#t"), &viewport).unwrap();
+    ink_rect
+}
+
+// Each Ahem glyph, including the space, is 1em wide; see the comment for the ahem_font test.
+#[test]
+fn whitespace_mode_preserves_leading_spaces() {
+    setup_font_map();
+
+    let input = br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="500" height="500">
+  <text id="t" style="font: 50px Ahem;" x="50" y="50">  abc</text>
+</svg>"##;
+
+    let collapsed = ink_rect_with_whitespace_mode(WhitespaceMode::Default, input);
+    assert!(rectangle_approx_eq(
+        &collapsed,
+        &rect(50.0, 10.0, 150.0, 50.0)
+    ));
+
+    let preserved = ink_rect_with_whitespace_mode(WhitespaceMode::Preserve, input);
+    assert!(rectangle_approx_eq(
+        &preserved,
+        &rect(150.0, 10.0, 150.0, 50.0)
+    ));
+}
+
+#[test]
+fn whitespace_mode_preserve_honors_explicit_xml_space() {
+    setup_font_map();
+
+    let input = br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="500" height="500">
+  <text id="t" xml:space="default" style="font: 50px Ahem;" x="50" y="50">  abc</text>
+</svg>"##;
+
+    let preserved = ink_rect_with_whitespace_mode(WhitespaceMode::Preserve, input);
+    assert!(rectangle_approx_eq(
+        &preserved,
+        &rect(50.0, 10.0, 150.0, 50.0)
+    ));
+
+    let forced = ink_rect_with_whitespace_mode(WhitespaceMode::ForcePreserve, input);
+    assert!(rectangle_approx_eq(
+        &forced,
+        &rect(150.0, 10.0, 150.0, 50.0)
+    ));
+}
+
+// Letter spacing goes between glyphs, so it does not extend the ink rectangle past the
+// first and last glyphs.
+#[test]
+fn letter_spacing_widens_text_geometry() {
+    setup_font_map();
+
+    let widened = ink_rect_with_whitespace_mode(
+        WhitespaceMode::Default,
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="500" height="500">
+  <text id="t" style="font: 20px Ahem; letter-spacing: 4px;" x="50" y="50">abc</text>
+</svg>"##,
+    );
+    assert!(approx_eq!(f64, widened.width(), 68.0, epsilon = 0.01));
+
+    let narrowed = ink_rect_with_whitespace_mode(
+        WhitespaceMode::Default,
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="500" height="500">
+  <text id="t" style="font: 20px Ahem;" letter-spacing="-4" x="50" y="50">abc</text>
+</svg>"##,
+    );
+    assert!(approx_eq!(f64, narrowed.width(), 52.0, epsilon = 0.01));
+}
+
+#[test]
+fn word_spacing_widens_gaps_between_words() {
+    setup_font_map();
+
+    let ink_rect = ink_rect_with_whitespace_mode(
+        WhitespaceMode::Default,
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="500" height="500">
+  <text id="t" style="font: 20px Ahem; word-spacing: 10px;" x="50" y="50">a b</text>
+</svg>"##,
+    );
+    assert!(approx_eq!(f64, ink_rect.width(), 70.0, epsilon = 0.01));
+}
+
+#[test]
+fn text_anchor_middle_centers_letter_spaced_text() {
+    setup_font_map();
+
+    let ink_rect = ink_rect_with_whitespace_mode(
+        WhitespaceMode::Default,
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="500" height="500">
+  <text id="t" style="font: 20px Ahem; letter-spacing: 4px; word-spacing: 6px;"
+        text-anchor="middle" x="100" y="50">ab cd</text>
+</svg>"##,
+    );
+    assert!(approx_eq!(f64, ink_rect.width(), 122.0, epsilon = 0.01));
+    assert!(approx_eq!(
+        f64,
+        ink_rect.x() + ink_rect.width() / 2.0,
+        100.0,
+        epsilon = 0.01
+    ));
+}
+
+#[test]
+fn baseline_shift_is_included_in_text_geometry() {
+    setup_font_map();
+
+    let ink_rect = ink_rect_with_whitespace_mode(
+        WhitespaceMode::Default,
+        br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="500" height="500">
+  <text id="t" style="font: 50px Ahem;" x="50" y="100">H<tspan baseline-shift="sub">2</tspan>O</text>
+</svg>"##,
+    );
+    assert!(rectangle_approx_eq(
+        &ink_rect,
+        &rect(50.0, 60.0, 150.0, 60.0)
+    ));
+}
+
+#[test]
+fn checked_fonts_reports_nothing_for_usable_fonts() {
+    setup_font_map();
+
+    let input = br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <text style="font: 50px Ahem;" x="10" y="50">ab</text>
+</svg>"##;
+
+    let bytes = glib::Bytes::from_static(input);
+    let stream = gio::MemoryInputStream::from_bytes(&bytes);
+
+    let handle = Loader::new()
+        .read_stream(&stream, None::<&gio::File>, None::<&gio::Cancellable>)
+        .unwrap();
+
+    let renderer = CairoRenderer::new(&handle).test_mode(true);
+
+    let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+    let cr = cairo::Context::new(&surface).unwrap();
+
+    let warnings = renderer
+        .render_document_checked_fonts(&cr, &rect(0.0, 0.0, 100.0, 100.0))
+        .unwrap();
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn checked_fonts_takes_quoted_family_lists_and_paints_background() {
+    setup_font_map();
+
+    let input = br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <text style="font-size: 50px; font-family: 'Ahem', &quot;No Such Font&quot;, sans-serif;"
+        x="10" y="50">ab</text>
+</svg>"##;
+
+    let bytes = glib::Bytes::from_static(input);
+    let stream = gio::MemoryInputStream::from_bytes(&bytes);
+
+    let handle = Loader::new()
+        .read_stream(&stream, None::<&gio::File>, None::<&gio::Cancellable>)
+        .unwrap();
+
+    let white = rsvg::parse_color("white").unwrap();
+    let renderer = CairoRenderer::new(&handle)
+        .test_mode(true)
+        .with_background_color(Some(white));
+
+    let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+
+    let warnings = {
+        let cr = cairo::Context::new(&surface).unwrap();
+
+        renderer
+            .render_document_checked_fonts(&cr, &rect(0.0, 0.0, 100.0, 100.0))
+            .unwrap()
+    };
+    assert!(warnings.is_empty());
+
+    let surface = SharedImageSurface::wrap(surface, SurfaceType::SRgb).unwrap();
+    let pixel = surface.get_pixel(95, 95);
+    assert_eq!((pixel.r, pixel.g, pixel.b, pixel.a), (255, 255, 255, 255));
+}
+
+// Fontconfig keeps the fonts it has scanned, so overwriting the file afterwards gives a
+// font that Pango picks but that cannot be loaded.
+#[cfg(all(not(target_os = "macos"), not(target_os = "windows")))]
+#[test]
+fn checked_fonts_falls_back_from_a_corrupt_font() {
+    let dir = std::env::temp_dir().join(format!("rsvg-corrupt-font-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let ahem = dir.join("Ahem.ttf");
+    std::fs::copy(
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/resources/Ahem.ttf"),
+        &ahem,
+    )
+    .unwrap();
+
+    let mut fonts = FontConfig::empty();
+    fonts.add_font_file(&ahem).unwrap();
+    fonts
+        .add_font_file(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/resources/DejaVuSans.ttf"
+        ))
+        .unwrap();
+
+    std::fs::write(&ahem, b"this is not a font").unwrap();
+
+    let input = br##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+  <text style="font-size: 50px; font-family: 'Ahem', &quot;DejaVu Sans&quot;;"
+        x="10" y="60" fill="black">ab</text>
+</svg>"##;
+
+    let bytes = glib::Bytes::from_static(input);
+    let stream = gio::MemoryInputStream::from_bytes(&bytes);
+
+    let handle = Loader::new()
+        .read_stream(&stream, None::<&gio::File>, None::<&gio::Cancellable>)
+        .unwrap();
+
+    let renderer = CairoRenderer::new(&handle).with_fonts(fonts);
+
+    let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, 100, 100).unwrap();
+
+    let warnings = {
+        let cr = cairo::Context::new(&surface).unwrap();
+
+        renderer
+            .render_document_checked_fonts(&cr, &rect(0.0, 0.0, 100.0, 100.0))
+            .unwrap()
+    };
+
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].family, "Ahem");
+
+    let surface = SharedImageSurface::wrap(surface, SurfaceType::SRgb).unwrap();
+    let drawn = (0..100)
+        .flat_map(|y| (0..100).map(move |x| (x, y)))
+        .any(|(x, y)| surface.get_pixel(x, y).a != 0);
+    assert!(drawn, "the text should be drawn with the fallback font");
+}
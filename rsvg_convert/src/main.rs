@@ -15,20 +15,29 @@ mod windows_imports {
 #[cfg(windows)]
 use self::windows_imports::*;
 
-use cssparser::{match_ignore_ascii_case, Color};
+use cssparser::{match_ignore_ascii_case, Color, RGBA};
 
 use librsvg_c::{handle::PathOrUrl, sizing::LegacySize};
 use rsvg::rsvg_convert_only::{
     set_source_color_on_cairo, AspectRatio, CssLength, Dpi, Horizontal, Length, Normalize,
     NormalizeParams, Parse, Rect, Signed, ULength, Unsigned, Validate, Vertical, ViewBox,
 };
-use rsvg::{AcceptLanguage, CairoRenderer, Language, LengthUnit, Loader, RenderingError};
+use rsvg::{
+    AcceptLanguage, CairoRenderer, Language, LengthUnit, Loader, RenderingError, SvgHandle,
+};
 
-use std::ffi::OsString;
+use std::collections::HashSet;
+use std::ffi::{OsStr, OsString};
 use std::io;
 use std::io::IsTerminal;
 use std::ops::Deref;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use self::pdf_output::IccProfile;
+
+mod pdf_output;
 
 #[derive(Debug)]
 pub struct Error(String);
@@ -66,6 +75,8 @@ impl_error_from!(RenderingError);
 impl_error_from!(cairo::IoError);
 impl_error_from!(cairo::StreamWithError);
 impl_error_from!(clap::Error);
+impl_error_from!(serde_json::Error);
+impl_error_from!(png::EncodingError);
 
 macro_rules! error {
     ($($arg:tt)*) => (Error(std::format!($($arg)*)));
@@ -216,9 +227,16 @@ impl ResizeStrategy {
 }
 
 enum Surface {
-    Png(cairo::ImageSurface, OutputStream),
+    /// The resolution is only present with `--png-dpi`, to be written to the PNG.
+    Png(cairo::ImageSurface, OutputStream, Option<Dpi>),
+    Ansi(cairo::ImageSurface, OutputStream),
+    Jpeg(cairo::ImageSurface, OutputStream, u8),
+    #[cfg(feature = "webp")]
+    Webp(cairo::ImageSurface, OutputStream, WebpOptions),
+    /// The output stream and ICC profile are only present with `--icc-profile`; then the
+    /// surface writes to a `Vec<u8>`, which gets the output intent added when finishing.
     #[cfg(system_deps_have_cairo_pdf)]
-    Pdf(cairo::PdfSurface, Size),
+    Pdf(cairo::PdfSurface, Size, Option<(OutputStream, IccProfile)>),
     #[cfg(system_deps_have_cairo_ps)]
     Ps(cairo::PsSurface, Size),
     #[cfg(system_deps_have_cairo_svg)]
@@ -230,9 +248,13 @@ impl Deref for Surface {
 
     fn deref(&self) -> &cairo::Surface {
         match self {
-            Self::Png(surface, _) => surface,
+            Self::Png(surface, _, _) => surface,
+            Self::Ansi(surface, _) => surface,
+            Self::Jpeg(surface, _, _) => surface,
+            #[cfg(feature = "webp")]
+            Self::Webp(surface, _, _) => surface,
             #[cfg(system_deps_have_cairo_pdf)]
-            Self::Pdf(surface, _) => surface,
+            Self::Pdf(surface, _, _) => surface,
             #[cfg(system_deps_have_cairo_ps)]
             Self::Ps(surface, _) => surface,
             #[cfg(system_deps_have_cairo_svg)]
@@ -253,26 +275,50 @@ impl Surface {
         size: Size,
         stream: OutputStream,
         unit: LengthUnit,
+        dpi: Dpi,
+        info: &DocumentInfo,
+        icc_profile: Option<&IccProfile>,
     ) -> Result<Self, Error> {
+        use cairo::PdfVersion;
+
+        let version = match format {
+            Format::Pdf1_7 => Some(PdfVersion::_1_7),
+            Format::Pdf1_6 => Some(PdfVersion::_1_6),
+            Format::Pdf1_5 => Some(PdfVersion::_1_5),
+            Format::Pdf1_4 => Some(PdfVersion::_1_4),
+            _ => None,
+        };
+
         match format {
-            Format::Png => Self::new_for_png(size, stream),
-            Format::Pdf => Self::new_for_pdf(size, stream, None),
-            Format::Pdf1_7 => Self::new_for_pdf(size, stream, Some(cairo::PdfVersion::_1_7)),
-            Format::Pdf1_6 => Self::new_for_pdf(size, stream, Some(cairo::PdfVersion::_1_6)),
-            Format::Pdf1_5 => Self::new_for_pdf(size, stream, Some(cairo::PdfVersion::_1_5)),
-            Format::Pdf1_4 => Self::new_for_pdf(size, stream, Some(cairo::PdfVersion::_1_4)),
+            Format::Png(options) => Ok(Self::Png(
+                new_image_surface(size)?,
+                stream,
+                options.dpi_metadata.then_some(dpi),
+            )),
+            Format::Pdf | Format::Pdf1_7 | Format::Pdf1_6 | Format::Pdf1_5 | Format::Pdf1_4 => {
+                Self::new_for_pdf(size, stream, version, info, icc_profile)
+            }
             Format::Ps => Self::new_for_ps(size, stream, false),
             Format::Eps => Self::new_for_ps(size, stream, true),
             Format::Svg => Self::new_for_svg(size, stream, unit),
+            Format::Ansi => Ok(Self::Ansi(new_image_surface(size)?, stream)),
+            Format::Jpeg(quality) => Ok(Self::Jpeg(new_image_surface(size)?, stream, quality)),
+            Format::Webp(options) => Self::new_for_webp(size, stream, options),
         }
     }
 
-    fn new_for_png(size: Size, stream: OutputStream) -> Result<Self, Error> {
-        // We use ceil() to avoid chopping off the last pixel if it is partially covered.
-        let w = checked_i32(size.w.ceil())?;
-        let h = checked_i32(size.h.ceil())?;
-        let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, w, h)?;
-        Ok(Self::Png(surface, stream))
+    #[cfg(feature = "webp")]
+    fn new_for_webp(size: Size, stream: OutputStream, options: WebpOptions) -> Result<Self, Error> {
+        Ok(Self::Webp(new_image_surface(size)?, stream, options))
+    }
+
+    #[cfg(not(feature = "webp"))]
+    fn new_for_webp(
+        _size: Size,
+        _stream: OutputStream,
+        _options: WebpOptions,
+    ) -> Result<Self, Error> {
+        Err(Error("unsupported format".to_string()))
     }
 
     #[cfg(system_deps_have_cairo_pdf)]
@@ -280,19 +326,50 @@ impl Surface {
         size: Size,
         stream: OutputStream,
         version: Option<cairo::PdfVersion>,
+        info: &DocumentInfo,
+        icc_profile: Option<&IccProfile>,
     ) -> Result<Self, Error> {
-        let surface = cairo::PdfSurface::for_stream(size.w, size.h, stream.into_write())?;
+        let (surface, output_intent) = match icc_profile {
+            None => {
+                let surface = cairo::PdfSurface::for_stream(size.w, size.h, stream.into_write())?;
+                (surface, None)
+            }
+
+            // The output intent gets added to the finished file; see pdf_output.
+            Some(profile) => {
+                let surface = cairo::PdfSurface::for_stream(size.w, size.h, Vec::<u8>::new())?;
+                (surface, Some((stream, profile.clone())))
+            }
+        };
         if let Some(ver) = version {
             surface.restrict(ver)?;
         }
         if let Some(date) = metadata::creation_date()? {
+            let date = date.to_rfc3339();
             surface.set_metadata(cairo::PdfMetadata::CreateDate, &date)?;
+            surface.set_metadata(cairo::PdfMetadata::ModDate, &date)?;
         }
-        Ok(Self::Pdf(surface, size))
+        for (key, value) in [
+            (cairo::PdfMetadata::Title, &info.title),
+            (cairo::PdfMetadata::Author, &info.author),
+            (cairo::PdfMetadata::Subject, &info.subject),
+            (cairo::PdfMetadata::Keywords, &info.keywords),
+        ] {
+            if let Some(value) = value {
+                surface.set_metadata(key, value)?;
+            }
+        }
+        Ok(Self::Pdf(surface, size, output_intent))
     }
 
     #[cfg(not(system_deps_have_cairo_pdf))]
-    fn new_for_pdf(_size: Size, _stream: OutputStream) -> Result<Self, Error> {
+    fn new_for_pdf(
+        _size: Size,
+        _stream: OutputStream,
+        _version: Option<cairo::PdfVersion>,
+        _info: &DocumentInfo,
+        _icc_profile: Option<&IccProfile>,
+    ) -> Result<Self, Error> {
         Err(Error("unsupported format".to_string()))
     }
 
@@ -300,6 +377,13 @@ impl Surface {
     fn new_for_ps(size: Size, stream: OutputStream, eps: bool) -> Result<Self, Error> {
         let surface = cairo::PsSurface::for_stream(size.w, size.h, stream.into_write())?;
         surface.set_eps(eps);
+        if let Some(date) = metadata::creation_date()? {
+            // Header comments must be added before anything is drawn on the surface.
+            surface.dsc_comment(&format!(
+                "%%CreationDate: {}",
+                date.format("%a %b %e %H:%M:%S %Y")
+            ));
+        }
         Ok(Self::Ps(surface, size))
     }
 
@@ -338,6 +422,7 @@ impl Surface {
         top: f64,
         final_size: Size,
         geometry: cairo::Rectangle,
+        viewport: cairo::Rectangle,
         background_color: Option<Color>,
         id: Option<&str>,
     ) -> Result<(), Error> {
@@ -362,23 +447,90 @@ impl Surface {
 
         cr.scale(scale.x, scale.y);
 
-        let viewport = cairo::Rectangle::new(0.0, 0.0, geometry.width(), geometry.height());
-
         match id {
             None => renderer.render_document(&cr, &viewport)?,
             Some(_) => renderer.render_element(&cr, id, &viewport)?,
         }
 
-        if !matches!(self, Self::Png(_, _)) {
+        let is_image = match self {
+            Self::Png(_, _, _) | Self::Ansi(_, _) | Self::Jpeg(_, _, _) => true,
+            #[cfg(feature = "webp")]
+            Self::Webp(_, _, _) => true,
+            _ => false,
+        };
+
+        if !is_image {
             cr.show_page()?;
         }
 
         Ok(())
     }
 
+    /// Emits an empty page with the size of the previous one, for multi-page surfaces.
+    pub fn show_blank_page(&self) -> Result<(), Error> {
+        let cr = cairo::Context::new(self)?;
+        cr.show_page()?;
+        Ok(())
+    }
+
     pub fn finish(self) -> Result<(), Error> {
         match self {
-            Self::Png(surface, stream) => surface.write_to_png(&mut stream.into_write())?,
+            Self::Png(surface, stream, None) => {
+                surface.write_to_png(&mut stream.into_write())?;
+            }
+            Self::Png(surface, stream, Some(dpi)) => {
+                png_output::encode_with_dpi(&surface, dpi, stream.into_write())?;
+            }
+            Self::Ansi(surface, stream) => {
+                use std::io::Write;
+
+                let text = ansi::to_ansi_text(&surface)?;
+
+                stream
+                    .into_write()
+                    .write_all(text.as_bytes())
+                    .map_err(|e| error!("{}", e))?;
+            }
+            Self::Jpeg(surface, stream, quality) => {
+                let rgb = jpeg::to_rgb(&surface)?;
+
+                image::codecs::jpeg::JpegEncoder::new_with_quality(stream.into_write(), quality)
+                    .encode(
+                        &rgb,
+                        surface.width() as u32,
+                        surface.height() as u32,
+                        image::ExtendedColorType::Rgb8,
+                    )
+                    .map_err(|e| error!("{}", e))?;
+            }
+            #[cfg(feature = "webp")]
+            Self::Webp(surface, stream, options) => {
+                use std::io::Write;
+
+                let bytes = webp_output::encode(&surface, options)?;
+
+                stream
+                    .into_write()
+                    .write_all(&bytes)
+                    .map_err(|e| error!("{}", e))?;
+            }
+            #[cfg(system_deps_have_cairo_pdf)]
+            Self::Pdf(surface, _, Some((stream, profile))) => {
+                use std::io::Write;
+
+                let pdf = surface
+                    .finish_output_stream()?
+                    .downcast::<Vec<u8>>()
+                    .expect("PDF surface with an output intent writes to a Vec<u8>");
+
+                let pdf = pdf_output::with_output_intent(*pdf, &profile)
+                    .map_err(|e| error!("Error adding the ICC profile to the PDF: {}", e))?;
+
+                stream
+                    .into_write()
+                    .write_all(&pdf)
+                    .map_err(|e| error!("{}", e))?;
+            }
             _ => self.finish_output_stream().map(|_| ())?,
         }
 
@@ -390,19 +542,291 @@ fn checked_i32(x: f64) -> Result<i32, cairo::Error> {
     cast::i32(x).map_err(|_| cairo::Error::InvalidSize)
 }
 
+/// Creates the ARGB32 surface on which the raster formats get rendered.
+fn new_image_surface(size: Size) -> Result<cairo::ImageSurface, Error> {
+    // We use ceil() to avoid chopping off the last pixel if it is partially covered.
+    let w = checked_i32(size.w.ceil())?;
+    let h = checked_i32(size.h.ceil())?;
+    Ok(cairo::ImageSurface::create(cairo::Format::ARgb32, w, h)?)
+}
+
+mod pixels {
+    use super::Error;
+    use rsvg::rsvg_convert_only::{Pixel, PixelOps};
+
+    /// Returns the pixels of an ARGB32 image row by row, premultiplied as Cairo stores them.
+    pub fn premultiplied(surface: &cairo::ImageSurface) -> Result<Vec<Pixel>, Error> {
+        let width = surface.width() as usize;
+        let height = surface.height() as usize;
+        let stride = surface.stride() as usize;
+
+        let mut pixels = Vec::with_capacity(width * height);
+
+        surface
+            .with_data(|data| {
+                pixels.extend(
+                    data.chunks(stride)
+                        .take(height)
+                        .flat_map(|row| row[..width * 4].chunks_exact(4))
+                        .map(|argb| Pixel::from_u32(u32::from_ne_bytes(argb.try_into().unwrap()))),
+                )
+            })
+            .map_err(|e| error!("{}", e))?;
+
+        Ok(pixels)
+    }
+
+    /// Returns the pixels of an ARGB32 image row by row, unpremultiplied as the encoders
+    /// want them.
+    pub fn unpremultiplied(surface: &cairo::ImageSurface) -> Result<Vec<Pixel>, Error> {
+        Ok(premultiplied(surface)?
+            .into_iter()
+            .map(PixelOps::unpremultiply)
+            .collect())
+    }
+
+    /// Packs pixels as RGBA bytes.
+    pub fn to_rgba(pixels: &[Pixel]) -> Vec<u8> {
+        pixels.iter().flat_map(|p| [p.r, p.g, p.b, p.a]).collect()
+    }
+}
+
+mod ansi {
+    use super::{pixels, Error};
+    use rsvg::rsvg_convert_only::Pixel;
+
+    /// Pixels with less opacity than this are shown with the terminal's default background.
+    const ALPHA_THRESHOLD: u8 = 128;
+
+    /// Width in terminal columns used when no size is requested.
+    pub const DEFAULT_COLUMNS: f64 = 80.0;
+
+    #[derive(Clone, Copy, PartialEq)]
+    struct Rgb(u8, u8, u8);
+
+    /// Returns the color of an unpremultiplied pixel, or `None` if it is mostly transparent.
+    fn rgb(pixel: Pixel) -> Option<Rgb> {
+        (pixel.a >= ALPHA_THRESHOLD).then_some(Rgb(pixel.r, pixel.g, pixel.b))
+    }
+
+    /// Converts an ARGB32 image into lines of 24-bit color ANSI escape sequences.
+    ///
+    /// Each character cell uses the "upper half block" glyph to show two vertically
+    /// stacked pixels: the top one as the foreground color, the bottom one as the
+    /// background color.
+    pub fn to_ansi_text(surface: &cairo::ImageSurface) -> Result<String, Error> {
+        let width = surface.width() as usize;
+        let height = surface.height() as usize;
+
+        let pixels = pixels::unpremultiplied(surface)?;
+        let pixel_at = |x: usize, y: usize| rgb(pixels[y * width + x]);
+
+        let mut text = String::new();
+
+        for y in (0..height).step_by(2) {
+            for x in 0..width {
+                let top = pixel_at(x, y);
+                let bottom = if y + 1 < height {
+                    pixel_at(x, y + 1)
+                } else {
+                    None
+                };
+
+                match (top, bottom) {
+                    (None, None) => text.push_str("\x1b[0m "),
+
+                    (Some(Rgb(r, g, b)), None) => {
+                        text.push_str(&format!("\x1b[0;38;2;{r};{g};{b}m\u{2580}"))
+                    }
+
+                    (None, Some(Rgb(r, g, b))) => {
+                        text.push_str(&format!("\x1b[0;38;2;{r};{g};{b}m\u{2584}"))
+                    }
+
+                    (Some(Rgb(r1, g1, b1)), Some(Rgb(r2, g2, b2))) => text.push_str(&format!(
+                        "\x1b[0;38;2;{r1};{g1};{b1};48;2;{r2};{g2};{b2}m\u{2580}"
+                    )),
+                }
+            }
+
+            text.push_str("\x1b[0m\n");
+        }
+
+        Ok(text)
+    }
+}
+
+mod png_output {
+    use super::{pixels, Dpi, Error};
+
+    /// Encodes an ARGB32 image as a PNG with a pHYs chunk for the given resolution.
+    ///
+    /// Cairo's PNG writer cannot write a pHYs chunk, so viewers would otherwise assume a
+    /// default resolution instead of the one the image was rendered at.
+    pub fn encode_with_dpi<W: std::io::Write>(
+        surface: &cairo::ImageSurface,
+        dpi: Dpi,
+        w: W,
+    ) -> Result<(), Error> {
+        let mut encoder = png::Encoder::new(w, surface.width() as u32, surface.height() as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_pixel_dims(Some(pixel_dims(dpi)));
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&pixels::to_rgba(&pixels::unpremultiplied(surface)?))?;
+
+        Ok(writer.finish()?)
+    }
+
+    /// The pHYs chunk for a resolution; PNG stores it in pixels per meter.
+    pub fn pixel_dims(dpi: Dpi) -> png::PixelDimensions {
+        let ppm = |dpi: f64| (dpi / 0.0254).round() as u32;
+
+        png::PixelDimensions {
+            xppu: ppm(dpi.x),
+            yppu: ppm(dpi.y),
+            unit: png::Unit::Meter,
+        }
+    }
+}
+
+mod jpeg {
+    use super::{pixels, Error};
+
+    /// Lowest quality that the JPEG encoder accepts; `--quality` starts here.
+    pub const MIN_QUALITY: u8 = 1;
+
+    /// Converts an ARGB32 image to packed RGB bytes.
+    ///
+    /// JPEG has no alpha channel, so the image is expected to be opaque by having been
+    /// rendered on top of a background color.  The premultiplied color values are used
+    /// directly, which is like compositing any leftover transparency over black.
+    pub fn to_rgb(surface: &cairo::ImageSurface) -> Result<Vec<u8>, Error> {
+        Ok(pixels::premultiplied(surface)?
+            .iter()
+            .flat_map(|p| [p.r, p.g, p.b])
+            .collect())
+    }
+}
+
+#[cfg(feature = "webp")]
+mod webp_output {
+    use super::{pixels, Error, WebpOptions};
+
+    /// Encodes an ARGB32 image as WebP.
+    pub fn encode(surface: &cairo::ImageSurface, options: WebpOptions) -> Result<Vec<u8>, Error> {
+        let rgba = pixels::to_rgba(&pixels::unpremultiplied(surface)?);
+        let encoder =
+            webp::Encoder::from_rgba(&rgba, surface.width() as u32, surface.height() as u32);
+
+        let encoded = if options.lossless {
+            encoder.encode_lossless()
+        } else {
+            encoder.encode(options.quality)
+        };
+
+        Ok(encoded.to_vec())
+    }
+}
+
+#[cfg(feature = "apng")]
+mod apng_output {
+    use super::{pixels, png_output, Dpi, Error};
+
+    /// Encodes ARGB32 images, which must all have the same size, as the frames of an
+    /// animated PNG that loops forever.  The resolution gets written if there is one.
+    pub fn encode<W: std::io::Write>(
+        frames: &[cairo::ImageSurface],
+        fps: u16,
+        dpi: Option<Dpi>,
+        w: W,
+    ) -> Result<(), Error> {
+        let (width, height) = (frames[0].width() as u32, frames[0].height() as u32);
+
+        let mut encoder = png::Encoder::new(w, width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_pixel_dims(dpi.map(png_output::pixel_dims));
+        encoder.set_animated(frames.len() as u32, 0)?;
+        encoder.set_frame_delay(1, fps)?;
+
+        let mut writer = encoder.write_header()?;
+
+        for frame in frames {
+            writer.write_image_data(&pixels::to_rgba(&pixels::unpremultiplied(frame)?))?;
+        }
+
+        Ok(writer.finish()?)
+    }
+}
+
+#[cfg(not(feature = "apng"))]
+mod apng_output {
+    use super::{Dpi, Error};
+
+    pub fn encode<W: std::io::Write>(
+        _frames: &[cairo::ImageSurface],
+        _fps: u16,
+        _dpi: Option<Dpi>,
+        _w: W,
+    ) -> Result<(), Error> {
+        Err(Error(
+            "rsvg-convert was built without support for animated PNG".to_string(),
+        ))
+    }
+}
+
+mod geometry_output {
+    use serde::Serialize;
+
+    /// What `--geometry` prints for a document.
+    #[derive(Serialize)]
+    pub struct Geometry {
+        pub intrinsic_dimensions: rsvg::IntrinsicDimensions,
+        pub elements: Vec<ElementGeometry>,
+    }
+
+    /// Rectangles from `CairoRenderer::geometry_for_element`.
+    #[derive(Serialize)]
+    pub struct ElementGeometry {
+        pub id: String,
+        pub ink_rect: Rectangle,
+        pub logical_rect: Rectangle,
+    }
+
+    #[derive(Serialize)]
+    pub struct Rectangle {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    }
+
+    impl From<cairo::Rectangle> for Rectangle {
+        fn from(r: cairo::Rectangle) -> Rectangle {
+            Rectangle {
+                x: r.x(),
+                y: r.y(),
+                width: r.width(),
+                height: r.height(),
+            }
+        }
+    }
+}
+
 mod metadata {
     use super::Error;
     use chrono::prelude::*;
     use std::env;
     use std::str::FromStr;
 
-    pub fn creation_date() -> Result<Option<String>, Error> {
+    /// Gets the date to put in the output's metadata from `$SOURCE_DATE_EPOCH`, for
+    /// reproducible builds.
+    pub fn creation_date() -> Result<Option<DateTime<Utc>>, Error> {
         match env::var("SOURCE_DATE_EPOCH") {
             Ok(epoch) => match i64::from_str(&epoch) {
-                Ok(seconds) => {
-                    let datetime = Utc.timestamp_opt(seconds, 0).unwrap();
-                    Ok(Some(datetime.to_rfc3339()))
-                }
+                Ok(seconds) => Ok(Some(Utc.timestamp_opt(seconds, 0).unwrap())),
                 Err(e) => Err(error!("Environment variable $SOURCE_DATE_EPOCH: {}", e)),
             },
             Err(env::VarError::NotPresent) => Ok(None),
@@ -461,6 +885,24 @@ enum Input {
     Named(PathOrUrl),
 }
 
+impl Input {
+    /// Name of the input file without its directory or extension, for `{name}` in the
+    /// `--output-template`.
+    fn stem(&self) -> String {
+        let stem = match self {
+            Input::Stdin => None,
+            Input::Named(PathOrUrl::Path(p)) => p.file_stem(),
+            Input::Named(PathOrUrl::Url(u)) => u
+                .path_segments()
+                .and_then(|mut segments| segments.next_back())
+                .and_then(|segment| Path::new(segment).file_stem()),
+        };
+
+        stem.map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| String::from("stdin"))
+    }
+}
+
 impl std::fmt::Display for Input {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -488,7 +930,7 @@ impl std::fmt::Display for Output {
 // Keep this enum in sync with supported_formats in parse_args()
 #[derive(Clone, Copy, Debug)]
 enum Format {
-    Png,
+    Png(PngOptions),
     Pdf,
     Pdf1_7,
     Pdf1_6,
@@ -497,6 +939,61 @@ enum Format {
     Ps,
     Eps,
     Svg,
+    Ansi,
+    Jpeg(u8),
+    Webp(WebpOptions),
+}
+
+/// Encoding options for PNG output.
+#[derive(Clone, Copy, Debug)]
+struct PngOptions {
+    /// Write the resolution from `--dpi-x` and `--dpi-y` in a pHYs chunk.
+    dpi_metadata: bool,
+}
+
+/// Encoding options for WebP output.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(not(feature = "webp"), allow(dead_code))]
+struct WebpOptions {
+    /// Quality of lossy compression, from 0 to 100.
+    quality: f32,
+
+    /// Use lossless compression instead; `quality` is ignored.
+    lossless: bool,
+}
+
+/// Document properties for PDF output.
+#[derive(Clone, Debug, Default)]
+struct DocumentInfo {
+    title: Option<String>,
+    author: Option<String>,
+    subject: Option<String>,
+    keywords: Option<String>,
+}
+
+impl DocumentInfo {
+    fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.author.is_none()
+            && self.subject.is_none()
+            && self.keywords.is_none()
+    }
+}
+
+/// An object to render, and where to write it.
+struct Export {
+    /// SVG id of the object, or `None` to render the whole document.
+    id: Option<String>,
+    output: Output,
+}
+
+/// Input files that get rendered together into the same outputs.
+///
+/// For PDF and PostScript, each input is a page in the outputs.  Image formats only
+/// support a single input per task.
+struct Task {
+    inputs: Vec<Input>,
+    exports: Vec<Export>,
 }
 
 struct Converter {
@@ -509,15 +1006,22 @@ struct Converter {
     pub top: Option<Length<Vertical>>,
     pub page_size: Option<(ULength<Horizontal>, ULength<Vertical>)>,
     pub format: Format,
-    pub export_id: Option<String>,
+    pub tasks: Vec<Task>,
+    pub jobs: usize,
     pub keep_aspect_ratio: bool,
+    pub crop: bool,
     pub background_color: Option<Color>,
     pub stylesheet: Option<PathBuf>,
     pub language: Language,
     pub unlimited: bool,
     pub keep_image_data: bool,
-    pub input: Vec<Input>,
-    pub output: Output,
+    pub max_pages: Option<usize>,
+    pub even_pages: bool,
+    pub document_info: DocumentInfo,
+    pub icc_profile: Option<IccProfile>,
+    pub frames_from_ids: Option<String>,
+    pub fps: u16,
+    pub geometry: bool,
     pub testing: bool,
 }
 
@@ -530,29 +1034,59 @@ impl Converter {
             None => None,
         };
 
-        let mut surface: Option<Surface> = None;
+        match self.tasks.as_slice() {
+            [task] => self.run_task(task, stylesheet.as_deref()),
+            tasks => self.run_tasks_in_parallel(tasks, stylesheet.as_deref()),
+        }
+    }
 
-        // Use user units per default
-        let mut unit = LengthUnit::Px;
+    /// Runs independent tasks on up to `self.jobs` threads.
+    ///
+    /// Errors are reported in the order of the tasks, not in the order in which they happen.
+    fn run_tasks_in_parallel(&self, tasks: &[Task], stylesheet: Option<&str>) -> Result<(), Error> {
+        let next_task = AtomicUsize::new(0);
+        let results: Vec<Mutex<Result<(), Error>>> =
+            tasks.iter().map(|_| Mutex::new(Ok(()))).collect();
+
+        std::thread::scope(|scope| {
+            for _ in 0..self.jobs.clamp(1, tasks.len()) {
+                scope.spawn(|| loop {
+                    let idx = next_task.fetch_add(1, Ordering::Relaxed);
+                    let Some(task) = tasks.get(idx) else {
+                        break;
+                    };
 
-        fn set_unit<N: Normalize, V: Validate>(
-            l: CssLength<N, V>,
-            p: &NormalizeParams,
-            u: LengthUnit,
-        ) -> f64 {
-            match u {
-                LengthUnit::Pt => l.to_points(p),
-                LengthUnit::In => l.to_inches(p),
-                LengthUnit::Cm => l.to_cm(p),
-                LengthUnit::Mm => l.to_mm(p),
-                LengthUnit::Pc => l.to_picas(p),
-                _ => l.to_user(p),
+                    *results[idx].lock().unwrap() = self.run_task(task, stylesheet);
+                });
             }
+        });
+
+        let errors: Vec<String> = results
+            .into_iter()
+            .filter_map(|r| r.into_inner().unwrap().err())
+            .map(|e| e.0)
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Error(errors.join("\n")))
         }
+    }
+
+    fn run_task(&self, task: &Task, stylesheet: Option<&str>) -> Result<(), Error> {
+        // One surface per export; each input document is parsed only once and then
+        // rendered into all of them.
+        let mut surfaces: Vec<Option<Surface>> = task.exports.iter().map(|_| None).collect();
 
         let stdin = Stdin;
 
-        for (page_idx, input) in self.input.iter().enumerate() {
+        let inputs = match self.max_pages {
+            Some(max) => &task.inputs[..max.min(task.inputs.len())],
+            None => &task.inputs[..],
+        };
+
+        for (page_idx, input) in inputs.iter().enumerate() {
             let (stream, basefile) = match input {
                 Input::Stdin => {
                     if stdin.is_terminal() {
@@ -578,7 +1112,7 @@ impl Converter {
                 .read_stream(&stream, basefile.as_ref(), None::<&Cancellable>)
                 .map_err(|e| error!("Error reading SVG {}: {}", input, e))?;
 
-            if let Some(ref css) = stylesheet {
+            if let Some(css) = stylesheet {
                 handle
                     .set_stylesheet(css)
                     .map_err(|e| error!("Error applying stylesheet: {}", e))?;
@@ -589,170 +1123,375 @@ impl Converter {
                 .with_language(&self.language)
                 .test_mode(self.testing);
 
-            let geometry = natural_geometry(&renderer, input, self.export_id.as_deref())?;
-
-            let natural_size = Size::new(geometry.width(), geometry.height());
-
-            let params = NormalizeParams::from_dpi(Dpi::new(self.dpi_x.0, self.dpi_y.0));
+            for (export, surface) in task.exports.iter().zip(surfaces.iter_mut()) {
+                if self.geometry {
+                    self.write_geometry(&handle, &renderer, input, export)?;
+                    continue;
+                }
 
-            // Convert natural size and requested size to pixels or points, depending on the target format,
-            let (natural_size, requested_width, requested_height, page_size) = match self.format {
-                Format::Png => {
-                    // PNG surface requires units in pixels
-                    (
-                        natural_size,
-                        self.width.map(|l| l.to_user(&params)),
-                        self.height.map(|l| l.to_user(&params)),
-                        self.page_size.map(|(w, h)| Size {
-                            w: w.to_user(&params),
-                            h: h.to_user(&params),
-                        }),
-                    )
+                match self.frames_from_ids {
+                    Some(ref prefix) => {
+                        self.render_frames(&handle, &renderer, input, prefix, export)?
+                    }
+                    None => self.render_page(&renderer, input, page_idx, export, surface)?,
                 }
+            }
+        }
 
-                Format::Pdf
-                | Format::Pdf1_7
-                | Format::Pdf1_6
-                | Format::Pdf1_5
-                | Format::Pdf1_4
-                | Format::Ps
-                | Format::Eps => {
-                    // These surfaces require units in points
-                    unit = LengthUnit::Pt;
-
-                    (
-                        Size {
-                            w: ULength::<Horizontal>::new(natural_size.w, LengthUnit::Px)
-                                .to_points(&params),
-                            h: ULength::<Vertical>::new(natural_size.h, LengthUnit::Px)
-                                .to_points(&params),
-                        },
-                        self.width.map(|l| l.to_points(&params)),
-                        self.height.map(|l| l.to_points(&params)),
-                        self.page_size.map(|(w, h)| Size {
-                            w: w.to_points(&params),
-                            h: h.to_points(&params),
-                        }),
-                    )
+        if self.even_pages && inputs.len() % 2 == 1 {
+            for (export, surface) in task.exports.iter().zip(surfaces.iter()) {
+                if let Some(s) = surface {
+                    s.show_blank_page()
+                        .map_err(|e| error!("Error rendering to {}: {}", export.output, e))?;
                 }
+            }
+        }
 
-                Format::Svg => {
-                    let (w_unit, h_unit) =
-                        (self.width.map(|l| l.unit), self.height.map(|l| l.unit));
-
-                    unit = match (w_unit, h_unit) {
-                        (None, None) => LengthUnit::Px,
-                        (None, u) | (u, None) => u.unwrap(),
-                        (u1, u2) => {
-                            if u1 == u2 {
-                                u1.unwrap()
-                            } else {
-                                LengthUnit::Px
-                            }
-                        }
-                    };
+        for (export, surface) in task.exports.iter().zip(surfaces.iter_mut()) {
+            if let Some(s) = surface.take() {
+                s.finish()
+                    .map_err(|e| error!("Error saving output {}: {}", export.output, e))?
+            };
+        }
 
-                    // Supported SVG units are px, in, cm, mm, pt, pc, ch
-                    (
-                        Size {
-                            w: set_unit(
-                                ULength::<Horizontal>::new(natural_size.w, LengthUnit::Px),
-                                &params,
-                                unit,
-                            ),
-                            h: set_unit(
-                                ULength::<Vertical>::new(natural_size.h, LengthUnit::Px),
-                                &params,
-                                unit,
-                            ),
-                        },
-                        self.width.map(|l| set_unit(l, &params, unit)),
-                        self.height.map(|l| set_unit(l, &params, unit)),
-                        self.page_size.map(|(w, h)| Size {
-                            w: set_unit(w, &params, unit),
-                            h: set_unit(h, &params, unit),
-                        }),
-                    )
+        Ok(())
+    }
+
+    /// Writes the document's intrinsic dimensions and the geometry of its elements as JSON,
+    /// instead of rendering it.
+    ///
+    /// Only the exported element is measured if there is one; otherwise, all the elements
+    /// with an id are.
+    fn write_geometry(
+        &self,
+        handle: &SvgHandle,
+        renderer: &CairoRenderer,
+        input: &Input,
+        export: &Export,
+    ) -> Result<(), Error> {
+        use std::io::Write;
+
+        let ids = match export.id {
+            Some(ref id) => vec![id.clone()],
+            None => handle.element_ids(),
+        };
+
+        let elements = ids
+            .iter()
+            .map(|id| {
+                let (ink_r, logical_r) = renderer
+                    .geometry_for_element(Some(id))
+                    .map_err(|e| error!("Error measuring {} in SVG {}: {}", id, input, e))?;
+
+                Ok(geometry_output::ElementGeometry {
+                    id: id.trim_start_matches('#').to_string(),
+                    ink_rect: ink_r.into(),
+                    logical_rect: logical_r.into(),
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let geometry = geometry_output::Geometry {
+            intrinsic_dimensions: renderer.intrinsic_dimensions(),
+            elements,
+        };
+
+        let mut w = self.open_output(&export.output)?.into_write();
+        serde_json::to_writer_pretty(&mut w, &geometry)?;
+        writeln!(w).map_err(|e| error!("Error saving output {}: {}", export.output, e))
+    }
+
+    /// Renders each element whose id starts with `prefix` as a frame of an animated PNG.
+    ///
+    /// Every element with a matching id becomes a frame, including nested ones.  All the
+    /// frames get the size of the largest element, scaled like for a single page with
+    /// the `-w`, `-h`, `-z`, and `--keep-aspect-ratio` options.
+    fn render_frames(
+        &self,
+        handle: &SvgHandle,
+        renderer: &CairoRenderer,
+        input: &Input,
+        prefix: &str,
+        export: &Export,
+    ) -> Result<(), Error> {
+        let ids: Vec<String> = handle
+            .element_ids()
+            .into_iter()
+            .filter(|id| id.trim_start_matches('#').starts_with(prefix))
+            .collect();
+
+        if ids.is_empty() {
+            return Err(error!(
+                "File {} does not have any objects with an id that starts with \"{}\"",
+                input, prefix
+            ));
+        }
+
+        let geometries = ids
+            .iter()
+            .map(|id| natural_geometry(renderer, input, Some(id)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let natural_size = Size::new(
+            geometries.iter().map(|g| g.width()).fold(0.0, f64::max),
+            geometries.iter().map(|g| g.height()).fold(0.0, f64::max),
+        );
+
+        let params = NormalizeParams::from_dpi(Dpi::new(self.dpi_x.0, self.dpi_y.0));
+        let strategy = self.resize_strategy(
+            self.width.map(|l| l.to_user(&params)),
+            self.height.map(|l| l.to_user(&params)),
+        );
+        let final_size = self.final_size(&strategy, &natural_size, input)?;
+
+        let w = checked_i32(final_size.w.ceil())?;
+        let h = checked_i32(final_size.h.ceil())?;
+
+        let frames = ids
+            .iter()
+            .zip(geometries.iter())
+            .map(|(id, geometry)| {
+                let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, w, h)?;
+                let cr = cairo::Context::new(&surface)?;
+
+                if let Some(ref color) = self.background_color {
+                    set_source_color_on_cairo(&cr, color);
+                    cr.paint()?;
                 }
-            };
 
-            let strategy = match (requested_width, requested_height) {
-                // when w and h are not specified, scale to the requested zoom (if any)
-                (None, None) => ResizeStrategy::Scale(self.zoom),
-
-                // when w and h are specified, but zoom is not, scale to the requested size
-                (Some(width), Some(height)) if self.zoom.is_identity() => ResizeStrategy::Fit {
-                    size: Size::new(width, height),
-                    keep_aspect_ratio: self.keep_aspect_ratio,
-                },
-
-                // if only one between w and h is specified and there is no zoom, scale to the
-                // requested w or h and use the same scaling factor for the other
-                (Some(w), None) if self.zoom.is_identity() => ResizeStrategy::FitWidth(w),
-                (None, Some(h)) if self.zoom.is_identity() => ResizeStrategy::FitHeight(h),
-
-                // otherwise scale the image, but cap the zoom to match the requested size
-                _ => ResizeStrategy::ScaleWithMaxSize {
-                    scale: self.zoom,
-                    max_width: requested_width,
-                    max_height: requested_height,
-                    keep_aspect_ratio: self.keep_aspect_ratio,
-                },
-            };
+                cr.scale(final_size.w / natural_size.w, final_size.h / natural_size.h);
 
-            let final_size = self.final_size(&strategy, &natural_size, input)?;
-
-            // Create the surface once on the first input,
-            // except for PDF, PS, and EPS, which allow differently-sized pages.
-            let page_size = page_size.unwrap_or(final_size);
-            let s = match &mut surface {
-                Some(s) => {
-                    match s {
-                        #[cfg(system_deps_have_cairo_pdf)]
-                        Surface::Pdf(pdf, size) => {
-                            pdf.set_size(page_size.w, page_size.h).map_err(|e| {
-                                error!(
-                                    "Error setting PDF page #{} size {}: {}",
-                                    page_idx + 1,
-                                    input,
-                                    e
-                                )
-                            })?;
-                            *size = page_size;
-                        }
-                        #[cfg(system_deps_have_cairo_ps)]
-                        Surface::Ps(ps, size) => {
-                            ps.set_size(page_size.w, page_size.h);
-                            *size = page_size;
+                let viewport = cairo::Rectangle::new(0.0, 0.0, geometry.width(), geometry.height());
+                renderer.render_element(&cr, Some(id), &viewport)?;
+                drop(cr);
+
+                Ok(surface)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let stream = self.open_output(&export.output)?;
+        let dpi = match self.format {
+            Format::Png(options) if options.dpi_metadata => {
+                Some(Dpi::new(self.dpi_x.0, self.dpi_y.0))
+            }
+            _ => None,
+        };
+
+        apng_output::encode(&frames, self.fps, dpi, stream.into_write())
+            .map_err(|e| error!("Error saving output {}: {}", export.output, e))
+    }
+
+    fn render_page(
+        &self,
+        renderer: &CairoRenderer,
+        input: &Input,
+        page_idx: usize,
+        export: &Export,
+        surface: &mut Option<Surface>,
+    ) -> Result<(), Error> {
+        // Use user units per default
+        let mut unit = LengthUnit::Px;
+
+        fn set_unit<N: Normalize, V: Validate>(
+            l: CssLength<N, V>,
+            p: &NormalizeParams,
+            u: LengthUnit,
+        ) -> f64 {
+            match u {
+                LengthUnit::Pt => l.to_points(p),
+                LengthUnit::In => l.to_inches(p),
+                LengthUnit::Cm => l.to_cm(p),
+                LengthUnit::Mm => l.to_mm(p),
+                LengthUnit::Pc => l.to_picas(p),
+                _ => l.to_user(p),
+            }
+        }
+
+        let mut geometry = natural_geometry(renderer, input, export.id.as_deref())?;
+
+        // Where to place the document or element, relative to the output's origin.
+        let mut viewport = cairo::Rectangle::new(0.0, 0.0, geometry.width(), geometry.height());
+
+        // An exported element is already rendered tightly around its ink rectangle; for the
+        // whole document we shift it so that its drawn contents start at the origin.
+        if self.crop && export.id.is_none() {
+            let ink_r = ink_geometry(renderer, input, &viewport)?;
+            viewport =
+                cairo::Rectangle::new(-ink_r.x(), -ink_r.y(), viewport.width(), viewport.height());
+            geometry = ink_r;
+        }
+
+        let natural_size = Size::new(geometry.width(), geometry.height());
+
+        let params = NormalizeParams::from_dpi(Dpi::new(self.dpi_x.0, self.dpi_y.0));
+
+        // Convert natural size and requested size to pixels or points, depending on the target format,
+        let (natural_size, requested_width, requested_height, page_size) = match self.format {
+            Format::Png(_) | Format::Jpeg(_) | Format::Webp(_) | Format::Ansi => {
+                // PNG surface requires units in pixels
+                (
+                    natural_size,
+                    self.width.map(|l| l.to_user(&params)),
+                    self.height.map(|l| l.to_user(&params)),
+                    self.page_size.map(|(w, h)| Size {
+                        w: w.to_user(&params),
+                        h: h.to_user(&params),
+                    }),
+                )
+            }
+
+            Format::Pdf
+            | Format::Pdf1_7
+            | Format::Pdf1_6
+            | Format::Pdf1_5
+            | Format::Pdf1_4
+            | Format::Ps
+            | Format::Eps => {
+                // These surfaces require units in points
+                unit = LengthUnit::Pt;
+
+                (
+                    Size {
+                        w: ULength::<Horizontal>::new(natural_size.w, LengthUnit::Px)
+                            .to_points(&params),
+                        h: ULength::<Vertical>::new(natural_size.h, LengthUnit::Px)
+                            .to_points(&params),
+                    },
+                    self.width.map(|l| l.to_points(&params)),
+                    self.height.map(|l| l.to_points(&params)),
+                    self.page_size.map(|(w, h)| Size {
+                        w: w.to_points(&params),
+                        h: h.to_points(&params),
+                    }),
+                )
+            }
+
+            Format::Svg => {
+                let (w_unit, h_unit) = (self.width.map(|l| l.unit), self.height.map(|l| l.unit));
+
+                unit = match (w_unit, h_unit) {
+                    (None, None) => LengthUnit::Px,
+                    (None, u) | (u, None) => u.unwrap(),
+                    (u1, u2) => {
+                        if u1 == u2 {
+                            u1.unwrap()
+                        } else {
+                            LengthUnit::Px
                         }
-                        _ => {}
                     }
-                    s
+                };
+
+                // Supported SVG units are px, in, cm, mm, pt, pc, ch
+                (
+                    Size {
+                        w: set_unit(
+                            ULength::<Horizontal>::new(natural_size.w, LengthUnit::Px),
+                            &params,
+                            unit,
+                        ),
+                        h: set_unit(
+                            ULength::<Vertical>::new(natural_size.h, LengthUnit::Px),
+                            &params,
+                            unit,
+                        ),
+                    },
+                    self.width.map(|l| set_unit(l, &params, unit)),
+                    self.height.map(|l| set_unit(l, &params, unit)),
+                    self.page_size.map(|(w, h)| Size {
+                        w: set_unit(w, &params, unit),
+                        h: set_unit(h, &params, unit),
+                    }),
+                )
+            }
+        };
+
+        let strategy = self.resize_strategy(requested_width, requested_height);
+
+        let final_size = self.final_size(&strategy, &natural_size, input)?;
+
+        // Create the surface once on the first input,
+        // except for PDF, PS, and EPS, which allow differently-sized pages.
+        let page_size = page_size.unwrap_or(final_size);
+        let s = match surface {
+            Some(s) => {
+                match s {
+                    #[cfg(system_deps_have_cairo_pdf)]
+                    Surface::Pdf(pdf, size, _) => {
+                        pdf.set_size(page_size.w, page_size.h).map_err(|e| {
+                            error!(
+                                "Error setting PDF page #{} size {}: {}",
+                                page_idx + 1,
+                                input,
+                                e
+                            )
+                        })?;
+                        *size = page_size;
+                    }
+                    #[cfg(system_deps_have_cairo_ps)]
+                    Surface::Ps(ps, size) => {
+                        ps.set_size(page_size.w, page_size.h);
+                        *size = page_size;
+                    }
+                    _ => {}
                 }
-                surface @ None => surface.insert(self.create_surface(page_size, unit)?),
-            };
+                s
+            }
+            surface @ None => {
+                surface.insert(self.create_surface(page_size, unit, &export.output)?)
+            }
+        };
 
-            let left = self.left.map(|l| set_unit(l, &params, unit)).unwrap_or(0.0);
-            let top = self.top.map(|l| set_unit(l, &params, unit)).unwrap_or(0.0);
-
-            s.render(
-                &renderer,
-                left,
-                top,
-                final_size,
-                geometry,
-                self.background_color,
-                self.export_id.as_deref(),
-            )
-            .map_err(|e| error!("Error rendering SVG {}: {}", input, e))?
-        }
+        let left = self.left.map(|l| set_unit(l, &params, unit)).unwrap_or(0.0);
+        let top = self.top.map(|l| set_unit(l, &params, unit)).unwrap_or(0.0);
+
+        s.render(
+            renderer,
+            left,
+            top,
+            final_size,
+            geometry,
+            viewport,
+            self.background_color,
+            export.id.as_deref(),
+        )
+        .map_err(|e| error!("Error rendering SVG {}: {}", input, e))
+    }
 
-        if let Some(s) = surface.take() {
-            s.finish()
-                .map_err(|e| error!("Error saving output {}: {}", self.output, e))?
-        };
+    /// Decides how to scale the natural size of the output from the `-w`, `-h`, `-z`, and
+    /// `--keep-aspect-ratio` options, with the requested size already in the output's units.
+    fn resize_strategy(
+        &self,
+        requested_width: Option<f64>,
+        requested_height: Option<f64>,
+    ) -> ResizeStrategy {
+        match (requested_width, requested_height) {
+            // terminal previews are one column per pixel, so default to a typical terminal width
+            (None, None) if matches!(self.format, Format::Ansi) && self.zoom.is_identity() => {
+                ResizeStrategy::FitWidth(ansi::DEFAULT_COLUMNS)
+            }
 
-        Ok(())
+            // when w and h are not specified, scale to the requested zoom (if any)
+            (None, None) => ResizeStrategy::Scale(self.zoom),
+
+            // when w and h are specified, but zoom is not, scale to the requested size
+            (Some(width), Some(height)) if self.zoom.is_identity() => ResizeStrategy::Fit {
+                size: Size::new(width, height),
+                keep_aspect_ratio: self.keep_aspect_ratio,
+            },
+
+            // if only one between w and h is specified and there is no zoom, scale to the
+            // requested w or h and use the same scaling factor for the other
+            (Some(w), None) if self.zoom.is_identity() => ResizeStrategy::FitWidth(w),
+            (None, Some(h)) if self.zoom.is_identity() => ResizeStrategy::FitHeight(h),
+
+            // otherwise scale the image, but cap the zoom to match the requested size
+            _ => ResizeStrategy::ScaleWithMaxSize {
+                scale: self.zoom,
+                max_width: requested_width,
+                max_height: requested_height,
+                keep_aspect_ratio: self.keep_aspect_ratio,
+            },
+        }
     }
 
     fn final_size(
@@ -766,19 +1505,37 @@ impl Converter {
             .ok_or_else(|| error!("The SVG {} has no dimensions", input))
     }
 
-    fn create_surface(&self, size: Size, unit: LengthUnit) -> Result<Surface, Error> {
-        let output_stream = match self.output {
-            Output::Stdout => Stdout::stream(),
+    fn open_output(&self, output: &Output) -> Result<OutputStream, Error> {
+        match output {
+            Output::Stdout => Ok(Stdout::stream()),
             Output::Path(ref p) => {
                 let file = gio::File::for_path(p);
                 let stream = file
                     .replace(None, false, FileCreateFlags::NONE, None::<&Cancellable>)
-                    .map_err(|e| error!("Error opening output \"{}\": {}", self.output, e))?;
-                stream.upcast::<OutputStream>()
+                    .map_err(|e| error!("Error opening output \"{}\": {}", output, e))?;
+                Ok(stream.upcast::<OutputStream>())
             }
-        };
+        }
+    }
 
-        Surface::new(self.format, size, output_stream, unit)
+    fn create_surface(
+        &self,
+        size: Size,
+        unit: LengthUnit,
+        output: &Output,
+    ) -> Result<Surface, Error> {
+        let output_stream = self.open_output(output)?;
+        let dpi = Dpi::new(self.dpi_x.0, self.dpi_y.0);
+
+        Surface::new(
+            self.format,
+            size,
+            output_stream,
+            unit,
+            dpi,
+            &self.document_info,
+            self.icc_profile.as_ref(),
+        )
     }
 }
 
@@ -802,6 +1559,30 @@ fn natural_geometry(
     })
 }
 
+/// Computes the ink rectangle of the whole document when rendered to the `viewport`,
+/// clipped to the viewport itself.
+fn ink_geometry(
+    renderer: &CairoRenderer,
+    input: &Input,
+    viewport: &cairo::Rectangle,
+) -> Result<cairo::Rectangle, Error> {
+    let (ink_r, _) = renderer
+        .geometry_for_layer(None, viewport)
+        .map_err(|e| error!("Error rendering SVG {}: {}", input, e))?;
+
+    let x0 = ink_r.x().max(viewport.x());
+    let y0 = ink_r.y().max(viewport.y());
+    let x1 = (ink_r.x() + ink_r.width()).min(viewport.x() + viewport.width());
+    let y1 = (ink_r.y() + ink_r.height()).min(viewport.y() + viewport.height());
+
+    Ok(cairo::Rectangle::new(
+        x0,
+        y0,
+        (x1 - x0).max(0.0),
+        (y1 - y0).max(0.0),
+    ))
+}
+
 fn build_cli() -> clap::Command {
     let supported_formats = vec![
         "png",
@@ -821,6 +1602,11 @@ fn build_cli() -> clap::Command {
         "eps",
         #[cfg(system_deps_have_cairo_svg)]
         "svg",
+        "ansi",
+        "jpeg",
+        "jpg",
+        #[cfg(feature = "webp")]
+        "webp",
     ];
 
     clap::Command::new("rsvg-convert")
@@ -864,6 +1650,16 @@ fn build_cli() -> clap::Command {
                 .help("Pixels per inch")
                 .action(clap::ArgAction::Set),
         )
+        .arg(
+            clap::Arg::new("res")
+                .long("dpi")
+                .num_args(1)
+                .value_name("number")
+                .conflicts_with_all(["res_x", "res_y"])
+                .value_parser(parse_resolution)
+                .help("Pixels per inch, for both axes")
+                .action(clap::ArgAction::Set),
+        )
         .arg(
             clap::Arg::new("zoom_x")
                 .short('x')
@@ -963,6 +1759,28 @@ fn build_cli() -> clap::Command {
                 .help("Output format")
                 .action(clap::ArgAction::Set),
         )
+        .arg(
+            clap::Arg::new("quality")
+                .long("quality")
+                .num_args(1)
+                .value_name("number")
+                .value_parser(clap::value_parser!(u8).range(i64::from(jpeg::MIN_QUALITY)..=100))
+                .help("Quality of JPEG or lossy WebP output, from 1 to 100 [defaults to 75]")
+                .action(clap::ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("lossless")
+                .long("lossless")
+                .help("Use lossless compression for WebP output")
+                .conflicts_with("quality")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("png_dpi")
+                .long("png-dpi")
+                .help("Record the resolution from --dpi-x and --dpi-y in PNG output")
+                .action(clap::ArgAction::SetTrue),
+        )
         .arg(
             clap::Arg::new("output")
                 .short('o')
@@ -978,7 +1796,17 @@ fn build_cli() -> clap::Command {
                 .long("export-id")
                 .value_parser(clap::builder::NonEmptyStringValueParser::new())
                 .value_name("object id")
-                .help("SVG id of object to export [default is to export all objects]")
+                .help("SVG id of object to export, or a comma-separated list of ids [default is to export all objects]")
+                .action(clap::ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("output_template")
+                .long("output-template")
+                .num_args(1)
+                .value_parser(clap::builder::NonEmptyStringValueParser::new())
+                .value_name("template")
+                .help("Filename for each output, where {id} is replaced by the exported id and {name} by the input's name, like \"out/{name}-{id}.png\"")
+                .conflicts_with("output")
                 .action(clap::ArgAction::Set),
         )
         .arg(
@@ -997,6 +1825,12 @@ fn build_cli() -> clap::Command {
                 .help("Preserve the aspect ratio")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            clap::Arg::new("crop")
+                .long("crop")
+                .help("Crop the output to the area with drawn contents")
+                .action(clap::ArgAction::SetTrue),
+        )
         .arg(
             clap::Arg::new("background")
                 .short('b')
@@ -1039,6 +1873,88 @@ fn build_cli() -> clap::Command {
                 .conflicts_with("keep_image_data")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            clap::Arg::new("max_pages")
+                .long("max-pages")
+                .num_args(1)
+                .value_parser(clap::value_parser!(u32).range(1..))
+                .value_name("number")
+                .help("Only convert the first <number> input files, for PDF and (E)PS output")
+                .action(clap::ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("even_pages")
+                .long("even-pages")
+                .help("Append a blank page if the number of pages is odd, for PDF and (E)PS output")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("title")
+                .long("title")
+                .num_args(1)
+                .value_name("text")
+                .help("Title of the document, for PDF output")
+                .action(clap::ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("author")
+                .long("author")
+                .num_args(1)
+                .value_name("text")
+                .help("Author of the document, for PDF output")
+                .action(clap::ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("subject")
+                .long("subject")
+                .num_args(1)
+                .value_name("text")
+                .help("Subject of the document, for PDF output")
+                .action(clap::ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("keywords")
+                .long("keywords")
+                .num_args(1)
+                .value_name("text")
+                .help("Keywords for the document, for PDF output")
+                .action(clap::ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("icc_profile")
+                .long("icc-profile")
+                .num_args(1)
+                .value_parser(clap::value_parser!(PathBuf))
+                .value_name("filename.icc")
+                .help("ICC profile for the output intent of PDF output; sRGB is assumed without one")
+                .action(clap::ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("frames_from_ids")
+                .long("frames-from-ids")
+                .num_args(1)
+                .value_name("prefix")
+                .help("Write an animated PNG with a frame for each object whose id starts with <prefix>")
+                .conflicts_with("export_id")
+                .action(clap::ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("fps")
+                .long("fps")
+                .num_args(1)
+                .value_parser(clap::value_parser!(u16).range(1..))
+                .value_name("number")
+                .help("Frames per second for --frames-from-ids [default: 10]")
+                .requires("frames_from_ids")
+                .action(clap::ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("geometry")
+                .long("geometry")
+                .help("Print the geometry of the document and its objects as JSON instead of rendering")
+                .conflicts_with_all(["frames_from_ids", "crop"])
+                .action(clap::ArgAction::SetTrue),
+        )
         .arg(
             clap::Arg::new("testing")
                 .long("testing")
@@ -1054,6 +1970,25 @@ fn build_cli() -> clap::Command {
                 .action(clap::ArgAction::Set)
                 .value_parser(clap::value_parser!(Shell)),
         )
+        .arg(
+            clap::Arg::new("jobs")
+                .short('j')
+                .long("jobs")
+                .num_args(1)
+                .value_parser(clap::value_parser!(u32).range(1..))
+                .value_name("number")
+                .help("Number of input files to convert at the same time for image formats [defaults to the number of CPUs]")
+                .action(clap::ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("files_from")
+                .long("files-from")
+                .num_args(1)
+                .value_parser(clap::value_parser!(OsString))
+                .value_name("list")
+                .help("Read the names of input files from a file with one name per line, or - for stdin")
+                .action(clap::ArgAction::Set),
+        )
         .arg(
             clap::Arg::new("FILE")
                 .value_parser(clap::value_parser!(OsString))
@@ -1067,6 +2002,12 @@ fn print_completions<G: Generator>(gen: G, cmd: &mut clap::Command) {
     clap_complete::generate(gen, cmd, cmd.get_name().to_string(), &mut io::stdout());
 }
 
+/// Placeholder for the id in the `--output-template` option.
+const ID_TOKEN: &str = "{id}";
+
+/// Placeholder for the name of the input file in the `--output-template` option.
+const NAME_TOKEN: &str = "{name}";
+
 fn parse_args() -> Result<Converter, Error> {
     let cli = build_cli();
     let matches = cli.get_matches();
@@ -1082,9 +2023,13 @@ fn parse_args() -> Result<Converter, Error> {
         .get_one("format")
         .expect("already provided default_value");
 
+    let quality = matches.get_one::<u8>("quality").copied().unwrap_or(75);
+
     let format = match_ignore_ascii_case! {
         format_str,
-        "png" => Format::Png,
+        "png" => Format::Png(PngOptions {
+            dpi_metadata: matches.get_flag("png_dpi"),
+        }),
         "pdf" => Format::Pdf,
         "pdf1.7" => Format::Pdf1_7,
         "pdf1.6" => Format::Pdf1_6,
@@ -1093,6 +2038,12 @@ fn parse_args() -> Result<Converter, Error> {
         "ps" => Format::Ps,
         "eps" => Format::Eps,
         "svg" => Format::Svg,
+        "ansi" => Format::Ansi,
+        "jpeg" | "jpg" => Format::Jpeg(quality),
+        "webp" => Format::Webp(WebpOptions {
+            quality: f32::from(quality),
+            lossless: matches.get_flag("lossless"),
+        }),
         _ => unreachable!("clap should already have the list of possible values"),
     };
 
@@ -1114,6 +2065,27 @@ fn parse_args() -> Result<Converter, Error> {
     let background_color: Option<Color> = parse_background_color(background_str)
         .map_err(|e| clap::Error::raw(clap::error::ErrorKind::InvalidValue, e))?;
 
+    // JPEG has no transparency, so it gets a white background unless the user asks otherwise.
+    let background_color = match (format, background_color) {
+        (Format::Jpeg(_), None) => {
+            if matches.value_source("background") == Some(clap::parser::ValueSource::DefaultValue) {
+                Some(Color::Rgba(RGBA::new(
+                    Some(255),
+                    Some(255),
+                    Some(255),
+                    Some(1.0),
+                )))
+            } else {
+                return Err(error!(
+                    "JPEG output cannot have a transparent background; \
+                     please use --background-color with a color other than \"none\"."
+                ));
+            }
+        }
+
+        (_, color) => color,
+    };
+
     // librsvg expects ids starting with '#', so it can lookup ids in externs like "subfile.svg#subid".
     // For the user's convenience, we prepend '#' automatically; we only support specifying ids from
     // the toplevel, and don't expect users to lookup things in externs.
@@ -1144,44 +2116,288 @@ fn parse_args() -> Result<Converter, Error> {
         (Some(w), Some(h)) => Some((w, h)),
     };
 
-    let dpi_x = *matches
-        .get_one::<Resolution>("res_x")
-        .expect("already provided default_value");
-    let dpi_y = *matches
-        .get_one::<Resolution>("res_y")
-        .expect("already provided default_value");
+    let dpi = matches.get_one::<Resolution>("res").copied();
+    let dpi_x = dpi.unwrap_or_else(|| {
+        *matches
+            .get_one::<Resolution>("res_x")
+            .expect("already provided default_value")
+    });
+    let dpi_y = dpi.unwrap_or_else(|| {
+        *matches
+            .get_one::<Resolution>("res_y")
+            .expect("already provided default_value")
+    });
 
     let zoom: Option<ZoomFactor> = matches.get_one("zoom").copied();
     let zoom_x: Option<ZoomFactor> = matches.get_one("zoom_x").copied();
     let zoom_y: Option<ZoomFactor> = matches.get_one("zoom_y").copied();
 
-    let input = match matches.get_many::<std::ffi::OsString>("FILE") {
+    let files_from = matches.get_one::<OsString>("files_from");
+
+    let mut input = match matches.get_many::<std::ffi::OsString>("FILE") {
         Some(values) => values
-            .map(|f| PathOrUrl::from_os_str(f).map_err(Error))
-            .map(|r| match r {
-                Ok(p) if p.is_stdin_alias() => Ok(Input::Stdin),
-                p => p.map(Input::Named),
-            })
+            .map(|f| parse_input(f))
             .collect::<Result<Vec<Input>, Error>>()?,
 
+        None if files_from.is_some() => Vec::new(),
         None => vec![Input::Stdin],
     };
 
-    if input.iter().filter(|i| matches!(i, Input::Stdin)).count() > 1 {
+    let list_uses_stdin = files_from.is_some_and(|list| list == "-");
+
+    if let Some(list) = files_from {
+        input.extend(read_files_from(list)?);
+    }
+
+    let stdin_count = input.iter().filter(|i| matches!(i, Input::Stdin)).count();
+
+    if stdin_count + usize::from(list_uses_stdin) > 1 {
         return Err(error!("Only one input file can be read from stdin."));
     }
 
-    if input.len() > 1 && !matches!(format, Format::Ps | Format::Eps | Format::Pdf) {
+    if input.is_empty() {
+        return Err(error!("The list of input files is empty."));
+    }
+
+    let output_template = matches.get_one::<String>("output_template");
+    let output_per_input = output_template.is_some_and(|t| t.contains(NAME_TOKEN));
+
+    if input.len() > 1 {
+        match format {
+            Format::Ps
+            | Format::Eps
+            | Format::Pdf
+            | Format::Pdf1_7
+            | Format::Pdf1_6
+            | Format::Pdf1_5
+            | Format::Pdf1_4
+                if output_per_input =>
+            {
+                return Err(error!(
+                    "The --output-template can only contain {} for PNG, JPEG, or WebP output.",
+                    NAME_TOKEN
+                ));
+            }
+
+            Format::Ps
+            | Format::Eps
+            | Format::Pdf
+            | Format::Pdf1_7
+            | Format::Pdf1_6
+            | Format::Pdf1_5
+            | Format::Pdf1_4 => (),
+
+            Format::Png(_) | Format::Jpeg(_) | Format::Webp(_) if output_per_input => (),
+
+            _ => {
+                return Err(error!(
+                    "Multiple SVG files are only allowed for PDF and (E)PS output, \
+                     or for PNG, JPEG, and WebP output with an --output-template \
+                     that contains {}.",
+                    NAME_TOKEN
+                ));
+            }
+        }
+    }
+
+    let max_pages = matches.get_one::<u32>("max_pages").map(|n| *n as usize);
+    let even_pages = matches.get_flag("even_pages");
+
+    let is_multi_page = matches!(
+        format,
+        Format::Pdf
+            | Format::Pdf1_7
+            | Format::Pdf1_6
+            | Format::Pdf1_5
+            | Format::Pdf1_4
+            | Format::Ps
+            | Format::Eps
+    );
+
+    if (max_pages.is_some() || even_pages) && !is_multi_page {
+        return Err(error!(
+            "The --max-pages and --even-pages options are only allowed for PDF and (E)PS output."
+        ));
+    }
+
+    let document_info = DocumentInfo {
+        title: matches.get_one::<String>("title").cloned(),
+        author: matches.get_one::<String>("author").cloned(),
+        subject: matches.get_one::<String>("subject").cloned(),
+        keywords: matches.get_one::<String>("keywords").cloned(),
+    };
+
+    let is_pdf = matches!(
+        format,
+        Format::Pdf | Format::Pdf1_7 | Format::Pdf1_6 | Format::Pdf1_5 | Format::Pdf1_4
+    );
+
+    if !document_info.is_empty() && !is_pdf {
         return Err(error!(
-            "Multiple SVG files are only allowed for PDF and (E)PS output."
+            "The --title, --author, --subject, and --keywords options are only allowed for PDF output."
         ));
     }
 
-    let export_id: Option<String> = matches.get_one::<String>("export_id").map(lookup_id);
+    let icc_profile = match matches.get_one::<PathBuf>("icc_profile") {
+        None => None,
 
-    let output = match matches.get_one::<PathBuf>("output") {
-        None => Output::Stdout,
-        Some(path) => Output::Path(path.clone()),
+        Some(_) if !is_pdf => {
+            return Err(error!(
+                "The --icc-profile option is only allowed for PDF output."
+            ));
+        }
+
+        // Cairo writes cross-reference streams for PDF 1.5 and later, which pdf_output
+        // cannot update.  Plain --format=pdf is not accepted either, so that the
+        // option does not change the PDF version behind the user's back.
+        Some(_) if !matches!(format, Format::Pdf1_4) => {
+            return Err(error!(
+                "The --icc-profile option needs PDF 1.4 output; please use --format=pdf1.4."
+            ));
+        }
+
+        Some(path) => {
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+
+            let profile = std::fs::read(path)
+                .map_err(|e| e.to_string())
+                .and_then(|data| IccProfile::new(data, &name))
+                .map_err(|e| error!("Error reading ICC profile {}: {}", path.display(), e))?;
+
+            Some(profile)
+        }
+    };
+
+    let frames_from_ids = matches
+        .get_one::<String>("frames_from_ids")
+        .map(|prefix| prefix.trim_start_matches('#').to_string());
+
+    if frames_from_ids.is_some() && !matches!(format, Format::Png(_)) {
+        return Err(error!(
+            "The --frames-from-ids option is only allowed for PNG output."
+        ));
+    }
+
+    if matches.get_flag("geometry") && input.len() > 1 && !output_per_input {
+        return Err(error!(
+            "The --geometry option needs an --output-template that contains {} \
+             for multiple SVG files.",
+            NAME_TOKEN
+        ));
+    }
+
+    let export_ids: Vec<String> = match matches.get_one::<String>("export_id") {
+        None => Vec::new(),
+        Some(ids) => ids.split(',').map(|id| id.trim().to_string()).collect(),
+    };
+
+    if export_ids.iter().any(|id| id.is_empty() || id == "#") {
+        return Err(error!(
+            "The --export-id option has an empty id in its list."
+        ));
+    }
+
+    if let Some(template) = output_template {
+        if export_ids.len() > 1 && !template.contains(ID_TOKEN) {
+            return Err(error!(
+                "The --output-template must contain {} when exporting several ids.",
+                ID_TOKEN
+            ));
+        }
+
+        if export_ids.is_empty() && template.contains(ID_TOKEN) {
+            return Err(error!(
+                "The --output-template contains {} but no --export-id was given.",
+                ID_TOKEN
+            ));
+        }
+    } else if export_ids.len() > 1 {
+        return Err(error!(
+            "Please use --output-template to specify an output file for each id."
+        ));
+    }
+
+    // Computes the objects to export from an input with the given name, and where to put them.
+    let exports_for = |name: &str| -> Vec<Export> {
+        match output_template {
+            Some(template) => {
+                let template = template.replace(NAME_TOKEN, name);
+
+                if export_ids.is_empty() {
+                    vec![Export {
+                        id: None,
+                        output: Output::Path(PathBuf::from(template)),
+                    }]
+                } else {
+                    export_ids
+                        .iter()
+                        .map(|id| Export {
+                            id: Some(lookup_id(id)),
+                            output: Output::Path(PathBuf::from(
+                                template.replace(ID_TOKEN, id.trim_start_matches('#')),
+                            )),
+                        })
+                        .collect()
+                }
+            }
+
+            None => {
+                let output = match matches.get_one::<PathBuf>("output") {
+                    None => Output::Stdout,
+                    Some(path) => Output::Path(path.clone()),
+                };
+
+                vec![Export {
+                    id: export_ids.first().map(lookup_id),
+                    output,
+                }]
+            }
+        }
+    };
+
+    // PDF and PostScript put all the inputs as pages in a single set of files; image
+    // formats get a set of files for each input.
+    let tasks = if input.len() > 1 && output_per_input {
+        input
+            .into_iter()
+            .map(|input| Task {
+                exports: exports_for(&input.stem()),
+                inputs: vec![input],
+            })
+            .collect()
+    } else {
+        let name = match input.as_slice() {
+            [input] => input.stem(),
+            _ => String::new(),
+        };
+
+        vec![Task {
+            exports: exports_for(&name),
+            inputs: input,
+        }]
+    };
+
+    // Inputs with the same name in different directories, or the same input given twice,
+    // would overwrite each other's output.
+    let mut output_paths = HashSet::new();
+    for export in tasks.iter().flat_map(|task| task.exports.iter()) {
+        if let Output::Path(ref path) = export.output {
+            if !output_paths.insert(path) {
+                return Err(error!(
+                    "Several outputs would be written to the same file {}; please use a \
+                     different --output-template.",
+                    export.output
+                ));
+            }
+        }
+    }
+
+    let jobs = match matches.get_one::<u32>("jobs") {
+        Some(jobs) => *jobs as usize,
+        None => std::thread::available_parallelism().map_or(1, |n| n.get()),
     };
 
     Ok(Converter {
@@ -1197,25 +2413,58 @@ fn parse_args() -> Result<Converter, Error> {
         top,
         page_size,
         format,
-        export_id,
+        tasks,
+        jobs,
         keep_aspect_ratio: matches.get_flag("keep_aspect"),
+        crop: matches.get_flag("crop"),
         background_color,
         stylesheet: matches.get_one("stylesheet").cloned(),
         unlimited: matches.get_flag("unlimited"),
         keep_image_data,
         language,
-        input,
-        output,
+        max_pages,
+        even_pages,
+        document_info,
+        icc_profile,
+        frames_from_ids,
+        fps: matches.get_one::<u16>("fps").copied().unwrap_or(10),
+        geometry: matches.get_flag("geometry"),
         testing: matches.get_flag("testing"),
     })
 }
 
+fn parse_input(f: &OsStr) -> Result<Input, Error> {
+    match PathOrUrl::from_os_str(f).map_err(Error)? {
+        p if p.is_stdin_alias() => Ok(Input::Stdin),
+        p => Ok(Input::Named(p)),
+    }
+}
+
+/// Reads the names of input files from `list`, or from stdin if it is `-`.
+///
+/// The file has one name per line; blank lines and lines that start with `#` are ignored.
+fn read_files_from(list: &OsStr) -> Result<Vec<Input>, Error> {
+    let contents = if list == "-" {
+        io::read_to_string(io::stdin())
+    } else {
+        std::fs::read_to_string(list)
+    }
+    .map_err(|e| error!("Error reading list of files {:?}: {}", list, e))?;
+
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| parse_input(OsStr::new(line)))
+        .collect()
+}
+
 #[derive(Copy, Clone)]
 struct Resolution(f64);
 
 fn parse_resolution(v: &str) -> Result<Resolution, String> {
     match v.parse::<f64>() {
-        Ok(res) if res > 0.0 => Ok(Resolution(res)),
+        Ok(res) if res > 0.0 && res.is_finite() => Ok(Resolution(res)),
         Ok(_) => Err(String::from("Invalid resolution")),
         Err(e) => Err(format!("{e}")),
     }
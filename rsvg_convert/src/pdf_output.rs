@@ -0,0 +1,732 @@
+//! Adds an output intent with an ICC profile to PDF files written by Cairo.
+//!
+//! Cairo has no API for output intents, so this appends an incremental update to the
+//! finished file: the profile's stream, the output intent that refers to it, and a new
+//! version of the document catalog that lists the output intent.
+//!
+//! Only files with cross-reference tables can be updated.  Cairo writes those for PDF
+//! 1.4; for PDF 1.5 and later it writes cross-reference streams, and may put the
+//! catalog in a compressed object stream.
+
+use std::collections::{HashMap, HashSet};
+
+/// Length of the header of an ICC profile.
+const ICC_HEADER_SIZE: usize = 128;
+
+/// An ICC profile to attach to PDF output as its output intent.
+#[derive(Clone, Debug)]
+pub struct IccProfile {
+    data: Vec<u8>,
+
+    /// Number of color components, for the `/N` entry of the profile's stream.
+    components: u8,
+
+    /// Shown to the user as the output intent's description.
+    name: String,
+}
+
+impl IccProfile {
+    /// Checks the header of an ICC profile and finds out its number of color components.
+    pub fn new(data: Vec<u8>, name: &str) -> Result<IccProfile, String> {
+        if data.len() < ICC_HEADER_SIZE || &data[36..40] != b"acsp" {
+            return Err(String::from("not an ICC profile"));
+        }
+
+        let size = u32::from_be_bytes(data[0..4].try_into().unwrap());
+        if size as usize != data.len() {
+            return Err(String::from(
+                "the size in the ICC profile's header does not match the file",
+            ));
+        }
+
+        let components = match &data[16..20] {
+            b"GRAY" => 1,
+            b"RGB " => 3,
+            b"CMYK" => 4,
+            space => {
+                return Err(format!(
+                    "ICC profiles for the \"{}\" color space are not supported",
+                    String::from_utf8_lossy(space).trim_end()
+                ))
+            }
+        };
+
+        Ok(IccProfile {
+            data,
+            components,
+            name: name.to_string(),
+        })
+    }
+}
+
+/// Adds an output intent with an ICC profile to a PDF file.
+///
+/// The output intent has the `GTS_PDFA1` subtype, which viewers and printing software
+/// use as the default output intent.  The file does not claim to conform to PDF/A or
+/// PDF/X, though.
+pub fn with_output_intent(mut pdf: Vec<u8>, profile: &IccProfile) -> Result<Vec<u8>, String> {
+    let xref = Xref::read(&pdf)?;
+    let (root, root_gen) = xref.root;
+
+    let catalog = xref.object(&pdf, root, root_gen).ok_or_else(invalid)?;
+    let catalog_start = find(catalog, b"<<").ok_or_else(invalid)?;
+    let catalog_end = dict_end(catalog, catalog_start).ok_or_else(invalid)?;
+    let catalog = &catalog[catalog_start..catalog_end - b">>".len()];
+
+    if dict_value(catalog, b"/OutputIntents").is_some() {
+        return Err(String::from("the PDF already has an output intent"));
+    }
+
+    let profile_num = xref.size;
+    let intent_num = xref.size + 1;
+
+    let mut new_catalog = trim_end(catalog).to_vec();
+    new_catalog
+        .extend_from_slice(format!("\n   /OutputIntents [ {intent_num} 0 R ]\n>>").as_bytes());
+
+    if !pdf.ends_with(b"\n") {
+        pdf.push(b'\n');
+    }
+
+    let alternate = match profile.components {
+        1 => "DeviceGray",
+        3 => "DeviceRGB",
+        _ => "DeviceCMYK",
+    };
+
+    let profile_offset = pdf.len();
+    pdf.extend_from_slice(
+        format!(
+            "{profile_num} 0 obj\n\
+             << /N {}\n   /Alternate /{alternate}\n   /Length {}\n>>\nstream\n",
+            profile.components,
+            profile.data.len()
+        )
+        .as_bytes(),
+    );
+    pdf.extend_from_slice(&profile.data);
+    pdf.extend_from_slice(b"\nendstream\nendobj\n");
+
+    let name = pdf_string(&profile.name);
+    let intent_offset = pdf.len();
+    pdf.extend_from_slice(
+        format!(
+            "{intent_num} 0 obj\n\
+             << /Type /OutputIntent\n   \
+             /S /GTS_PDFA1\n   \
+             /OutputConditionIdentifier {name}\n   \
+             /Info {name}\n   \
+             /DestOutputProfile {profile_num} 0 R\n>>\nendobj\n",
+        )
+        .as_bytes(),
+    );
+
+    let catalog_offset = pdf.len();
+    pdf.extend_from_slice(format!("{root} {root_gen} obj\n").as_bytes());
+    pdf.extend_from_slice(&new_catalog);
+    pdf.extend_from_slice(b"\nendobj\n");
+
+    let xref_offset = pdf.len();
+    pdf.extend_from_slice(
+        format!(
+            "xref\n\
+             {root} 1\n{catalog_offset:010} {root_gen:05} n \n\
+             {profile_num} 2\n{profile_offset:010} 00000 n \n{intent_offset:010} 00000 n \n\
+             trailer\n<< /Size {}\n   /Root {root} {root_gen} R\n",
+            xref.size + 2
+        )
+        .as_bytes(),
+    );
+
+    for (key, value) in [("/Info", xref.info), ("/ID", xref.id)] {
+        if let Some(value) = value {
+            pdf.extend_from_slice(format!("   {key} ").as_bytes());
+            pdf.extend_from_slice(&value);
+            pdf.push(b'\n');
+        }
+    }
+
+    pdf.extend_from_slice(
+        format!(
+            "   /Prev {}\n>>\nstartxref\n{xref_offset}\n%%EOF\n",
+            xref.start
+        )
+        .as_bytes(),
+    );
+
+    Ok(pdf)
+}
+
+fn invalid() -> String {
+    String::from("could not parse the PDF written by Cairo")
+}
+
+/// The cross-reference information of a PDF file, collected from all its sections.
+struct Xref {
+    /// Offset of the newest cross-reference section, for the `/Prev` of the next one.
+    start: usize,
+
+    /// Offsets of the objects in use, by object number.  Entries in newer sections
+    /// replace the ones in older sections.
+    offsets: HashMap<usize, usize>,
+
+    /// The following are from the newest trailer.
+    size: usize,
+    root: (usize, u16),
+    info: Option<Vec<u8>>,
+    id: Option<Vec<u8>>,
+}
+
+impl Xref {
+    /// Reads the cross-reference table at the `startxref` offset, and the older ones
+    /// that it refers to with `/Prev`.
+    fn read(pdf: &[u8]) -> Result<Xref, String> {
+        let startxref = rfind(pdf, b"startxref").ok_or_else(invalid)?;
+        let start: usize =
+            parse_number(&pdf[startxref + b"startxref".len()..]).ok_or_else(invalid)?;
+
+        let mut entries = HashMap::new();
+        let mut newest_trailer = None;
+        let mut visited = HashSet::new();
+        let mut next = Some(start);
+
+        while let Some(pos) = next {
+            if !visited.insert(pos) {
+                return Err(invalid());
+            }
+
+            let section = trim_start(pdf.get(pos..).ok_or_else(invalid)?);
+            if !section.starts_with(b"xref") {
+                return Err(String::from(
+                    "the PDF has a cross-reference stream; only PDF 1.4 output is supported",
+                ));
+            }
+
+            let trailer_pos = find(section, b"trailer").ok_or_else(invalid)?;
+            let table =
+                parse_xref_table(&section[b"xref".len()..trailer_pos]).ok_or_else(invalid)?;
+
+            // Newer sections come first, so keep the entries that are already there.
+            for (num, offset) in table {
+                entries.entry(num).or_insert(offset);
+            }
+
+            let trailer_start =
+                trailer_pos + find(&section[trailer_pos..], b"<<").ok_or_else(invalid)?;
+            let trailer_end = dict_end(section, trailer_start).ok_or_else(invalid)?;
+            let trailer = &section[trailer_start..trailer_end];
+
+            // In hybrid files, some objects are only listed in a cross-reference stream.
+            if dict_value(trailer, b"/XRefStm").is_some() {
+                return Err(String::from(
+                    "the PDF has a cross-reference stream; only PDF 1.4 output is supported",
+                ));
+            }
+
+            next = match dict_value(trailer, b"/Prev") {
+                Some(prev) => Some(parse_number(prev).ok_or_else(invalid)?),
+                None => None,
+            };
+
+            newest_trailer.get_or_insert(trailer);
+        }
+
+        let trailer = newest_trailer.ok_or_else(invalid)?;
+
+        let size = dict_value(trailer, b"/Size")
+            .and_then(parse_number)
+            .ok_or_else(invalid)?;
+        let root = dict_value(trailer, b"/Root")
+            .and_then(parse_reference)
+            .ok_or_else(invalid)?;
+        let info = dict_value(trailer, b"/Info").map(<[u8]>::to_vec);
+        let id = dict_value(trailer, b"/ID").map(<[u8]>::to_vec);
+
+        let offsets = entries
+            .into_iter()
+            .filter_map(|(num, offset)| Some((num, offset?)))
+            .collect();
+
+        Ok(Xref {
+            start,
+            offsets,
+            size,
+            root,
+            info,
+            id,
+        })
+    }
+
+    /// Returns the data of an object, starting at its body, if the object is in use and
+    /// its header matches the object and generation numbers.
+    fn object<'a>(&self, pdf: &'a [u8], num: usize, gen: u16) -> Option<&'a [u8]> {
+        let data = trim_start(pdf.get(*self.offsets.get(&num)?..)?);
+        let header = format!("{num} {gen} obj");
+
+        if data.starts_with(header.as_bytes()) {
+            Some(&data[header.len()..])
+        } else {
+            None
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+fn rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).rposition(|w| w == needle)
+}
+
+fn trim_start(s: &[u8]) -> &[u8] {
+    let start = s
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(s.len());
+    &s[start..]
+}
+
+fn trim_end(s: &[u8]) -> &[u8] {
+    let end = s
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())
+        .map_or(0, |i| i + 1);
+    &s[..end]
+}
+
+fn is_delimiter(b: u8) -> bool {
+    b.is_ascii_whitespace() || b"()<>[]{}/%".contains(&b)
+}
+
+/// Parses the number at the start of `s`, after any whitespace.
+fn parse_number<T: std::str::FromStr>(s: &[u8]) -> Option<T> {
+    let s = trim_start(s);
+    let len = s
+        .iter()
+        .position(|b| !b.is_ascii_digit())
+        .unwrap_or(s.len());
+    std::str::from_utf8(&s[..len]).ok()?.parse().ok()
+}
+
+/// Parses an indirect reference like `12 0 R` into the object and generation numbers.
+fn parse_reference(s: &[u8]) -> Option<(usize, u16)> {
+    let s = std::str::from_utf8(s).ok()?;
+    match s.split_ascii_whitespace().collect::<Vec<_>>()[..] {
+        [num, gen, "R"] => Some((num.parse().ok()?, gen.parse().ok()?)),
+        _ => None,
+    }
+}
+
+/// Parses the subsections of a cross-reference table into the offsets of objects, or
+/// `None` for free objects.
+fn parse_xref_table(table: &[u8]) -> Option<HashMap<usize, Option<usize>>> {
+    let table = std::str::from_utf8(table).ok()?;
+    let mut tokens = table.split_ascii_whitespace();
+    let mut entries = HashMap::new();
+
+    while let Some(first) = tokens.next() {
+        let first: usize = first.parse().ok()?;
+        let count: usize = tokens.next()?.parse().ok()?;
+
+        for num in first..first.checked_add(count)? {
+            let offset = tokens.next()?.parse().ok()?;
+            let _gen = tokens.next()?;
+            let entry = match tokens.next()? {
+                "n" => Some(offset),
+                "f" => None,
+                _ => return None,
+            };
+            entries.insert(num, entry);
+        }
+    }
+
+    Some(entries)
+}
+
+/// Returns the position just after the `>>` that closes the dictionary at `start`.
+fn dict_end(data: &[u8], start: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut i = start;
+
+    while i < data.len() {
+        match data[i] {
+            b'(' => i = string_end(data, i)?,
+
+            b'<' if data.get(i + 1) == Some(&b'<') => {
+                depth += 1;
+                i += 2;
+            }
+
+            b'>' if data.get(i + 1) == Some(&b'>') => {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+
+            // hex string
+            b'<' => i += data[i..].iter().position(|&b| b == b'>')? + 1,
+
+            _ => i += 1,
+        }
+    }
+
+    None
+}
+
+/// Returns the position just after the `)` that closes the literal string at `start`.
+fn string_end(data: &[u8], start: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut i = start;
+
+    while i < data.len() {
+        match data[i] {
+            b'\\' => i += 1,
+            b'(' => depth += 1,
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            _ => (),
+        }
+        i += 1;
+    }
+
+    None
+}
+
+/// Returns the value of a key in the dictionary `dict`, for values that are numbers,
+/// references, or arrays without nested arrays.
+///
+/// Only keys of `dict` itself are considered, not the ones in nested dictionaries or
+/// in strings.
+fn dict_value<'a>(dict: &'a [u8], key: &[u8]) -> Option<&'a [u8]> {
+    let mut depth = 0;
+    let mut i = 0;
+
+    let value = loop {
+        match *dict.get(i)? {
+            b'(' => i = string_end(dict, i)?,
+
+            b'<' if dict.get(i + 1) == Some(&b'<') => {
+                depth += 1;
+                i += 2;
+            }
+
+            b'>' if dict.get(i + 1) == Some(&b'>') => {
+                depth -= 1;
+                i += 2;
+            }
+
+            // hex string
+            b'<' => i += dict[i..].iter().position(|&b| b == b'>')? + 1,
+
+            b'/' if depth == 1
+                && dict[i..].starts_with(key)
+                && dict.get(i + key.len()).map_or(true, |&b| is_delimiter(b)) =>
+            {
+                break trim_start(&dict[i + key.len()..]);
+            }
+
+            _ => i += 1,
+        }
+    };
+
+    let len = if value.starts_with(b"[") {
+        value.iter().position(|&b| b == b']')? + 1
+    } else {
+        value
+            .iter()
+            .position(|&b| b == b'/' || b == b'>')
+            .unwrap_or(value.len())
+    };
+
+    Some(trim_end(&value[..len]))
+}
+
+/// Encodes a PDF literal string, with octal escapes for anything that is not
+/// printable ASCII.
+fn pdf_string(s: &str) -> String {
+    let mut out = String::from("(");
+
+    for &b in s.as_bytes() {
+        match b {
+            b'(' | b')' | b'\\' => {
+                out.push('\\');
+                out.push(char::from(b));
+            }
+            0x20..=0x7e => out.push(char::from(b)),
+            _ => out.push_str(&format!("\\{b:03o}")),
+        }
+    }
+
+    out.push(')');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes a PDF file, keeping track of the offsets of its objects.
+    struct PdfWriter {
+        data: Vec<u8>,
+        eol: &'static str,
+        offsets: Vec<(usize, u16, usize)>,
+        last_xref: Option<usize>,
+    }
+
+    impl PdfWriter {
+        fn new(eol: &'static str) -> PdfWriter {
+            PdfWriter {
+                data: format!("%PDF-1.4{eol}").into_bytes(),
+                eol,
+                offsets: Vec::new(),
+                last_xref: None,
+            }
+        }
+
+        fn object(&mut self, num: usize, gen: u16, body: &str) -> &mut PdfWriter {
+            let eol = self.eol;
+            self.offsets.push((num, gen, self.data.len()));
+            self.data.extend_from_slice(
+                format!("{num} {gen} obj{eol}{body}{eol}endobj{eol}").as_bytes(),
+            );
+            self
+        }
+
+        /// Writes a cross-reference section for the objects written since the last one,
+        /// with a subsection for each object, and a trailer with the given entries.
+        fn xref(&mut self, trailer: &str) -> &mut PdfWriter {
+            let eol = self.eol;
+            let xref_offset = self.data.len();
+
+            let mut section = format!("xref{eol}0 1{eol}0000000000 65535 f{eol}");
+            for (num, gen, offset) in self.offsets.drain(..) {
+                section.push_str(&format!("{num} 1{eol}{offset:010} {gen:05} n{eol}"));
+            }
+
+            let prev = self
+                .last_xref
+                .map(|p| format!(" /Prev {p}"))
+                .unwrap_or_default();
+
+            section.push_str(&format!(
+                "trailer{eol}<< {trailer}{prev} >>{eol}startxref{eol}{xref_offset}{eol}%%EOF{eol}"
+            ));
+            self.data.extend_from_slice(section.as_bytes());
+            self.last_xref = Some(xref_offset);
+            self
+        }
+
+        fn finish(&mut self) -> Vec<u8> {
+            std::mem::take(&mut self.data)
+        }
+    }
+
+    fn rgb_profile() -> IccProfile {
+        let mut data = vec![0u8; ICC_HEADER_SIZE];
+        data[0..4].copy_from_slice(&(ICC_HEADER_SIZE as u32).to_be_bytes());
+        data[16..20].copy_from_slice(b"RGB ");
+        data[36..40].copy_from_slice(b"acsp");
+        IccProfile::new(data, "press (v2).icc").unwrap()
+    }
+
+    fn object(pdf: &[u8], num: usize, gen: u16) -> String {
+        let xref = Xref::read(pdf).unwrap();
+        let data = xref.object(pdf, num, gen).unwrap();
+        let end = find(data, b"endobj").unwrap();
+        String::from_utf8_lossy(&data[..end]).into_owned()
+    }
+
+    fn newest_trailer(pdf: &[u8]) -> String {
+        let start = rfind(pdf, b"trailer").unwrap();
+        let end = rfind(pdf, b"startxref").unwrap();
+        String::from_utf8_lossy(&pdf[start..end]).into_owned()
+    }
+
+    fn check_output_intent(pdf: &[u8], size: usize, root: (usize, u16)) {
+        let (profile_num, intent_num) = (size, size + 1);
+
+        let catalog = object(pdf, root.0, root.1);
+        assert!(catalog.contains(&format!("/OutputIntents [ {intent_num} 0 R ]")));
+
+        let intent = object(pdf, intent_num, 0);
+        assert!(intent.contains("/S /GTS_PDFA1"));
+        assert!(intent.contains("/OutputConditionIdentifier (press \\(v2\\).icc)"));
+        assert!(intent.contains(&format!("/DestOutputProfile {profile_num} 0 R")));
+
+        let profile = object(pdf, profile_num, 0);
+        assert!(profile.contains("/N 3"));
+        assert!(profile.contains("/Alternate /DeviceRGB"));
+
+        assert!(newest_trailer(pdf).contains(&format!("/Size {}", size + 2)));
+    }
+
+    #[test]
+    fn adds_output_intent() {
+        let pdf = PdfWriter::new("\n")
+            .object(1, 0, "<< /Type /Pages /Kids [] /Count 0 >>")
+            .object(2, 0, "<< /Type /Catalog\n   /Pages 1 0 R\n>>")
+            .xref("/Size 3 /Root 2 0 R")
+            .finish();
+
+        let new_pdf = with_output_intent(pdf.clone(), &rgb_profile()).unwrap();
+
+        assert!(new_pdf.starts_with(&pdf));
+        check_output_intent(&new_pdf, 3, (2, 0));
+
+        let trailer = newest_trailer(&new_pdf);
+        let prev = Xref::read(&pdf).unwrap().start;
+        assert!(trailer.contains(&format!("/Prev {prev}")));
+
+        // The catalog keeps its other entries.
+        assert!(object(&new_pdf, 2, 0).contains("/Pages 1 0 R"));
+    }
+
+    #[test]
+    fn handles_crlf_generations_and_trailer_entries() {
+        let pdf = PdfWriter::new("\r\n")
+            .object(3, 0, "<< /Type /Pages /Kids [] /Count 0 >>")
+            .object(4, 0, "<< /Producer (test) >>")
+            .object(7, 2, "<</Type/Catalog/Pages 3 0 R>>")
+            .xref("/Size 8 /Root 7 2 R /Info 4 0 R /ID [<0123abcd><4567ef89>]")
+            .finish();
+
+        let new_pdf = with_output_intent(pdf, &rgb_profile()).unwrap();
+
+        check_output_intent(&new_pdf, 8, (7, 2));
+
+        let trailer = newest_trailer(&new_pdf);
+        assert!(trailer.contains("/Root 7 2 R"));
+        assert!(trailer.contains("/Info 4 0 R"));
+        assert!(trailer.contains("/ID [<0123abcd><4567ef89>]"));
+    }
+
+    #[test]
+    fn finds_catalog_in_older_cross_reference_section() {
+        // The second section only updates the pages, so the catalog is only listed in
+        // the first one.
+        let pdf = PdfWriter::new("\n")
+            .object(1, 0, "<< /Type /Pages /Kids [] /Count 0 >>")
+            .object(2, 0, "<< /Type /Catalog /Pages 1 0 R >>")
+            .xref("/Size 3 /Root 2 0 R")
+            .object(1, 0, "<< /Type /Pages /Kids [] /Count 0 /Rotate 90 >>")
+            .xref("/Size 3 /Root 2 0 R")
+            .finish();
+
+        let new_pdf = with_output_intent(pdf, &rgb_profile()).unwrap();
+
+        check_output_intent(&new_pdf, 3, (2, 0));
+        assert!(object(&new_pdf, 1, 0).contains("/Rotate 90"));
+    }
+
+    #[test]
+    fn keeps_nested_dictionaries_and_strings_in_catalog() {
+        let catalog = "<< /Type /Catalog /Pages 1 0 R \
+                       /ViewerPreferences << /DisplayDocTitle true >> \
+                       /Lang (en \\) >> /OutputIntents) \
+                       /PageLabels << /Nums [0 << /S /D >>] >> >>";
+
+        let pdf = PdfWriter::new("\n")
+            .object(1, 0, "<< /Type /Pages /Kids [] /Count 0 >>")
+            .object(2, 0, catalog)
+            .xref("/Size 3 /Root 2 0 R")
+            .finish();
+
+        let new_pdf = with_output_intent(pdf, &rgb_profile()).unwrap();
+
+        check_output_intent(&new_pdf, 3, (2, 0));
+
+        let new_catalog = object(&new_pdf, 2, 0);
+        assert!(new_catalog.contains(catalog[..catalog.len() - 2].trim_end()));
+        assert!(new_catalog
+            .trim_end()
+            .ends_with("/OutputIntents [ 4 0 R ]\n>>"));
+    }
+
+    #[test]
+    fn rejects_pdf_with_output_intent() {
+        let pdf = PdfWriter::new("\n")
+            .object(1, 0, "<< /Type /Pages /Kids [] /Count 0 >>")
+            .object(2, 0, "<< /Type /Catalog /Pages 1 0 R /OutputIntents [] >>")
+            .xref("/Size 3 /Root 2 0 R")
+            .finish();
+
+        let new_pdf = with_output_intent(pdf, &rgb_profile()).unwrap_err();
+        assert!(new_pdf.contains("already has an output intent"));
+    }
+
+    #[test]
+    fn adding_output_intent_twice_fails() {
+        let pdf = PdfWriter::new("\n")
+            .object(1, 0, "<< /Type /Pages /Kids [] /Count 0 >>")
+            .object(2, 0, "<< /Type /Catalog /Pages 1 0 R >>")
+            .xref("/Size 3 /Root 2 0 R")
+            .finish();
+
+        let new_pdf = with_output_intent(pdf, &rgb_profile()).unwrap();
+        assert!(with_output_intent(new_pdf, &rgb_profile()).is_err());
+    }
+
+    #[test]
+    fn rejects_cross_reference_streams() {
+        let mut pdf = PdfWriter::new("\n")
+            .object(1, 0, "<< /Type /Catalog >>")
+            .finish();
+        let xref_offset = pdf.len();
+        pdf.extend_from_slice(
+            format!(
+                "2 0 obj\n<< /Type /XRef /Size 3 /Root 1 0 R /W [1 2 1] /Length 0 >>\n\
+                 stream\n\nendstream\nendobj\nstartxref\n{xref_offset}\n%%EOF\n"
+            )
+            .as_bytes(),
+        );
+
+        let error = with_output_intent(pdf, &rgb_profile()).unwrap_err();
+        assert!(error.contains("cross-reference stream"));
+    }
+
+    #[test]
+    fn rejects_hybrid_files() {
+        let pdf = PdfWriter::new("\n")
+            .object(1, 0, "<< /Type /Pages /Kids [] /Count 0 >>")
+            .object(2, 0, "<< /Type /Catalog /Pages 1 0 R >>")
+            .xref("/Size 4 /Root 2 0 R /XRefStm 1234")
+            .finish();
+
+        let error = with_output_intent(pdf, &rgb_profile()).unwrap_err();
+        assert!(error.contains("cross-reference stream"));
+    }
+
+    #[test]
+    fn rejects_truncated_pdf() {
+        let pdf = PdfWriter::new("\n")
+            .object(1, 0, "<< /Type /Pages /Kids [] /Count 0 >>")
+            .object(2, 0, "<< /Type /Catalog /Pages 1 0 R >>")
+            .xref("/Size 3 /Root 2 0 R")
+            .finish();
+
+        let truncated = pdf[..rfind(&pdf, b"trailer").unwrap()].to_vec();
+        assert!(with_output_intent(truncated, &rgb_profile()).is_err());
+    }
+
+    #[test]
+    fn rejects_files_that_are_not_icc_profiles() {
+        let mut data = rgb_profile().data;
+        data[36..40].copy_from_slice(b"xxxx");
+        assert!(IccProfile::new(data, "foo.icc").is_err());
+
+        let mut data = rgb_profile().data;
+        data.push(0);
+        assert!(IccProfile::new(data, "foo.icc").is_err());
+
+        let mut data = rgb_profile().data;
+        data[16..20].copy_from_slice(b"Lab ");
+        assert!(IccProfile::new(data, "foo.icc").is_err());
+    }
+}
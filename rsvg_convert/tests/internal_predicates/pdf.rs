@@ -44,6 +44,20 @@ impl PdfPredicate {
         }
     }
 
+    pub fn with_modification_date(self, when: DateTime<Utc>) -> DetailPredicate<Self> {
+        DetailPredicate::<Self> {
+            p: self,
+            d: Detail::ModificationDate(when),
+        }
+    }
+
+    pub fn with_title(self, title: &str) -> DetailPredicate<Self> {
+        DetailPredicate::<Self> {
+            p: self,
+            d: Detail::Title(title.to_string()),
+        }
+    }
+
     pub fn with_link(self, link: &str) -> DetailPredicate<Self> {
         DetailPredicate::<Self> {
             p: self,
@@ -64,6 +78,13 @@ impl PdfPredicate {
             d: Detail::Version(version.to_string()),
         }
     }
+
+    pub fn with_output_intent(self, icc_profile: &[u8]) -> DetailPredicate<Self> {
+        DetailPredicate::<Self> {
+            p: self,
+            d: Detail::OutputIntent(icc_profile.to_vec()),
+        }
+    }
 }
 
 impl Predicate<[u8]> for PdfPredicate {
@@ -99,9 +120,12 @@ enum Detail {
     PageCount(usize),
     PageSize(Dimensions, usize),
     CreationDate(DateTime<Utc>),
+    ModificationDate(DateTime<Utc>),
+    Title(String),
     Link(String),
     Text(String),
     Version(String),
+    OutputIntent(Vec<u8>),
 }
 
 /// A PDF page's dimensions from its `MediaBox`.
@@ -161,6 +185,8 @@ trait Details {
     fn get_page_count(&self) -> usize;
     fn get_page_size(&self, idx: usize) -> Option<Dimensions>;
     fn get_creation_date(&self) -> Option<DateTime<Utc>>;
+    fn get_modification_date(&self) -> Option<DateTime<Utc>>;
+    fn get_title(&self) -> Option<String>;
     fn get_from_trailer<'a>(&'a self, key: &[u8]) -> lopdf::Result<&'a lopdf::Object>;
     fn get_from_page<'a>(&'a self, idx: usize, key: &[u8]) -> lopdf::Result<&'a lopdf::Object>;
 }
@@ -171,9 +197,14 @@ impl DetailPredicate<PdfPredicate> {
             Detail::PageCount(n) => doc.get_page_count() == *n,
             Detail::PageSize(d, idx) => doc.get_page_size(*idx).map_or(false, |dim| dim == *d),
             Detail::CreationDate(d) => doc.get_creation_date().map_or(false, |date| date == *d),
+            Detail::ModificationDate(d) => {
+                doc.get_modification_date().map_or(false, |date| date == *d)
+            }
+            Detail::Title(t) => doc.get_title().map_or(false, |title| title == *t),
             Detail::Link(link) => document_has_link(doc, link),
             Detail::Text(text) => document_has_text(doc, text),
             Detail::Version(version) => document_has_version(doc, version),
+            Detail::OutputIntent(profile) => document_has_output_intent(doc, profile),
         }
     }
 
@@ -203,6 +234,11 @@ impl DetailPredicate<PdfPredicate> {
                 "actual creation date",
                 format!("{:?}", doc.get_creation_date()),
             ),
+            Detail::ModificationDate(_) => Product::new(
+                "actual modification date",
+                format!("{:?}", doc.get_modification_date()),
+            ),
+            Detail::Title(_) => Product::new("actual title", format!("{:?}", doc.get_title())),
             Detail::Link(_) => Product::new(
                 "actual link contents",
                 "FIXME: who knows, but it's not what we expected".to_string(),
@@ -211,6 +247,13 @@ impl DetailPredicate<PdfPredicate> {
                 Product::new("actual text contents", doc.extract_text(&[1]).unwrap())
             }
             Detail::Version(_) => Product::new("actual version contents", doc.version.to_string()),
+            Detail::OutputIntent(_) => Product::new(
+                "actual output intent",
+                match output_intent_profile(doc) {
+                    Ok(profile) => format!("ICC profile of {} bytes", profile.len()),
+                    Err(e) => format!("{}", e),
+                },
+            ),
         }
     }
 }
@@ -257,6 +300,23 @@ impl Details for lopdf::Document {
         }
     }
 
+    fn get_modification_date(&self) -> Option<DateTime<Utc>> {
+        match self.get_from_trailer(b"ModDate") {
+            Ok(obj) => obj.as_datetime().map(|date| date.with_timezone(&Utc)),
+            Err(_) => None,
+        }
+    }
+
+    fn get_title(&self) -> Option<String> {
+        match self.get_from_trailer(b"Title") {
+            Ok(obj) => obj
+                .as_str()
+                .ok()
+                .map(|s| String::from_utf8_lossy(s).into_owned()),
+            Err(_) => None,
+        }
+    }
+
     fn get_from_trailer<'a>(&'a self, key: &[u8]) -> lopdf::Result<&'a lopdf::Object> {
         let id = self.trailer.get(b"Info")?.as_reference()?;
         self.get_object(id)?.as_dict()?.get(key)
@@ -303,9 +363,18 @@ impl fmt::Display for DetailPredicate<PdfPredicate> {
             Detail::PageCount(n) => write!(f, "is a PDF with {} page(s)", n),
             Detail::PageSize(d, _) => write!(f, "is a PDF sized {}", d),
             Detail::CreationDate(d) => write!(f, "is a PDF created {:?}", d),
+            Detail::ModificationDate(d) => write!(f, "is a PDF modified {:?}", d),
+            Detail::Title(t) => write!(f, "is a PDF titled \"{}\"", t),
             Detail::Link(l) => write!(f, "is a PDF with a link to {}", l),
             Detail::Text(t) => write!(f, "is a PDF with \"{}\" in its text content", t),
             Detail::Version(v) => write!(f, "is a PDF with version {}", v),
+            Detail::OutputIntent(p) => {
+                write!(
+                    f,
+                    "is a PDF with an output intent for an ICC profile of {} bytes",
+                    p.len()
+                )
+            }
         }
     }
 }
@@ -324,6 +393,31 @@ fn document_has_version(document: &lopdf::Document, version_to_search: &str) ->
     document.version == version_to_search
 }
 
+fn document_has_output_intent(document: &lopdf::Document, icc_profile: &[u8]) -> bool {
+    output_intent_profile(document).map_or(false, |profile| profile == icc_profile)
+}
+
+/// Returns the ICC profile of the first output intent in the document catalog.
+fn output_intent_profile(doc: &lopdf::Document) -> lopdf::Result<Vec<u8>> {
+    let catalog_id = doc.trailer.get(b"Root")?.as_reference()?;
+    let catalog = doc.get_object(catalog_id)?.as_dict()?;
+
+    let intent_id = catalog
+        .get(b"OutputIntents")?
+        .as_array()?
+        .first()
+        .ok_or(lopdf::Error::ObjectNotFound)?
+        .as_reference()?;
+    let intent = doc.get_object(intent_id)?.as_dict()?;
+
+    if intent.get(b"S")?.as_name_str()? != "GTS_PDFA1" {
+        return Err(lopdf::Error::Type);
+    }
+
+    let profile_id = intent.get(b"DestOutputProfile")?.as_reference()?;
+    Ok(doc.get_object(profile_id)?.as_stream()?.content.clone())
+}
+
 // We do a super simple test that a PDF actually contains an Annotation object
 // with a particular link.  We don't test that this annotation is actually linked
 // from a page; that would be nicer.
@@ -18,6 +18,10 @@ impl PngPredicate {
         SizePredicate::<Self> { p: self, w, h }
     }
 
+    pub fn with_dpi(self, x: f64, y: f64) -> DpiPredicate<Self> {
+        DpiPredicate::<Self> { p: self, x, y }
+    }
+
     pub fn with_contents<P: AsRef<Path>>(self, reference: P) -> ReferencePredicate<Self> {
         let mut path = PathBuf::new();
         path.push(reference);
@@ -107,6 +111,74 @@ impl fmt::Display for SizePredicate<PngPredicate> {
     }
 }
 
+/// Extends a PngPredicate by a check for the resolution in the PNG's pHYs chunk.
+#[derive(Debug)]
+pub struct DpiPredicate<PngPredicate> {
+    p: PngPredicate,
+    x: f64,
+    y: f64,
+}
+
+impl DpiPredicate<PngPredicate> {
+    fn eval_info(&self, info: &png::Info) -> bool {
+        let ppm = |dpi: f64| (dpi / 0.0254).round() as u32;
+
+        match info.pixel_dims {
+            Some(png::PixelDimensions {
+                xppu,
+                yppu,
+                unit: png::Unit::Meter,
+            }) => xppu == ppm(self.x) && yppu == ppm(self.y),
+            _ => false,
+        }
+    }
+
+    fn find_case_for_info<'a>(&'a self, expected: bool, info: &png::Info) -> Option<Case<'a>> {
+        if self.eval_info(info) == expected {
+            let product = self.product_for_info(info);
+            Some(Case::new(Some(self), false).add_product(product))
+        } else {
+            None
+        }
+    }
+
+    fn product_for_info(&self, info: &png::Info) -> Product {
+        let actual_dims = format!("{:?}", info.pixel_dims);
+        Product::new("actual pixel dimensions", actual_dims)
+    }
+}
+
+impl Predicate<[u8]> for DpiPredicate<PngPredicate> {
+    fn eval(&self, data: &[u8]) -> bool {
+        let decoder = png::Decoder::new(data);
+        match decoder.read_info() {
+            Ok(reader) => self.eval_info(reader.info()),
+            _ => false,
+        }
+    }
+
+    fn find_case<'a>(&'a self, expected: bool, data: &[u8]) -> Option<Case<'a>> {
+        let decoder = png::Decoder::new(data);
+        match decoder.read_info() {
+            Ok(reader) => self.find_case_for_info(expected, reader.info()),
+            Err(e) => Some(Case::new(Some(self), false).add_product(Product::new("Error", e))),
+        }
+    }
+}
+
+impl PredicateReflection for DpiPredicate<PngPredicate> {
+    fn children<'a>(&'a self) -> Box<dyn Iterator<Item = Child<'a>> + 'a> {
+        let params = vec![Child::new("predicate", &self.p)];
+        Box::new(params.into_iter())
+    }
+}
+
+impl fmt::Display for DpiPredicate<PngPredicate> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "is a PNG with {} x {} DPI", self.x, self.y)
+    }
+}
+
 /// Extends a PngPredicate by a comparison to the contents of a reference file
 #[derive(Debug)]
 pub struct ReferencePredicate<PngPredicate> {
@@ -20,7 +20,7 @@ use url::Url;
 //  - command-line options that affect size (width, height, zoom, resolution) ✔
 //  - pixel dimensions of the output (should be sufficient to do that for PNG) ✔
 //  - limit on output size (32767 pixels) ✔
-//  - output formats (PNG, PDF, PS, EPS, SVG) ✔
+//  - output formats (PNG, PDF, PS, EPS, SVG, ANSI) ✔
 //  - multi-page output (for PDF) ✔
 //  - output file option ✔
 //  - SOURCE_DATA_EPOCH environment variable for PDF output ✔
@@ -202,6 +202,205 @@ fn output_format_svg_short_option() {
         .stdout(file::is_svg());
 }
 
+#[test]
+fn output_format_ansi() {
+    RsvgConvert::new_with_input("tests/fixtures/empty-10x10.svg")
+        .arg("--format=ansi")
+        .arg("--width=2")
+        .arg("--height=2")
+        .arg("--background-color=lime")
+        .assert()
+        .success()
+        .stdout(concat!(
+            "\x1b[0;38;2;0;255;0;48;2;0;255;0m\u{2580}",
+            "\x1b[0;38;2;0;255;0;48;2;0;255;0m\u{2580}",
+            "\x1b[0m\n",
+        ));
+}
+
+#[test]
+fn output_format_ansi_transparent_uses_default_background() {
+    RsvgConvert::new_with_input("tests/fixtures/empty-10x10.svg")
+        .arg("--format=ansi")
+        .arg("--width=2")
+        .arg("--height=2")
+        .assert()
+        .success()
+        .stdout("\x1b[0m \x1b[0m \x1b[0m\n");
+}
+
+#[cfg(feature = "webp")]
+#[test]
+fn output_format_webp() {
+    RsvgConvert::new_with_input("tests/fixtures/empty-10x10.svg")
+        .arg("--format=webp")
+        .assert()
+        .success()
+        .stdout(predicate::function(|output: &[u8]| {
+            output.len() > 12 && &output[0..4] == b"RIFF" && &output[8..12] == b"WEBP"
+        }));
+}
+
+#[cfg(feature = "webp")]
+#[test]
+fn output_format_webp_lossless() {
+    RsvgConvert::new_with_input("tests/fixtures/empty-10x10.svg")
+        .arg("--format=webp")
+        .arg("--lossless")
+        .assert()
+        .success()
+        .stdout(predicate::function(|output: &[u8]| {
+            output.len() > 12 && &output[0..4] == b"RIFF" && &output[8..12] == b"WEBP"
+        }));
+}
+
+#[test]
+fn webp_lossless_conflicts_with_quality() {
+    RsvgConvert::new_with_input("tests/fixtures/empty-10x10.svg")
+        .arg("--lossless")
+        .arg("--quality=50")
+        .assert()
+        .failure();
+}
+
+#[cfg(feature = "apng")]
+#[test]
+fn frames_from_ids_creates_animated_png() {
+    RsvgConvert::new_with_input("tests/fixtures/frames.svg")
+        .arg("--frames-from-ids=frame")
+        .arg("--fps=5")
+        .assert()
+        .success()
+        .stdout(
+            file::is_png()
+                .with_size(20, 20)
+                .and(predicate::function(|output: &[u8]| {
+                    let reader = png::Decoder::new(output).read_info().unwrap();
+                    let info = reader.info();
+                    info.animation_control.map(|a| a.num_frames) == Some(3)
+                        && info
+                            .frame_control
+                            .is_some_and(|f| f.delay_num == 1 && f.delay_den == 5)
+                })),
+        );
+}
+
+#[cfg(feature = "apng")]
+#[test]
+fn frames_from_ids_are_scaled_like_pages() {
+    RsvgConvert::new_with_input("tests/fixtures/frames.svg")
+        .arg("--frames-from-ids=frame")
+        .arg("--zoom=2")
+        .assert()
+        .success()
+        .stdout(file::is_png().with_size(40, 40));
+
+    RsvgConvert::new_with_input("tests/fixtures/frames.svg")
+        .arg("--frames-from-ids=frame")
+        .arg("--width=10")
+        .assert()
+        .success()
+        .stdout(file::is_png().with_size(10, 10));
+}
+
+#[test]
+fn geometry_option_prints_json() {
+    RsvgConvert::new_with_input("tests/fixtures/geometry-element.svg")
+        .arg("--geometry")
+        .assert()
+        .success()
+        .stdout(predicate::function(|output: &[u8]| {
+            let json: serde_json::Value = serde_json::from_slice(output).unwrap();
+            let dimensions = &json["intrinsic_dimensions"];
+            let foo = &json["elements"][0];
+
+            dimensions["width"]["length"] == 100.0
+                && dimensions["width"]["unit"] == "px"
+                && dimensions["vbox"].is_null()
+                && foo["id"] == "foo"
+                && foo["ink_rect"]["width"] == 40.0
+                && foo["ink_rect"]["height"] == 50.0
+                && foo["logical_rect"]["x"] == 5.0
+                && foo["logical_rect"]["width"] == 30.0
+        }));
+}
+
+#[test]
+fn frames_from_ids_not_allowed_for_pdf_output() {
+    RsvgConvert::new_with_input("tests/fixtures/frames.svg")
+        .arg("--format=pdf")
+        .arg("--frames-from-ids=frame")
+        .assert()
+        .failure()
+        .stderr(contains("only allowed for PNG output"));
+}
+
+fn is_jpeg(output: &[u8]) -> bool {
+    output.len() > 4 && output[0..3] == [0xff, 0xd8, 0xff]
+}
+
+#[test]
+fn output_format_jpeg() {
+    RsvgConvert::new_with_input("tests/fixtures/empty-10x10.svg")
+        .arg("--format=jpeg")
+        .assert()
+        .success()
+        .stdout(predicate::function(is_jpeg));
+}
+
+#[test]
+fn output_format_jpg_is_a_synonym_for_jpeg() {
+    RsvgConvert::new_with_input("tests/fixtures/empty-10x10.svg")
+        .arg("--format=jpg")
+        .assert()
+        .success()
+        .stdout(predicate::function(is_jpeg));
+}
+
+#[test]
+fn jpeg_quality_bounds() {
+    for quality in ["1", "100"] {
+        RsvgConvert::new_with_input("tests/fixtures/empty-10x10.svg")
+            .arg("--format=jpeg")
+            .arg(format!("--quality={quality}"))
+            .assert()
+            .success()
+            .stdout(predicate::function(is_jpeg));
+    }
+}
+
+#[test]
+fn jpeg_quality_out_of_range() {
+    for quality in ["-1", "0", "101"] {
+        RsvgConvert::new_with_input("tests/fixtures/empty-10x10.svg")
+            .arg("--format=jpeg")
+            .arg(format!("--quality={quality}"))
+            .assert()
+            .failure()
+            .stderr(contains("quality"));
+    }
+}
+
+#[test]
+fn jpeg_with_background_color() {
+    RsvgConvert::new_with_input("tests/fixtures/empty-10x10.svg")
+        .arg("--format=jpeg")
+        .arg("--background-color=#ff0000")
+        .assert()
+        .success()
+        .stdout(predicate::function(is_jpeg));
+}
+
+#[test]
+fn jpeg_with_transparent_background_is_an_error() {
+    RsvgConvert::new_with_input("tests/fixtures/empty-10x10.svg")
+        .arg("--format=jpeg")
+        .arg("--background-color=none")
+        .assert()
+        .failure()
+        .stderr(contains("transparent background"));
+}
+
 #[cfg(system_deps_have_cairo_svg)]
 #[test]
 fn user_specified_width_and_height() {
@@ -354,6 +553,72 @@ fn multiple_input_files_not_allowed_for_png_output() {
         ));
 }
 
+#[test]
+fn multiple_input_files_to_separate_png_files() {
+    let dir = Builder::new().tempdir().unwrap();
+    let template = dir.path().join("{name}.png");
+
+    RsvgConvert::new()
+        .arg("--jobs=2")
+        .arg(format!("--output-template={}", template.display()))
+        .arg("tests/fixtures/bug521-with-viewbox.svg")
+        .arg("tests/fixtures/dpi.svg")
+        .arg("tests/fixtures/example.svg")
+        .assert()
+        .success()
+        .stdout(is_empty());
+
+    for (name, width, height) in [
+        ("bug521-with-viewbox", 200, 100),
+        ("dpi", 96, 384),
+        ("example", 100, 400),
+    ] {
+        let output = std::fs::read(dir.path().join(format!("{name}.png"))).unwrap();
+        assert!(file::is_png().with_size(width, height).eval(&output));
+    }
+}
+
+#[test]
+fn multiple_input_files_to_png_report_the_failing_input() {
+    let dir = Builder::new().tempdir().unwrap();
+    let template = dir.path().join("{name}.png");
+
+    RsvgConvert::new()
+        .arg(format!("--output-template={}", template.display()))
+        .arg("tests/fixtures/example.svg")
+        .arg("tests/fixtures/does-not-exist.svg")
+        .assert()
+        .failure()
+        .stderr(contains("does-not-exist.svg"));
+
+    assert!(predicates::path::is_file().eval(&dir.path().join("example.png")));
+}
+
+#[test]
+fn multiple_input_files_with_the_same_name_are_rejected() {
+    let dir = Builder::new().tempdir().unwrap();
+    let template = dir.path().join("{name}.png");
+
+    RsvgConvert::new()
+        .arg(format!("--output-template={}", template.display()))
+        .arg("tests/fixtures/example.svg")
+        .arg("tests/fixtures/dpi.svg")
+        .arg("tests/fixtures/example.svg")
+        .assert()
+        .failure()
+        .stderr(contains("example.png"));
+
+    assert!(predicates::path::missing().eval(&dir.path().join("dpi.png")));
+}
+
+#[test]
+fn jobs_option_must_be_positive() {
+    RsvgConvert::new_with_input("tests/fixtures/example.svg")
+        .arg("--jobs=0")
+        .assert()
+        .failure();
+}
+
 #[cfg(system_deps_have_cairo_ps)]
 #[test]
 fn multiple_input_files_accepted_for_eps_output() {
@@ -404,6 +669,79 @@ fn multiple_input_files_create_multi_page_pdf_output() {
         );
 }
 
+#[cfg(system_deps_have_cairo_pdf)]
+#[test]
+fn files_from_stdin_create_multi_page_pdf_output() {
+    RsvgConvert::new()
+        .arg("--format=pdf")
+        .arg("--files-from=-")
+        .write_stdin(
+            "tests/fixtures/bug521-with-viewbox.svg\n\
+             tests/fixtures/sub-rect-no-unit.svg\n\
+             tests/fixtures/example.svg\n",
+        )
+        .assert()
+        .success()
+        .stdout(
+            file::is_pdf()
+                .with_page_count(3)
+                .and(file::is_pdf().with_page_size(0, 150.0, 75.0))
+                .and(file::is_pdf().with_page_size(1, 123.0, 123.0))
+                .and(file::is_pdf().with_page_size(2, 75.0, 300.0)),
+        );
+}
+
+#[cfg(system_deps_have_cairo_pdf)]
+#[test]
+fn files_from_list_is_merged_with_positional_files() {
+    let mut list = Builder::new().suffix(".txt").tempfile().unwrap();
+    std::io::Write::write_all(
+        &mut list,
+        b"# comments and blank lines are ignored\n\
+          \n\
+          tests/fixtures/sub-rect-no-unit.svg\n\
+          \n\
+          tests/fixtures/example.svg\n",
+    )
+    .unwrap();
+
+    RsvgConvert::new()
+        .arg("--format=pdf")
+        .arg(format!("--files-from={}", list.path().display()))
+        .arg("tests/fixtures/bug521-with-viewbox.svg")
+        .assert()
+        .success()
+        .stdout(
+            file::is_pdf()
+                .with_page_count(3)
+                .and(file::is_pdf().with_page_size(0, 150.0, 75.0))
+                .and(file::is_pdf().with_page_size(1, 123.0, 123.0))
+                .and(file::is_pdf().with_page_size(2, 75.0, 300.0)),
+        );
+}
+
+#[test]
+fn files_from_stdin_cannot_also_read_svg_from_stdin() {
+    RsvgConvert::new()
+        .arg("--format=pdf")
+        .arg("--files-from=-")
+        .arg("-")
+        .write_stdin("tests/fixtures/example.svg\n")
+        .assert()
+        .failure()
+        .stderr(contains("Only one input file can be read from stdin"));
+}
+
+#[test]
+fn files_from_empty_list_is_an_error() {
+    RsvgConvert::new()
+        .arg("--files-from=-")
+        .write_stdin("# nothing here\n\n")
+        .assert()
+        .failure()
+        .stderr(contains("list of input files is empty"));
+}
+
 #[cfg(system_deps_have_cairo_pdf)]
 #[test]
 fn multiple_input_files_create_multi_page_pdf_output_fixed_size() {
@@ -434,6 +772,139 @@ fn multiple_input_files_create_multi_page_pdf_output_fixed_size() {
         );
 }
 
+#[cfg(system_deps_have_cairo_pdf)]
+#[test]
+fn even_pages_appends_blank_page_to_pdf_output() {
+    RsvgConvert::new()
+        .arg("--format=pdf")
+        .arg("--even-pages")
+        .arg("tests/fixtures/bug521-with-viewbox.svg")
+        .arg("tests/fixtures/sub-rect-no-unit.svg")
+        .arg("tests/fixtures/example.svg")
+        .assert()
+        .success()
+        .stdout(
+            file::is_pdf()
+                .with_page_count(4)
+                .and(file::is_pdf().with_page_size(3, 75.0, 300.0)),
+        );
+}
+
+#[cfg(system_deps_have_cairo_pdf)]
+#[test]
+fn max_pages_limits_the_number_of_inputs() {
+    RsvgConvert::new()
+        .arg("--format=pdf")
+        .arg("--max-pages=2")
+        .arg("tests/fixtures/bug521-with-viewbox.svg")
+        .arg("tests/fixtures/sub-rect-no-unit.svg")
+        .arg("tests/fixtures/example.svg")
+        .assert()
+        .success()
+        .stdout(file::is_pdf().with_page_count(2));
+}
+
+#[test]
+fn page_options_not_allowed_for_png_output() {
+    RsvgConvert::new_with_input("tests/fixtures/example.svg")
+        .arg("--even-pages")
+        .assert()
+        .failure()
+        .stderr(contains("only allowed for PDF and (E)PS output"));
+}
+
+#[cfg(system_deps_have_cairo_pdf)]
+#[test]
+fn pdf_document_info_options() {
+    RsvgConvert::new_with_input("tests/fixtures/example.svg")
+        .arg("--format=pdf")
+        .arg("--title=Quarterly report")
+        .arg("--author=Jane Doe")
+        .arg("--subject=Sales")
+        .arg("--keywords=sales, report")
+        .assert()
+        .success()
+        .stdout(file::is_pdf().with_title("Quarterly report"));
+}
+
+#[test]
+fn pdf_document_info_options_not_allowed_for_png_output() {
+    RsvgConvert::new_with_input("tests/fixtures/example.svg")
+        .arg("--title=Quarterly report")
+        .assert()
+        .failure()
+        .stderr(contains("only allowed for PDF output"));
+}
+
+#[cfg(system_deps_have_cairo_pdf)]
+#[test]
+fn pdf_icc_profile_adds_output_intent() {
+    let profile = std::fs::read("tests/fixtures/rgb-gamma22.icc").unwrap();
+    RsvgConvert::new_with_input("tests/fixtures/example.svg")
+        .arg("--format=pdf1.4")
+        .arg("--icc-profile=tests/fixtures/rgb-gamma22.icc")
+        .assert()
+        .success()
+        .stdout(file::is_pdf().with_output_intent(&profile));
+}
+
+#[cfg(system_deps_have_cairo_pdf)]
+#[test]
+fn pdf_icc_profile_with_multiple_pages() {
+    let profile = std::fs::read("tests/fixtures/rgb-gamma22.icc").unwrap();
+    RsvgConvert::new()
+        .arg("--format=pdf1.4")
+        .arg("--icc-profile=tests/fixtures/rgb-gamma22.icc")
+        .arg("tests/fixtures/example.svg")
+        .arg("tests/fixtures/dpi.svg")
+        .assert()
+        .success()
+        .stdout(
+            file::is_pdf()
+                .with_page_count(2)
+                .and(file::is_pdf().with_output_intent(&profile)),
+        );
+}
+
+#[test]
+fn icc_profile_not_allowed_for_png_output() {
+    RsvgConvert::new_with_input("tests/fixtures/example.svg")
+        .arg("--icc-profile=tests/fixtures/rgb-gamma22.icc")
+        .assert()
+        .failure()
+        .stderr(contains("only allowed for PDF output"));
+}
+
+#[test]
+fn icc_profile_needs_pdf_1_4() {
+    RsvgConvert::new_with_input("tests/fixtures/example.svg")
+        .arg("--format=pdf1.5")
+        .arg("--icc-profile=tests/fixtures/rgb-gamma22.icc")
+        .assert()
+        .failure()
+        .stderr(contains("needs PDF 1.4"));
+}
+
+#[test]
+fn icc_profile_does_not_change_default_pdf_version() {
+    RsvgConvert::new_with_input("tests/fixtures/example.svg")
+        .arg("--format=pdf")
+        .arg("--icc-profile=tests/fixtures/rgb-gamma22.icc")
+        .assert()
+        .failure()
+        .stderr(contains("please use --format=pdf1.4"));
+}
+
+#[test]
+fn icc_profile_must_be_an_icc_profile() {
+    RsvgConvert::new_with_input("tests/fixtures/example.svg")
+        .arg("--format=pdf1.4")
+        .arg("--icc-profile=tests/fixtures/example.svg")
+        .assert()
+        .failure()
+        .stderr(contains("not an ICC profile"));
+}
+
 #[cfg(system_deps_have_cairo_pdf)]
 #[test]
 fn pdf_has_link() {
@@ -492,6 +963,47 @@ fn env_source_data_epoch_controls_pdf_creation_date() {
         .stdout(file::is_pdf().with_creation_date(Utc.timestamp_opt(date, 0).unwrap()));
 }
 
+#[cfg(system_deps_have_cairo_pdf)]
+#[test]
+fn env_source_data_epoch_controls_pdf_modification_date() {
+    let input = Path::new("tests/fixtures/bug521-with-viewbox.svg");
+    let date = 1581411039; // seconds since epoch
+    RsvgConvert::new()
+        .env("SOURCE_DATE_EPOCH", format!("{}", date))
+        .arg("--format=pdf")
+        .arg(input)
+        .assert()
+        .success()
+        .stdout(file::is_pdf().with_modification_date(Utc.timestamp_opt(date, 0).unwrap()));
+}
+
+#[cfg(system_deps_have_cairo_ps)]
+#[test]
+fn env_source_data_epoch_controls_ps_creation_date() {
+    let input = Path::new("tests/fixtures/bug521-with-viewbox.svg");
+    RsvgConvert::new()
+        .env("SOURCE_DATE_EPOCH", "1581411039")
+        .arg("--format=ps")
+        .arg(input)
+        .assert()
+        .success()
+        .stdout(contains("%%CreationDate: Tue Feb 11 08:50:39 2020"));
+}
+
+#[cfg(system_deps_have_cairo_ps)]
+#[test]
+fn env_source_data_epoch_no_digits_for_ps() {
+    // intentionally not testing for the full error string here
+    let input = Path::new("tests/fixtures/bug521-with-viewbox.svg");
+    RsvgConvert::new()
+        .env("SOURCE_DATE_EPOCH", "foobar")
+        .arg("--format=ps")
+        .arg(input)
+        .assert()
+        .failure()
+        .stderr(starts_with("Environment variable $SOURCE_DATE_EPOCH"));
+}
+
 #[cfg(system_deps_have_cairo_pdf)]
 #[test]
 fn env_source_data_epoch_no_digits() {
@@ -734,6 +1246,45 @@ fn x_and_y_resolution() {
         .stdout(file::is_png().with_size(300, 600));
 }
 
+#[test]
+fn resolution_for_both_axes() {
+    RsvgConvert::new_with_input("tests/fixtures/dpi.svg")
+        .arg("--dpi=150")
+        .assert()
+        .success()
+        .stdout(file::is_png().with_size(150, 600));
+}
+
+#[test]
+fn resolution_for_both_axes_conflicts_with_x_resolution() {
+    RsvgConvert::new_with_input("tests/fixtures/dpi.svg")
+        .arg("--dpi=150")
+        .arg("--dpi-x=300")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn png_output_records_resolution() {
+    RsvgConvert::new_with_input("tests/fixtures/dpi.svg")
+        .arg("--dpi-x=300")
+        .arg("--dpi-y=150")
+        .arg("--png-dpi")
+        .assert()
+        .success()
+        .stdout(file::is_png().with_dpi(300.0, 150.0));
+}
+
+#[test]
+fn png_output_has_no_resolution_by_default() {
+    RsvgConvert::new_with_input("tests/fixtures/dpi.svg")
+        .arg("--dpi-x=300")
+        .arg("--dpi-y=150")
+        .assert()
+        .success()
+        .stdout(file::is_png().and(file::is_png().with_dpi(300.0, 150.0).not()));
+}
+
 #[test]
 fn zero_resolution_is_invalid() {
     RsvgConvert::new_with_input("tests/fixtures/dpi.svg")
@@ -1090,6 +1641,98 @@ fn export_id_option_error() {
         .stderr(starts_with("File stdin does not have an object with id \""));
 }
 
+#[test]
+fn export_several_ids_with_output_template() {
+    let dir = Builder::new().tempdir().unwrap();
+    let template = dir.path().join("{id}.png");
+
+    RsvgConvert::new_with_input("tests/fixtures/dpi.svg")
+        .arg("--export-id=one,#two")
+        .arg(format!("--output-template={}", template.display()))
+        .assert()
+        .success()
+        .stdout(is_empty());
+
+    for id in ["one", "two"] {
+        let output = std::fs::read(dir.path().join(format!("{id}.png"))).unwrap();
+        assert!(file::is_png().with_size(100, 200).eval(&output));
+    }
+}
+
+#[test]
+fn export_single_id_with_output_template() {
+    let dir = Builder::new().tempdir().unwrap();
+    let template = dir.path().join("icon-{id}.png");
+
+    RsvgConvert::new_with_input("tests/fixtures/geometry-element.svg")
+        .arg("--export-id=foo")
+        .arg(format!("--output-template={}", template.display()))
+        .assert()
+        .success()
+        .stdout(is_empty());
+
+    let output = std::fs::read(dir.path().join("icon-foo.png")).unwrap();
+    assert!(file::is_png().with_size(40, 50).eval(&output));
+}
+
+#[test]
+fn export_several_ids_requires_output_template() {
+    RsvgConvert::new_with_input("tests/fixtures/dpi.svg")
+        .arg("--export-id=one,two")
+        .assert()
+        .failure()
+        .stderr(contains("--output-template"));
+}
+
+#[test]
+fn output_template_requires_id_token_for_several_ids() {
+    RsvgConvert::new_with_input("tests/fixtures/dpi.svg")
+        .arg("--export-id=one,two")
+        .arg("--output-template=out.png")
+        .assert()
+        .failure()
+        .stderr(contains("{id}"));
+}
+
+#[test]
+fn output_template_conflicts_with_output() {
+    RsvgConvert::new_with_input("tests/fixtures/dpi.svg")
+        .arg("--export-id=one,two")
+        .arg("--output-template={id}.png")
+        .arg("--output=out.png")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn crop_option() {
+    RsvgConvert::new_with_input("tests/fixtures/small-rect-in-large-viewbox.svg")
+        .arg("--crop")
+        .assert()
+        .success()
+        .stdout(file::is_png().with_size(30, 40));
+}
+
+#[test]
+fn crop_option_then_fit_to_width() {
+    RsvgConvert::new_with_input("tests/fixtures/small-rect-in-large-viewbox.svg")
+        .arg("--crop")
+        .arg("--width=60")
+        .assert()
+        .success()
+        .stdout(file::is_png().with_size(60, 80));
+}
+
+#[test]
+fn crop_option_with_export_id() {
+    RsvgConvert::new_with_input("tests/fixtures/geometry-element.svg")
+        .arg("--crop")
+        .arg("--export-id=foo")
+        .assert()
+        .success()
+        .stdout(file::is_png().with_size(40, 50));
+}
+
 #[test]
 fn unlimited_option() {
     RsvgConvert::accepts_arg("--unlimited");